@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Default location of the persisted price alerts, next to config.toml
+pub const ALERTS_FILE: &str = "alerts.json";
+
+/// Fraction of the threshold price must move away before a fired alert re-arms
+/// Prevents a price hovering at the threshold from firing on every tick
+const REARM_BAND: f64 = 0.001;
+
+/// Price condition an alert watches for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertCondition {
+    Above,
+    Below,
+    Crosses,
+}
+
+impl AlertCondition {
+    pub const ALL: [AlertCondition; 3] = [
+        AlertCondition::Above,
+        AlertCondition::Below,
+        AlertCondition::Crosses,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AlertCondition::Above => "Above",
+            AlertCondition::Below => "Below",
+            AlertCondition::Crosses => "Crosses",
+        }
+    }
+}
+
+/// User-defined price alert for a single instrument
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceAlert {
+    pub id: String,
+    pub instrument_token: u32,
+    pub condition: AlertCondition,
+    pub threshold: f64,
+    pub repeating: bool, // One-shot alerts disable themselves after firing
+    pub note: Option<String>,
+    pub enabled: bool,
+
+    // Runtime trigger state, rebuilt from the first tick after launch
+    #[serde(skip)]
+    armed: bool,
+    // Side of the threshold at the first tick or the last firing, whichever came later
+    #[serde(skip)]
+    last_side_above: Option<bool>,
+}
+
+impl PriceAlert {
+    pub fn new(
+        instrument_token: u32,
+        condition: AlertCondition,
+        threshold: f64,
+        repeating: bool,
+        note: Option<String>,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            instrument_token,
+            condition,
+            threshold,
+            repeating,
+            note,
+            enabled: true,
+            armed: true,
+            last_side_above: None,
+        }
+    }
+
+    /// Feed a new price; returns true when the alert fires
+    fn evaluate(&mut self, price: f64) -> bool {
+        let band = self.threshold.abs() * REARM_BAND;
+        let above = price >= self.threshold;
+
+        // The first price only sets the baseline: a level already passed when the alert
+        // was set, or when the app started, is not news
+        let Some(last_side_above) = self.last_side_above else {
+            self.last_side_above = Some(above);
+            self.armed = self.rearms_at(price);
+            return false;
+        };
+
+        // Re-arm only once price has clearly left the threshold
+        if !self.armed && (price - self.threshold).abs() > band {
+            self.armed = self.rearms_at(price);
+        }
+
+        let fired = self.armed
+            && match self.condition {
+                AlertCondition::Above => above,
+                AlertCondition::Below => price <= self.threshold,
+                // Against the side it last fired on, so leaving the band on the far side
+                // is a crossing too
+                AlertCondition::Crosses => last_side_above != above,
+            };
+
+        if fired {
+            self.armed = false;
+            self.last_side_above = Some(above);
+            if !self.repeating {
+                self.enabled = false;
+            }
+        }
+
+        fired
+    }
+
+    /// Whether a price puts the alert back on watch: on the near side of the threshold for
+    /// Above and Below, anywhere for Crosses
+    fn rearms_at(&self, price: f64) -> bool {
+        match self.condition {
+            AlertCondition::Above => price < self.threshold,
+            AlertCondition::Below => price > self.threshold,
+            AlertCondition::Crosses => true,
+        }
+    }
+}
+
+/// Collection of price alerts evaluated on every tick
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertBook {
+    pub alerts: Vec<PriceAlert>,
+}
+
+impl AlertBook {
+    /// Load alerts from disk; a missing file yields an empty book
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        let mut book: Self = serde_json::from_str(&json)?;
+        for alert in &mut book.alerts {
+            alert.armed = true;
+        }
+        Ok(book)
+    }
+
+    /// Persist alerts as pretty-printed JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    pub fn create(&mut self, alert: PriceAlert) {
+        self.alerts.push(alert);
+    }
+
+    /// Replace an alert's definition, resetting its trigger state
+    pub fn update(&mut self, mut alert: PriceAlert) -> bool {
+        match self
+            .alerts
+            .iter_mut()
+            .find(|existing| existing.id == alert.id)
+        {
+            Some(existing) => {
+                alert.armed = true;
+                alert.last_side_above = None;
+                *existing = alert;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn delete(&mut self, alert_id: &str) -> bool {
+        let len = self.alerts.len();
+        self.alerts.retain(|alert| alert.id != alert_id);
+        self.alerts.len() != len
+    }
+
+    /// Evaluate enabled alerts for one instrument and return those that fired
+    pub fn evaluate(&mut self, instrument_token: u32, price: f64) -> Vec<PriceAlert> {
+        self.alerts
+            .iter_mut()
+            .filter(|alert| alert.enabled && alert.instrument_token == instrument_token)
+            .filter_map(|alert| alert.evaluate(price).then(|| alert.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `prices` in turn, returning the ones the alert fired on
+    fn fired_at(alert: &mut PriceAlert, prices: &[f64]) -> Vec<f64> {
+        prices
+            .iter()
+            .copied()
+            .filter(|&price| alert.enabled && alert.evaluate(price))
+            .collect()
+    }
+
+    fn alert(condition: AlertCondition, repeating: bool) -> PriceAlert {
+        PriceAlert::new(1, condition, 100.0, repeating, None)
+    }
+
+    #[test]
+    fn above_fires_when_price_rises_through_the_threshold() {
+        let mut alert = alert(AlertCondition::Above, true);
+        assert_eq!(fired_at(&mut alert, &[98.0, 99.0, 100.5, 101.0]), [100.5]);
+    }
+
+    #[test]
+    fn above_ignores_a_first_price_already_past_the_threshold() {
+        let mut alert = alert(AlertCondition::Above, true);
+        assert!(fired_at(&mut alert, &[105.0, 106.0]).is_empty());
+        // It arms once price drops back below, and fires on the way up again
+        assert_eq!(fired_at(&mut alert, &[99.0, 100.2]), [100.2]);
+    }
+
+    #[test]
+    fn below_ignores_a_first_price_already_past_the_threshold() {
+        let mut alert = alert(AlertCondition::Below, true);
+        assert!(fired_at(&mut alert, &[95.0, 94.0]).is_empty());
+        assert_eq!(fired_at(&mut alert, &[101.0, 99.5]), [99.5]);
+    }
+
+    #[test]
+    fn repeating_alert_waits_for_price_to_leave_the_band() {
+        let mut alert = alert(AlertCondition::Above, true);
+        // 99.95 is inside the 0.1% band, so hovering there doesn't re-arm
+        let prices = [99.0, 100.0, 99.95, 100.0, 99.0, 100.0];
+        assert_eq!(fired_at(&mut alert, &prices), [100.0, 100.0]);
+        assert!(alert.enabled);
+    }
+
+    #[test]
+    fn one_shot_alert_disables_itself() {
+        let mut alert = alert(AlertCondition::Below, false);
+        assert_eq!(fired_at(&mut alert, &[101.0, 99.0, 101.0, 99.0]), [99.0]);
+        assert!(!alert.enabled);
+    }
+
+    #[test]
+    fn crosses_fires_each_way_without_chattering() {
+        let mut alert = alert(AlertCondition::Crosses, true);
+        let prices = [99.0, 100.0, 99.95, 100.05, 101.0, 98.0, 97.0];
+        assert_eq!(fired_at(&mut alert, &prices), [100.0, 98.0]);
+    }
+
+    #[test]
+    fn crosses_fires_when_re_arming_on_the_far_side() {
+        let mut alert = alert(AlertCondition::Crosses, true);
+        // Fires rising through 100; the next price both clears the band and is below,
+        // which is the crossing back down
+        assert_eq!(fired_at(&mut alert, &[99.0, 100.0, 98.0]), [100.0, 98.0]);
+    }
+
+    #[test]
+    fn update_resets_the_baseline() {
+        let mut book = AlertBook::default();
+        let created = alert(AlertCondition::Above, true);
+        let id = created.id.clone();
+        book.create(created);
+        assert!(book.evaluate(1, 99.0).is_empty());
+        assert_eq!(book.evaluate(1, 101.0).len(), 1);
+
+        let mut edited = book.alerts[0].clone();
+        edited.threshold = 102.0;
+        assert!(book.update(edited));
+        // Already past the new threshold when it was set, so no alert
+        assert!(book.evaluate(1, 103.0).is_empty());
+        assert!(book.delete(&id));
+        assert!(!book.delete(&id));
+    }
+}
@@ -0,0 +1,173 @@
+// src/api/auth_callback.rs - One-shot HTTP listener for the Kite login redirect
+// Shared with the auth_helper binary, so it depends on nothing else in the crate
+use std::process::Command;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// How long to wait for the user to finish logging in before giving up
+pub const LOGIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Largest request the listener reads; the redirect is a single short GET
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CallbackError {
+    #[error("could not listen on 127.0.0.1:{port}: {source}")]
+    Bind { port: u16, source: std::io::Error },
+    #[error("login was not completed within {} seconds", .0.as_secs())]
+    TimedOut(Duration),
+    #[error("Kite redirected back without a session ({0})")]
+    LoginFailed(String),
+    #[error("callback listener failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Redirect URL to register with the Kite app for a callback port
+pub fn redirect_url(port: u16) -> String {
+    format!("http://127.0.0.1:{}/", port)
+}
+
+/// Listener bound to the redirect port; dropping it frees the port
+pub struct CallbackListener {
+    listener: TcpListener,
+}
+
+impl CallbackListener {
+    /// Bind before the browser is opened, so a busy port is reported up front
+    pub async fn bind(port: u16) -> Result<Self, CallbackError> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|source| CallbackError::Bind { port, source })?;
+        Ok(Self { listener })
+    }
+
+    /// Answer requests until Kite redirects back, returning its request_token
+    /// Stray requests such as /favicon.ico get a 404 and are otherwise ignored
+    pub async fn wait_for_request_token(self, timeout: Duration) -> Result<String, CallbackError> {
+        tokio::time::timeout(timeout, self.accept_redirect())
+            .await
+            .map_err(|_| CallbackError::TimedOut(timeout))?
+    }
+
+    async fn accept_redirect(&self) -> Result<String, CallbackError> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+            let Some(path) = read_request_path(&mut stream).await? else {
+                respond(&mut stream, "404 Not Found", "Not found").await;
+                continue;
+            };
+            let Some(redirect) = Redirect::parse(&path) else {
+                respond(&mut stream, "404 Not Found", "Not found").await;
+                continue;
+            };
+
+            return match (redirect.status.as_deref(), redirect.request_token) {
+                (Some("success"), Some(request_token)) => {
+                    respond(
+                        &mut stream,
+                        "200 OK",
+                        "Login complete. You can close this tab and return to the dashboard.",
+                    )
+                    .await;
+                    Ok(request_token)
+                }
+                (status, _) => {
+                    let reason = format!(
+                        "action={}, status={}",
+                        redirect.action.as_deref().unwrap_or("?"),
+                        status.unwrap_or("?")
+                    );
+                    respond(
+                        &mut stream,
+                        "200 OK",
+                        "Login failed. Return to the dashboard and try again.",
+                    )
+                    .await;
+                    Err(CallbackError::LoginFailed(reason))
+                }
+            };
+        }
+    }
+}
+
+/// Query parameters Kite appends to the redirect URL
+struct Redirect {
+    action: Option<String>,
+    status: Option<String>,
+    request_token: Option<String>,
+}
+
+impl Redirect {
+    /// None when the request carries neither a status nor a request_token
+    fn parse(path: &str) -> Option<Self> {
+        let url = url::Url::parse(&format!("http://127.0.0.1{}", path)).ok()?;
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+                .filter(|value| !value.is_empty())
+        };
+        let redirect = Self {
+            action: param("action"),
+            status: param("status"),
+            request_token: param("request_token"),
+        };
+        (redirect.status.is_some() || redirect.request_token.is_some()).then_some(redirect)
+    }
+}
+
+/// Path and query of a GET request, read up to the end of its request line
+async fn read_request_path(stream: &mut TcpStream) -> std::io::Result<Option<String>> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buffer.windows(2).any(|window| window == b"\r\n") && buffer.len() < MAX_REQUEST_BYTES {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&buffer);
+    let mut parts = request
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split_whitespace();
+    Ok(match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => Some(path.to_string()),
+        _ => None,
+    })
+}
+
+/// Minimal HTML reply; the browser is all that reads it, so write errors are ignored
+async fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let body = format!(
+        "<!doctype html><html><body style=\"font-family:sans-serif\"><p>{}</p></body></html>",
+        message
+    );
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Open a URL in the default browser; false when no opener could be started
+pub fn open_in_browser(url: &str) -> bool {
+    // cmd's start would split the URL at its '&'s
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(url).spawn().is_ok()
+}
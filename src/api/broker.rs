@@ -0,0 +1,77 @@
+// src/api/broker.rs - Where orders are sent and account state is read from
+// Kite in live mode, or the local paper book when trading on paper
+use crate::api::ZerodhaClient;
+use crate::data_structures::*;
+use anyhow::Result;
+use futures_util::future::BoxFuture;
+use tokio::sync::RwLock;
+
+/// Order routing and the account state orders change: positions, orders and trades
+/// Market data, instruments, funds and authentication always come from Kite
+pub trait BrokerClient: Send + Sync {
+    fn get_positions(&self) -> BoxFuture<'_, Result<Vec<Position>>>;
+    fn get_orders(&self) -> BoxFuture<'_, Result<Vec<Order>>>;
+    fn get_trades(&self) -> BoxFuture<'_, Result<Vec<Trade>>>;
+    fn get_order_history<'a>(&'a self, order_id: &'a str) -> BoxFuture<'a, Result<Vec<Order>>>;
+    fn get_order_trades<'a>(&'a self, order_id: &'a str) -> BoxFuture<'a, Result<Vec<Trade>>>;
+    /// Returns the new order's id
+    fn place_order<'a>(&'a self, order_request: &'a OrderRequest) -> BoxFuture<'a, Result<String>>;
+    fn modify_order<'a>(
+        &'a self,
+        order_id: &'a str,
+        order_request: &'a OrderRequest,
+    ) -> BoxFuture<'a, Result<String>>;
+    fn cancel_order<'a>(
+        &'a self,
+        order_id: &'a str,
+        variety: &'a str,
+    ) -> BoxFuture<'a, Result<String>>;
+}
+
+/// Live trading: every call goes to Kite with the handler's current access token
+impl BrokerClient for RwLock<ZerodhaClient> {
+    fn get_positions(&self) -> BoxFuture<'_, Result<Vec<Position>>> {
+        Box::pin(async move { self.read().await.get_positions().await })
+    }
+
+    fn get_orders(&self) -> BoxFuture<'_, Result<Vec<Order>>> {
+        Box::pin(async move { self.read().await.get_orders().await })
+    }
+
+    fn get_trades(&self) -> BoxFuture<'_, Result<Vec<Trade>>> {
+        Box::pin(async move { self.read().await.get_trades().await })
+    }
+
+    fn get_order_history<'a>(&'a self, order_id: &'a str) -> BoxFuture<'a, Result<Vec<Order>>> {
+        Box::pin(async move { self.read().await.get_order_history(order_id).await })
+    }
+
+    fn get_order_trades<'a>(&'a self, order_id: &'a str) -> BoxFuture<'a, Result<Vec<Trade>>> {
+        Box::pin(async move { self.read().await.get_order_trades(order_id).await })
+    }
+
+    fn place_order<'a>(&'a self, order_request: &'a OrderRequest) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move { self.read().await.place_order(order_request).await })
+    }
+
+    fn modify_order<'a>(
+        &'a self,
+        order_id: &'a str,
+        order_request: &'a OrderRequest,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move {
+            self.read()
+                .await
+                .modify_order(order_id, order_request)
+                .await
+        })
+    }
+
+    fn cancel_order<'a>(
+        &'a self,
+        order_id: &'a str,
+        variety: &'a str,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move { self.read().await.cancel_order(order_id, variety).await })
+    }
+}
@@ -1,3 +1,6 @@
+pub mod auth_callback;
+pub mod broker;
 pub mod zerodha_client;
 
+pub use broker::BrokerClient;
 pub use zerodha_client::*;
@@ -1,4 +1,6 @@
+use crate::candles::Candle;
 use crate::data_structures::*;
+use crate::redact;
 use anyhow::{Context, Result};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -7,6 +9,7 @@ use url::Url;
 
 /// High-performance Zerodha API client optimized for low-latency trading
 /// Uses connection pooling and async I/O for maximum throughput
+#[derive(Clone)]
 pub struct ZerodhaClient {
     client: Client,
     api_key: String,
@@ -24,8 +27,13 @@ pub struct ApiResponse<T> {
     pub error_type: Option<String>,
 }
 
+/// Kite refused the access token outright, as it does once the token has expired
+#[derive(Debug, thiserror::Error)]
+#[error("access token rejected: {0}")]
+pub struct TokenRejected(pub String);
+
 /// Session data returned after successful authentication
-#[derive(Debug, Deserialize)]
+#[derive(Deserialize)]
 pub struct SessionData {
     pub access_token: String,
     pub refresh_token: String,
@@ -40,6 +48,17 @@ pub struct SessionData {
     pub order_types: Vec<String>,
 }
 
+impl std::fmt::Debug for SessionData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionData")
+            .field("access_token", &redact::mask(&self.access_token))
+            .field("refresh_token", &redact::mask(&self.refresh_token))
+            .field("user_id", &self.user_id)
+            .field("user_name", &self.user_name)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Order response from Zerodha API
 #[derive(Debug, Deserialize)]
 pub struct OrderResponse {
@@ -73,12 +92,17 @@ impl ZerodhaClient {
 
     /// Generate login URL for Zerodha OAuth flow
     pub fn generate_login_url(&self, _redirect_url: &str) -> Result<String> {
-        let mut url = Url::parse("https://kite.trade/connect/login")?;
+        Ok(Self::login_url(&self.api_key))
+    }
+
+    /// Kite login page for an API key; it redirects back with a request_token
+    pub fn login_url(api_key: &str) -> String {
+        let mut url = Url::parse("https://kite.trade/connect/login").expect("valid login URL");
         url.query_pairs_mut()
-            .append_pair("api_key", &self.api_key)
+            .append_pair("api_key", api_key)
             .append_pair("v", "3");
 
-        Ok(url.to_string())
+        url.to_string()
     }
 
     /// Exchange request token for access token - critical for authentication flow
@@ -247,6 +271,119 @@ impl ZerodhaClient {
         }
     }
 
+    /// Fetch every state an order has passed through, oldest first
+    pub async fn get_order_history(&self, order_id: &str) -> Result<Vec<Order>> {
+        let access_token = self
+            .access_token
+            .as_ref()
+            .context("Access token not available")?;
+
+        let url = format!("{}/orders/{}", self.base_url, order_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .header(
+                "Authorization",
+                format!("token {}:{}", self.api_key, access_token),
+            )
+            .send()
+            .await
+            .context("Failed to fetch order history")?;
+
+        let api_response: ApiResponse<Vec<ApiOrder>> = response
+            .json()
+            .await
+            .context("Failed to parse order history response")?;
+
+        match api_response.status.as_str() {
+            "success" => Ok(api_response
+                .data
+                .unwrap_or_default()
+                .into_iter()
+                .map(|api_order| self.convert_api_order(api_order))
+                .collect()),
+            _ => {
+                let error_msg = api_response
+                    .message
+                    .unwrap_or_else(|| "Failed to fetch order history".to_string());
+                anyhow::bail!("API error: {}", error_msg)
+            }
+        }
+    }
+
+    /// Fetch the executions of a single order
+    pub async fn get_order_trades(&self, order_id: &str) -> Result<Vec<Trade>> {
+        let access_token = self
+            .access_token
+            .as_ref()
+            .context("Access token not available")?;
+
+        let url = format!("{}/orders/{}/trades", self.base_url, order_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .header(
+                "Authorization",
+                format!("token {}:{}", self.api_key, access_token),
+            )
+            .send()
+            .await
+            .context("Failed to fetch order trades")?;
+
+        let api_response: ApiResponse<Vec<Trade>> = response
+            .json()
+            .await
+            .context("Failed to parse order trades response")?;
+
+        match api_response.status.as_str() {
+            "success" => Ok(api_response.data.unwrap_or_default()),
+            _ => {
+                let error_msg = api_response
+                    .message
+                    .unwrap_or_else(|| "Failed to fetch order trades".to_string());
+                anyhow::bail!("API error: {}", error_msg)
+            }
+        }
+    }
+
+    /// Fetch today's executions from the tradebook
+    pub async fn get_trades(&self) -> Result<Vec<Trade>> {
+        let access_token = self
+            .access_token
+            .as_ref()
+            .context("Access token not available")?;
+
+        let url = format!("{}/trades", self.base_url);
+
+        let response = self
+            .client
+            .get(&url)
+            .header(
+                "Authorization",
+                format!("token {}:{}", self.api_key, access_token),
+            )
+            .send()
+            .await
+            .context("Failed to fetch trades")?;
+
+        let api_response: ApiResponse<Vec<Trade>> = response
+            .json()
+            .await
+            .context("Failed to parse trades response")?;
+
+        match api_response.status.as_str() {
+            "success" => Ok(api_response.data.unwrap_or_default()),
+            _ => {
+                let error_msg = api_response
+                    .message
+                    .unwrap_or_else(|| "Failed to fetch trades".to_string());
+                anyhow::bail!("API error: {}", error_msg)
+            }
+        }
+    }
+
     /// Place a new order with comprehensive validation
     pub async fn place_order(&self, order_request: &OrderRequest) -> Result<String> {
         let access_token = self
@@ -254,7 +391,7 @@ impl ZerodhaClient {
             .as_ref()
             .context("Access token not available")?;
 
-        let url = format!("{}/orders/regular", self.base_url);
+        let url = format!("{}/orders/{}", self.base_url, order_request.variety);
 
         let mut params = HashMap::new();
         params.insert("tradingsymbol", order_request.tradingsymbol.as_str());
@@ -268,6 +405,12 @@ impl ZerodhaClient {
         params.insert("product", order_request.product.as_str());
         params.insert("validity", order_request.validity.as_str());
 
+        let validity_ttl_str;
+        if let Some(validity_ttl) = order_request.validity_ttl {
+            validity_ttl_str = validity_ttl.to_string();
+            params.insert("validity_ttl", validity_ttl_str.as_str());
+        }
+
         let price_str;
         if let Some(price) = order_request.price {
             price_str = price.to_string();
@@ -366,6 +509,211 @@ impl ZerodhaClient {
         }
     }
 
+    /// Modify price, quantity, trigger or order type of an open order
+    pub async fn modify_order(
+        &self,
+        order_id: &str,
+        order_request: &OrderRequest,
+    ) -> Result<String> {
+        let access_token = self
+            .access_token
+            .as_ref()
+            .context("Access token not available")?;
+
+        let url = format!(
+            "{}/orders/{}/{}",
+            self.base_url, order_request.variety, order_id
+        );
+
+        let mut params = HashMap::new();
+        params.insert("order_type", order_request.order_type.as_str());
+        params.insert("validity", order_request.validity.as_str());
+
+        let quantity_str = order_request.quantity.to_string();
+        params.insert("quantity", quantity_str.as_str());
+
+        let price_str;
+        if let Some(price) = order_request.price {
+            price_str = price.to_string();
+            params.insert("price", price_str.as_str());
+        }
+
+        let trigger_price_str;
+        if let Some(trigger_price) = order_request.trigger_price {
+            trigger_price_str = trigger_price.to_string();
+            params.insert("trigger_price", trigger_price_str.as_str());
+        }
+
+        let disclosed_quantity_str;
+        if let Some(disclosed_quantity) = order_request.disclosed_quantity {
+            disclosed_quantity_str = disclosed_quantity.to_string();
+            params.insert("disclosed_quantity", disclosed_quantity_str.as_str());
+        }
+
+        let response = self
+            .client
+            .put(&url)
+            .header(
+                "Authorization",
+                format!("token {}:{}", self.api_key, access_token),
+            )
+            .form(&params)
+            .send()
+            .await
+            .context("Failed to modify order")?;
+
+        let api_response: ApiResponse<OrderResponse> = response
+            .json()
+            .await
+            .context("Failed to parse modify response")?;
+
+        match api_response.status.as_str() {
+            "success" => {
+                if let Some(order_data) = api_response.data {
+                    Ok(order_data.order_id)
+                } else {
+                    anyhow::bail!("Order ID not found in response")
+                }
+            }
+            _ => {
+                let error_msg = api_response
+                    .message
+                    .unwrap_or_else(|| "Failed to modify order".to_string());
+                anyhow::bail!("Order modification error: {}", error_msg)
+            }
+        }
+    }
+
+    /// Fetch account margins snapshot for all segments
+    pub async fn get_margins(&self) -> Result<Margins> {
+        let access_token = self
+            .access_token
+            .as_ref()
+            .context("Access token not available")?;
+
+        let url = format!("{}/user/margins", self.base_url);
+
+        let response = self
+            .client
+            .get(&url)
+            .header(
+                "Authorization",
+                format!("token {}:{}", self.api_key, access_token),
+            )
+            .send()
+            .await
+            .context("Failed to fetch margins")?;
+
+        let api_response: ApiResponse<Margins> = response
+            .json()
+            .await
+            .context("Failed to parse margins response")?;
+
+        match api_response.status.as_str() {
+            "success" => Ok(api_response.data.unwrap_or_default()),
+            _ => {
+                let error_msg = api_response
+                    .message
+                    .unwrap_or_else(|| "Failed to fetch margins".to_string());
+                anyhow::bail!("API error: {}", error_msg)
+            }
+        }
+    }
+
+    /// Fetch the account profile; a refused token fails with [`TokenRejected`]
+    pub async fn get_profile(&self) -> Result<UserProfile> {
+        let access_token = self
+            .access_token
+            .as_ref()
+            .context("Access token not available")?;
+
+        let url = format!("{}/user/profile", self.base_url);
+
+        let response = self
+            .client
+            .get(&url)
+            .header(
+                "Authorization",
+                format!("token {}:{}", self.api_key, access_token),
+            )
+            .send()
+            .await
+            .context("Failed to fetch profile")?;
+
+        let status = response.status();
+        let api_response: ApiResponse<UserProfile> = response
+            .json()
+            .await
+            .context("Failed to parse profile response")?;
+
+        match api_response.status.as_str() {
+            "success" => api_response.data.context("Profile missing from response"),
+            _ => {
+                let error_msg = api_response
+                    .message
+                    .unwrap_or_else(|| "Failed to fetch profile".to_string());
+                if status == reqwest::StatusCode::FORBIDDEN
+                    || api_response.error_type.as_deref() == Some("TokenException")
+                {
+                    return Err(TokenRejected(error_msg).into());
+                }
+                anyhow::bail!("API error: {}", error_msg)
+            }
+        }
+    }
+
+    /// Preview the margin an order would block before placing it
+    pub async fn get_order_margin(&self, order_request: &OrderRequest) -> Result<OrderMargin> {
+        let access_token = self
+            .access_token
+            .as_ref()
+            .context("Access token not available")?;
+
+        let url = format!("{}/margins/orders", self.base_url);
+
+        let body = serde_json::json!([{
+            "exchange": order_request.exchange,
+            "tradingsymbol": order_request.tradingsymbol,
+            "transaction_type": order_request.transaction_type,
+            "variety": order_request.variety,
+            "product": order_request.product,
+            "order_type": order_request.order_type,
+            "quantity": order_request.quantity,
+            "price": order_request.price.unwrap_or(0.0),
+            "trigger_price": order_request.trigger_price.unwrap_or(0.0),
+        }]);
+
+        let response = self
+            .client
+            .post(&url)
+            .header(
+                "Authorization",
+                format!("token {}:{}", self.api_key, access_token),
+            )
+            .json(&body)
+            .send()
+            .await
+            .context("Failed to fetch order margins")?;
+
+        let api_response: ApiResponse<Vec<OrderMargin>> = response
+            .json()
+            .await
+            .context("Failed to parse order margins response")?;
+
+        match api_response.status.as_str() {
+            "success" => api_response
+                .data
+                .and_then(|margins| margins.into_iter().next())
+                .context("Order margin not found in response"),
+            _ => {
+                let error_msg = api_response
+                    .message
+                    .unwrap_or_else(|| "Failed to fetch order margins".to_string());
+                anyhow::bail!("API error: {}", error_msg)
+            }
+        }
+    }
+
     /// Fetch instrument master data for symbol lookup
     pub async fn get_instruments(&self, exchange: &str) -> Result<Vec<Instrument>> {
         let url = format!("{}/instruments/{}", self.base_url, exchange);
@@ -386,17 +734,60 @@ impl ZerodhaClient {
         self.parse_instruments_csv(&csv_data)
     }
 
+    /// Fetch one-minute candles for an instrument between two IST timestamps
+    pub async fn get_historical_candles(
+        &self,
+        instrument_token: u32,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<Candle>> {
+        let access_token = self
+            .access_token
+            .as_ref()
+            .context("Access token not available")?;
+
+        let url = format!(
+            "{}/instruments/historical/{}/minute",
+            self.base_url, instrument_token
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .query(&[("from", from), ("to", to)])
+            .header(
+                "Authorization",
+                format!("token {}:{}", self.api_key, access_token),
+            )
+            .send()
+            .await
+            .context("Failed to fetch historical candles")?;
+
+        let api_response: ApiResponse<ApiHistoricalData> = response
+            .json()
+            .await
+            .context("Failed to parse historical candles response")?;
+
+        match api_response.status.as_str() {
+            "success" => api_response
+                .data
+                .map(|data| data.candles)
+                .unwrap_or_default()
+                .into_iter()
+                .map(convert_api_candle)
+                .collect(),
+            _ => {
+                let error_msg = api_response
+                    .message
+                    .unwrap_or_else(|| "Failed to fetch historical candles".to_string());
+                anyhow::bail!("API error: {}", error_msg)
+            }
+        }
+    }
+
     /// Convert API order format to our Order struct
     fn convert_api_order(&self, api_order: ApiOrder) -> Order {
-        let status = match api_order.status.as_str() {
-            "OPEN" => OrderStatus::Open,
-            "COMPLETE" => OrderStatus::Complete,
-            "CANCELLED" => OrderStatus::Cancelled,
-            "REJECTED" => OrderStatus::Rejected,
-            "TRIGGER PENDING" => OrderStatus::Trigger,
-            "MODIFIED" => OrderStatus::Modified,
-            _ => OrderStatus::Open,
-        };
+        let status = OrderStatus::from_api_str(&api_order.status);
 
         Order {
             order_id: api_order.order_id,
@@ -522,3 +913,28 @@ struct ApiOrder {
     status_message: Option<String>,
     tag: Option<String>,
 }
+
+/// API response structure for historical candles
+/// Each candle is [timestamp, open, high, low, close, volume]
+#[derive(Debug, Deserialize)]
+struct ApiHistoricalData {
+    candles: Vec<(String, f64, f64, f64, f64, u64)>,
+}
+
+/// Convert an API candle row, whose timestamp carries an IST offset like +0530
+fn convert_api_candle(
+    (timestamp, open, high, low, close, volume): (String, f64, f64, f64, f64, u64),
+) -> Result<Candle> {
+    let start = chrono::DateTime::parse_from_str(&timestamp, "%Y-%m-%dT%H:%M:%S%z")
+        .with_context(|| format!("Invalid candle timestamp: {}", timestamp))?
+        .with_timezone(&chrono::Utc);
+
+    Ok(Candle {
+        start,
+        open,
+        high,
+        low,
+        close,
+        volume,
+    })
+}
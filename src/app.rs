@@ -1,154 +1,1019 @@
+use crate::api::BrokerClient;
+use crate::broadcast::Broadcaster;
 use crate::data_structures::LogLevel;
-use crate::state::{AppState, AuthState, Config, EventSender};
+use crate::market_calendar::MarketStatus;
+use crate::notifier::Notifier;
+use crate::paper::PaperBroker;
+use crate::sound::{SoundEvent, SoundPlayer};
+use crate::state::{
+    AppConfig, AppEvent, AppState, AuthState, Command, CommandReceivers, Config, ConfigReport,
+    ConnectionState, DetachedView, EventSender, SavedUiState, TickRateWindow, TradingMode, Worker,
+    API_LATENCY_BUCKETS, CONFIG_FILE, TICK_RATE_WINDOW_SECS, UI_STATE_STORAGE_KEY,
+};
+use crate::strategy::{StrategyData, StrategyRunner};
 use crate::ui;
-use crate::workers::{ApiHandler, WebSocketHandler};
+use crate::ui::format::format_inr_signed;
+use crate::ui::theme::{Theme, ThemeMode, THEME_STORAGE_KEY, UI_SCALE_RANGE, UI_SCALE_STORAGE_KEY};
+use crate::workers::circuit_breaker::CircuitState;
+use crate::workers::{ApiHandler, ConfigWatcher, WebSocketHandler};
 use crossbeam_channel::Receiver;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, watch};
+
+/// Native window title, and the suffix of the live one
+pub const WINDOW_TITLE: &str = "Professional Trading Dashboard";
+
+/// Repaint cadence while ticks are streaming in
+const STREAMING_REPAINT: Duration = Duration::from_millis(100);
+
+/// Repaint cadence with nothing arriving, enough for the status bar clock
+const IDLE_REPAINT: Duration = Duration::from_secs(1);
+
+/// With no tick applied for this long, ticks no longer count as streaming
+const STREAMING_TICK_AGE: Duration = Duration::from_secs(2);
+
+/// How often the live window title is recomputed
+const TITLE_REFRESH: Duration = Duration::from_secs(1);
+
+/// Pause before restarting a panicked worker, doubled on each crash up to the cap
+const WORKER_RESTART_DELAY: Duration = Duration::from_secs(1);
+const WORKER_RESTART_DELAY_MAX: Duration = Duration::from_secs(30);
+
+/// A worker that stayed up this long starts over from the shortest restart delay
+const WORKER_STABLE_AFTER: Duration = Duration::from_secs(60);
 
 /// Main trading application implementing eframe::App
 /// Designed for ultra-low latency UI updates and responsive user interaction
 pub struct TradingApp {
     app_state: AppState,
     current_view: AppView,
-    // Worker handles for cleanup
-    _api_handler: tokio::task::JoinHandle<()>,
-    _websocket_handler: tokio::task::JoinHandle<()>,
+    // Theme last installed on the egui context, to notice runtime changes
+    applied_theme: Theme,
+    // Zoom factor shown in the settings menu and saved on exit
+    ui_scale: f32,
+    // Where each detached window was opened; later moves are saved but not fed back to it
+    detached_placements: HashMap<AppView, DetachedView>,
+    // Native window title as last sent, and when it was last recomputed
+    window_title: String,
+    title_checked_at: Instant,
+    // Supervised workers, waited for and then aborted on exit
+    workers: Vec<WorkerHandle>,
+    // ticks_gen as last seen, and when it last moved
+    ticks_seen: (u64, Instant),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AppView {
+    #[default]
     Overview,
     Positions,
+    Watchlist,
+    Charts,
     Orders,
+    Trades,
     PnL,
     Logs,
+    Instruments,
+    // Opened from the ⚙ menu rather than a tab
+    Settings,
+}
+
+impl AppView {
+    /// Navigation order; number keys 1-9 follow it
+    pub const ALL: [AppView; 9] = [
+        AppView::Overview,
+        AppView::Positions,
+        AppView::Watchlist,
+        AppView::Charts,
+        AppView::Orders,
+        AppView::Trades,
+        AppView::PnL,
+        AppView::Logs,
+        AppView::Instruments,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AppView::Overview => "📊 Overview",
+            AppView::Positions => "💼 Positions",
+            AppView::Watchlist => "👁 Watchlist",
+            AppView::Charts => "📈 Charts",
+            AppView::Orders => "📋 Orders",
+            AppView::Trades => "🧾 Trades",
+            AppView::PnL => "💰 P&L",
+            AppView::Logs => "📝 Logs",
+            AppView::Instruments => "🔍 Instruments",
+            AppView::Settings => "⚙ Settings",
+        }
+    }
+
+    /// Views that can pop out into a window of their own, e.g. on a second monitor
+    pub fn detachable(self) -> bool {
+        matches!(self, AppView::Positions | AppView::Charts)
+    }
+}
+
+/// What it takes to start each worker again: the shared command and event channels, and
+/// config.toml as last seen by the watcher, so a restart after re-authentication gets the
+/// new token
+struct WorkerRegistry {
+    event_sender: EventSender,
+    // The paper book in paper mode; it outlives API handler restarts
+    paper_broker: Option<Arc<dyn BrokerClient>>,
+    config_updates: watch::Receiver<Config>,
+    commands: CommandReceivers,
+    // Its state is shared with the command processor the first run spawns, which keeps
+    // going across restarts, so only the connection loop starts over
+    websocket: WebSocketHandler,
+}
+
+type WorkerRun = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A supervised worker as the app holds it for shutdown
+pub struct WorkerHandle {
+    worker: Worker,
+    supervisor: tokio::task::JoinHandle<()>,
+    // Fires when the worker returns on its own, i.e. it finished on Shutdown
+    stopped: oneshot::Receiver<()>,
+}
+
+impl WorkerRegistry {
+    /// A worker's first run, or a fresh one after a panic
+    fn run(&self, worker: Worker, restarted: bool) -> WorkerRun {
+        match worker {
+            Worker::Api => {
+                let mut handler = ApiHandler::new(
+                    self.config_updates.borrow().clone(),
+                    self.event_sender.clone(),
+                    self.config_updates.clone(),
+                    self.paper_broker.clone(),
+                );
+                let commands = self.commands.api.clone();
+                let priority = self.commands.api_priority.clone();
+                Box::pin(async move { handler.run(priority, commands).await })
+            }
+            Worker::WebSocket => {
+                let mut handler = self.websocket.clone_for_task();
+                let commands = self.commands.websocket.clone();
+                Box::pin(async move {
+                    if restarted {
+                        handler.resume().await;
+                    } else {
+                        handler.run(commands).await;
+                    }
+                })
+            }
+        }
+    }
+
+    /// Start a worker under supervision
+    fn spawn(self: &Arc<Self>, worker: Worker) -> WorkerHandle {
+        let (stopped_sender, stopped) = oneshot::channel();
+        WorkerHandle {
+            worker,
+            supervisor: tokio::spawn(Arc::clone(self).supervise(worker, stopped_sender)),
+            stopped,
+        }
+    }
+
+    /// Keep a worker running: after a panic, report it and start it again following a
+    /// growing delay; a worker that returns, e.g. on Shutdown, stays stopped
+    async fn supervise(self: Arc<Self>, worker: Worker, stopped: oneshot::Sender<()>) {
+        let mut delay = WORKER_RESTART_DELAY;
+        let mut restarted = false;
+        loop {
+            let started = Instant::now();
+            let error = match tokio::spawn(self.run(worker, restarted)).await {
+                Ok(()) => {
+                    let _ = stopped.send(());
+                    return;
+                }
+                Err(error) if error.is_panic() => error,
+                // Cancelled as the runtime shuts down
+                Err(_) => return,
+            };
+
+            if started.elapsed() >= WORKER_STABLE_AFTER {
+                delay = WORKER_RESTART_DELAY;
+            }
+            let _ = self.event_sender.send(AppEvent::WorkerPanicked {
+                worker,
+                message: panic_message(error.into_panic()),
+                restart_in: delay,
+            });
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(WORKER_RESTART_DELAY_MAX);
+            restarted = true;
+        }
+    }
+}
+
+/// Start everything behind the dashboard for `app_state`, with or without a window: the
+/// config watcher, the paper book and strategies when configured, and the supervised
+/// workers; then restore the watchlist, alerts and journal
+/// Events wake `repaint` when there is a window to redraw
+pub fn start_backend(
+    app_state: &mut AppState,
+    command_receivers: CommandReceivers,
+    config: &Config,
+    repaint: Option<egui::Context>,
+) -> Vec<WorkerHandle> {
+    // Create event sender for workers
+    let (event_sender_tx, event_receiver_rx) = crossbeam_channel::unbounded();
+    let (tick_sender, tick_receiver) =
+        crossbeam_channel::bounded(config.performance.tick_buffer_size);
+    crate::logging::attach(EventSender::new(event_sender_tx.clone()));
+    crate::crash::watch_logs(Arc::clone(&app_state.logs));
+    let mut event_sender = EventSender::new(event_sender_tx).with_ticks(
+        tick_sender,
+        tick_receiver.clone(),
+        config.performance.drop_policy,
+        Arc::clone(&app_state.dropped_ticks),
+    );
+    if let Some(ctx) = repaint {
+        event_sender = event_sender.with_repaint(ctx);
+    }
+
+    // Update app state with event receiver
+    app_state.event_receiver = event_receiver_rx;
+    app_state.tick_receiver = tick_receiver;
+
+    if config.server.broadcast_port != 0 {
+        app_state.broadcaster = Some(Broadcaster::start(config.server.broadcast_port));
+    }
+    #[cfg(feature = "metrics")]
+    if config.server.metrics_port != 0 {
+        crate::metrics::start(
+            config.server.metrics_port,
+            Arc::clone(&app_state.metrics),
+            Arc::clone(&app_state.connection_state),
+            app_state.broadcaster.clone(),
+        );
+    }
+    #[cfg(not(feature = "metrics"))]
+    if config.server.metrics_port != 0 {
+        app_state.add_log(
+            LogLevel::Warning,
+            "server.metrics_port is set, but this build has no metrics exporter; build with --features metrics".to_string(),
+            Some("server".to_string()),
+        );
+    }
+
+    if !config.app.ntp_server.is_empty() {
+        crate::clock::start(config.app.ntp_server.clone(), event_sender.clone());
+    }
+
+    app_state.notifier = Some(Notifier::start(
+        config.notifier.clone(),
+        event_sender.clone(),
+    ));
+
+    // Watch config.toml so settings can change without a restart
+    let (config_watcher, config_updates) = ConfigWatcher::new(config.clone(), event_sender.clone());
+    tokio::spawn(config_watcher.run());
+
+    // In paper mode orders fill against live ticks in a local book instead of going to Kite
+    let paper_broker = (config.app.mode == TradingMode::Paper).then(|| {
+        let broker = Arc::new(PaperBroker::new(
+            app_state.instruments.clone(),
+            app_state.instrument_index.clone(),
+            app_state.tick_data.clone(),
+            config.app.paper_slippage_pct,
+        ));
+        broker.start(app_state.command_sender.clone());
+        app_state.add_log(
+            LogLevel::Warning,
+            "Paper trading: orders are simulated and never reach the exchange".to_string(),
+            Some("paper".to_string()),
+        );
+        broker as Arc<dyn BrokerClient>
+    });
+
+    // Strategies from config.toml; their orders come back through send_command, so the
+    // kill switch and order checks apply to them too
+    if !config.strategies.is_empty() {
+        app_state.strategies = StrategyRunner::start(
+            &config.strategies,
+            StrategyData {
+                positions: app_state.positions.clone(),
+                orders: app_state.orders.clone(),
+                tag_positions: app_state.tag_positions.clone(),
+                instruments: app_state.instruments.clone(),
+                instrument_index: app_state.instrument_index.clone(),
+                tick_data: app_state.tick_data.clone(),
+                candles: app_state.candles.clone(),
+                event_sender: event_sender.clone(),
+            },
+        );
+        app_state.add_log(
+            LogLevel::Info,
+            format!("{} strategies loaded", config.strategies.len()),
+            Some("strategy".to_string()),
+        );
+    }
+
+    // Start the API and WebSocket workers under supervision
+    let workers = Arc::new(WorkerRegistry {
+        paper_broker,
+        websocket: WebSocketHandler::new(
+            config.clone(),
+            event_sender.clone(),
+            config_updates.clone(),
+            Arc::clone(&app_state.instruments),
+        ),
+        event_sender: event_sender.clone(),
+        config_updates,
+        commands: command_receivers,
+    });
+    let workers = vec![workers.spawn(Worker::Api), workers.spawn(Worker::WebSocket)];
+
+    // Restore the watchlist now that workers are listening for subscriptions
+    app_state.load_watchlist();
+    app_state.load_alerts();
+    app_state.open_journal();
+    app_state.open_history(event_sender.clone());
+    // Check the access token up front; a working token with no recorded age is dated
+    app_state.send_command(Command::FetchUserProfile);
+    workers
+}
+
+/// The text a panic was raised with, when it has one
+pub fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "no panic message".to_string())
 }
 
 impl TradingApp {
     /// Create new trading application with all workers and communication channels
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        // Load configuration
-        let config = Config::load().expect("Failed to load configuration");
-
+    pub fn new(cc: &eframe::CreationContext<'_>, config: Config) -> Self {
         // Initialize application state and channels
-        let (mut app_state, command_receiver) = AppState::new(config.clone());
+        let (mut app_state, command_receivers) = AppState::new(config.clone());
 
-        // Create event sender for workers
-        let (event_sender_tx, event_receiver_rx) = crossbeam_channel::unbounded();
-        let event_sender = EventSender::new(event_sender_tx);
+        // A theme picked in the settings menu outlives the config.toml default
+        if let Some(theme) = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<Theme>(storage, THEME_STORAGE_KEY))
+        {
+            app_state.theme = theme;
+        }
+        app_state.theme.apply(&cc.egui_ctx);
 
-        // Update app state with event receiver
-        app_state.event_receiver = event_receiver_rx;
+        let ui_scale = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<f32>(storage, UI_SCALE_STORAGE_KEY))
+            .unwrap_or(1.0)
+            .clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end());
+        cc.egui_ctx.set_zoom_factor(ui_scale);
 
-        // Start API handler worker
-        let api_handler = ApiHandler::new(config.clone(), event_sender.clone());
-        let command_receiver_clone = command_receiver.clone();
-        let api_handler_task = tokio::spawn(async move {
-            let mut handler = api_handler;
-            handler.run(command_receiver_clone).await;
-        });
+        // Unreadable state from an older or damaged save just means defaults
+        let current_view = cc
+            .storage
+            .and_then(|storage| eframe::get_value::<SavedUiState>(storage, UI_STATE_STORAGE_KEY))
+            .unwrap_or_default()
+            .restore(&mut app_state.ui_input);
 
-        // Start WebSocket handler worker
-        let websocket_handler = WebSocketHandler::new(config.clone(), event_sender.clone());
-        let command_receiver_clone = command_receiver.clone();
-        let websocket_handler_task = tokio::spawn(async move {
-            let mut handler = websocket_handler;
-            handler.run(command_receiver_clone).await;
-        });
+        let workers = start_backend(
+            &mut app_state,
+            command_receivers,
+            &config,
+            Some(cc.egui_ctx.clone()),
+        );
+        if let Some(token) = app_state.ui_input.selected_instrument_token.take() {
+            app_state.select_instrument(token);
+        }
 
         app_state.add_log(
             LogLevel::Info,
             "Trading application initialized".to_string(),
             Some("app".to_string()),
         );
+        if let Some(report) = crate::crash::take_pending() {
+            app_state.add_log(
+                LogLevel::Warning,
+                format!("The last session crashed; report in {}", report.display()),
+                Some("app".to_string()),
+            );
+            app_state.ui_input.crash_report = Some(report);
+        }
 
         Self {
+            applied_theme: app_state.theme,
+            ui_scale,
+            detached_placements: HashMap::new(),
+            window_title: WINDOW_TITLE.to_string(),
+            title_checked_at: Instant::now(),
+            ticks_seen: (0, Instant::now()),
             app_state,
-            current_view: AppView::Overview,
-            _api_handler: api_handler_task,
-            _websocket_handler: websocket_handler_task,
+            current_view,
+            workers,
         }
     }
 
     /// Render main navigation tabs
+    /// Tabs wrap onto a second row when a large UI scale leaves too little width
     fn render_navigation(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            ui.selectable_value(&mut self.current_view, AppView::Overview, "📊 Overview");
-            ui.selectable_value(&mut self.current_view, AppView::Positions, "💼 Positions");
-            ui.selectable_value(&mut self.current_view, AppView::Orders, "📋 Orders");
-            ui.selectable_value(&mut self.current_view, AppView::PnL, "💰 P&L");
-            ui.selectable_value(&mut self.current_view, AppView::Logs, "📝 Logs");
+        ui.horizontal_wrapped(|ui| {
+            for view in AppView::ALL {
+                ui.selectable_value(&mut self.current_view, view, view.label());
+            }
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                // Show personal trading indicator
-                ui.label("👤 Personal Trading");
+                if render_settings_menu(
+                    ui,
+                    &mut self.app_state.theme,
+                    &mut self.ui_scale,
+                    &mut self.current_view,
+                ) {
+                    self.reset_layout(ui.ctx());
+                }
+                ui::render_notification_bell(ui, &mut self.app_state);
+
+                let view = self.current_view;
+                if view.detachable()
+                    && self.detached(view).is_none()
+                    && ui
+                        .small_button("⧉ Detach")
+                        .on_hover_text(format!("Open {} in its own window", view.label()))
+                        .clicked()
+                {
+                    self.app_state
+                        .ui_input
+                        .detached_views
+                        .push(DetachedView::new(view));
+                }
+                render_kill_switch(ui, &mut self.app_state);
+
+                // Show personal trading indicator, and make paper trading impossible to miss
+                if self.app_state.config.app.mode == TradingMode::Paper {
+                    ui.label(
+                        egui::RichText::new(" PAPER ")
+                            .strong()
+                            .color(egui::Color32::BLACK)
+                            .background_color(egui::Color32::from_rgb(245, 158, 11)),
+                    )
+                    .on_hover_text("Paper trading: orders fill against live ticks in a local book and never reach the exchange");
+                } else {
+                    ui.label("👤 Personal Trading");
+                }
             });
         });
     }
 
+    /// Live P&L and connection state in the window title, when enabled, so a minimized
+    /// dashboard can still be read from the taskbar
+    /// Recomputed once a second and only sent to the window when it changes
+    fn update_window_title(&mut self, ctx: &egui::Context) {
+        if self.title_checked_at.elapsed() < TITLE_REFRESH {
+            return;
+        }
+        self.title_checked_at = Instant::now();
+
+        let mut title = if self.app_state.config.app.live_window_title {
+            let total = self.app_state.calculate_total_pnl().total;
+            format!(
+                "{} {} · {} — {}",
+                if total >= 0.0 { "▲" } else { "▼" },
+                format_inr_signed(total),
+                self.app_state.connection_state.read().label(),
+                WINDOW_TITLE
+            )
+        } else {
+            WINDOW_TITLE.to_string()
+        };
+        if self.app_state.config.app.mode == TradingMode::Paper {
+            title.insert_str(0, "[PAPER] ");
+        }
+
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+    }
+
+    /// When to draw the next frame: straight away after events that changed state, at ~10Hz
+    /// while connected and ticking, and once a second otherwise
+    /// Workers wake the UI themselves for anything but market data, see EventSender
+    fn repaint_interval(&mut self, changed: bool) -> Duration {
+        // Measured on this machine's monotonic clock, so exchange timestamps and clock
+        // drift don't decide it
+        let (seen, moved_at) = &mut self.ticks_seen;
+        if self.app_state.ticks_gen.changed_since(*seen) {
+            *seen = self.app_state.ticks_gen.get();
+            *moved_at = Instant::now();
+        }
+        if changed {
+            return Duration::ZERO;
+        }
+
+        let connected = *self.app_state.connection_state.read() == ConnectionState::Connected;
+        let ticking = self.ticks_seen.1.elapsed() < STREAMING_TICK_AGE;
+        if connected && ticking {
+            STREAMING_REPAINT
+        } else {
+            IDLE_REPAINT
+        }
+    }
+
+    /// Forget the saved view, filters and sorting, and egui's window positions
+    fn reset_layout(&mut self, ctx: &egui::Context) {
+        self.current_view = SavedUiState::default().restore(&mut self.app_state.ui_input);
+        self.detached_placements.clear();
+        ctx.memory_mut(|memory| {
+            memory.reset_areas();
+            memory.data = Default::default();
+        });
+    }
+
     /// Render main content area based on current view
+    /// A detached view leaves a placeholder here while it shows in its own window
     fn render_content(&mut self, ui: &mut egui::Ui) {
-        match self.current_view {
-            AppView::Overview => {
-                ui::render_overview(ui, &mut self.app_state);
-            }
-            AppView::Positions => {
-                ui::render_positions(ui, &mut self.app_state);
+        if let Some(view) = self.app_state.ui_input.requested_view.take() {
+            self.current_view = view;
+            if let Some(detached) = self.detached(view) {
+                ui.ctx()
+                    .send_viewport_cmd_to(detached.viewport_id(), egui::ViewportCommand::Focus);
             }
-            AppView::Orders => {
-                ui::render_orders(ui, &mut self.app_state);
-            }
-            AppView::PnL => {
-                ui::render_pnl(ui, &mut self.app_state);
+        }
+
+        if self.detached(self.current_view).is_some() {
+            ui.vertical_centered(|ui| {
+                ui.add_space(50.0);
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{} is open in its own window",
+                        self.current_view.label()
+                    ))
+                    .size(18.0)
+                    .color(egui::Color32::GRAY),
+                );
+                ui.add_space(10.0);
+                if ui.button("⮌ Re-attach").clicked() {
+                    let view = self.current_view;
+                    self.reattach(view);
+                }
+            });
+            return;
+        }
+
+        render_view(ui, self.current_view, &mut self.app_state);
+    }
+
+    fn detached(&self, view: AppView) -> Option<&DetachedView> {
+        self.app_state
+            .ui_input
+            .detached_views
+            .iter()
+            .find(|detached| detached.view == view)
+    }
+
+    fn reattach(&mut self, view: AppView) {
+        self.detached_placements.remove(&view);
+        self.app_state
+            .ui_input
+            .detached_views
+            .retain(|detached| detached.view != view);
+    }
+
+    /// Draw each detached view in its own OS window, within this frame so it shares AppState
+    /// Closing the window re-attaches the view; its position and size are kept for next time
+    fn render_detached_views(&mut self, ctx: &egui::Context) {
+        let mut reattached = Vec::new();
+        let app_state = &mut self.app_state;
+
+        for index in 0..app_state.ui_input.detached_views.len() {
+            let detached = app_state.ui_input.detached_views[index];
+            let placement = *self
+                .detached_placements
+                .entry(detached.view)
+                .or_insert(detached);
+            let mut builder = egui::ViewportBuilder::default()
+                .with_title(format!("{} — {}", detached.view.label(), WINDOW_TITLE))
+                .with_inner_size(placement.size.unwrap_or(egui::vec2(900.0, 600.0)));
+            if let Some(position) = placement.position {
+                builder = builder.with_position(position);
             }
-            AppView::Logs => {
-                ui::render_logs(ui, &mut self.app_state);
+
+            let (close, position, size) =
+                ctx.show_viewport_immediate(detached.viewport_id(), builder, |ctx, class| {
+                    if class == egui::ViewportClass::Embedded {
+                        // No native multi-window support, so fall back to an egui window
+                        let mut open = true;
+                        egui::Window::new(detached.view.label())
+                            .open(&mut open)
+                            .default_size(egui::vec2(900.0, 600.0))
+                            .show(ctx, |ui| render_view(ui, detached.view, app_state));
+                        return (!open, None, None);
+                    }
+
+                    egui::CentralPanel::default()
+                        .show(ctx, |ui| render_view(ui, detached.view, app_state));
+                    ctx.input(|input| {
+                        let viewport = input.viewport();
+                        (
+                            viewport.close_requested(),
+                            viewport.outer_rect.map(|rect| rect.min),
+                            viewport.inner_rect.map(|rect| rect.size()),
+                        )
+                    })
+                });
+
+            let detached = &mut app_state.ui_input.detached_views[index];
+            detached.position = position.or(detached.position);
+            detached.size = size.or(detached.size);
+            if close {
+                reattached.push(detached.view);
             }
         }
+
+        for view in reattached {
+            self.reattach(view);
+        }
     }
 
     /// Render status bar with connection info and metrics
     fn render_status_bar(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            // Connection status
+        ui.horizontal_wrapped(|ui| {
+            // Connection status, as reported by the WebSocket worker
+            let connection_state = *self.app_state.connection_state.read();
+            match connection_state {
+                ConnectionState::Connecting => {
+                    ui.colored_label(egui::Color32::GRAY, "⚪ Connecting…");
+                }
+                ConnectionState::Connected => {
+                    ui.colored_label(egui::Color32::GREEN, "🟢 Connected");
+                }
+                ConnectionState::Reconnecting { attempt } => {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!("🟡 Reconnecting (attempt {})", attempt),
+                    );
+                }
+                ConnectionState::Disconnected => {
+                    ui.colored_label(egui::Color32::RED, "🔴 Disconnected");
+                }
+                ConnectionState::AuthExpired => {
+                    ui.colored_label(egui::Color32::RED, "🔴 Auth expired");
+                }
+            }
+
+            if connection_state != ConnectionState::Connected
+                && ui.small_button("Reconnect").clicked()
+            {
+                self.app_state.send_command(Command::ReconnectWebSocket);
+            }
+
             let metrics = self.app_state.metrics.read();
 
-            if let Some(last_tick) = metrics.last_tick_timestamp {
-                let elapsed = chrono::Utc::now().signed_duration_since(last_tick);
-                if elapsed.num_seconds() < 5 {
-                    ui.colored_label(egui::Color32::GREEN, "🟢 Live");
-                } else {
-                    ui.colored_label(egui::Color32::YELLOW, "🟡 Delayed");
+            // No ticks are expected while the market is closed, so don't alarm
+            let market_status = MarketStatus::now("NSE");
+            if !market_status.is_trading() {
+                ui.colored_label(egui::Color32::GRAY, market_status.label());
+            } else if connection_state == ConnectionState::Connected {
+                let stale = metrics.last_tick_timestamp.is_none_or(|last_tick| {
+                    chrono::Utc::now()
+                        .signed_duration_since(last_tick)
+                        .num_seconds()
+                        >= 5
+                });
+                if stale {
+                    ui.colored_label(egui::Color32::YELLOW, "Stream stale");
                 }
-            } else {
-                ui.colored_label(egui::Color32::RED, "🔴 Disconnected");
             }
 
             ui.separator();
 
             // Performance metrics
-            ui.label(format!("Ticks: {}", metrics.ticks_processed));
+            render_tick_rate(ui, &metrics.tick_rate, &self.app_state.theme);
             ui.label(format!("Orders: {}", metrics.orders_processed));
+            if let Some(average) = metrics.api_latency.average_ms() {
+                let p95 = match metrics.api_latency.p95_bound_seconds() {
+                    Some(bound) => format!("95% within {} s", bound),
+                    None => format!(
+                        "95% within more than {} s",
+                        API_LATENCY_BUCKETS[API_LATENCY_BUCKETS.len() - 1]
+                    ),
+                };
+                ui.label(format!("API {:.0} ms", average))
+                    .on_hover_text(format!(
+                        "Average of {} API requests this session; {}",
+                        metrics.api_latency.count, p95
+                    ));
+            }
+            if !metrics.api_circuits.is_empty() {
+                let open = metrics
+                    .api_circuits
+                    .values()
+                    .any(|state| matches!(state, CircuitState::Open { .. }));
+                let (color, icon) = if open {
+                    (self.app_state.theme.loss, "⛔")
+                } else {
+                    (egui::Color32::YELLOW, "◐")
+                };
+                let details = metrics
+                    .api_circuits
+                    .iter()
+                    .map(|(category, state)| match state {
+                        CircuitState::Open { retry_at } => format!(
+                            "{}: failing fast, retrying at {}",
+                            category.label(),
+                            retry_at.with_timezone(&chrono::Local).format("%H:%M:%S")
+                        ),
+                        _ => format!("{}: trying one request", category.label()),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ui.colored_label(
+                    color,
+                    format!(
+                        "{} API {}",
+                        icon,
+                        metrics
+                            .api_circuits
+                            .keys()
+                            .map(|category| category.label())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                )
+                .on_hover_text(details);
+            }
+            ui.label(
+                egui::RichText::new(format!("{} fps", metrics.frames_per_second))
+                    .small()
+                    .color(egui::Color32::GRAY),
+            )
+            .on_hover_text("Frames drawn in the last second; about 1 while nothing changes");
 
-            if metrics.average_tick_latency_ms > 0.0 {
-                ui.label(format!("Latency: {:.1}ms", metrics.average_tick_latency_ms));
+            if metrics.worker_restarts > 0 {
+                let noun = if metrics.worker_restarts == 1 {
+                    "time"
+                } else {
+                    "times"
+                };
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!("⚠ Worker restarted {} {}", metrics.worker_restarts, noun),
+                )
+                .on_hover_text(metrics.last_worker_panic.as_deref().unwrap_or_default());
             }
 
+            let token_issued_at = self.app_state.config.zerodha.access_token_issued_at;
+            let token_expiry = self.app_state.token_expiry();
+            let loss_color = self.app_state.theme.loss;
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 // Current time
                 let now = chrono::Local::now();
                 ui.label(format!("🕐 {}", now.format("%H:%M:%S")));
+                ui.separator();
+                render_token_age(ui, token_issued_at, token_expiry, loss_color);
+
+                if self.app_state.config.app.sounds {
+                    ui.separator();
+                    render_sound_controls(
+                        ui,
+                        &mut self.app_state.sounds,
+                        &mut self.app_state.config.app,
+                    );
+                }
             });
         });
     }
 }
 
+/// Contents of one view, in the main window or a detached one
+fn render_view(ui: &mut egui::Ui, view: AppView, app_state: &mut AppState) {
+    match view {
+        AppView::Overview => ui::render_overview(ui, app_state),
+        AppView::Positions => ui::render_positions(ui, app_state),
+        AppView::Watchlist => ui::render_watchlist(ui, app_state),
+        AppView::Charts => ui::render_charts(ui, app_state),
+        AppView::Orders => ui::render_orders(ui, app_state),
+        AppView::Trades => ui::render_trades(ui, app_state),
+        AppView::PnL => ui::render_pnl(ui, app_state),
+        AppView::Logs => ui::render_logs(ui, app_state),
+        AppView::Instruments => ui::render_instruments(ui, app_state),
+        AppView::Settings => ui::render_settings(ui, app_state),
+    }
+}
+
+/// Time since the access token was issued, red once it is past its 06:00 IST expiry
+fn render_token_age(
+    ui: &mut egui::Ui,
+    issued_at: Option<chrono::DateTime<chrono::Utc>>,
+    expiry: Option<chrono::DateTime<chrono::Utc>>,
+    expired_color: egui::Color32,
+) {
+    let (Some(issued_at), Some(expiry)) = (issued_at, expiry) else {
+        ui.colored_label(egui::Color32::GRAY, "🔑 ?")
+            .on_hover_text("Access token age unknown until the profile check succeeds");
+        return;
+    };
+
+    let now = chrono::Utc::now();
+    let age = now.signed_duration_since(issued_at);
+    let color = if now >= expiry {
+        expired_color
+    } else {
+        egui::Color32::GRAY
+    };
+    let local = |at: chrono::DateTime<chrono::Utc>| {
+        at.with_timezone(&chrono::Local)
+            .format("%a %H:%M")
+            .to_string()
+    };
+    ui.colored_label(
+        color,
+        format!("🔑 {}h {:02}m", age.num_hours(), age.num_minutes() % 60),
+    )
+    .on_hover_text(format!(
+        "Access token issued {}, {} {}",
+        local(issued_at),
+        if now >= expiry { "expired" } else { "expires" },
+        local(expiry)
+    ));
+}
+
+/// Last minute of ticks per second as a sparkline, with the latest rate and p95 latency
+/// Hovering the sparkline shows the second under the pointer
+fn render_tick_rate(ui: &mut egui::Ui, tick_rate: &TickRateWindow, theme: &Theme) {
+    let seconds = tick_rate.last_minute(chrono::Utc::now());
+    let rates: [f64; TICK_RATE_WINDOW_SECS] = std::array::from_fn(|i| seconds[i].ticks as f64);
+
+    let response =
+        ui::components::sparkline_colored(ui, &rates, egui::vec2(60.0, 14.0), theme.accent);
+    if let Some(pointer) = response.hover_pos() {
+        let fraction = (pointer.x - response.rect.left()) / response.rect.width();
+        let index = ((fraction * (TICK_RATE_WINDOW_SECS - 1) as f32).round() as usize)
+            .min(TICK_RATE_WINDOW_SECS - 1);
+        let second = seconds[index];
+        let time = chrono::DateTime::from_timestamp(second.second, 0)
+            .map(|time| {
+                time.with_timezone(&chrono::Local)
+                    .format("%H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        response.on_hover_text_at_pointer(format!(
+            "{}: {} ticks, {:.1} ms average latency",
+            time,
+            second.ticks,
+            second.average_latency_ms()
+        ));
+    }
+
+    ui.label(format!(
+        "{} ticks/s",
+        seconds[TICK_RATE_WINDOW_SECS - 1].ticks
+    ));
+    if let Some(p95) = TickRateWindow::p95_latency_ms(&seconds) {
+        ui.label(format!("p95 {:.1}ms", p95));
+    }
+}
+
+/// Master mute plus a menu of per-event toggles; toggles last until config.toml reloads
+fn render_sound_controls(ui: &mut egui::Ui, sounds: &mut SoundPlayer, config: &mut AppConfig) {
+    ui.menu_button("⏷", |ui| {
+        for event in SoundEvent::ALL {
+            ui.checkbox(event.toggle_in(config), event.label());
+        }
+    });
+
+    let (icon, hint) = if sounds.muted {
+        ("🔇", "Unmute sounds")
+    } else {
+        ("🔊", "Mute sounds")
+    };
+    if ui.small_button(icon).on_hover_text(hint).clicked() {
+        sounds.muted = !sounds.muted;
+    }
+}
+
+/// Toggle that stops all order actions, shown in red while it is on
+fn render_kill_switch(ui: &mut egui::Ui, app_state: &mut AppState) {
+    let text = if app_state.kill_switch {
+        egui::RichText::new("🛑 Trading halted").color(app_state.theme.loss)
+    } else {
+        egui::RichText::new("🛑 Kill switch")
+    };
+    let response = ui
+        .toggle_value(&mut app_state.kill_switch, text)
+        .on_hover_text("Block placing, modifying and cancelling orders");
+    if response.changed() {
+        let message = if app_state.kill_switch {
+            "Kill switch on: order actions are blocked"
+        } else {
+            "Kill switch off: order actions are allowed again"
+        };
+        app_state.add_log(
+            LogLevel::Warning,
+            message.to_string(),
+            Some("orders".to_string()),
+        );
+    }
+}
+
+/// Settings menu for scale and theme; theme edits take effect on the next frame
+/// Returns true when Reset layout was clicked
+fn render_settings_menu(
+    ui: &mut egui::Ui,
+    theme: &mut Theme,
+    ui_scale: &mut f32,
+    current_view: &mut AppView,
+) -> bool {
+    let mut reset_layout = false;
+    ui.menu_button("⚙", |ui| {
+        if ui
+            .button("🔧 Settings…")
+            .on_hover_text(format!("Edit {}", CONFIG_FILE))
+            .clicked()
+        {
+            *current_view = AppView::Settings;
+            ui.close();
+        }
+
+        ui.separator();
+        // Zooming mid-drag would move the slider under the pointer, so apply on release
+        ui.label("UI scale");
+        let slider = ui.add(
+            egui::Slider::new(ui_scale, UI_SCALE_RANGE)
+                .step_by(0.05)
+                .suffix("×"),
+        );
+        if slider.drag_stopped() || (slider.changed() && !slider.dragged()) {
+            ui.ctx().set_zoom_factor(*ui_scale);
+        }
+
+        ui.separator();
+        ui.label("Theme");
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut theme.mode, ThemeMode::Dark, "🌙 Dark");
+            ui.selectable_value(&mut theme.mode, ThemeMode::Light, "☀ Light");
+        });
+
+        ui.separator();
+        for (label, color) in [
+            ("Accent", &mut theme.accent),
+            ("Profit", &mut theme.profit),
+            ("Loss", &mut theme.loss),
+        ] {
+            ui.horizontal(|ui| {
+                egui::color_picker::color_edit_button_srgba(
+                    ui,
+                    color,
+                    egui::color_picker::Alpha::Opaque,
+                );
+                ui.label(label);
+            });
+        }
+
+        if ui.button("Reset colors").clicked() {
+            *theme = Theme::preset(theme.mode);
+        }
+
+        ui.separator();
+        if ui
+            .button("Reset layout")
+            .on_hover_text("Back to Overview with default filters, sorting and window positions")
+            .clicked()
+        {
+            reset_layout = true;
+            ui.close();
+        }
+    });
+    reset_layout
+}
+
 impl eframe::App for TradingApp {
     /// Main update loop - processes events and renders UI
     /// Optimized for 60+ FPS with minimal allocations
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Process all pending events from worker threads
-        self.app_state.process_events();
+        // Process all pending events from worker threads, timing the drain
+        let depth = self.app_state.pending_events();
+        let started = std::time::Instant::now();
+        let changed = self.app_state.process_events();
+        self.app_state
+            .record_frame_metrics(depth, started.elapsed());
+
+        // Follow Ctrl +/- zooming, except while the scale slider is being dragged
+        if !ctx.is_using_pointer() {
+            self.ui_scale = ctx.zoom_factor();
+        }
+
+        if self.app_state.theme != self.applied_theme {
+            self.app_state.theme.apply(ctx);
+            self.applied_theme = self.app_state.theme;
+        }
+
+        self.update_window_title(ctx);
+        ui::handle_shortcuts(ctx, &mut self.app_state);
 
         // Main application UI - always show since we're bypassing authentication
         egui::TopBottomPanel::top("nav_panel").show(ctx, |ui| {
@@ -157,29 +1022,192 @@ impl eframe::App for TradingApp {
             ui.add_space(5.0);
         });
 
+        if ui::reauth_needed(&self.app_state) {
+            egui::TopBottomPanel::top("reauth_banner")
+                .frame(
+                    egui::Frame::new()
+                        .fill(self.app_state.theme.loss)
+                        .inner_margin(6.0),
+                )
+                .show(ctx, |ui| {
+                    ui::render_reauth_banner(ui, &mut self.app_state);
+                });
+        }
+
         egui::TopBottomPanel::bottom("status_panel").show(ctx, |ui| {
             ui.add_space(3.0);
             self.render_status_bar(ui);
             ui.add_space(3.0);
         });
 
+        if self.app_state.ui_input.selected_instrument_token.is_some() {
+            egui::SidePanel::right("depth_panel")
+                .resizable(false)
+                .min_width(260.0)
+                .show(ctx, |ui| {
+                    ui.add_space(5.0);
+                    ui::render_depth_panel(ui, &mut self.app_state);
+                    ui.add_space(10.0);
+                    ui.separator();
+                    ui::render_tape(ui, &mut self.app_state);
+                });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             self.render_content(ui);
         });
+        self.render_detached_views(ctx);
+
+        // Shortcut-driven dialogs work from every view
+        ui::render_cancel_all_confirmation(ctx, &mut self.app_state);
+        ui::render_shortcut_help(ctx, &mut self.app_state);
+        ui::render_reauth_dialog(ctx, &mut self.app_state);
+        ui::render_critical_error(ctx, &mut self.app_state);
+        ui::render_crash_report(ctx, &mut self.app_state);
+
+        ui::components::render_toasts(ctx, &mut self.app_state.toasts);
 
-        // Request repaint for real-time updates
-        ctx.request_repaint();
+        // Detached windows follow the same cadence, so new data shows in them too
+        let interval = self.repaint_interval(changed);
+        ctx.request_repaint_after(interval);
+        for detached in &self.app_state.ui_input.detached_views {
+            ctx.request_repaint_after_for(interval, detached.viewport_id());
+        }
     }
 
-    /// Handle application shutdown
-    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
-        self.app_state.add_log(
-            LogLevel::Info,
-            "Application shutting down".to_string(),
-            Some("app".to_string()),
+    /// Remember the theme, UI scale, view and filters for the next launch
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, THEME_STORAGE_KEY, &self.app_state.theme);
+        eframe::set_value(storage, UI_SCALE_STORAGE_KEY, &self.ui_scale);
+        eframe::set_value(
+            storage,
+            UI_STATE_STORAGE_KEY,
+            &SavedUiState::capture(self.current_view, &self.app_state.ui_input),
         );
+    }
+
+    /// eframe calls this synchronously, so waiting for the workers blocks the UI thread,
+    /// bounded by shutdown_timeout_secs
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        let workers = std::mem::take(&mut self.workers);
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(shutdown(&mut self.app_state, workers))
+        });
+    }
+}
+
+/// Stop the workers in order: queue Shutdown behind their pending commands, wait up to
+/// `shutdown_timeout_secs` for each to finish, let their last events reach the journal and
+/// flush it, then abort whatever is still running
+pub async fn shutdown(app_state: &mut AppState, mut workers: Vec<WorkerHandle>) {
+    let timeout = Duration::from_secs(app_state.config.app.shutdown_timeout_secs);
+    log::info!(
+        "Shutting down: stopping workers (waiting up to {} s)",
+        timeout.as_secs()
+    );
+    app_state.add_log(
+        LogLevel::Info,
+        "Application shutting down".to_string(),
+        Some("app".to_string()),
+    );
 
-        // Send shutdown command to workers
-        self.app_state.send_command(crate::state::Command::Shutdown);
+    // Workers finish the commands queued ahead of Shutdown; nothing is queued after it
+    app_state.send_command(Command::Shutdown);
+    app_state.shutting_down = true;
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    for handle in &mut workers {
+        let finished = tokio::time::timeout_at(deadline, &mut handle.stopped)
+            .await
+            .is_ok_and(|ack| ack.is_ok());
+        if finished {
+            log::info!("Shutting down: {} stopped", handle.worker.label());
+        } else {
+            log::warn!(
+                "Shutting down: {} did not stop within {} s, aborting it",
+                handle.worker.label(),
+                timeout.as_secs()
+            );
+        }
+    }
+
+    // Order updates the workers sent before stopping still reach the journal
+    app_state.process_events();
+    if let Some(journal) = &app_state.journal {
+        log::info!("Shutting down: flushing the order journal");
+        if !journal.flush(timeout) {
+            log::warn!(
+                "Shutting down: the order journal did not flush within {} s",
+                timeout.as_secs()
+            );
+        }
+    }
+    if let Some(history) = &app_state.history {
+        log::info!("Shutting down: flushing the history store");
+        if !history.flush(timeout) {
+            log::warn!(
+                "Shutting down: the history store did not flush within {} s",
+                timeout.as_secs()
+            );
+        }
+    }
+
+    for handle in workers {
+        handle.supervisor.abort();
+    }
+    log::info!("Shutdown complete");
+}
+
+/// Shown instead of the dashboard when config.toml is missing or invalid
+/// Workers are never started, so nothing talks to the broker with a bad config
+pub struct SetupScreen {
+    report: ConfigReport,
+}
+
+impl SetupScreen {
+    pub fn new(report: ConfigReport) -> Self {
+        for problem in &report.problems {
+            log::error!("Configuration problem: {}", problem);
+        }
+
+        Self { report }
+    }
+}
+
+impl eframe::App for SetupScreen {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(40.0);
+                ui.label(egui::RichText::new("⚙ Setup Required").size(28.0).strong());
+                ui.add_space(10.0);
+                ui.label("The dashboard could not start because of problems in its configuration.");
+            });
+
+            ui.add_space(20.0);
+
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Config file:").strong());
+                    ui.monospace(self.report.path.display().to_string());
+                    if ui.small_button("📋 Copy").clicked() {
+                        ui.ctx().copy_text(self.report.path.display().to_string());
+                    }
+                });
+
+                ui.add_space(10.0);
+
+                for problem in &self.report.problems {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.colored_label(egui::Color32::from_rgb(239, 68, 68), "✖");
+                        ui.label(problem);
+                    });
+                }
+            });
+
+            ui.add_space(20.0);
+            ui.label("Fix the problems above, then restart the dashboard.");
+            ui.label("See the Configuration section of README.md for a complete example.");
+        });
     }
 }
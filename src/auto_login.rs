@@ -0,0 +1,459 @@
+// src/auto_login.rs - Headless Kite login with a TOTP second factor, for auth_helper
+// Built into the auth_helper binary only; the dashboard never handles the password
+// or TOTP secret. Neither is logged, and errors never echo them back.
+use hmac::{Hmac, Mac};
+use reqwest::header::{COOKIE, LOCATION, SET_COOKIE};
+use reqwest::{Client, Response, StatusCode};
+use serde::Deserialize;
+use sha1::Sha1;
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LOGIN_URL: &str = "https://kite.zerodha.com/api/login";
+const TWOFA_URL: &str = "https://kite.zerodha.com/api/twofa";
+const CONNECT_URL: &str = "https://kite.zerodha.com/connect/login";
+
+/// Redirects followed from the connect page before giving up on a request_token
+const MAX_REDIRECTS: usize = 10;
+
+/// TOTP step and length used by Kite, as in RFC 6238's defaults
+const TOTP_STEP_SECS: u64 = 30;
+const TOTP_DIGITS: u32 = 6;
+
+/// The [auto_login] section of config.toml, or [profiles.<name>.auto_login] for another
+/// account; headless login runs only when `enabled`
+/// Secrets are never stored here: each is read from the environment, from the OS keyring
+/// entry for `user_id` under the `*_keyring` service, or from what a command prints
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AutoLoginConfig {
+    pub enabled: bool,
+    pub user_id: String,
+    pub password_keyring: Option<String>,
+    pub totp_secret_keyring: Option<String>,
+    pub password_command: Option<String>,
+    pub totp_secret_command: Option<String>,
+}
+
+/// Environment variables that take precedence over the configured commands, named after
+/// the account's prefix: ZERODHA_PASSWORD, or ZERODHA_<PROFILE>_PASSWORD for a profile
+const PASSWORD_ENV: &str = "PASSWORD";
+const TOTP_SECRET_ENV: &str = "TOTP_SECRET";
+
+#[derive(Debug, thiserror::Error)]
+pub enum AutoLoginError {
+    #[error("auto_login.user_id is not set")]
+    MissingUserId,
+    #[error("no {what}: set {env}, auto_login.{keyring_key} or auto_login.{command_key}")]
+    MissingSecret {
+        what: &'static str,
+        env: String,
+        keyring_key: &'static str,
+        command_key: &'static str,
+    },
+    #[error("auto_login.{key} failed: {reason}")]
+    Keyring { key: &'static str, reason: String },
+    #[error("auto_login.{key} failed: {reason}")]
+    SecretCommand { key: &'static str, reason: String },
+    #[error("the TOTP secret is not valid base32")]
+    InvalidTotpSecret,
+    #[error("Kite asked for a captcha; log in once in the browser (auth_helper --interactive), then retry")]
+    Captcha,
+    #[error("Kite blocked the login: {0}")]
+    Blocked(String),
+    #[error("login rejected: {0}")]
+    LoginRejected(String),
+    #[error("TOTP rejected: {0} (check the secret and this machine's clock)")]
+    TwoFactorRejected(String),
+    #[error("Kite did not redirect back with a request_token; the app may need to be authorized once in the browser (auth_helper --interactive)")]
+    NoRequestToken,
+    #[error("Kite redirected back with status \"{0}\"")]
+    RedirectFailed(String),
+    #[error("network error: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+/// A password or TOTP secret; Debug never shows it
+pub struct Secret(String);
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Secret(***)")
+    }
+}
+
+/// Everything a headless login needs, resolved from the config and environment
+#[derive(Debug)]
+pub struct Credentials {
+    pub user_id: String,
+    password: Secret,
+    totp_secret: Secret,
+}
+
+impl Credentials {
+    /// `env_prefix` namespaces the secret variables, e.g. "ZERODHA_" or "ZERODHA_ALT_"
+    pub fn resolve(config: &AutoLoginConfig, env_prefix: &str) -> Result<Self, AutoLoginError> {
+        if config.user_id.trim().is_empty() {
+            return Err(AutoLoginError::MissingUserId);
+        }
+        let user_id = config.user_id.trim().to_string();
+        let password = read_secret(
+            "password",
+            format!("{}{}", env_prefix, PASSWORD_ENV),
+            ("password_keyring", config.password_keyring.as_deref()),
+            ("password_command", config.password_command.as_deref()),
+            &user_id,
+        )?;
+        let totp_secret = read_secret(
+            "TOTP secret",
+            format!("{}{}", env_prefix, TOTP_SECRET_ENV),
+            ("totp_secret_keyring", config.totp_secret_keyring.as_deref()),
+            ("totp_secret_command", config.totp_secret_command.as_deref()),
+            &user_id,
+        )?;
+
+        Ok(Self {
+            user_id,
+            password,
+            totp_secret,
+        })
+    }
+}
+
+/// Secret from its environment variable, else from `user_id`'s entry in the keyring
+/// service, else from the first line its command prints
+/// `keyring` and `command` pair each config key with its value
+fn read_secret(
+    what: &'static str,
+    env: String,
+    keyring: (&'static str, Option<&str>),
+    command: (&'static str, Option<&str>),
+    user_id: &str,
+) -> Result<Secret, AutoLoginError> {
+    if let Ok(value) = std::env::var(&env) {
+        if !value.is_empty() {
+            return Ok(Secret(value));
+        }
+    }
+
+    let (keyring_key, service) = keyring;
+    if let Some(service) = service.filter(|service| !service.trim().is_empty()) {
+        return read_keyring(keyring_key, service.trim(), user_id);
+    }
+
+    let (key, command) = command;
+    let command = command.filter(|command| !command.trim().is_empty()).ok_or(
+        AutoLoginError::MissingSecret {
+            what,
+            env,
+            keyring_key,
+            command_key: key,
+        },
+    )?;
+
+    let mut shell = if cfg!(target_os = "windows") {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    // Only the exit status is reported; the command's output may be the secret itself
+    let output = shell
+        .arg(command)
+        .output()
+        .map_err(|e| AutoLoginError::SecretCommand {
+            key,
+            reason: e.to_string(),
+        })?;
+    if !output.status.success() {
+        return Err(AutoLoginError::SecretCommand {
+            key,
+            reason: format!("exited with {}", output.status),
+        });
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    if value.is_empty() {
+        return Err(AutoLoginError::SecretCommand {
+            key,
+            reason: "printed nothing".to_string(),
+        });
+    }
+    Ok(Secret(value))
+}
+
+/// Secret stored for `user_id` under `service` in the OS keyring, e.g. by
+/// `secret-tool store --label kite service kite username AB1234` or the macOS Keychain
+fn read_keyring(key: &'static str, service: &str, user_id: &str) -> Result<Secret, AutoLoginError> {
+    let keyring_error = |e: keyring::Error| AutoLoginError::Keyring {
+        key,
+        reason: match e {
+            keyring::Error::NoEntry => format!("no entry for {} in \"{}\"", user_id, service),
+            e => e.to_string(),
+        },
+    };
+    let value = keyring::Entry::new(service, user_id)
+        .and_then(|entry| entry.get_password())
+        .map_err(keyring_error)?;
+    if value.trim().is_empty() {
+        return Err(AutoLoginError::Keyring {
+            key,
+            reason: format!("the entry for {} in \"{}\" is empty", user_id, service),
+        });
+    }
+    Ok(Secret(value.trim().to_string()))
+}
+
+/// Log in with password and TOTP, then follow the connect flow to the app's redirect,
+/// returning the request_token it carries without requesting the redirect URL itself
+pub async fn fetch_request_token(
+    api_key: &str,
+    credentials: &Credentials,
+) -> Result<String, AutoLoginError> {
+    let client = Client::builder()
+        .user_agent("ZerodhaAuthHelper/1.0")
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+    let mut cookies = CookieJar::default();
+
+    let response = client
+        .post(LOGIN_URL)
+        .form(&[
+            ("user_id", credentials.user_id.as_str()),
+            ("password", credentials.password.0.as_str()),
+        ])
+        .send()
+        .await?;
+    cookies.store(&response);
+    let login = read_kite_response(response)
+        .await?
+        .map_err(AutoLoginError::LoginRejected)?;
+    let request_id = login
+        .get("request_id")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| AutoLoginError::LoginRejected("no request_id in the response".to_string()))?
+        .to_string();
+
+    let code = totp(&credentials.totp_secret.0, unix_time())?;
+    let response = client
+        .post(TWOFA_URL)
+        .header(COOKIE, cookies.header())
+        .form(&[
+            ("user_id", credentials.user_id.as_str()),
+            ("request_id", request_id.as_str()),
+            ("twofa_value", code.as_str()),
+            ("twofa_type", "totp"),
+        ])
+        .send()
+        .await?;
+    cookies.store(&response);
+    read_kite_response(response)
+        .await?
+        .map_err(AutoLoginError::TwoFactorRejected)?;
+
+    let mut url = url::Url::parse_with_params(CONNECT_URL, &[("v", "3"), ("api_key", api_key)])
+        .expect("valid connect URL");
+    for _ in 0..MAX_REDIRECTS {
+        let response = client
+            .get(url.clone())
+            .header(COOKIE, cookies.header())
+            .send()
+            .await?;
+        cookies.store(&response);
+        if !response.status().is_redirection() {
+            break;
+        }
+        let Some(location) = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|location| url.join(location).ok())
+        else {
+            break;
+        };
+
+        let param = |name: &str| {
+            location
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        if let Some(request_token) = param("request_token") {
+            return match param("status").as_deref() {
+                None | Some("success") => Ok(request_token),
+                Some(status) => Err(AutoLoginError::RedirectFailed(status.to_string())),
+            };
+        }
+        url = location;
+    }
+
+    Err(AutoLoginError::NoRequestToken)
+}
+
+/// Body of a kite.zerodha.com API answer: data on success, the message on a plain error
+/// Captcha and lockout answers become their own errors
+async fn read_kite_response(
+    response: Response,
+) -> Result<Result<serde_json::Value, String>, AutoLoginError> {
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    let message = body["message"].as_str().unwrap_or_default().to_string();
+
+    if message.to_lowercase().contains("captcha") || !body["data"]["captcha"].is_null() {
+        return Err(AutoLoginError::Captcha);
+    }
+    if status == StatusCode::TOO_MANY_REQUESTS
+        || message.to_lowercase().contains("blocked")
+        || message.to_lowercase().contains("locked")
+    {
+        let reason = if message.is_empty() {
+            status.to_string()
+        } else {
+            message
+        };
+        return Err(AutoLoginError::Blocked(reason));
+    }
+    if status.is_success() && body["status"] == "success" {
+        return Ok(Ok(body["data"].clone()));
+    }
+
+    Ok(Err(if message.is_empty() {
+        format!("HTTP {}", status)
+    } else {
+        message
+    }))
+}
+
+/// Session cookies from kite.zerodha.com, sent back on every later request
+/// Every request goes to the one host, so domains and paths are not tracked
+#[derive(Default)]
+struct CookieJar(BTreeMap<String, String>);
+
+impl CookieJar {
+    fn store(&mut self, response: &Response) {
+        for header in response.headers().get_all(SET_COOKIE) {
+            let Ok(header) = header.to_str() else {
+                continue;
+            };
+            let pair = header.split(';').next().unwrap_or_default();
+            if let Some((name, value)) = pair.split_once('=') {
+                self.0
+                    .insert(name.trim().to_string(), value.trim().to_string());
+            }
+        }
+    }
+
+    fn header(&self) -> String {
+        self.0
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// RFC 6238 code for a base32 secret, as authenticator apps show it
+fn totp(secret: &str, unix_time: u64) -> Result<String, AutoLoginError> {
+    let normalized: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    let key = data_encoding::BASE32_NOPAD
+        .decode(normalized.as_bytes())
+        .map_err(|_| AutoLoginError::InvalidTotpSecret)?;
+
+    let counter = (unix_time / TOTP_STEP_SECS).to_be_bytes();
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key).expect("HMAC takes keys of any length");
+    mac.update(&counter);
+    let mac = mac.finalize().into_bytes();
+
+    // Dynamic truncation (RFC 4226 §5.3)
+    let offset = (mac[19] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        mac[offset] & 0x7f,
+        mac[offset + 1],
+        mac[offset + 2],
+        mac[offset + 3],
+    ]);
+    Ok(format!(
+        "{:0width$}",
+        binary % 10u32.pow(TOTP_DIGITS),
+        width = TOTP_DIGITS as usize
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The RFC 6238 SHA-1 seed, "12345678901234567890", in base32
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn totp_matches_the_rfc_6238_sha1_vectors() {
+        // Appendix B lists 8 digits; Kite's 6-digit codes are their last six
+        let vectors = [
+            (59, "94287082"),
+            (1_111_111_109, "07081804"),
+            (1_111_111_111, "14050471"),
+            (1_234_567_890, "89005924"),
+            (2_000_000_000, "69279037"),
+            (20_000_000_000, "65353130"),
+        ];
+        for (unix_time, expected) in vectors {
+            assert_eq!(
+                totp(RFC_SECRET, unix_time).unwrap(),
+                expected[2..],
+                "T = {}",
+                unix_time
+            );
+        }
+    }
+
+    #[test]
+    fn totp_accepts_secrets_as_authenticator_apps_show_them() {
+        let shown = "gezd gnbv gy3t qojq gezd gnbv gy3t qojq====";
+        assert_eq!(totp(shown, 59).unwrap(), "287082");
+    }
+
+    #[test]
+    fn totp_rejects_secrets_that_are_not_base32() {
+        assert!(matches!(
+            totp("not base32!", 59),
+            Err(AutoLoginError::InvalidTotpSecret)
+        ));
+    }
+
+    #[test]
+    fn missing_secrets_name_every_source() {
+        let error = read_secret(
+            "password",
+            "ZERODHA_TEST_UNSET_PASSWORD".to_string(),
+            ("password_keyring", None),
+            ("password_command", Some(" ")),
+            "AB1234",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "no password: set ZERODHA_TEST_UNSET_PASSWORD, auto_login.password_keyring or auto_login.password_command"
+        );
+    }
+}
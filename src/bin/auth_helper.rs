@@ -1,19 +1,237 @@
 // src/bin/auth_helper.rs - Standalone Zerodha authentication helper
-use anyhow::Result;
+#[path = "../api/auth_callback.rs"]
+mod auth_callback;
+#[path = "../auto_login.rs"]
+mod auto_login;
+#[path = "../config_writer.rs"]
+#[allow(dead_code)] // the dashboard also dates tokens it finds undated
+mod config_writer;
+#[path = "../redact.rs"]
+mod redact;
+
+use auth_callback::{CallbackError, CallbackListener};
+use auto_login::{AutoLoginConfig, AutoLoginError, Credentials};
+use chrono::{Duration, TimeZone, Utc};
+use chrono_tz::Asia::Kolkata;
+use clap::Parser;
 use reqwest::Client;
-use serde_json::Value;
+use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::OnceLock;
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// Printed after the options in --help
+const AFTER_HELP: &str =
+    "Without --profile, an interactive run with [profiles] configured asks which account
+to use. Environment overrides are ZERODHA_API_KEY etc., or ZERODHA_<PROFILE>_API_KEY.
+
+Exit codes: 0 success, 1 other failure, 2 bad arguments, 3 invalid credentials
+or access token, 4 invalid request_token, 5 network failure";
+
+/// Why a run failed, chosen so a cron job can tell the cases apart by exit code
+#[derive(Debug, thiserror::Error)]
+enum AuthError {
+    #[error("{0}")]
+    Usage(String),
+    #[error("invalid credentials: {0}")]
+    InvalidCredentials(String),
+    #[error("invalid request token: {0}")]
+    InvalidRequestToken(String),
+    #[error("network failure: {0}")]
+    Network(String),
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl AuthError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            AuthError::Failed(_) => 1,
+            AuthError::Usage(_) => 2,
+            AuthError::InvalidCredentials(_) => 3,
+            AuthError::InvalidRequestToken(_) => 4,
+            AuthError::Network(_) => 5,
+        }
+    }
+}
+
+impl From<reqwest::Error> for AuthError {
+    fn from(e: reqwest::Error) -> Self {
+        AuthError::Network(e.to_string())
+    }
+}
+
+impl From<io::Error> for AuthError {
+    fn from(e: io::Error) -> Self {
+        AuthError::Failed(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AuthError {
+    fn from(e: serde_json::Error) -> Self {
+        AuthError::Failed(format!("unexpected response: {}", e))
+    }
+}
+
+impl From<AutoLoginError> for AuthError {
+    fn from(e: AutoLoginError) -> Self {
+        match e {
+            AutoLoginError::Http(e) => AuthError::Network(e.to_string()),
+            AutoLoginError::MissingUserId
+            | AutoLoginError::MissingSecret { .. }
+            | AutoLoginError::Keyring { .. }
+            | AutoLoginError::SecretCommand { .. }
+            | AutoLoginError::InvalidTotpSecret
+            | AutoLoginError::LoginRejected(_)
+            | AutoLoginError::TwoFactorRejected(_) => {
+                AuthError::InvalidCredentials(format!("headless login: {}", e))
+            }
+            _ => AuthError::Failed(format!("headless login: {}", e)),
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, AuthError>;
+
+/// Where progress messages go: stdout normally, stderr when --json owns stdout,
+/// nowhere with --quiet
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Progress {
+    Stdout,
+    Stderr,
+    Off,
+}
+
+static PROGRESS: OnceLock<Progress> = OnceLock::new();
+
+fn progress() -> Progress {
+    *PROGRESS.get().unwrap_or(&Progress::Stdout)
+}
+
+macro_rules! say {
+    ($($arg:tt)*) => {
+        match progress() {
+            Progress::Stdout => println!($($arg)*),
+            Progress::Stderr => eprintln!($($arg)*),
+            Progress::Off => {}
+        }
+    };
+}
+
+/// Ask for a line on stdin; the question shows even with --quiet, on stderr
+fn prompt(question: &str) -> Result<String> {
+    if progress() == Progress::Stdout {
+        print!("{}", question);
+        io::stdout().flush()?;
+    } else {
+        eprint!("{}", question);
+    }
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+/// Whether a new token is written to config.toml
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WriteConfig {
+    // Write if possible, otherwise say how to paste it by hand
+    Default,
+    // --write-config: failing to write fails the run
+    Required,
+    // --no-write
+    Never,
+}
+
+/// Standalone Zerodha authentication helper
+// clap exits with code 2 on bad arguments, matching AuthError::Usage
+#[derive(Debug, Parser)]
+#[command(name = "auth_helper", after_help = AFTER_HELP)]
+struct Options {
+    /// Use the [profiles.<name>] account instead of [zerodha]
+    #[arg(long, value_name = "name", value_parser = non_empty)]
+    profile: Option<String>,
+    /// Exchange this request_token instead of logging in
+    #[arg(long, value_name = "t", value_parser = non_empty, conflicts_with = "test_only")]
+    request_token: Option<String>,
+    /// Check the configured access token and exit
+    #[arg(long)]
+    test_only: bool,
+    /// Fail unless the new token is saved to config.toml
+    #[arg(long, conflicts_with = "no_write")]
+    write_config: bool,
+    /// Print the new access token instead of saving it to config.toml
+    #[arg(long)]
+    no_write: bool,
+    /// Log in through the browser even when [auto_login] is enabled
+    #[arg(long)]
+    interactive: bool,
+    /// Print the result as JSON on stdout; progress goes to stderr
+    #[arg(long)]
+    json: bool,
+    /// Print nothing but the result and errors
+    #[arg(long)]
+    quiet: bool,
+}
+
+impl Options {
+    fn write_config(&self) -> WriteConfig {
+        if self.write_config {
+            WriteConfig::Required
+        } else if self.no_write {
+            WriteConfig::Never
+        } else {
+            WriteConfig::Default
+        }
+    }
+}
+
+/// Trimmed value of an option that needs one
+fn non_empty(value: &str) -> std::result::Result<String, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("needs a value".to_string());
+    }
+    Ok(value.to_string())
+}
+
+/// Account behind an access token, from the profile endpoint
+struct Profile {
+    user_id: String,
+}
+
+/// Kite access tokens lapse at 6 AM IST the next morning; the next such instant
+fn expires_hint() -> String {
+    let now = Utc::now().with_timezone(&Kolkata);
+    let six = now
+        .date_naive()
+        .and_hms_opt(6, 0, 0)
+        .expect("valid time of day");
+    let expiry = if now.naive_local() < six {
+        six
+    } else {
+        six + Duration::days(1)
+    };
+    Kolkata
+        .from_local_datetime(&expiry)
+        .single()
+        .map(|expiry| expiry.to_rfc3339())
+        .unwrap_or_default()
+}
 
 pub struct AuthHelper {
     client: Client,
     api_key: String,
     api_secret: String,
+    callback_port: u16,
 }
 
 impl AuthHelper {
-    pub fn new(api_key: String, api_secret: String) -> Self {
+    pub fn new(api_key: String, api_secret: String, callback_port: u16) -> Self {
         let client = Client::builder()
             .user_agent("ZerodhaAuthHelper/1.0")
             .build()
@@ -23,6 +241,7 @@ impl AuthHelper {
             client,
             api_key,
             api_secret,
+            callback_port,
         }
     }
 
@@ -35,62 +254,107 @@ impl AuthHelper {
         hex::encode(result)
     }
 
-    /// Interactive authentication flow
-    pub async fn interactive_auth(&self) -> Result<String> {
-        println!("🚀 Zerodha KiteConnect Authentication Helper");
-        println!("============================================");
-        println!();
-        println!("API Key: {}", self.api_key);
-        println!();
+    /// Interactive authentication flow; returns the request_token to exchange
+    async fn interactive_auth(&self) -> Result<String> {
+        say!("🚀 Zerodha KiteConnect Authentication Helper");
+        say!("============================================");
+        say!();
+        say!("API Key: {}", redact::mask(&self.api_key));
+        say!();
 
         // Generate login URL
         let login_url = format!(
             "https://kite.zerodha.com/connect/login?v=3&api_key={}",
             self.api_key
         );
-        println!("📋 STEP 1: Open this URL in your browser:");
-        println!("{}", login_url);
-        println!();
+        match self.capture_request_token(&login_url).await {
+            Ok(Some(request_token)) => Ok(request_token),
+            Ok(None) => self.prompt_request_token(&login_url),
+            Err(CallbackError::LoginFailed(reason)) => Err(AuthError::InvalidCredentials(format!(
+                "Kite login did not complete ({})",
+                reason
+            ))),
+            Err(e) => Err(AuthError::Failed(format!("login failed: {}", e))),
+        }
+    }
 
-        println!("📋 STEP 2: Complete the login process");
-        println!("   - Enter your Zerodha credentials");
-        println!("   - Complete 2FA if enabled");
-        println!("   - You'll be redirected to a URL with request_token");
-        println!();
+    /// Log in without a browser using the [auto_login] credentials and a TOTP code;
+    /// returns the request_token to exchange
+    async fn headless_auth(&self, credentials: &Credentials) -> Result<String> {
+        say!(
+            "🤖 Logging in to Kite as {} with TOTP...",
+            credentials.user_id
+        );
+        Ok(auto_login::fetch_request_token(&self.api_key, credentials).await?)
+    }
 
-        print!("📋 STEP 3: Paste the request_token from the redirect URL: ");
-        io::stdout().flush()?;
+    /// Catch the login redirect on the callback port, opening the login page in the browser
+    /// None when the listener is off or the port is taken, so the token has to be pasted
+    async fn capture_request_token(
+        &self,
+        login_url: &str,
+    ) -> std::result::Result<Option<String>, CallbackError> {
+        if self.callback_port == 0 {
+            return Ok(None);
+        }
 
-        let mut request_token = String::new();
-        io::stdin().read_line(&mut request_token)?;
-        let request_token = request_token.trim();
+        let listener = match CallbackListener::bind(self.callback_port).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                say!("⚠️  {}", e);
+                say!("   Falling back to pasting the request_token by hand");
+                say!();
+                return Ok(None);
+            }
+        };
 
-        if request_token.is_empty() {
-            anyhow::bail!("Request token cannot be empty");
+        say!("📋 Log in to Kite in the browser window that opens");
+        if !auth_callback::open_in_browser(login_url) {
+            say!("   Could not open a browser; open this URL yourself:");
         }
+        say!("   {}", login_url);
+        say!(
+            "   Waiting up to {} minutes for the redirect to {}",
+            auth_callback::LOGIN_TIMEOUT.as_secs() / 60,
+            auth_callback::redirect_url(self.callback_port)
+        );
+        say!("   (it must be the redirect URL registered for your Kite app)");
+        say!();
 
-        println!();
-        println!("🔐 Generating access token...");
+        let request_token = listener
+            .wait_for_request_token(auth_callback::LOGIN_TIMEOUT)
+            .await?;
+        say!("📥 Received request_token from the login redirect");
+        Ok(Some(request_token))
+    }
+
+    /// Manual flow: print the login URL and read the request_token from stdin
+    fn prompt_request_token(&self, login_url: &str) -> Result<String> {
+        say!("📋 STEP 1: Open this URL in your browser:");
+        say!("{}", login_url);
+        say!();
 
-        // Generate access token
-        let access_token = self.generate_session(request_token).await?;
+        say!("📋 STEP 2: Complete the login process");
+        say!("   - Enter your Zerodha credentials");
+        say!("   - Complete 2FA if enabled");
+        say!("   - You'll be redirected to a URL with request_token");
+        say!();
 
-        println!("✅ Authentication successful!");
-        println!("🔑 Access Token: {}", access_token);
-        println!();
-        println!("📝 Copy this token to your config.toml file:");
-        println!("   access_token = \"{}\"", access_token);
-        println!();
-        println!("⚠️  Security Notes:");
-        println!("   - Keep this token secure");
-        println!("   - Token expires daily - you'll need to regenerate it");
-        println!("   - Don't commit this token to version control");
+        let request_token = prompt("📋 STEP 3: Paste the request_token from the redirect URL: ")?;
+        if request_token.is_empty() {
+            return Err(AuthError::InvalidRequestToken(
+                "request token cannot be empty".to_string(),
+            ));
+        }
 
-        Ok(access_token)
+        Ok(request_token)
     }
 
     /// Generate session using request token
     async fn generate_session(&self, request_token: &str) -> Result<String> {
+        say!();
+        say!("🔐 Generating access token...");
+
         let checksum = self.generate_checksum(request_token);
         let url = "https://api.kite.trade/session/token";
 
@@ -99,9 +363,9 @@ impl AuthHelper {
         params.insert("request_token", request_token);
         params.insert("checksum", checksum.as_str());
 
-        println!("   📡 API Key: {}", self.api_key);
-        println!("   📡 Request Token: {}", request_token);
-        println!("   📡 Checksum: {}", checksum);
+        say!("   📡 API Key: {}", redact::mask(&self.api_key));
+        say!("   📡 Request Token: {}", redact::mask(request_token));
+        say!("   📡 Checksum: {}", redact::mask(&checksum));
 
         let response = self
             .client
@@ -114,30 +378,50 @@ impl AuthHelper {
         let status_code = response.status();
         let response_text = response.text().await?;
 
-        println!("   📥 Response Status: {}", status_code);
-
-        if !status_code.is_success() {
-            println!("   ❌ Response Body: {}", response_text);
-            anyhow::bail!("Authentication failed with status: {}", status_code);
-        }
-
-        let json_response: Value = serde_json::from_str(&response_text)?;
+        say!("   📥 Response Status: {}", status_code);
 
-        if json_response["status"] == "success" {
+        let json_response: Value = serde_json::from_str(&response_text).unwrap_or_default();
+        if status_code.is_success() && json_response["status"] == "success" {
             let access_token = json_response["data"]["access_token"]
                 .as_str()
-                .ok_or_else(|| anyhow::anyhow!("Access token not found in response"))?;
-
-            Ok(access_token.to_string())
-        } else {
-            let error_msg = json_response["message"].as_str().unwrap_or("Unknown error");
-            anyhow::bail!("API Error: {}", error_msg);
+                .ok_or_else(|| AuthError::Failed("access token not found in response".into()))?;
+
+            say!("✅ Authentication successful!");
+            say!("🔑 Access Token: {}", redact::mask(access_token));
+            say!();
+            say!("⚠️  Security Notes:");
+            say!("   - Keep this token secure");
+            say!("   - Token expires daily - you'll need to regenerate it");
+            say!("   - Don't commit this token to version control");
+            return Ok(access_token.to_string());
         }
+
+        say!(
+            "   ❌ Response Body: {}",
+            redact::redact_params(&response_text)
+        );
+        let message = json_response["message"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("status {}", status_code));
+
+        // A bad checksum or API key means the configured credentials are wrong; anything
+        // else Kite rejects here is the request_token, which is single-use and short-lived
+        let lowered = message.to_lowercase();
+        Err(
+            if lowered.contains("checksum") || lowered.contains("api_key") {
+                AuthError::InvalidCredentials(message)
+            } else if status_code.is_client_error() {
+                AuthError::InvalidRequestToken(message)
+            } else {
+                AuthError::Failed(format!("session exchange failed: {}", message))
+            },
+        )
     }
 
     /// Test access token validity
-    pub async fn test_token(&self, access_token: &str) -> Result<()> {
-        println!("🧪 Testing access token validity...");
+    async fn test_token(&self, access_token: &str) -> Result<Profile> {
+        say!("🧪 Testing access token validity...");
 
         let url = "https://api.kite.trade/user/profile";
 
@@ -157,113 +441,416 @@ impl AuthHelper {
 
         if status_code == 200 {
             let profile: Value = serde_json::from_str(&response_text)?;
-            println!("✅ Token is valid!");
-            println!(
+            say!("✅ Token is valid!");
+            say!(
                 "   User: {}",
                 profile["data"]["user_name"].as_str().unwrap_or("Unknown")
             );
-            println!(
+            say!(
                 "   Email: {}",
                 profile["data"]["email"].as_str().unwrap_or("Unknown")
             );
-            Ok(())
+            Ok(Profile {
+                user_id: profile["data"]["user_id"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            })
         } else if status_code == 403 {
-            println!("❌ Token is invalid or expired (403 Forbidden)");
-            anyhow::bail!("Invalid access token");
+            say!("❌ Token is invalid or expired (403 Forbidden)");
+            Err(AuthError::InvalidCredentials(
+                "access token is invalid or expired".to_string(),
+            ))
         } else {
-            println!("⚠️  Unexpected response: {}", response_text);
-            anyhow::bail!("Token test failed with status: {}", status_code);
+            say!("⚠️  Unexpected response: {}", response_text);
+            Err(AuthError::Failed(format!(
+                "token test failed with status: {}",
+                status_code
+            )))
         }
     }
 }
 
-// Standalone binary for authentication
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Read API credentials from environment or config file or prompt
-    let api_key = std::env::var("ZERODHA_API_KEY")
-        .or_else(|_| {
-            // Try to read from config.toml
-            if let Ok(config_str) = std::fs::read_to_string("config.toml") {
-                if let Ok(config) = toml::from_str::<toml::Value>(&config_str) {
-                    if let Some(api_key) = config
-                        .get("zerodha")
-                        .and_then(|z| z.get("api_key"))
-                        .and_then(|k| k.as_str())
-                    {
-                        return Ok(api_key.to_string());
-                    }
-                }
+/// Put the new token into the account's section of config.toml in place, or say how to
+/// do it by hand; returns whether the file was updated
+fn save_access_token(
+    account: &Account,
+    access_token: &str,
+    write_config: WriteConfig,
+) -> Result<bool> {
+    let path = Path::new(CONFIG_FILE);
+    let problem = match write_config {
+        WriteConfig::Never => None,
+        _ if !path.exists() => Some(format!(
+            "{} not found in the current directory",
+            CONFIG_FILE
+        )),
+        _ => match config_writer::write_access_token(
+            path,
+            &account.section_path(),
+            access_token,
+            Utc::now(),
+        ) {
+            Ok(backup) => {
+                let shown = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+                say!(
+                    "📝 Updated [{}] access_token in {}",
+                    account.section_path().join("."),
+                    shown.display()
+                );
+                say!("   Previous file backed up to {}", backup.display());
+                return Ok(true);
             }
-            Err(std::env::VarError::NotPresent)
-        })
-        .unwrap_or_else(|_| {
-            print!("Enter your Zerodha API Key: ");
-            io::stdout().flush().unwrap();
-            let mut input = String::new();
-            io::stdin().read_line(&mut input).unwrap();
-            input.trim().to_string()
-        });
-
-    let api_secret = std::env::var("ZERODHA_API_SECRET")
-        .or_else(|_| {
-            // Try to read from config.toml
-            if let Ok(config_str) = std::fs::read_to_string("config.toml") {
-                if let Ok(config) = toml::from_str::<toml::Value>(&config_str) {
-                    if let Some(api_secret) = config
-                        .get("zerodha")
-                        .and_then(|z| z.get("api_secret"))
-                        .and_then(|s| s.as_str())
-                    {
-                        return Ok(api_secret.to_string());
-                    }
+            Err(e) => Some(format!("could not update {}: {:#}", CONFIG_FILE, e)),
+        },
+    };
+
+    if let Some(problem) = problem {
+        if write_config == WriteConfig::Required {
+            return Err(AuthError::Failed(problem));
+        }
+        say!("⚠️  {}", problem);
+    }
+    say!(
+        "📝 Copy this token to [{}] in your config.toml file:",
+        account.section_path().join(".")
+    );
+    say!("   access_token = \"{}\"", access_token);
+    Ok(false)
+}
+
+/// Name --profile takes for the [zerodha] account
+const DEFAULT_PROFILE: &str = "default";
+
+/// One Kite account in config.toml: [zerodha], or a [profiles.<name>] entry
+struct Account {
+    // None for [zerodha]
+    profile: Option<String>,
+    section: Option<toml::Value>,
+    auto_login: Option<toml::Value>,
+}
+
+impl Account {
+    /// The account a run uses: --profile, else a choice among the configured profiles
+    /// when someone is there to answer, else [zerodha]
+    fn select(file_config: &Option<toml::Value>, options: &Options) -> Result<Self> {
+        let profiles = file_config
+            .as_ref()
+            .and_then(|config| config.get("profiles"))
+            .and_then(toml::Value::as_table);
+        let names: Vec<&String> = profiles
+            .map(|profiles| profiles.keys().collect())
+            .unwrap_or_default();
+
+        let profile = match &options.profile {
+            Some(name) if name == DEFAULT_PROFILE => None,
+            Some(name) => Some(name.clone()),
+            None if names.is_empty() || options.quiet || !io::stdin().is_terminal() => None,
+            None => {
+                say!("👤 Accounts in {}:", CONFIG_FILE);
+                say!("   0) {} [zerodha]", DEFAULT_PROFILE);
+                for (index, name) in names.iter().enumerate() {
+                    say!("   {}) {}", index + 1, name);
+                }
+                let answer = prompt("Choose an account [0]: ")?;
+                match answer.parse::<usize>() {
+                    _ if answer.is_empty() => None,
+                    Ok(0) => None,
+                    Ok(index) if index <= names.len() => Some(names[index - 1].clone()),
+                    _ if names.iter().any(|name| **name == answer) => Some(answer),
+                    _ if answer == DEFAULT_PROFILE => None,
+                    _ => return Err(AuthError::Usage(format!("no account \"{}\"", answer))),
                 }
             }
-            Err(std::env::VarError::NotPresent)
+        };
+
+        let Some(name) = profile else {
+            return Ok(Self {
+                profile: None,
+                section: file_config
+                    .as_ref()
+                    .and_then(|config| config.get("zerodha").cloned()),
+                auto_login: file_config
+                    .as_ref()
+                    .and_then(|config| config.get("auto_login").cloned()),
+            });
+        };
+
+        let section = profiles.and_then(|profiles| profiles.get(&name)).cloned();
+        let account = Self {
+            auto_login: section
+                .as_ref()
+                .and_then(|section| section.get("auto_login").cloned()),
+            section,
+            profile: Some(name),
+        };
+        // An account known only from the environment is fine; a typo is not
+        if account.section.is_none() && account.env("API_KEY").is_none() {
+            let available = std::iter::once(DEFAULT_PROFILE)
+                .chain(names.iter().map(|name| name.as_str()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(AuthError::Usage(format!(
+                "no [profiles.{}] in {} (available: {})",
+                account.name(),
+                CONFIG_FILE,
+                available
+            )));
+        }
+        Ok(account)
+    }
+
+    fn name(&self) -> &str {
+        self.profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Table the account lives in, for writing its token back
+    fn section_path(&self) -> Vec<&str> {
+        match &self.profile {
+            Some(name) => vec!["profiles", name],
+            None => config_writer::ZERODHA_SECTION.to_vec(),
+        }
+    }
+
+    /// ZERODHA_, or ZERODHA_<PROFILE>_ with the name upper-cased and other characters as _
+    fn env_prefix(&self) -> String {
+        match &self.profile {
+            Some(name) => {
+                let name: String = name
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() {
+                            c.to_ascii_uppercase()
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect();
+                format!("ZERODHA_{}_", name)
+            }
+            None => "ZERODHA_".to_string(),
+        }
+    }
+
+    fn env(&self, key: &str) -> Option<String> {
+        std::env::var(format!("{}{}", self.env_prefix(), key))
+            .ok()
+            .filter(|value| !value.is_empty())
+    }
+
+    /// A value from the account's environment variable, else its section of config.toml
+    fn setting(&self, key: &str) -> Option<String> {
+        self.env(&key.to_uppercase()).or_else(|| {
+            self.section
+                .as_ref()?
+                .get(key)?
+                .as_str()
+                .map(str::to_string)
         })
-        .unwrap_or_else(|_| {
-            print!("Enter your Zerodha API Secret: ");
-            io::stdout().flush().unwrap();
-            let mut input = String::new();
-            io::stdin().read_line(&mut input).unwrap();
-            input.trim().to_string()
-        });
+    }
+
+    /// Redirect listener port: env, then config.toml, then the default; 0 turns it off
+    fn callback_port(&self) -> u16 {
+        self.env("CALLBACK_PORT")
+            .and_then(|port| port.trim().parse::<u16>().ok())
+            .or_else(|| {
+                let port = self.section.as_ref()?.get("callback_port")?.as_integer()?;
+                u16::try_from(port).ok()
+            })
+            .unwrap_or(8080)
+    }
+}
+
+// Standalone binary for authentication
+#[tokio::main]
+async fn main() -> ExitCode {
+    let options = Options::parse();
+
+    let _ = PROGRESS.set(if options.quiet {
+        Progress::Off
+    } else if options.json {
+        Progress::Stderr
+    } else {
+        Progress::Stdout
+    });
+
+    match run(&options).await {
+        Ok(result) => {
+            if options.json {
+                println!("{}", result);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            if options.json {
+                println!(
+                    "{}",
+                    json!({ "error": e.to_string(), "exit_code": e.exit_code() })
+                );
+            } else {
+                eprintln!("❌ {}", e);
+            }
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+/// Everything after argument parsing; the JSON value is what --json prints
+async fn run(options: &Options) -> Result<Value> {
+    let file_config = std::fs::read_to_string(CONFIG_FILE)
+        .ok()
+        .and_then(|config_str| toml::from_str::<toml::Value>(&config_str).ok());
+
+    let account = Account::select(&file_config, options)?;
+    if account.profile.is_some() {
+        say!("👤 Using the {} account", account.name());
+    }
+
+    // Read API credentials from environment or config file or prompt
+    let api_key = match account.setting("api_key") {
+        Some(api_key) => api_key,
+        None => prompt("Enter your Zerodha API Key: ")?,
+    };
+    let api_secret = match account.setting("api_secret") {
+        Some(api_secret) => api_secret,
+        None => prompt("Enter your Zerodha API Secret: ")?,
+    };
 
     if api_key.is_empty() || api_secret.is_empty() {
-        anyhow::bail!("API Key and Secret are required");
+        return Err(AuthError::InvalidCredentials(
+            "API Key and Secret are required".to_string(),
+        ));
     }
 
-    let auth_helper = AuthHelper::new(api_key, api_secret);
+    let auth_helper = AuthHelper::new(api_key, api_secret, account.callback_port());
+
+    if options.test_only {
+        let access_token = account
+            .setting("access_token")
+            .filter(|token| token != "your_access_token_here")
+            .ok_or_else(|| {
+                AuthError::InvalidCredentials("no access token is configured".to_string())
+            })?;
+        let profile = auth_helper.test_token(&access_token).await?;
+        return Ok(json!({
+            "valid": true,
+            "profile": account.name(),
+            "user_id": profile.user_id,
+        }));
+    }
 
     // Check if we should test an existing token
-    if let Ok(existing_token) = std::env::var("ZERODHA_ACCESS_TOKEN") {
-        if !existing_token.is_empty() && existing_token != "your_access_token_here" {
-            println!("🔍 Found existing access token, testing validity...");
-            match auth_helper.test_token(&existing_token).await {
-                Ok(_) => {
-                    println!("✅ Existing token is valid, no need to re-authenticate");
-                    return Ok(());
-                }
-                Err(_) => {
-                    println!("❌ Existing token is invalid, starting authentication flow...");
+    if options.request_token.is_none() {
+        if let Some(existing_token) = account.env("ACCESS_TOKEN") {
+            if existing_token != "your_access_token_here" {
+                say!("🔍 Found existing access token, testing validity...");
+                match auth_helper.test_token(&existing_token).await {
+                    Ok(profile) => {
+                        say!("✅ Existing token is valid, no need to re-authenticate");
+                        return Ok(json!({
+                            "access_token": existing_token,
+                            "profile": account.name(),
+                            "user_id": profile.user_id,
+                            "expires_hint": expires_hint(),
+                        }));
+                    }
+                    Err(AuthError::Network(e)) => return Err(AuthError::Network(e)),
+                    Err(_) => {
+                        say!("❌ Existing token is invalid, starting authentication flow...");
+                    }
                 }
             }
         }
     }
 
-    // Start interactive authentication
-    let access_token = auth_helper.interactive_auth().await?;
+    // A given request_token, else headless login when [auto_login] opts in, else the browser
+    let auto_login = account
+        .auto_login
+        .clone()
+        .map(|section| section.try_into::<AutoLoginConfig>())
+        .transpose()
+        .map_err(|e| AuthError::Failed(format!("invalid auto_login in {}: {}", CONFIG_FILE, e)))?
+        .filter(|auto_login| auto_login.enabled && !options.interactive);
+
+    let request_token = match (&options.request_token, auto_login) {
+        (Some(request_token), _) => request_token.clone(),
+        (None, Some(auto_login)) => {
+            let credentials = Credentials::resolve(&auto_login, &account.env_prefix())?;
+            auth_helper.headless_auth(&credentials).await?
+        }
+        (None, None) => auth_helper.interactive_auth().await?,
+    };
+    let access_token = auth_helper.generate_session(&request_token).await?;
 
     // Test the new token
-    auth_helper.test_token(&access_token).await?;
+    let profile = auth_helper.test_token(&access_token).await?;
+
+    say!();
+    let saved = save_access_token(&account, &access_token, options.write_config())?;
+
+    say!();
+    say!("🎉 Authentication completed successfully!");
+    say!("💡 Tips:");
+    // The full token is printed only when it has nowhere else to go
+    if !saved {
+        say!("   - Update your config.toml with the new access token, or");
+        say!(
+            "     export {}ACCESS_TOKEN=\"{}\"",
+            account.env_prefix(),
+            access_token
+        );
+    }
+    say!("   - Restart your trading dashboard after updating the token");
+
+    Ok(json!({
+        "access_token": access_token,
+        "profile": account.name(),
+        "user_id": profile.user_id,
+        "expires_hint": expires_hint(),
+    }))
+}
 
-    println!();
-    println!("🎉 Authentication completed successfully!");
-    println!("💡 Tips:");
-    println!("   - Update your config.toml with the new access token");
-    println!("   - Set environment variable to avoid re-entering credentials:");
-    println!("     export ZERODHA_ACCESS_TOKEN=\"{}\"", access_token);
-    println!("   - Restart your trading dashboard after updating the token");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn parse(args: &[&str]) -> std::result::Result<Options, clap::Error> {
+        Options::try_parse_from(std::iter::once("auth_helper").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn command_definition_is_consistent() {
+        Options::command().debug_assert();
+    }
 
-    Ok(())
+    #[test]
+    fn values_are_trimmed_and_must_not_be_blank() {
+        let options = parse(&["--profile", " alt ", "--request-token", "abc "]).unwrap();
+        assert_eq!(options.profile.as_deref(), Some("alt"));
+        assert_eq!(options.request_token.as_deref(), Some("abc"));
+        assert!(parse(&["--profile", "  "]).is_err());
+        assert!(parse(&["--request-token"]).is_err());
+    }
+
+    #[test]
+    fn write_flags_choose_how_the_token_is_saved() {
+        assert_eq!(parse(&[]).unwrap().write_config(), WriteConfig::Default);
+        assert_eq!(
+            parse(&["--write-config"]).unwrap().write_config(),
+            WriteConfig::Required
+        );
+        assert_eq!(
+            parse(&["--no-write"]).unwrap().write_config(),
+            WriteConfig::Never
+        );
+        assert!(parse(&["--write-config", "--no-write"]).is_err());
+    }
+
+    #[test]
+    fn bad_arguments_exit_with_code_2() {
+        for args in [&["--bogus"][..], &["--test-only", "--request-token", "abc"]] {
+            assert_eq!(parse(args).unwrap_err().exit_code(), 2, "{:?}", args);
+        }
+    }
 }
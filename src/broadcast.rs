@@ -0,0 +1,333 @@
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Notify;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Messages waiting per client; past this, tick batches are dropped to make room
+const CLIENT_QUEUE_LEN: usize = 256;
+
+/// How long a new client has to send its subscription
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Kinds of message a client can subscribe to
+pub const MESSAGE_TYPES: &[&str] = &["ticks", "orders", "pnl"];
+
+/// One message for every client subscribed to it, shared between their queues
+#[derive(Debug)]
+pub enum Broadcast {
+    /// Ticks applied in one pass over worker events
+    Ticks(Vec<TickMessage>),
+    /// An order event, or an order whose state changed in a refresh
+    Order {
+        instrument_token: Option<u32>,
+        message: Value,
+    },
+    /// A sample of the session P&L, as recorded for the equity curve
+    Pnl(Value),
+}
+
+impl Broadcast {
+    fn kind(&self) -> &'static str {
+        match self {
+            Broadcast::Ticks(_) => "ticks",
+            Broadcast::Order { .. } => "orders",
+            Broadcast::Pnl(_) => "pnl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TickMessage {
+    pub instrument_token: u32,
+    pub last_price: f64,
+    pub last_quantity: u32,
+    pub volume: u64,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// A client's subscription handshake: `{"subscribe": {"types": [...], "tokens": [...]}}`
+/// Empty or missing lists mean everything
+#[derive(Debug, Default, Deserialize)]
+struct SubscribeRequest {
+    subscribe: Subscription,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+struct Subscription {
+    types: HashSet<String>,
+    tokens: HashSet<u32>,
+}
+
+impl Subscription {
+    fn wants_kind(&self, kind: &str) -> bool {
+        self.types.is_empty() || self.types.contains(kind)
+    }
+
+    fn wants_token(&self, token: Option<u32>) -> bool {
+        self.tokens.is_empty() || token.is_none_or(|token| self.tokens.contains(&token))
+    }
+}
+
+/// Fans selected app events out as JSON to WebSocket clients on a localhost port
+/// Cloned into AppState, which publishes as it applies worker events
+#[derive(Clone, Default)]
+pub struct Broadcaster {
+    clients: Arc<Mutex<Vec<Arc<Client>>>>,
+    // Tick batches dropped for slow clients this session, across all of them
+    dropped_tick_batches: Arc<AtomicU64>,
+}
+
+/// One connected client's subscription and pending messages
+#[derive(Default)]
+struct Client {
+    subscription: Mutex<Subscription>,
+    queue: Mutex<VecDeque<Arc<Broadcast>>>,
+    ready: Notify,
+    // Tick batches dropped since the client last got one
+    dropped_ticks: AtomicU64,
+    closed: AtomicBool,
+}
+
+impl Client {
+    /// Queue a message; when the queue is full the oldest tick batch makes room, and a
+    /// tick batch with none to evict is itself dropped, while order events always go in
+    /// Returns whether a tick batch was dropped
+    fn push(&self, message: Arc<Broadcast>) -> bool {
+        let mut queue = self.queue.lock();
+        let mut dropped = false;
+        if queue.len() >= CLIENT_QUEUE_LEN {
+            match queue
+                .iter()
+                .position(|queued| matches!(**queued, Broadcast::Ticks(_)))
+            {
+                Some(index) => {
+                    queue.remove(index);
+                    dropped = true;
+                }
+                None if matches!(*message, Broadcast::Ticks(_)) => {
+                    self.dropped_ticks.fetch_add(1, Ordering::Relaxed);
+                    return true;
+                }
+                None => {}
+            }
+        }
+        if dropped {
+            self.dropped_ticks.fetch_add(1, Ordering::Relaxed);
+        }
+        queue.push_back(message);
+        drop(queue);
+        self.ready.notify_one();
+        dropped
+    }
+
+    /// The JSON the client gets for a message, or None when its subscription filters it out
+    fn render(&self, message: &Broadcast) -> Option<String> {
+        let subscription = self.subscription.lock();
+        let body = match message {
+            Broadcast::Ticks(ticks) => {
+                let ticks: Vec<&TickMessage> = ticks
+                    .iter()
+                    .filter(|tick| subscription.wants_token(Some(tick.instrument_token)))
+                    .collect();
+                if ticks.is_empty() {
+                    return None;
+                }
+                json!({
+                    "type": "ticks",
+                    "ticks": ticks,
+                    "dropped_batches": self.dropped_ticks.swap(0, Ordering::Relaxed),
+                })
+            }
+            Broadcast::Order {
+                instrument_token,
+                message,
+            } => {
+                if !subscription.wants_token(*instrument_token) {
+                    return None;
+                }
+                message.clone()
+            }
+            Broadcast::Pnl(message) => message.clone(),
+        };
+        Some(body.to_string())
+    }
+}
+
+impl Broadcaster {
+    /// Listen on 127.0.0.1:`port` and serve clients for as long as the runtime runs
+    pub fn start(port: u16) -> Self {
+        let broadcaster = Self::default();
+        let clients = Arc::clone(&broadcaster.clients);
+        tokio::spawn(async move {
+            let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+            let listener = match TcpListener::bind(address).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    log::error!("Event broadcast could not listen on {}: {}", address, e);
+                    return;
+                }
+            };
+            log::info!("Event broadcast on ws://{}", address);
+            loop {
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        tokio::spawn(serve_client(stream, peer, Arc::clone(&clients)));
+                    }
+                    Err(e) => log::warn!("Event broadcast accept failed: {}", e),
+                }
+            }
+        });
+        broadcaster
+    }
+
+    /// Whether anyone is listening, so callers can skip building messages
+    pub fn has_clients(&self) -> bool {
+        !self.clients.lock().is_empty()
+    }
+
+    /// Tick batches dropped for slow clients this session
+    #[cfg(feature = "metrics")]
+    pub fn dropped_tick_batches(&self) -> u64 {
+        self.dropped_tick_batches.load(Ordering::Relaxed)
+    }
+
+    /// Queue a message for every client subscribed to its kind
+    pub fn publish(&self, message: Broadcast) {
+        let message = Arc::new(message);
+        let mut clients = self.clients.lock();
+        clients.retain(|client| !client.closed.load(Ordering::Relaxed));
+        for client in clients.iter() {
+            if client.subscription.lock().wants_kind(message.kind())
+                && client.push(Arc::clone(&message))
+            {
+                self.dropped_tick_batches.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Parse a subscription, rejecting unknown message types
+fn parse_subscription(text: &str) -> Result<Subscription, String> {
+    let request: SubscribeRequest = serde_json::from_str(text).map_err(|e| {
+        format!(
+            "expected {{\"subscribe\": {{\"types\": [...], \"tokens\": [...]}}}}: {}",
+            e
+        )
+    })?;
+    if let Some(unknown) = request
+        .subscribe
+        .types
+        .iter()
+        .find(|kind| !MESSAGE_TYPES.contains(&kind.as_str()))
+    {
+        return Err(format!(
+            "unknown type \"{}\"; expected any of: {}",
+            unknown,
+            MESSAGE_TYPES.join(", ")
+        ));
+    }
+    Ok(request.subscribe)
+}
+
+fn subscribed_message(subscription: &Subscription) -> Message {
+    let mut types: Vec<&String> = subscription.types.iter().collect();
+    types.sort();
+    let mut tokens: Vec<&u32> = subscription.tokens.iter().collect();
+    tokens.sort();
+    Message::Text(
+        json!({ "type": "subscribed", "types": types, "tokens": tokens })
+            .to_string()
+            .into(),
+    )
+}
+
+fn error_message(error: &str) -> Message {
+    Message::Text(
+        json!({ "type": "error", "error": error })
+            .to_string()
+            .into(),
+    )
+}
+
+/// Complete the handshake, then stream the client's queue until either side closes
+/// A later subscribe message replaces the filters
+async fn serve_client(stream: TcpStream, peer: SocketAddr, clients: Arc<Mutex<Vec<Arc<Client>>>>) {
+    let socket = match tokio_tungstenite::accept_async(stream).await {
+        Ok(socket) => socket,
+        Err(e) => {
+            log::debug!("Event broadcast handshake with {} failed: {}", peer, e);
+            return;
+        }
+    };
+    let (mut sink, mut source) = socket.split();
+
+    let subscription = match tokio::time::timeout(HANDSHAKE_TIMEOUT, source.next()).await {
+        Ok(Some(Ok(Message::Text(text)))) => parse_subscription(&text),
+        Ok(_) => Err("expected a subscribe message".to_string()),
+        Err(_) => Err("no subscribe message in time".to_string()),
+    };
+    let subscription = match subscription {
+        Ok(subscription) => subscription,
+        Err(e) => {
+            let _ = sink.send(error_message(&e)).await;
+            let _ = sink.close().await;
+            return;
+        }
+    };
+    if sink.send(subscribed_message(&subscription)).await.is_err() {
+        return;
+    }
+
+    let client = Arc::new(Client {
+        subscription: Mutex::new(subscription),
+        ..Client::default()
+    });
+    clients.lock().push(Arc::clone(&client));
+    log::info!("Event broadcast client {} subscribed", peer);
+
+    loop {
+        tokio::select! {
+            _ = client.ready.notified() => {
+                let pending: Vec<Arc<Broadcast>> = client.queue.lock().drain(..).collect();
+                for message in pending {
+                    let Some(text) = client.render(&message) else {
+                        continue;
+                    };
+                    if sink.send(Message::Text(text.into())).await.is_err() {
+                        client.closed.store(true, Ordering::Relaxed);
+                        return;
+                    }
+                }
+            }
+            incoming = source.next() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = match parse_subscription(&text) {
+                        Ok(subscription) => {
+                            let reply = subscribed_message(&subscription);
+                            *client.subscription.lock() = subscription;
+                            reply
+                        }
+                        Err(e) => error_message(&e),
+                    };
+                    if sink.send(reply).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // tungstenite answers pings itself
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    client.closed.store(true, Ordering::Relaxed);
+    log::info!("Event broadcast client {} disconnected", peer);
+}
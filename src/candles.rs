@@ -0,0 +1,196 @@
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One-minute bars kept per instrument; a full NSE session is 375 minutes
+const MAX_MINUTE_CANDLES: usize = 1000;
+
+/// OHLCV bar starting at `start`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    pub start: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: u64,
+}
+
+impl Candle {
+    fn opening(start: DateTime<Utc>, price: f64, volume: u64) -> Self {
+        Self {
+            start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume,
+        }
+    }
+
+    fn absorb(&mut self, other: &Candle) {
+        self.high = self.high.max(other.high);
+        self.low = self.low.min(other.low);
+        self.close = other.close;
+        self.volume += other.volume;
+    }
+}
+
+/// Bar size offered by the chart; larger sizes are resampled from one-minute bars
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CandleInterval {
+    #[default]
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 3] = [
+        CandleInterval::OneMinute,
+        CandleInterval::FiveMinutes,
+        CandleInterval::FifteenMinutes,
+    ];
+
+    pub fn minutes(&self) -> i64 {
+        match self {
+            CandleInterval::OneMinute => 1,
+            CandleInterval::FiveMinutes => 5,
+            CandleInterval::FifteenMinutes => 15,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CandleInterval::OneMinute => "1m",
+            CandleInterval::FiveMinutes => "5m",
+            CandleInterval::FifteenMinutes => "15m",
+        }
+    }
+}
+
+/// Session one-minute bars for an instrument, seeded from history and extended by ticks
+#[derive(Debug, Clone, Default)]
+pub struct CandleSeries {
+    candles: Vec<Candle>,
+    last_cumulative_volume: Option<u64>,
+    // Bumped on every change so views can cache derived data
+    revision: u64,
+}
+
+impl CandleSeries {
+    pub fn candles(&self) -> &[Candle] {
+        &self.candles
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Fold a tick into the current minute's bar
+    /// `cumulative_volume` is the day volume reported by the ticker
+    /// Returns the previous bar when this tick opens a new one, as that bar is now closed
+    pub fn record_tick(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        price: f64,
+        cumulative_volume: u64,
+    ) -> Option<Candle> {
+        let Ok(start) = timestamp.duration_trunc(TimeDelta::minutes(1)) else {
+            return None;
+        };
+
+        let traded = self
+            .last_cumulative_volume
+            .map(|last| cumulative_volume.saturating_sub(last))
+            .unwrap_or(0);
+        self.last_cumulative_volume = Some(cumulative_volume);
+
+        let mut closed = None;
+        match self.candles.last_mut() {
+            Some(candle) if candle.start == start => {
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.close = price;
+                candle.volume += traded;
+            }
+            Some(candle) if candle.start > start => return None, // late tick for a closed bar
+            last => {
+                closed = last.copied();
+                self.candles.push(Candle::opening(start, price, traded));
+                if self.candles.len() > MAX_MINUTE_CANDLES {
+                    self.candles.remove(0);
+                }
+            }
+        }
+
+        self.revision += 1;
+        closed
+    }
+
+    /// Merge bars from the historical API or the history store, in whichever order they
+    /// arrive; bars already held win for minutes both cover
+    pub fn merge_history(&mut self, history: Vec<Candle>) {
+        let mut merged: Vec<Candle> = history
+            .into_iter()
+            .filter(|candle| {
+                self.candles
+                    .binary_search_by_key(&candle.start, |held| held.start)
+                    .is_err()
+            })
+            .collect();
+        merged.append(&mut self.candles);
+        merged.sort_by_key(|candle| candle.start);
+
+        let excess = merged.len().saturating_sub(MAX_MINUTE_CANDLES);
+        merged.drain(..excess);
+
+        self.candles = merged;
+        self.revision += 1;
+    }
+}
+
+/// Combine one-minute bars into bars of the given interval, aligned to the clock
+pub fn resample(candles: &[Candle], interval: CandleInterval) -> Vec<Candle> {
+    if interval == CandleInterval::OneMinute {
+        return candles.to_vec();
+    }
+
+    let size = TimeDelta::minutes(interval.minutes());
+    let mut bars: Vec<Candle> = Vec::new();
+
+    for candle in candles {
+        let Ok(start) = candle.start.duration_trunc(size) else {
+            continue;
+        };
+
+        match bars.last_mut() {
+            Some(bar) if bar.start == start => bar.absorb(candle),
+            _ => bars.push(Candle { start, ..*candle }),
+        }
+    }
+
+    bars
+}
+
+/// Resampled bars for one (instrument, interval), rebuilt only when the series changes
+#[derive(Debug, Clone, Default)]
+pub struct ResampledCandles {
+    key: Option<(u32, CandleInterval, u64)>,
+    bars: Vec<Candle>,
+}
+
+impl ResampledCandles {
+    pub fn get(
+        &mut self,
+        instrument_token: u32,
+        interval: CandleInterval,
+        series: &CandleSeries,
+    ) -> &[Candle] {
+        let key = (instrument_token, interval, series.revision());
+        if self.key != Some(key) {
+            self.bars = resample(series.candles(), interval);
+            self.key = Some(key);
+        }
+        &self.bars
+    }
+}
@@ -0,0 +1,118 @@
+use crate::state::{AppEvent, EventSender};
+use anyhow::Context;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+const NTP_PORT: u16 = 123;
+
+/// How often the local clock is checked after the startup check
+const CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Offset past which the local clock is reported as drifting
+pub const DRIFT_WARN_THRESHOLD: TimeDelta = TimeDelta::milliseconds(100);
+
+/// Seconds from the NTP epoch (1900) to the Unix epoch
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+/// How far the local clock is behind true time, as measured against an NTP server
+#[derive(Debug, Clone)]
+pub struct ClockOffset {
+    /// Added to a local time to get the server's; positive when the local clock is slow
+    pub offset: TimeDelta,
+    pub round_trip: TimeDelta,
+    pub server: String,
+    pub measured_at: DateTime<Utc>,
+}
+
+impl ClockOffset {
+    pub fn is_drifting(&self) -> bool {
+        self.offset.abs() > DRIFT_WARN_THRESHOLD
+    }
+}
+
+/// Check the local clock against `server` now and every CHECK_INTERVAL, reporting each
+/// result as ClockOffsetMeasured
+pub fn start(server: String, event_sender: EventSender) {
+    tokio::spawn(async move {
+        let mut checks = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            checks.tick().await;
+            let result = query(&server).await.map_err(|e| format!("{:#}", e));
+            if event_sender
+                .send(AppEvent::ClockOffsetMeasured(result))
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+}
+
+/// One SNTP exchange: offset is the mean of the two one-way differences, so a symmetric
+/// network delay cancels out
+async fn query(server: &str) -> anyhow::Result<ClockOffset> {
+    let address = tokio::net::lookup_host((server, NTP_PORT))
+        .await?
+        .next()
+        .with_context(|| format!("{} has no address", server))?;
+    let socket = if address.is_ipv4() {
+        UdpSocket::bind("0.0.0.0:0").await?
+    } else {
+        UdpSocket::bind("[::]:0").await?
+    };
+    socket.connect(address).await?;
+
+    // Version 4, client mode; the transmit time comes back as the originate time
+    let mut request = [0u8; 48];
+    request[0] = 0x23;
+    let sent = Utc::now();
+    request[40..48].copy_from_slice(&to_ntp(sent));
+    socket.send(&request).await?;
+
+    let mut reply = [0u8; 48];
+    let length = tokio::time::timeout(QUERY_TIMEOUT, socket.recv(&mut reply))
+        .await
+        .with_context(|| format!("no answer from {} within {:?}", server, QUERY_TIMEOUT))??;
+    let received = Utc::now();
+
+    anyhow::ensure!(
+        length == reply.len() && reply[0] & 0x07 == 4,
+        "{} did not answer as an NTP server",
+        server
+    );
+    // Stratum 0 is a kiss-o'-death: the server refuses to serve this client
+    anyhow::ensure!(reply[1] != 0, "{} refused the request", server);
+    anyhow::ensure!(
+        reply[24..32] == request[40..48],
+        "{} answered a different request",
+        server
+    );
+
+    let server_received = from_ntp(&reply[32..40]);
+    let server_sent = from_ntp(&reply[40..48]);
+    Ok(ClockOffset {
+        offset: ((server_received - sent) + (server_sent - received)) / 2,
+        round_trip: (received - sent) - (server_sent - server_received),
+        server: server.to_string(),
+        measured_at: received,
+    })
+}
+
+fn to_ntp(time: DateTime<Utc>) -> [u8; 8] {
+    let seconds = (time.timestamp() + NTP_UNIX_OFFSET) as u32;
+    let fraction = ((time.timestamp_subsec_nanos() as u64) << 32) / 1_000_000_000;
+    let mut bytes = [0u8; 8];
+    bytes[..4].copy_from_slice(&seconds.to_be_bytes());
+    bytes[4..].copy_from_slice(&(fraction as u32).to_be_bytes());
+    bytes
+}
+
+fn from_ntp(bytes: &[u8]) -> DateTime<Utc> {
+    let seconds = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as i64;
+    let fraction = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as u64;
+    let nanos = ((fraction * 1_000_000_000) >> 32) as u32;
+    DateTime::from_timestamp(seconds - NTP_UNIX_OFFSET, nanos).unwrap_or_default()
+}
@@ -0,0 +1,105 @@
+// src/config_writer.rs - In-place edits of config.toml that keep its comments and layout
+// Shared with the auth_helper binary, so it depends on nothing else in the crate
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, TableLike, Value};
+
+/// The dashboard's account; other accounts live under [profiles.<name>]
+pub const ZERODHA_SECTION: &[&str] = &["zerodha"];
+
+/// Set access_token and when it was issued in an account's section, e.g. [zerodha],
+/// leaving every other line as it was
+/// The previous file is copied to a timestamped backup first; returns the backup's path
+pub fn write_access_token(
+    path: &Path,
+    section: &[&str],
+    access_token: &str,
+    issued_at: DateTime<Utc>,
+) -> Result<PathBuf> {
+    let mut document = read(path)?;
+    let table = section_table(&mut document, section)?;
+    set_string(table, "access_token", access_token);
+    set_string(table, "access_token_issued_at", &timestamp(issued_at));
+
+    let backup = backup_path(path);
+    std::fs::copy(path, &backup)
+        .with_context(|| format!("backing up {} to {}", path.display(), backup.display()))?;
+    replace(path, &document)?;
+
+    Ok(backup)
+}
+
+/// Set [zerodha] access_token_issued_at alone, for a token that was written without it
+pub fn write_token_issued_at(path: &Path, issued_at: DateTime<Utc>) -> Result<()> {
+    let mut document = read(path)?;
+    let zerodha = section_table(&mut document, ZERODHA_SECTION)?;
+    set_string(zerodha, "access_token_issued_at", &timestamp(issued_at));
+    replace(path, &document)
+}
+
+fn read(path: &Path) -> Result<DocumentMut> {
+    std::fs::read_to_string(path)
+        .with_context(|| format!("reading {}", path.display()))?
+        .parse()
+        .with_context(|| format!("parsing {}", path.display()))
+}
+
+/// A section's table by its dotted path, created if missing
+/// Only the innermost table gets a header of its own, e.g. [profiles.alt] without [profiles]
+fn section_table<'a>(
+    document: &'a mut DocumentMut,
+    section: &[&str],
+) -> Result<&'a mut dyn TableLike> {
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+    for (depth, key) in section.iter().enumerate() {
+        let implicit = depth + 1 < section.len();
+        table = table
+            .entry(key)
+            .or_insert_with(|| {
+                let mut table = toml_edit::Table::new();
+                table.set_implicit(implicit);
+                Item::Table(table)
+            })
+            .as_table_like_mut()
+            .with_context(|| format!("[{}] is not a table", section[..=depth].join(".")))?;
+    }
+    Ok(table)
+}
+
+fn set_string(table: &mut dyn TableLike, key: &str, value: &str) {
+    match table.get_mut(key).and_then(Item::as_value_mut) {
+        // Keep the comment trailing the old value
+        Some(existing) => {
+            let decor = existing.decor().clone();
+            *existing = Value::from(value);
+            *existing.decor_mut() = decor;
+        }
+        None => {
+            table.insert(key, toml_edit::value(value));
+        }
+    }
+}
+
+/// Whole seconds, so the value read back equals the one kept in memory
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Write beside the file and rename over it, so a failed write never leaves it half done
+fn replace(path: &Path, document: &DocumentMut) -> Result<()> {
+    let staging = path.with_extension("toml.tmp");
+    std::fs::write(&staging, document.to_string())
+        .with_context(|| format!("writing {}", staging.display()))?;
+    std::fs::rename(&staging, path).with_context(|| format!("replacing {}", path.display()))
+}
+
+/// e.g. config.toml.20260116-091502.bak next to the original
+fn backup_path(path: &Path) -> PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "config.toml".to_string());
+    path.with_file_name(format!("{}.{}.bak", name, stamp))
+}
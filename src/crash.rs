@@ -0,0 +1,101 @@
+use crate::data_structures::LogEntry;
+use chrono::Local;
+use parking_lot::RwLock;
+use std::backtrace::Backtrace;
+use std::fs::File;
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// Where crash reports go, relative to the working directory like config.toml
+pub const CRASH_DIR: &str = "crashes";
+
+/// Names the report the next launch offers to open; removed once it has been offered
+const PENDING_MARKER: &str = "last_crash";
+
+/// Log entries at the end of a report
+const REPORT_LOG_ENTRIES: usize = 200;
+
+/// How long the hook waits for the log buffer; the panicking thread may hold its lock
+const LOG_LOCK_TIMEOUT: Duration = Duration::from_millis(200);
+
+static LOGS: OnceLock<Arc<RwLock<Vec<LogEntry>>>> = OnceLock::new();
+
+/// Write a report for every panic before the default hook prints it
+pub fn install() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write_report(info) {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Could not write a crash report: {}", e),
+        }
+        default_hook(info);
+    }));
+}
+
+/// Include the tail of `logs` in later reports
+pub fn watch_logs(logs: Arc<RwLock<Vec<LogEntry>>>) {
+    let _ = LOGS.set(logs);
+}
+
+/// The report of a crash since the last launch, if any; each report is handed out once
+pub fn take_pending() -> Option<PathBuf> {
+    let marker = Path::new(CRASH_DIR).join(PENDING_MARKER);
+    let report = std::fs::read_to_string(&marker).ok()?;
+    let _ = std::fs::remove_file(&marker);
+    let report = PathBuf::from(report.trim());
+    report.is_file().then_some(report)
+}
+
+/// Write the report section by section, so a failure part way still leaves the panic
+/// and backtrace on disk
+/// Nothing here may panic: a panic inside the hook aborts without any report
+fn write_report(info: &PanicHookInfo) -> std::io::Result<PathBuf> {
+    let now = Local::now();
+    std::fs::create_dir_all(CRASH_DIR)?;
+    let path = Path::new(CRASH_DIR).join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S")));
+    let mut file = File::create(&path)?;
+
+    writeln!(
+        file,
+        "{} {} crash report",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(file, "Time: {}", now.to_rfc3339())?;
+    writeln!(
+        file,
+        "Thread: {}",
+        std::thread::current().name().unwrap_or("unnamed")
+    )?;
+    writeln!(file, "Panic: {}", info)?;
+    writeln!(file, "\nBacktrace:\n{}", Backtrace::force_capture())?;
+    file.flush()?;
+
+    // The report is worth offering even if the log tail can't be added
+    let _ = std::fs::write(
+        Path::new(CRASH_DIR).join(PENDING_MARKER),
+        path.to_string_lossy().as_bytes(),
+    );
+
+    match LOGS.get() {
+        None => writeln!(
+            file,
+            "\nNo log entries: the panic came before the app started"
+        )?,
+        Some(logs) => match logs.try_read_for(LOG_LOCK_TIMEOUT) {
+            None => writeln!(file, "\nNo log entries: the log buffer was locked")?,
+            Some(logs) => {
+                let start = logs.len().saturating_sub(REPORT_LOG_ENTRIES);
+                writeln!(file, "\nLast {} log entries:", logs.len() - start)?;
+                for entry in &logs[start..] {
+                    writeln!(file, "{}", entry.line())?;
+                }
+            }
+        },
+    }
+    file.flush()?;
+    Ok(path)
+}
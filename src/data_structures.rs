@@ -24,6 +24,12 @@ pub struct Position {
 }
 
 impl Position {
+    /// Kite reports one row per instrument and product, so a CNC and an MIS holding of the
+    /// same instrument are separate positions
+    pub fn key(&self) -> (u32, String) {
+        (self.instrument_token, self.product.clone())
+    }
+
     /// Calculate PnL dynamically for ultra-low latency updates
     pub fn calculate_pnl(&mut self) {
         if self.quantity != 0 {
@@ -32,6 +38,20 @@ impl Position {
         }
     }
 
+    /// Percentage move of the last price from the average entry price
+    pub fn change_pct(&self) -> f64 {
+        if self.average_price > 0.0 {
+            ((self.last_price - self.average_price) / self.average_price) * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Market value at the last price, negative for short positions
+    pub fn value(&self) -> f64 {
+        self.quantity as f64 * self.last_price
+    }
+
     /// Update last price and recalculate PnL in a single atomic operation
     pub fn update_last_price(&mut self, new_price: f64) {
         self.last_price = new_price;
@@ -69,7 +89,7 @@ pub struct Order {
     pub tag: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "rkyv", derive(Archive, RkyvSerialize, RkyvDeserialize))]
 pub enum OrderStatus {
     Open,
@@ -78,6 +98,260 @@ pub enum OrderStatus {
     Rejected,
     Trigger,
     Modified,
+    // Transient states while the OMS processes a request
+    PutOrderReqReceived,
+    ValidationPending,
+    OpenPending,
+    Pending, // MODIFY PENDING, MODIFY VALIDATION PENDING, CANCEL PENDING
+    // After-market orders
+    AmoReqReceived,
+    CancelledAmo,
+    /// Status string this version does not recognise, kept verbatim
+    Unknown(String),
+}
+
+impl OrderStatus {
+    /// Map a Kite order status string; unrecognised values are kept as Unknown
+    pub fn from_api_str(status: &str) -> Self {
+        match status {
+            "OPEN" => OrderStatus::Open,
+            "COMPLETE" => OrderStatus::Complete,
+            "CANCELLED" => OrderStatus::Cancelled,
+            "REJECTED" => OrderStatus::Rejected,
+            "TRIGGER PENDING" => OrderStatus::Trigger,
+            "MODIFIED" => OrderStatus::Modified,
+            "PUT ORDER REQ RECEIVED" => OrderStatus::PutOrderReqReceived,
+            "VALIDATION PENDING" => OrderStatus::ValidationPending,
+            "OPEN PENDING" => OrderStatus::OpenPending,
+            "MODIFY PENDING" | "MODIFY VALIDATION PENDING" | "CANCEL PENDING" => {
+                OrderStatus::Pending
+            }
+            "AMO REQ RECEIVED" => OrderStatus::AmoReqReceived,
+            "CANCELLED AMO" => OrderStatus::CancelledAmo,
+            other => OrderStatus::Unknown(other.to_string()),
+        }
+    }
+
+    /// Whether the order rests at the exchange (or in the AMO queue) and can be cancelled or modified
+    /// Orders still passing OMS validation must not be acted on until they settle
+    pub fn is_cancellable(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Open
+                | OrderStatus::Trigger
+                | OrderStatus::Modified
+                | OrderStatus::AmoReqReceived
+        )
+    }
+}
+
+impl std::fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            OrderStatus::Open => "Open",
+            OrderStatus::Complete => "Complete",
+            OrderStatus::Cancelled => "Cancelled",
+            OrderStatus::Rejected => "Rejected",
+            OrderStatus::Trigger => "Trigger Pending",
+            OrderStatus::Modified => "Modified",
+            OrderStatus::PutOrderReqReceived => "Put Order Req Received",
+            OrderStatus::ValidationPending => "Validation Pending",
+            OrderStatus::OpenPending => "Open Pending",
+            OrderStatus::Pending => "Pending",
+            OrderStatus::AmoReqReceived => "AMO Req Received",
+            OrderStatus::CancelledAmo => "Cancelled AMO",
+            OrderStatus::Unknown(status) => status,
+        };
+        f.write_str(label)
+    }
+}
+
+/// One execution from the day's tradebook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    pub trade_id: String, // Empty for fills seen live but not yet in the tradebook
+    pub order_id: String,
+    pub exchange_order_id: Option<String>,
+    pub tradingsymbol: String,
+    pub exchange: String,
+    pub instrument_token: u32,
+    pub product: String,
+    pub transaction_type: String,
+    pub quantity: i32,
+    pub average_price: f64,
+    pub fill_timestamp: DateTime<Utc>,
+}
+
+/// An order's status transitions, oldest first, with the fills it produced
+#[derive(Debug, Clone, Default)]
+pub struct OrderHistory {
+    pub states: Vec<Order>,
+    pub trades: Vec<Trade>,
+}
+
+/// Bought and sold totals of one symbol's fills, with the volume-weighted price of each side
+#[derive(Debug, Clone, Default)]
+pub struct TradeSummary {
+    pub tradingsymbol: String,
+    pub bought_quantity: i32,
+    pub bought_value: f64,
+    pub sold_quantity: i32,
+    pub sold_value: f64,
+}
+
+impl TradeSummary {
+    pub fn buy_vwap(&self) -> Option<f64> {
+        (self.bought_quantity > 0).then(|| self.bought_value / self.bought_quantity as f64)
+    }
+
+    pub fn sell_vwap(&self) -> Option<f64> {
+        (self.sold_quantity > 0).then(|| self.sold_value / self.sold_quantity as f64)
+    }
+}
+
+/// Aggregate trades per symbol, sorted by symbol
+pub fn summarize_trades<'a>(trades: impl IntoIterator<Item = &'a Trade>) -> Vec<TradeSummary> {
+    let mut by_symbol: HashMap<&str, TradeSummary> = HashMap::new();
+
+    for trade in trades {
+        let summary = by_symbol
+            .entry(&trade.tradingsymbol)
+            .or_insert_with(|| TradeSummary {
+                tradingsymbol: trade.tradingsymbol.clone(),
+                ..Default::default()
+            });
+        let value = trade.average_price * trade.quantity as f64;
+        if trade.transaction_type == "BUY" {
+            summary.bought_quantity += trade.quantity;
+            summary.bought_value += value;
+        } else {
+            summary.sold_quantity += trade.quantity;
+            summary.sold_value += value;
+        }
+    }
+
+    let mut summaries: Vec<TradeSummary> = by_symbol.into_values().collect();
+    summaries.sort_by(|a, b| a.tradingsymbol.cmp(&b.tradingsymbol));
+    summaries
+}
+
+/// Net filled quantity contributed by orders carrying a strategy tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagPosition {
+    pub instrument_token: u32,
+    pub tradingsymbol: String,
+    pub exchange: String,
+    pub product: String,
+    pub net_quantity: i32,
+}
+
+/// Aggregate filled quantities of tagged orders into per-tag, per-instrument net positions
+/// Instruments that net out to zero are dropped
+pub fn aggregate_tag_positions<'a>(
+    orders: impl IntoIterator<Item = &'a Order>,
+) -> HashMap<String, Vec<TagPosition>> {
+    let mut by_tag: HashMap<String, HashMap<(u32, String), TagPosition>> = HashMap::new();
+
+    for order in orders {
+        let Some(tag) = &order.tag else {
+            continue;
+        };
+        if order.filled_quantity == 0 {
+            continue;
+        }
+
+        let signed_quantity = if order.transaction_type == "SELL" {
+            -order.filled_quantity
+        } else {
+            order.filled_quantity
+        };
+
+        by_tag
+            .entry(tag.clone())
+            .or_default()
+            .entry((order.instrument_token, order.product.clone()))
+            .or_insert_with(|| TagPosition {
+                instrument_token: order.instrument_token,
+                tradingsymbol: order.tradingsymbol.clone(),
+                exchange: order.exchange.clone(),
+                product: order.product.clone(),
+                net_quantity: 0,
+            })
+            .net_quantity += signed_quantity;
+    }
+
+    by_tag
+        .into_iter()
+        .filter_map(|(tag, instruments)| {
+            let positions: Vec<TagPosition> = instruments
+                .into_values()
+                .filter(|position| position.net_quantity != 0)
+                .collect();
+            (!positions.is_empty()).then_some((tag, positions))
+        })
+        .collect()
+}
+
+/// Realized and open P&L attributed to one tag's orders
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TagPnl {
+    pub realized: f64,
+    pub unrealized: f64,
+}
+
+/// P&L of a tag's filled orders, netted per instrument and product at average cost in fill
+/// order; open quantity is valued at `last_price`, or not at all while it has none
+pub fn tag_pnl<'a>(
+    orders: impl IntoIterator<Item = &'a Order>,
+    last_price: impl Fn(u32) -> Option<f64>,
+) -> TagPnl {
+    let mut fills: Vec<&Order> = orders
+        .into_iter()
+        .filter(|order| order.filled_quantity > 0)
+        .collect();
+    fills.sort_by_key(|order| order.exchange_timestamp.unwrap_or(order.order_timestamp));
+
+    let mut pnl = TagPnl::default();
+    // Net quantity and average cost per (instrument, product)
+    let mut open: HashMap<(u32, &str), (i32, f64)> = HashMap::new();
+    for order in fills {
+        let quantity = if order.transaction_type == "SELL" {
+            -order.filled_quantity
+        } else {
+            order.filled_quantity
+        };
+        let price = order.average_price;
+        let (net, average) = open
+            .entry((order.instrument_token, order.product.as_str()))
+            .or_default();
+
+        if *net == 0 || net.signum() == quantity.signum() {
+            let total = net.abs() + quantity.abs();
+            *average = (*average * net.abs() as f64 + price * quantity.abs() as f64) / total as f64;
+            *net += quantity;
+            continue;
+        }
+
+        let closed = quantity.abs().min(net.abs());
+        pnl.realized += closed as f64 * (price - *average) * net.signum() as f64;
+        *net += quantity;
+        if *net == 0 {
+            *average = 0.0;
+        } else if net.signum() == quantity.signum() {
+            // Reversed through flat; the remainder opened at this fill's price
+            *average = price;
+        }
+    }
+
+    for ((instrument_token, _), (net, average)) in open {
+        if net == 0 {
+            continue;
+        }
+        if let Some(price) = last_price(instrument_token) {
+            pnl.unrealized += net as f64 * (price - average);
+        }
+    }
+    pnl
 }
 
 /// Zero-copy tick data structure for ultra-low latency WebSocket processing
@@ -95,6 +369,60 @@ pub struct TickData {
     pub timestamp_nanos: i64, // Unix timestamp in nanoseconds
 }
 
+/// One price level of the order book
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DepthLevel {
+    pub price: f64,
+    pub quantity: u32,
+    pub orders: u16,
+}
+
+/// Best five bid and ask levels from a full-mode tick
+#[derive(Debug, Clone, Default)]
+pub struct MarketDepth {
+    pub bids: Vec<DepthLevel>, // best bid first
+    pub asks: Vec<DepthLevel>, // best ask first
+    pub total_buy_quantity: u64,
+    pub total_sell_quantity: u64,
+}
+
+impl MarketDepth {
+    /// Best ask minus best bid, when both sides are quoted
+    pub fn spread(&self) -> Option<f64> {
+        let bid = self.bids.first().filter(|level| level.price > 0.0)?;
+        let ask = self.asks.first().filter(|level| level.price > 0.0)?;
+        Some(ask.price - bid.price)
+    }
+
+    /// Largest quantity on either side, for scaling depth bars
+    pub fn max_level_quantity(&self) -> u32 {
+        self.bids
+            .iter()
+            .chain(&self.asks)
+            .map(|level| level.quantity)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Kite ticker streaming mode; full mode adds market depth to each tick
+/// Quote is the ticker's default, and the one instruments drop back to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TickMode {
+    Quote,
+    Full,
+}
+
+impl TickMode {
+    /// String expected by the ticker's "mode" message
+    pub fn as_api_str(&self) -> &'static str {
+        match self {
+            TickMode::Quote => "quote",
+            TickMode::Full => "full",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "rkyv", derive(Archive, RkyvSerialize, RkyvDeserialize))]
 pub struct OHLC {
@@ -114,17 +442,34 @@ pub struct OrderRequest {
     pub quantity: i32,
     pub price: Option<f64>,
     pub product: String,  // CNC, MIS, NRML
-    pub validity: String, // DAY, IOC
+    pub validity: String, // DAY, IOC, TTL
+    /// Minutes a TTL order stays open
+    #[serde(default)]
+    pub validity_ttl: Option<u32>,
     pub disclosed_quantity: Option<i32>,
     pub trigger_price: Option<f64>,
     pub squareoff: Option<f64>,
     pub stoploss: Option<f64>,
     pub trailing_stoploss: Option<f64>,
     pub tag: Option<String>,
+    /// Order variety used in the API path: regular, amo, co, iceberg
+    #[serde(default = "default_variety")]
+    pub variety: String,
+    /// Skip the local margin pre-check, e.g. when a simultaneous exit frees margin
+    #[serde(default)]
+    pub bypass_margin_check: bool,
+    /// Set by AppState when the order is sent, and echoed back on OrderPlaced, OrderRejected
+    /// or OrderPlaceFailed
+    #[serde(default)]
+    pub request_id: Option<u64>,
+}
+
+fn default_variety() -> String {
+    "regular".to_string()
 }
 
 /// PnL data structure for performance analytics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PnlData {
     pub realized: f64,
     pub unrealized: f64,
@@ -134,16 +479,126 @@ pub struct PnlData {
     pub day_unrealized: f64,
 }
 
+impl PnlData {
+    /// Fold one position into the running totals
+    pub fn add_position(&mut self, position: &Position) {
+        self.realized += position.realized_pnl;
+        self.unrealized += position.unrealized_pnl;
+        self.total = self.realized + self.unrealized;
+        self.day_pnl = self.unrealized; // Simplified - in real implementation, track day-specific PnL
+        self.day_unrealized = self.unrealized;
+    }
+}
+
+/// Account margins snapshot from the user margins endpoint
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Margins {
+    pub equity: SegmentMargins,
+    pub commodity: SegmentMargins,
+}
+
+impl Margins {
+    /// Margins of the segment an exchange settles in
+    pub fn for_exchange(&self, exchange: &str) -> &SegmentMargins {
+        match exchange {
+            "MCX" => &self.commodity,
+            _ => &self.equity,
+        }
+    }
+}
+
+/// Per-segment margin breakdown
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SegmentMargins {
+    pub enabled: bool,
+    pub net: f64, // Net cash available for new orders
+    pub available: AvailableMargins,
+    pub utilised: UtilisedMargins,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AvailableMargins {
+    pub adhoc_margin: f64,
+    pub cash: f64,
+    pub opening_balance: f64,
+    pub live_balance: f64,
+    pub collateral: f64,
+    pub intraday_payin: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UtilisedMargins {
+    pub debits: f64,
+    pub exposure: f64,
+    pub span: f64,
+    pub option_premium: f64,
+    pub m2m_realised: f64,
+    pub m2m_unrealised: f64,
+}
+
+/// Margin required for a single order from the order margins preview endpoint
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrderMargin {
+    pub tradingsymbol: String,
+    pub exchange: String,
+    pub span: f64,
+    pub exposure: f64,
+    pub option_premium: f64,
+    pub total: f64,
+    pub charges: OrderCharges,
+}
+
+/// Estimated brokerage, taxes and fees for an order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrderCharges {
+    pub total: f64,
+}
+
 /// Log levels for the trading application
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "rkyv", derive(Archive, RkyvSerialize, RkyvDeserialize))]
 pub enum LogLevel {
     Info,
     Warning,
     Error,
+    #[default]
     Debug,
 }
 
+impl LogLevel {
+    /// Least to most severe
+    pub const ALL: [LogLevel; 4] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warning,
+        LogLevel::Error,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogLevel::Info => "INFO",
+            LogLevel::Warning => "WARN",
+            LogLevel::Error => "ERROR",
+            LogLevel::Debug => "DEBUG",
+        }
+    }
+
+    pub fn severity(self) -> u8 {
+        match self {
+            LogLevel::Debug => 0,
+            LogLevel::Info => 1,
+            LogLevel::Warning => 2,
+            LogLevel::Error => 3,
+        }
+    }
+}
+
 /// Log entry structure for application logging
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
@@ -162,6 +617,41 @@ impl LogEntry {
             module,
         }
     }
+
+    /// One line of text, as copied from or exported by the Logs view
+    pub fn line(&self) -> String {
+        let module = self
+            .module
+            .as_deref()
+            .map(|module| format!(" [{}]", module))
+            .unwrap_or_default();
+        format!(
+            "{} {}{} {}",
+            self.timestamp
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            self.level.label(),
+            module,
+            self.message
+        )
+    }
+}
+
+/// Transient notification shown on top of the main window
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub level: LogLevel,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Toast {
+    pub fn new(level: LogLevel, message: String) -> Self {
+        Self {
+            level,
+            message,
+            created_at: Utc::now(),
+        }
+    }
 }
 
 /// User profile information
@@ -193,3 +683,343 @@ pub struct Instrument {
     pub segment: String,
     pub exchange: String,
 }
+
+impl Instrument {
+    /// Whether this instrument trades in lots (futures and options)
+    pub fn is_derivative(&self) -> bool {
+        matches!(self.instrument_type.as_str(), "FUT" | "CE" | "PE")
+    }
+
+    /// Round a price to the nearest valid tick for this instrument
+    pub fn round_to_tick(&self, price: f64) -> f64 {
+        if self.tick_size <= 0.0 {
+            return price;
+        }
+        let rounded = (price / self.tick_size).round() * self.tick_size;
+        // Trim floating point noise so 101.35000000000001 displays and serializes cleanly
+        (rounded * 1e8).round() / 1e8
+    }
+
+    /// Check whether a price lies on the tick grid within decimal tolerance
+    pub fn is_valid_tick(&self, price: f64) -> bool {
+        if self.tick_size <= 0.0 {
+            return true;
+        }
+        let steps = price / self.tick_size;
+        (steps - steps.round()).abs() < 1e-6
+    }
+}
+
+/// Reasons an order is rejected locally before reaching the exchange
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum OrderValidationError {
+    #[error("{tradingsymbol}: price ₹{price} is not a multiple of tick size {tick_size} (nearest valid price ₹{nearest})")]
+    InvalidTick {
+        tradingsymbol: String,
+        price: f64,
+        tick_size: f64,
+        nearest: f64,
+    },
+    #[error("{tradingsymbol}: quantity {quantity} is not a multiple of lot size {lot_size}")]
+    InvalidLot {
+        tradingsymbol: String,
+        quantity: i32,
+        lot_size: u32,
+    },
+    #[error("{tradingsymbol} is listed on {expected}, not {requested}")]
+    ExchangeMismatch {
+        tradingsymbol: String,
+        requested: String,
+        expected: String,
+    },
+    #[error("order {order_id} is {status} and cannot be modified")]
+    NotModifiable {
+        order_id: String,
+        status: OrderStatus,
+    },
+    #[error("{tradingsymbol}: quantity {quantity} must be greater than zero")]
+    NonPositiveQuantity {
+        tradingsymbol: String,
+        quantity: i32,
+    },
+    #[error("{tradingsymbol}: {field} ₹{price} must be a positive number")]
+    NonPositivePrice {
+        tradingsymbol: String,
+        field: &'static str,
+        price: f64,
+    },
+    #[error("order {order_id}: quantity {quantity} is below the {filled} already filled")]
+    QuantityBelowFilled {
+        order_id: String,
+        quantity: i32,
+        filled: i32,
+    },
+}
+
+impl Order {
+    /// Whether the broker still accepts modifications or cancellation for this order
+    pub fn is_cancellable(&self) -> bool {
+        self.status.is_cancellable()
+    }
+}
+
+impl OrderRequest {
+    /// Check the quantity and any prices are positive, whatever built the request
+    pub fn validate_amounts(&self) -> Result<(), OrderValidationError> {
+        if self.quantity <= 0 {
+            return Err(OrderValidationError::NonPositiveQuantity {
+                tradingsymbol: self.tradingsymbol.clone(),
+                quantity: self.quantity,
+            });
+        }
+        for (field, price) in [("price", self.price), ("trigger price", self.trigger_price)] {
+            if let Some(price) = price.filter(|price| !(price.is_finite() && *price > 0.0)) {
+                return Err(OrderValidationError::NonPositivePrice {
+                    tradingsymbol: self.tradingsymbol.clone(),
+                    field,
+                    price,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate price, trigger price, quantity and exchange against instrument master data
+    pub fn validate_against(&self, instrument: &Instrument) -> Result<(), OrderValidationError> {
+        if self.exchange != instrument.exchange {
+            return Err(OrderValidationError::ExchangeMismatch {
+                tradingsymbol: self.tradingsymbol.clone(),
+                requested: self.exchange.clone(),
+                expected: instrument.exchange.clone(),
+            });
+        }
+
+        for price in [self.price, self.trigger_price].into_iter().flatten() {
+            if !instrument.is_valid_tick(price) {
+                return Err(OrderValidationError::InvalidTick {
+                    tradingsymbol: self.tradingsymbol.clone(),
+                    price,
+                    tick_size: instrument.tick_size,
+                    nearest: instrument.round_to_tick(price),
+                });
+            }
+        }
+
+        if instrument.is_derivative()
+            && instrument.lot_size > 1
+            && self.quantity % instrument.lot_size as i32 != 0
+        {
+            return Err(OrderValidationError::InvalidLot {
+                tradingsymbol: self.tradingsymbol.clone(),
+                quantity: self.quantity,
+                lot_size: instrument.lot_size,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl OrderRequest {
+    /// A regular day market order, for unit tests
+    pub(crate) fn market(tradingsymbol: &str, transaction_type: &str, quantity: i32) -> Self {
+        Self {
+            tradingsymbol: tradingsymbol.to_string(),
+            exchange: "NSE".to_string(),
+            transaction_type: transaction_type.to_string(),
+            order_type: "MARKET".to_string(),
+            quantity,
+            price: None,
+            product: "CNC".to_string(),
+            validity: "DAY".to_string(),
+            validity_ttl: None,
+            disclosed_quantity: None,
+            trigger_price: None,
+            squareoff: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: None,
+            variety: default_variety(),
+            bypass_margin_check: false,
+            request_id: None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Order {
+    /// A 10-share INFY limit buy at 100, for unit tests
+    pub(crate) fn limit(order_id: &str, status: OrderStatus) -> Self {
+        Self {
+            order_id: order_id.to_string(),
+            parent_order_id: None,
+            exchange_order_id: String::new(),
+            placed_by: String::new(),
+            variety: "regular".to_string(),
+            status,
+            tradingsymbol: "INFY".to_string(),
+            exchange: "NSE".to_string(),
+            instrument_token: 1,
+            transaction_type: "BUY".to_string(),
+            order_type: "LIMIT".to_string(),
+            product: "CNC".to_string(),
+            validity: "DAY".to_string(),
+            price: 100.0,
+            quantity: 10,
+            pending_quantity: 10,
+            filled_quantity: 0,
+            disclosed_quantity: 0,
+            trigger_price: 0.0,
+            average_price: 0.0,
+            order_timestamp: Utc::now(),
+            exchange_timestamp: None,
+            status_message: None,
+            tag: None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Instrument {
+    /// An equity traded in single shares at 5 paise ticks, for unit tests
+    pub(crate) fn equity(instrument_token: u32, tradingsymbol: &str, exchange: &str) -> Self {
+        Self {
+            instrument_token,
+            exchange_token: instrument_token,
+            tradingsymbol: tradingsymbol.to_string(),
+            name: tradingsymbol.to_string(),
+            last_price: 0.0,
+            expiry: None,
+            strike: None,
+            tick_size: 0.05,
+            lot_size: 1,
+            instrument_type: "EQ".to_string(),
+            segment: exchange.to_string(),
+            exchange: exchange.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_amounts_refuses_non_positive_quantities() {
+        for quantity in [0, -1] {
+            let order = OrderRequest::market("INFY", "BUY", quantity);
+            assert!(matches!(
+                order.validate_amounts(),
+                Err(OrderValidationError::NonPositiveQuantity { quantity: q, .. }) if q == quantity
+            ));
+        }
+        assert!(OrderRequest::market("INFY", "BUY", 1)
+            .validate_amounts()
+            .is_ok());
+    }
+
+    #[test]
+    fn validate_amounts_refuses_non_positive_prices() {
+        for price in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let mut order = OrderRequest::market("INFY", "BUY", 1);
+            order.price = Some(price);
+            assert!(matches!(
+                order.validate_amounts(),
+                Err(OrderValidationError::NonPositivePrice { field: "price", .. })
+            ));
+
+            let mut order = OrderRequest::market("INFY", "BUY", 1);
+            order.trigger_price = Some(price);
+            assert!(matches!(
+                order.validate_amounts(),
+                Err(OrderValidationError::NonPositivePrice {
+                    field: "trigger price",
+                    ..
+                })
+            ));
+        }
+
+        let mut order = OrderRequest::market("INFY", "BUY", 1);
+        order.price = Some(1500.5);
+        order.trigger_price = Some(1499.0);
+        assert!(order.validate_amounts().is_ok());
+    }
+
+    #[test]
+    fn every_kite_order_status_maps_to_a_variant() {
+        for (api, status, label) in [
+            ("OPEN", OrderStatus::Open, "Open"),
+            ("COMPLETE", OrderStatus::Complete, "Complete"),
+            ("CANCELLED", OrderStatus::Cancelled, "Cancelled"),
+            ("REJECTED", OrderStatus::Rejected, "Rejected"),
+            ("TRIGGER PENDING", OrderStatus::Trigger, "Trigger Pending"),
+            ("MODIFIED", OrderStatus::Modified, "Modified"),
+            (
+                "PUT ORDER REQ RECEIVED",
+                OrderStatus::PutOrderReqReceived,
+                "Put Order Req Received",
+            ),
+            (
+                "VALIDATION PENDING",
+                OrderStatus::ValidationPending,
+                "Validation Pending",
+            ),
+            ("OPEN PENDING", OrderStatus::OpenPending, "Open Pending"),
+            ("MODIFY PENDING", OrderStatus::Pending, "Pending"),
+            ("MODIFY VALIDATION PENDING", OrderStatus::Pending, "Pending"),
+            ("CANCEL PENDING", OrderStatus::Pending, "Pending"),
+            (
+                "AMO REQ RECEIVED",
+                OrderStatus::AmoReqReceived,
+                "AMO Req Received",
+            ),
+            ("CANCELLED AMO", OrderStatus::CancelledAmo, "Cancelled AMO"),
+        ] {
+            let parsed = OrderStatus::from_api_str(api);
+            assert_eq!(parsed, status, "{}", api);
+            assert_eq!(parsed.to_string(), label);
+        }
+    }
+
+    #[test]
+    fn unknown_order_status_is_kept_verbatim() {
+        let status = OrderStatus::from_api_str("LAPSED");
+        assert_eq!(status, OrderStatus::Unknown("LAPSED".to_string()));
+        assert_eq!(status.to_string(), "LAPSED");
+        assert!(!status.is_cancellable());
+    }
+
+    #[test]
+    fn only_resting_orders_are_cancellable() {
+        for status in [
+            OrderStatus::Open,
+            OrderStatus::Trigger,
+            OrderStatus::Modified,
+            OrderStatus::AmoReqReceived,
+        ] {
+            assert!(Order::limit("ORD1", status).is_cancellable());
+        }
+        for status in [
+            OrderStatus::Complete,
+            OrderStatus::Cancelled,
+            OrderStatus::Rejected,
+            OrderStatus::PutOrderReqReceived,
+            OrderStatus::ValidationPending,
+            OrderStatus::OpenPending,
+            OrderStatus::Pending,
+            OrderStatus::CancelledAmo,
+        ] {
+            assert!(!Order::limit("ORD1", status).is_cancellable());
+        }
+
+        let error = OrderValidationError::NotModifiable {
+            order_id: "ORD1".to_string(),
+            status: OrderStatus::Pending,
+        };
+        assert_eq!(
+            error.to_string(),
+            "order ORD1 is Pending and cannot be modified"
+        );
+    }
+}
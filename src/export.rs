@@ -0,0 +1,238 @@
+use crate::data_structures::{LogEntry, Order, OrderStatus, Position};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+
+/// File format chosen from the target path's extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// `.json` exports JSON, anything else CSV
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => ExportFormat::Json,
+            _ => ExportFormat::Csv,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+// Row types fix the column order; numbers are written with a plain decimal point
+// and full precision, timestamps as RFC 3339
+
+#[derive(Debug, Serialize)]
+struct PositionRow<'a> {
+    tradingsymbol: &'a str,
+    exchange: &'a str,
+    instrument_token: u32,
+    product: &'a str,
+    quantity: i32,
+    overnight_quantity: i32,
+    day_quantity: i32,
+    average_price: f64,
+    last_price: f64,
+    close_price: f64,
+    multiplier: f64,
+    pnl: f64,
+    realized_pnl: f64,
+    unrealized_pnl: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct OrderRow<'a> {
+    order_timestamp: String,
+    exchange_timestamp: Option<String>,
+    order_id: &'a str,
+    exchange_order_id: &'a str,
+    tradingsymbol: &'a str,
+    exchange: &'a str,
+    transaction_type: &'a str,
+    order_type: &'a str,
+    product: &'a str,
+    validity: &'a str,
+    status: String,
+    quantity: i32,
+    filled_quantity: i32,
+    pending_quantity: i32,
+    price: f64,
+    trigger_price: f64,
+    average_price: f64,
+    tag: Option<&'a str>,
+    status_message: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct TradeRow<'a> {
+    timestamp: String,
+    order_id: &'a str,
+    tradingsymbol: &'a str,
+    exchange: &'a str,
+    transaction_type: &'a str,
+    product: &'a str,
+    quantity: i32,
+    price: f64,
+    value: f64,
+    tag: Option<&'a str>,
+}
+
+fn iso(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339()
+}
+
+/// Write positions in the given order; returns the row count
+/// A CSV `comment` is written as a leading `#` line
+pub fn export_positions(
+    positions: &[Position],
+    path: &Path,
+    comment: Option<&str>,
+) -> anyhow::Result<usize> {
+    let rows: Vec<PositionRow> = positions
+        .iter()
+        .map(|position| PositionRow {
+            tradingsymbol: &position.tradingsymbol,
+            exchange: &position.exchange,
+            instrument_token: position.instrument_token,
+            product: &position.product,
+            quantity: position.quantity,
+            overnight_quantity: position.overnight_quantity,
+            day_quantity: position.day_quantity,
+            average_price: position.average_price,
+            last_price: position.last_price,
+            close_price: position.close_price,
+            multiplier: position.multiplier,
+            pnl: position.pnl,
+            realized_pnl: position.realized_pnl,
+            unrealized_pnl: position.unrealized_pnl,
+        })
+        .collect();
+
+    write_rows(&rows, path, comment)
+}
+
+/// Write orders in the given order; returns the row count
+/// A CSV `comment` is written as a leading `#` line
+pub fn export_orders(
+    orders: &[Order],
+    path: &Path,
+    comment: Option<&str>,
+) -> anyhow::Result<usize> {
+    let rows: Vec<OrderRow> = orders
+        .iter()
+        .map(|order| OrderRow {
+            order_timestamp: iso(order.order_timestamp),
+            exchange_timestamp: order.exchange_timestamp.map(iso),
+            order_id: &order.order_id,
+            exchange_order_id: &order.exchange_order_id,
+            tradingsymbol: &order.tradingsymbol,
+            exchange: &order.exchange,
+            transaction_type: &order.transaction_type,
+            order_type: &order.order_type,
+            product: &order.product,
+            validity: &order.validity,
+            status: order.status.to_string(),
+            quantity: order.quantity,
+            filled_quantity: order.filled_quantity,
+            pending_quantity: order.pending_quantity,
+            price: order.price,
+            trigger_price: order.trigger_price,
+            average_price: order.average_price,
+            tag: order.tag.as_deref(),
+            status_message: order.status_message.as_deref(),
+        })
+        .collect();
+
+    write_rows(&rows, path, comment)
+}
+
+/// Write executed quantity per order at its average fill price; returns the row count
+pub fn export_trades(orders: &[Order], path: &Path) -> anyhow::Result<usize> {
+    let rows: Vec<TradeRow> = sorted_by_time(orders)
+        .into_iter()
+        .filter(|order| order.filled_quantity > 0 && !matches!(order.status, OrderStatus::Rejected))
+        .map(|order| TradeRow {
+            timestamp: iso(order.exchange_timestamp.unwrap_or(order.order_timestamp)),
+            order_id: &order.order_id,
+            tradingsymbol: &order.tradingsymbol,
+            exchange: &order.exchange,
+            transaction_type: &order.transaction_type,
+            product: &order.product,
+            quantity: order.filled_quantity,
+            price: order.average_price,
+            value: order.average_price * order.filled_quantity as f64,
+            tag: order.tag.as_deref(),
+        })
+        .collect();
+
+    write_rows(&rows, path, None)
+}
+
+/// Write log entries one per line under a `#` comment; returns the line count
+pub fn export_logs(entries: &[LogEntry], path: &Path, comment: &str) -> anyhow::Result<usize> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    writeln!(file, "# {}", comment)?;
+    for entry in entries {
+        writeln!(file, "{}", entry.line())?;
+    }
+    file.flush()?;
+
+    Ok(entries.len())
+}
+
+/// Orders oldest first, the order used by file-based exports
+pub fn sorted_by_time(orders: &[Order]) -> Vec<&Order> {
+    let mut orders: Vec<&Order> = orders.iter().collect();
+    orders.sort_by(|a, b| (a.order_timestamp, &a.order_id).cmp(&(b.order_timestamp, &b.order_id)));
+    orders
+}
+
+fn write_rows<T: Serialize>(
+    rows: &[T],
+    path: &Path,
+    comment: Option<&str>,
+) -> anyhow::Result<usize> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    match ExportFormat::from_path(path) {
+        ExportFormat::Csv => {
+            let mut file = std::fs::File::create(path)?;
+            if let Some(comment) = comment {
+                writeln!(file, "# {}", comment)?;
+            }
+
+            let mut writer = csv::Writer::from_writer(file);
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            writer.flush()?;
+        }
+        ExportFormat::Json => {
+            let json = serde_json::to_string_pretty(rows)?;
+            std::fs::write(path, json)?;
+        }
+    }
+
+    Ok(rows.len())
+}
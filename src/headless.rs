@@ -0,0 +1,352 @@
+use crate::app;
+use crate::data_structures::*;
+use crate::journal::{self, JournalQuery};
+use crate::market_calendar::MarketStatus;
+use crate::state::{AppState, Command, Config};
+use anyhow::Context;
+use axum::body::Bytes;
+use axum::extract::{Path, Query as QueryParams, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::DateTime;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+
+/// How often worker events are applied to the app state, standing in for the window's frames
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Requests waiting for the main task before handlers are made to wait
+const QUERY_BACKLOG: usize = 64;
+
+/// Tag on orders from POST /orders that carry none of their own
+const API_ORDER_TAG: &str = "api";
+
+/// Work a request handler needs done on the app state, which only the main task touches
+type Query = Box<dyn FnOnce(&mut AppState) + Send>;
+
+#[derive(Clone)]
+struct ServerState {
+    queries: mpsc::Sender<Query>,
+    api_token: Arc<str>,
+    journal_path: Option<Arc<str>>,
+}
+
+impl ServerState {
+    /// Run `query` against the app state on the main task and wait for its answer
+    async fn query<T: Send + 'static>(
+        &self,
+        query: impl FnOnce(&mut AppState) -> T + Send + 'static,
+    ) -> Result<T, StatusCode> {
+        let (reply, answer) = oneshot::channel();
+        self.queries
+            .send(Box::new(move |app_state| {
+                let _ = reply.send(query(app_state));
+            }))
+            .await
+            .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+        answer.await.map_err(|_| StatusCode::SERVICE_UNAVAILABLE)
+    }
+}
+
+/// Run without a window: the dashboard's workers and app state, with events applied on
+/// this task and a REST API on server.bind_address
+/// Returns after Ctrl+C, once the workers have shut down as they do when the window closes
+pub async fn run(config: Config) -> anyhow::Result<()> {
+    let (mut app_state, command_receivers) = AppState::new(config.clone());
+    let workers = app::start_backend(&mut app_state, command_receivers, &config, None);
+    if let Some(report) = crate::crash::take_pending() {
+        log::warn!("The last session crashed; report in {}", report.display());
+    }
+
+    let listener = match tokio::net::TcpListener::bind(&config.server.bind_address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            app::shutdown(&mut app_state, workers).await;
+            return Err(e).with_context(|| {
+                format!(
+                    "could not listen on server.bind_address {}",
+                    config.server.bind_address
+                )
+            });
+        }
+    };
+
+    let (queries_sender, mut queries) = mpsc::channel::<Query>(QUERY_BACKLOG);
+    let router = router(ServerState {
+        queries: queries_sender,
+        api_token: config.server.api_token.as_str().into(),
+        journal_path: config.app.journal_path.as_deref().map(Arc::from),
+    });
+    let server = tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            log::error!("REST API stopped: {}", e);
+        }
+    });
+
+    log::info!(
+        "Headless mode: REST API on http://{}",
+        config.server.bind_address
+    );
+    if config.server.api_token.is_empty() {
+        log::warn!("server.api_token is not set, so POST /orders is refused");
+    }
+    let loopback = config
+        .server
+        .bind_address
+        .parse::<std::net::SocketAddr>()
+        .is_ok_and(|address| address.ip().is_loopback());
+    if !loopback {
+        log::warn!(
+            "The REST API listens beyond this machine; anyone who can reach {} can read positions and orders",
+            config.server.bind_address
+        );
+    }
+
+    let mut events = tokio::time::interval(EVENT_POLL_INTERVAL);
+    events.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        tokio::select! {
+            Some(query) = queries.recv() => query(&mut app_state),
+            _ = events.tick() => {
+                let depth = app_state.pending_events();
+                let started = Instant::now();
+                app_state.process_events();
+                app_state.record_frame_metrics(depth, started.elapsed());
+            }
+            _ = &mut ctrl_c => break,
+        }
+    }
+
+    server.abort();
+    app::shutdown(&mut app_state, workers).await;
+    Ok(())
+}
+
+fn router(state: ServerState) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/positions", get(positions))
+        .route("/orders", get(orders).post(place_order))
+        .route("/pnl", get(pnl))
+        .route("/ticks/{token}", get(tick))
+        .route("/journal", get(journal_orders))
+        .with_state(state)
+}
+
+/// JSON error body with the given status
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+/// Connection, market and worker state, for monitoring
+async fn health(State(server): State<ServerState>) -> Response {
+    let health = server.query(|app_state| {
+        let metrics = app_state.metrics.read();
+        json!({
+            "status": "ok",
+            "mode": app_state.config.app.mode,
+            "connection": app_state.connection_state.read().label(),
+            "market": MarketStatus::now("NSE").label(),
+            "kill_switch": app_state.kill_switch,
+            "last_tick": metrics.last_tick_timestamp,
+            "ticks_processed": metrics.ticks_processed,
+            "worker_restarts": metrics.worker_restarts,
+        })
+    });
+    match health.await {
+        Ok(health) => Json(health).into_response(),
+        Err(status) => error(status, "the app is shutting down"),
+    }
+}
+
+async fn positions(State(server): State<ServerState>) -> Response {
+    let positions = server.query(|app_state| {
+        let mut positions: Vec<Position> = app_state
+            .positions
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+        positions
+            .sort_by(|a, b| (&a.exchange, &a.tradingsymbol).cmp(&(&b.exchange, &b.tradingsymbol)));
+        positions
+    });
+    match positions.await {
+        Ok(positions) => Json(positions).into_response(),
+        Err(status) => error(status, "the app is shutting down"),
+    }
+}
+
+/// Today's orders, newest first
+async fn orders(State(server): State<ServerState>) -> Response {
+    let orders = server.query(|app_state| {
+        let mut orders: Vec<Order> = app_state
+            .orders
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+        orders.sort_by_key(|order| std::cmp::Reverse(order.order_timestamp));
+        orders
+    });
+    match orders.await {
+        Ok(orders) => Json(orders).into_response(),
+        Err(status) => error(status, "the app is shutting down"),
+    }
+}
+
+async fn pnl(State(server): State<ServerState>) -> Response {
+    match server
+        .query(|app_state| app_state.calculate_total_pnl())
+        .await
+    {
+        Ok(pnl) => Json(pnl).into_response(),
+        Err(status) => error(status, "the app is shutting down"),
+    }
+}
+
+/// Latest tick of one instrument; 404 until it has streamed
+async fn tick(State(server): State<ServerState>, Path(token): Path<u32>) -> Response {
+    let tick = server.query(move |app_state| {
+        let tick = app_state.tick_data.get(&token).map(|tick| {
+            json!({
+                "instrument_token": tick.instrument_token,
+                "last_price": tick.last_price,
+                "last_quantity": tick.last_quantity,
+                "average_price": tick.average_price,
+                "volume": tick.volume,
+                "buy_quantity": tick.buy_quantity,
+                "sell_quantity": tick.sell_quantity,
+                "ohlc": {
+                    "open": tick.ohlc.open,
+                    "high": tick.ohlc.high,
+                    "low": tick.ohlc.low,
+                    "close": tick.ohlc.close,
+                },
+                "timestamp": DateTime::from_timestamp_nanos(tick.timestamp_nanos),
+            })
+        });
+        tick.ok_or_else(|| app_state.instrument_label(token))
+    });
+    match tick.await {
+        Ok(Ok(tick)) => Json(tick).into_response(),
+        Ok(Err(instrument)) => error(
+            StatusCode::NOT_FOUND,
+            format!("no ticks for instrument {}", instrument),
+        ),
+        Err(status) => error(status, "the app is shutting down"),
+    }
+}
+
+/// Journaled order requests, newest first, filtered by `tradingsymbol`, `since` (RFC 3339)
+/// and `limit`; read from the journal file directly, so they span past sessions too
+async fn journal_orders(
+    State(server): State<ServerState>,
+    QueryParams(query): QueryParams<JournalQuery>,
+) -> Response {
+    let Some(path) = server.journal_path.clone() else {
+        return error(
+            StatusCode::NOT_FOUND,
+            "the order journal is off; set app.journal_path to keep one",
+        );
+    };
+
+    let orders =
+        tokio::task::spawn_blocking(move || journal::query_orders(path.as_ref(), &query)).await;
+    match orders {
+        Ok(Ok(orders)) => Json(orders).into_response(),
+        Ok(Err(e)) => error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("could not read the journal: {}", e),
+        ),
+        Err(_) => error(StatusCode::INTERNAL_SERVER_ERROR, "the journal read failed"),
+    }
+}
+
+/// Place an order through the same checks as the dashboard's order form; 202 means it was
+/// handed to the API worker, and its fate shows in GET /orders
+async fn place_order(
+    State(server): State<ServerState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if server.api_token.is_empty() {
+        return error(
+            StatusCode::FORBIDDEN,
+            "POST endpoints are off until server.api_token is set",
+        );
+    }
+    if !authorized(&headers, &server.api_token) {
+        let mut response = error(StatusCode::UNAUTHORIZED, "missing or wrong bearer token");
+        response.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            header::HeaderValue::from_static("Bearer"),
+        );
+        return response;
+    }
+
+    let mut details: OrderRequest = match serde_json::from_slice(&body) {
+        Ok(details) => details,
+        Err(e) => return error(StatusCode::BAD_REQUEST, format!("invalid order: {}", e)),
+    };
+    // Callers don't get to skip checks or pose as another request
+    details.bypass_margin_check = false;
+    details.request_id = None;
+    let tag = details
+        .tag
+        .get_or_insert_with(|| API_ORDER_TAG.to_string())
+        .clone();
+
+    let placed = server.query(move |app_state| {
+        if let Some(reason) = app_state.trading_blocked_reason() {
+            return Err((StatusCode::CONFLICT, reason.to_string()));
+        }
+        app_state
+            .validate_order(&details)
+            .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+        app_state.add_log(
+            LogLevel::Info,
+            format!(
+                "REST API order: {} {} {} {}",
+                details.transaction_type,
+                details.quantity,
+                details.tradingsymbol,
+                details.order_type
+            ),
+            Some("server".to_string()),
+        );
+        app_state.send_command(Command::PlaceOrder { details });
+        Ok(())
+    });
+    match placed.await {
+        Ok(Ok(())) => (
+            StatusCode::ACCEPTED,
+            Json(json!({ "status": "accepted", "tag": tag })),
+        )
+            .into_response(),
+        Ok(Err((status, reason))) => error(status, reason),
+        Err(status) => error(status, "the app is shutting down"),
+    }
+}
+
+/// Whether the request carries the configured bearer token
+fn authorized(headers: &HeaderMap, api_token: &str) -> bool {
+    let Some(presented) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    // Every byte is compared, so the response time says nothing about how much matched
+    presented.len() == api_token.len()
+        && presented
+            .bytes()
+            .zip(api_token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
@@ -0,0 +1,349 @@
+use crate::candles::Candle;
+use crate::data_structures::Trade;
+use crate::state::{AppEvent, EventSender};
+use chrono::{DateTime, NaiveDate, Utc};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use rusqlite::{params, Connection, OpenFlags};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS candles (
+    instrument_token INTEGER NOT NULL,
+    interval TEXT NOT NULL,
+    ts INTEGER NOT NULL,
+    open REAL NOT NULL,
+    high REAL NOT NULL,
+    low REAL NOT NULL,
+    close REAL NOT NULL,
+    volume INTEGER NOT NULL,
+    PRIMARY KEY (instrument_token, interval, ts)
+);
+CREATE TABLE IF NOT EXISTS daily_pnl (
+    date TEXT PRIMARY KEY,
+    realized REAL NOT NULL,
+    unrealized_close REAL NOT NULL,
+    fees REAL
+);
+CREATE TABLE IF NOT EXISTS trades (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    date TEXT NOT NULL,
+    filled_at TEXT NOT NULL,
+    order_id TEXT NOT NULL,
+    tradingsymbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    transaction_type TEXT NOT NULL,
+    product TEXT NOT NULL,
+    quantity INTEGER NOT NULL,
+    price REAL NOT NULL,
+    tag TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_trades_date ON trades (date);
+";
+
+/// Candles are stored at the one-minute size the dashboard aggregates; larger sizes are
+/// resampled from them
+const MINUTE_INTERVAL: &str = "1m";
+
+/// Bars backfilled before today's: one NSE session
+const BACKFILL_CANDLES: usize = 375;
+
+/// Today's P&L row is rewritten at most this often, and once more on flush
+const PNL_WRITE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Something to keep beyond the session
+#[derive(Debug, Clone)]
+pub enum HistoryRecord {
+    /// Closed one-minute bars; a bar already stored for the same minute is replaced
+    Candles {
+        instrument_token: u32,
+        candles: Vec<Candle>,
+    },
+    /// The account's P&L so far on `date` (IST); the last one written stands as the close
+    DailyPnl {
+        date: NaiveDate,
+        realized: f64,
+        unrealized: f64,
+    },
+    Trade {
+        trade: Box<Trade>,
+        tag: Option<String>,
+    },
+}
+
+#[derive(Debug)]
+enum WriterMessage {
+    Record(HistoryRecord),
+    /// Stored bars of an instrument before `before`, answered with StoredCandlesLoaded
+    LoadCandles {
+        instrument_token: u32,
+        before: DateTime<Utc>,
+    },
+    Flush(Sender<()>),
+}
+
+/// One day of the daily_pnl table with its trade count
+#[derive(Debug, Clone)]
+pub struct DaySummary {
+    pub date: NaiveDate,
+    pub realized: f64,
+    pub unrealized_close: f64,
+    pub fees: Option<f64>,
+    pub trades: u32,
+}
+
+/// Handle for queueing history writes and candle lookups from the UI thread
+/// Like the journal, SQLite is only touched on a dedicated thread
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    sender: Sender<WriterMessage>,
+}
+
+impl HistoryStore {
+    /// Open (creating if needed) the history database and start its thread
+    pub fn open(path: impl AsRef<Path>, event_sender: EventSender) -> anyhow::Result<Self> {
+        let connection = Connection::open(path.as_ref())?;
+        connection.execute_batch(SCHEMA)?;
+
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        std::thread::Builder::new()
+            .name("history".to_string())
+            .spawn(move || Writer::new(connection, event_sender).run(receiver))?;
+
+        Ok(Self { sender })
+    }
+
+    pub fn record(&self, record: HistoryRecord) {
+        let _ = self.sender.send(WriterMessage::Record(record));
+    }
+
+    /// Ask for the session's worth of stored bars before `before`, for the chart
+    pub fn load_candles(&self, instrument_token: u32, before: DateTime<Utc>) {
+        let _ = self.sender.send(WriterMessage::LoadCandles {
+            instrument_token,
+            before,
+        });
+    }
+
+    /// Wait until everything queued so far is written; false if that took longer than
+    /// `timeout` or the thread is gone
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let (done, written) = crossbeam_channel::bounded(1);
+        self.sender.send(WriterMessage::Flush(done)).is_ok()
+            && written.recv_timeout(timeout).is_ok()
+    }
+}
+
+struct Writer {
+    connection: Connection,
+    event_sender: EventSender,
+    // Latest P&L not yet written, and when the row was last written
+    pending_pnl: Option<HistoryRecord>,
+    pnl_written_at: Option<Instant>,
+}
+
+impl Writer {
+    fn new(connection: Connection, event_sender: EventSender) -> Self {
+        Self {
+            connection,
+            event_sender,
+            pending_pnl: None,
+            pnl_written_at: None,
+        }
+    }
+
+    fn run(mut self, receiver: Receiver<WriterMessage>) {
+        loop {
+            let message = match receiver.recv_timeout(PNL_WRITE_INTERVAL) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => {
+                    self.write_pending_pnl();
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            match message {
+                WriterMessage::Record(record @ HistoryRecord::DailyPnl { date, .. }) => {
+                    // A new day's first sample must not overwrite the last day's close
+                    if matches!(self.pending_pnl, Some(HistoryRecord::DailyPnl { date: pending, .. }) if pending != date)
+                    {
+                        self.write_pending_pnl();
+                    }
+                    self.pending_pnl = Some(record);
+                    if self
+                        .pnl_written_at
+                        .is_none_or(|at| at.elapsed() >= PNL_WRITE_INTERVAL)
+                    {
+                        self.write_pending_pnl();
+                    }
+                }
+                WriterMessage::Record(record) => self.write_logged(&record),
+                WriterMessage::LoadCandles {
+                    instrument_token,
+                    before,
+                } => match self.candles_before(instrument_token, before) {
+                    Ok(candles) if candles.is_empty() => {}
+                    Ok(candles) => {
+                        let _ = self.event_sender.send(AppEvent::StoredCandlesLoaded {
+                            instrument_token,
+                            candles,
+                        });
+                    }
+                    Err(e) => log::error!(
+                        "Failed to read stored candles of {}: {}",
+                        instrument_token,
+                        e
+                    ),
+                },
+                WriterMessage::Flush(done) => {
+                    self.write_pending_pnl();
+                    let _ = done.send(());
+                }
+            }
+        }
+        self.write_pending_pnl();
+    }
+
+    fn write_pending_pnl(&mut self) {
+        if let Some(record) = self.pending_pnl.take() {
+            self.write_logged(&record);
+            self.pnl_written_at = Some(Instant::now());
+        }
+    }
+
+    fn write_logged(&self, record: &HistoryRecord) {
+        if let Err(e) = self.write(record) {
+            log::error!("Failed to store {:?}: {}", record, e);
+        }
+    }
+
+    fn write(&self, record: &HistoryRecord) -> anyhow::Result<()> {
+        match record {
+            HistoryRecord::Candles {
+                instrument_token,
+                candles,
+            } => {
+                let transaction = self.connection.unchecked_transaction()?;
+                {
+                    let mut statement = transaction.prepare_cached(
+                        "INSERT OR REPLACE INTO candles
+                            (instrument_token, interval, ts, open, high, low, close, volume)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    )?;
+                    for candle in candles {
+                        statement.execute(params![
+                            instrument_token,
+                            MINUTE_INTERVAL,
+                            candle.start.timestamp(),
+                            candle.open,
+                            candle.high,
+                            candle.low,
+                            candle.close,
+                            candle.volume as i64,
+                        ])?;
+                    }
+                }
+                transaction.commit()?;
+            }
+            HistoryRecord::DailyPnl {
+                date,
+                realized,
+                unrealized,
+            } => {
+                // Fees are left as they are; nothing in the dashboard knows them yet
+                self.connection.execute(
+                    "INSERT INTO daily_pnl (date, realized, unrealized_close) VALUES (?1, ?2, ?3)
+                     ON CONFLICT (date) DO UPDATE
+                     SET realized = excluded.realized, unrealized_close = excluded.unrealized_close",
+                    params![date.to_string(), realized, unrealized],
+                )?;
+            }
+            HistoryRecord::Trade { trade, tag } => {
+                let filled_at = trade.fill_timestamp;
+                self.connection.execute(
+                    "INSERT INTO trades (date, filled_at, order_id, tradingsymbol, exchange,
+                        transaction_type, product, quantity, price, tag)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    params![
+                        filled_at
+                            .with_timezone(&chrono_tz::Asia::Kolkata)
+                            .date_naive()
+                            .to_string(),
+                        filled_at.to_rfc3339(),
+                        trade.order_id,
+                        trade.tradingsymbol,
+                        trade.exchange,
+                        trade.transaction_type,
+                        trade.product,
+                        trade.quantity,
+                        trade.average_price,
+                        tag,
+                    ],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Up to BACKFILL_CANDLES bars before `before`, oldest first
+    fn candles_before(
+        &self,
+        instrument_token: u32,
+        before: DateTime<Utc>,
+    ) -> rusqlite::Result<Vec<Candle>> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT ts, open, high, low, close, volume FROM candles
+             WHERE instrument_token = ?1 AND interval = ?2 AND ts < ?3
+             ORDER BY ts DESC
+             LIMIT ?4",
+        )?;
+        let rows = statement.query_map(
+            params![
+                instrument_token,
+                MINUTE_INTERVAL,
+                before.timestamp(),
+                BACKFILL_CANDLES as i64
+            ],
+            |row| {
+                Ok(Candle {
+                    start: DateTime::from_timestamp(row.get(0)?, 0).unwrap_or_default(),
+                    open: row.get(1)?,
+                    high: row.get(2)?,
+                    low: row.get(3)?,
+                    close: row.get(4)?,
+                    volume: row.get::<_, i64>(5)?.max(0) as u64,
+                })
+            },
+        )?;
+        let mut candles = rows.collect::<Result<Vec<_>, _>>()?;
+        candles.reverse();
+        Ok(candles)
+    }
+}
+
+/// Daily P&L rows from `since` onwards, oldest first, read from a separate read-only
+/// connection
+pub fn daily_summaries(
+    path: impl AsRef<Path>,
+    since: NaiveDate,
+) -> anyhow::Result<Vec<DaySummary>> {
+    let connection = Connection::open_with_flags(path.as_ref(), OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut statement = connection.prepare(
+        "SELECT date, realized, unrealized_close, fees,
+                (SELECT COUNT(*) FROM trades WHERE trades.date = daily_pnl.date)
+         FROM daily_pnl
+         WHERE date >= ?1
+         ORDER BY date",
+    )?;
+    let rows = statement.query_map(params![since.to_string()], |row| {
+        let date: String = row.get(0)?;
+        Ok(DaySummary {
+            date: date.parse().unwrap_or_default(),
+            realized: row.get(1)?,
+            unrealized_close: row.get(2)?,
+            fees: row.get(3)?,
+            trades: row.get(4)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
@@ -0,0 +1,175 @@
+use crate::data_structures::Instrument;
+use dashmap::DashMap;
+use std::collections::BTreeSet;
+
+/// Lowercased search keys over the instrument master for symbol autocomplete
+/// Sorted by symbol so prefix matches come from a binary-searched range
+#[derive(Debug, Default)]
+pub struct InstrumentIndex {
+    entries: Vec<IndexEntry>,
+    facets: InstrumentFacets,
+}
+
+/// Distinct values the Instruments view offers as filters
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentFacets {
+    pub exchanges: BTreeSet<String>,
+    pub instrument_types: BTreeSet<String>,
+    pub segments: BTreeSet<String>,
+    // "YYYY-MM" months that have expiring contracts
+    pub expiry_months: BTreeSet<String>,
+}
+
+#[derive(Debug)]
+struct IndexEntry {
+    symbol: String,
+    name: String,
+    exchange: String,
+    instrument_token: u32,
+}
+
+impl InstrumentIndex {
+    pub fn build(instruments: &DashMap<u32, Instrument>) -> Self {
+        let mut facets = InstrumentFacets::default();
+        let mut entries: Vec<IndexEntry> = instruments
+            .iter()
+            .map(|instrument| {
+                facets.exchanges.insert(instrument.exchange.clone());
+                facets
+                    .instrument_types
+                    .insert(instrument.instrument_type.clone());
+                facets.segments.insert(instrument.segment.clone());
+                if let Some(month) = instrument.expiry.as_deref().and_then(expiry_month) {
+                    facets.expiry_months.insert(month.to_string());
+                }
+
+                IndexEntry {
+                    symbol: instrument.tradingsymbol.to_lowercase(),
+                    name: instrument.name.to_lowercase(),
+                    exchange: instrument.exchange.clone(),
+                    instrument_token: instrument.instrument_token,
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        Self { entries, facets }
+    }
+
+    pub fn facets(&self) -> &InstrumentFacets {
+        &self.facets
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Token of the instrument with exactly this symbol, preferring `exchange`
+    pub fn lookup(&self, tradingsymbol: &str, exchange: &str) -> Option<u32> {
+        let symbol = tradingsymbol.trim().to_lowercase();
+        let start = self
+            .entries
+            .partition_point(|entry| entry.symbol.as_str() < symbol.as_str());
+        let matches = self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.symbol == symbol);
+
+        let mut fallback = None;
+        for entry in matches {
+            if entry.exchange == exchange {
+                return Some(entry.instrument_token);
+            }
+            fallback.get_or_insert(entry.instrument_token);
+        }
+        fallback
+    }
+
+    /// Tokens of up to `limit` instruments on `exchange` matching `query`
+    /// Symbol prefix matches come first, then substring matches on symbol or name
+    pub fn search(&self, query: &str, exchange: &str, limit: usize) -> Vec<u32> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let start = self
+            .entries
+            .partition_point(|entry| entry.symbol.as_str() < query.as_str());
+        let mut matches: Vec<u32> = self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.symbol.starts_with(&query))
+            .filter(|entry| entry.exchange == exchange)
+            .take(limit)
+            .map(|entry| entry.instrument_token)
+            .collect();
+
+        if matches.len() < limit {
+            let remaining = limit - matches.len();
+            let substring_matches: Vec<u32> = self
+                .entries
+                .iter()
+                .filter(|entry| {
+                    entry.exchange == exchange
+                        && !entry.symbol.starts_with(&query)
+                        && (entry.symbol.contains(&query) || entry.name.contains(&query))
+                })
+                .take(remaining)
+                .map(|entry| entry.instrument_token)
+                .collect();
+            matches.extend(substring_matches);
+        }
+
+        matches
+    }
+}
+
+/// "YYYY-MM" part of an expiry date such as "2024-01-25"
+fn expiry_month(expiry: &str) -> Option<&str> {
+    expiry.get(..7).filter(|month| month.len() == 7)
+}
+
+/// Filters of the Instruments view; blank fields match everything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InstrumentQuery {
+    pub text: String,
+    pub exchange: String,
+    pub instrument_type: String,
+    pub segment: String,
+    pub expiry_month: String,
+}
+
+impl InstrumentQuery {
+    /// Tokens of every matching instrument, ordered by symbol then exchange
+    /// Scans the whole master, so callers run it off the UI thread
+    pub fn run(&self, instruments: &DashMap<u32, Instrument>) -> Vec<u32> {
+        let text = self.text.trim().to_lowercase();
+        let mut matches: Vec<(String, String, u32)> = instruments
+            .iter()
+            .filter(|instrument| self.matches(instrument, &text))
+            .map(|instrument| {
+                (
+                    instrument.tradingsymbol.clone(),
+                    instrument.exchange.clone(),
+                    instrument.instrument_token,
+                )
+            })
+            .collect();
+        matches.sort();
+
+        matches.into_iter().map(|(_, _, token)| token).collect()
+    }
+
+    fn matches(&self, instrument: &Instrument, text: &str) -> bool {
+        let field = |filter: &str, value: &str| filter.is_empty() || filter == value;
+
+        field(&self.exchange, &instrument.exchange)
+            && field(&self.instrument_type, &instrument.instrument_type)
+            && field(&self.segment, &instrument.segment)
+            && (self.expiry_month.is_empty()
+                || instrument.expiry.as_deref().and_then(expiry_month)
+                    == Some(self.expiry_month.as_str()))
+            && (text.is_empty()
+                || instrument.tradingsymbol.to_lowercase().contains(text)
+                || instrument.name.to_lowercase().contains(text))
+    }
+}
@@ -0,0 +1,328 @@
+use crate::data_structures::OrderRequest;
+use chrono::{DateTime, Utc};
+use crossbeam_channel::{Receiver, Sender};
+use rusqlite::{params, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS orders (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recorded_at TEXT NOT NULL,
+    action TEXT NOT NULL,
+    order_id TEXT,
+    tradingsymbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    transaction_type TEXT NOT NULL,
+    order_type TEXT NOT NULL,
+    product TEXT NOT NULL,
+    quantity INTEGER NOT NULL,
+    price REAL,
+    trigger_price REAL,
+    tag TEXT,
+    request_json TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS fills (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recorded_at TEXT NOT NULL,
+    order_id TEXT NOT NULL,
+    fill_price REAL NOT NULL,
+    fill_quantity INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS app_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recorded_at TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    order_id TEXT,
+    message TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_orders_symbol ON orders (tradingsymbol, recorded_at);
+";
+
+/// Something worth keeping in the audit trail
+#[derive(Debug, Clone)]
+pub enum JournalRecord {
+    /// An order request as sent to the broker ("place" or "modify")
+    OrderRequest {
+        action: &'static str,
+        order_id: Option<String>,
+        request: Box<OrderRequest>,
+    },
+    Fill {
+        order_id: String,
+        fill_price: f64,
+        fill_quantity: i32,
+    },
+    /// Order lifecycle outcome such as placed, cancelled or rejected
+    Event {
+        kind: &'static str,
+        order_id: Option<String>,
+        message: String,
+    },
+}
+
+/// What the writer thread receives: a record to write, or a flush to acknowledge once
+/// everything queued before it is written
+#[derive(Debug)]
+enum WriterMessage {
+    Record(DateTime<Utc>, JournalRecord),
+    Flush(Sender<()>),
+}
+
+/// Handle for queueing journal records from the UI thread
+/// Writes happen on a dedicated thread so the hot path never waits on SQLite
+#[derive(Debug, Clone)]
+pub struct Journal {
+    sender: Sender<WriterMessage>,
+}
+
+impl Journal {
+    /// Open (creating if needed) the journal database and start its writer thread
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let connection = Connection::open(path.as_ref())?;
+        connection.execute_batch(SCHEMA)?;
+
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        std::thread::Builder::new()
+            .name("journal".to_string())
+            .spawn(move || Self::write_loop(connection, receiver))?;
+
+        Ok(Self { sender })
+    }
+
+    /// Queue a record stamped with the current time
+    pub fn record(&self, record: JournalRecord) {
+        let _ = self.sender.send(WriterMessage::Record(Utc::now(), record));
+    }
+
+    /// Wait until every record queued so far is written; false if that took longer
+    /// than `timeout` or the writer thread is gone
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let (done, written) = crossbeam_channel::bounded(1);
+        self.sender.send(WriterMessage::Flush(done)).is_ok()
+            && written.recv_timeout(timeout).is_ok()
+    }
+
+    fn write_loop(connection: Connection, receiver: Receiver<WriterMessage>) {
+        while let Ok(message) = receiver.recv() {
+            match message {
+                WriterMessage::Record(recorded_at, record) => {
+                    if let Err(e) = Self::write(&connection, recorded_at, &record) {
+                        log::error!("Failed to journal {:?}: {}", record, e);
+                    }
+                }
+                WriterMessage::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+
+    fn write(
+        connection: &Connection,
+        recorded_at: DateTime<Utc>,
+        record: &JournalRecord,
+    ) -> anyhow::Result<()> {
+        let recorded_at = recorded_at.to_rfc3339();
+
+        match record {
+            JournalRecord::OrderRequest {
+                action,
+                order_id,
+                request,
+            } => {
+                connection.execute(
+                    "INSERT INTO orders (recorded_at, action, order_id, tradingsymbol, exchange,
+                        transaction_type, order_type, product, quantity, price, trigger_price,
+                        tag, request_json)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                    params![
+                        recorded_at,
+                        action,
+                        order_id,
+                        request.tradingsymbol,
+                        request.exchange,
+                        request.transaction_type,
+                        request.order_type,
+                        request.product,
+                        request.quantity,
+                        request.price,
+                        request.trigger_price,
+                        request.tag,
+                        serde_json::to_string(request)?,
+                    ],
+                )?;
+            }
+            JournalRecord::Fill {
+                order_id,
+                fill_price,
+                fill_quantity,
+            } => {
+                connection.execute(
+                    "INSERT INTO fills (recorded_at, order_id, fill_price, fill_quantity)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![recorded_at, order_id, fill_price, fill_quantity],
+                )?;
+            }
+            JournalRecord::Event {
+                kind,
+                order_id,
+                message,
+            } => {
+                connection.execute(
+                    "INSERT INTO app_events (recorded_at, kind, order_id, message)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![recorded_at, kind, order_id, message],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// One row of the orders table
+#[derive(Debug, Clone, Serialize)]
+pub struct JournaledOrder {
+    pub recorded_at: DateTime<Utc>,
+    pub action: String,
+    pub order_id: Option<String>,
+    pub tradingsymbol: String,
+    pub exchange: String,
+    pub transaction_type: String,
+    pub order_type: String,
+    pub product: String,
+    pub quantity: i32,
+    pub price: Option<f64>,
+    pub trigger_price: Option<f64>,
+    pub tag: Option<String>,
+}
+
+/// Filters for reading back journaled orders, newest first
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct JournalQuery {
+    pub tradingsymbol: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+/// Read journaled orders from a separate read-only connection
+pub fn query_orders(
+    path: impl AsRef<Path>,
+    query: &JournalQuery,
+) -> anyhow::Result<Vec<JournaledOrder>> {
+    let connection = Connection::open_with_flags(path.as_ref(), OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let mut statement = connection.prepare(
+        "SELECT recorded_at, action, order_id, tradingsymbol, exchange, transaction_type,
+                order_type, product, quantity, price, trigger_price, tag
+         FROM orders
+         WHERE (?1 IS NULL OR tradingsymbol = ?1)
+           AND (?2 IS NULL OR recorded_at >= ?2)
+         ORDER BY recorded_at DESC
+         LIMIT ?3",
+    )?;
+
+    let limit = query.limit.map(|limit| limit as i64).unwrap_or(-1);
+    let since = query.since.map(|since| since.to_rfc3339());
+
+    let rows = statement.query_map(params![query.tradingsymbol, since, limit], |row| {
+        let recorded_at: String = row.get(0)?;
+        Ok(JournaledOrder {
+            recorded_at: DateTime::parse_from_rfc3339(&recorded_at)
+                .map(|at| at.with_timezone(&Utc))
+                .unwrap_or_default(),
+            action: row.get(1)?,
+            order_id: row.get(2)?,
+            tradingsymbol: row.get(3)?,
+            exchange: row.get(4)?,
+            transaction_type: row.get(5)?,
+            order_type: row.get(6)?,
+            product: row.get(7)?,
+            quantity: row.get(8)?,
+            price: row.get(9)?,
+            trigger_price: row.get(10)?,
+            tag: row.get(11)?,
+        })
+    })?;
+
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh journal file in the temp directory, removed when dropped
+    struct TempJournal(std::path::PathBuf);
+
+    impl TempJournal {
+        fn new() -> Self {
+            Self(std::env::temp_dir().join(format!("journal-{}.db", uuid::Uuid::new_v4())))
+        }
+    }
+
+    impl Drop for TempJournal {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn placed(journal: &Journal, order_id: &str, tradingsymbol: &str, quantity: i32) {
+        journal.record(JournalRecord::OrderRequest {
+            action: "place",
+            order_id: Some(order_id.to_string()),
+            request: Box::new(OrderRequest::market(tradingsymbol, "BUY", quantity)),
+        });
+    }
+
+    fn order_ids(orders: &[JournaledOrder]) -> Vec<&str> {
+        orders
+            .iter()
+            .filter_map(|order| order.order_id.as_deref())
+            .collect()
+    }
+
+    #[test]
+    fn query_reads_back_orders_newest_first() {
+        let file = TempJournal::new();
+        let journal = Journal::open(&file.0).unwrap();
+        placed(&journal, "ORD1", "INFY", 5);
+        placed(&journal, "ORD2", "TCS", 1);
+        placed(&journal, "ORD3", "INFY", 2);
+        assert!(journal.flush(Duration::from_secs(5)));
+
+        let all = query_orders(&file.0, &JournalQuery::default()).unwrap();
+        assert_eq!(order_ids(&all), ["ORD3", "ORD2", "ORD1"]);
+        assert_eq!(
+            (all[2].action.as_str(), all[2].quantity, all[2].price),
+            ("place", 5, None)
+        );
+
+        let infy = JournalQuery {
+            tradingsymbol: Some("INFY".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            order_ids(&query_orders(&file.0, &infy).unwrap()),
+            ["ORD3", "ORD1"]
+        );
+
+        let latest = JournalQuery {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            order_ids(&query_orders(&file.0, &latest).unwrap()),
+            ["ORD3"]
+        );
+
+        let later = JournalQuery {
+            since: Some(Utc::now() + chrono::TimeDelta::minutes(1)),
+            ..Default::default()
+        };
+        assert!(query_orders(&file.0, &later).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::data_structures::LogLevel;
+use crate::state::{AppEvent, EventSender};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use parking_lot::RwLock;
+use std::io::Write;
+
+/// Target prefix of the dashboard's own log calls
+const CRATE_TARGET: &str = env!("CARGO_CRATE_NAME");
+
+static LOGGER: Bridge = Bridge {
+    stderr: RwLock::new(Filter::new()),
+    app: RwLock::new(None),
+};
+
+/// Install the bridge as the `log` backend: records go to stderr as RUST_LOG selects,
+/// and to the Logs tab once `attach` is given the app's event sender
+pub fn init() {
+    let filter = std::env::var("RUST_LOG")
+        .map(|spec| Filter::parse(&spec))
+        .unwrap_or_else(|_| Filter::new());
+    log::set_max_level(filter.max_level().max(LevelFilter::Info));
+    *LOGGER.stderr.write() = filter;
+    if let Err(e) = log::set_logger(&LOGGER) {
+        eprintln!("Logging is already set up: {}", e);
+    }
+}
+
+/// Forward records to the app from now on; records logged before this only reach stderr
+pub fn attach(event_sender: EventSender) {
+    *LOGGER.app.write() = Some(event_sender);
+}
+
+/// Write an add_log entry to stderr when RUST_LOG enables its module, the other half of
+/// the bridge
+pub fn echo(level: LogLevel, module: Option<&str>, message: &str) {
+    let level = match level {
+        LogLevel::Error => Level::Error,
+        LogLevel::Warning => Level::Warn,
+        LogLevel::Info => Level::Info,
+        LogLevel::Debug => Level::Debug,
+    };
+    let target = module.unwrap_or("app");
+    if level <= LOGGER.stderr.read().level_for(target) {
+        write_stderr(level, target, format_args!("{}", message));
+    }
+}
+
+fn write_stderr(level: Level, target: &str, message: std::fmt::Arguments) {
+    let _ = writeln!(
+        std::io::stderr().lock(),
+        "[{} {:<5} {}] {}",
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+        level,
+        target,
+        message
+    );
+}
+
+/// RUST_LOG in env_logger's form: a default level and `target=level` overrides, comma
+/// separated, e.g. `info,reqwest=warn`
+/// The longest matching target wins; a `/regex` message filter is ignored
+#[derive(Debug)]
+struct Filter {
+    default: LevelFilter,
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    /// env_logger's default when RUST_LOG is unset: errors only
+    const fn new() -> Self {
+        Self {
+            default: LevelFilter::Error,
+            directives: Vec::new(),
+        }
+    }
+
+    fn parse(spec: &str) -> Self {
+        let mut filter = Self::new();
+        let spec = spec.split('/').next().unwrap_or_default();
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => match level.trim().parse() {
+                    Ok(level) => filter.directives.push((target.trim().to_string(), level)),
+                    Err(_) => eprintln!("RUST_LOG: ignoring {}", directive),
+                },
+                // A bare word is a level, or a target to log everything from
+                None => match directive.parse() {
+                    Ok(level) => filter.default = level,
+                    Err(_) => filter
+                        .directives
+                        .push((directive.to_string(), LevelFilter::Trace)),
+                },
+            }
+        }
+        // Longest first, so the first match is the most specific
+        filter
+            .directives
+            .sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        filter
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.directives
+            .iter()
+            .find(|(prefix, _)| {
+                target == prefix
+                    || target
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+/// `log` backend feeding both the terminal and the in-app log buffer
+struct Bridge {
+    stderr: RwLock<Filter>,
+    app: RwLock<Option<EventSender>>,
+}
+
+impl Bridge {
+    /// The Logs tab gets the dashboard's own info and above, other crates' warnings and
+    /// above, and whatever RUST_LOG turns on for the terminal
+    fn wants_in_app(target: &str, level: Level) -> bool {
+        let threshold = if own_path(target).is_some() {
+            Level::Info
+        } else {
+            Level::Warn
+        };
+        level <= threshold
+    }
+}
+
+impl Log for Bridge {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.stderr.read().level_for(metadata.target())
+            || Self::wants_in_app(metadata.target(), metadata.level())
+    }
+
+    fn log(&self, record: &Record) {
+        let to_stderr = record.level() <= self.stderr.read().level_for(record.target());
+        if to_stderr {
+            write_stderr(record.level(), record.target(), *record.args());
+        }
+        if !(to_stderr || Self::wants_in_app(record.target(), record.level())) {
+            return;
+        }
+        if let Some(event_sender) = self.app.read().as_ref() {
+            let level = match record.level() {
+                Level::Error => LogLevel::Error,
+                Level::Warn => LogLevel::Warning,
+                Level::Info => LogLevel::Info,
+                Level::Debug | Level::Trace => LogLevel::Debug,
+            };
+            let _ = event_sender.send(AppEvent::LogRecord {
+                level,
+                message: record.args().to_string(),
+                module: Some(module_name(record.target())),
+            });
+        }
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+/// Module path within the dashboard, empty for main.rs, or None for another crate
+fn own_path(target: &str) -> Option<&str> {
+    target
+        .strip_prefix(CRATE_TARGET)
+        .filter(|path| path.is_empty() || path.starts_with("::"))
+}
+
+/// Module filter name for a target: the last path segment for the dashboard's own
+/// modules, in line with add_log's names, and the crate name for libraries
+fn module_name(target: &str) -> String {
+    match own_path(target) {
+        Some("") => "main",
+        Some(path) => path.rsplit("::").next().unwrap_or(path),
+        None => target.split("::").next().unwrap_or(target),
+    }
+    .to_string()
+}
@@ -0,0 +1,248 @@
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Asia::Kolkata;
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+/// Optional override for the embedded holiday table, next to config.toml
+pub const HOLIDAYS_FILE: &str = "holidays.toml";
+
+/// NSE/BSE trading holidays for 2026 as published by the exchanges
+/// Weekend holidays are omitted since weekends are closed anyway
+const EMBEDDED_HOLIDAYS: &[(&str, &str)] = &[
+    ("2026-01-26", "Republic Day"),
+    ("2026-03-03", "Holi"),
+    ("2026-03-26", "Shri Ram Navami"),
+    ("2026-03-31", "Shri Mahavir Jayanti"),
+    ("2026-04-03", "Good Friday"),
+    ("2026-04-14", "Dr. Baba Saheb Ambedkar Jayanti"),
+    ("2026-05-01", "Maharashtra Day"),
+    ("2026-05-28", "Bakri Id"),
+    ("2026-06-26", "Muharram"),
+    ("2026-09-14", "Ganesh Chaturthi"),
+    ("2026-10-02", "Mahatma Gandhi Jayanti"),
+    ("2026-10-20", "Dussehra"),
+    ("2026-11-10", "Diwali Balipratipada"),
+    ("2026-11-24", "Prakash Gurpurb Sri Guru Nanak Dev"),
+    ("2026-12-25", "Christmas"),
+];
+
+/// Trading session an exchange is in at a given moment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarketStatus {
+    PreOpen,
+    Open,
+    ClosingSession,
+    EveningSession,
+    Closed(ClosedReason),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClosedReason {
+    Weekend,
+    Holiday(String),
+    OutsideHours,
+}
+
+impl MarketStatus {
+    /// Current status of an exchange, evaluated in IST regardless of the system timezone
+    pub fn now(exchange: &str) -> Self {
+        Self::at(exchange, ist_now())
+    }
+
+    /// Status of an exchange at a specific IST instant
+    pub fn at(exchange: &str, now: DateTime<Tz>) -> Self {
+        if matches!(now.weekday(), Weekday::Sat | Weekday::Sun) {
+            return MarketStatus::Closed(ClosedReason::Weekend);
+        }
+
+        if let Some(name) = MarketCalendar::global().holiday(now.date_naive()) {
+            return MarketStatus::Closed(ClosedReason::Holiday(name.to_string()));
+        }
+
+        let time = now.time();
+        let between = |start: (u32, u32), end: (u32, u32)| {
+            time >= NaiveTime::from_hms_opt(start.0, start.1, 0).unwrap()
+                && time < NaiveTime::from_hms_opt(end.0, end.1, 0).unwrap()
+        };
+
+        match exchange {
+            // Commodity segment trades through the evening
+            "MCX" => {
+                if between((9, 0), (17, 0)) {
+                    MarketStatus::Open
+                } else if between((17, 0), (23, 30)) {
+                    MarketStatus::EveningSession
+                } else {
+                    MarketStatus::Closed(ClosedReason::OutsideHours)
+                }
+            }
+            // Currency derivatives
+            "CDS" | "BCD" => {
+                if between((9, 0), (17, 0)) {
+                    MarketStatus::Open
+                } else {
+                    MarketStatus::Closed(ClosedReason::OutsideHours)
+                }
+            }
+            // Equity derivatives have no pre-open auction
+            "NFO" | "BFO" => {
+                if between((9, 15), (15, 30)) {
+                    MarketStatus::Open
+                } else {
+                    MarketStatus::Closed(ClosedReason::OutsideHours)
+                }
+            }
+            // NSE, BSE cash market
+            _ => {
+                if between((9, 0), (9, 15)) {
+                    MarketStatus::PreOpen
+                } else if between((9, 15), (15, 30)) {
+                    MarketStatus::Open
+                } else if between((15, 30), (16, 0)) {
+                    MarketStatus::ClosingSession
+                } else {
+                    MarketStatus::Closed(ClosedReason::OutsideHours)
+                }
+            }
+        }
+    }
+
+    /// Whether ticks are expected to flow in this status
+    pub fn is_trading(&self) -> bool {
+        !matches!(self, MarketStatus::Closed(_))
+    }
+
+    /// Short human-readable description for status widgets
+    pub fn label(&self) -> String {
+        match self {
+            MarketStatus::PreOpen => "Pre-Open".to_string(),
+            MarketStatus::Open => "Market Open".to_string(),
+            MarketStatus::ClosingSession => "Closing Session".to_string(),
+            MarketStatus::EveningSession => "Evening Session".to_string(),
+            MarketStatus::Closed(ClosedReason::Weekend) => "Market Closed (Weekend)".to_string(),
+            MarketStatus::Closed(ClosedReason::Holiday(name)) => {
+                format!("Market Closed ({})", name)
+            }
+            MarketStatus::Closed(ClosedReason::OutsideHours) => "Market Closed".to_string(),
+        }
+    }
+}
+
+/// Exchange holiday list, loaded once from holidays.toml or the embedded table
+#[derive(Debug, Default)]
+pub struct MarketCalendar {
+    holidays: Vec<(NaiveDate, String)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HolidayFile {
+    holidays: Vec<HolidayEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HolidayEntry {
+    date: NaiveDate,
+    name: String,
+}
+
+impl MarketCalendar {
+    /// Shared calendar instance, loaded on first use
+    pub fn global() -> &'static MarketCalendar {
+        static CALENDAR: OnceLock<MarketCalendar> = OnceLock::new();
+        CALENDAR.get_or_init(|| match Self::load(HOLIDAYS_FILE) {
+            Ok(Some(calendar)) => calendar,
+            Ok(None) => Self::embedded(),
+            Err(e) => {
+                log::error!(
+                    "Failed to load {}: {}, using embedded holidays",
+                    HOLIDAYS_FILE,
+                    e
+                );
+                Self::embedded()
+            }
+        })
+    }
+
+    /// Holiday table compiled into the binary
+    pub fn embedded() -> Self {
+        let holidays = EMBEDDED_HOLIDAYS
+            .iter()
+            .filter_map(|(date, name)| {
+                NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .ok()
+                    .map(|date| (date, name.to_string()))
+            })
+            .collect();
+
+        Self { holidays }
+    }
+
+    /// Load a holiday override file; returns None when the file does not exist
+    ///
+    /// ```toml
+    /// [[holidays]]
+    /// date = "2026-01-26"
+    /// name = "Republic Day"
+    /// ```
+    pub fn load(path: &str) -> anyhow::Result<Option<Self>> {
+        if !std::path::Path::new(path).exists() {
+            return Ok(None);
+        }
+
+        let file: HolidayFile = toml::from_str(&std::fs::read_to_string(path)?)?;
+        let holidays = file
+            .holidays
+            .into_iter()
+            .map(|entry| (entry.date, entry.name))
+            .collect();
+
+        Ok(Some(Self { holidays }))
+    }
+
+    /// Name of the holiday on a given date, if any
+    pub fn holiday(&self, date: NaiveDate) -> Option<&str> {
+        self.holidays
+            .iter()
+            .find(|(holiday, _)| *holiday == date)
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+/// Current time in IST for display next to market status
+pub fn ist_now() -> DateTime<Tz> {
+    Utc::now().with_timezone(&Kolkata)
+}
+
+/// Start of the current IST day
+pub fn ist_midnight() -> DateTime<Utc> {
+    // IST has no daylight saving, so midnight maps to exactly one instant
+    ist_now()
+        .date_naive()
+        .and_time(NaiveTime::MIN)
+        .and_local_timezone(Kolkata)
+        .single()
+        .map_or_else(Utc::now, |midnight| midnight.with_timezone(&Utc))
+}
+
+/// Kite access tokens stop working at 06:00 IST the morning after they are issued
+const TOKEN_EXPIRY_HOUR: u32 = 6;
+
+/// When an access token issued at a given moment expires; one issued before 06:00 IST
+/// lasts only until 06:00 that same morning
+pub fn token_expiry(issued_at: DateTime<Utc>) -> DateTime<Utc> {
+    let issued = issued_at.with_timezone(&Kolkata);
+    let mut expiry = issued
+        .date_naive()
+        .and_hms_opt(TOKEN_EXPIRY_HOUR, 0, 0)
+        .expect("valid time of day");
+    if issued.naive_local() >= expiry {
+        expiry += chrono::Duration::days(1);
+    }
+    // IST has no daylight saving, so every local time maps to exactly one instant
+    expiry
+        .and_local_timezone(Kolkata)
+        .single()
+        .map(|expiry| expiry.with_timezone(&Utc))
+        .unwrap_or(issued_at)
+}
@@ -0,0 +1,173 @@
+use crate::broadcast::Broadcaster;
+use crate::state::{ConnectionState, PerformanceMetrics, API_LATENCY_BUCKETS};
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use parking_lot::RwLock;
+use std::fmt::Write;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+/// Prefix of every exported metric name
+const PREFIX: &str = "trading_dashboard";
+
+/// What the exporter reads: the status bar's metrics, shared with AppState
+#[derive(Clone)]
+struct Sources {
+    metrics: Arc<RwLock<PerformanceMetrics>>,
+    connection_state: Arc<RwLock<ConnectionState>>,
+    broadcaster: Option<Broadcaster>,
+}
+
+/// Serve /metrics in the Prometheus text format on 127.0.0.1:`port`
+pub fn start(
+    port: u16,
+    metrics: Arc<RwLock<PerformanceMetrics>>,
+    connection_state: Arc<RwLock<ConnectionState>>,
+    broadcaster: Option<Broadcaster>,
+) {
+    let router = Router::new()
+        .route("/metrics", get(metrics_page))
+        .with_state(Sources {
+            metrics,
+            connection_state,
+            broadcaster,
+        });
+    tokio::spawn(async move {
+        let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        let listener = match tokio::net::TcpListener::bind(address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::error!("Metrics exporter could not listen on {}: {}", address, e);
+                return;
+            }
+        };
+        log::info!("Metrics on http://{}/metrics", address);
+        if let Err(e) = axum::serve(listener, router).await {
+            log::error!("Metrics exporter stopped: {}", e);
+        }
+    });
+}
+
+async fn metrics_page(State(sources): State<Sources>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render(&sources),
+    )
+}
+
+fn render(sources: &Sources) -> String {
+    let mut out = String::new();
+    let metrics = sources.metrics.read();
+    let now = chrono::Utc::now();
+    let seconds = metrics.tick_rate.last_minute(now);
+
+    let mut sample = |name: &str, kind: &str, help: &str, value: f64| {
+        let _ = writeln!(out, "# HELP {PREFIX}_{name} {help}");
+        let _ = writeln!(out, "# TYPE {PREFIX}_{name} {kind}");
+        let _ = writeln!(out, "{PREFIX}_{name} {value}");
+    };
+    sample(
+        "ticks_processed_total",
+        "counter",
+        "Ticks applied since startup",
+        metrics.ticks_processed as f64,
+    );
+    sample(
+        "ticks_per_second",
+        "gauge",
+        "Ticks received during the last full second",
+        seconds[seconds.len() - 1].ticks as f64,
+    );
+    if let Some(p95) = crate::state::TickRateWindow::p95_latency_ms(&seconds) {
+        sample(
+            "tick_latency_p95_seconds",
+            "gauge",
+            "95th percentile of per-second average tick latency over the last minute",
+            p95 / 1000.0,
+        );
+    }
+    if let Some(last_tick) = metrics.last_tick_timestamp {
+        sample(
+            "last_tick_timestamp_seconds",
+            "gauge",
+            "Exchange timestamp of the latest tick",
+            last_tick.timestamp_millis() as f64 / 1000.0,
+        );
+    }
+    sample(
+        "orders_placed_total",
+        "counter",
+        "Orders the broker accepted since startup",
+        metrics.orders_processed as f64,
+    );
+    sample(
+        "websocket_connected",
+        "gauge",
+        "1 while the market data feed is connected",
+        f64::from(u8::from(
+            *sources.connection_state.read() == ConnectionState::Connected,
+        )),
+    );
+    sample(
+        "websocket_reconnections_total",
+        "counter",
+        "Reconnection attempts of the market data feed",
+        f64::from(metrics.websocket_reconnections),
+    );
+    sample(
+        "worker_restarts_total",
+        "counter",
+        "Worker restarts after panics",
+        f64::from(metrics.worker_restarts),
+    );
+    sample(
+        "event_channel_depth",
+        "gauge",
+        "Worker events waiting when last sampled",
+        metrics.event_channel_depth as f64,
+    );
+    sample(
+        "event_channel_depth_max",
+        "gauge",
+        "Most worker events waiting at once during the last minute",
+        metrics.max_event_channel_depth_last_minute as f64,
+    );
+    sample(
+        "process_events_seconds",
+        "gauge",
+        "Time the latest pass over worker events took",
+        metrics.process_events_time_us as f64 / 1_000_000.0,
+    );
+    sample(
+        "frames_per_second",
+        "gauge",
+        "Frames drawn during the last full second; 0 in headless mode",
+        f64::from(metrics.frames_per_second),
+    );
+    if let Some(broadcaster) = &sources.broadcaster {
+        sample(
+            "broadcast_dropped_tick_batches_total",
+            "counter",
+            "Tick batches dropped for slow event broadcast clients",
+            broadcaster.dropped_tick_batches() as f64,
+        );
+    }
+
+    let name = format!("{PREFIX}_api_request_duration_seconds");
+    let latency = &metrics.api_latency;
+    let _ = writeln!(
+        out,
+        "# HELP {name} Time the API worker took per command or background refresh"
+    );
+    let _ = writeln!(out, "# TYPE {name} histogram");
+    for (bound, count) in API_LATENCY_BUCKETS.iter().zip(&latency.buckets) {
+        let _ = writeln!(out, "{name}_bucket{{le=\"{bound}\"}} {count}");
+    }
+    let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {}", latency.count);
+    let _ = writeln!(out, "{name}_sum {}", latency.sum_seconds);
+    let _ = writeln!(out, "{name}_count {}", latency.count);
+    out
+}
@@ -0,0 +1,346 @@
+use crate::data_structures::LogLevel;
+use crate::redact;
+use crate::state::{AppEvent, EventSender};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Conditions that can flap are sent at most this often per kind; the rest are counted
+/// into the next message
+const FLAPPY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Attempts per destination before a message is given up on
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Wait before the second attempt, doubled for each one after
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The [notifier] section: where fills, rejections and connection trouble are sent when
+/// nobody is watching the window
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifierConfig {
+    /// Bot token from @BotFather; leave empty and set telegram_bot_token_command to keep
+    /// it in a keyring instead
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub telegram_bot_token: String,
+    /// Command printing the bot token, e.g. `secret-tool lookup service telegram`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telegram_bot_token_command: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub telegram_chat_id: String,
+    /// Receives a JSON POST per message: {"kind", "text", "timestamp"}
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub webhook_url: String,
+}
+
+impl std::fmt::Debug for NotifierConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NotifierConfig")
+            .field(
+                "telegram_bot_token",
+                &redact::mask(&self.telegram_bot_token),
+            )
+            .field(
+                "telegram_bot_token_command",
+                &self.telegram_bot_token_command,
+            )
+            .field("telegram_chat_id", &self.telegram_chat_id)
+            // Webhook URLs often embed their own secret
+            .field("webhook_url", &redact::mask(&self.webhook_url))
+            .finish()
+    }
+}
+
+impl NotifierConfig {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn has_telegram_token(&self) -> bool {
+        !self.telegram_bot_token.trim().is_empty()
+            || self
+                .telegram_bot_token_command
+                .as_deref()
+                .is_some_and(|command| !command.trim().is_empty())
+    }
+
+    /// Readable message per problem with the section
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let has_chat = !self.telegram_chat_id.trim().is_empty();
+        if self.has_telegram_token() && !has_chat {
+            problems.push("notifier.telegram_chat_id is needed with a bot token".to_string());
+        }
+        if has_chat && !self.has_telegram_token() {
+            problems.push(
+                "notifier.telegram_chat_id is set without telegram_bot_token or telegram_bot_token_command"
+                    .to_string(),
+            );
+        }
+        if !self.webhook_url.is_empty()
+            && !url::Url::parse(&self.webhook_url)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+        {
+            problems.push("notifier.webhook_url must be an http or https URL".to_string());
+        }
+        problems
+    }
+}
+
+/// What a message is about; conditions that flap are rate limited per kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    Fill,
+    Rejection,
+    Disconnected,
+    AuthExpired,
+}
+
+impl AlertKind {
+    fn name(self) -> &'static str {
+        match self {
+            AlertKind::Fill => "fill",
+            AlertKind::Rejection => "rejection",
+            AlertKind::Disconnected => "disconnected",
+            AlertKind::AuthExpired => "auth_expired",
+        }
+    }
+
+    fn flaps(self) -> bool {
+        matches!(self, AlertKind::Disconnected | AlertKind::AuthExpired)
+    }
+}
+
+enum Request {
+    Send(AlertKind, String),
+    Reconfigure(NotifierConfig),
+    /// Deliver a test message with settings that may not be saved yet, answered with
+    /// NotificationTested
+    Test(NotifierConfig),
+}
+
+/// Sends remote notifications from a background task, so a slow or unreachable
+/// destination never holds up the UI
+pub struct Notifier {
+    requests: mpsc::UnboundedSender<Request>,
+}
+
+impl Notifier {
+    /// Start the task; with no destinations configured, messages are dropped until a
+    /// reload adds one
+    pub fn start(config: NotifierConfig, event_sender: EventSender) -> Self {
+        let (requests, receiver) = mpsc::unbounded_channel();
+        let _ = requests.send(Request::Reconfigure(config));
+        tokio::spawn(run(receiver, event_sender));
+        Self { requests }
+    }
+
+    pub fn send(&self, kind: AlertKind, message: String) {
+        let _ = self.requests.send(Request::Send(kind, message));
+    }
+
+    pub fn reconfigure(&self, config: NotifierConfig) {
+        let _ = self.requests.send(Request::Reconfigure(config));
+    }
+
+    pub fn test(&self, config: NotifierConfig) {
+        let _ = self.requests.send(Request::Test(config));
+    }
+}
+
+/// Resolved places to send to
+#[derive(Default)]
+struct Destinations {
+    telegram: Option<(String, String)>, // bot token, chat id
+    webhook: Option<String>,
+}
+
+impl Destinations {
+    async fn resolve(config: &NotifierConfig) -> Result<Self, String> {
+        let mut destinations = Self::default();
+        if !config.telegram_chat_id.trim().is_empty() {
+            let token = if config.telegram_bot_token.trim().is_empty() {
+                match config.telegram_bot_token_command.as_deref() {
+                    Some(command) => run_secret_command(command).await?,
+                    None => String::new(),
+                }
+            } else {
+                config.telegram_bot_token.trim().to_string()
+            };
+            if !token.is_empty() {
+                destinations.telegram = Some((token, config.telegram_chat_id.trim().to_string()));
+            }
+        }
+        if !config.webhook_url.is_empty() {
+            destinations.webhook = Some(config.webhook_url.clone());
+        }
+        Ok(destinations)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.telegram.is_none() && self.webhook.is_none()
+    }
+
+    /// Deliver to every destination, returning the first failure
+    async fn deliver(
+        &self,
+        client: &reqwest::Client,
+        kind: &str,
+        text: &str,
+    ) -> Result<(), String> {
+        let mut result = Ok(());
+        if let Some((token, chat_id)) = &self.telegram {
+            let url = format!("https://api.telegram.org/bot{}/sendMessage", token);
+            let body = json!({ "chat_id": chat_id, "text": text });
+            if let Err(e) = post_with_retry(client, &url, &body).await {
+                result = Err(format!("Telegram: {}", e));
+            }
+        }
+        if let Some(url) = &self.webhook {
+            let body = json!({
+                "kind": kind,
+                "text": text,
+                "timestamp": chrono::Utc::now(),
+            });
+            if let Err(e) = post_with_retry(client, url, &body).await {
+                result = result.and(Err(format!("webhook: {}", e)));
+            }
+        }
+        result
+    }
+}
+
+/// First line the command prints, run through the shell like auth_helper's secret commands
+async fn run_secret_command(command: &str) -> Result<String, String> {
+    let mut shell = if cfg!(target_os = "windows") {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    // Only the exit status is reported; the output is the secret itself
+    let output = shell
+        .arg(command)
+        .output()
+        .await
+        .map_err(|e| format!("notifier.telegram_bot_token_command failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "notifier.telegram_bot_token_command exited with {}",
+            output.status
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string())
+}
+
+/// POST `body`, retrying network errors, rate limiting and server errors with backoff
+/// Errors never carry the URL, which holds the bot token
+async fn post_with_retry(
+    client: &reqwest::Client,
+    url: &str,
+    body: &serde_json::Value,
+) -> Result<(), String> {
+    let mut delay = RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        let error = match client.post(url).json(body).send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                if !(status.is_server_error() || status.as_u16() == 429) {
+                    return Err(format!("refused with {}", status));
+                }
+                format!("answered {}", status)
+            }
+            Err(e) => e.without_url().to_string(),
+        };
+        if attempt >= MAX_ATTEMPTS {
+            return Err(format!("{} after {} attempts", error, attempt));
+        }
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+        attempt += 1;
+    }
+}
+
+async fn run(mut requests: mpsc::UnboundedReceiver<Request>, event_sender: EventSender) {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let mut destinations = Destinations::default();
+    // When each flapping kind last went out, and how many were held back since
+    let mut last_sent: HashMap<AlertKind, Instant> = HashMap::new();
+    let mut suppressed: HashMap<AlertKind, u32> = HashMap::new();
+
+    while let Some(request) = requests.recv().await {
+        match request {
+            Request::Reconfigure(config) => {
+                destinations = match Destinations::resolve(&config).await {
+                    Ok(destinations) => destinations,
+                    Err(e) => {
+                        event_sender.send_error(e, Some("notifier".to_string()));
+                        Destinations::default()
+                    }
+                };
+            }
+            Request::Test(config) => {
+                let result = match Destinations::resolve(&config).await {
+                    Ok(destinations) if destinations.is_empty() => {
+                        Err("no Telegram chat or webhook is configured".to_string())
+                    }
+                    Ok(destinations) => {
+                        destinations
+                            .deliver(
+                                &client,
+                                "test",
+                                "Test notification from the trading dashboard",
+                            )
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                let _ = event_sender.send(AppEvent::NotificationTested(result));
+            }
+            Request::Send(kind, message) => {
+                if destinations.is_empty() {
+                    continue;
+                }
+                if kind.flaps() {
+                    if last_sent
+                        .get(&kind)
+                        .is_some_and(|sent| sent.elapsed() < FLAPPY_INTERVAL)
+                    {
+                        *suppressed.entry(kind).or_default() += 1;
+                        continue;
+                    }
+                    last_sent.insert(kind, Instant::now());
+                }
+                let text = match suppressed.remove(&kind) {
+                    Some(count) => format!("{} ({} more like it held back)", message, count),
+                    None => message,
+                };
+                if let Err(e) = destinations.deliver(&client, kind.name(), &text).await {
+                    event_sender.send_notification(
+                        LogLevel::Warning,
+                        format!("Remote notification not delivered: {}", e),
+                        Some("notifier".to_string()),
+                    );
+                }
+            }
+        }
+    }
+}
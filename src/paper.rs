@@ -0,0 +1,614 @@
+use crate::api::BrokerClient;
+use crate::data_structures::*;
+use crate::instrument_index::InstrumentIndex;
+use crate::state::Command;
+use anyhow::{bail, Result};
+use chrono::Utc;
+use dashmap::DashMap;
+use futures_util::future::BoxFuture;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How often resting paper orders are checked against the latest ticks
+const MATCH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Simulated broker for paper trading: orders live in a local book and fill against the
+/// live tick stream, so the rest of the app sees the same orders, trades and positions
+/// it would get from Kite
+pub struct PaperBroker {
+    book: Mutex<PaperBook>,
+    // Shared with AppState: the instrument master to resolve symbols, and the latest tick
+    // per instrument to price fills
+    instruments: Arc<DashMap<u32, Instrument>>,
+    instrument_index: Arc<RwLock<InstrumentIndex>>,
+    ticks: Arc<DashMap<u32, TickData>>,
+    slippage_pct: f64,
+}
+
+/// Orders, fills and net positions of the paper session; nothing outlives the app
+#[derive(Default)]
+struct PaperBook {
+    orders: Vec<Order>,
+    // Every state each order went through, oldest first
+    history: HashMap<String, Vec<Order>>,
+    trades: Vec<Trade>,
+    // Net positions by instrument and product, as Kite reports CNC and MIS holdings apart
+    positions: HashMap<(u32, String), Position>,
+    next_order: u64,
+    next_trade: u64,
+}
+
+impl PaperBroker {
+    pub fn new(
+        instruments: Arc<DashMap<u32, Instrument>>,
+        instrument_index: Arc<RwLock<InstrumentIndex>>,
+        ticks: Arc<DashMap<u32, TickData>>,
+        slippage_pct: f64,
+    ) -> Self {
+        Self {
+            book: Mutex::new(PaperBook::default()),
+            instruments,
+            instrument_index,
+            ticks,
+            slippage_pct,
+        }
+    }
+
+    /// Fill resting orders as ticks cross their prices, for as long as the runtime runs
+    /// After each fill the API handler is asked to re-read the book, so fills are reported
+    /// through the same order refresh as live ones
+    pub fn start(self: &Arc<Self>, api_commands: mpsc::UnboundedSender<Command>) {
+        let broker = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(MATCH_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                if broker.match_resting_orders()
+                    && api_commands.send(Command::SyncPaperBook).is_err()
+                {
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Trigger and fill whatever the latest prices allow; true when any order changed
+    fn match_resting_orders(&self) -> bool {
+        let mut book = self.book.lock();
+        let mut changed = false;
+        for index in 0..book.orders.len() {
+            let order = &book.orders[index];
+            if !order.is_cancellable() {
+                continue;
+            }
+            let Some(ltp) = self.last_price(order.instrument_token) else {
+                continue;
+            };
+            changed |= self.try_execute(&mut book, index, ltp);
+        }
+        changed
+    }
+
+    /// Move one open order on at the given price: trigger a stop, then fill it if marketable
+    fn try_execute(&self, book: &mut PaperBook, index: usize, ltp: f64) -> bool {
+        let order = &book.orders[index];
+        let buy = order.transaction_type == "BUY";
+
+        if order.status == OrderStatus::Trigger {
+            let triggered = if buy {
+                ltp >= order.trigger_price
+            } else {
+                ltp <= order.trigger_price
+            };
+            if !triggered {
+                return false;
+            }
+            if order.order_type == "SL-M" {
+                let price = self.with_slippage(order.instrument_token, buy, ltp);
+                let previous_close = self.previous_close(order.instrument_token);
+                book.fill(index, price, previous_close);
+            } else {
+                book.update(index, |order| order.status = OrderStatus::Open);
+                self.fill_limit(book, index, ltp);
+            }
+            return true;
+        }
+
+        self.fill_limit(book, index, ltp)
+    }
+
+    /// Fill an open limit order once the price reaches it, at the limit or better
+    fn fill_limit(&self, book: &mut PaperBook, index: usize, ltp: f64) -> bool {
+        let order = &book.orders[index];
+        let fill = match order.transaction_type.as_str() {
+            "BUY" if ltp <= order.price => ltp,
+            "SELL" if ltp >= order.price => ltp,
+            _ => return false,
+        };
+        let previous_close = self.previous_close(order.instrument_token);
+        book.fill(index, fill, previous_close);
+        true
+    }
+
+    fn last_price(&self, instrument_token: u32) -> Option<f64> {
+        self.ticks
+            .get(&instrument_token)
+            .map(|tick| tick.last_price)
+            .filter(|price| *price > 0.0)
+    }
+
+    /// Last session's close from the instrument master, which new positions are priced
+    /// against for day P&L as live positions are
+    fn previous_close(&self, instrument_token: u32) -> Option<f64> {
+        self.instruments
+            .get(&instrument_token)
+            .map(|instrument| instrument.last_price)
+            .filter(|close| *close > 0.0)
+    }
+
+    /// A market fill price: the LTP moved against the order by the configured slippage
+    fn with_slippage(&self, instrument_token: u32, buy: bool, ltp: f64) -> f64 {
+        let factor = self.slippage_pct / 100.0;
+        let price = if buy {
+            ltp * (1.0 + factor)
+        } else {
+            ltp * (1.0 - factor)
+        };
+        self.instruments
+            .get(&instrument_token)
+            .map_or(price, |instrument| instrument.round_to_tick(price))
+    }
+
+    /// Token of the ordered instrument on the ordered exchange
+    fn resolve(&self, order_request: &OrderRequest) -> Result<u32> {
+        let token = self
+            .instrument_index
+            .read()
+            .lookup(&order_request.tradingsymbol, &order_request.exchange)
+            .filter(|token| {
+                self.instruments
+                    .get(token)
+                    .is_some_and(|instrument| instrument.exchange == order_request.exchange)
+            });
+        match token {
+            Some(token) => Ok(token),
+            None => bail!(
+                "{}:{} is not in the loaded instrument list; load instruments to paper trade it",
+                order_request.exchange,
+                order_request.tradingsymbol
+            ),
+        }
+    }
+
+    fn place(&self, order_request: &OrderRequest) -> Result<String> {
+        let instrument_token = self.resolve(order_request)?;
+        let buy = order_request.transaction_type == "BUY";
+        let ltp = self.last_price(instrument_token);
+
+        let status = match order_request.order_type.as_str() {
+            "MARKET" | "LIMIT" => OrderStatus::Open,
+            "SL" | "SL-M" => OrderStatus::Trigger,
+            other => bail!("order type {} is not supported in paper trading", other),
+        };
+        if order_request.order_type == "MARKET" && ltp.is_none() {
+            bail!(
+                "no live price for {} yet; stream its ticks (e.g. add it to the watchlist) to paper trade it",
+                order_request.tradingsymbol
+            );
+        }
+
+        let mut book = self.book.lock();
+        book.next_order += 1;
+        let now = Utc::now();
+        let order = Order {
+            order_id: format!("PAPER{:06}", book.next_order),
+            parent_order_id: None,
+            exchange_order_id: String::new(),
+            placed_by: "PAPER".to_string(),
+            variety: order_request.variety.clone(),
+            status,
+            tradingsymbol: order_request.tradingsymbol.clone(),
+            exchange: order_request.exchange.clone(),
+            instrument_token,
+            transaction_type: order_request.transaction_type.clone(),
+            order_type: order_request.order_type.clone(),
+            product: order_request.product.clone(),
+            validity: order_request.validity.clone(),
+            price: order_request.price.unwrap_or_default(),
+            quantity: order_request.quantity,
+            pending_quantity: order_request.quantity,
+            filled_quantity: 0,
+            disclosed_quantity: order_request.disclosed_quantity.unwrap_or_default(),
+            trigger_price: order_request.trigger_price.unwrap_or_default(),
+            average_price: 0.0,
+            order_timestamp: now,
+            exchange_timestamp: Some(now),
+            status_message: None,
+            tag: order_request.tag.clone(),
+        };
+        let order_id = order.order_id.clone();
+        book.history.insert(order_id.clone(), vec![order.clone()]);
+        book.orders.push(order);
+        let index = book.orders.len() - 1;
+
+        if let Some(ltp) = ltp {
+            if order_request.order_type == "MARKET" {
+                let price = self.with_slippage(instrument_token, buy, ltp);
+                book.fill(index, price, self.previous_close(instrument_token));
+            } else {
+                self.try_execute(&mut book, index, ltp);
+            }
+        }
+        // IOC orders that could not fill at once are cancelled, as on the exchange
+        if order_request.validity == "IOC" && book.orders[index].is_cancellable() {
+            book.update(index, |order| {
+                order.status = OrderStatus::Cancelled;
+                order.status_message = Some("IOC order not filled immediately".to_string());
+            });
+        }
+
+        Ok(order_id)
+    }
+
+    fn modify(&self, order_id: &str, order_request: &OrderRequest) -> Result<String> {
+        let mut book = self.book.lock();
+        let index = book.open_order(order_id)?;
+        book.update(index, |order| {
+            order.order_type = order_request.order_type.clone();
+            order.validity = order_request.validity.clone();
+            order.quantity = order_request.quantity;
+            order.pending_quantity = order.quantity - order.filled_quantity;
+            order.price = order_request.price.unwrap_or_default();
+            order.trigger_price = order_request.trigger_price.unwrap_or_default();
+            order.status = match order.order_type.as_str() {
+                "SL" | "SL-M" => OrderStatus::Trigger,
+                _ => OrderStatus::Open,
+            };
+        });
+
+        let order = &book.orders[index];
+        if let Some(ltp) = self.last_price(order.instrument_token) {
+            if order.order_type == "MARKET" {
+                let price = self.with_slippage(
+                    order.instrument_token,
+                    order.transaction_type == "BUY",
+                    ltp,
+                );
+                let previous_close = self.previous_close(order.instrument_token);
+                book.fill(index, price, previous_close);
+            } else {
+                self.try_execute(&mut book, index, ltp);
+            }
+        }
+        Ok(order_id.to_string())
+    }
+
+    fn cancel(&self, order_id: &str) -> Result<String> {
+        let mut book = self.book.lock();
+        let index = book.open_order(order_id)?;
+        book.update(index, |order| order.status = OrderStatus::Cancelled);
+        Ok(order_id.to_string())
+    }
+
+    /// Net positions, priced at the latest tick
+    fn positions(&self) -> Vec<Position> {
+        let book = self.book.lock();
+        book.positions
+            .values()
+            .cloned()
+            .map(|mut position| {
+                if let Some(ltp) = self.last_price(position.instrument_token) {
+                    position.update_last_price(ltp);
+                }
+                position
+            })
+            .collect()
+    }
+}
+
+impl PaperBook {
+    /// Index of an order that can still be changed
+    fn open_order(&self, order_id: &str) -> Result<usize> {
+        let Some(index) = self
+            .orders
+            .iter()
+            .position(|order| order.order_id == order_id)
+        else {
+            bail!("paper order {} not found", order_id);
+        };
+        let order = &self.orders[index];
+        if !order.is_cancellable() {
+            bail!("paper order {} is already {}", order_id, order.status);
+        }
+        Ok(index)
+    }
+
+    /// Change an order and record the new state in its history
+    fn update(&mut self, index: usize, change: impl FnOnce(&mut Order)) {
+        let order = &mut self.orders[index];
+        change(order);
+        order.exchange_timestamp = Some(Utc::now());
+        let state = order.clone();
+        self.history
+            .entry(state.order_id.clone())
+            .or_default()
+            .push(state);
+    }
+
+    /// Fill the rest of an order at `price`, recording the trade and moving the position
+    /// A position opened by the fill is priced against `previous_close`, else the fill
+    fn fill(&mut self, index: usize, price: f64, previous_close: Option<f64>) {
+        let quantity = self.orders[index].pending_quantity;
+        self.update(index, |order| {
+            order.status = OrderStatus::Complete;
+            order.filled_quantity = order.quantity;
+            order.pending_quantity = 0;
+            order.average_price = price;
+        });
+
+        let order = self.orders[index].clone();
+        self.next_trade += 1;
+        self.trades.push(Trade {
+            trade_id: format!("PT{:06}", self.next_trade),
+            order_id: order.order_id.clone(),
+            exchange_order_id: None,
+            tradingsymbol: order.tradingsymbol.clone(),
+            exchange: order.exchange.clone(),
+            instrument_token: order.instrument_token,
+            product: order.product.clone(),
+            transaction_type: order.transaction_type.clone(),
+            quantity,
+            average_price: price,
+            fill_timestamp: Utc::now(),
+        });
+
+        let signed = if order.transaction_type == "BUY" {
+            quantity
+        } else {
+            -quantity
+        };
+        let position = self
+            .positions
+            .entry((order.instrument_token, order.product.clone()))
+            .or_insert_with(|| Position {
+                instrument_token: order.instrument_token,
+                tradingsymbol: order.tradingsymbol.clone(),
+                exchange: order.exchange.clone(),
+                product: order.product.clone(),
+                quantity: 0,
+                average_price: 0.0,
+                last_price: price,
+                close_price: previous_close.unwrap_or(price),
+                pnl: 0.0,
+                unrealized_pnl: 0.0,
+                realized_pnl: 0.0,
+                multiplier: 1.0,
+                overnight_quantity: 0,
+                day_quantity: 0,
+            });
+        apply_fill(position, signed, price);
+    }
+}
+
+/// Net a fill into a position: adding to it re-averages the price, reducing it books
+/// realized P&L against the average, and crossing zero opens the rest at the fill price
+fn apply_fill(position: &mut Position, signed_quantity: i32, price: f64) {
+    let held = position.quantity;
+    if held == 0 || held.signum() == signed_quantity.signum() {
+        let total = held.abs() + signed_quantity.abs();
+        position.average_price = (position.average_price * held.abs() as f64
+            + price * signed_quantity.abs() as f64)
+            / total as f64;
+    } else {
+        let closed = held.abs().min(signed_quantity.abs());
+        position.realized_pnl += (price - position.average_price) * (closed * held.signum()) as f64;
+        if signed_quantity.abs() > held.abs() {
+            position.average_price = price;
+        } else if held + signed_quantity == 0 {
+            position.average_price = 0.0;
+        }
+    }
+
+    position.quantity = held + signed_quantity;
+    position.day_quantity = position.quantity;
+    position.last_price = price;
+    position.pnl = position.realized_pnl;
+    position.unrealized_pnl = 0.0;
+    position.calculate_pnl();
+}
+
+impl BrokerClient for PaperBroker {
+    fn get_positions(&self) -> BoxFuture<'_, Result<Vec<Position>>> {
+        Box::pin(async move { Ok(self.positions()) })
+    }
+
+    fn get_orders(&self) -> BoxFuture<'_, Result<Vec<Order>>> {
+        Box::pin(async move { Ok(self.book.lock().orders.clone()) })
+    }
+
+    fn get_trades(&self) -> BoxFuture<'_, Result<Vec<Trade>>> {
+        Box::pin(async move { Ok(self.book.lock().trades.clone()) })
+    }
+
+    fn get_order_history<'a>(&'a self, order_id: &'a str) -> BoxFuture<'a, Result<Vec<Order>>> {
+        Box::pin(async move {
+            Ok(self
+                .book
+                .lock()
+                .history
+                .get(order_id)
+                .cloned()
+                .unwrap_or_default())
+        })
+    }
+
+    fn get_order_trades<'a>(&'a self, order_id: &'a str) -> BoxFuture<'a, Result<Vec<Trade>>> {
+        Box::pin(async move {
+            Ok(self
+                .book
+                .lock()
+                .trades
+                .iter()
+                .filter(|trade| trade.order_id == order_id)
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn place_order<'a>(&'a self, order_request: &'a OrderRequest) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move { self.place(order_request) })
+    }
+
+    fn modify_order<'a>(
+        &'a self,
+        order_id: &'a str,
+        order_request: &'a OrderRequest,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move { self.modify(order_id, order_request) })
+    }
+
+    fn cancel_order<'a>(
+        &'a self,
+        order_id: &'a str,
+        _variety: &'a str,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(async move { self.cancel(order_id) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{AppEvent, AppState, Config};
+
+    const INFY: u32 = 1;
+
+    /// Broker with INFY loaded, last closed at 95, streaming at `ltp`, with no slippage
+    fn broker(ltp: f64) -> PaperBroker {
+        let instruments = Arc::new(DashMap::new());
+        let mut infy = Instrument::equity(INFY, "INFY", "NSE");
+        infy.last_price = 95.0;
+        instruments.insert(INFY, infy);
+        let index = Arc::new(RwLock::new(InstrumentIndex::build(&instruments)));
+        let broker = PaperBroker::new(instruments, index, Arc::new(DashMap::new()), 0.0);
+        set_ltp(&broker, ltp);
+        broker
+    }
+
+    fn set_ltp(broker: &PaperBroker, last_price: f64) {
+        broker.ticks.insert(
+            INFY,
+            TickData {
+                instrument_token: INFY,
+                last_price,
+                last_quantity: 1,
+                average_price: last_price,
+                volume: 0,
+                buy_quantity: 0,
+                sell_quantity: 0,
+                ohlc: OHLC {
+                    open: 0.0,
+                    high: 0.0,
+                    low: 0.0,
+                    close: 0.0,
+                },
+                timestamp_nanos: 0,
+            },
+        );
+    }
+
+    fn market(transaction_type: &str, quantity: i32, product: &str) -> OrderRequest {
+        let mut order = OrderRequest::market("INFY", transaction_type, quantity);
+        order.product = product.to_string();
+        order
+    }
+
+    fn position(broker: &PaperBroker, product: &str) -> Position {
+        broker
+            .positions()
+            .into_iter()
+            .find(|position| position.product == product)
+            .expect("position for product")
+    }
+
+    #[test]
+    fn products_are_held_as_separate_positions() {
+        let broker = broker(100.0);
+        broker.place(&market("BUY", 10, "CNC")).unwrap();
+        broker.place(&market("SELL", 4, "MIS")).unwrap();
+
+        assert_eq!(broker.positions().len(), 2);
+        assert_eq!(position(&broker, "CNC").quantity, 10);
+        assert_eq!(position(&broker, "MIS").quantity, -4);
+    }
+
+    #[test]
+    fn app_state_shows_each_paper_product_as_its_own_position() {
+        let broker = broker(100.0);
+        broker.place(&market("BUY", 10, "CNC")).unwrap();
+        broker.place(&market("SELL", 4, "MIS")).unwrap();
+        set_ltp(&broker, 110.0);
+
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        state.event_receiver = receiver;
+        sender
+            .send(AppEvent::PositionsUpdated(broker.positions()))
+            .unwrap();
+        state.process_events();
+
+        assert_eq!(state.positions.len(), 2);
+        // Trade P&L of +100 on the long and -40 on the short
+        assert_eq!(state.calculate_total_pnl().realized, 0.0);
+        let pnl: f64 = state.positions.iter().map(|entry| entry.pnl).sum();
+        assert_eq!(pnl, 60.0);
+    }
+
+    #[test]
+    fn positions_keep_the_previous_close_through_fills() {
+        let broker = broker(100.0);
+        broker.place(&market("BUY", 10, "CNC")).unwrap();
+
+        let held = position(&broker, "CNC");
+        assert_eq!((held.average_price, held.close_price), (100.0, 95.0));
+        // Day P&L is against yesterday's close, trade P&L against the entry
+        assert_eq!((held.unrealized_pnl, held.pnl), (50.0, 0.0));
+
+        set_ltp(&broker, 110.0);
+        broker.place(&market("SELL", 4, "CNC")).unwrap();
+        let held = position(&broker, "CNC");
+        assert_eq!(held.quantity, 6);
+        assert_eq!(held.realized_pnl, 40.0);
+        assert_eq!((held.average_price, held.close_price), (100.0, 95.0));
+        assert_eq!(held.unrealized_pnl, 90.0);
+    }
+
+    #[test]
+    fn modify_leaves_filled_quantity_out_of_pending() {
+        let broker = broker(100.0);
+        let mut limit = market("BUY", 10, "CNC");
+        limit.order_type = "LIMIT".to_string();
+        limit.price = Some(90.0);
+        let order_id = broker.place(&limit).unwrap();
+
+        broker.book.lock().orders[0].filled_quantity = 4;
+        limit.quantity = 12;
+        broker.modify(&order_id, &limit).unwrap();
+        assert_eq!(broker.book.lock().orders[0].pending_quantity, 8);
+
+        // Only what was still pending trades when the price comes down
+        set_ltp(&broker, 89.0);
+        assert!(broker.match_resting_orders());
+        let book = broker.book.lock();
+        assert_eq!(book.orders[0].status, OrderStatus::Complete);
+        assert_eq!(book.trades.len(), 1);
+        assert_eq!(
+            (book.trades[0].quantity, book.trades[0].average_price),
+            (8, 89.0)
+        );
+    }
+}
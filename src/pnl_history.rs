@@ -0,0 +1,114 @@
+use crate::data_structures::PnlData;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::VecDeque;
+
+/// Minimum spacing between recorded P&L samples
+pub const PNL_SAMPLE_INTERVAL: TimeDelta = TimeDelta::seconds(5);
+
+/// A full day of samples at the sampling interval
+const MAX_PNL_SAMPLES: usize = 24 * 60 * 60 / 5;
+
+/// Session P&L at one instant
+#[derive(Debug, Clone, Copy)]
+pub struct PnlSample {
+    pub timestamp: DateTime<Utc>,
+    pub realized: f64,
+    pub unrealized: f64,
+    pub total: f64,
+}
+
+/// An order fill to mark on the equity curve
+#[derive(Debug, Clone)]
+pub struct FillMarker {
+    pub timestamp: DateTime<Utc>,
+    pub label: String,
+    pub is_buy: bool,
+}
+
+/// Largest peak-to-trough fall of total P&L seen so far
+#[derive(Debug, Clone, Copy)]
+pub struct Drawdown {
+    pub peak: PnlSample,
+    pub trough: PnlSample,
+}
+
+impl Drawdown {
+    pub fn amount(&self) -> f64 {
+        self.peak.total - self.trough.total
+    }
+}
+
+/// Intraday equity curve: sampled P&L plus fill markers
+#[derive(Debug, Default)]
+pub struct PnlHistory {
+    samples: VecDeque<PnlSample>,
+    fills: Vec<FillMarker>,
+}
+
+impl PnlHistory {
+    /// Whether a sample taken at `timestamp` would be recorded
+    pub fn is_due(&self, timestamp: DateTime<Utc>) -> bool {
+        self.samples
+            .back()
+            .is_none_or(|last| timestamp - last.timestamp >= PNL_SAMPLE_INTERVAL)
+    }
+
+    /// Record a sample unless the previous one is more recent than the sampling interval
+    pub fn record(&mut self, timestamp: DateTime<Utc>, pnl: &PnlData) {
+        if !self.is_due(timestamp) {
+            return;
+        }
+
+        if self.samples.len() >= MAX_PNL_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(PnlSample {
+            timestamp,
+            realized: pnl.realized,
+            unrealized: pnl.unrealized,
+            total: pnl.total,
+        });
+    }
+
+    pub fn record_fill(&mut self, marker: FillMarker) {
+        self.fills.push(marker);
+    }
+
+    pub fn samples(&self) -> &VecDeque<PnlSample> {
+        &self.samples
+    }
+
+    pub fn fills(&self) -> &[FillMarker] {
+        &self.fills
+    }
+
+    /// Highest total P&L reached this session
+    pub fn high_water(&self) -> Option<PnlSample> {
+        self.samples
+            .iter()
+            .copied()
+            .max_by(|a, b| a.total.total_cmp(&b.total))
+    }
+
+    /// Deepest drawdown from a running peak, None while P&L has only risen
+    pub fn max_drawdown(&self) -> Option<Drawdown> {
+        let mut peak = *self.samples.front()?;
+        let mut worst: Option<Drawdown> = None;
+
+        for sample in &self.samples {
+            if sample.total > peak.total {
+                peak = *sample;
+            }
+
+            let drawdown = Drawdown {
+                peak,
+                trough: *sample,
+            };
+            if drawdown.amount() > worst.map_or(0.0, |worst| worst.amount()) {
+                worst = Some(drawdown);
+            }
+        }
+
+        worst
+    }
+}
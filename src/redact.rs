@@ -0,0 +1,104 @@
+// src/redact.rs - Masking of credentials before they reach logs, notifications or stdout
+// Shared with the auth_helper binary, so it depends on nothing else in the crate
+
+/// Query and form parameters whose values are credentials
+const SENSITIVE_PARAMS: &[&str] = &[
+    "api_key",
+    "api_secret",
+    "access_token",
+    "request_token",
+    "checksum",
+];
+
+/// Characters left visible at each end of a masked value
+const VISIBLE_CHARS: usize = 3;
+
+/// Shorter values are hidden entirely, since their ends would give away most of them
+const MIN_PARTIAL_LEN: usize = 10;
+
+/// A credential with all but its first and last three characters hidden, e.g. "abc***xyz"
+pub fn mask(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() < MIN_PARTIAL_LEN {
+        return "***".to_string();
+    }
+    let head: String = chars[..VISIBLE_CHARS].iter().collect();
+    let tail: String = chars[chars.len() - VISIBLE_CHARS..].iter().collect();
+    format!("{}***{}", head, tail)
+}
+
+/// Text with the value of every credential `name=value` pair masked, for messages that
+/// interpolate URLs or form bodies
+pub fn redact_params(text: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    loop {
+        let next = SENSITIVE_PARAMS
+            .iter()
+            .filter_map(|name| {
+                let pattern = format!("{}=", name);
+                rest.find(&pattern).map(|start| start + pattern.len())
+            })
+            .min();
+        let Some(value_start) = next else {
+            redacted.push_str(rest);
+            return redacted;
+        };
+
+        let value_len = rest[value_start..]
+            .find(|c: char| matches!(c, '&' | '"' | '\'' | ')' | ',' | ';') || c.is_whitespace())
+            .unwrap_or(rest.len() - value_start);
+        redacted.push_str(&rest[..value_start]);
+        redacted.push_str(&mask(&rest[value_start..value_start + value_len]));
+        rest = &rest[value_start + value_len..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_keeps_only_the_ends_of_long_values() {
+        assert_eq!(mask("abcdefghijkl"), "abc***jkl");
+        assert_eq!(mask("0123456789"), "012***789");
+    }
+
+    #[test]
+    fn mask_hides_short_values_entirely() {
+        assert_eq!(mask(""), "***");
+        assert_eq!(mask("123456789"), "***");
+    }
+
+    #[test]
+    fn mask_counts_characters_not_bytes() {
+        assert_eq!(mask("₹₹₹abcdefg€€€"), "₹₹₹***€€€");
+    }
+
+    #[test]
+    fn redact_params_masks_every_credential() {
+        let url = "wss://ws.kite.trade?api_key=kiteapikey123&access_token=tokenvalue987";
+        assert_eq!(
+            redact_params(url),
+            "wss://ws.kite.trade?api_key=kit***123&access_token=tok***987"
+        );
+    }
+
+    #[test]
+    fn redact_params_stops_at_value_delimiters() {
+        assert_eq!(
+            redact_params(r#"{"request_token=reqtoken0000", "x": 1}"#),
+            r#"{"request_token=req***000", "x": 1}"#
+        );
+        assert_eq!(
+            redact_params("checksum=abcdef123456; next"),
+            "checksum=abc***456; next"
+        );
+    }
+
+    #[test]
+    fn redact_params_leaves_other_text_alone() {
+        let text = "GET /quote?i=NSE:INFY&mode=full";
+        assert_eq!(redact_params(text), text);
+    }
+}
@@ -0,0 +1,110 @@
+use crate::state::AppConfig;
+use crossbeam_channel::Sender;
+use rodio::Source;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+/// Repeats of the same sound inside this window are dropped
+const SOUND_DEBOUNCE: Duration = Duration::from_secs(1);
+
+const FILL_WAV: &[u8] = include_bytes!("../assets/sounds/fill.wav");
+const REJECTION_WAV: &[u8] = include_bytes!("../assets/sounds/rejection.wav");
+const ALERT_WAV: &[u8] = include_bytes!("../assets/sounds/alert.wav");
+
+/// Events that can play a sound
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundEvent {
+    Fill,
+    Rejection,
+    Alert,
+}
+
+impl SoundEvent {
+    pub const ALL: [SoundEvent; 3] = [SoundEvent::Fill, SoundEvent::Rejection, SoundEvent::Alert];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SoundEvent::Fill => "Order filled",
+            SoundEvent::Rejection => "Order rejected",
+            SoundEvent::Alert => "Price alert",
+        }
+    }
+
+    /// Whether config.toml enables this event's sound
+    pub fn enabled_in(self, config: &AppConfig) -> bool {
+        match self {
+            SoundEvent::Fill => config.sound_on_fill,
+            SoundEvent::Rejection => config.sound_on_rejection,
+            SoundEvent::Alert => config.sound_on_alert,
+        }
+    }
+
+    /// Per-event toggle, for the status bar menu
+    pub fn toggle_in(self, config: &mut AppConfig) -> &mut bool {
+        match self {
+            SoundEvent::Fill => &mut config.sound_on_fill,
+            SoundEvent::Rejection => &mut config.sound_on_rejection,
+            SoundEvent::Alert => &mut config.sound_on_alert,
+        }
+    }
+
+    fn wav(self) -> &'static [u8] {
+        match self {
+            SoundEvent::Fill => FILL_WAV,
+            SoundEvent::Rejection => REJECTION_WAV,
+            SoundEvent::Alert => ALERT_WAV,
+        }
+    }
+}
+
+/// Plays event sounds on a background thread so decoding and output never block the UI
+/// The audio device is opened on the first sound, so a disabled config never touches it
+#[derive(Default)]
+pub struct SoundPlayer {
+    sender: Option<Sender<SoundEvent>>,
+    last_played: HashMap<SoundEvent, Instant>,
+    pub muted: bool,
+}
+
+impl SoundPlayer {
+    /// Queue a sound unless sounds are off, muted, or the same sound just played
+    pub fn play(&mut self, event: SoundEvent, config: &AppConfig) {
+        if !config.sounds || self.muted || !event.enabled_in(config) {
+            return;
+        }
+
+        let now = Instant::now();
+        if self
+            .last_played
+            .get(&event)
+            .is_some_and(|played| now.duration_since(*played) < SOUND_DEBOUNCE)
+        {
+            return;
+        }
+        self.last_played.insert(event, now);
+
+        let sender = self.sender.get_or_insert_with(spawn_output_thread);
+        let _ = sender.send(event);
+    }
+}
+
+/// Own the output stream on a dedicated thread; it is not Send and must outlive playback
+fn spawn_output_thread() -> Sender<SoundEvent> {
+    let (sender, receiver) = crossbeam_channel::unbounded::<SoundEvent>();
+
+    std::thread::spawn(move || {
+        // Without an output device sounds are silently dropped
+        let Ok((_stream, handle)) = rodio::OutputStream::try_default() else {
+            return;
+        };
+
+        while let Ok(event) = receiver.recv() {
+            if let Ok(source) = rodio::Decoder::new(Cursor::new(event.wav())) {
+                let _ = handle.play_raw(source.convert_samples());
+            }
+        }
+    });
+
+    sender
+}
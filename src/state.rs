@@ -1,44 +1,697 @@
+use crate::alerts::{AlertBook, AlertCondition, PriceAlert, ALERTS_FILE};
+use crate::api::ZerodhaClient;
+use crate::app::AppView;
+use crate::broadcast::{Broadcast, Broadcaster, TickMessage};
+use crate::candles::{Candle, CandleInterval, CandleSeries, ResampledCandles};
+use crate::clock::ClockOffset;
+use crate::config_writer;
 use crate::data_structures::*;
-use chrono::{DateTime, Utc};
+use crate::export;
+use crate::history::{self, DaySummary, HistoryRecord, HistoryStore};
+use crate::instrument_index::{InstrumentIndex, InstrumentQuery};
+use crate::journal::{Journal, JournalRecord};
+use crate::market_calendar::{ist_midnight, ist_now, token_expiry, ClosedReason, MarketStatus};
+use crate::notifier::{AlertKind, Notifier, NotifierConfig};
+use crate::pnl_history::{FillMarker, PnlHistory};
+use crate::redact;
+use crate::sound::{SoundEvent, SoundPlayer};
+use crate::strategy::{StrategyConfig, StrategyStatus};
+use crate::ui::shortcuts::ShortcutTable;
+use crate::ui::theme::{Theme, ThemeMode};
+use crate::watchlist::{Watchlist, WATCHLIST_FILE};
+use crate::workers::circuit_breaker::{ApiCategory, CircuitState};
+use chrono::{DateTime, NaiveDate, NaiveTime, SubsecRound, TimeDelta, Utc};
+use chrono_tz::Asia::Kolkata;
 use crossbeam_channel::{Receiver, Sender};
 use dashmap::DashMap;
 use figment::providers::Format;
 use figment::{providers::Toml, Figment};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 
 /// Configuration structure mirroring config.toml for type-safe access
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Config {
     pub zerodha: ZerodhaConfig,
     pub app: AppConfig,
+    /// auth_helper's headless login settings; carried along so saving Settings keeps them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_login: Option<toml::Table>,
+    /// auth_helper's other accounts, [profiles.<name>]; carried along the same way
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<toml::Table>,
+    /// Local REST API, event broadcast and metrics ports
+    #[serde(default)]
+    pub server: ServerConfig,
+    /// Tick buffering between the WebSocket worker and the UI; read once at startup
+    #[serde(default)]
+    pub performance: PerformanceConfig,
+    /// Telegram and webhook notifications
+    #[serde(default, skip_serializing_if = "NotifierConfig::is_empty")]
+    pub notifier: NotifierConfig,
+    /// Automated strategies, [[strategies]]; read once at startup
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub strategies: Vec<StrategyConfig>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct ZerodhaConfig {
     pub api_key: String,
     pub api_secret: String,
     pub access_token: String,
+    /// Local port Kite's login redirect is registered on, to capture the request_token
+    /// without pasting it; 0 turns the listener off
+    #[serde(default = "default_callback_port")]
+    pub callback_port: u16,
+    /// When access_token was generated, written alongside it; tokens expire at 06:00 IST
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token_issued_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The [server] section: what other programs on this machine can connect to
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// Where --headless mode serves its REST API; reads are unauthenticated, so keep this on
+    /// loopback unless something else guards it
+    #[serde(default = "default_bind_address")]
+    pub bind_address: String,
+    /// Bearer token that POST endpoints require; they are refused while it is empty
+    #[serde(default)]
+    pub api_token: String,
+    /// Localhost port streaming ticks, order updates and P&L samples over WebSocket; 0 is off
+    #[serde(default)]
+    pub broadcast_port: u16,
+    /// Localhost port serving Prometheus metrics at /metrics, in builds with the `metrics`
+    /// feature; 0 is off
+    #[serde(default)]
+    pub metrics_port: u16,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: default_bind_address(),
+            api_token: String::new(),
+            broadcast_port: 0,
+            metrics_port: 0,
+        }
+    }
+}
+
+/// What happens to a new tick when tick_buffer_size ticks are already waiting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DropPolicy {
+    /// Discard the oldest waiting tick, so the UI always gets the latest prices
+    #[default]
+    Oldest,
+    /// Discard the new tick, keeping the ones already waiting
+    Newest,
+}
+
+impl DropPolicy {
+    pub const ALL: [DropPolicy; 2] = [DropPolicy::Oldest, DropPolicy::Newest];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DropPolicy::Oldest => "Drop oldest",
+            DropPolicy::Newest => "Drop newest",
+        }
+    }
+}
+
+/// The [performance] section: how market data is buffered and batched on its way to the UI
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PerformanceConfig {
+    /// Ticks and depth updates that can wait for the UI before drop_policy applies
+    pub tick_buffer_size: usize,
+    /// Hold ticks this long and send only each instrument's latest; 0 sends every tick
+    pub conflation_interval_ms: u64,
+    /// Most worker events applied per frame; the rest wait for the next one
+    pub max_event_batch: usize,
+    pub drop_policy: DropPolicy,
+}
+
+impl Default for PerformanceConfig {
+    fn default() -> Self {
+        Self {
+            tick_buffer_size: 1000,
+            conflation_interval_ms: 0,
+            max_event_batch: 10_000,
+            drop_policy: DropPolicy::Oldest,
+        }
+    }
+}
+
+impl PerformanceConfig {
+    /// Readable message per problem with the section
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !(1..=1_000_000).contains(&self.tick_buffer_size) {
+            problems.push(format!(
+                "performance.tick_buffer_size must be between 1 and 1000000 (got {})",
+                self.tick_buffer_size
+            ));
+        }
+        if !(1..=1_000_000).contains(&self.max_event_batch) {
+            problems.push(format!(
+                "performance.max_event_batch must be between 1 and 1000000 (got {})",
+                self.max_event_batch
+            ));
+        }
+        // Prices held longer than this would no longer look live
+        if self.conflation_interval_ms > 1000 {
+            problems.push(format!(
+                "performance.conflation_interval_ms must be at most 1000 (got {})",
+                self.conflation_interval_ms
+            ));
+        }
+        problems
+    }
+}
+
+/// Where orders go: to Kite, or into a local book that fills them from live ticks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TradingMode {
+    #[default]
+    Live,
+    Paper,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppConfig {
     pub log_level: String,
+    /// Live or paper trading; read at startup only, so switching needs a restart
+    #[serde(default)]
+    pub mode: TradingMode,
+    /// Paper market and stop-market orders fill this % worse than the LTP
+    #[serde(default = "default_paper_slippage_pct")]
+    pub paper_slippage_pct: f64,
     pub websocket_reconnect_delay_ms: u64,
     pub max_reconnect_attempts: u32,
-    pub tick_buffer_size: usize,
+    /// Preview order margins and block orders the account cannot fund
+    #[serde(default)]
+    pub margin_precheck: bool,
+    /// Mirror triggered price alerts as desktop notifications
+    #[serde(default)]
+    pub os_notifications: bool,
+    /// Recent ticks kept per instrument for sparklines, short-term change and the tick tape
+    #[serde(default = "default_tick_history_size")]
+    pub tick_history_size: usize,
+    /// Trades of at least this quantity are highlighted in the tick tape; 0 turns it off
+    #[serde(default = "default_large_trade_quantity")]
+    pub large_trade_quantity: u32,
+    /// Pending worker events that count as a backlog worth warning about
+    #[serde(default = "default_event_backlog_warn_threshold")]
+    pub event_backlog_warn_threshold: usize,
+    /// Stream ticks for open positions as soon as they load, so their P&L stays live
+    #[serde(default = "default_true")]
+    pub auto_subscribe_positions: bool,
+    /// Stop streaming an auto-subscribed position once it is flat and not watched
+    #[serde(default)]
+    pub unsubscribe_flat_positions: bool,
+    /// Ask before sending one-click market orders from position rows
+    #[serde(default = "default_true")]
+    pub confirm_market_orders: bool,
+    /// SQLite file for the order journal; journaling is off when unset
+    #[serde(default)]
+    pub journal_path: Option<String>,
+    /// SQLite file keeping closed candles, daily P&L and trades across sessions; off when
+    /// unset
+    #[serde(default)]
+    pub history_path: Option<String>,
+    /// Show summary amounts of a crore or more as e.g. ₹1.25 Cr
+    #[serde(default)]
+    pub abbreviate_crores: bool,
+    /// Put total P&L and connection state in the window title; off by default for screen sharing
+    #[serde(default)]
+    pub live_window_title: bool,
+    /// Day change % the watchlist heatmap treats as neutral
+    #[serde(default)]
+    pub heatmap_midpoint_pct: f64,
+    /// Distance from the midpoint, in %, at which a heatmap tile is fully red or green
+    #[serde(default = "default_heatmap_full_scale_pct")]
+    pub heatmap_full_scale_pct: f64,
+    /// IST time of day to warn, once a day, that the access token is from a previous day
+    #[serde(default = "default_token_reminder_time")]
+    pub token_reminder_time: String,
+    /// Exchanges whose instrument master is loaded at startup and refreshed each day
+    /// after INSTRUMENTS_REFRESH_TIME; empty loads instruments only on demand
+    #[serde(default)]
+    pub instrument_exchanges: Vec<String>,
+    /// NTP server the local clock is checked against at startup and hourly, so tick
+    /// latency can be corrected for drift; empty turns the check off
+    #[serde(default = "default_ntp_server")]
+    pub ntp_server: String,
+    /// Play short sounds for fills, rejections and triggered alerts
+    #[serde(default)]
+    pub sounds: bool,
+    #[serde(default = "default_true")]
+    pub sound_on_fill: bool,
+    #[serde(default = "default_true")]
+    pub sound_on_rejection: bool,
+    #[serde(default = "default_true")]
+    pub sound_on_alert: bool,
+    /// Re-fetch positions and orders this often during market hours; 0 turns it off
+    #[serde(default)]
+    pub auto_refresh_secs: u64,
+    /// Separate intervals for orders and positions, in place of auto_refresh_secs; 0 turns
+    /// that refresh off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orders_refresh_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub positions_refresh_secs: Option<u64>,
+    /// How long workers get to finish in-flight requests on exit before they are aborted
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
+    /// Starting theme; a choice made in the settings menu is remembered over this
+    #[serde(default)]
+    pub theme: ThemeMode,
+    /// Hex colors such as "#3b82f6" overriding the theme's accent and P&L colors
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    #[serde(default)]
+    pub profit_color: Option<String>,
+    #[serde(default)]
+    pub loss_color: Option<String>,
+}
+
+impl AppConfig {
+    /// How often the API handler refreshes orders during market hours, None when off
+    pub fn orders_refresh_interval(&self) -> Option<Duration> {
+        refresh_interval(self.orders_refresh_secs.unwrap_or(self.auto_refresh_secs))
+    }
+
+    /// How often the API handler refreshes positions during market hours, None when off
+    pub fn positions_refresh_interval(&self) -> Option<Duration> {
+        refresh_interval(
+            self.positions_refresh_secs
+                .unwrap_or(self.auto_refresh_secs),
+        )
+    }
+
+    /// token_reminder_time as a time of day, None when it does not parse
+    pub fn reminder_time(&self) -> Option<NaiveTime> {
+        NaiveTime::parse_from_str(self.token_reminder_time.trim(), "%H:%M").ok()
+    }
+}
+
+// Debug output ends up in logs, so credentials are masked and other profiles only named
+impl std::fmt::Debug for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Config")
+            .field("zerodha", &self.zerodha)
+            .field("app", &self.app)
+            .field("auto_login", &self.auto_login)
+            .field(
+                "profiles",
+                &self
+                    .profiles
+                    .as_ref()
+                    .map(|profiles| profiles.keys().collect::<Vec<_>>()),
+            )
+            .field("server", &self.server)
+            .field("performance", &self.performance)
+            .field("notifier", &self.notifier)
+            .field("strategies", &self.strategies)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for ServerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerConfig")
+            .field("bind_address", &self.bind_address)
+            .field("api_token", &redact::mask(&self.api_token))
+            .field("broadcast_port", &self.broadcast_port)
+            .field("metrics_port", &self.metrics_port)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for ZerodhaConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZerodhaConfig")
+            .field("api_key", &redact::mask(&self.api_key))
+            .field("api_secret", &redact::mask(&self.api_secret))
+            .field("access_token", &redact::mask(&self.access_token))
+            .field("callback_port", &self.callback_port)
+            .field("access_token_issued_at", &self.access_token_issued_at)
+            .finish()
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_callback_port() -> u16 {
+    8080
+}
+
+fn default_bind_address() -> String {
+    "127.0.0.1:8787".to_string()
+}
+
+fn default_tick_history_size() -> usize {
+    300
+}
+
+fn default_large_trade_quantity() -> u32 {
+    1000
+}
+
+fn default_event_backlog_warn_threshold() -> usize {
+    1000
+}
+
+fn default_heatmap_full_scale_pct() -> f64 {
+    3.0
+}
+
+fn default_ntp_server() -> String {
+    "pool.ntp.org".to_string()
+}
+
+fn default_token_reminder_time() -> String {
+    "08:45".to_string()
+}
+
+fn default_paper_slippage_pct() -> f64 {
+    0.05
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    5
+}
+
+fn refresh_interval(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Configuration file, resolved against the working directory
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Access token value shipped in the sample config
+pub const PLACEHOLDER_ACCESS_TOKEN: &str = "your_access_token_here";
+
+pub const LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+
+/// Exchanges Kite publishes an instrument master for
+pub const INSTRUMENT_EXCHANGES: &[&str] = &["NSE", "BSE", "NFO", "BFO", "CDS", "BCD", "MCX"];
+
+/// IST time after which the day's instrument master is out; new strikes and expiries
+/// show up in it
+const INSTRUMENTS_REFRESH_TIME: NaiveTime = match NaiveTime::from_hms_opt(8, 30, 0) {
+    Some(time) => time,
+    None => panic!("invalid instruments refresh time"),
+};
+
+/// Days of daily P&L the P&L view's History section shows
+pub const DAILY_HISTORY_DAYS: u64 = 30;
+
+/// Shortest server.api_token accepted, so a guessable one can't authorize orders
+const MIN_API_TOKEN_LEN: usize = 16;
+
+/// Every problem found in config.toml, reported together on the setup screen
+#[derive(Debug, Clone)]
+pub struct ConfigReport {
+    pub path: PathBuf,
+    pub problems: Vec<String>,
 }
 
 impl Config {
     /// Load configuration from config.toml with comprehensive error handling
     pub fn load() -> Result<Self, figment::Error> {
-        Figment::new().merge(Toml::file("config.toml")).extract()
+        Figment::new().merge(Toml::file(CONFIG_FILE)).extract()
+    }
+
+    /// Load config.toml and validate it, collecting all problems instead of panicking
+    pub fn load_validated() -> Result<Self, ConfigReport> {
+        let path = std::env::current_dir()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .unwrap_or_else(|_| PathBuf::from(CONFIG_FILE));
+
+        if !path.exists() {
+            return Err(ConfigReport {
+                path,
+                problems: vec![format!(
+                    "{} was not found; copy the sample from README.md and fill in your Zerodha credentials",
+                    CONFIG_FILE
+                )],
+            });
+        }
+
+        let config = Self::load().map_err(|e| ConfigReport {
+            path: path.clone(),
+            problems: e.into_iter().map(|e| e.to_string()).collect(),
+        })?;
+
+        let problems = config.validate();
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigReport { path, problems })
+        }
+    }
+
+    /// Write the config back as TOML; comments in the existing file are not kept
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let toml = toml::to_string_pretty(self)?;
+        std::fs::write(path, toml)?;
+        Ok(())
+    }
+
+    /// Take the hot-reloadable settings from a re-parsed config, keeping credentials
+    pub fn apply_reload(&mut self, reloaded: &Config) {
+        // The broker is chosen once at startup
+        let mode = self.app.mode;
+        self.app = reloaded.app.clone();
+        self.app.mode = mode;
+        self.zerodha.callback_port = reloaded.zerodha.callback_port;
+        self.notifier = reloaded.notifier.clone();
+    }
+
+    /// Whether a re-parsed config carries different Zerodha credentials
+    pub fn credentials_differ(&self, other: &Config) -> bool {
+        self.zerodha.api_key != other.zerodha.api_key
+            || self.zerodha.api_secret != other.zerodha.api_secret
+            || self.zerodha.access_token != other.zerodha.access_token
+    }
+
+    /// Check credentials and numeric ranges, returning a readable message per problem
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let unset = |value: &str| value.trim().is_empty() || value.starts_with("your_");
+
+        if unset(&self.zerodha.api_key) {
+            problems.push("zerodha.api_key is not set".to_string());
+        }
+        if unset(&self.zerodha.api_secret) {
+            problems.push("zerodha.api_secret is not set".to_string());
+        }
+        if self.zerodha.access_token.trim().is_empty()
+            || self.zerodha.access_token == PLACEHOLDER_ACCESS_TOKEN
+        {
+            problems.push(
+                "zerodha.access_token is missing or still the placeholder; run `cargo run --bin auth_helper` to generate one"
+                    .to_string(),
+            );
+        }
+
+        let app = &self.app;
+        if !LOG_LEVELS.contains(&app.log_level.to_lowercase().as_str()) {
+            problems.push(format!(
+                "app.log_level \"{}\" must be one of: {}",
+                app.log_level,
+                LOG_LEVELS.join(", ")
+            ));
+        }
+        if app.websocket_reconnect_delay_ms == 0 {
+            problems.push("app.websocket_reconnect_delay_ms must be greater than 0".to_string());
+        }
+        if app.max_reconnect_attempts == 0 {
+            problems.push("app.max_reconnect_attempts must be at least 1".to_string());
+        }
+        if !(1..=100_000).contains(&app.tick_history_size) {
+            problems.push(format!(
+                "app.tick_history_size must be between 1 and 100000 (got {})",
+                app.tick_history_size
+            ));
+        }
+        if app.event_backlog_warn_threshold == 0 {
+            problems.push("app.event_backlog_warn_threshold must be greater than 0".to_string());
+        }
+        if app.heatmap_full_scale_pct.is_nan() || app.heatmap_full_scale_pct <= 0.0 {
+            problems.push(format!(
+                "app.heatmap_full_scale_pct must be greater than 0 (got {})",
+                app.heatmap_full_scale_pct
+            ));
+        }
+        if !(0.0..=5.0).contains(&app.paper_slippage_pct) {
+            problems.push(format!(
+                "app.paper_slippage_pct must be between 0 and 5 (got {})",
+                app.paper_slippage_pct
+            ));
+        }
+        if !(1..=60).contains(&app.shutdown_timeout_secs) {
+            problems.push(format!(
+                "app.shutdown_timeout_secs must be between 1 and 60 (got {})",
+                app.shutdown_timeout_secs
+            ));
+        }
+        for exchange in &app.instrument_exchanges {
+            if !INSTRUMENT_EXCHANGES.contains(&exchange.as_str()) {
+                problems.push(format!(
+                    "app.instrument_exchanges has unknown exchange \"{}\"; expected any of: {}",
+                    exchange,
+                    INSTRUMENT_EXCHANGES.join(", ")
+                ));
+            }
+        }
+        if app.ntp_server.contains(char::is_whitespace) {
+            problems.push(format!(
+                "app.ntp_server \"{}\" must be a host name without spaces",
+                app.ntp_server
+            ));
+        }
+        if app.reminder_time().is_none() {
+            problems.push(format!(
+                "app.token_reminder_time \"{}\" must be an IST time like 08:45",
+                app.token_reminder_time
+            ));
+        }
+        if self
+            .server
+            .bind_address
+            .parse::<std::net::SocketAddr>()
+            .is_err()
+        {
+            problems.push(format!(
+                "server.bind_address \"{}\" must be an IP address and port like 127.0.0.1:8787",
+                self.server.bind_address
+            ));
+        }
+        if !self.server.api_token.is_empty() && self.server.api_token.len() < MIN_API_TOKEN_LEN {
+            problems.push(format!(
+                "server.api_token must be at least {} characters",
+                MIN_API_TOKEN_LEN
+            ));
+        }
+        if self.server.metrics_port != 0 && self.server.metrics_port == self.server.broadcast_port {
+            problems.push(format!(
+                "server.metrics_port and server.broadcast_port are both {}; they need ports of their own",
+                self.server.metrics_port
+            ));
+        }
+        problems.extend(self.performance.validate());
+        problems.extend(self.notifier.validate());
+        let mut strategy_names = HashSet::new();
+        for strategy in &self.strategies {
+            problems.extend(strategy.validate());
+            if !strategy_names.insert(strategy.name.as_str()) {
+                problems.push(format!(
+                    "strategy name \"{}\" is used more than once",
+                    strategy.name
+                ));
+            }
+        }
+        for (key, color) in [
+            ("accent_color", &app.accent_color),
+            ("profit_color", &app.profit_color),
+            ("loss_color", &app.loss_color),
+        ] {
+            if let Some(hex) = color {
+                if egui::Color32::from_hex(hex).is_err() {
+                    problems.push(format!(
+                        "app.{} \"{}\" must be a hex color like #22c55e",
+                        key, hex
+                    ));
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+#[cfg(test)]
+impl Config {
+    /// Live-mode settings with placeholder credentials, and background refresh and margin
+    /// pre-checks off, so nothing reaches Kite unless a test asks for it
+    pub(crate) fn for_tests() -> Self {
+        toml::from_str(
+            r#"
+            [zerodha]
+            api_key = "test_api_key"
+            api_secret = "test_api_secret"
+            access_token = "test_access_token"
+
+            [app]
+            log_level = "info"
+            websocket_reconnect_delay_ms = 1000
+            max_reconnect_attempts = 3
+            "#,
+        )
+        .expect("test config parses")
+    }
+}
+
+/// Market data socket state as last reported by the WebSocket worker
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConnectionState {
+    #[default]
+    Connecting,
+    Connected,
+    Reconnecting {
+        attempt: u32,
+    },
+    Disconnected,
+    // The access token was refused; reconnecting will not help until it is replaced
+    AuthExpired,
+}
+
+impl ConnectionState {
+    /// Plain name, for places without room for the status bar's colors
+    pub fn label(self) -> &'static str {
+        match self {
+            ConnectionState::Connecting => "Connecting",
+            ConnectionState::Connected => "Connected",
+            ConnectionState::Reconnecting { .. } => "Reconnecting",
+            ConnectionState::Disconnected => "Disconnected",
+            ConnectionState::AuthExpired => "Auth expired",
+        }
+    }
+}
+
+/// Background workers the app supervises, restarting them after a panic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Worker {
+    Api,
+    WebSocket,
+}
+
+impl Worker {
+    pub fn label(self) -> &'static str {
+        match self {
+            Worker::Api => "API handler",
+            Worker::WebSocket => "WebSocket handler",
+        }
     }
 }
 
 /// Authentication states for the trading application
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum AuthState {
     LoggedIn {
         access_token: String,
@@ -47,6 +700,23 @@ pub enum AuthState {
     },
 }
 
+impl std::fmt::Debug for AuthState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthState::LoggedIn {
+                access_token,
+                user_name,
+                user_profile,
+            } => f
+                .debug_struct("LoggedIn")
+                .field("access_token", &redact::mask(access_token))
+                .field("user_name", user_name)
+                .field("user_profile", user_profile)
+                .finish(),
+        }
+    }
+}
+
 /// Commands sent from UI thread to worker threads
 /// Designed for zero-allocation message passing using crossbeam-channel
 #[derive(Debug, Clone)]
@@ -54,10 +724,28 @@ pub enum Command {
     // Data fetching commands
     FetchPositions,
     FetchOrders,
+    FetchTrades,
+    FetchOrderHistory {
+        order_id: String,
+    },
+    FetchMargins,
+    // Margin and charges for an order form, answered with OrderMarginPreviewed
+    PreviewOrderMargin {
+        preview_id: u64,
+        details: OrderRequest,
+    },
     FetchUserProfile,
     FetchInstruments {
         exchange: String,
     },
+    FetchHistoricalCandles {
+        instrument_token: u32,
+    },
+    // Charges for closing a position, answered with ExitChargesEstimated
+    EstimateExitCharges {
+        instrument_token: u32,
+        details: OrderRequest,
+    },
 
     // WebSocket commands
     SubscribeToTicks {
@@ -66,6 +754,10 @@ pub enum Command {
     UnsubscribeFromTicks {
         instrument_tokens: Vec<u32>,
     },
+    SetTickMode {
+        instrument_tokens: Vec<u32>,
+        mode: TickMode,
+    },
 
     // Trading commands
     PlaceOrder {
@@ -77,6 +769,73 @@ pub enum Command {
     },
     CancelOrder {
         order_id: String,
+        // The order's variety, which Kite's cancel endpoint takes in its path
+        variety: String,
+    },
+    SquareOffByTag {
+        tag: String,
+    },
+
+    // Watchlist commands, applied by AppState on the UI thread
+    AddToWatchlist {
+        instrument_token: u32,
+        note: Option<String>,
+    },
+    RemoveFromWatchlist {
+        instrument_token: u32,
+    },
+    ReorderWatchlist {
+        from: usize,
+        to: usize,
+    },
+
+    // Price alert commands, applied by AppState on the UI thread
+    CreateAlert {
+        alert: PriceAlert,
+    },
+    UpdateAlert {
+        alert: PriceAlert,
+    },
+    DeleteAlert {
+        alert_id: String,
+    },
+
+    // Export commands, written by AppState from its current snapshot
+    ExportPositions {
+        path: PathBuf,
+    },
+    ExportOrders {
+        path: PathBuf,
+    },
+    ExportTrades {
+        path: PathBuf,
+    },
+
+    // Re-authentication: trade a login request_token for an access token, answered with
+    // SessionGenerated, then hand the new token to both workers
+    ExchangeRequestToken {
+        request_token: String,
+    },
+    UpdateAccessToken {
+        access_token: String,
+    },
+    // Listen on the callback port for the login redirect and open the login page,
+    // answered with LoginCallback; a new request or CancelLoginCallback stops the last one
+    AwaitLoginCallback {
+        port: u16,
+        login_url: String,
+    },
+    CancelLoginCallback,
+
+    // Paper trading: a simulated fill changed the paper book, so re-read orders, positions
+    // and trades from it
+    SyncPaperBook,
+
+    // Health check of credentials entered in Settings, answered with ConnectionTested
+    TestConnection {
+        api_key: String,
+        api_secret: String,
+        access_token: String,
     },
 
     // Connection management
@@ -84,6 +843,56 @@ pub enum Command {
     Shutdown,
 }
 
+impl AppEvent {
+    /// Ticks and depth, which stream too fast to wake the UI one by one
+    pub fn is_market_data(&self) -> bool {
+        matches!(
+            self,
+            AppEvent::TickUpdate { .. } | AppEvent::DepthUpdate { .. }
+        )
+    }
+}
+
+/// Which worker consumes a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CommandRoute {
+    Api,
+    // The API worker's priority channel, read ahead of everything else queued for it
+    ApiPriority,
+    WebSocket,
+    Both,
+}
+
+impl Command {
+    fn route(&self) -> CommandRoute {
+        match self {
+            Command::SubscribeToTicks { .. }
+            | Command::UnsubscribeFromTicks { .. }
+            | Command::SetTickMode { .. }
+            | Command::ReconnectWebSocket => CommandRoute::WebSocket,
+            Command::UpdateAccessToken { .. } | Command::Shutdown => CommandRoute::Both,
+            // Order actions never wait behind fetches
+            Command::PlaceOrder { .. }
+            | Command::ModifyOrder { .. }
+            | Command::CancelOrder { .. }
+            | Command::SquareOffByTag { .. } => CommandRoute::ApiPriority,
+            _ => CommandRoute::Api,
+        }
+    }
+}
+
+/// The API worker's end of one of its command channels; a restarted worker takes over the
+/// receiver its predecessor held
+pub type ApiCommandReceiver = Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<Command>>>;
+
+/// Command channels of the two workers
+/// A channel delivers each command to one receiver, so every worker gets its own
+pub struct CommandReceivers {
+    pub api: ApiCommandReceiver,
+    pub api_priority: ApiCommandReceiver,
+    pub websocket: Receiver<Command>,
+}
+
 /// Events sent from worker threads back to UI thread
 /// Optimized for high-frequency updates without blocking the UI
 #[derive(Debug, Clone)]
@@ -91,15 +900,50 @@ pub enum AppEvent {
     // Data update events
     PositionsUpdated(Vec<Position>),
     OrdersUpdated(Vec<Order>),
+    TradesUpdated(Vec<Trade>),
+    OrderHistoryLoaded {
+        order_id: String,
+        history: OrderHistory,
+    },
     UserProfileUpdated(UserProfile),
-    InstrumentsUpdated(Vec<Instrument>),
+    // One exchange's full instrument master
+    InstrumentsUpdated {
+        exchange: String,
+        instruments: Vec<Instrument>,
+    },
 
     // Real-time market data events (high frequency)
     TickUpdate {
         instrument_token: u32,
         last_price: f64,
+        last_quantity: u32,
         volume: u64,
         timestamp: DateTime<Utc>,
+        // When the exchange stamped the tick, to the second; full mode only
+        exchange_timestamp: Option<DateTime<Utc>>,
+    },
+    DepthUpdate {
+        instrument_token: u32,
+        depth: MarketDepth,
+    },
+    MarginsUpdated(Margins),
+    OrderMarginPreviewed {
+        preview_id: u64,
+        result: Result<OrderMargin, String>,
+    },
+    HistoricalCandlesLoaded {
+        instrument_token: u32,
+        candles: Vec<Candle>,
+    },
+    // Bars from earlier sessions, read back from the history store
+    StoredCandlesLoaded {
+        instrument_token: u32,
+        candles: Vec<Candle>,
+    },
+    ExitChargesEstimated {
+        instrument_token: u32,
+        quantity: i32,
+        result: Result<f64, String>,
     },
 
     // WebSocket connection events
@@ -108,10 +952,22 @@ pub enum AppEvent {
     WebSocketReconnecting {
         attempt: u32,
     },
+    // Kite refused the access token, at the WebSocket handshake or a profile check
+    AccessTokenRejected,
+    // The API worker finished a command or background refresh
+    ApiRequestTimed(Duration),
+    // A category of API request started or stopped failing fast
+    ApiCircuitChanged {
+        category: ApiCategory,
+        state: CircuitState,
+    },
+    // The local clock was checked against the NTP server
+    ClockOffsetMeasured(Result<ClockOffset, String>),
 
     // Trading events
     OrderPlaced {
         order_id: String,
+        request_id: Option<u64>,
     },
     OrderModified {
         order_id: String,
@@ -124,16 +980,64 @@ pub enum AppEvent {
         fill_price: f64,
         fill_quantity: i32,
     },
+    OrderRejected {
+        order_id: Option<String>, // None when the API refused the order outright
+        tradingsymbol: String,
+        reason: String,
+        request_id: Option<u64>,
+    },
+    // A placement that got no answer from the broker, so the order may or may not exist
+    OrderPlaceFailed {
+        tradingsymbol: String,
+        error: String,
+        request_id: Option<u64>,
+    },
+    AlertTriggered {
+        alert: PriceAlert,
+        price: f64,
+    },
+    TagSquaredOff {
+        tag: String,
+        orders_placed: usize,
+        failures: Vec<String>,
+    },
+    // An order, cancellation or subscription from a running strategy, checked like the
+    // user's own before it goes to a worker
+    StrategyCommand {
+        strategy: String,
+        command: Command,
+    },
 
     // System events
+    // A worker task panicked; its supervisor starts it again after `restart_in`
+    WorkerPanicked {
+        worker: Worker,
+        message: String,
+        restart_in: Duration,
+    },
+    ConfigReloaded(Box<Config>),
+    ConnectionTested(Result<(), String>),
+    // Answer to the Settings view's test notification
+    NotificationTested(Result<(), String>),
+    // Access token from a re-authentication, or why the exchange failed
+    SessionGenerated(Result<String, String>),
+    // request_token captured from the login redirect, or why none arrived
+    LoginCallback(Result<String, String>),
     Notification {
         level: LogLevel,
         message: String,
         module: Option<String>,
     },
+    // A `log` record from any crate, already on stderr unless RUST_LOG filtered it out
+    LogRecord {
+        level: LogLevel,
+        message: String,
+        module: Option<String>,
+    },
     Error {
         error: String,
         module: Option<String>,
+        severity: ErrorSeverity,
     },
 }
 
@@ -142,320 +1046,5706 @@ pub enum AppEvent {
 pub struct UiInputState {
     // Order placement fields
     pub order_symbol_input: String,
-    pub order_quantity_input: String,
-    pub order_price_input: String,
+    pub order_instrument: Option<Instrument>, // Set when picked from autocomplete
+    pub symbol_suggestions: Vec<u32>,
+    pub order_quantity_input: ValidatedField<i32>,
+    // Lot size of the resolved derivative; None keeps quantity a plain integer
+    pub order_lot_size: Option<u32>,
+    pub order_price_input: ValidatedField<f64>,
+    pub order_trigger_price_input: ValidatedField<f64>,
+    // Minutes for TTL validity
+    pub order_ttl_input: ValidatedField<u32>,
+    pub margin_preview: MarginPreview,
 
     // Filters
     pub position_filter: String,
+    pub position_filters: PositionFilters,
+    pub position_sort: SortState<PositionColumn>,
+    pub position_columns: ColumnLayout<PositionColumn>,
+    pub position_rows: RowCache<TableKey<PositionColumn>, PositionGroup>,
+    // Exchanges whose positions are folded away
+    pub collapsed_position_groups: HashSet<String>,
+    // Positions showing their detail panel, by Position::key
+    pub expanded_positions: HashSet<(u32, String)>,
     pub order_filter: String,
+    pub order_tab: OrderTab,
+    pub order_sort: SortState<OrderColumn>,
+    pub order_columns: ColumnLayout<OrderColumn>,
+    pub order_rows: RowCache<TableKey<OrderColumn>, Order>,
+    // Orders ticked for bulk cancel
+    pub selected_order_ids: HashSet<String>,
+    // Row order shown while the pointer is over the orders table
+    pub frozen_order_ids: Option<Vec<String>>,
     pub log_filter: String,
+    pub log_min_level: LogLevel,
+    pub log_module: Option<String>,
+    // Logs frozen by Pause; new entries keep accumulating in AppState::logs
+    pub log_snapshot: Option<Vec<LogEntry>>,
+    // Set by scrolling up the logs, which stops them following new entries until Follow is ticked
+    pub log_scrolled_up: bool,
+    // Set once the Trades view has asked for the tradebook
+    pub trades_requested: bool,
+
+    // Directory for CSV/JSON exports, blank for the working directory
+    pub export_dir: String,
 
     // UI state
     pub show_order_dialog: bool,
+    pub order_edit: Option<OrderEditForm>,
+    pub pending_market_orders: Option<PendingMarketOrders>,
+    // Quantity being chosen in a position's square-off popover
+    pub square_off_draft: Option<SquareOffDraft>,
+    // Stop-loss/target dialog opened from a position row
+    pub protection_form: Option<ProtectionForm>,
+    // Price alert dialog opened from the watchlist
+    pub alert_form: Option<AlertForm>,
+    // Open orders awaiting confirmation before they are all cancelled
+    pub pending_cancel_all: Option<Vec<String>>,
+    // Draft of config.toml being edited in the Settings view
+    pub settings_form: Option<SettingsForm>,
+    // Login flow opened from the expired-token banner
+    pub reauth: Option<ReauthForm>,
+    // Report of a crash in the last session, offered until dismissed
+    pub crash_report: Option<PathBuf>,
+    pub shortcuts: ShortcutTable,
+    pub show_shortcut_help: bool,
     pub selected_order_type: OrderType,
     pub selected_transaction_type: TransactionType,
     pub selected_product_type: ProductType,
-}
+    pub selected_validity: Validity,
+    pub selected_exchange: Exchange,
 
-#[derive(Debug, Clone, Copy, Default)]
-pub enum OrderType {
-    #[default]
-    Market,
-    Limit,
-    StopLoss,
-    StopLossMarket,
-}
+    // Watchlist panel
+    pub watchlist_symbol_input: String,
+    pub watchlist_exchange: Exchange,
+    pub watchlist_suggestions: Vec<u32>,
+    // Tiles colored by day change instead of the table
+    pub watchlist_heatmap: bool,
+    pub price_flashes: PriceFlashes,
+    // Order whose detail drawer is open
+    pub inspected_order_id: Option<String>,
+    // Instrument clicked last in a table, streamed in full mode
+    pub selected_instrument_token: Option<u32>,
+    // Equity curve series toggles
+    pub equity_series: EquitySeries,
+    // Instruments view query and its background results
+    pub instrument_search: InstrumentSearch,
+    // Tick tape frozen while the pointer is over it, with the instrument it shows
+    pub tape_snapshot: Option<(u32, Vec<TickSample>)>,
+    // Positions and watchlist ranked by day change for the Overview movers cards
+    pub day_movers: RowCache<MoversKey, DayMover>,
+    // How the P&L breakdown groups positions, and which groups are folded away
+    pub pnl_grouping: PnlGrouping,
+    pub collapsed_pnl_groups: HashSet<String>,
 
-#[derive(Debug, Clone, Copy, Default)]
-pub enum TransactionType {
-    #[default]
-    Buy,
-    Sell,
-}
+    // Charts view
+    pub chart_interval: CandleInterval,
+    pub chart_viewport: ChartViewport,
+    pub chart_bars: ResampledCandles,
+    pub chart_overlays: ChartOverlays,
+    pub exit_charges: Option<ExitCharges>,
 
-#[derive(Debug, Clone, Copy, Default)]
-pub enum ProductType {
-    #[default]
-    CNC, // Cash and Carry
-    MIS,  // Margin Intraday Squareoff
-    NRML, // Normal
+    // View the UI should switch to on the next frame
+    pub requested_view: Option<AppView>,
+    // Views popped out into their own OS windows
+    pub detached_views: Vec<DetachedView>,
 }
 
-/// Main application state using high-performance concurrent data structures
-/// All collections use lock-free designs for ultra-low latency access
-pub struct AppState {
-    // Configuration
-    pub config: Config,
+impl UiInputState {
+    /// Re-run every order form validator, e.g. after an edit or an order type change
+    pub fn validate_order_fields(&mut self) {
+        let order_type = self.selected_order_type;
 
-    // Authentication state
-    pub auth_state: Arc<RwLock<AuthState>>,
+        let lot_size = self.order_lot_size;
 
-    // Trading data - using DashMap for lock-free concurrent access
-    pub positions: Arc<DashMap<u32, Position>>, // keyed by instrument_token
-    pub orders: Arc<DashMap<String, Order>>,    // keyed by order_id
-    pub instruments: Arc<DashMap<u32, Instrument>>, // keyed by instrument_token
+        self.order_quantity_input.validate(true, |quantity| {
+            positive(quantity, "Quantity")?;
+            match lot_size {
+                Some(lot_size) if quantity % lot_size as i32 != 0 => Err(format!(
+                    "Quantity must be a multiple of the lot size {}",
+                    lot_size
+                )),
+                _ => Ok(()),
+            }
+        });
+        self.order_price_input
+            .validate(order_type.requires_price(), |price| {
+                positive(price, "Price")
+            });
+        self.order_trigger_price_input
+            .validate(order_type.requires_trigger(), |trigger| {
+                positive(trigger, "Trigger price")
+            });
+        self.order_ttl_input
+            .validate(self.selected_validity == Validity::Ttl, |minutes| {
+                positive(minutes, "Validity minutes")
+            });
+    }
 
-    // User profile
-    pub user_profile: Arc<RwLock<Option<UserProfile>>>,
+    /// Whether the order form can be submitted as it stands
+    pub fn order_form_ready(&self) -> bool {
+        !self.order_symbol_input.trim().is_empty()
+            && self.order_quantity_input.value().is_some()
+            && self.order_quantity_input.is_valid()
+            && self.order_price_input.is_valid()
+            && self.order_trigger_price_input.is_valid()
+            && self.order_ttl_input.is_valid()
+    }
 
-    // Real-time data
-    pub tick_data: Arc<DashMap<u32, TickData>>, // keyed by instrument_token
+    /// Open the order dialog pre-filled for an instrument and switch to the Orders view
+    /// The form is only reset when it was filled for a different symbol
+    pub fn open_order_for(&mut self, symbol: &str, instrument: Option<Instrument>) {
+        if self.order_symbol_input != symbol {
+            self.clear_order_fields();
+            self.order_symbol_input = symbol.to_string();
+        }
+        if let Some(exchange) = instrument
+            .as_ref()
+            .and_then(|instrument| instrument.exchange.parse::<Exchange>().ok())
+        {
+            self.selected_exchange = exchange;
+        }
+        if instrument.is_some() {
+            self.order_instrument = instrument;
+        }
+        self.show_order_dialog = true;
+        self.requested_view = Some(AppView::Orders);
+    }
 
-    // Application logs with reader-writer lock for batch operations
-    pub logs: Arc<RwLock<Vec<LogEntry>>>,
+    /// Reset the order form after submission
+    pub fn clear_order_fields(&mut self) {
+        self.order_symbol_input.clear();
+        self.order_instrument = None;
+        self.symbol_suggestions.clear();
+        self.order_quantity_input.clear();
+        self.order_price_input.clear();
+        self.order_trigger_price_input.clear();
+        self.order_ttl_input.clear();
+    }
+}
 
-    // UI state
-    pub ui_input: UiInputState,
+/// A view shown in its own OS window, with where that window was last seen
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DetachedView {
+    pub view: AppView,
+    pub position: Option<egui::Pos2>,
+    pub size: Option<egui::Vec2>,
+}
 
-    // Communication channels
-    pub command_sender: Sender<Command>,
-    pub event_receiver: Receiver<AppEvent>,
+impl DetachedView {
+    pub fn new(view: AppView) -> Self {
+        Self {
+            view,
+            position: None,
+            size: None,
+        }
+    }
 
-    // Performance metrics
-    pub metrics: Arc<RwLock<PerformanceMetrics>>,
+    pub fn viewport_id(&self) -> egui::ViewportId {
+        egui::ViewportId::from_hash_of(("detached_view", self.view))
+    }
 }
 
-/// Performance metrics for monitoring system health
-#[derive(Debug, Default)]
-pub struct PerformanceMetrics {
-    pub ticks_processed: u64,
-    pub orders_processed: u64,
-    pub websocket_reconnections: u32,
-    pub last_tick_timestamp: Option<DateTime<Utc>>,
-    pub average_tick_latency_ms: f64,
+/// Key the view, filters and sort choices are stored under in eframe's app storage
+pub const UI_STATE_STORAGE_KEY: &str = "ui_state";
+
+/// View, filter and sort choices restored on the next launch
+/// Missing fields default, so state saved by an older build still loads
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedUiState {
+    pub current_view: AppView,
+    pub position_filter: String,
+    pub position_filters: PositionFilters,
+    pub position_sort: SortState<PositionColumn>,
+    pub position_columns: ColumnLayout<PositionColumn>,
+    pub order_filter: String,
+    pub order_tab: OrderTab,
+    pub order_sort: SortState<OrderColumn>,
+    pub order_columns: ColumnLayout<OrderColumn>,
+    pub detached_views: Vec<DetachedView>,
+    pub log_filter: String,
+    pub log_min_level: LogLevel,
+    pub log_module: Option<String>,
+    pub selected_instrument_token: Option<u32>,
+    pub equity_series: EquitySeries,
+    pub pnl_grouping: PnlGrouping,
+    pub chart_interval: CandleInterval,
+    pub chart_overlays: ChartOverlays,
+    pub watchlist_heatmap: bool,
 }
 
-impl AppState {
-    /// Create new application state with initialized channels and data structures
-    pub fn new(config: Config) -> (Self, Receiver<Command>) {
-        let (command_sender, command_receiver) = crossbeam_channel::unbounded();
-        let (_event_sender, event_receiver) = crossbeam_channel::unbounded();
+impl SavedUiState {
+    /// Snapshot the parts of the live UI state worth keeping
+    pub fn capture(current_view: AppView, ui_input: &UiInputState) -> Self {
+        Self {
+            current_view,
+            position_filter: ui_input.position_filter.clone(),
+            position_filters: ui_input.position_filters.clone(),
+            position_sort: ui_input.position_sort,
+            position_columns: ui_input.position_columns.clone(),
+            order_filter: ui_input.order_filter.clone(),
+            order_tab: ui_input.order_tab,
+            detached_views: ui_input.detached_views.clone(),
+            order_sort: ui_input.order_sort,
+            order_columns: ui_input.order_columns.clone(),
+            log_filter: ui_input.log_filter.clone(),
+            log_min_level: ui_input.log_min_level,
+            log_module: ui_input.log_module.clone(),
+            selected_instrument_token: ui_input.selected_instrument_token,
+            equity_series: ui_input.equity_series,
+            pnl_grouping: ui_input.pnl_grouping,
+            chart_interval: ui_input.chart_interval,
+            chart_overlays: ui_input.chart_overlays,
+            watchlist_heatmap: ui_input.watchlist_heatmap,
+        }
+    }
 
-        // Initialize with a mock logged-in state for personal trading
-        let initial_auth_state = AuthState::LoggedIn {
-            access_token: "mock_token".to_string(),
-            user_name: "Personal Trading".to_string(),
-            user_profile: None,
-        };
+    /// Copy the saved choices into the live UI state, returning the view to show
+    pub fn restore(self, ui_input: &mut UiInputState) -> AppView {
+        ui_input.position_filter = self.position_filter;
+        ui_input.position_filters = self.position_filters;
+        ui_input.position_sort = self.position_sort;
+        ui_input.position_columns = self.position_columns;
+        ui_input.position_columns.reconcile();
+        ui_input.order_filter = self.order_filter;
+        ui_input.order_tab = self.order_tab;
+        ui_input.detached_views = self.detached_views;
+        ui_input.order_sort = self.order_sort;
+        ui_input.order_columns = self.order_columns;
+        ui_input.order_columns.reconcile();
+        ui_input.log_filter = self.log_filter;
+        ui_input.log_min_level = self.log_min_level;
+        ui_input.log_module = self.log_module;
+        ui_input.selected_instrument_token = self.selected_instrument_token;
+        ui_input.equity_series = self.equity_series;
+        ui_input.pnl_grouping = self.pnl_grouping;
+        ui_input.chart_interval = self.chart_interval;
+        ui_input.chart_overlays = self.chart_overlays;
+        ui_input.watchlist_heatmap = self.watchlist_heatmap;
+        self.current_view
+    }
+}
 
-        let state = Self {
-            config,
-            auth_state: Arc::new(RwLock::new(initial_auth_state)),
-            positions: Arc::new(DashMap::with_capacity(1000)),
-            orders: Arc::new(DashMap::with_capacity(10000)),
-            instruments: Arc::new(DashMap::with_capacity(50000)),
-            user_profile: Arc::new(RwLock::new(None)),
-            tick_data: Arc::new(DashMap::with_capacity(1000)),
-            logs: Arc::new(RwLock::new(Vec::with_capacity(10000))),
-            ui_input: UiInputState::default(),
-            command_sender,
-            event_receiver,
-            metrics: Arc::new(RwLock::new(PerformanceMetrics::default())),
-        };
+/// Which P&L lines the equity curve draws
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EquitySeries {
+    pub total: bool,
+    pub realized: bool,
+    pub unrealized: bool,
+}
 
-        (state, command_receiver)
+impl Default for EquitySeries {
+    fn default() -> Self {
+        Self {
+            total: true,
+            realized: true,
+            unrealized: true,
+        }
     }
+}
 
-    /// Add log entry with automatic timestamping
-    pub fn add_log(&self, level: LogLevel, message: String, module: Option<String>) {
-        let log_entry = LogEntry::new(level, message, module);
-        let mut logs = self.logs.write();
+/// Which price lines the candlestick chart draws over the bars
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ChartOverlays {
+    pub average: bool,
+    pub breakeven: bool,
+    pub orders: bool,
+}
 
-        // Keep only the last 10000 log entries for memory efficiency
-        if logs.len() >= 10000 {
-            logs.drain(0..1000);
+impl Default for ChartOverlays {
+    fn default() -> Self {
+        Self {
+            average: true,
+            breakeven: true,
+            orders: true,
         }
+    }
+}
 
-        logs.push(log_entry);
+/// Estimated charges for closing the charted position, behind its breakeven line
+/// Re-requested whenever the position's token or quantity changes
+#[derive(Debug, Clone)]
+pub struct ExitCharges {
+    pub instrument_token: u32,
+    pub quantity: i32,
+    pub result: Option<Result<f64, String>>,
+}
+
+/// Key the P&L breakdown groups positions by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PnlGrouping {
+    #[default]
+    None,
+    Tag,
+    Product,
+    Exchange,
+}
+
+impl PnlGrouping {
+    pub const ALL: [PnlGrouping; 4] = [
+        PnlGrouping::None,
+        PnlGrouping::Tag,
+        PnlGrouping::Product,
+        PnlGrouping::Exchange,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PnlGrouping::None => "None",
+            PnlGrouping::Tag => "Tag",
+            PnlGrouping::Product => "Product",
+            PnlGrouping::Exchange => "Exchange",
+        }
     }
+}
 
-    /// Update position with new tick data - optimized for high frequency updates
-    pub fn update_position_price(&self, instrument_token: u32, last_price: f64) {
-        if let Some(mut position) = self.positions.get_mut(&instrument_token) {
-            position.update_last_price(last_price);
+/// Bucket for positions no tagged order contributed to
+pub const UNTAGGED_GROUP: &str = "untagged";
+
+/// One group of the P&L breakdown: its key, subtotal and positions in the positions
+/// table's order
+pub type PnlGroup<'a> = (String, PnlData, Vec<&'a Position>);
+
+/// Visible window of the candlestick chart, anchored to the latest bar
+#[derive(Debug, Clone, Copy)]
+pub struct ChartViewport {
+    pub visible_bars: usize,
+    pub bars_from_end: f32, // how far the view is panned back from the latest bar
+}
+
+impl Default for ChartViewport {
+    fn default() -> Self {
+        Self {
+            visible_bars: 60,
+            bars_from_end: 0.0,
         }
     }
+}
 
-    /// Calculate total PnL across all positions
-    pub fn calculate_total_pnl(&self) -> PnlData {
-        let mut realized = 0.0;
-        let mut unrealized = 0.0;
+/// How long a price cell stays tinted after the price ticks
+pub const PRICE_FLASH_DURATION: Duration = Duration::from_millis(500);
 
-        for entry in self.positions.iter() {
-            let position = entry.value();
-            realized += position.realized_pnl;
-            unrealized += position.unrealized_pnl;
+/// Last rendered price per instrument, used to flash cells when the price moves
+#[derive(Debug, Default, Clone)]
+pub struct PriceFlashes {
+    entries: HashMap<u32, PriceFlash>,
+}
+
+#[derive(Debug, Clone)]
+struct PriceFlash {
+    price: f64,
+    changed: Option<(Instant, bool)>, // when the price last moved and whether it went up
+}
+
+impl PriceFlashes {
+    /// Record the price about to be rendered
+    /// Returns whether the last move was up and the remaining flash intensity in 0..=1
+    pub fn observe(&mut self, instrument_token: u32, price: f64) -> Option<(bool, f32)> {
+        let now = Instant::now();
+        let flash = self.entries.entry(instrument_token).or_insert(PriceFlash {
+            price,
+            changed: None,
+        });
+
+        if price != flash.price {
+            flash.changed = Some((now, price > flash.price));
+            flash.price = price;
         }
 
-        PnlData {
-            realized,
-            unrealized,
-            total: realized + unrealized,
-            day_pnl: unrealized, // Simplified - in real implementation, track day-specific PnL
-            day_realized: 0.0,
-            day_unrealized: unrealized,
+        let (changed_at, up) = flash.changed?;
+        let elapsed = now.duration_since(changed_at);
+        if elapsed >= PRICE_FLASH_DURATION {
+            flash.changed = None;
+            return None;
         }
+
+        Some((
+            up,
+            1.0 - elapsed.as_secs_f32() / PRICE_FLASH_DURATION.as_secs_f32(),
+        ))
     }
+}
 
-    /// Get filtered orders based on tradingsymbol
-    pub fn get_filtered_orders(&self, filter: &str) -> Vec<Order> {
-        if filter.is_empty() {
-            self.orders
-                .iter()
-                .map(|entry| entry.value().clone())
-                .collect()
-        } else {
-            self.orders
-                .iter()
-                .filter(|entry| {
-                    entry
-                        .value()
-                        .tradingsymbol
-                        .to_lowercase()
-                        .contains(&filter.to_lowercase())
-                })
-                .map(|entry| entry.value().clone())
-                .collect()
+/// What a cached table was built from; any change rebuilds its rows
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableKey<C> {
+    pub revision: u64,
+    pub filter: String,
+    pub sort: SortState<C>,
+    pub frozen: bool,
+}
+
+/// Notifications kept in the center before the oldest are dropped
+const MAX_NOTIFICATIONS: usize = 200;
+
+/// Critical errors waiting to be shown before the oldest are dropped
+const MAX_CRITICAL_ERRORS: usize = 20;
+
+/// How loudly an AppEvent::Error is raised; workers pick it when they send the error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorSeverity {
+    /// Logged only, like a refresh the next one retries
+    Normal,
+    /// Also shown in a dialog until dismissed: an order action failed or the session ended
+    Critical,
+}
+
+/// Error shown in a dialog, waiting in AppState::critical_errors until dismissed
+#[derive(Debug, Clone)]
+pub struct CriticalError {
+    pub message: String,
+    pub module: Option<String>,
+    pub raised_at: DateTime<Utc>,
+}
+
+/// Event recorded in the notification center, unlike a toast it stays until cleared
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: LogLevel,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+    pub read: bool,
+    /// View opened on click
+    pub target: Option<NotificationTarget>,
+}
+
+/// Where a notification leads: a view, narrowed to a symbol or instrument when known
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotificationTarget {
+    pub view: AppView,
+    /// Filter applied to the orders and positions tables
+    pub symbol: Option<String>,
+    /// Instrument selected on the charts view; a symbol alone doesn't say which exchange
+    pub instrument_token: Option<u32>,
+}
+
+impl NotificationTarget {
+    pub fn view(view: AppView) -> Self {
+        Self {
+            view,
+            symbol: None,
+            instrument_token: None,
         }
     }
 
-    /// Send command to worker threads
-    pub fn send_command(&self, command: Command) {
-        if let Err(e) = self.command_sender.send(command) {
-            self.add_log(
-                LogLevel::Error,
-                format!("Failed to send command: {}", e),
-                Some("state".to_string()),
-            );
+    pub fn symbol(view: AppView, symbol: Option<String>) -> Self {
+        Self {
+            symbol,
+            ..Self::view(view)
         }
     }
 
-    /// Process all pending events from worker threads
-    pub fn process_events(&mut self) {
-        while let Ok(event) = self.event_receiver.try_recv() {
-            self.handle_event(event);
+    pub fn instrument(view: AppView, instrument_token: u32) -> Self {
+        Self {
+            instrument_token: Some(instrument_token),
+            ..Self::view(view)
         }
     }
+}
 
-    /// Handle individual events from worker threads
-    fn handle_event(&mut self, event: AppEvent) {
-        match event {
-            AppEvent::PositionsUpdated(positions) => {
-                self.positions.clear();
-                for position in positions {
-                    self.positions.insert(position.instrument_token, position);
-                }
+/// Filtered, sorted table rows kept between frames so tables don't re-clone every row
+/// Rows sit behind an Arc so a view can hold them while it mutates the rest of the state
+#[derive(Debug, Clone)]
+pub struct RowCache<K, T> {
+    key: Option<K>,
+    rows: Arc<Vec<T>>,
+}
 
-                self.add_log(
-                    LogLevel::Info,
-                    format!("Updated {} positions", self.positions.len()),
-                    Some("positions".to_string()),
-                );
-            }
+impl<K, T> Default for RowCache<K, T> {
+    fn default() -> Self {
+        Self {
+            key: None,
+            rows: Arc::new(Vec::new()),
+        }
+    }
+}
 
-            AppEvent::OrdersUpdated(orders) => {
-                // Update orders, preserving existing ones not in the update
-                for order in orders {
-                    self.orders.insert(order.order_id.clone(), order);
-                }
+impl<K: PartialEq, T> RowCache<K, T> {
+    pub fn is_current(&self, key: &K) -> bool {
+        self.key.as_ref() == Some(key)
+    }
 
-                self.add_log(
-                    LogLevel::Info,
-                    format!("Updated orders, total: {}", self.orders.len()),
-                    Some("orders".to_string()),
-                );
-            }
+    pub fn store(&mut self, key: K, rows: Vec<T>) {
+        self.key = Some(key);
+        self.rows = Arc::new(rows);
+    }
 
-            AppEvent::TickUpdate {
-                instrument_token,
-                last_price,
-                volume,
-                timestamp,
-            } => {
-                // Update position prices for real-time PnL calculation
-                self.update_position_price(instrument_token, last_price);
+    pub fn rows(&self) -> Arc<Vec<T>> {
+        Arc::clone(&self.rows)
+    }
+}
 
-                // Update tick data
-                if let Some(mut tick_data) = self.tick_data.get_mut(&instrument_token) {
-                    tick_data.last_price = last_price;
-                    tick_data.volume = volume;
-                    tick_data.timestamp_nanos = timestamp.timestamp_nanos();
-                } else {
-                    // Create new tick data entry
-                    let tick_data = TickData {
-                        instrument_token,
-                        last_price,
-                        last_quantity: 0,
-                        average_price: last_price,
-                        volume,
-                        buy_quantity: 0,
-                        sell_quantity: 0,
-                        ohlc: OHLC {
-                            open: last_price,
-                            high: last_price,
-                            low: last_price,
-                            close: last_price,
-                        },
-                        timestamp_nanos: timestamp.timestamp_nanos(),
-                    };
-                    self.tick_data.insert(instrument_token, tick_data);
-                }
+/// Quiet period after the last query edit before the Instruments view searches
+const INSTRUMENT_SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
 
-                // Update metrics
-                let mut metrics = self.metrics.write();
-                metrics.ticks_processed += 1;
-                metrics.last_tick_timestamp = Some(timestamp);
-            }
+/// Instruments view search, run on a background thread once the query settles
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentSearch {
+    pub query: InstrumentQuery,
+    pub page: usize,
+    // Query the results belong to
+    searched: Option<InstrumentQuery>,
+    changed_at: Option<Instant>,
+    results: Arc<Vec<u32>>,
+    pending: Option<Receiver<Vec<u32>>>,
+}
 
-            AppEvent::Notification {
-                level,
-                message,
-                module,
-            } => {
-                self.add_log(level, message, module);
+impl InstrumentSearch {
+    /// Collect finished results and start a search once the query has been still long enough
+    /// Returns how long until the debounce elapses, so the caller can schedule a repaint
+    pub fn update(&mut self, instruments: &Arc<DashMap<u32, Instrument>>) -> Option<Duration> {
+        if let Some(receiver) = &self.pending {
+            match receiver.try_recv() {
+                Ok(results) => {
+                    self.results = Arc::new(results);
+                    self.pending = None;
+                }
+                Err(crossbeam_channel::TryRecvError::Disconnected) => self.pending = None,
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
             }
+        }
 
-            AppEvent::Error { error, module } => {
-                self.add_log(LogLevel::Error, error, module);
-            }
+        if self.searched.as_ref() == Some(&self.query) {
+            self.changed_at = None;
+            return None;
+        }
 
-            // Handle other events...
-            _ => {
-                self.add_log(
-                    LogLevel::Debug,
-                    format!("Unhandled event: {:?}", event),
-                    Some("state".to_string()),
-                );
-            }
+        let changed_at = *self.changed_at.get_or_insert_with(Instant::now);
+        let waited = changed_at.elapsed();
+        if waited < INSTRUMENT_SEARCH_DEBOUNCE {
+            return Some(INSTRUMENT_SEARCH_DEBOUNCE - waited);
         }
+
+        // A newer query replaces the receiver, so results of an older scan are dropped
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let query = self.query.clone();
+        let instruments = Arc::clone(instruments);
+        std::thread::spawn(move || {
+            let _ = sender.send(query.run(&instruments));
+        });
+
+        self.pending = Some(receiver);
+        self.searched = Some(self.query.clone());
+        self.changed_at = None;
+        self.page = 0;
+        None
+    }
+
+    pub fn results(&self) -> Arc<Vec<u32>> {
+        Arc::clone(&self.results)
+    }
+
+    /// Whether a background scan is still running
+    pub fn is_searching(&self) -> bool {
+        self.pending.is_some()
     }
 }
 
-/// Event sender handle for worker threads
-/// Allows workers to send events back to the UI thread
-#[derive(Clone)]
-pub struct EventSender {
-    sender: Sender<AppEvent>,
+/// (ticks, positions, watchlist length) a day movers ranking was built from
+pub type MoversKey = (u64, u64, usize);
+
+/// Day change of a position or watchlist instrument, as ranked on the Overview
+#[derive(Debug, Clone)]
+pub struct DayMover {
+    pub instrument_token: u32,
+    pub tradingsymbol: String,
+    pub last_price: f64,
+    pub change_pct: f64,
+    pub in_positions: bool,
 }
 
-impl EventSender {
-    pub fn new(sender: Sender<AppEvent>) -> Self {
-        Self { sender }
+/// Positions of one exchange, as grouped in the positions table: rows of the positions
+/// snapshot they were built from
+pub type PositionGroup = (String, Vec<usize>);
+
+/// Counter bumped each time a shared collection is mutated, so caches and the repaint
+/// schedule can tell whether it changed since they last looked
+#[derive(Debug, Default)]
+pub struct Generation(AtomicU64);
+
+impl Generation {
+    pub fn get(&self) -> u64 {
+        self.0.load(AtomicOrdering::Acquire)
     }
 
-    pub fn send(&self, event: AppEvent) -> Result<(), crossbeam_channel::SendError<AppEvent>> {
-        self.sender.send(event)
+    pub fn bump(&self) {
+        self.0.fetch_add(1, AtomicOrdering::Release);
     }
 
-    pub fn send_notification(&self, level: LogLevel, message: String, module: Option<String>) {
-        let _ = self.send(AppEvent::Notification {
-            level,
-            message,
-            module,
-        });
+    /// Whether the collection changed after `seen` was read from get()
+    pub fn changed_since(&self, seen: u64) -> bool {
+        self.get() != seen
     }
+}
 
-    pub fn send_error(&self, error: String, module: Option<String>) {
-        let _ = self.send(AppEvent::Error { error, module });
+/// Every position in one Vec, sorted like the positions table, for the views that list
+/// positions to share instead of each copying the map every frame
+/// A positions refresh rebuilds it; a tick reprices its row in place
+#[derive(Debug, Default)]
+pub struct PositionsSnapshot {
+    positions: Arc<Vec<Position>>,
+    // Rows of each instrument in `positions`, one per product
+    rows: HashMap<u32, Vec<usize>>,
+    // Order `positions` is in, None when a rebuild or repricing disturbed it
+    sort: Option<SortState<PositionColumn>>,
+    // positions_gen the rows reflect
+    generation: Option<u64>,
+}
+
+impl PositionsSnapshot {
+    /// The rows as of the last sync
+    pub fn positions(&self) -> Arc<Vec<Position>> {
+        Arc::clone(&self.positions)
+    }
+
+    /// Whether the rows reflect the map at `generation`
+    fn is_current(&self, generation: &Generation) -> bool {
+        self.generation == Some(generation.get())
+    }
+
+    /// Apply a tick to the instrument's rows, as update_position_price does to the map, so
+    /// the rows stay current at `generation` without a rebuild
+    fn reprice(&mut self, instrument_token: u32, last_price: f64, generation: u64) {
+        self.generation = Some(generation);
+        if let Some(rows) = self.rows.get(&instrument_token) {
+            let positions = Arc::make_mut(&mut self.positions);
+            for &row in rows {
+                positions[row].update_last_price(last_price);
+            }
+            if self.sort.is_some_and(|sort| sort.column.follows_price()) {
+                self.sort = None;
+            }
+        }
+    }
+
+    /// Rebuild from `positions` if it changed in ways not patched in, and sort by `sort` if
+    /// out of order
+    fn sync(
+        &mut self,
+        positions: &DashMap<(u32, String), Position>,
+        generation: &Generation,
+        sort: SortState<PositionColumn>,
+    ) {
+        if !self.is_current(generation) {
+            self.positions = Arc::new(
+                positions
+                    .iter()
+                    .map(|entry| entry.value().clone())
+                    .collect(),
+            );
+            self.generation = Some(generation.get());
+            self.sort = None;
+        }
+        if self.sort != Some(sort) {
+            // Mostly in order already after repricing, which the stable sort is quick on
+            Arc::make_mut(&mut self.positions)
+                .sort_by(|a, b| sort.apply(sort.column.compare(a, b)));
+            self.rows.clear();
+            for (row, position) in self.positions.iter().enumerate() {
+                self.rows
+                    .entry(position.instrument_token)
+                    .or_default()
+                    .push(row);
+            }
+            self.sort = Some(sort);
+        }
+    }
+}
+
+/// Column identifiers of a customizable table
+pub trait TableColumn: Copy + PartialEq + 'static {
+    /// Every column, in default order
+    const ALL: &'static [Self];
+}
+
+/// Order and visibility of a table's columns, as arranged in its column menu
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnLayout<C> {
+    pub columns: Vec<(C, bool)>,
+}
+
+impl<C: TableColumn> Default for ColumnLayout<C> {
+    fn default() -> Self {
+        Self {
+            columns: C::ALL.iter().map(|column| (*column, true)).collect(),
+        }
+    }
+}
+
+impl<C: TableColumn> ColumnLayout<C> {
+    /// Shown columns, left to right
+    pub fn visible(&self) -> impl Iterator<Item = C> + '_ {
+        self.columns
+            .iter()
+            .filter(|(_, shown)| *shown)
+            .map(|(column, _)| *column)
+    }
+
+    /// Keep a saved layout usable across versions: drop duplicates, append new columns
+    pub fn reconcile(&mut self) {
+        let mut seen = Vec::new();
+        self.columns.retain(|(column, _)| {
+            let first = !seen.contains(column);
+            seen.push(*column);
+            first
+        });
+        for column in C::ALL {
+            if !seen.contains(column) {
+                self.columns.push((*column, true));
+            }
+        }
+    }
+
+    /// Swap a column with its neighbour; out-of-range moves are ignored
+    pub fn move_column(&mut self, index: usize, later: bool) {
+        let other = if later {
+            index + 1
+        } else {
+            index.wrapping_sub(1)
+        };
+        if index < self.columns.len() && other < self.columns.len() {
+            self.columns.swap(index, other);
+        }
+    }
+}
+
+/// Table sort selection: which column and which direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortState<C> {
+    pub column: C,
+    pub descending: bool,
+}
+
+impl<C: Copy + PartialEq> SortState<C> {
+    /// Clicking the active column flips direction; a new column starts ascending
+    pub fn toggle(&mut self, column: C) {
+        if self.column == column {
+            self.descending = !self.descending;
+        } else {
+            self.column = column;
+            self.descending = false;
+        }
+    }
+
+    /// Orient an ascending comparison according to the selected direction
+    pub fn apply(&self, ordering: Ordering) -> Ordering {
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// Sortable columns of the positions table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PositionColumn {
+    #[default]
+    Symbol,
+    Exchange,
+    Product,
+    Quantity,
+    AveragePrice,
+    LastPrice,
+    Pnl,
+    DayPnl,
+    ChangePct,
+    Value,
+    PortfolioPct,
+}
+
+impl TableColumn for PositionColumn {
+    const ALL: &'static [PositionColumn] = &[
+        PositionColumn::Symbol,
+        PositionColumn::Exchange,
+        PositionColumn::Product,
+        PositionColumn::Quantity,
+        PositionColumn::AveragePrice,
+        PositionColumn::LastPrice,
+        PositionColumn::Value,
+        PositionColumn::PortfolioPct,
+        PositionColumn::Pnl,
+        PositionColumn::DayPnl,
+        PositionColumn::ChangePct,
+    ];
+}
+
+impl PositionColumn {
+    /// Whether a new last price can move a position in this column's order
+    pub fn follows_price(self) -> bool {
+        matches!(
+            self,
+            PositionColumn::LastPrice
+                | PositionColumn::Pnl
+                | PositionColumn::DayPnl
+                | PositionColumn::ChangePct
+                | PositionColumn::Value
+                | PositionColumn::PortfolioPct
+        )
+    }
+
+    /// Ascending comparison, tie-broken by symbol and token so equal rows never swap
+    pub fn compare(self, a: &Position, b: &Position) -> Ordering {
+        let primary = match self {
+            PositionColumn::Symbol => Ordering::Equal,
+            PositionColumn::Exchange => a.exchange.cmp(&b.exchange),
+            PositionColumn::Product => a.product.cmp(&b.product),
+            PositionColumn::Quantity => a.quantity.cmp(&b.quantity),
+            PositionColumn::AveragePrice => a.average_price.total_cmp(&b.average_price),
+            PositionColumn::LastPrice => a.last_price.total_cmp(&b.last_price),
+            PositionColumn::Pnl => a.pnl.total_cmp(&b.pnl),
+            PositionColumn::DayPnl => a.unrealized_pnl.total_cmp(&b.unrealized_pnl),
+            PositionColumn::ChangePct => a.change_pct().total_cmp(&b.change_pct()),
+            PositionColumn::Value => a.value().total_cmp(&b.value()),
+            // Share of gross exposure, so shorts rank by size too
+            PositionColumn::PortfolioPct => a.value().abs().total_cmp(&b.value().abs()),
+        };
+
+        primary
+            .then_with(|| a.tradingsymbol.cmp(&b.tradingsymbol))
+            .then_with(|| a.instrument_token.cmp(&b.instrument_token))
+    }
+}
+
+impl Default for SortState<PositionColumn> {
+    fn default() -> Self {
+        Self {
+            column: PositionColumn::Symbol,
+            descending: false,
+        }
+    }
+}
+
+/// Exchange, product and flat-position narrowing of the positions table, on top of its text filter
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PositionFilters {
+    pub exchange: Option<String>,
+    pub product: Option<ProductType>,
+    // Closed intraday positions stay in the book with quantity 0 until the day ends
+    pub hide_flat: bool,
+}
+
+impl PositionFilters {
+    pub fn matches(&self, position: &Position) -> bool {
+        self.exchange
+            .as_ref()
+            .is_none_or(|exchange| *exchange == position.exchange)
+            && self
+                .product
+                .is_none_or(|product| product.as_api_str() == position.product)
+            && !(self.hide_flat && position.quantity == 0)
+    }
+
+    pub fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// Active filters as text, e.g. `exchange:NFO product:MIS non-zero`
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(exchange) = &self.exchange {
+            parts.push(format!("exchange:{}", exchange));
+        }
+        if let Some(product) = self.product {
+            parts.push(format!("product:{}", product.as_api_str()));
+        }
+        if self.hide_flat {
+            parts.push("non-zero".to_string());
+        }
+        parts.join(" ")
+    }
+}
+
+/// Status tabs above the orders table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum OrderTab {
+    #[default]
+    All,
+    Open,
+    Executed,
+    Cancelled,
+    Rejected,
+}
+
+impl OrderTab {
+    pub const ALL: [OrderTab; 5] = [
+        OrderTab::All,
+        OrderTab::Open,
+        OrderTab::Executed,
+        OrderTab::Cancelled,
+        OrderTab::Rejected,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OrderTab::All => "All",
+            OrderTab::Open => "Open",
+            OrderTab::Executed => "Executed",
+            OrderTab::Cancelled => "Cancelled",
+            OrderTab::Rejected => "Rejected",
+        }
+    }
+
+    /// Open takes every order still working, including those the OMS is processing
+    pub fn matches(self, status: &OrderStatus) -> bool {
+        match self {
+            OrderTab::All => true,
+            OrderTab::Open => !matches!(
+                status,
+                OrderStatus::Complete
+                    | OrderStatus::Cancelled
+                    | OrderStatus::CancelledAmo
+                    | OrderStatus::Rejected
+                    | OrderStatus::Unknown(_)
+            ),
+            OrderTab::Executed => *status == OrderStatus::Complete,
+            OrderTab::Cancelled => {
+                matches!(status, OrderStatus::Cancelled | OrderStatus::CancelledAmo)
+            }
+            OrderTab::Rejected => *status == OrderStatus::Rejected,
+        }
+    }
+}
+
+/// Sortable columns of the orders table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OrderColumn {
+    Symbol,
+    Type,
+    Quantity,
+    Status,
+    Price,
+    #[default]
+    Time,
+    Filled,
+}
+
+impl TableColumn for OrderColumn {
+    const ALL: &'static [OrderColumn] = &[
+        OrderColumn::Symbol,
+        OrderColumn::Type,
+        OrderColumn::Quantity,
+        OrderColumn::Price,
+        OrderColumn::Status,
+        OrderColumn::Filled,
+        OrderColumn::Time,
+    ];
+}
+
+impl OrderColumn {
+    /// Ascending comparison, tie-broken by timestamp and order id so rows never swap
+    pub fn compare(self, a: &Order, b: &Order) -> Ordering {
+        let primary = match self {
+            OrderColumn::Symbol => a.tradingsymbol.cmp(&b.tradingsymbol),
+            OrderColumn::Type => a.transaction_type.cmp(&b.transaction_type),
+            OrderColumn::Quantity => a.quantity.cmp(&b.quantity),
+            OrderColumn::Status => a.status.to_string().cmp(&b.status.to_string()),
+            OrderColumn::Price => a.price.total_cmp(&b.price),
+            OrderColumn::Time => Ordering::Equal,
+            OrderColumn::Filled => a.filled_quantity.cmp(&b.filled_quantity),
+        };
+
+        primary
+            .then_with(|| a.order_timestamp.cmp(&b.order_timestamp))
+            .then_with(|| a.order_id.cmp(&b.order_id))
+    }
+}
+
+impl Default for SortState<OrderColumn> {
+    fn default() -> Self {
+        Self {
+            column: OrderColumn::Time,
+            descending: true,
+        }
+    }
+}
+
+/// Margin and charges preview for the order form, re-requested once its inputs settle
+#[derive(Debug, Clone, Default)]
+pub struct MarginPreview {
+    // Serialized request the form last described, and when that changed
+    pub inputs: String,
+    pub changed_at: Option<Instant>,
+    // Id of the latest request; answers to older ones are dropped
+    pub requested_id: u64,
+    pub result: Option<Result<OrderMargin, String>>,
+}
+
+/// Tag on orders placed from the order dialog
+pub const MANUAL_ORDER_TAG: &str = "manual_order";
+
+/// How long an unanswered order keeps its button busy, in case no event comes back
+const ORDER_IN_FLIGHT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Orders sent but not yet answered, keyed by the request id they carry to the worker
+#[derive(Debug, Default)]
+pub struct OrdersInFlight {
+    next_id: u64,
+    requests: HashMap<u64, (OrderRequest, Instant)>,
+}
+
+impl OrdersInFlight {
+    /// Give the order a request id and remember it until its answer arrives
+    pub fn start(&mut self, details: &mut OrderRequest) {
+        self.requests
+            .retain(|_, (_, sent)| sent.elapsed() < ORDER_IN_FLIGHT_TIMEOUT);
+        self.next_id += 1;
+        details.request_id = Some(self.next_id);
+        self.requests
+            .insert(self.next_id, (details.clone(), Instant::now()));
+    }
+
+    pub fn finish(&mut self, request_id: u64) -> Option<OrderRequest> {
+        self.requests.remove(&request_id).map(|(order, _)| order)
+    }
+
+    /// Whether an order matching `filter` is still waiting on the broker
+    pub fn any(&self, filter: impl Fn(&OrderRequest) -> bool) -> bool {
+        self.requests
+            .values()
+            .any(|(order, sent)| sent.elapsed() < ORDER_IN_FLIGHT_TIMEOUT && filter(order))
+    }
+}
+
+/// Market orders held back until the user confirms them
+#[derive(Debug, Clone)]
+pub struct PendingMarketOrders {
+    pub message: String,
+    pub orders: Vec<OrderRequest>,
+}
+
+/// Partial exit being set up from a position row, defaulting to the full quantity
+#[derive(Debug, Clone)]
+pub struct SquareOffDraft {
+    pub instrument_token: u32,
+    pub product: String,
+    pub quantity: String,
+    // Limit at the last traded price instead of a market order
+    pub limit_at_ltp: bool,
+}
+
+/// Edit form for an open order, pre-filled from its current values
+#[derive(Debug, Clone)]
+pub struct OrderEditForm {
+    pub order_id: String,
+    pub order_type: OrderType,
+    pub filled_quantity: i32,
+    pub quantity_input: ValidatedField<i32>,
+    pub price_input: ValidatedField<f64>,
+    pub trigger_price_input: ValidatedField<f64>,
+}
+
+impl OrderEditForm {
+    pub fn from_order(order: &Order) -> Self {
+        let order_type = OrderType::from_str(&order.order_type).unwrap_or(OrderType::Limit);
+
+        let mut form = Self {
+            order_id: order.order_id.clone(),
+            order_type,
+            filled_quantity: order.filled_quantity,
+            quantity_input: ValidatedField::default(),
+            price_input: ValidatedField::default(),
+            trigger_price_input: ValidatedField::default(),
+        };
+
+        form.quantity_input.set(order.quantity);
+        if order.price > 0.0 {
+            form.price_input.set(order.price);
+        }
+        if order.trigger_price > 0.0 {
+            form.trigger_price_input.set(order.trigger_price);
+        }
+        form.validate();
+        form
+    }
+
+    /// Re-run validators; quantity may not drop below what has already filled
+    pub fn validate(&mut self) {
+        let filled = self.filled_quantity;
+
+        self.quantity_input.validate(true, |quantity| {
+            positive(quantity, "Quantity")?;
+            if quantity < filled {
+                Err(format!(
+                    "Quantity cannot be below the {} already filled",
+                    filled
+                ))
+            } else {
+                Ok(())
+            }
+        });
+        self.price_input.validate(
+            matches!(self.order_type, OrderType::Limit | OrderType::StopLoss),
+            |price| positive(price, "Price"),
+        );
+        self.trigger_price_input
+            .validate(self.order_type.requires_trigger(), |trigger| {
+                positive(trigger, "Trigger price")
+            });
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.quantity_input.value().is_some()
+            && self.quantity_input.is_valid()
+            && self.price_input.is_valid()
+            && self.trigger_price_input.is_valid()
+    }
+
+    /// Build the modification request, carrying over the order's fixed attributes
+    pub fn to_request(&self, order: &Order) -> Option<OrderRequest> {
+        let quantity = self.quantity_input.value()?;
+
+        Some(OrderRequest {
+            tradingsymbol: order.tradingsymbol.clone(),
+            exchange: order.exchange.clone(),
+            transaction_type: order.transaction_type.clone(),
+            order_type: self.order_type.as_api_str().to_string(),
+            quantity,
+            price: match self.order_type {
+                OrderType::Limit | OrderType::StopLoss => self.price_input.value(),
+                _ => None,
+            },
+            product: order.product.clone(),
+            validity: order.validity.clone(),
+            validity_ttl: None,
+            disclosed_quantity: (order.disclosed_quantity > 0).then_some(order.disclosed_quantity),
+            trigger_price: if self.order_type.requires_trigger() {
+                self.trigger_price_input.value()
+            } else {
+                None
+            },
+            squareoff: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: order.tag.clone(),
+            variety: order.variety.clone(),
+            bypass_margin_check: true,
+            request_id: None,
+        })
+    }
+}
+
+/// In-app Kite login: the user signs in through the login URL and pastes back the request_token
+#[derive(Debug, Clone, Default)]
+pub struct ReauthForm {
+    pub login_url: String,
+    // The request_token, or the whole redirect URL it arrived on
+    pub request_token: String,
+    // Port the login redirect is awaited on, while the listener runs
+    pub listening: Option<u16>,
+    pub exchanging: bool,
+    pub error: Option<String>,
+}
+
+impl ReauthForm {
+    /// request_token from the pasted text, which may be the full redirect URL
+    pub fn request_token(&self) -> Option<String> {
+        let input = self.request_token.trim();
+        let token = match url::Url::parse(input) {
+            Ok(url) => url
+                .query_pairs()
+                .find(|(key, _)| key == "request_token")
+                .map(|(_, value)| value.into_owned())?,
+            Err(_) => input.to_string(),
+        };
+        Some(token).filter(|token| !token.is_empty())
+    }
+}
+
+/// Outcome of a test run from the Settings view
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ConnectionTest {
+    #[default]
+    Untested,
+    Testing,
+    Passed,
+    Failed(String),
+}
+
+/// Editable copy of the config, written to config.toml only when saved
+#[derive(Debug, Clone)]
+pub struct SettingsForm {
+    pub draft: Config,
+    pub reveal_secrets: bool,
+    pub connection_test: ConnectionTest,
+    pub notification_test: ConnectionTest,
+    // Validation problems from the last save attempt
+    pub problems: Vec<String>,
+}
+
+impl SettingsForm {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            draft: config.clone(),
+            reveal_secrets: false,
+            connection_test: ConnectionTest::Untested,
+            notification_test: ConnectionTest::Untested,
+            problems: Vec::new(),
+        }
+    }
+
+    /// Unsaved edits, against the settings currently in effect
+    pub fn is_dirty(&self, config: &Config) -> bool {
+        self.draft != *config
+    }
+}
+
+/// Protective SL-M and target LIMIT orders being set up for an open position
+#[derive(Debug, Clone)]
+pub struct ProtectionForm {
+    pub position: Position,
+    // Tick size source for snapping; positions may load before the instrument master
+    pub instrument: Option<Instrument>,
+    pub quantity_input: ValidatedField<i32>,
+    pub place_stop: bool,
+    pub stop_input: ValidatedField<f64>,
+    pub place_target: bool,
+    pub target_input: ValidatedField<f64>,
+}
+
+impl ProtectionForm {
+    /// Stop 1% against and target 2% in favour of the average price
+    pub fn from_position(position: &Position, instrument: Option<Instrument>) -> Self {
+        let direction = if position.quantity > 0 { 1.0 } else { -1.0 };
+
+        let mut form = Self {
+            position: position.clone(),
+            instrument,
+            quantity_input: ValidatedField::default(),
+            place_stop: true,
+            stop_input: ValidatedField::default(),
+            place_target: true,
+            target_input: ValidatedField::default(),
+        };
+
+        form.quantity_input.set(position.quantity.abs());
+        form.stop_input
+            .set(form.snap(position.average_price * (1.0 - 0.01 * direction)));
+        form.target_input
+            .set(form.snap(position.average_price * (1.0 + 0.02 * direction)));
+        form.validate();
+        form
+    }
+
+    pub fn snap(&self, price: f64) -> f64 {
+        snap_price(self.instrument.as_ref(), price)
+    }
+
+    /// Re-run validators; the stop must sit on the losing side of the target
+    pub fn validate(&mut self) {
+        let held = self.position.quantity.abs();
+        let long = self.position.quantity > 0;
+
+        self.quantity_input.validate(true, |quantity| {
+            positive(quantity, "Quantity")?;
+            if quantity > held {
+                Err(format!("Only {} held", held))
+            } else {
+                Ok(())
+            }
+        });
+        self.stop_input
+            .validate(self.place_stop, |stop| positive(stop, "Stop-loss"));
+        self.target_input
+            .validate(self.place_target, |target| positive(target, "Target"));
+
+        if let (true, true, Some(stop), Some(target)) = (
+            self.place_stop,
+            self.place_target,
+            self.stop_input.value(),
+            self.target_input.value(),
+        ) {
+            if (long && stop >= target) || (!long && stop <= target) {
+                let side = if long { "below" } else { "above" };
+                self.stop_input.validate(true, |_| {
+                    Err(format!("Stop-loss must be {} the target", side))
+                });
+            }
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        (self.place_stop || self.place_target)
+            && self.quantity_input.value().is_some()
+            && self.stop_input.is_valid()
+            && self.target_input.is_valid()
+    }
+}
+
+/// Price alert being created or edited from the watchlist
+#[derive(Debug, Clone)]
+pub struct AlertForm {
+    // Alert being edited; None creates a new one
+    pub existing: Option<PriceAlert>,
+    pub instrument_token: u32,
+    pub condition: AlertCondition,
+    pub threshold_input: ValidatedField<f64>,
+    pub repeating: bool,
+    pub note: String,
+}
+
+impl AlertForm {
+    /// New alert starting at the last traded price, if there is one
+    pub fn new(instrument_token: u32, last_price: Option<f64>) -> Self {
+        let mut form = Self {
+            existing: None,
+            instrument_token,
+            condition: AlertCondition::Above,
+            threshold_input: ValidatedField::default(),
+            repeating: false,
+            note: String::new(),
+        };
+        if let Some(price) = last_price {
+            form.threshold_input.set(price);
+        }
+        form.validate();
+        form
+    }
+
+    pub fn edit(alert: &PriceAlert) -> Self {
+        let mut form = Self {
+            existing: Some(alert.clone()),
+            instrument_token: alert.instrument_token,
+            condition: alert.condition,
+            threshold_input: ValidatedField::default(),
+            repeating: alert.repeating,
+            note: alert.note.clone().unwrap_or_default(),
+        };
+        form.threshold_input.set(alert.threshold);
+        form.validate();
+        form
+    }
+
+    pub fn validate(&mut self) {
+        self.threshold_input
+            .validate(true, |threshold| positive(threshold, "Price"));
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.threshold_input.value().is_some()
+    }
+
+    /// CreateAlert for a new alert, UpdateAlert for an edited one
+    pub fn to_command(&self) -> Option<Command> {
+        let threshold = self.threshold_input.value()?;
+        let note = Some(self.note.trim().to_string()).filter(|note| !note.is_empty());
+
+        Some(match &self.existing {
+            Some(existing) => {
+                let mut alert = existing.clone();
+                alert.condition = self.condition;
+                alert.threshold = threshold;
+                alert.repeating = self.repeating;
+                alert.note = note;
+                alert.enabled = true;
+                Command::UpdateAlert { alert }
+            }
+            None => Command::CreateAlert {
+                alert: PriceAlert::new(
+                    self.instrument_token,
+                    self.condition,
+                    threshold,
+                    self.repeating,
+                    note,
+                ),
+            },
+        })
+    }
+}
+
+/// Tokens named in full in a message about several instruments; the rest are counted
+const LISTED_INSTRUMENTS: usize = 5;
+
+/// "NSE:RELIANCE" for a token in log and event messages, or the bare number while the
+/// instrument master hasn't loaded it
+pub fn instrument_label(instruments: &DashMap<u32, Instrument>, instrument_token: u32) -> String {
+    instruments
+        .get(&instrument_token)
+        .map(|instrument| format!("{}:{}", instrument.exchange, instrument.tradingsymbol))
+        .unwrap_or_else(|| instrument_token.to_string())
+}
+
+/// Labels for a batch of tokens, e.g. "NSE:INFY, NSE:TCS and 3 more"
+pub fn instrument_list_label(instruments: &DashMap<u32, Instrument>, tokens: &[u32]) -> String {
+    let mut label = tokens
+        .iter()
+        .take(LISTED_INSTRUMENTS)
+        .map(|token| instrument_label(instruments, *token))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if tokens.len() > LISTED_INSTRUMENTS {
+        label.push_str(&format!(" and {} more", tokens.len() - LISTED_INSTRUMENTS));
+    }
+    label
+}
+
+/// Round a price to the instrument's tick, or to paise when the instrument is unknown
+pub fn snap_price(instrument: Option<&Instrument>, price: f64) -> f64 {
+    match instrument {
+        Some(instrument) => instrument.round_to_tick(price),
+        None => (price * 100.0).round() / 100.0,
+    }
+}
+
+fn positive<T: PartialOrd + Default>(value: T, name: &str) -> Result<(), String> {
+    if value > T::default() {
+        Ok(())
+    } else {
+        Err(format!("{} must be greater than zero", name))
+    }
+}
+
+/// Why text typed into a numeric field can't be used
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum InputError {
+    #[error("Required")]
+    Empty,
+    #[error("'{0}' is not a valid number")]
+    NotANumber(String),
+}
+
+/// Numbers typed into forms; floats must also be finite, so "inf" and "1e999" are typos
+pub trait NumericInput: FromStr + Copy {
+    fn is_finite(self) -> bool {
+        true
+    }
+}
+
+impl NumericInput for i32 {}
+impl NumericInput for u32 {}
+
+impl NumericInput for f64 {
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+}
+
+/// Parse a numeric input, saying why it can't be used rather than defaulting to zero
+pub fn parse_with_feedback<T: NumericInput>(text: &str) -> Result<T, InputError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(InputError::Empty);
+    }
+    text.parse::<T>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| InputError::NotANumber(text.to_string()))
+}
+
+/// Text input that keeps its parsed value and last validation error side by side
+#[derive(Debug, Clone, Default)]
+pub struct ValidatedField<T> {
+    pub text: String,
+    value: Option<T>,
+    error: Option<String>,
+}
+
+impl<T: NumericInput> ValidatedField<T> {
+    /// Parse the current text; empty input is an error only when the field is required
+    pub fn validate(&mut self, required: bool, check: impl Fn(T) -> Result<(), String>) {
+        let result = match parse_with_feedback(&self.text) {
+            Ok(value) => check(value).map(|_| Some(value)),
+            Err(InputError::Empty) if !required => Ok(None),
+            Err(e) => Err(e.to_string()),
+        };
+
+        match result {
+            Ok(value) => {
+                self.value = value;
+                self.error = None;
+            }
+            Err(error) => {
+                self.value = None;
+                self.error = Some(error);
+            }
+        }
+    }
+
+    /// Replace the text, e.g. from a one-click fix, and keep the parsed value in sync
+    pub fn set(&mut self, value: T)
+    where
+        T: std::fmt::Display,
+    {
+        self.text = value.to_string();
+        self.value = Some(value);
+        self.error = None;
+    }
+
+    pub fn value(&self) -> Option<T> {
+        self.value
+    }
+}
+
+impl<T> ValidatedField<T> {
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.value = None;
+        self.error = None;
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderType {
+    #[default]
+    #[serde(rename = "MARKET")]
+    Market,
+    #[serde(rename = "LIMIT")]
+    Limit,
+    #[serde(rename = "SL")]
+    StopLoss,
+    #[serde(rename = "SL-M")]
+    StopLossMarket,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionType {
+    #[default]
+    #[serde(rename = "BUY")]
+    Buy,
+    #[serde(rename = "SELL")]
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProductType {
+    #[default]
+    CNC, // Cash and Carry
+    MIS,  // Margin Intraday Squareoff
+    NRML, // Normal
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Validity {
+    #[default]
+    Day,
+    Ioc,
+    // Good for a number of minutes
+    Ttl,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Exchange {
+    #[default]
+    Nse,
+    Bse,
+    Nfo,
+    Bfo,
+    Mcx,
+    Cds,
+}
+
+/// Error returned when an API string does not name a known enum variant
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("unknown {kind}: {value}")]
+pub struct ParseEnumError {
+    kind: &'static str,
+    value: String,
+}
+
+impl OrderType {
+    pub const ALL: [OrderType; 4] = [
+        OrderType::Market,
+        OrderType::Limit,
+        OrderType::StopLoss,
+        OrderType::StopLossMarket,
+    ];
+
+    /// String expected by the Kite order APIs
+    pub fn as_api_str(&self) -> &'static str {
+        match self {
+            OrderType::Market => "MARKET",
+            OrderType::Limit => "LIMIT",
+            OrderType::StopLoss => "SL",
+            OrderType::StopLossMarket => "SL-M",
+        }
+    }
+
+    /// Whether the order needs a limit price
+    pub fn requires_price(&self) -> bool {
+        matches!(self, OrderType::Limit | OrderType::StopLoss)
+    }
+
+    /// Whether the order needs a trigger price
+    pub fn requires_trigger(&self) -> bool {
+        matches!(self, OrderType::StopLoss | OrderType::StopLossMarket)
+    }
+}
+
+impl TransactionType {
+    pub const ALL: [TransactionType; 2] = [TransactionType::Buy, TransactionType::Sell];
+
+    /// String expected by the Kite order APIs
+    pub fn as_api_str(&self) -> &'static str {
+        match self {
+            TransactionType::Buy => "BUY",
+            TransactionType::Sell => "SELL",
+        }
+    }
+}
+
+impl Exchange {
+    pub const ALL: [Exchange; 6] = [
+        Exchange::Nse,
+        Exchange::Bse,
+        Exchange::Nfo,
+        Exchange::Bfo,
+        Exchange::Mcx,
+        Exchange::Cds,
+    ];
+
+    /// String expected by the Kite order APIs
+    pub fn as_api_str(&self) -> &'static str {
+        match self {
+            Exchange::Nse => "NSE",
+            Exchange::Bse => "BSE",
+            Exchange::Nfo => "NFO",
+            Exchange::Bfo => "BFO",
+            Exchange::Mcx => "MCX",
+            Exchange::Cds => "CDS",
+        }
+    }
+}
+
+impl ProductType {
+    pub const ALL: [ProductType; 3] = [ProductType::CNC, ProductType::MIS, ProductType::NRML];
+
+    /// String expected by the Kite order APIs
+    pub fn as_api_str(&self) -> &'static str {
+        match self {
+            ProductType::CNC => "CNC",
+            ProductType::MIS => "MIS",
+            ProductType::NRML => "NRML",
+        }
+    }
+}
+
+impl Validity {
+    pub const ALL: [Validity; 3] = [Validity::Day, Validity::Ioc, Validity::Ttl];
+
+    /// String expected by the Kite order APIs
+    pub fn as_api_str(&self) -> &'static str {
+        match self {
+            Validity::Day => "DAY",
+            Validity::Ioc => "IOC",
+            Validity::Ttl => "TTL",
+        }
+    }
+}
+
+/// Implement Display and FromStr in terms of the API string table
+macro_rules! impl_api_str_conversions {
+    ($ty:ty, $kind:literal) => {
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_api_str())
+            }
+        }
+
+        impl std::str::FromStr for $ty {
+            type Err = ParseEnumError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::ALL
+                    .into_iter()
+                    .find(|variant| variant.as_api_str().eq_ignore_ascii_case(s.trim()))
+                    .ok_or_else(|| ParseEnumError {
+                        kind: $kind,
+                        value: s.to_string(),
+                    })
+            }
+        }
+    };
+}
+
+impl_api_str_conversions!(OrderType, "order type");
+impl_api_str_conversions!(TransactionType, "transaction type");
+impl_api_str_conversions!(ProductType, "product type");
+impl_api_str_conversions!(Validity, "validity");
+impl_api_str_conversions!(Exchange, "exchange");
+
+/// One tick kept in an instrument's history
+#[derive(Debug, Clone, Copy)]
+pub struct TickSample {
+    pub timestamp: DateTime<Utc>,
+    pub price: f64,
+    // Last traded quantity; 0 for LTP-mode ticks, which don't carry it
+    pub quantity: u32,
+}
+
+/// Recent tick samples for one instrument, oldest first
+pub type TickHistory = VecDeque<TickSample>;
+
+/// Latest funds response and when it arrived
+pub type MarginsSnapshot = (DateTime<Utc>, Margins);
+
+/// Main application state using high-performance concurrent data structures
+/// All collections use lock-free designs for ultra-low latency access
+pub struct AppState {
+    // Configuration; credentials stay as they were at startup
+    pub config: Config,
+    // config.toml as last read or saved from Settings
+    pub loaded_config: Config,
+
+    // Authentication state
+    pub auth_state: Arc<RwLock<AuthState>>,
+    pub connection_state: Arc<RwLock<ConnectionState>>,
+
+    // Trading data - using DashMap for lock-free concurrent access
+    pub positions: Arc<DashMap<(u32, String), Position>>, // keyed by Position::key
+    pub orders: Arc<DashMap<String, Order>>,              // keyed by order_id
+    pub trades: Arc<RwLock<Vec<Trade>>>,                  // today's tradebook, oldest first
+    pub order_history: Arc<DashMap<String, OrderHistory>>, // keyed by order_id, fetched on demand
+    pub instruments: Arc<DashMap<u32, Instrument>>,       // keyed by instrument_token
+    pub instrument_index: Arc<RwLock<InstrumentIndex>>,   // rebuilt on InstrumentsUpdated
+
+    // Net filled quantities per strategy tag, rebuilt from orders on every update
+    pub tag_positions: Arc<RwLock<HashMap<String, Vec<TagPosition>>>>,
+    // Configured strategies, shared with the strategy runner, which reads `enabled`
+    pub strategies: Arc<RwLock<Vec<StrategyStatus>>>,
+    // Fans events out to external tools when server.broadcast_port is set
+    pub broadcaster: Option<Broadcaster>,
+    // Telegram and webhook messages about fills and trouble, started with the workers
+    pub notifier: Option<Notifier>,
+
+    // User-curated watchlist, persisted to watchlist.json
+    pub watchlist: Arc<RwLock<Watchlist>>,
+
+    // Price alerts evaluated on every tick, persisted to alerts.json
+    pub alerts: Arc<RwLock<AlertBook>>,
+
+    // Latest funds snapshot and when it arrived
+    pub margins: Arc<RwLock<Option<MarginsSnapshot>>>,
+
+    // User profile
+    pub user_profile: Arc<RwLock<Option<UserProfile>>>,
+
+    // Real-time data
+    pub tick_data: Arc<DashMap<u32, TickData>>, // keyed by instrument_token
+
+    // Session one-minute bars per instrument_token, extended live by ticks
+    pub candles: Arc<DashMap<u32, CandleSeries>>,
+    // Tokens streamed because they back a position, and tokens subscribed explicitly
+    // Flat-position cleanup only drops the former
+    pub auto_subscribed_tokens: Arc<RwLock<HashSet<u32>>>,
+    pub explicit_subscriptions: Arc<RwLock<HashSet<u32>>>,
+    // Tokens whose session history has been requested, so charts ask only once
+    pub candle_history_requested: Arc<RwLock<HashSet<u32>>>,
+
+    // Orders sent to the API worker that have not yet been placed or rejected
+    pub orders_in_flight: Arc<RwLock<OrdersInFlight>>,
+
+    // Sampled session P&L for the equity curve
+    pub pnl_history: Arc<RwLock<PnlHistory>>,
+
+    // Order book from full-mode ticks, keyed by instrument_token
+    pub market_depth: Arc<DashMap<u32, MarketDepth>>,
+
+    // Bounded (timestamp, last_price) history per instrument_token
+    pub tick_history: Arc<DashMap<u32, TickHistory>>,
+
+    // Application logs with reader-writer lock for batch operations
+    pub logs: Arc<RwLock<Vec<LogEntry>>>,
+
+    // UI state
+    pub ui_input: UiInputState,
+    pub toasts: Vec<Toast>,
+    // Blocks every order action until turned off again
+    pub kill_switch: bool,
+    // Set once the app starts shutting down; later commands are dropped
+    pub shutting_down: bool,
+    pub notifications: VecDeque<Notification>,
+    // Shown one at a time, oldest first
+    pub critical_errors: VecDeque<CriticalError>,
+
+    // Communication channels
+    pub command_sender: mpsc::UnboundedSender<Command>,
+    pub priority_command_sender: mpsc::UnboundedSender<Command>,
+    pub websocket_command_sender: Sender<Command>,
+    pub event_receiver: Receiver<AppEvent>,
+    // Ticks and depth updates, bounded by performance.tick_buffer_size
+    pub tick_receiver: Receiver<AppEvent>,
+    // Market data events discarded under drop_policy this session
+    pub dropped_ticks: Arc<AtomicU64>,
+
+    // Performance metrics
+    pub metrics: Arc<RwLock<PerformanceMetrics>>,
+
+    // SQLite audit trail of order activity, None when journaling is disabled
+    pub journal: Option<Journal>,
+    // Candles, daily P&L and trades kept across sessions, None when history_path is unset
+    pub history: Option<HistoryStore>,
+    // Earlier days of the history store, for the P&L view
+    pub daily_history: Vec<DaySummary>,
+
+    // Event sounds, played off the UI thread
+    pub sounds: SoundPlayer,
+
+    // When positions and orders last arrived, for the background refresh countdowns
+    pub positions_refreshed_at: Option<Instant>,
+    pub orders_refreshed_at: Option<Instant>,
+
+    // Active visuals and P&L colors; the app re-applies it to egui when it changes
+    pub theme: Theme,
+
+    // Bumped by handle_event whenever positions, orders or tick_data change, so cached
+    // rows and totals know to rebuild
+    pub positions_gen: Generation,
+    pub orders_gen: Generation,
+    pub ticks_gen: Generation,
+    pub positions_snapshot: PositionsSnapshot,
+    // Total P&L and the positions_gen it was summed at
+    pnl_cache: parking_lot::Mutex<Option<(u64, PnlData)>>,
+    // Events raised on the UI thread itself, such as orders rejected before sending,
+    // applied with the next batch of worker events
+    local_events: parking_lot::Mutex<VecDeque<AppEvent>>,
+
+    // IST day the stale-token reminder last fired, and whether a token past its expiry
+    // has already been health-checked
+    pub token_reminded_on: Option<NaiveDate>,
+    pub token_expiry_checked: bool,
+    // When the configured exchanges' instruments were last requested
+    pub instruments_requested_at: Option<DateTime<Utc>>,
+}
+
+/// Performance metrics for monitoring system health
+#[derive(Debug, Default)]
+pub struct PerformanceMetrics {
+    pub ticks_processed: u64,
+    pub orders_processed: u64,
+    pub websocket_reconnections: u32,
+    // Worker restarts after panics this session, and the latest panic
+    pub worker_restarts: u32,
+    pub last_worker_panic: Option<String>,
+    // Circuit breakers of API request categories that are not closed
+    pub api_circuits: BTreeMap<ApiCategory, CircuitState>,
+    // Latest NTP check of the local clock, applied to tick latency
+    pub clock_offset: Option<ClockOffset>,
+    pub last_tick_timestamp: Option<DateTime<Utc>>,
+    pub tick_rate: TickRateWindow,
+    pub api_latency: ApiLatency,
+
+    // Event channel diagnostics, sampled once per frame
+    pub event_channel_depth: usize,
+    pub max_event_channel_depth_last_minute: usize,
+    pub process_events_time_us: u64,
+    // Frames drawn during the last full second, to check the UI idles when nothing changes
+    pub frames_per_second: u32,
+    frame_count: Option<(Instant, u32)>,
+    depth_window: VecDeque<(Instant, usize)>, // per-second maxima
+    backlog_since: Option<Instant>,
+    backlog_warned: bool,
+}
+
+impl PerformanceMetrics {
+    /// Record one frame's channel depth and event processing time
+    /// Returns how long the backlog has exceeded `threshold` the first time it passes BACKLOG_GRACE
+    pub fn record_frame(
+        &mut self,
+        depth: usize,
+        process_time: Duration,
+        threshold: usize,
+    ) -> Option<Duration> {
+        let now = Instant::now();
+
+        self.event_channel_depth = depth;
+        self.process_events_time_us = process_time.as_micros() as u64;
+
+        let (second_started, frames) = self.frame_count.get_or_insert((now, 0));
+        *frames += 1;
+        if now.duration_since(*second_started) >= Duration::from_secs(1) {
+            self.frames_per_second = *frames;
+            self.frame_count = Some((now, 0));
+        }
+
+        // Bucket per second so the one-minute window stays at ~60 entries
+        match self.depth_window.back_mut() {
+            Some((started, max)) if now.duration_since(*started) < Duration::from_secs(1) => {
+                *max = (*max).max(depth);
+            }
+            _ => self.depth_window.push_back((now, depth)),
+        }
+        while let Some((started, _)) = self.depth_window.front() {
+            if now.duration_since(*started) > Duration::from_secs(60) {
+                self.depth_window.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.max_event_channel_depth_last_minute = self
+            .depth_window
+            .iter()
+            .map(|(_, max)| *max)
+            .max()
+            .unwrap_or(0);
+
+        if depth <= threshold {
+            self.backlog_since = None;
+            self.backlog_warned = false;
+            return None;
+        }
+
+        let since = *self.backlog_since.get_or_insert(now);
+        let sustained = now.duration_since(since);
+        if sustained >= BACKLOG_GRACE && !self.backlog_warned {
+            self.backlog_warned = true;
+            return Some(sustained);
+        }
+
+        None
+    }
+}
+
+/// Seconds of tick throughput kept for the status bar
+pub const TICK_RATE_WINDOW_SECS: usize = 60;
+
+/// Ticks received during one wall-clock second and their summed latency
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TickSecond {
+    pub second: i64,
+    pub ticks: u32,
+    latency_ms_sum: f64,
+}
+
+impl TickSecond {
+    pub fn average_latency_ms(&self) -> f64 {
+        if self.ticks == 0 {
+            0.0
+        } else {
+            self.latency_ms_sum / self.ticks as f64
+        }
+    }
+}
+
+/// Per-second tick counts and latencies for the last minute, in a fixed ring so
+/// recording a tick never allocates
+#[derive(Debug, Clone)]
+pub struct TickRateWindow {
+    seconds: [TickSecond; TICK_RATE_WINDOW_SECS],
+}
+
+impl Default for TickRateWindow {
+    fn default() -> Self {
+        Self {
+            seconds: [TickSecond::default(); TICK_RATE_WINDOW_SECS],
+        }
+    }
+}
+
+impl TickRateWindow {
+    /// Count a tick received at `now`, `latency_ms` after its exchange timestamp
+    pub fn record(&mut self, now: DateTime<Utc>, latency_ms: f64) {
+        let second = now.timestamp();
+        let slot = &mut self.seconds[second.rem_euclid(TICK_RATE_WINDOW_SECS as i64) as usize];
+        if slot.second != second {
+            *slot = TickSecond {
+                second,
+                ..Default::default()
+            };
+        }
+        slot.ticks += 1;
+        slot.latency_ms_sum += latency_ms.max(0.0);
+    }
+
+    /// The last full minute before `now`, oldest first, with empty seconds zeroed
+    pub fn last_minute(&self, now: DateTime<Utc>) -> [TickSecond; TICK_RATE_WINDOW_SECS] {
+        let end = now.timestamp();
+        std::array::from_fn(|index| {
+            let second = end - (TICK_RATE_WINDOW_SECS - index) as i64;
+            let slot = self.seconds[second.rem_euclid(TICK_RATE_WINDOW_SECS as i64) as usize];
+            if slot.second == second {
+                slot
+            } else {
+                TickSecond {
+                    second,
+                    ..Default::default()
+                }
+            }
+        })
+    }
+
+    /// 95th percentile of the per-second average latencies over seconds that had ticks
+    pub fn p95_latency_ms(seconds: &[TickSecond]) -> Option<f64> {
+        let mut latencies = [0.0; TICK_RATE_WINDOW_SECS];
+        let mut count = 0;
+        for second in seconds.iter().filter(|second| second.ticks > 0) {
+            latencies[count] = second.average_latency_ms();
+            count += 1;
+        }
+        let latencies = &mut latencies[..count];
+        if latencies.is_empty() {
+            return None;
+        }
+        latencies.sort_by(f64::total_cmp);
+        let rank = ((latencies.len() as f64 * 0.95).ceil() as usize).clamp(1, latencies.len());
+        Some(latencies[rank - 1])
+    }
+}
+
+/// Upper bounds, in seconds, of the API request duration buckets
+pub const API_LATENCY_BUCKETS: [f64; 8] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Durations of the API worker's commands and refreshes this session, bucketed like a
+/// Prometheus histogram
+#[derive(Debug, Default, Clone)]
+pub struct ApiLatency {
+    // Requests at or under each bound of API_LATENCY_BUCKETS, cumulative
+    pub buckets: [u64; API_LATENCY_BUCKETS.len()],
+    pub count: u64,
+    pub sum_seconds: f64,
+}
+
+impl ApiLatency {
+    pub fn record(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        for (bound, bucket) in API_LATENCY_BUCKETS.iter().zip(&mut self.buckets) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum_seconds += seconds;
+    }
+
+    pub fn average_ms(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum_seconds * 1000.0 / self.count as f64)
+    }
+
+    /// Smallest bucket bound holding at least 95% of requests; None if some took longer
+    /// than the last bound
+    pub fn p95_bound_seconds(&self) -> Option<f64> {
+        let needed = (self.count as f64 * 0.95).ceil() as u64;
+        API_LATENCY_BUCKETS
+            .iter()
+            .zip(&self.buckets)
+            .find(|(_, count)| **count >= needed)
+            .map(|(bound, _)| *bound)
+    }
+}
+
+/// How long the event backlog may stay above threshold before warning
+const BACKLOG_GRACE: Duration = Duration::from_secs(3);
+
+/// Header line for view exports, e.g. `positions export at 2026-01-16T15:15:00+05:30, filter: "nifty"`
+fn export_comment(kind: &str, filter: &str) -> String {
+    let filter = if filter.trim().is_empty() {
+        "none".to_string()
+    } else {
+        format!("{:?}", filter.trim())
+    };
+    format!(
+        "{} export at {}, filter: {}",
+        kind,
+        ist_now().to_rfc3339(),
+        filter
+    )
+}
+
+/// Symbol substring or exact order id match against an already lowercased filter
+fn order_matches_filter(order: &Order, filter: &str) -> bool {
+    order.tradingsymbol.to_lowercase().contains(filter) || order.order_id == filter
+}
+
+impl AppState {
+    /// Create new application state with initialized channels and data structures
+    pub fn new(config: Config) -> (Self, CommandReceivers) {
+        let (command_sender, api_receiver) = mpsc::unbounded_channel();
+        let (priority_command_sender, api_priority_receiver) = mpsc::unbounded_channel();
+        let (websocket_command_sender, websocket_receiver) = crossbeam_channel::unbounded();
+        let (_event_sender, event_receiver) = crossbeam_channel::unbounded();
+        let (_tick_sender, tick_receiver) = crossbeam_channel::unbounded();
+
+        // Initialize with a mock logged-in state for personal trading
+        let initial_auth_state = AuthState::LoggedIn {
+            access_token: "mock_token".to_string(),
+            user_name: "Personal Trading".to_string(),
+            user_profile: None,
+        };
+        let theme = Theme::from_config(&config.app);
+
+        let state = Self {
+            loaded_config: config.clone(),
+            config,
+            auth_state: Arc::new(RwLock::new(initial_auth_state)),
+            connection_state: Arc::new(RwLock::new(ConnectionState::default())),
+            positions: Arc::new(DashMap::with_capacity(1000)),
+            orders: Arc::new(DashMap::with_capacity(10000)),
+            trades: Arc::new(RwLock::new(Vec::new())),
+            order_history: Arc::new(DashMap::new()),
+            instruments: Arc::new(DashMap::with_capacity(50000)),
+            instrument_index: Arc::new(RwLock::new(InstrumentIndex::default())),
+            tag_positions: Arc::new(RwLock::new(HashMap::new())),
+            strategies: Arc::new(RwLock::new(Vec::new())),
+            broadcaster: None,
+            notifier: None,
+            watchlist: Arc::new(RwLock::new(Watchlist::default())),
+            alerts: Arc::new(RwLock::new(AlertBook::default())),
+            user_profile: Arc::new(RwLock::new(None)),
+            tick_data: Arc::new(DashMap::with_capacity(1000)),
+            candles: Arc::new(DashMap::new()),
+            candle_history_requested: Arc::new(RwLock::new(HashSet::new())),
+            auto_subscribed_tokens: Arc::new(RwLock::new(HashSet::new())),
+            explicit_subscriptions: Arc::new(RwLock::new(HashSet::new())),
+            orders_in_flight: Arc::new(RwLock::new(OrdersInFlight::default())),
+            pnl_history: Arc::new(RwLock::new(PnlHistory::default())),
+            margins: Arc::new(RwLock::new(None)),
+            market_depth: Arc::new(DashMap::new()),
+            tick_history: Arc::new(DashMap::with_capacity(1000)),
+            logs: Arc::new(RwLock::new(Vec::with_capacity(10000))),
+            ui_input: UiInputState::default(),
+            toasts: Vec::new(),
+            kill_switch: false,
+            shutting_down: false,
+            notifications: VecDeque::new(),
+            critical_errors: VecDeque::new(),
+            command_sender,
+            priority_command_sender,
+            websocket_command_sender,
+            event_receiver,
+            tick_receiver,
+            dropped_ticks: Arc::new(AtomicU64::new(0)),
+            metrics: Arc::new(RwLock::new(PerformanceMetrics::default())),
+            journal: None,
+            history: None,
+            daily_history: Vec::new(),
+            sounds: SoundPlayer::default(),
+            positions_refreshed_at: None,
+            orders_refreshed_at: None,
+            theme,
+            positions_gen: Generation::default(),
+            orders_gen: Generation::default(),
+            ticks_gen: Generation::default(),
+            positions_snapshot: PositionsSnapshot::default(),
+            pnl_cache: parking_lot::Mutex::new(None),
+            local_events: parking_lot::Mutex::new(VecDeque::new()),
+            token_reminded_on: None,
+            instruments_requested_at: None,
+            token_expiry_checked: false,
+        };
+
+        (
+            state,
+            CommandReceivers {
+                api: Arc::new(tokio::sync::Mutex::new(api_receiver)),
+                api_priority: Arc::new(tokio::sync::Mutex::new(api_priority_receiver)),
+                websocket: websocket_receiver,
+            },
+        )
+    }
+
+    /// Add log entry with automatic timestamping
+    /// Credentials interpolated into the message, e.g. in a URL, are masked on the way in
+    pub fn add_log(&self, level: LogLevel, message: String, module: Option<String>) {
+        let message = redact::redact_params(&message);
+        crate::logging::echo(level, module.as_deref(), &message);
+        self.push_log(LogEntry::new(level, message, module));
+    }
+
+    fn push_log(&self, log_entry: LogEntry) {
+        let mut logs = self.logs.write();
+
+        // Keep only the last 10000 log entries for memory efficiency
+        if logs.len() >= 10000 {
+            logs.drain(0..1000);
+        }
+
+        logs.push(log_entry);
+    }
+
+    /// Show a transient toast notification over the main window
+    pub fn push_toast(&mut self, level: LogLevel, message: String) {
+        // Bound the queue so a burst of errors cannot cover the screen
+        if self.toasts.len() >= 5 {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(Toast::new(level, message));
+    }
+
+    /// Toast an event and keep it in the notification center
+    pub fn notify(&mut self, level: LogLevel, message: String, target: Option<NotificationTarget>) {
+        if self.notifications.len() >= MAX_NOTIFICATIONS {
+            self.notifications.pop_front();
+        }
+        self.notifications.push_back(Notification {
+            level,
+            message: message.clone(),
+            created_at: Utc::now(),
+            read: false,
+            target,
+        });
+        self.push_toast(level, message);
+    }
+
+    /// Queue an error for the dialog that stays until dismissed
+    pub fn raise_critical(&mut self, message: String, module: Option<String>) {
+        if self.critical_errors.len() >= MAX_CRITICAL_ERRORS {
+            self.critical_errors.pop_front();
+        }
+        self.critical_errors.push_back(CriticalError {
+            message,
+            module,
+            raised_at: Utc::now(),
+        });
+    }
+
+    pub fn unread_notifications(&self) -> usize {
+        self.notifications.iter().filter(|n| !n.read).count()
+    }
+
+    /// Switch to a notification's view, filtering it to the notification's symbol
+    pub fn open_notification(&mut self, index: usize) {
+        let Some(notification) = self.notifications.get_mut(index) else {
+            return;
+        };
+        notification.read = true;
+        let Some(target) = notification.target.clone() else {
+            return;
+        };
+
+        match (target.view, target.symbol, target.instrument_token) {
+            (AppView::Orders, Some(symbol), _) => {
+                self.ui_input.order_filter = symbol;
+                self.ui_input.order_tab = OrderTab::All;
+            }
+            (AppView::Positions, Some(symbol), _) => {
+                self.ui_input.position_filter = symbol;
+                self.ui_input.position_filters = PositionFilters::default();
+            }
+            (AppView::Charts, _, Some(token)) => self.select_instrument(token),
+            _ => {}
+        }
+        self.ui_input.requested_view = Some(target.view);
+    }
+
+    /// Update position with new tick data - optimized for high frequency updates
+    /// Every product row of the instrument is repriced
+    /// Returns whether the tick repriced a held position
+    pub fn update_position_price(&self, instrument_token: u32, last_price: f64) -> bool {
+        let mut repriced = false;
+        for mut position in self.positions.iter_mut() {
+            if position.instrument_token == instrument_token {
+                position.update_last_price(last_price);
+                repriced = true;
+            }
+        }
+        repriced
+    }
+
+    /// The instrument's positions, one per product, CNC before MIS before NRML
+    pub fn positions_of(&self, instrument_token: u32) -> Vec<Position> {
+        let mut positions: Vec<Position> = self
+            .positions
+            .iter()
+            .filter(|entry| entry.instrument_token == instrument_token)
+            .map(|entry| entry.value().clone())
+            .collect();
+        positions.sort_by(|a, b| a.product.cmp(&b.product));
+        positions
+    }
+
+    /// Whether any product of the instrument is still held
+    pub fn has_open_position(&self, instrument_token: u32) -> bool {
+        self.positions
+            .iter()
+            .any(|entry| entry.instrument_token == instrument_token && entry.quantity != 0)
+    }
+
+    /// Append a tick to the instrument's history, evicting the oldest beyond capacity
+    fn record_tick_history(&self, instrument_token: u32, sample: TickSample) {
+        let capacity = self.config.app.tick_history_size;
+        if capacity == 0 {
+            return;
+        }
+
+        let mut history = self
+            .tick_history
+            .entry(instrument_token)
+            .or_insert_with(|| VecDeque::with_capacity(capacity));
+        while history.len() >= capacity {
+            history.pop_front();
+        }
+        history.push_back(sample);
+    }
+
+    /// Copy of an instrument's recent ticks, oldest first
+    pub fn tick_history(&self, instrument_token: u32) -> Vec<TickSample> {
+        self.tick_history
+            .get(&instrument_token)
+            .map(|history| history.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Calculate total PnL across all positions, summed again only after positions change
+    pub fn calculate_total_pnl(&self) -> PnlData {
+        let generation = self.positions_gen.get();
+        let mut cache = self.pnl_cache.lock();
+        if let Some((_, pnl)) = cache.as_ref().filter(|(at, _)| *at == generation) {
+            return pnl.clone();
+        }
+
+        let mut pnl = PnlData::default();
+        for entry in self.positions.iter() {
+            pnl.add_position(entry.value());
+        }
+        *cache = Some((generation, pnl.clone()));
+        pnl
+    }
+
+    /// Whether a position passes the Positions view's text and dropdown filters
+    pub fn position_visible(&self, position: &Position) -> bool {
+        let filter = self.ui_input.position_filter.to_lowercase();
+        (filter.is_empty() || position.tradingsymbol.to_lowercase().contains(&filter))
+            && self.ui_input.position_filters.matches(position)
+    }
+
+    /// PnL and count of the positions the Positions view's filters let through
+    pub fn visible_positions_pnl(&self) -> (PnlData, usize) {
+        let mut pnl = PnlData::default();
+        let mut count = 0;
+        for position in self.positions_snapshot.positions.iter() {
+            if self.position_visible(position) {
+                pnl.add_position(position);
+                count += 1;
+            }
+        }
+        (pnl, count)
+    }
+
+    /// Bring positions_snapshot up to date with the map and the positions table's sort
+    pub fn sync_positions_snapshot(&mut self) {
+        self.positions_snapshot.sync(
+            &self.positions,
+            &self.positions_gen,
+            self.ui_input.position_sort,
+        );
+    }
+
+    /// PnL subtotals per exchange, summing to calculate_total_pnl
+    pub fn positions_by_exchange(&self) -> BTreeMap<String, PnlData> {
+        self.group_pnl(|position| &position.exchange)
+    }
+
+    /// Positions of a snapshot split into P&L groups
+    /// Each position lands in exactly one group, so the subtotals sum to calculate_total_pnl
+    pub fn pnl_groups<'a>(
+        &self,
+        positions: &'a [Position],
+        grouping: PnlGrouping,
+    ) -> Vec<PnlGroup<'a>> {
+        let tags = self.position_tags();
+        let mut groups: BTreeMap<String, (PnlData, Vec<&'a Position>)> = BTreeMap::new();
+
+        for position in positions {
+            let key = match grouping {
+                PnlGrouping::None => String::new(),
+                PnlGrouping::Tag => tags
+                    .get(&(position.instrument_token, position.product.clone()))
+                    .cloned()
+                    .unwrap_or_else(|| UNTAGGED_GROUP.to_string()),
+                PnlGrouping::Product => position.product.clone(),
+                PnlGrouping::Exchange => position.exchange.clone(),
+            };
+
+            let (pnl, positions) = groups.entry(key).or_default();
+            pnl.add_position(position);
+            positions.push(position);
+        }
+
+        groups
+            .into_iter()
+            .map(|(key, (pnl, positions))| (key, pnl, positions))
+            .collect()
+    }
+
+    /// Strategy tag owning each (instrument, product) position
+    /// When several tags traded the same position, the one with the largest net quantity wins
+    fn position_tags(&self) -> HashMap<(u32, String), String> {
+        let mut owners: HashMap<(u32, String), (String, i32)> = HashMap::new();
+
+        for (tag, positions) in self.tag_positions.read().iter() {
+            for position in positions {
+                let size = position.net_quantity.abs();
+                let key = (position.instrument_token, position.product.clone());
+                match owners.get(&key) {
+                    Some((owner, owner_size))
+                        if *owner_size > size || (*owner_size == size && owner < tag) => {}
+                    _ => {
+                        owners.insert(key, (tag.clone(), size));
+                    }
+                }
+            }
+        }
+
+        owners
+            .into_iter()
+            .map(|(key, (tag, _))| (key, tag))
+            .collect()
+    }
+
+    fn group_pnl(&self, key: impl Fn(&Position) -> &str) -> BTreeMap<String, PnlData> {
+        let mut groups: BTreeMap<String, PnlData> = BTreeMap::new();
+        for position in self.positions_snapshot.positions.iter() {
+            groups
+                .entry(key(position).to_string())
+                .or_default()
+                .add_position(position);
+        }
+        groups
+    }
+
+    /// Orders matching a tradingsymbol or order id filter and a status tab
+    pub fn get_filtered_orders(&self, filter: &str, tab: OrderTab) -> Vec<Order> {
+        let filter = filter.to_lowercase();
+        self.orders
+            .iter()
+            .filter(|entry| {
+                let order = entry.value();
+                tab.matches(&order.status) && order_matches_filter(order, &filter)
+            })
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    /// Orders per status tab among those the filter matches
+    pub fn order_tab_counts(&self, filter: &str) -> HashMap<OrderTab, usize> {
+        let filter = filter.to_lowercase();
+        let mut counts = HashMap::new();
+        for entry in self.orders.iter() {
+            let order = entry.value();
+            if !order_matches_filter(order, &filter) {
+                continue;
+            }
+            for tab in OrderTab::ALL {
+                if tab.matches(&order.status) {
+                    *counts.entry(tab).or_insert(0) += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    /// Find instrument master data by trading symbol, preferring the given exchange
+    pub fn find_instrument(&self, tradingsymbol: &str, exchange: &str) -> Option<Instrument> {
+        let token = self
+            .instrument_index
+            .read()
+            .lookup(tradingsymbol, exchange)?;
+        self.instruments
+            .get(&token)
+            .map(|entry| entry.value().clone())
+    }
+
+    /// Validate an order against loaded instrument data
+    /// Orders for instruments that are not loaded yet pass through unchanged
+    pub fn validate_order(&self, details: &OrderRequest) -> Result<(), OrderValidationError> {
+        details.validate_amounts()?;
+        match self.find_instrument(&details.tradingsymbol, &details.exchange) {
+            Some(instrument) => details.validate_against(&instrument),
+            None => Ok(()),
+        }
+    }
+
+    /// Ask for an instrument's session candles unless they were already requested
+    pub fn request_candle_history(&self, instrument_token: u32) {
+        if self
+            .candle_history_requested
+            .write()
+            .insert(instrument_token)
+        {
+            self.send_command(Command::FetchHistoricalCandles { instrument_token });
+            if let Some(history) = &self.history {
+                history.load_candles(instrument_token, ist_midnight());
+            }
+        }
+    }
+
+    /// Why orders can't be placed, modified or cancelled right now, if they can't
+    /// Paper orders never reach the broker, so only live trading needs the feed up
+    pub fn trading_blocked_reason(&self) -> Option<&'static str> {
+        if self.kill_switch {
+            return Some("Kill switch is on");
+        }
+        if self.config.app.mode == TradingMode::Paper {
+            return None;
+        }
+        match *self.connection_state.read() {
+            ConnectionState::Connected => None,
+            ConnectionState::AuthExpired => Some("Access token expired; re-authenticate to trade"),
+            _ => Some("Market data feed is disconnected"),
+        }
+    }
+
+    pub fn trading_enabled(&self) -> bool {
+        self.trading_blocked_reason().is_none()
+    }
+
+    /// Re-fetch everything the dashboard shows from the broker
+    pub fn refresh_all(&self) {
+        self.send_command(Command::FetchPositions);
+        self.send_command(Command::FetchOrders);
+        self.send_command(Command::FetchTrades);
+        self.send_command(Command::FetchMargins);
+
+        self.add_log(
+            LogLevel::Info,
+            "Refreshing all data...".to_string(),
+            Some("app".to_string()),
+        );
+    }
+
+    /// Time left until the API handler's next background positions refresh
+    pub fn positions_refresh_remaining(&self) -> Option<Duration> {
+        self.refresh_remaining(
+            self.config.app.positions_refresh_interval(),
+            self.positions_refreshed_at,
+        )
+    }
+
+    /// Time left until the API handler's next background orders refresh
+    pub fn orders_refresh_remaining(&self) -> Option<Duration> {
+        self.refresh_remaining(
+            self.config.app.orders_refresh_interval(),
+            self.orders_refreshed_at,
+        )
+    }
+
+    /// Counted from the last update to arrive, which the handler's schedule also follows
+    /// None when that refresh is off, the market is closed or the access token was rejected
+    fn refresh_remaining(
+        &self,
+        interval: Option<Duration>,
+        refreshed_at: Option<Instant>,
+    ) -> Option<Duration> {
+        let interval = interval?;
+        if !MarketStatus::now("NSE").is_trading()
+            || *self.connection_state.read() == ConnectionState::AuthExpired
+        {
+            return None;
+        }
+        Some(refreshed_at.map_or(Duration::ZERO, |at| interval.saturating_sub(at.elapsed())))
+    }
+
+    /// Ask for confirmation before cancelling every open order
+    pub fn request_cancel_all_orders(&mut self) {
+        let order_ids: Vec<String> = self
+            .orders
+            .iter()
+            .filter(|entry| entry.value().is_cancellable())
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        if order_ids.is_empty() {
+            self.push_toast(LogLevel::Info, "No open orders to cancel".to_string());
+        } else {
+            self.ui_input.pending_cancel_all = Some(order_ids);
+        }
+    }
+
+    /// Open the detail drawer for an order and fetch its lifecycle
+    pub fn inspect_order(&mut self, order_id: &str) {
+        self.ui_input.inspected_order_id = Some(order_id.to_string());
+        self.send_command(Command::FetchOrderHistory {
+            order_id: order_id.to_string(),
+        });
+    }
+
+    /// Make an instrument the target of the depth panel and stream it in full mode
+    pub fn select_instrument(&mut self, instrument_token: u32) {
+        if self.ui_input.selected_instrument_token == Some(instrument_token) {
+            return;
+        }
+
+        self.ui_input.selected_instrument_token = Some(instrument_token);
+        self.send_command(Command::SetTickMode {
+            instrument_tokens: vec![instrument_token],
+            mode: TickMode::Full,
+        });
+    }
+
+    /// Previous session close for day change: the position's close price, else the instrument master's
+    pub fn previous_close(&self, instrument_token: u32) -> Option<f64> {
+        self.positions_of(instrument_token)
+            .iter()
+            .map(|position| position.close_price)
+            .find(|close| *close > 0.0)
+            .or_else(|| {
+                self.instruments
+                    .get(&instrument_token)
+                    .map(|instrument| instrument.last_price)
+                    .filter(|close| *close > 0.0)
+            })
+    }
+
+    /// Positions and watchlist instruments ranked by day change, biggest gainer first
+    /// Instruments without a previous close (e.g. new listings) or a price are left out
+    pub fn day_movers(&self) -> Vec<DayMover> {
+        let mut tokens: Vec<u32> = self
+            .positions
+            .iter()
+            .map(|entry| entry.instrument_token)
+            .collect();
+        tokens.extend(self.watchlist.read().tokens());
+        tokens.sort_unstable();
+        tokens.dedup();
+
+        let mut movers: Vec<DayMover> = tokens
+            .into_iter()
+            .filter_map(|token| {
+                let close = self.previous_close(token)?;
+                let position = self.positions_of(token).into_iter().next();
+                let last_price = self
+                    .tick_data
+                    .get(&token)
+                    .map(|tick| tick.last_price)
+                    .or_else(|| position.as_ref().map(|position| position.last_price))
+                    .filter(|price| *price > 0.0)?;
+                let tradingsymbol = match &position {
+                    Some(position) => position.tradingsymbol.clone(),
+                    None => self.instruments.get(&token)?.tradingsymbol.clone(),
+                };
+
+                Some(DayMover {
+                    instrument_token: token,
+                    tradingsymbol,
+                    last_price,
+                    change_pct: (last_price - close) / close * 100.0,
+                    in_positions: position.is_some(),
+                })
+            })
+            .collect();
+
+        movers.sort_by(|a, b| b.change_pct.total_cmp(&a.change_pct));
+        movers
+    }
+
+    /// Autocomplete candidates for the order dialog's symbol field
+    pub fn search_instruments(&self, query: &str, exchange: Exchange) -> Vec<u32> {
+        self.instrument_index
+            .read()
+            .search(query, exchange.as_api_str(), 10)
+    }
+
+    /// Check a modification against the order's current state
+    pub fn validate_modification(
+        &self,
+        order_id: &str,
+        details: &OrderRequest,
+    ) -> Result<(), OrderValidationError> {
+        if let Some(order) = self.orders.get(order_id) {
+            if !order.is_cancellable() {
+                return Err(OrderValidationError::NotModifiable {
+                    order_id: order_id.to_string(),
+                    status: order.status.clone(),
+                });
+            }
+            if details.quantity < order.filled_quantity {
+                return Err(OrderValidationError::QuantityBelowFilled {
+                    order_id: order_id.to_string(),
+                    quantity: details.quantity,
+                    filled: order.filled_quantity,
+                });
+            }
+        }
+
+        self.validate_order(details)
+    }
+
+    /// Load the persisted watchlist and subscribe to ticks for its members
+    pub fn load_watchlist(&self) {
+        match Watchlist::load(WATCHLIST_FILE) {
+            Ok(watchlist) => {
+                let tokens = watchlist.tokens();
+                *self.watchlist.write() = watchlist;
+
+                if !tokens.is_empty() {
+                    self.add_log(
+                        LogLevel::Info,
+                        format!("Loaded watchlist with {} instruments", tokens.len()),
+                        Some("watchlist".to_string()),
+                    );
+                    self.send_command(Command::SubscribeToTicks {
+                        instrument_tokens: tokens,
+                    });
+                }
+            }
+            Err(e) => {
+                self.add_log(
+                    LogLevel::Error,
+                    format!("Failed to load {}: {}", WATCHLIST_FILE, e),
+                    Some("watchlist".to_string()),
+                );
+            }
+        }
+    }
+
+    /// Apply a watchlist command, persist the result and adjust tick subscriptions
+    fn apply_watchlist_command(&self, command: Command) {
+        let changed = {
+            let mut watchlist = self.watchlist.write();
+            match &command {
+                Command::AddToWatchlist {
+                    instrument_token,
+                    note,
+                } => watchlist.add(*instrument_token, note.clone()),
+                Command::RemoveFromWatchlist { instrument_token } => {
+                    watchlist.remove(*instrument_token)
+                }
+                Command::ReorderWatchlist { from, to } => watchlist.reorder(*from, *to),
+                _ => false,
+            }
+        };
+
+        if !changed {
+            return;
+        }
+
+        if let Err(e) = self.watchlist.read().save(WATCHLIST_FILE) {
+            self.add_log(
+                LogLevel::Error,
+                format!("Failed to save {}: {}", WATCHLIST_FILE, e),
+                Some("watchlist".to_string()),
+            );
+        }
+
+        match command {
+            Command::AddToWatchlist {
+                instrument_token, ..
+            } => {
+                self.send_command(Command::SubscribeToTicks {
+                    instrument_tokens: vec![instrument_token],
+                });
+            }
+            // Keep streaming instruments that still back an open position
+            Command::RemoveFromWatchlist { instrument_token }
+                if !self.has_open_position(instrument_token) =>
+            {
+                self.send_command(Command::UnsubscribeFromTicks {
+                    instrument_tokens: vec![instrument_token],
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Subscribe to open positions not yet streaming, and with `unsubscribe_flat_positions`
+    /// drop auto-subscribed ones that went flat
+    /// Watchlist members and the selected instrument are left to their own subscriptions
+    fn sync_position_subscriptions(&self) {
+        let watched = self.watchlist.read().tokens();
+        let open: HashSet<u32> = self
+            .positions
+            .iter()
+            .filter(|entry| entry.quantity != 0)
+            .map(|entry| entry.instrument_token)
+            .collect();
+
+        let explicit = self.explicit_subscriptions.read().clone();
+        let added: Vec<u32> = {
+            let subscribed = self.auto_subscribed_tokens.read();
+            open.iter()
+                .filter(|token| {
+                    !subscribed.contains(token)
+                        && !watched.contains(token)
+                        && !explicit.contains(token)
+                })
+                .copied()
+                .collect()
+        };
+        if !added.is_empty() {
+            self.auto_subscribed_tokens.write().extend(&added);
+            // Straight to the worker, so these don't count as explicit subscriptions
+            self.dispatch(Command::SubscribeToTicks {
+                instrument_tokens: added.clone(),
+            });
+            self.add_log(
+                LogLevel::Info,
+                format!("Auto-subscribed {} position instruments", added.len()),
+                Some("positions".to_string()),
+            );
+        }
+
+        if !self.config.app.unsubscribe_flat_positions {
+            return;
+        }
+
+        let selected = self.ui_input.selected_instrument_token;
+        let flat: Vec<u32> = {
+            let mut subscribed = self.auto_subscribed_tokens.write();
+            let flat: Vec<u32> = subscribed
+                .iter()
+                .filter(|token| {
+                    !open.contains(token)
+                        && !watched.contains(token)
+                        && !explicit.contains(token)
+                        && selected != Some(**token)
+                })
+                .copied()
+                .collect();
+            for token in &flat {
+                subscribed.remove(token);
+            }
+            flat
+        };
+        if !flat.is_empty() {
+            self.add_log(
+                LogLevel::Info,
+                format!("Unsubscribed {} flat position instruments", flat.len()),
+                Some("positions".to_string()),
+            );
+            self.send_command(Command::UnsubscribeFromTicks {
+                instrument_tokens: flat,
+            });
+        }
+    }
+
+    /// Open the re-authentication dialog with the Kite login URL for the configured API key
+    pub fn start_reauth(&mut self) {
+        let login_url = ZerodhaClient::login_url(&self.config.zerodha.api_key);
+        let port = self.config.zerodha.callback_port;
+        if port != 0 {
+            self.send_command(Command::AwaitLoginCallback {
+                port,
+                login_url: login_url.clone(),
+            });
+        }
+        self.ui_input.reauth = Some(ReauthForm {
+            login_url,
+            listening: Some(port).filter(|port| *port != 0),
+            ..Default::default()
+        });
+    }
+
+    /// Close the re-authentication dialog, freeing the callback port if it is still held
+    pub fn cancel_reauth(&mut self) {
+        if let Some(form) = self.ui_input.reauth.take() {
+            if form.listening.is_some() {
+                self.send_command(Command::CancelLoginCallback);
+            }
+        }
+    }
+
+    /// Send the pasted request_token off to be exchanged for an access token
+    pub fn submit_reauth(&mut self) {
+        let Some(form) = &mut self.ui_input.reauth else {
+            return;
+        };
+        let Some(request_token) = form.request_token() else {
+            form.error = Some("Paste the request_token from the redirect URL".to_string());
+            return;
+        };
+        form.exchanging = true;
+        form.error = None;
+        self.send_command(Command::ExchangeRequestToken { request_token });
+    }
+
+    /// Hand a fresh access token to both workers and write it to config.toml
+    /// Trading stays blocked until the market data feed reconnects with it
+    fn apply_access_token(&mut self, access_token: String) {
+        self.send_command(Command::UpdateAccessToken {
+            access_token: access_token.clone(),
+        });
+        // Whole seconds, as config.toml stores it
+        let issued_at = Utc::now().trunc_subsecs(0);
+        self.config.zerodha.access_token = access_token.clone();
+        self.config.zerodha.access_token_issued_at = Some(issued_at);
+        self.token_expiry_checked = false;
+        *self.connection_state.write() = ConnectionState::Connecting;
+        self.cancel_reauth();
+
+        // Only the token lines change, so the file watcher's echo matches loaded_config
+        // and is skipped
+        match config_writer::write_access_token(
+            Path::new(CONFIG_FILE),
+            config_writer::ZERODHA_SECTION,
+            &access_token,
+            issued_at,
+        ) {
+            Ok(backup) => {
+                self.loaded_config.zerodha.access_token = access_token;
+                self.track_saved_token_issued_at(issued_at);
+                self.add_log(
+                    LogLevel::Info,
+                    format!(
+                        "Saved the new access token to {} (previous file kept as {})",
+                        CONFIG_FILE,
+                        backup.display()
+                    ),
+                    Some("auth".to_string()),
+                );
+            }
+            Err(e) => self.add_log(
+                LogLevel::Error,
+                format!(
+                    "Re-authenticated, but failed to save the token to {}: {}",
+                    CONFIG_FILE, e
+                ),
+                Some("auth".to_string()),
+            ),
+        }
+
+        self.notify(
+            LogLevel::Info,
+            "Re-authenticated; reconnecting with the new access token".to_string(),
+            None,
+        );
+        self.refresh_all();
+    }
+
+    /// Validate the Settings draft, write it to config.toml and apply it as a reload
+    pub fn save_settings(&mut self) {
+        let Some(form) = &mut self.ui_input.settings_form else {
+            return;
+        };
+        form.problems = form.draft.validate();
+        if !form.problems.is_empty() {
+            return;
+        }
+
+        let draft = form.draft.clone();
+        if let Err(e) = draft.save(CONFIG_FILE) {
+            self.add_log(
+                LogLevel::Error,
+                format!("Failed to write {}: {}", CONFIG_FILE, e),
+                Some("config".to_string()),
+            );
+            return;
+        }
+
+        self.add_log(
+            LogLevel::Info,
+            format!("Settings saved to {}", CONFIG_FILE),
+            Some("config".to_string()),
+        );
+        self.handle_event(AppEvent::ConfigReloaded(Box::new(draft)));
+    }
+
+    /// Check the credentials in the Settings draft against the API without saving them
+    pub fn test_settings_connection(&mut self) {
+        let Some(form) = &mut self.ui_input.settings_form else {
+            return;
+        };
+        form.connection_test = ConnectionTest::Testing;
+        let zerodha = form.draft.zerodha.clone();
+        self.send_command(Command::TestConnection {
+            api_key: zerodha.api_key,
+            api_secret: zerodha.api_secret,
+            access_token: zerodha.access_token,
+        });
+    }
+
+    /// Send a test message with the notifier settings in the Settings draft
+    pub fn test_notification(&mut self) {
+        let Some(form) = &mut self.ui_input.settings_form else {
+            return;
+        };
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+        form.notification_test = ConnectionTest::Testing;
+        notifier.test(form.draft.notifier.clone());
+    }
+
+    /// Tell the remote notifier, when one is running
+    fn notify_remote(&self, kind: AlertKind, message: &str) {
+        if let Some(notifier) = &self.notifier {
+            notifier.send(kind, message.to_string());
+        }
+    }
+
+    /// Load persisted price alerts
+    pub fn load_alerts(&self) {
+        match AlertBook::load(ALERTS_FILE) {
+            Ok(book) => *self.alerts.write() = book,
+            Err(e) => {
+                self.add_log(
+                    LogLevel::Error,
+                    format!("Failed to load {}: {}", ALERTS_FILE, e),
+                    Some("alerts".to_string()),
+                );
+            }
+        }
+    }
+
+    /// Send a running strategy's request on through send_command, tagging its orders
+    /// Requests from a strategy switched off since are dropped, as is anything a strategy
+    /// has no business sending
+    fn apply_strategy_command(&self, strategy: &str, mut command: Command) {
+        let enabled = self
+            .strategies
+            .read()
+            .iter()
+            .any(|status| status.name == strategy && status.enabled);
+        if !enabled {
+            log::debug!("Dropped a request from disabled strategy {}", strategy);
+            return;
+        }
+
+        match &mut command {
+            Command::PlaceOrder { details } => {
+                details.tag = Some(strategy.to_string());
+                self.add_log(
+                    LogLevel::Info,
+                    format!(
+                        "Strategy {}: {} {} {} {}",
+                        strategy,
+                        details.transaction_type,
+                        details.quantity,
+                        details.tradingsymbol,
+                        details.order_type
+                    ),
+                    Some("strategy".to_string()),
+                );
+            }
+            Command::CancelOrder { order_id, .. } => {
+                let own = self
+                    .orders
+                    .get(order_id.as_str())
+                    .is_some_and(|order| order.tag.as_deref() == Some(strategy));
+                if !own {
+                    self.add_log(
+                        LogLevel::Warning,
+                        format!(
+                            "Strategy {} tried to cancel order {}, which it did not place",
+                            strategy, order_id
+                        ),
+                        Some("strategy".to_string()),
+                    );
+                    return;
+                }
+            }
+            Command::SubscribeToTicks { instrument_tokens } => {
+                // Candles from the session so far let averages start without waiting
+                for token in instrument_tokens.iter() {
+                    self.request_candle_history(*token);
+                }
+            }
+            _ => {
+                log::warn!("Ignored an unsupported request from strategy {}", strategy);
+                return;
+            }
+        }
+        self.send_command(command);
+    }
+
+    /// Realized and open P&L of a strategy's own fills, priced at the latest ticks
+    pub fn strategy_pnl(&self, strategy: &str) -> TagPnl {
+        let orders: Vec<Order> = self
+            .orders
+            .iter()
+            .filter(|order| order.tag.as_deref() == Some(strategy))
+            .map(|order| order.value().clone())
+            .collect();
+        tag_pnl(&orders, |token| {
+            self.tick_data.get(&token).map(|tick| tick.last_price)
+        })
+    }
+
+    /// Apply an alert command and persist the alert book
+    fn apply_alert_command(&self, command: Command) {
+        let changed = {
+            let mut book = self.alerts.write();
+            match command {
+                Command::CreateAlert { alert } => {
+                    book.create(alert);
+                    true
+                }
+                Command::UpdateAlert { alert } => book.update(alert),
+                Command::DeleteAlert { alert_id } => book.delete(&alert_id),
+                _ => false,
+            }
+        };
+
+        if changed {
+            self.save_alerts();
+        }
+    }
+
+    fn save_alerts(&self) {
+        if let Err(e) = self.alerts.read().save(ALERTS_FILE) {
+            self.add_log(
+                LogLevel::Error,
+                format!("Failed to save {}: {}", ALERTS_FILE, e),
+                Some("alerts".to_string()),
+            );
+        }
+    }
+
+    /// Trading symbol for an instrument token, falling back to the token itself
+    pub fn symbol_for_token(&self, instrument_token: u32) -> String {
+        if let Some(position) = self.positions_of(instrument_token).first() {
+            return position.tradingsymbol.clone();
+        }
+        self.instruments
+            .get(&instrument_token)
+            .map(|instrument| instrument.tradingsymbol.clone())
+            .unwrap_or_else(|| instrument_token.to_string())
+    }
+
+    /// "NSE:RELIANCE" for messages; a position names instruments the master hasn't
+    /// loaded yet, and failing both it's the bare token
+    pub fn instrument_label(&self, instrument_token: u32) -> String {
+        match self.instruments.get(&instrument_token) {
+            Some(instrument) => format!("{}:{}", instrument.exchange, instrument.tradingsymbol),
+            None => self.positions_of(instrument_token).first().map_or_else(
+                || instrument_token.to_string(),
+                |position| format!("{}:{}", position.exchange, position.tradingsymbol),
+            ),
+        }
+    }
+
+    /// Cancellation of a known order, sent with the variety it was placed as
+    pub fn cancel_order_command(&self, order_id: String) -> Command {
+        let variety = self
+            .orders
+            .get(&order_id)
+            .map_or_else(|| "regular".to_string(), |order| order.variety.clone());
+        Command::CancelOrder { order_id, variety }
+    }
+
+    /// Send command to worker threads
+    pub fn send_command(&self, mut command: Command) {
+        if self.shutting_down {
+            log::debug!("Command dropped: the app is shutting down");
+            return;
+        }
+
+        match command {
+            Command::AddToWatchlist { .. }
+            | Command::RemoveFromWatchlist { .. }
+            | Command::ReorderWatchlist { .. } => {
+                self.apply_watchlist_command(command);
+                return;
+            }
+            Command::CreateAlert { .. }
+            | Command::UpdateAlert { .. }
+            | Command::DeleteAlert { .. } => {
+                self.apply_alert_command(command);
+                return;
+            }
+            Command::ExportPositions { .. }
+            | Command::ExportOrders { .. }
+            | Command::ExportTrades { .. } => {
+                self.apply_export_command(command);
+                return;
+            }
+            _ => {}
+        }
+
+        match &command {
+            Command::SubscribeToTicks { instrument_tokens } => {
+                self.explicit_subscriptions
+                    .write()
+                    .extend(instrument_tokens);
+            }
+            Command::UnsubscribeFromTicks { instrument_tokens } => {
+                let mut explicit = self.explicit_subscriptions.write();
+                let mut auto = self.auto_subscribed_tokens.write();
+                for token in instrument_tokens {
+                    explicit.remove(token);
+                    auto.remove(token);
+                }
+            }
+            _ => {}
+        }
+
+        // Free tick history for instruments nobody is watching any more
+        if let Command::UnsubscribeFromTicks { instrument_tokens } = &command {
+            for token in instrument_tokens {
+                if !self.has_open_position(*token) {
+                    self.tick_history.remove(token);
+                }
+                self.market_depth.remove(token);
+            }
+        }
+
+        if self.kill_switch
+            && matches!(
+                command,
+                Command::PlaceOrder { .. }
+                    | Command::ModifyOrder { .. }
+                    | Command::SquareOffByTag { .. }
+            )
+        {
+            self.add_log(
+                LogLevel::Warning,
+                "Order not sent: the kill switch is on".to_string(),
+                Some("orders".to_string()),
+            );
+            return;
+        }
+
+        if let Command::PlaceOrder { details } = &mut command {
+            self.orders_in_flight.write().start(details);
+        }
+
+        // Reject invalid orders locally instead of waiting for an exchange rejection,
+        // reporting them the same way
+        let validation = match &command {
+            Command::PlaceOrder { details } => {
+                self.validate_order(details).map_err(|e| (details, e))
+            }
+            Command::ModifyOrder { order_id, details } => self
+                .validate_modification(order_id, details)
+                .map_err(|e| (details, e)),
+            _ => Ok(()),
+        };
+        if let Err((details, e)) = validation {
+            self.local_events.lock().push_back(AppEvent::OrderRejected {
+                order_id: None,
+                tradingsymbol: details.tradingsymbol.clone(),
+                reason: e.to_string(),
+                request_id: details.request_id,
+            });
+            return;
+        }
+
+        if let Some(journal) = &self.journal {
+            match &command {
+                Command::PlaceOrder { details } => journal.record(JournalRecord::OrderRequest {
+                    action: "place",
+                    order_id: None,
+                    request: Box::new(details.clone()),
+                }),
+                Command::ModifyOrder { order_id, details } => {
+                    journal.record(JournalRecord::OrderRequest {
+                        action: "modify",
+                        order_id: Some(order_id.clone()),
+                        request: Box::new(details.clone()),
+                    })
+                }
+                Command::CancelOrder { order_id, .. } => journal.record(JournalRecord::Event {
+                    kind: "cancel_requested",
+                    order_id: Some(order_id.clone()),
+                    message: "Cancellation requested".to_string(),
+                }),
+                _ => {}
+            }
+        }
+
+        self.dispatch(command);
+    }
+
+    /// Hand a command to the worker, or both workers, that consume it
+    fn dispatch(&self, command: Command) {
+        // A send only fails once the worker has stopped, so the undelivered command
+        // isn't worth carrying back
+        let send_api =
+            |sender: &mpsc::UnboundedSender<Command>, command| sender.send(command).map_err(drop);
+        let send_websocket = |command| self.websocket_command_sender.send(command).map_err(drop);
+        let result = match command.route() {
+            CommandRoute::Api => send_api(&self.command_sender, command),
+            CommandRoute::ApiPriority => send_api(&self.priority_command_sender, command),
+            CommandRoute::WebSocket => send_websocket(command),
+            CommandRoute::Both => {
+                // A new token must not be overtaken by an order sent after it, while
+                // Shutdown waits for the fetches queued before it
+                let api = match command {
+                    Command::UpdateAccessToken { .. } => &self.priority_command_sender,
+                    _ => &self.command_sender,
+                };
+                send_api(api, command.clone()).and_then(|()| send_websocket(command))
+            }
+        };
+        if result.is_err() {
+            self.add_log(
+                LogLevel::Error,
+                "Failed to send command: the worker has stopped".to_string(),
+                Some("state".to_string()),
+            );
+        }
+    }
+
+    /// Write positions, today's orders or trades to the command's path
+    fn apply_export_command(&self, command: Command) {
+        let result = match &command {
+            Command::ExportPositions { path } => {
+                let mut positions: Vec<Position> = self
+                    .positions
+                    .iter()
+                    .map(|entry| entry.value().clone())
+                    .collect();
+                positions.sort_by(|a, b| {
+                    (&a.exchange, &a.tradingsymbol).cmp(&(&b.exchange, &b.tradingsymbol))
+                });
+                export::export_positions(&positions, path, None).map(|rows| (path, rows))
+            }
+            Command::ExportOrders { path } => {
+                let orders = self.todays_orders();
+                let orders: Vec<Order> = export::sorted_by_time(&orders)
+                    .into_iter()
+                    .cloned()
+                    .collect();
+                export::export_orders(&orders, path, None).map(|rows| (path, rows))
+            }
+            Command::ExportTrades { path } => {
+                export::export_trades(&self.todays_orders(), path).map(|rows| (path, rows))
+            }
+            _ => return,
+        };
+
+        match result {
+            Ok((path, rows)) => self.add_log(
+                LogLevel::Info,
+                format!("Exported {} rows to {}", rows, path.display()),
+                Some("export".to_string()),
+            ),
+            Err(e) => self.add_log(
+                LogLevel::Error,
+                format!("Export failed: {}", e),
+                Some("export".to_string()),
+            ),
+        }
+    }
+
+    /// Write the rows a table currently shows, as filtered and sorted on screen
+    /// The filter and export time go in a header comment; the outcome is toasted
+    pub fn export_positions_view(&mut self, positions: &[Position], filter: &str, path: &Path) {
+        let comment = export_comment("positions", filter);
+        let result = export::export_positions(positions, path, Some(&comment));
+        self.finish_view_export(path, result);
+    }
+
+    /// Orders counterpart of [`AppState::export_positions_view`]
+    pub fn export_orders_view(&mut self, orders: &[Order], filter: &str, path: &Path) {
+        let comment = export_comment("orders", filter);
+        let result = export::export_orders(orders, path, Some(&comment));
+        self.finish_view_export(path, result);
+    }
+
+    /// Logs counterpart of [`AppState::export_positions_view`], written as plain text lines
+    pub fn export_logs_view(&mut self, entries: &[LogEntry], filter: &str, path: &Path) {
+        let comment = export_comment("logs", filter);
+        let result = export::export_logs(entries, path, &comment);
+        self.finish_view_export(path, result);
+    }
+
+    fn finish_view_export(&mut self, path: &Path, result: anyhow::Result<usize>) {
+        match result {
+            Ok(rows) => {
+                let message = format!("Exported {} rows to {}", rows, path.display());
+                self.add_log(LogLevel::Info, message.clone(), Some("export".to_string()));
+                self.push_toast(LogLevel::Info, message);
+            }
+            Err(e) => {
+                let message = format!("Export failed: {}", e);
+                self.add_log(LogLevel::Error, message.clone(), Some("export".to_string()));
+                self.push_toast(LogLevel::Error, message);
+            }
+        }
+    }
+
+    /// Orders placed on the current IST trading day
+    fn todays_orders(&self) -> Vec<Order> {
+        let today = ist_now().date_naive();
+        self.orders
+            .iter()
+            .filter(|entry| {
+                entry
+                    .value()
+                    .order_timestamp
+                    .with_timezone(&Kolkata)
+                    .date_naive()
+                    == today
+            })
+            .map(|entry| entry.value().clone())
+            .collect()
+    }
+
+    /// Open the order journal when a journal_path is configured
+    pub fn open_journal(&mut self) {
+        let Some(path) = self.config.app.journal_path.clone() else {
+            return;
+        };
+
+        match Journal::open(&path) {
+            Ok(journal) => {
+                self.journal = Some(journal);
+                self.add_log(
+                    LogLevel::Info,
+                    format!("Journaling order activity to {}", path),
+                    Some("journal".to_string()),
+                );
+            }
+            Err(e) => {
+                self.add_log(
+                    LogLevel::Error,
+                    format!("Failed to open order journal {}: {}", path, e),
+                    Some("journal".to_string()),
+                );
+            }
+        }
+    }
+
+    /// Open the history store when a history_path is configured, and read back the days
+    /// before today for the P&L view
+    pub fn open_history(&mut self, event_sender: EventSender) {
+        let Some(path) = self.config.app.history_path.clone() else {
+            return;
+        };
+
+        match HistoryStore::open(&path, event_sender) {
+            Ok(history) => {
+                self.history = Some(history);
+                self.add_log(
+                    LogLevel::Info,
+                    format!("Keeping candles and daily P&L in {}", path),
+                    Some("history".to_string()),
+                );
+            }
+            Err(e) => {
+                self.add_log(
+                    LogLevel::Error,
+                    format!("Failed to open history store {}: {}", path, e),
+                    Some("history".to_string()),
+                );
+                return;
+            }
+        }
+
+        let today = ist_now().date_naive();
+        let since = today - chrono::Days::new(DAILY_HISTORY_DAYS);
+        match history::daily_summaries(&path, since) {
+            Ok(mut days) => {
+                days.retain(|day| day.date < today);
+                self.daily_history = days;
+            }
+            Err(e) => {
+                self.add_log(
+                    LogLevel::Error,
+                    format!("Failed to read daily P&L from {}: {}", path, e),
+                    Some("history".to_string()),
+                );
+            }
+        }
+    }
+
+    /// Queue journal records for order lifecycle events
+    fn journal_event(&self, event: &AppEvent) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+
+        let record = match event {
+            AppEvent::OrderPlaced { order_id, .. } => JournalRecord::Event {
+                kind: "placed",
+                order_id: Some(order_id.clone()),
+                message: "Order accepted by broker".to_string(),
+            },
+            AppEvent::OrderModified { order_id } => JournalRecord::Event {
+                kind: "modified",
+                order_id: Some(order_id.clone()),
+                message: "Order modification accepted".to_string(),
+            },
+            AppEvent::OrderCancelled { order_id } => JournalRecord::Event {
+                kind: "cancelled",
+                order_id: Some(order_id.clone()),
+                message: "Order cancelled".to_string(),
+            },
+            AppEvent::OrderFilled {
+                order_id,
+                fill_price,
+                fill_quantity,
+            } => JournalRecord::Fill {
+                order_id: order_id.clone(),
+                fill_price: *fill_price,
+                fill_quantity: *fill_quantity,
+            },
+            AppEvent::OrderRejected {
+                order_id,
+                tradingsymbol,
+                reason,
+                ..
+            } => JournalRecord::Event {
+                kind: "rejected",
+                order_id: order_id.clone(),
+                message: format!("{}: {}", tradingsymbol, reason),
+            },
+            AppEvent::OrderPlaceFailed {
+                tradingsymbol,
+                error,
+                ..
+            } => JournalRecord::Event {
+                kind: "place_failed",
+                order_id: None,
+                message: format!("{}: {}", tradingsymbol, error),
+            },
+            _ => return,
+        };
+
+        journal.record(record);
+    }
+
+    /// Update channel diagnostics after a frame's event processing
+    pub fn record_frame_metrics(&self, depth: usize, process_time: Duration) {
+        let threshold = self.config.app.event_backlog_warn_threshold;
+        let sustained = self
+            .metrics
+            .write()
+            .record_frame(depth, process_time, threshold);
+
+        if let Some(sustained) = sustained {
+            self.add_log(
+                LogLevel::Warning,
+                format!(
+                    "Event backlog of {} exceeds {} for {:.1}s, UI is falling behind workers",
+                    depth,
+                    threshold,
+                    sustained.as_secs_f64()
+                ),
+                Some("app".to_string()),
+            );
+        }
+    }
+
+    /// Worker and locally raised events waiting to be applied, market data included
+    pub fn pending_events(&self) -> usize {
+        self.local_events.lock().len() + self.event_receiver.len() + self.tick_receiver.len()
+    }
+
+    /// Apply pending events from worker threads, up to performance.max_event_batch with
+    /// everything but market data first
+    /// Returns true when anything besides market data arrived, or events were left over
+    pub fn process_events(&mut self) -> bool {
+        let broadcaster = self
+            .broadcaster
+            .clone()
+            .filter(|broadcaster| broadcaster.has_clients());
+        let mut ticks = Vec::new();
+
+        let mut changed = false;
+        let mut budget = self.config.performance.max_event_batch;
+        while budget > 0 {
+            let local = self.local_events.get_mut().pop_front();
+            let Some(event) = local.or_else(|| {
+                self.event_receiver
+                    .try_recv()
+                    .or_else(|_| self.tick_receiver.try_recv())
+                    .ok()
+            }) else {
+                break;
+            };
+            budget -= 1;
+            changed |= !event.is_market_data();
+            if let Some(broadcaster) = &broadcaster {
+                self.broadcast_event(broadcaster, &event, &mut ticks);
+            }
+            self.handle_event(event);
+        }
+        if let Some(broadcaster) = broadcaster.as_ref().filter(|_| !ticks.is_empty()) {
+            broadcaster.publish(Broadcast::Ticks(ticks));
+        }
+        // Draw the next frame straight away to apply the rest
+        changed |= budget == 0 && self.pending_events() > 0;
+        self.sync_positions_snapshot();
+
+        let now = Utc::now();
+        if !self.positions.is_empty() && self.pnl_history.read().is_due(now) {
+            let pnl = self.calculate_total_pnl();
+            self.pnl_history.write().record(now, &pnl);
+            if let Some(history) = &self.history {
+                history.record(HistoryRecord::DailyPnl {
+                    date: now.with_timezone(&Kolkata).date_naive(),
+                    realized: pnl.realized,
+                    unrealized: pnl.unrealized,
+                });
+            }
+            if let Some(broadcaster) = &broadcaster {
+                broadcaster.publish(Broadcast::Pnl(serde_json::json!({
+                    "type": "pnl",
+                    "timestamp": now,
+                    "realized": pnl.realized,
+                    "unrealized": pnl.unrealized,
+                    "total": pnl.total,
+                })));
+            }
+        }
+        self.check_token_expiry(now);
+        self.check_instruments_refresh(now);
+
+        changed
+    }
+
+    /// Publish what external tools follow of an event before it is applied: ticks join
+    /// the pass's batch, order events go out as they are, and a refresh sends the orders
+    /// that changed since the last one
+    fn broadcast_event(
+        &self,
+        broadcaster: &Broadcaster,
+        event: &AppEvent,
+        ticks: &mut Vec<TickMessage>,
+    ) {
+        let order_event = |name: &str, order_id: Option<&String>, details: serde_json::Value| {
+            let instrument_token = order_id
+                .and_then(|order_id| self.orders.get(order_id))
+                .map(|order| order.instrument_token);
+            let mut message = serde_json::json!({
+                "type": "orders",
+                "event": name,
+                "order_id": order_id,
+                "instrument_token": instrument_token,
+            });
+            if let (Some(message), serde_json::Value::Object(details)) =
+                (message.as_object_mut(), details)
+            {
+                message.extend(details);
+            }
+            broadcaster.publish(Broadcast::Order {
+                instrument_token,
+                message,
+            });
+        };
+
+        match event {
+            AppEvent::TickUpdate {
+                instrument_token,
+                last_price,
+                last_quantity,
+                volume,
+                timestamp,
+                ..
+            } => ticks.push(TickMessage {
+                instrument_token: *instrument_token,
+                last_price: *last_price,
+                last_quantity: *last_quantity,
+                volume: *volume,
+                timestamp: *timestamp,
+            }),
+            AppEvent::OrdersUpdated(orders) => {
+                for order in orders {
+                    let unchanged = self.orders.get(&order.order_id).is_some_and(|known| {
+                        known.status == order.status
+                            && known.quantity == order.quantity
+                            && known.filled_quantity == order.filled_quantity
+                            && known.pending_quantity == order.pending_quantity
+                            && known.price == order.price
+                            && known.trigger_price == order.trigger_price
+                    });
+                    if !unchanged {
+                        broadcaster.publish(Broadcast::Order {
+                            instrument_token: Some(order.instrument_token),
+                            message: serde_json::json!({
+                                "type": "orders",
+                                "event": "update",
+                                "order": order,
+                            }),
+                        });
+                    }
+                }
+            }
+            AppEvent::OrderPlaced { order_id, .. } => {
+                order_event("placed", Some(order_id), serde_json::Value::Null)
+            }
+            AppEvent::OrderModified { order_id } => {
+                order_event("modified", Some(order_id), serde_json::Value::Null)
+            }
+            AppEvent::OrderCancelled { order_id } => {
+                order_event("cancelled", Some(order_id), serde_json::Value::Null)
+            }
+            AppEvent::OrderFilled {
+                order_id,
+                fill_price,
+                fill_quantity,
+            } => order_event(
+                "filled",
+                Some(order_id),
+                serde_json::json!({ "fill_price": fill_price, "fill_quantity": fill_quantity }),
+            ),
+            AppEvent::OrderRejected {
+                order_id,
+                tradingsymbol,
+                reason,
+                ..
+            } => order_event(
+                "rejected",
+                order_id.as_ref(),
+                serde_json::json!({ "tradingsymbol": tradingsymbol, "reason": reason }),
+            ),
+            AppEvent::OrderPlaceFailed {
+                tradingsymbol,
+                error,
+                ..
+            } => order_event(
+                "place_failed",
+                None,
+                serde_json::json!({ "tradingsymbol": tradingsymbol, "error": error }),
+            ),
+            _ => {}
+        }
+    }
+
+    /// When the current access token stops working, if its issue time is known
+    pub fn token_expiry(&self) -> Option<DateTime<Utc>> {
+        self.config.zerodha.access_token_issued_at.map(token_expiry)
+    }
+
+    /// Once the token is past its 06:00 IST expiry, health-check it so the re-auth banner
+    /// shows before an order fails, and warn once a day at token_reminder_time
+    fn check_token_expiry(&mut self, now: DateTime<Utc>) {
+        if self.token_expiry().is_none_or(|expiry| now < expiry) {
+            return;
+        }
+
+        if !self.token_expiry_checked
+            && *self.connection_state.read() != ConnectionState::AuthExpired
+        {
+            self.token_expiry_checked = true;
+            self.add_log(
+                LogLevel::Info,
+                "Access token is past its 06:00 IST expiry; checking it".to_string(),
+                Some("auth".to_string()),
+            );
+            self.send_command(Command::FetchUserProfile);
+        }
+
+        // Nothing to refresh for on a day the market stays shut
+        let ist = now.with_timezone(&Kolkata);
+        let today = ist.date_naive();
+        let due = self
+            .config
+            .app
+            .reminder_time()
+            .is_some_and(|time| ist.time() >= time);
+        let closed_today = matches!(
+            MarketStatus::at("NSE", ist),
+            MarketStatus::Closed(ClosedReason::Weekend | ClosedReason::Holiday(_))
+        );
+        if due && !closed_today && self.token_reminded_on != Some(today) {
+            self.token_reminded_on = Some(today);
+            self.notify(
+                LogLevel::Warning,
+                "The access token is from a previous day; re-authenticate before trading"
+                    .to_string(),
+                None,
+            );
+        }
+    }
+
+    /// Load the configured exchanges' instruments at startup, then again on the first
+    /// check after INSTRUMENTS_REFRESH_TIME each day
+    fn check_instruments_refresh(&mut self, now: DateTime<Utc>) {
+        if self.config.app.instrument_exchanges.is_empty() {
+            return;
+        }
+        let ist = now.with_timezone(&Kolkata);
+        let mut released_on = ist.date_naive();
+        if ist.time() < INSTRUMENTS_REFRESH_TIME {
+            released_on = released_on.pred_opt().unwrap_or(released_on);
+        }
+        let released = released_on
+            .and_time(INSTRUMENTS_REFRESH_TIME)
+            .and_local_timezone(Kolkata)
+            .single()
+            .map(|released| released.with_timezone(&Utc));
+        if self
+            .instruments_requested_at
+            .is_none_or(|requested| released.is_some_and(|released| requested < released))
+        {
+            self.refresh_instruments();
+        }
+    }
+
+    /// Fetch the instrument master of the configured exchanges, NSE when none are
+    /// configured; changes since the last load are summarized as they arrive
+    pub fn refresh_instruments(&mut self) {
+        self.instruments_requested_at = Some(Utc::now());
+        let mut exchanges = self.config.app.instrument_exchanges.clone();
+        if exchanges.is_empty() {
+            exchanges.push("NSE".to_string());
+        }
+        self.add_log(
+            LogLevel::Info,
+            format!("Loading {} instruments...", exchanges.join(", ")),
+            Some("instruments".to_string()),
+        );
+        for exchange in exchanges {
+            self.send_command(Command::FetchInstruments { exchange });
+        }
+    }
+
+    /// Replace one exchange's instruments with a fresh master, keeping expired ones that
+    /// are still held, and summarize what changed
+    fn apply_instruments(&mut self, exchange: String, instruments: Vec<Instrument>) {
+        let previous: HashSet<u32> = self
+            .instruments
+            .iter()
+            .filter(|instrument| instrument.exchange == exchange)
+            .map(|instrument| instrument.instrument_token)
+            .collect();
+        let fresh: HashSet<u32> = instruments
+            .iter()
+            .map(|instrument| instrument.instrument_token)
+            .collect();
+        let added = fresh.difference(&previous).count();
+
+        let mut removed = 0;
+        for token in previous.difference(&fresh) {
+            if self.has_open_position(*token) {
+                let symbol = self.symbol_for_token(*token);
+                self.add_log(
+                    LogLevel::Warning,
+                    format!(
+                        "{} is gone from the {} instrument master but still has an open position; keeping it",
+                        symbol, exchange
+                    ),
+                    Some("instruments".to_string()),
+                );
+            } else {
+                self.instruments.remove(token);
+                removed += 1;
+            }
+        }
+        for instrument in instruments {
+            self.instruments
+                .insert(instrument.instrument_token, instrument);
+        }
+
+        let index = InstrumentIndex::build(&self.instruments);
+        let count = index.len();
+        *self.instrument_index.write() = index;
+        // Search results were taken from the old set
+        self.ui_input.instrument_search.searched = None;
+
+        self.add_log(
+            LogLevel::Info,
+            format!("Indexed {} instruments for symbol search", count),
+            Some("instruments".to_string()),
+        );
+        if previous.is_empty() {
+            self.add_log(
+                LogLevel::Info,
+                format!("Loaded {} {} instruments", fresh.len(), exchange),
+                Some("instruments".to_string()),
+            );
+        } else if added > 0 || removed > 0 {
+            self.notify(
+                LogLevel::Info,
+                format!(
+                    "{} new {} instruments, {} expired removed",
+                    added, exchange, removed
+                ),
+                Some(NotificationTarget::view(AppView::Instruments)),
+            );
+        } else {
+            self.add_log(
+                LogLevel::Info,
+                format!("{} instruments unchanged", exchange),
+                Some("instruments".to_string()),
+            );
+        }
+    }
+
+    /// Date the token in memory once config.toml holds the same date, so the file
+    /// watcher's echo is skipped and an open Settings draft does not turn dirty
+    fn track_saved_token_issued_at(&mut self, issued_at: DateTime<Utc>) {
+        self.loaded_config.zerodha.access_token_issued_at = Some(issued_at);
+        if let Some(form) = &mut self.ui_input.settings_form {
+            form.draft.zerodha.access_token_issued_at = Some(issued_at);
+        }
+    }
+
+    /// Handle individual events from worker threads
+    fn handle_event(&mut self, event: AppEvent) {
+        self.journal_event(&event);
+
+        match event {
+            AppEvent::PositionsUpdated(positions) => {
+                self.positions.clear();
+                for position in positions {
+                    self.positions.insert(position.key(), position);
+                }
+                self.positions_gen.bump();
+                self.positions_refreshed_at = Some(Instant::now());
+
+                self.add_log(
+                    LogLevel::Info,
+                    format!("Updated {} positions", self.positions.len()),
+                    Some("positions".to_string()),
+                );
+
+                if self.config.app.auto_subscribe_positions {
+                    self.sync_position_subscriptions();
+                }
+            }
+
+            AppEvent::OrdersUpdated(orders) => {
+                // Update orders, preserving existing ones not in the update
+                let mut inspected_changed = false;
+                for order in orders {
+                    if self.ui_input.inspected_order_id.as_ref() == Some(&order.order_id) {
+                        inspected_changed = self.orders.get(&order.order_id).is_none_or(|old| {
+                            old.status != order.status
+                                || old.filled_quantity != order.filled_quantity
+                                || old.price != order.price
+                                || old.quantity != order.quantity
+                        });
+                    }
+                    self.orders.insert(order.order_id.clone(), order);
+                }
+                self.orders_gen.bump();
+                self.orders_refreshed_at = Some(Instant::now());
+
+                // Keep an open detail drawer in step with the order
+                if inspected_changed {
+                    if let Some(order_id) = self.ui_input.inspected_order_id.clone() {
+                        self.send_command(Command::FetchOrderHistory { order_id });
+                    }
+                }
+
+                let orders: Vec<Order> = self
+                    .orders
+                    .iter()
+                    .map(|entry| entry.value().clone())
+                    .collect();
+                *self.tag_positions.write() = aggregate_tag_positions(&orders);
+
+                self.add_log(
+                    LogLevel::Info,
+                    format!("Updated orders, total: {}", self.orders.len()),
+                    Some("orders".to_string()),
+                );
+            }
+
+            AppEvent::OrderHistoryLoaded { order_id, history } => {
+                self.order_history.insert(order_id, history);
+            }
+
+            AppEvent::TradesUpdated(mut trades) => {
+                trades.sort_by_key(|trade| trade.fill_timestamp);
+                let count = trades.len();
+                *self.trades.write() = trades;
+
+                self.add_log(
+                    LogLevel::Info,
+                    format!("Updated tradebook, total: {}", count),
+                    Some("orders".to_string()),
+                );
+            }
+
+            AppEvent::TickUpdate {
+                instrument_token,
+                last_price,
+                last_quantity,
+                volume,
+                timestamp,
+                exchange_timestamp,
+            } => {
+                // Update position prices for real-time PnL calculation
+                if self.update_position_price(instrument_token, last_price) {
+                    let patchable = self.positions_snapshot.is_current(&self.positions_gen);
+                    self.positions_gen.bump();
+                    if patchable {
+                        self.positions_snapshot.reprice(
+                            instrument_token,
+                            last_price,
+                            self.positions_gen.get(),
+                        );
+                    }
+                }
+                self.ticks_gen.bump();
+
+                // Update tick data
+                if let Some(mut tick_data) = self.tick_data.get_mut(&instrument_token) {
+                    tick_data.last_price = last_price;
+                    tick_data.last_quantity = last_quantity;
+                    tick_data.volume = volume;
+                    tick_data.timestamp_nanos = timestamp.timestamp_nanos();
+                } else {
+                    // Create new tick data entry
+                    let tick_data = TickData {
+                        instrument_token,
+                        last_price,
+                        last_quantity,
+                        average_price: last_price,
+                        volume,
+                        buy_quantity: 0,
+                        sell_quantity: 0,
+                        ohlc: OHLC {
+                            open: last_price,
+                            high: last_price,
+                            low: last_price,
+                            close: last_price,
+                        },
+                        timestamp_nanos: timestamp.timestamp_nanos(),
+                    };
+                    self.tick_data.insert(instrument_token, tick_data);
+                }
+
+                self.record_tick_history(
+                    instrument_token,
+                    TickSample {
+                        timestamp,
+                        price: last_price,
+                        quantity: last_quantity,
+                    },
+                );
+                let closed = self
+                    .candles
+                    .entry(instrument_token)
+                    .or_default()
+                    .record_tick(timestamp, last_price, volume);
+                if let (Some(history), Some(candle)) = (&self.history, closed) {
+                    history.record(HistoryRecord::Candles {
+                        instrument_token,
+                        candles: vec![candle],
+                    });
+                }
+
+                // Update metrics
+                {
+                    let mut metrics = self.metrics.write();
+                    metrics.ticks_processed += 1;
+                    metrics.last_tick_timestamp = Some(timestamp);
+                    let now = Utc::now();
+                    // Measured from the exchange's stamp when there is one, with the local
+                    // clock moved onto true time so drift doesn't show up as latency
+                    let latency = match exchange_timestamp {
+                        Some(exchange_timestamp) => {
+                            let offset = metrics
+                                .clock_offset
+                                .as_ref()
+                                .map_or(TimeDelta::zero(), |clock| clock.offset);
+                            now + offset - exchange_timestamp
+                        }
+                        None => now.signed_duration_since(timestamp),
+                    };
+                    metrics
+                        .tick_rate
+                        .record(now, latency.num_microseconds().unwrap_or(0) as f64 / 1000.0);
+                }
+
+                // Evaluate price alerts for this instrument
+                let triggered = self.alerts.write().evaluate(instrument_token, last_price);
+                if !triggered.is_empty() {
+                    // One-shot alerts changed state, keep the file in sync
+                    self.save_alerts();
+                    for alert in triggered {
+                        self.handle_event(AppEvent::AlertTriggered {
+                            alert,
+                            price: last_price,
+                        });
+                    }
+                }
+            }
+
+            AppEvent::AlertTriggered { alert, price } => {
+                self.sounds.play(SoundEvent::Alert, &self.config.app);
+                let condition = match alert.condition {
+                    AlertCondition::Above => "above",
+                    AlertCondition::Below => "below",
+                    AlertCondition::Crosses => "crossed",
+                };
+                let mut message = format!(
+                    "Alert: {} {} ₹{:.2} (last ₹{:.2})",
+                    self.instrument_label(alert.instrument_token),
+                    condition,
+                    alert.threshold,
+                    price
+                );
+                if let Some(note) = &alert.note {
+                    message.push_str(&format!(" - {}", note));
+                }
+
+                self.add_log(
+                    LogLevel::Warning,
+                    message.clone(),
+                    Some("alerts".to_string()),
+                );
+                self.notify(
+                    LogLevel::Warning,
+                    message.clone(),
+                    Some(NotificationTarget::instrument(
+                        AppView::Charts,
+                        alert.instrument_token,
+                    )),
+                );
+
+                if self.config.app.os_notifications {
+                    // Desktop notification APIs can block, keep them off the UI thread
+                    std::thread::spawn(move || {
+                        let _ = notify_rust::Notification::new()
+                            .summary("Price Alert")
+                            .body(&message)
+                            .show();
+                    });
+                }
+            }
+
+            AppEvent::Notification {
+                level,
+                message,
+                module,
+            } => {
+                self.add_log(level, message, module);
+            }
+
+            AppEvent::LogRecord {
+                level,
+                message,
+                module,
+            } => {
+                self.push_log(LogEntry::new(
+                    level,
+                    redact::redact_params(&message),
+                    module,
+                ));
+            }
+
+            AppEvent::TagSquaredOff {
+                tag,
+                orders_placed,
+                failures,
+            } => {
+                let level = if failures.is_empty() {
+                    LogLevel::Info
+                } else {
+                    LogLevel::Warning
+                };
+                let mut message = format!(
+                    "Square-off for tag '{}': {} orders placed, {} failed",
+                    tag,
+                    orders_placed,
+                    failures.len()
+                );
+                for failure in &failures {
+                    message.push_str(&format!("\n  {}", failure));
+                }
+                self.add_log(level, message, Some("orders".to_string()));
+            }
+
+            AppEvent::StrategyCommand { strategy, command } => {
+                self.apply_strategy_command(&strategy, command);
+            }
+
+            AppEvent::DepthUpdate {
+                instrument_token,
+                depth,
+            } => {
+                self.market_depth.insert(instrument_token, depth);
+            }
+
+            AppEvent::ApiRequestTimed(elapsed) => {
+                self.metrics.write().api_latency.record(elapsed);
+            }
+
+            AppEvent::ApiCircuitChanged { category, state } => {
+                let (level, message) = match state {
+                    CircuitState::Open { retry_at } => (
+                        LogLevel::Warning,
+                        format!(
+                            "Kite {} requests keep failing; pausing them until {}",
+                            category.label(),
+                            retry_at.with_timezone(&chrono::Local).format("%H:%M:%S")
+                        ),
+                    ),
+                    CircuitState::HalfOpen => (
+                        LogLevel::Info,
+                        format!("Kite {} requests: trying one again", category.label()),
+                    ),
+                    CircuitState::Closed => (
+                        LogLevel::Info,
+                        format!("Kite {} requests are working again", category.label()),
+                    ),
+                };
+                {
+                    let mut metrics = self.metrics.write();
+                    if state == CircuitState::Closed {
+                        metrics.api_circuits.remove(&category);
+                    } else {
+                        metrics.api_circuits.insert(category, state);
+                    }
+                }
+                self.add_log(level, message, Some("api_handler".to_string()));
+            }
+
+            AppEvent::ClockOffsetMeasured(Ok(clock)) => {
+                let was_drifting = self
+                    .metrics
+                    .read()
+                    .clock_offset
+                    .as_ref()
+                    .is_some_and(ClockOffset::is_drifting);
+                let message = format!(
+                    "Local clock is {} ms {} than {} (round trip {} ms)",
+                    clock.offset.num_milliseconds().abs(),
+                    if clock.offset > TimeDelta::zero() {
+                        "slower"
+                    } else {
+                        "faster"
+                    },
+                    clock.server,
+                    clock.round_trip.num_milliseconds()
+                );
+                if clock.is_drifting() && !was_drifting {
+                    self.add_log(
+                        LogLevel::Warning,
+                        message.clone(),
+                        Some("clock".to_string()),
+                    );
+                    self.notify(
+                        LogLevel::Warning,
+                        format!("{}; tick latency is corrected for it", message),
+                        Some(NotificationTarget::view(AppView::Logs)),
+                    );
+                } else {
+                    self.add_log(LogLevel::Debug, message, Some("clock".to_string()));
+                }
+                self.metrics.write().clock_offset = Some(clock);
+            }
+
+            AppEvent::ClockOffsetMeasured(Err(e)) => {
+                self.add_log(
+                    LogLevel::Warning,
+                    format!("Clock drift check failed: {}", e),
+                    Some("clock".to_string()),
+                );
+            }
+
+            AppEvent::WebSocketConnected => {
+                *self.connection_state.write() = ConnectionState::Connected;
+            }
+
+            // An expired token stays reported until a connection succeeds
+            AppEvent::WebSocketReconnecting { attempt } => {
+                self.metrics.write().websocket_reconnections += 1;
+                let mut state = self.connection_state.write();
+                if *state != ConnectionState::AuthExpired {
+                    *state = ConnectionState::Reconnecting { attempt };
+                }
+            }
+
+            AppEvent::WebSocketDisconnected => {
+                let was_connected = {
+                    let mut state = self.connection_state.write();
+                    let was_connected = *state == ConnectionState::Connected;
+                    if *state != ConnectionState::AuthExpired {
+                        *state = ConnectionState::Disconnected;
+                    }
+                    was_connected
+                };
+
+                // Only the drop itself, not every failed reconnect after it
+                if was_connected {
+                    self.notify_remote(AlertKind::Disconnected, "Market data feed disconnected");
+                    self.notify(
+                        LogLevel::Warning,
+                        "Market data feed disconnected".to_string(),
+                        Some(NotificationTarget::view(AppView::Logs)),
+                    );
+                }
+            }
+
+            AppEvent::AccessTokenRejected => {
+                let previous = std::mem::replace(
+                    &mut *self.connection_state.write(),
+                    ConnectionState::AuthExpired,
+                );
+                let message = "Access token rejected; re-authenticate from the banner".to_string();
+                self.notify_remote(AlertKind::AuthExpired, &message);
+                self.add_log(
+                    LogLevel::Error,
+                    message.clone(),
+                    Some("websocket".to_string()),
+                );
+                // Every worker reports the same rejection; one dialog is enough
+                if previous != ConnectionState::AuthExpired {
+                    self.raise_critical(message.clone(), Some("websocket".to_string()));
+                }
+                self.notify(
+                    LogLevel::Error,
+                    message,
+                    Some(NotificationTarget::view(AppView::Logs)),
+                );
+            }
+
+            AppEvent::MarginsUpdated(margins) => {
+                *self.margins.write() = Some((Utc::now(), margins));
+            }
+
+            AppEvent::OrderMarginPreviewed { preview_id, result } => {
+                let preview = &mut self.ui_input.margin_preview;
+                // A newer request supersedes answers to earlier form inputs
+                if preview_id == preview.requested_id {
+                    preview.result = Some(result);
+                }
+            }
+
+            AppEvent::ExitChargesEstimated {
+                instrument_token,
+                quantity,
+                result,
+            } => {
+                // Drop answers for a position that has since changed size
+                if let Some(charges) = self.ui_input.exit_charges.as_mut().filter(|charges| {
+                    charges.instrument_token == instrument_token && charges.quantity == quantity
+                }) {
+                    charges.result = Some(result);
+                }
+            }
+
+            AppEvent::HistoricalCandlesLoaded {
+                instrument_token,
+                candles,
+            } => {
+                let count = candles.len();
+                if let Some(history) = &self.history {
+                    // The current minute's bar is still forming
+                    let now = Utc::now();
+                    let closed: Vec<Candle> = candles
+                        .iter()
+                        .filter(|candle| now - candle.start >= TimeDelta::minutes(1))
+                        .copied()
+                        .collect();
+                    if !closed.is_empty() {
+                        history.record(HistoryRecord::Candles {
+                            instrument_token,
+                            candles: closed,
+                        });
+                    }
+                }
+                self.candles
+                    .entry(instrument_token)
+                    .or_default()
+                    .merge_history(candles);
+
+                self.add_log(
+                    LogLevel::Debug,
+                    format!(
+                        "Loaded {} historical candles for {}",
+                        count,
+                        self.instrument_label(instrument_token)
+                    ),
+                    Some("charts".to_string()),
+                );
+            }
+
+            AppEvent::StoredCandlesLoaded {
+                instrument_token,
+                candles,
+            } => {
+                self.candles
+                    .entry(instrument_token)
+                    .or_default()
+                    .merge_history(candles);
+            }
+
+            AppEvent::InstrumentsUpdated {
+                exchange,
+                instruments,
+            } => {
+                self.apply_instruments(exchange, instruments);
+            }
+
+            AppEvent::OrderFilled {
+                order_id,
+                fill_price,
+                fill_quantity,
+            } => {
+                let description = self
+                    .orders
+                    .get(&order_id)
+                    .map(|order| format!("{} {}", order.transaction_type, order.tradingsymbol))
+                    .unwrap_or_else(|| format!("order {}", order_id));
+
+                // Shown until the next tradebook fetch replaces it with the exchange's own rows
+                if let Some(order) = self.orders.get(&order_id) {
+                    let trade = Trade {
+                        trade_id: String::new(),
+                        order_id: order_id.clone(),
+                        exchange_order_id: Some(order.exchange_order_id.clone())
+                            .filter(|id| !id.is_empty()),
+                        tradingsymbol: order.tradingsymbol.clone(),
+                        exchange: order.exchange.clone(),
+                        instrument_token: order.instrument_token,
+                        product: order.product.clone(),
+                        transaction_type: order.transaction_type.clone(),
+                        quantity: fill_quantity,
+                        average_price: fill_price,
+                        fill_timestamp: Utc::now(),
+                    };
+                    if let Some(history) = &self.history {
+                        history.record(HistoryRecord::Trade {
+                            trade: Box::new(trade.clone()),
+                            tag: order.tag.clone(),
+                        });
+                    }
+                    self.trades.write().push(trade);
+                }
+
+                self.pnl_history.write().record_fill(FillMarker {
+                    timestamp: Utc::now(),
+                    label: format!("{} {} @ ₹{:.2}", description, fill_quantity, fill_price),
+                    is_buy: self
+                        .orders
+                        .get(&order_id)
+                        .is_some_and(|order| order.transaction_type == "BUY"),
+                });
+
+                self.sounds.play(SoundEvent::Fill, &self.config.app);
+
+                let message = format!(
+                    "{}: filled {} @ ₹{:.2}",
+                    description, fill_quantity, fill_price
+                );
+                self.notify_remote(AlertKind::Fill, &message);
+                self.add_log(LogLevel::Info, message.clone(), Some("orders".to_string()));
+                let symbol = self
+                    .orders
+                    .get(&order_id)
+                    .map(|order| order.tradingsymbol.clone());
+                self.notify(
+                    LogLevel::Info,
+                    message,
+                    Some(NotificationTarget::symbol(AppView::Orders, symbol)),
+                );
+            }
+
+            AppEvent::OrderPlaced { request_id, .. } => {
+                self.metrics.write().orders_processed += 1;
+                if let Some(request_id) = request_id {
+                    let placed = self.orders_in_flight.write().finish(request_id);
+                    // The order dialog stays open until the broker accepts its order
+                    if placed.is_some_and(|order| order.tag.as_deref() == Some(MANUAL_ORDER_TAG)) {
+                        self.ui_input.clear_order_fields();
+                        self.ui_input.show_order_dialog = false;
+                    }
+                }
+            }
+
+            AppEvent::OrderRejected {
+                order_id,
+                tradingsymbol,
+                reason,
+                request_id,
+            } => {
+                if let Some(request_id) = request_id {
+                    self.orders_in_flight.write().finish(request_id);
+                }
+
+                if let Some(order_id) = &order_id {
+                    if let Some(mut order) = self.orders.get_mut(order_id) {
+                        order.status = OrderStatus::Rejected;
+                        order.status_message = Some(reason.clone());
+                        self.orders_gen.bump();
+                    }
+                }
+
+                self.sounds.play(SoundEvent::Rejection, &self.config.app);
+
+                let message = format!("Order rejected for {}: {}", tradingsymbol, reason);
+                self.notify_remote(AlertKind::Rejection, &message);
+                self.add_log(LogLevel::Error, message.clone(), Some("orders".to_string()));
+                self.raise_critical(message.clone(), Some("orders".to_string()));
+                self.notify(
+                    LogLevel::Error,
+                    message,
+                    Some(NotificationTarget::symbol(
+                        AppView::Orders,
+                        Some(tradingsymbol),
+                    )),
+                );
+            }
+
+            AppEvent::OrderPlaceFailed {
+                tradingsymbol,
+                error,
+                request_id,
+            } => {
+                // The order dialog stays open; the order book refresh shows whether it went in
+                if let Some(request_id) = request_id {
+                    self.orders_in_flight.write().finish(request_id);
+                }
+
+                let message = format!(
+                    "Order for {} may not have been placed: {}; check the order book before retrying",
+                    tradingsymbol, error
+                );
+                self.add_log(LogLevel::Error, message.clone(), Some("orders".to_string()));
+                self.raise_critical(message.clone(), Some("orders".to_string()));
+                self.notify(
+                    LogLevel::Error,
+                    message,
+                    Some(NotificationTarget::symbol(
+                        AppView::Orders,
+                        Some(tradingsymbol),
+                    )),
+                );
+            }
+
+            AppEvent::Error {
+                error,
+                module,
+                severity,
+            } => {
+                self.add_log(LogLevel::Error, error.clone(), module.clone());
+                if severity == ErrorSeverity::Critical {
+                    self.raise_critical(error, module);
+                }
+            }
+
+            AppEvent::ConfigReloaded(config) => {
+                // A save from Settings is applied at once and then seen again by the file watcher
+                if *config == self.loaded_config {
+                    return;
+                }
+
+                // Credentials are bound into the workers' clients at startup
+                if self.config.credentials_differ(&config) {
+                    self.add_log(
+                        LogLevel::Warning,
+                        "Zerodha credentials changed in config.toml; restart the dashboard to use them"
+                            .to_string(),
+                        Some("config".to_string()),
+                    );
+                }
+
+                // Only an edited theme section overrides a choice made in the settings menu
+                let reloaded_theme = Theme::from_config(&config.app);
+                if reloaded_theme != Theme::from_config(&self.config.app) {
+                    self.theme = reloaded_theme;
+                }
+
+                if config.notifier != self.config.notifier {
+                    if let Some(notifier) = &self.notifier {
+                        notifier.reconfigure(config.notifier.clone());
+                    }
+                }
+                self.config.apply_reload(&config);
+
+                // An untouched Settings draft follows the file
+                if let Some(form) = &mut self.ui_input.settings_form {
+                    if !form.is_dirty(&self.loaded_config) {
+                        form.draft = (*config).clone();
+                    }
+                }
+                if let Ok(level) = log::LevelFilter::from_str(&config.app.log_level) {
+                    log::set_max_level(level);
+                }
+                self.loaded_config = *config;
+
+                self.add_log(
+                    LogLevel::Info,
+                    "Configuration reloaded from config.toml".to_string(),
+                    Some("config".to_string()),
+                );
+            }
+
+            AppEvent::SessionGenerated(Ok(access_token)) => {
+                self.apply_access_token(access_token);
+            }
+
+            AppEvent::LoginCallback(result) => {
+                // Nothing to do once the dialog has been closed
+                if let Some(form) = &mut self.ui_input.reauth {
+                    form.listening = None;
+                    match result {
+                        Ok(request_token) if !form.exchanging => {
+                            form.request_token = request_token;
+                            self.submit_reauth();
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            form.error = Some(format!("{}; paste the request_token instead", e));
+                            self.add_log(
+                                LogLevel::Warning,
+                                format!("Login redirect not captured: {}", e),
+                                Some("auth".to_string()),
+                            );
+                        }
+                    }
+                }
+            }
+
+            AppEvent::SessionGenerated(Err(e)) => {
+                self.add_log(
+                    LogLevel::Error,
+                    format!("Re-authentication failed: {}", e),
+                    Some("auth".to_string()),
+                );
+                if let Some(form) = &mut self.ui_input.reauth {
+                    form.exchanging = false;
+                    form.error = Some(e);
+                }
+            }
+
+            AppEvent::WorkerPanicked {
+                worker,
+                message,
+                restart_in,
+            } => {
+                {
+                    let mut metrics = self.metrics.write();
+                    metrics.worker_restarts += 1;
+                    metrics.last_worker_panic = Some(format!("{}: {}", worker.label(), message));
+                }
+                // The socket went down with the connection loop
+                if worker == Worker::WebSocket {
+                    let mut connection_state = self.connection_state.write();
+                    if *connection_state != ConnectionState::AuthExpired {
+                        *connection_state = ConnectionState::Disconnected;
+                    }
+                }
+
+                let message = format!(
+                    "{} crashed ({}); restarting it in {} s",
+                    worker.label(),
+                    message,
+                    restart_in.as_secs()
+                );
+                self.add_log(
+                    LogLevel::Error,
+                    message.clone(),
+                    Some("supervisor".to_string()),
+                );
+                self.notify(
+                    LogLevel::Error,
+                    message,
+                    Some(NotificationTarget::view(AppView::Logs)),
+                );
+            }
+
+            AppEvent::UserProfileUpdated(profile) => {
+                *self.user_profile.write() = Some(profile);
+
+                // The token works, so one never dated, or dated past its expiry, was issued
+                // later than recorded; any moment it works at gives the same 06:00 expiry
+                let now = Utc::now().trunc_subsecs(0);
+                if self.token_expiry().is_none_or(|expiry| now >= expiry) {
+                    self.config.zerodha.access_token_issued_at = Some(now);
+                    self.token_expiry_checked = false;
+                    match config_writer::write_token_issued_at(Path::new(CONFIG_FILE), now) {
+                        Ok(()) => self.track_saved_token_issued_at(now),
+                        Err(e) => self.add_log(
+                            LogLevel::Warning,
+                            format!(
+                                "Failed to record the access token's age in {}: {}",
+                                CONFIG_FILE, e
+                            ),
+                            Some("auth".to_string()),
+                        ),
+                    }
+                }
+            }
+
+            AppEvent::NotificationTested(result) => {
+                if let Some(form) = &mut self.ui_input.settings_form {
+                    form.notification_test = match result {
+                        Ok(()) => ConnectionTest::Passed,
+                        Err(e) => ConnectionTest::Failed(e),
+                    };
+                }
+            }
+
+            AppEvent::ConnectionTested(result) => {
+                if let Some(form) = &mut self.ui_input.settings_form {
+                    form.connection_test = match result {
+                        Ok(()) => ConnectionTest::Passed,
+                        Err(e) => ConnectionTest::Failed(e),
+                    };
+                }
+            }
+
+            // Handle other events...
+            _ => {
+                self.add_log(
+                    LogLevel::Debug,
+                    format!("Unhandled event: {:?}", event),
+                    Some("state".to_string()),
+                );
+            }
+        }
+    }
+}
+
+/// Event sender handle for worker threads
+/// Allows workers to send events back to the UI thread
+#[derive(Clone)]
+pub struct EventSender {
+    sender: Sender<AppEvent>,
+    // Woken for every event except market data, which the UI picks up on its own cadence
+    repaint: Option<egui::Context>,
+    // Bounded channel market data goes through instead of `sender`, when set
+    ticks: Option<TickLane>,
+}
+
+/// Fixed-size buffer for ticks and depth updates, so a burst the UI can't keep up with
+/// costs stale ticks rather than memory
+#[derive(Clone)]
+struct TickLane {
+    sender: Sender<AppEvent>,
+    // Held to evict the oldest waiting tick under DropPolicy::Oldest
+    receiver: Receiver<AppEvent>,
+    drop_policy: DropPolicy,
+    dropped: Arc<AtomicU64>,
+}
+
+impl TickLane {
+    /// Queue `event`, handing it back if the app is gone
+    fn send(&self, mut event: AppEvent) -> Option<AppEvent> {
+        loop {
+            match self.sender.try_send(event) {
+                Ok(()) => return None,
+                Err(crossbeam_channel::TrySendError::Disconnected(event)) => return Some(event),
+                Err(crossbeam_channel::TrySendError::Full(rejected)) => {
+                    self.dropped.fetch_add(1, AtomicOrdering::Relaxed);
+                    match self.drop_policy {
+                        DropPolicy::Newest => return None,
+                        DropPolicy::Oldest => {
+                            let _ = self.receiver.try_recv();
+                            event = rejected;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl EventSender {
+    pub fn new(sender: Sender<AppEvent>) -> Self {
+        Self {
+            sender,
+            repaint: None,
+            ticks: None,
+        }
+    }
+
+    /// Send market data through a bounded channel, counting what `drop_policy` discards
+    /// into `dropped`
+    pub fn with_ticks(
+        mut self,
+        sender: Sender<AppEvent>,
+        receiver: Receiver<AppEvent>,
+        drop_policy: DropPolicy,
+        dropped: Arc<AtomicU64>,
+    ) -> Self {
+        self.ticks = Some(TickLane {
+            sender,
+            receiver,
+            drop_policy,
+            dropped,
+        });
+        self
+    }
+
+    /// Request a repaint of `ctx` whenever an event other than market data is sent
+    pub fn with_repaint(mut self, ctx: egui::Context) -> Self {
+        self.repaint = Some(ctx);
+        self
+    }
+
+    pub fn send(&self, event: AppEvent) -> Result<(), crossbeam_channel::SendError<AppEvent>> {
+        let wake = !event.is_market_data();
+        match self.ticks.as_ref().filter(|_| !wake) {
+            Some(ticks) => {
+                if let Some(event) = ticks.send(event) {
+                    return Err(crossbeam_channel::SendError(event));
+                }
+            }
+            None => self.sender.send(event)?,
+        }
+        if let Some(ctx) = self.repaint.as_ref().filter(|_| wake) {
+            ctx.request_repaint();
+        }
+        Ok(())
+    }
+
+    pub fn send_notification(&self, level: LogLevel, message: String, module: Option<String>) {
+        let _ = self.send(AppEvent::Notification {
+            level,
+            message,
+            module,
+        });
+    }
+
+    pub fn send_error(&self, error: String, module: Option<String>) {
+        let _ = self.send(AppEvent::Error {
+            error,
+            module,
+            severity: ErrorSeverity::Normal,
+        });
+    }
+
+    /// Report an error the user must see: it stays in a dialog until dismissed
+    pub fn send_critical_error(&self, error: String, module: Option<String>) {
+        let _ = self.send(AppEvent::Error {
+            error,
+            module,
+            severity: ErrorSeverity::Critical,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(instrument_token: u32, tradingsymbol: &str, last_price: f64) -> Position {
+        let mut position = Position {
+            instrument_token,
+            tradingsymbol: tradingsymbol.to_string(),
+            exchange: "NSE".to_string(),
+            product: "CNC".to_string(),
+            quantity: 10,
+            average_price: 100.0,
+            last_price,
+            close_price: 100.0,
+            pnl: 0.0,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            multiplier: 1.0,
+            overnight_quantity: 0,
+            day_quantity: 10,
+        };
+        position.calculate_pnl();
+        position
+    }
+
+    fn position_map(positions: Vec<Position>) -> DashMap<(u32, String), Position> {
+        positions
+            .into_iter()
+            .map(|position| (position.key(), position))
+            .collect()
+    }
+
+    fn symbols(snapshot: &PositionsSnapshot) -> Vec<String> {
+        snapshot
+            .positions()
+            .iter()
+            .map(|position| position.tradingsymbol.clone())
+            .collect()
+    }
+
+    fn sort_by(column: PositionColumn, descending: bool) -> SortState<PositionColumn> {
+        SortState { column, descending }
+    }
+
+    #[test]
+    fn snapshot_sorts_like_the_positions_table() {
+        let positions = position_map(vec![
+            position(1, "TCS", 110.0),
+            position(2, "INFY", 90.0),
+            position(3, "SBIN", 120.0),
+        ]);
+        let generation = Generation::default();
+        let mut snapshot = PositionsSnapshot::default();
+
+        snapshot.sync(
+            &positions,
+            &generation,
+            sort_by(PositionColumn::Symbol, false),
+        );
+        assert_eq!(symbols(&snapshot), ["INFY", "SBIN", "TCS"]);
+
+        snapshot.sync(&positions, &generation, sort_by(PositionColumn::Pnl, true));
+        assert_eq!(symbols(&snapshot), ["SBIN", "TCS", "INFY"]);
+        assert_eq!(snapshot.rows[&2], [2]);
+    }
+
+    #[test]
+    fn reprice_patches_the_row_and_resorts_price_columns() {
+        let positions = position_map(vec![position(1, "TCS", 110.0), position(2, "INFY", 90.0)]);
+        let generation = Generation::default();
+        let sort = sort_by(PositionColumn::LastPrice, false);
+        let mut snapshot = PositionsSnapshot::default();
+        snapshot.sync(&positions, &generation, sort);
+        assert_eq!(symbols(&snapshot), ["INFY", "TCS"]);
+
+        generation.bump();
+        snapshot.reprice(2, 150.0, generation.get());
+        assert!(snapshot.is_current(&generation));
+        assert_eq!(snapshot.sort, None);
+
+        // The map was never touched, so the patched row must survive the sync
+        snapshot.sync(&positions, &generation, sort);
+        assert_eq!(symbols(&snapshot), ["TCS", "INFY"]);
+        let infy = &snapshot.positions()[1];
+        assert_eq!(infy.last_price, 150.0);
+        assert_eq!(infy.pnl, 500.0);
+    }
+
+    #[test]
+    fn reprice_keeps_the_order_of_columns_that_ignore_price() {
+        let positions = position_map(vec![position(1, "TCS", 110.0), position(2, "INFY", 90.0)]);
+        let generation = Generation::default();
+        let sort = sort_by(PositionColumn::Symbol, false);
+        let mut snapshot = PositionsSnapshot::default();
+        snapshot.sync(&positions, &generation, sort);
+
+        generation.bump();
+        snapshot.reprice(2, 150.0, generation.get());
+        assert_eq!(snapshot.sort, Some(sort));
+        assert_eq!(symbols(&snapshot), ["INFY", "TCS"]);
+    }
+
+    #[test]
+    fn snapshot_rebuilds_when_the_map_changes() {
+        let positions = position_map(vec![position(1, "TCS", 110.0)]);
+        let generation = Generation::default();
+        let sort = sort_by(PositionColumn::Symbol, false);
+        let mut snapshot = PositionsSnapshot::default();
+        snapshot.sync(&positions, &generation, sort);
+
+        let infy = position(2, "INFY", 90.0);
+        positions.insert(infy.key(), infy);
+        generation.bump();
+        assert!(!snapshot.is_current(&generation));
+        snapshot.sync(&positions, &generation, sort);
+        assert_eq!(symbols(&snapshot), ["INFY", "TCS"]);
+    }
+
+    fn tick(instrument_token: u32, last_price: f64) -> AppEvent {
+        AppEvent::TickUpdate {
+            instrument_token,
+            last_price,
+            last_quantity: 1,
+            volume: 0,
+            timestamp: Utc::now(),
+            exchange_timestamp: None,
+        }
+    }
+
+    /// Deterministic stand-in for a random source: a 64-bit linear congruential generator
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn below(&mut self, bound: u64) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 33) % bound
+        }
+    }
+
+    #[test]
+    fn snapshot_matches_the_map_through_random_updates() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        let mut positions: Vec<Position> = (0..2000)
+            .map(|token| position(token, &format!("SYM{:04}", token), 100.0))
+            .collect();
+        state.handle_event(AppEvent::PositionsUpdated(positions.clone()));
+
+        let sorts = [
+            sort_by(PositionColumn::Symbol, false),
+            sort_by(PositionColumn::Pnl, true),
+            sort_by(PositionColumn::Value, false),
+            sort_by(PositionColumn::Quantity, true),
+        ];
+        let mut random = Lcg(7);
+        for round in 0..40 {
+            for _ in 0..100 {
+                let token = random.below(2000) as u32;
+                let price = 50.0 + random.below(10_000) as f64 / 100.0;
+                state.handle_event(tick(token, price));
+            }
+            if round % 10 == 9 {
+                // A refresh that drops one position and changes another's size
+                positions.remove(random.below(positions.len() as u64) as usize);
+                positions[0].quantity += 5;
+                state.handle_event(AppEvent::PositionsUpdated(positions.clone()));
+            }
+            state.ui_input.position_sort = sorts[random.below(sorts.len() as u64) as usize];
+            state.sync_positions_snapshot();
+
+            let sort = state.ui_input.position_sort;
+            let mut expected: Vec<Position> = state
+                .positions
+                .iter()
+                .map(|entry| entry.value().clone())
+                .collect();
+            expected.sort_by(|a, b| sort.apply(sort.column.compare(a, b)));
+            let snapshot = state.positions_snapshot.positions();
+            assert_eq!(snapshot.len(), expected.len());
+            for (row, expected) in snapshot.iter().zip(&expected) {
+                assert_eq!(row.instrument_token, expected.instrument_token);
+                assert_eq!(row.last_price, expected.last_price);
+                assert_eq!(row.quantity, expected.quantity);
+                assert_eq!(row.pnl, expected.pnl);
+            }
+        }
+
+        // A frame with nothing new reuses the rows instead of copying the map
+        let before = state.positions_snapshot.positions();
+        state.sync_positions_snapshot();
+        assert!(Arc::ptr_eq(&before, &state.positions_snapshot.positions()));
+    }
+
+    /// positions_gen, orders_gen and ticks_gen as (positions, orders, ticks)
+    fn generations(state: &AppState) -> (u64, u64, u64) {
+        (
+            state.positions_gen.get(),
+            state.orders_gen.get(),
+            state.ticks_gen.get(),
+        )
+    }
+
+    #[test]
+    fn generation_reports_changes_since_a_reading() {
+        let generation = Generation::default();
+        let seen = generation.get();
+        assert!(!generation.changed_since(seen));
+        generation.bump();
+        assert!(generation.changed_since(seen));
+        assert!(!generation.changed_since(generation.get()));
+    }
+
+    #[test]
+    fn each_update_bumps_only_its_own_collection() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+
+        state.handle_event(AppEvent::PositionsUpdated(vec![position(1, "INFY", 100.0)]));
+        assert_eq!(generations(&state), (1, 0, 0));
+
+        state.handle_event(AppEvent::OrdersUpdated(vec![Order::limit(
+            "ORD1",
+            OrderStatus::Open,
+        )]));
+        assert_eq!(generations(&state), (1, 1, 0));
+
+        // A tick for a held instrument reprices the position too
+        state.handle_event(tick(1, 105.0));
+        assert_eq!(generations(&state), (2, 1, 1));
+
+        state.handle_event(tick(2, 50.0));
+        assert_eq!(generations(&state), (2, 1, 2));
+    }
+
+    #[test]
+    fn rejection_bumps_orders_only_for_a_known_order() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        state.handle_event(AppEvent::OrdersUpdated(vec![Order::limit(
+            "ORD1",
+            OrderStatus::Open,
+        )]));
+        let rejected = |order_id: Option<&str>| AppEvent::OrderRejected {
+            order_id: order_id.map(str::to_string),
+            tradingsymbol: "INFY".to_string(),
+            reason: "RMS".to_string(),
+            request_id: None,
+        };
+
+        state.handle_event(rejected(None));
+        assert_eq!(generations(&state), (0, 1, 0));
+        state.handle_event(rejected(Some("ORD1")));
+        assert_eq!(generations(&state), (0, 2, 0));
+        state.handle_event(rejected(Some("ORD2")));
+        assert_eq!(generations(&state), (0, 2, 0));
+    }
+
+    #[test]
+    fn invalid_order_is_reported_like_an_exchange_rejection() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        state.send_command(Command::PlaceOrder {
+            details: OrderRequest::market("INFY", "BUY", 0),
+        });
+        assert!(state.orders_in_flight.read().any(|_| true));
+
+        assert!(state.process_events());
+        assert!(!state.orders_in_flight.read().any(|_| true));
+        let critical = state.critical_errors.back().expect("critical error raised");
+        assert!(critical.message.starts_with("Order rejected for INFY"));
+        let notification = state.notifications.back().expect("notification raised");
+        assert_eq!(
+            notification.target,
+            Some(NotificationTarget::symbol(
+                AppView::Orders,
+                Some("INFY".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn unanswered_placement_is_not_reported_as_a_rejection() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        let mut details = OrderRequest::market("INFY", "BUY", 1);
+        state.orders_in_flight.write().start(&mut details);
+
+        state.handle_event(AppEvent::OrderPlaceFailed {
+            tradingsymbol: "INFY".to_string(),
+            error: "Failed to place order: connection reset".to_string(),
+            request_id: details.request_id,
+        });
+
+        assert!(!state.orders_in_flight.read().any(|_| true));
+        let critical = state.critical_errors.back().expect("critical error raised");
+        assert!(critical.message.contains("may not have been placed"));
+        assert!(!critical.message.contains("rejected"));
+    }
+
+    #[test]
+    fn cancellations_carry_the_order_variety() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        let mut cover = Order::limit("ORD1", OrderStatus::Open);
+        cover.variety = "co".to_string();
+        state.handle_event(AppEvent::OrdersUpdated(vec![cover]));
+
+        let variety = |order_id: &str| match state.cancel_order_command(order_id.to_string()) {
+            Command::CancelOrder { variety, .. } => variety,
+            command => panic!("unexpected {:?}", command),
+        };
+        assert_eq!(variety("ORD1"), "co");
+        assert_eq!(variety("ORD2"), "regular");
+    }
+
+    #[test]
+    fn total_pnl_is_summed_again_only_after_positions_change() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        state.handle_event(AppEvent::PositionsUpdated(vec![position(1, "INFY", 110.0)]));
+        assert_eq!(state.calculate_total_pnl().total, 100.0);
+
+        // An edit that bypasses handle_event doesn't bump the counter, so the cache holds
+        state
+            .positions
+            .get_mut(&(1, "CNC".to_string()))
+            .unwrap()
+            .update_last_price(120.0);
+        assert_eq!(state.calculate_total_pnl().total, 100.0);
+
+        state.handle_event(tick(1, 130.0));
+        assert_eq!(state.calculate_total_pnl().total, 300.0);
+    }
+
+    #[test]
+    fn numeric_input_parses_strictly() {
+        assert_eq!(parse_with_feedback::<f64>(""), Err(InputError::Empty));
+        assert_eq!(parse_with_feedback::<f64>("   "), Err(InputError::Empty));
+        assert_eq!(parse_with_feedback::<f64>("0"), Ok(0.0));
+        assert_eq!(parse_with_feedback::<f64>("-1"), Ok(-1.0));
+        assert_eq!(parse_with_feedback::<f64>("1e3"), Ok(1000.0));
+        assert_eq!(parse_with_feedback::<f64>(" 5 "), Ok(5.0));
+        assert_eq!(
+            parse_with_feedback::<f64>("1.2.3"),
+            Err(InputError::NotANumber("1.2.3".to_string()))
+        );
+        assert_eq!(
+            parse_with_feedback::<f64>("1e999"),
+            Err(InputError::NotANumber("1e999".to_string()))
+        );
+
+        assert_eq!(parse_with_feedback::<i32>(" 5 "), Ok(5));
+        assert_eq!(parse_with_feedback::<i32>("-1"), Ok(-1));
+        assert_eq!(
+            parse_with_feedback::<i32>("1e3"),
+            Err(InputError::NotANumber("1e3".to_string()))
+        );
+        assert_eq!(
+            parse_with_feedback::<u32>("-1"),
+            Err(InputError::NotANumber("-1".to_string()))
+        );
+    }
+
+    /// A quantity field after validating `text` as a required positive number
+    fn quantity_field(text: &str) -> ValidatedField<i32> {
+        let mut field = ValidatedField {
+            text: text.to_string(),
+            ..Default::default()
+        };
+        field.validate(true, |quantity| positive(quantity, "Quantity"));
+        field
+    }
+
+    #[test]
+    fn validated_field_accepts_only_positive_numbers() {
+        let field = quantity_field(" 5 ");
+        assert_eq!(field.value(), Some(5));
+        assert!(field.is_valid());
+
+        for (text, error) in [
+            ("", "Required"),
+            ("0", "Quantity must be greater than zero"),
+            ("-1", "Quantity must be greater than zero"),
+            ("1e3", "'1e3' is not a valid number"),
+            ("1.2.3", "'1.2.3' is not a valid number"),
+        ] {
+            let field = quantity_field(text);
+            assert_eq!(field.value(), None, "{:?}", text);
+            assert_eq!(field.error(), Some(error), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn optional_field_may_be_left_empty() {
+        let mut field = ValidatedField::<f64>::default();
+        field.validate(false, |price| positive(price, "Price"));
+        assert!(field.is_valid());
+        assert_eq!(field.value(), None);
+    }
+
+    #[test]
+    fn validate_order_refuses_non_positive_amounts() {
+        let (state, _receivers) = AppState::new(Config::for_tests());
+        assert!(state
+            .validate_order(&OrderRequest::market("INFY", "BUY", 1))
+            .is_ok());
+        assert!(matches!(
+            state.validate_order(&OrderRequest::market("INFY", "BUY", 0)),
+            Err(OrderValidationError::NonPositiveQuantity { quantity: 0, .. })
+        ));
+    }
+
+    /// Every variant serializes, displays and parses as its API string
+    fn assert_api_str_round_trips<T>(variants: &[T], as_api_str: fn(&T) -> &'static str)
+    where
+        T: Copy
+            + PartialEq
+            + std::fmt::Debug
+            + std::fmt::Display
+            + std::str::FromStr
+            + Serialize
+            + serde::de::DeserializeOwned,
+        T::Err: std::fmt::Debug,
+    {
+        for &variant in variants {
+            let api = as_api_str(&variant);
+            assert_eq!(variant.to_string(), api);
+            assert_eq!(api.parse::<T>().unwrap(), variant);
+            assert_eq!(api.to_lowercase().parse::<T>().unwrap(), variant);
+
+            let json = serde_json::to_string(&variant).unwrap();
+            assert_eq!(json, format!("\"{}\"", api));
+            assert_eq!(serde_json::from_str::<T>(&json).unwrap(), variant);
+        }
+    }
+
+    #[test]
+    fn order_enums_round_trip_through_api_strings() {
+        assert_api_str_round_trips(&OrderType::ALL, OrderType::as_api_str);
+        assert_api_str_round_trips(&TransactionType::ALL, TransactionType::as_api_str);
+        assert_api_str_round_trips(&ProductType::ALL, ProductType::as_api_str);
+        assert_api_str_round_trips(&Validity::ALL, Validity::as_api_str);
+        assert_api_str_round_trips(&Exchange::ALL, Exchange::as_api_str);
+    }
+
+    #[test]
+    fn order_enums_refuse_unknown_strings() {
+        let error = "BO".parse::<ProductType>().unwrap_err();
+        assert_eq!(error.to_string(), "unknown product type: BO");
+        assert!("".parse::<OrderType>().is_err());
+        assert!(serde_json::from_str::<OrderType>("\"SLM\"").is_err());
+    }
+
+    #[test]
+    fn manual_orders_default_to_delivery() {
+        assert_eq!(ProductType::default(), ProductType::CNC);
+        assert_eq!(
+            UiInputState::default().selected_product_type,
+            ProductType::CNC
+        );
+    }
+
+    #[test]
+    fn alert_form_creates_then_updates_the_same_alert() {
+        let mut form = AlertForm::new(7, Some(250.0));
+        form.condition = AlertCondition::Below;
+        form.note = "  support ".to_string();
+        let Some(Command::CreateAlert { alert }) = form.to_command() else {
+            panic!("new alert form should create");
+        };
+        assert_eq!(
+            (alert.instrument_token, alert.condition, alert.threshold),
+            (7, AlertCondition::Below, 250.0)
+        );
+        assert_eq!(alert.note.as_deref(), Some("support"));
+
+        let mut form = AlertForm::edit(&alert);
+        form.threshold_input.text = "240".to_string();
+        form.validate();
+        let Some(Command::UpdateAlert { alert: updated }) = form.to_command() else {
+            panic!("edited alert form should update");
+        };
+        assert_eq!((updated.id, updated.threshold), (alert.id, 240.0));
+
+        form.threshold_input.text = "0".to_string();
+        form.validate();
+        assert!(!form.is_ready());
+        assert!(form.to_command().is_none());
+    }
+
+    fn held(token: u32, exchange: &str, product: &str, last_price: f64) -> Position {
+        let mut position = position(token, &format!("SYM{}", token), last_price);
+        position.exchange = exchange.to_string();
+        position.product = product.to_string();
+        position.realized_pnl = token as f64;
+        position
+    }
+
+    #[test]
+    fn pnl_groups_split_positions_and_sum_to_the_total() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        state.handle_event(AppEvent::PositionsUpdated(vec![
+            held(1, "NSE", "CNC", 110.0),
+            held(2, "NSE", "MIS", 95.0),
+            held(3, "NFO", "NRML", 130.0),
+            held(4, "NFO", "MIS", 100.0),
+        ]));
+        state.sync_positions_snapshot();
+        let positions = state.positions_snapshot.positions();
+        let total = state.calculate_total_pnl();
+        // Unrealized 100 - 50 + 300 + 0, realized 1 + 2 + 3 + 4
+        assert_eq!((total.unrealized, total.realized), (350.0, 10.0));
+
+        let summary = |grouping| {
+            state
+                .pnl_groups(&positions, grouping)
+                .into_iter()
+                .map(|(key, pnl, rows)| (key, pnl.total, rows.len()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            summary(PnlGrouping::Exchange),
+            [("NFO".to_string(), 307.0, 2), ("NSE".to_string(), 53.0, 2)]
+        );
+        assert_eq!(
+            summary(PnlGrouping::Product),
+            [
+                ("CNC".to_string(), 101.0, 1),
+                ("MIS".to_string(), -44.0, 2),
+                ("NRML".to_string(), 303.0, 1)
+            ]
+        );
+        assert_eq!(summary(PnlGrouping::None), [(String::new(), 360.0, 4)]);
+        // Positions no strategy traded share the untagged group
+        assert_eq!(
+            summary(PnlGrouping::Tag),
+            [(UNTAGGED_GROUP.to_string(), 360.0, 4)]
+        );
+
+        let by_exchange = state.positions_by_exchange();
+        assert_eq!(
+            by_exchange.values().map(|pnl| pnl.total).sum::<f64>(),
+            total.total
+        );
+        assert_eq!(by_exchange["NSE"].unrealized, 50.0);
+    }
+
+    #[test]
+    fn products_of_one_instrument_stay_apart_and_reprice_together() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        let mut intraday = held(1, "NSE", "MIS", 110.0);
+        intraday.quantity = -5;
+        intraday.calculate_pnl();
+        state.handle_event(AppEvent::PositionsUpdated(vec![
+            held(1, "NSE", "CNC", 110.0),
+            intraday,
+        ]));
+        state.sync_positions_snapshot();
+        let positions = state.positions_snapshot.positions();
+        assert_eq!(positions.len(), 2);
+
+        // Unrealized 100 - 50, realized 1 + 1
+        let total = state.calculate_total_pnl();
+        assert_eq!((total.unrealized, total.realized), (50.0, 2.0));
+        let products: Vec<(String, f64)> = state
+            .pnl_groups(&positions, PnlGrouping::Product)
+            .into_iter()
+            .map(|(key, pnl, _)| (key, pnl.total))
+            .collect();
+        assert_eq!(
+            products,
+            [("CNC".to_string(), 101.0), ("MIS".to_string(), -49.0)]
+        );
+        assert_eq!(state.positions_by_exchange()["NSE"].total, total.total);
+
+        // One tick moves both rows, in the map and in the snapshot
+        state.handle_event(tick(1, 120.0));
+        state.sync_positions_snapshot();
+        assert_eq!(state.calculate_total_pnl().unrealized, 100.0);
+        assert!(state
+            .positions_snapshot
+            .positions()
+            .iter()
+            .all(|position| position.last_price == 120.0));
+        assert_eq!(state.positions_of(1).len(), 2);
+        assert!(state.has_open_position(1));
+    }
+
+    #[test]
+    fn filtered_totals_cover_each_product_of_an_instrument() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        let mut intraday = held(1, "NSE", "MIS", 110.0);
+        intraday.quantity = -5;
+        intraday.calculate_pnl();
+        state.handle_event(AppEvent::PositionsUpdated(vec![
+            held(1, "NSE", "CNC", 110.0),
+            intraday,
+            held(2, "NSE", "CNC", 90.0),
+        ]));
+        state.sync_positions_snapshot();
+
+        let totals = |state: &AppState| {
+            let (pnl, count) = state.visible_positions_pnl();
+            (pnl.total, count)
+        };
+        assert_eq!(totals(&state), (state.calculate_total_pnl().total, 3));
+
+        state.ui_input.position_filters.product = Some(ProductType::MIS);
+        assert_eq!(totals(&state), (-49.0, 1));
+        // Unrealized 100 - 100, realized 1 + 2
+        state.ui_input.position_filters.product = Some(ProductType::CNC);
+        assert_eq!(totals(&state), (3.0, 2));
+    }
+
+    #[test]
+    fn row_cache_rebuilds_only_when_its_key_changes() {
+        let key = |revision, filter: &str, frozen| TableKey {
+            revision,
+            filter: filter.to_string(),
+            sort: SortState::<OrderColumn>::default(),
+            frozen,
+        };
+        let mut cache = RowCache::default();
+        assert!(!cache.is_current(&key(0, "", false)));
+
+        cache.store(key(1, "All:", false), vec![1, 2, 3]);
+        assert!(cache.is_current(&key(1, "All:", false)));
+        assert!(!cache.is_current(&key(2, "All:", false)));
+        assert!(!cache.is_current(&key(1, "All:infy", false)));
+        assert!(!cache.is_current(&key(1, "All:", true)));
+        let mut sorted = key(1, "All:", false);
+        sorted.sort.toggle(OrderColumn::Price);
+        assert!(!cache.is_current(&sorted));
+
+        // A view holding the rows keeps them while the cache is rebuilt
+        let held = cache.rows();
+        assert!(Arc::ptr_eq(&held, &cache.rows()));
+        cache.store(key(2, "All:", false), vec![4]);
+        assert_eq!(*held, [1, 2, 3]);
+        assert_eq!(*cache.rows(), [4]);
+    }
+
+    fn tagged(instrument_token: u32, product: &str, net_quantity: i32) -> TagPosition {
+        TagPosition {
+            instrument_token,
+            tradingsymbol: format!("SYM{}", instrument_token),
+            exchange: "NSE".to_string(),
+            product: product.to_string(),
+            net_quantity,
+        }
+    }
+
+    #[test]
+    fn tag_grouping_gives_each_position_to_its_largest_tag() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        state.handle_event(AppEvent::PositionsUpdated(vec![
+            held(1, "NSE", "CNC", 110.0),
+            held(2, "NSE", "MIS", 95.0),
+            held(3, "NSE", "CNC", 130.0),
+            held(4, "NSE", "CNC", 100.0),
+        ]));
+        *state.tag_positions.write() = HashMap::from([
+            // Largest net quantity wins token 1; token 2 is held as MIS, not CNC
+            (
+                "momentum".to_string(),
+                vec![
+                    tagged(1, "CNC", 8),
+                    tagged(2, "CNC", 10),
+                    tagged(3, "CNC", -5),
+                ],
+            ),
+            ("breakout".to_string(), vec![tagged(1, "CNC", -3)]),
+            // A tie on size goes to the first tag by name
+            ("arbitrage".to_string(), vec![tagged(3, "CNC", 5)]),
+        ]);
+        state.sync_positions_snapshot();
+        let positions = state.positions_snapshot.positions();
+
+        let groups: Vec<(String, Vec<u32>)> = state
+            .pnl_groups(&positions, PnlGrouping::Tag)
+            .into_iter()
+            .map(|(tag, _, rows)| {
+                let mut tokens: Vec<u32> = rows
+                    .iter()
+                    .map(|position| position.instrument_token)
+                    .collect();
+                tokens.sort();
+                (tag, tokens)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                ("arbitrage".to_string(), vec![3]),
+                ("momentum".to_string(), vec![1]),
+                (UNTAGGED_GROUP.to_string(), vec![2, 4]),
+            ]
+        );
+    }
+
+    /// Events for `state` to process, sent the way workers send them
+    fn event_channel(state: &mut AppState) -> EventSender {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        state.event_receiver = receiver;
+        EventSender::new(sender)
+    }
+
+    #[test]
+    fn only_ticks_and_depth_count_as_market_data() {
+        assert!(tick(1, 100.0).is_market_data());
+        assert!(!AppEvent::OrdersUpdated(Vec::new()).is_market_data());
+        assert!(!AppEvent::PositionsUpdated(Vec::new()).is_market_data());
+    }
+
+    #[test]
+    fn market_data_alone_does_not_ask_for_an_immediate_frame() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        let events = event_channel(&mut state);
+        assert!(!state.process_events());
+
+        events.send(tick(1, 100.0)).unwrap();
+        events.send(tick(2, 50.0)).unwrap();
+        assert!(!state.process_events());
+
+        events.send(tick(1, 101.0)).unwrap();
+        events
+            .send(AppEvent::OrdersUpdated(vec![Order::limit(
+                "ORD1",
+                OrderStatus::Open,
+            )]))
+            .unwrap();
+        assert!(state.process_events());
+    }
+
+    #[test]
+    fn leftover_events_ask_for_another_frame() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        state.config.performance.max_event_batch = 2;
+        let events = event_channel(&mut state);
+        for price in [100.0, 101.0, 102.0] {
+            events.send(tick(1, price)).unwrap();
+        }
+
+        assert!(state.process_events());
+        assert!(!state.process_events());
+        assert_eq!(state.pending_events(), 0);
+    }
+
+    #[test]
+    fn event_sender_wakes_the_ui_for_all_but_market_data() {
+        let ctx = egui::Context::default();
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let events = EventSender::new(sender).with_repaint(ctx.clone());
+        // Let the context settle its start-up repaint
+        let _ = ctx.run(egui::RawInput::default(), |_| {});
+        let _ = ctx.run(egui::RawInput::default(), |_| {});
+        assert!(!ctx.has_requested_repaint());
+
+        events.send(tick(1, 100.0)).unwrap();
+        assert!(!ctx.has_requested_repaint());
+
+        events.send(AppEvent::OrdersUpdated(Vec::new())).unwrap();
+        assert!(ctx.has_requested_repaint());
+    }
+
+    #[test]
+    fn live_trading_needs_the_feed_connected() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        for (connection, reason) in [
+            (ConnectionState::Connected, None),
+            (
+                ConnectionState::Connecting,
+                Some("Market data feed is disconnected"),
+            ),
+            (
+                ConnectionState::Reconnecting { attempt: 2 },
+                Some("Market data feed is disconnected"),
+            ),
+            (
+                ConnectionState::AuthExpired,
+                Some("Access token expired; re-authenticate to trade"),
+            ),
+        ] {
+            *state.connection_state.write() = connection;
+            assert_eq!(state.trading_blocked_reason(), reason, "{:?}", connection);
+        }
+
+        state.kill_switch = true;
+        *state.connection_state.write() = ConnectionState::Connected;
+        assert_eq!(state.trading_blocked_reason(), Some("Kill switch is on"));
+        assert!(!state.trading_enabled());
+    }
+
+    #[test]
+    fn paper_trading_ignores_the_feed_but_not_the_kill_switch() {
+        let mut config = Config::for_tests();
+        config.app.mode = TradingMode::Paper;
+        let (mut state, _receivers) = AppState::new(config);
+
+        for connection in [
+            ConnectionState::Connecting,
+            ConnectionState::Reconnecting { attempt: 1 },
+            ConnectionState::AuthExpired,
+        ] {
+            *state.connection_state.write() = connection;
+            assert!(state.trading_enabled(), "{:?}", connection);
+        }
+
+        state.kill_switch = true;
+        assert_eq!(state.trading_blocked_reason(), Some("Kill switch is on"));
+    }
+
+    #[test]
+    fn find_instrument_prefers_the_requested_exchange() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        assert!(state.find_instrument("INFY", "NSE").is_none());
+
+        state.handle_event(AppEvent::InstrumentsUpdated {
+            exchange: "NSE".to_string(),
+            instruments: vec![Instrument::equity(1, "INFY", "NSE")],
+        });
+        state.handle_event(AppEvent::InstrumentsUpdated {
+            exchange: "BSE".to_string(),
+            instruments: vec![
+                Instrument::equity(2, "INFY", "BSE"),
+                Instrument::equity(3, "RELIANCE", "BSE"),
+            ],
+        });
+        let token = |symbol, exchange| {
+            state
+                .find_instrument(symbol, exchange)
+                .map(|instrument| instrument.instrument_token)
+        };
+
+        assert_eq!(token("INFY", "NSE"), Some(1));
+        assert_eq!(token("INFY", "BSE"), Some(2));
+        assert_eq!(token(" infy ", "BSE"), Some(2));
+        // Listed elsewhere only: the other exchange's instrument
+        assert_eq!(token("RELIANCE", "NSE"), Some(3));
+        assert_eq!(token("TCS", "NSE"), None);
+    }
+
+    #[test]
+    fn alert_notification_opens_the_alerted_instrument() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        let events = event_channel(&mut state);
+        // BSE only, a lookup by symbol on NSE would miss it
+        let alert = PriceAlert::new(500325, AlertCondition::Above, 2500.0, false, None);
+        events
+            .send(AppEvent::AlertTriggered {
+                alert,
+                price: 2510.0,
+            })
+            .unwrap();
+        state.process_events();
+
+        let target = state.notifications[0].target.clone();
+        assert_eq!(
+            target,
+            Some(NotificationTarget::instrument(AppView::Charts, 500325))
+        );
+
+        state.open_notification(0);
+        assert_eq!(state.ui_input.selected_instrument_token, Some(500325));
+        assert_eq!(state.ui_input.requested_view, Some(AppView::Charts));
     }
 }
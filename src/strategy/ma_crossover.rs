@@ -0,0 +1,182 @@
+use super::{param, Strategy, StrategyContext};
+use crate::data_structures::{LogLevel, Order, OrderStatus, TickData};
+use crate::market_calendar::ist_now;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub const KIND: &str = "ma_crossover";
+
+/// How often the exit time is checked
+const EXIT_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Moving-average crossover on one-minute closes: long `quantity` while the fast average is
+/// above the slow one, short while it is below; flat from `exit_time` IST onwards
+pub struct MaCrossover {
+    fast: usize,
+    slow: usize,
+    quantity: i32,
+    product: String,
+    exit_time: NaiveTime,
+    instruments: HashMap<u32, Crossing>,
+    // Day the exit last ran, so it runs once per session
+    exited_on: Option<NaiveDate>,
+}
+
+/// Where the averages of one instrument stood at the last completed candle
+#[derive(Default)]
+struct Crossing {
+    candle_start: Option<DateTime<Utc>>,
+    fast_above: Option<bool>,
+}
+
+impl MaCrossover {
+    pub fn from_params(params: &toml::Table) -> Result<Self, String> {
+        let fast: usize = param(params, "fast", 9)?;
+        let slow: usize = param(params, "slow", 21)?;
+        let quantity: i32 = param(params, "quantity", 1)?;
+        if fast == 0 || fast >= slow {
+            return Err("params.fast must be at least 1 and less than params.slow".to_string());
+        }
+        if quantity <= 0 {
+            return Err("params.quantity must be greater than 0".to_string());
+        }
+
+        let text = |key: &str, default: &str| match params.get(key) {
+            None => Ok(default.to_string()),
+            Some(value) => value
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("params.{} must be a string", key)),
+        };
+        let product = text("product", "MIS")?;
+        let exit_time = NaiveTime::parse_from_str(&text("exit_time", "15:15")?, "%H:%M")
+            .map_err(|_| "params.exit_time must be an IST time like 15:15".to_string())?;
+
+        Ok(Self {
+            fast,
+            slow,
+            quantity,
+            product,
+            exit_time,
+            instruments: HashMap::new(),
+            exited_on: None,
+        })
+    }
+
+    fn past_exit_time(&self) -> bool {
+        ist_now().time() >= self.exit_time
+    }
+
+    /// Trade the strategy's own position in an instrument to `target`
+    fn trade_to(&self, instrument_token: u32, target: i32, reason: &str, ctx: &StrategyContext) {
+        let delta = target - ctx.net_quantity(instrument_token);
+        if delta == 0 {
+            return;
+        }
+        let Some(order) = ctx.market_order(instrument_token, delta, &self.product) else {
+            return;
+        };
+        ctx.notify(
+            LogLevel::Info,
+            format!(
+                "{}: {} {} {}",
+                reason, order.transaction_type, order.quantity, order.tradingsymbol
+            ),
+        );
+        ctx.place_order(order);
+    }
+}
+
+fn average(closes: &[f64]) -> f64 {
+    closes.iter().sum::<f64>() / closes.len() as f64
+}
+
+impl Strategy for MaCrossover {
+    fn on_tick(&mut self, tick: &TickData, ctx: &StrategyContext) {
+        let candles = ctx.minute_candles(tick.instrument_token);
+        // The last candle is still forming; only closed ones count
+        let Some((_, closed)) = candles.split_last() else {
+            return;
+        };
+        if closed.len() < self.slow {
+            return;
+        }
+
+        let crossing = self.instruments.entry(tick.instrument_token).or_default();
+        let candle_start = closed.last().map(|candle| candle.start);
+        if crossing.candle_start == candle_start {
+            return;
+        }
+        crossing.candle_start = candle_start;
+
+        let closes: Vec<f64> = closed.iter().map(|candle| candle.close).collect();
+        let fast = average(&closes[closes.len() - self.fast..]);
+        let slow = average(&closes[closes.len() - self.slow..]);
+        let fast_above = fast > slow;
+        let crossed = crossing.fast_above.is_some_and(|above| above != fast_above);
+        crossing.fast_above = Some(fast_above);
+
+        if !crossed || self.past_exit_time() {
+            return;
+        }
+        let (target, reason) = if fast_above {
+            (self.quantity, "fast average crossed above")
+        } else {
+            (-self.quantity, "fast average crossed below")
+        };
+        self.trade_to(tick.instrument_token, target, reason, ctx);
+    }
+
+    fn on_order_update(&mut self, order: &Order, ctx: &StrategyContext) {
+        if order.status == OrderStatus::Rejected {
+            ctx.notify(
+                LogLevel::Warning,
+                format!(
+                    "{} {} rejected: {}",
+                    order.transaction_type,
+                    order.tradingsymbol,
+                    order.status_message.as_deref().unwrap_or("no reason given")
+                ),
+            );
+        }
+    }
+
+    fn on_timer(&mut self, _elapsed: Duration, ctx: &StrategyContext) {
+        let today = ist_now().date_naive();
+        if !self.past_exit_time() || self.exited_on == Some(today) {
+            return;
+        }
+        self.exited_on = Some(today);
+
+        for order in ctx.open_orders() {
+            ctx.cancel_order(&order.order_id);
+        }
+        for &instrument_token in ctx.instruments() {
+            // Leave it alone if the account no longer holds what the strategy bought, e.g.
+            // after a manual square-off
+            let own = ctx.net_quantity(instrument_token);
+            let held = ctx
+                .position(instrument_token, &self.product)
+                .map(|position| position.quantity)
+                .unwrap_or_default();
+            if own == 0 || held.signum() != own.signum() {
+                continue;
+            }
+            let last_price = ctx
+                .quote(instrument_token)
+                .map(|tick| format!(" near {:.2}", tick.last_price))
+                .unwrap_or_default();
+            self.trade_to(
+                instrument_token,
+                0,
+                &format!("exit time reached{}", last_price),
+                ctx,
+            );
+        }
+    }
+
+    fn timer_interval(&self) -> Option<Duration> {
+        Some(EXIT_CHECK_INTERVAL)
+    }
+}
@@ -0,0 +1,308 @@
+mod ma_crossover;
+mod runner;
+
+pub use runner::{StrategyRunner, StrategyStatus};
+
+use crate::candles::Candle;
+use crate::data_structures::*;
+use crate::instrument_index::InstrumentIndex;
+use crate::state::{AppEvent, Command, EventSender};
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Kite accepts order tags of up to 20 characters
+const MAX_TAG_LEN: usize = 20;
+
+/// Every strategy kind this build ships, as named in config.toml
+pub const KINDS: &[&str] = &[ma_crossover::KIND];
+
+/// One [[strategies]] entry of config.toml
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyConfig {
+    /// Unique name, also the tag on every order the strategy places
+    pub name: String,
+    /// Which implementation runs, one of KINDS
+    pub kind: String,
+    /// Whether it trades from startup; it can be switched on and off from the P&L view
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// "EXCHANGE:SYMBOL", e.g. "NSE:INFY", or an instrument token
+    #[serde(default)]
+    pub instruments: Vec<String>,
+    /// Settings of the implementation, e.g. moving average lengths
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub params: toml::Table,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl StrategyConfig {
+    /// Readable message per problem with the entry itself or its params
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.name.is_empty()
+            || self.name.len() > MAX_TAG_LEN
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            problems.push(format!(
+                "strategies.name \"{}\" must be 1-{} letters, digits or underscores, as it becomes the order tag",
+                self.name, MAX_TAG_LEN
+            ));
+        }
+        if self.instruments.is_empty() {
+            problems.push(format!("strategy \"{}\" has no instruments", self.name));
+        }
+        for instrument in &self.instruments {
+            if InstrumentRef::parse(instrument).is_none() {
+                problems.push(format!(
+                    "strategy \"{}\": instrument \"{}\" must be EXCHANGE:SYMBOL or an instrument token",
+                    self.name, instrument
+                ));
+            }
+        }
+        if let Err(e) = build(self) {
+            problems.push(format!("strategy \"{}\": {}", self.name, e));
+        }
+        problems
+    }
+}
+
+/// An instrument as written in a strategy's config
+enum InstrumentRef<'a> {
+    Token(u32),
+    Symbol {
+        exchange: &'a str,
+        tradingsymbol: &'a str,
+    },
+}
+
+impl<'a> InstrumentRef<'a> {
+    fn parse(text: &'a str) -> Option<Self> {
+        let text = text.trim();
+        if let Ok(token) = text.parse() {
+            return Some(InstrumentRef::Token(token));
+        }
+        let (exchange, tradingsymbol) = text.split_once(':')?;
+        (!exchange.is_empty() && !tradingsymbol.is_empty()).then_some(InstrumentRef::Symbol {
+            exchange,
+            tradingsymbol,
+        })
+    }
+
+    /// The instrument token, once the instrument master is loaded for symbols
+    fn resolve(&self, index: &InstrumentIndex) -> Option<u32> {
+        match self {
+            InstrumentRef::Token(token) => Some(*token),
+            InstrumentRef::Symbol {
+                exchange,
+                tradingsymbol,
+            } => index.lookup(tradingsymbol, exchange),
+        }
+    }
+}
+
+/// A trading strategy driven by the runner
+/// Callbacks run on the runner's task and should return quickly; one that panics turns
+/// its strategy off rather than taking the others down
+pub trait Strategy: Send {
+    /// Latest tick of one of the strategy's instruments; ticks arriving between two runner
+    /// passes are conflated into the newest
+    fn on_tick(&mut self, tick: &TickData, ctx: &StrategyContext);
+
+    /// One of the strategy's own orders changed status or filled further
+    fn on_order_update(&mut self, _order: &Order, _ctx: &StrategyContext) {}
+
+    /// Called every `timer_interval` with the time since the previous call
+    fn on_timer(&mut self, _elapsed: Duration, _ctx: &StrategyContext) {}
+
+    /// How often on_timer runs; None never calls it
+    fn timer_interval(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Build the implementation a config entry names, with its params applied
+pub fn build(config: &StrategyConfig) -> Result<Box<dyn Strategy>, String> {
+    match config.kind.as_str() {
+        ma_crossover::KIND => Ok(Box::new(ma_crossover::MaCrossover::from_params(
+            &config.params,
+        )?)),
+        kind => Err(format!(
+            "unknown kind \"{}\"; expected one of: {}",
+            kind,
+            KINDS.join(", ")
+        )),
+    }
+}
+
+/// Read a numeric param, falling back to `default` when it is absent
+fn param<T: TryFrom<i64>>(params: &toml::Table, key: &str, default: T) -> Result<T, String> {
+    match params.get(key) {
+        None => Ok(default),
+        Some(value) => value
+            .as_integer()
+            .and_then(|value| T::try_from(value).ok())
+            .ok_or_else(|| format!("params.{} must be a positive whole number", key)),
+    }
+}
+
+/// App state strategies read, shared with AppState, and the channel their requests go back on
+#[derive(Clone)]
+pub struct StrategyData {
+    pub positions: Arc<DashMap<(u32, String), Position>>,
+    pub orders: Arc<DashMap<String, Order>>,
+    pub tag_positions: Arc<RwLock<HashMap<String, Vec<TagPosition>>>>,
+    pub instruments: Arc<DashMap<u32, Instrument>>,
+    pub instrument_index: Arc<RwLock<InstrumentIndex>>,
+    pub tick_data: Arc<DashMap<u32, TickData>>,
+    pub candles: Arc<DashMap<u32, crate::candles::CandleSeries>>,
+    pub event_sender: EventSender,
+}
+
+/// What a strategy can see and do from inside a callback
+/// Orders go back to the UI thread and through AppState::send_command, so the kill switch
+/// and every order check apply to them as to manual orders
+pub struct StrategyContext<'a> {
+    data: &'a StrategyData,
+    name: &'a str,
+    instruments: &'a [u32],
+}
+
+impl StrategyContext<'_> {
+    /// Tokens of the strategy's configured instruments
+    pub fn instruments(&self) -> &[u32] {
+        self.instruments
+    }
+
+    pub fn instrument(&self, instrument_token: u32) -> Option<Instrument> {
+        self.data
+            .instruments
+            .get(&instrument_token)
+            .map(|entry| entry.value().clone())
+    }
+
+    pub fn quote(&self, instrument_token: u32) -> Option<TickData> {
+        self.data
+            .tick_data
+            .get(&instrument_token)
+            .map(|entry| entry.value().clone())
+    }
+
+    /// The account's position in one product, including whatever other strategies and
+    /// manual orders did
+    pub fn position(&self, instrument_token: u32, product: &str) -> Option<Position> {
+        self.data
+            .positions
+            .get(&(instrument_token, product.to_string()))
+            .map(|entry| entry.value().clone())
+    }
+
+    /// Net filled quantity of this strategy's own orders, across products
+    pub fn net_quantity(&self, instrument_token: u32) -> i32 {
+        self.data
+            .tag_positions
+            .read()
+            .get(self.name)
+            .map(|positions| {
+                positions
+                    .iter()
+                    .filter(|position| position.instrument_token == instrument_token)
+                    .map(|position| position.net_quantity)
+                    .sum()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The strategy's own orders that can still be cancelled
+    pub fn open_orders(&self) -> Vec<Order> {
+        self.data
+            .orders
+            .iter()
+            .filter(|order| order.tag.as_deref() == Some(self.name) && order.is_cancellable())
+            .map(|order| order.value().clone())
+            .collect()
+    }
+
+    /// Today's one-minute candles, oldest first; the last one is still forming
+    pub fn minute_candles(&self, instrument_token: u32) -> Vec<Candle> {
+        self.data
+            .candles
+            .get(&instrument_token)
+            .map(|series| series.candles().to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Day market order buying (positive) or selling (negative) `quantity`
+    /// None until the instrument master has the instrument
+    pub fn market_order(
+        &self,
+        instrument_token: u32,
+        quantity: i32,
+        product: &str,
+    ) -> Option<OrderRequest> {
+        let instrument = self.instrument(instrument_token)?;
+        Some(OrderRequest {
+            tradingsymbol: instrument.tradingsymbol,
+            exchange: instrument.exchange,
+            transaction_type: if quantity > 0 { "BUY" } else { "SELL" }.to_string(),
+            order_type: "MARKET".to_string(),
+            quantity: quantity.abs(),
+            price: None,
+            product: product.to_string(),
+            validity: "DAY".to_string(),
+            validity_ttl: None,
+            disclosed_quantity: None,
+            trigger_price: None,
+            squareoff: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            tag: None,
+            variety: "regular".to_string(),
+            bypass_margin_check: false,
+            request_id: None,
+        })
+    }
+
+    /// Submit an order tagged with the strategy's name
+    pub fn place_order(&self, details: OrderRequest) {
+        self.send(Command::PlaceOrder { details });
+    }
+
+    /// Cancel one of the strategy's own open orders
+    pub fn cancel_order(&self, order_id: &str) {
+        let variety = self
+            .data
+            .orders
+            .get(order_id)
+            .map_or_else(|| "regular".to_string(), |order| order.variety.clone());
+        self.send(Command::CancelOrder {
+            order_id: order_id.to_string(),
+            variety,
+        });
+    }
+
+    /// Show a message as a notification and in the logs
+    pub fn notify(&self, level: LogLevel, message: String) {
+        self.data.event_sender.send_notification(
+            level,
+            format!("{}: {}", self.name, message),
+            Some("strategy".to_string()),
+        );
+    }
+
+    fn send(&self, command: Command) {
+        let _ = self.data.event_sender.send(AppEvent::StrategyCommand {
+            strategy: self.name.to_string(),
+            command,
+        });
+    }
+}
@@ -0,0 +1,206 @@
+use super::{build, InstrumentRef, Strategy, StrategyConfig, StrategyContext, StrategyData};
+use crate::app::panic_message;
+use crate::data_structures::{LogLevel, Order, OrderStatus};
+use crate::state::{AppEvent, Command};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often strategies are fed; ticks arriving in between are conflated into the latest
+const RUN_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A configured strategy as the P&L view shows it; `enabled` is switched from there and
+/// read by the runner on every pass
+#[derive(Debug, Clone)]
+pub struct StrategyStatus {
+    pub name: String,
+    pub kind: String,
+    pub instruments: Vec<String>,
+    pub enabled: bool,
+    /// Why the strategy turned itself off: it failed to build, or panicked
+    pub error: Option<String>,
+}
+
+/// Feeds every configured strategy its instruments' latest ticks, updates to its own
+/// orders and its timer, on a task of its own
+pub struct StrategyRunner {
+    data: StrategyData,
+    statuses: Arc<RwLock<Vec<StrategyStatus>>>,
+    strategies: Vec<RunningStrategy>,
+}
+
+struct RunningStrategy {
+    // Position in the status list
+    index: usize,
+    name: String,
+    strategy: Box<dyn Strategy>,
+    // Tokens resolved so far; symbols wait for the instrument master
+    tokens: Vec<u32>,
+    unresolved: Vec<String>,
+    // Last tick timestamp fed per instrument, and last status and fill seen per order
+    ticks_seen: HashMap<u32, i64>,
+    orders_seen: HashMap<String, (OrderStatus, i32)>,
+    timer_at: Instant,
+}
+
+impl StrategyRunner {
+    /// Build the configured strategies and start feeding them, returning the status list
+    /// shared with AppState; entries that fail to build are listed, disabled, with the error
+    pub fn start(
+        configs: &[StrategyConfig],
+        data: StrategyData,
+    ) -> Arc<RwLock<Vec<StrategyStatus>>> {
+        let mut statuses = Vec::new();
+        let mut strategies = Vec::new();
+        for config in configs {
+            let built = build(config);
+            statuses.push(StrategyStatus {
+                name: config.name.clone(),
+                kind: config.kind.clone(),
+                instruments: config.instruments.clone(),
+                enabled: config.enabled && built.is_ok(),
+                error: built.as_ref().err().cloned(),
+            });
+            if let Ok(strategy) = built {
+                strategies.push(RunningStrategy {
+                    index: statuses.len() - 1,
+                    name: config.name.clone(),
+                    strategy,
+                    tokens: Vec::new(),
+                    unresolved: config.instruments.clone(),
+                    ticks_seen: HashMap::new(),
+                    orders_seen: HashMap::new(),
+                    timer_at: Instant::now(),
+                });
+            }
+        }
+
+        let statuses = Arc::new(RwLock::new(statuses));
+        if strategies.is_empty() {
+            return statuses;
+        }
+
+        let mut runner = Self {
+            data,
+            statuses: Arc::clone(&statuses),
+            strategies,
+        };
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RUN_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                runner.run_pass();
+            }
+        });
+        statuses
+    }
+
+    /// Give every enabled strategy whatever changed since the last pass
+    fn run_pass(&mut self) {
+        for running in &mut self.strategies {
+            if !self.statuses.read()[running.index].enabled {
+                // The timer starts over when the strategy is switched back on
+                running.timer_at = Instant::now();
+                continue;
+            }
+            running.resolve_instruments(&self.data);
+
+            let result = catch_unwind(AssertUnwindSafe(|| running.feed(&self.data)));
+            if let Err(payload) = result {
+                let message = panic_message(payload);
+                let mut statuses = self.statuses.write();
+                let status = &mut statuses[running.index];
+                status.enabled = false;
+                status.error = Some(format!("panicked: {}", message));
+                self.data.event_sender.send_notification(
+                    LogLevel::Error,
+                    format!(
+                        "Strategy {} panicked and was disabled: {}",
+                        running.name, message
+                    ),
+                    Some("strategy".to_string()),
+                );
+            }
+        }
+    }
+}
+
+impl RunningStrategy {
+    /// Look up symbols the instrument master now has, and subscribe to their ticks
+    fn resolve_instruments(&mut self, data: &StrategyData) {
+        if self.unresolved.is_empty() {
+            return;
+        }
+        let index = data.instrument_index.read();
+        let mut resolved = Vec::new();
+        self.unresolved.retain(|instrument| {
+            match InstrumentRef::parse(instrument).and_then(|reference| reference.resolve(&index)) {
+                Some(token) => {
+                    resolved.push(token);
+                    false
+                }
+                None => true,
+            }
+        });
+        if resolved.is_empty() {
+            return;
+        }
+
+        self.tokens.extend(&resolved);
+        let _ = data.event_sender.send(AppEvent::StrategyCommand {
+            strategy: self.name.clone(),
+            command: Command::SubscribeToTicks {
+                instrument_tokens: resolved,
+            },
+        });
+    }
+
+    /// Call the strategy for new ticks, changed orders and a due timer
+    fn feed(&mut self, data: &StrategyData) {
+        let ctx = StrategyContext {
+            data,
+            name: &self.name,
+            instruments: &self.tokens,
+        };
+
+        for &token in &self.tokens {
+            let Some(tick) = data.tick_data.get(&token).map(|tick| tick.value().clone()) else {
+                continue;
+            };
+            if self.ticks_seen.insert(token, tick.timestamp_nanos) == Some(tick.timestamp_nanos) {
+                continue;
+            }
+            self.strategy.on_tick(&tick, &ctx);
+        }
+
+        // Orders from earlier in the session are reported once, when the runner starts
+        let changed: Vec<Order> = data
+            .orders
+            .iter()
+            .filter(|order| order.tag.as_deref() == Some(self.name.as_str()))
+            .filter(|order| {
+                self.orders_seen.get(&order.order_id)
+                    != Some(&(order.status.clone(), order.filled_quantity))
+            })
+            .map(|order| order.value().clone())
+            .collect();
+        for order in changed {
+            self.orders_seen.insert(
+                order.order_id.clone(),
+                (order.status.clone(), order.filled_quantity),
+            );
+            self.strategy.on_order_update(&order, &ctx);
+        }
+
+        if let Some(interval) = self.strategy.timer_interval() {
+            let elapsed = self.timer_at.elapsed();
+            if elapsed >= interval {
+                self.timer_at = Instant::now();
+                self.strategy.on_timer(elapsed, &ctx);
+            }
+        }
+    }
+}
@@ -0,0 +1,497 @@
+use crate::candles::{Candle, CandleInterval};
+use crate::data_structures::Position;
+use crate::state::{AppState, Command, ExitCharges, OrderType};
+use crate::ui::positions::exit_order;
+use chrono_tz::Asia::Kolkata;
+use egui::{Align2, Color32, FontId, Pos2, Rect, RichText, Sense, Shape, Stroke, Ui};
+use std::str::FromStr;
+
+const AVERAGE_COLOR: Color32 = Color32::from_rgb(59, 130, 246);
+const BREAKEVEN_COLOR: Color32 = Color32::from_rgb(234, 179, 8);
+const LIMIT_COLOR: Color32 = Color32::from_rgb(168, 85, 247);
+const STOP_COLOR: Color32 = Color32::from_rgb(249, 115, 22);
+const CHART_HEIGHT: f32 = 460.0;
+const MIN_VISIBLE_BARS: usize = 10;
+
+/// Render an intraday candlestick chart for the selected instrument
+/// Scroll to zoom, drag to pan, hover for the bar's OHLC
+pub fn render_charts(ui: &mut Ui, app_state: &mut AppState) {
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Charts").size(24.0).strong());
+            ui.add_space(20.0);
+            render_instrument_picker(ui, app_state);
+        });
+
+        ui.add_space(10.0);
+
+        let Some(token) = app_state.ui_input.selected_instrument_token else {
+            ui.centered_and_justified(|ui| {
+                ui.label(
+                    RichText::new("Select an instrument in Positions, Orders or Watchlist")
+                        .size(16.0)
+                        .color(Color32::GRAY),
+                );
+            });
+            return;
+        };
+
+        app_state.request_candle_history(token);
+
+        ui.horizontal(|ui| {
+            for interval in CandleInterval::ALL {
+                ui.selectable_value(
+                    &mut app_state.ui_input.chart_interval,
+                    interval,
+                    interval.label(),
+                );
+            }
+
+            ui.add_space(10.0);
+
+            if ui.button("Reset zoom").clicked() {
+                app_state.ui_input.chart_viewport = Default::default();
+            }
+
+            ui.add_space(20.0);
+            render_overlay_legend(ui, app_state);
+        });
+
+        ui.add_space(5.0);
+
+        let lines = price_lines(app_state, token);
+
+        let interval = app_state.ui_input.chart_interval;
+        let bars = match app_state.candles.get(&token) {
+            Some(series) => app_state
+                .ui_input
+                .chart_bars
+                .get(token, interval, &series)
+                .to_vec(),
+            None => Vec::new(),
+        };
+
+        if bars.is_empty() {
+            ui.label(RichText::new("Waiting for candles…").color(Color32::GRAY));
+            return;
+        }
+
+        render_candlestick_chart(ui, app_state, &bars, &lines);
+    });
+}
+
+/// Horizontal price level drawn across the chart, e.g. the average price or an order
+struct PriceLine {
+    price: f64,
+    label: String,
+    color: Color32,
+    dashed: bool,
+}
+
+/// Toggles for the overlay lines, colored like the lines they control
+fn render_overlay_legend(ui: &mut Ui, app_state: &mut AppState) {
+    let breakeven_hint = match app_state
+        .ui_input
+        .exit_charges
+        .as_ref()
+        .and_then(|charges| charges.result.as_ref())
+    {
+        Some(Ok(charges)) => format!(
+            "Average price plus round-trip charges of about ₹{:.2}",
+            charges * 2.0
+        ),
+        Some(Err(e)) => format!("Charges estimate unavailable: {}", e),
+        None => "Average price plus estimated round-trip charges".to_string(),
+    };
+
+    let overlays = &mut app_state.ui_input.chart_overlays;
+    ui.checkbox(
+        &mut overlays.average,
+        RichText::new("Avg").color(AVERAGE_COLOR),
+    );
+    ui.checkbox(
+        &mut overlays.breakeven,
+        RichText::new("Breakeven").color(BREAKEVEN_COLOR),
+    )
+    .on_hover_text(breakeven_hint);
+    ui.checkbox(
+        &mut overlays.orders,
+        RichText::new("Orders").color(LIMIT_COLOR),
+    )
+    .on_hover_text("Open limit and stop-loss orders");
+}
+
+/// Lines for the charted instrument's open position and working orders
+/// Rebuilt every frame, so they follow fills and order changes as they arrive
+fn price_lines(app_state: &mut AppState, token: u32) -> Vec<PriceLine> {
+    let overlays = app_state.ui_input.chart_overlays;
+    let mut lines = Vec::new();
+
+    let open: Vec<Position> = app_state
+        .positions_of(token)
+        .into_iter()
+        .filter(|position| position.quantity != 0)
+        .collect();
+
+    if overlays.average {
+        for position in &open {
+            // Name the product only when several are held, e.g. a CNC holding and an MIS trade
+            let label = if open.len() > 1 {
+                format!("Avg {} {:.2}", position.product, position.average_price)
+            } else {
+                format!("Avg {:.2}", position.average_price)
+            };
+            lines.push(PriceLine {
+                price: position.average_price,
+                label,
+                color: AVERAGE_COLOR,
+                dashed: false,
+            });
+        }
+    }
+    // The exit charges preview is kept for one position, so breakeven follows the first
+    if let Some(position) = open.first() {
+        if overlays.breakeven {
+            if let Some(breakeven) = breakeven_price(app_state, position) {
+                lines.push(PriceLine {
+                    price: breakeven,
+                    label: format!("Breakeven {:.2}", breakeven),
+                    color: BREAKEVEN_COLOR,
+                    dashed: false,
+                });
+            }
+        }
+    }
+
+    if overlays.orders {
+        for order in app_state.orders.iter() {
+            if order.instrument_token != token || !order.is_cancellable() {
+                continue;
+            }
+            let order_type = OrderType::from_str(&order.order_type).unwrap_or_default();
+            let (price, color, kind) = match order_type {
+                OrderType::Market => continue,
+                OrderType::Limit => (order.price, LIMIT_COLOR, "LMT"),
+                OrderType::StopLoss => (order.trigger_price, STOP_COLOR, "SL"),
+                OrderType::StopLossMarket => (order.trigger_price, STOP_COLOR, "SL-M"),
+            };
+            if price <= 0.0 {
+                continue;
+            }
+            lines.push(PriceLine {
+                price,
+                label: format!(
+                    "{} {} {} @ {:.2}",
+                    order.transaction_type, kind, order.pending_quantity, price
+                ),
+                color,
+                dashed: true,
+            });
+        }
+    }
+
+    lines
+}
+
+/// Price at which closing the position covers its estimated charges both ways
+/// The exit's charges are previewed once per position size and counted twice for the entry
+fn breakeven_price(app_state: &mut AppState, position: &Position) -> Option<f64> {
+    let current = app_state
+        .ui_input
+        .exit_charges
+        .as_ref()
+        .is_some_and(|charges| {
+            charges.instrument_token == position.instrument_token
+                && charges.quantity == position.quantity.abs()
+        });
+    if !current {
+        let quantity = position.quantity.abs();
+        app_state.ui_input.exit_charges = Some(ExitCharges {
+            instrument_token: position.instrument_token,
+            quantity,
+            result: None,
+        });
+        app_state.send_command(Command::EstimateExitCharges {
+            instrument_token: position.instrument_token,
+            details: exit_order(position, quantity, "breakeven_estimate"),
+        });
+        return None;
+    }
+
+    let charges = app_state
+        .ui_input
+        .exit_charges
+        .as_ref()
+        .and_then(|charges| charges.result.clone())
+        .and_then(Result::ok)?;
+    let per_unit = charges * 2.0 / position.quantity.abs() as f64;
+    Some(position.average_price + per_unit * position.quantity.signum() as f64)
+}
+
+/// Pick the charted instrument from current positions and the watchlist
+fn render_instrument_picker(ui: &mut Ui, app_state: &mut AppState) {
+    let mut choices: Vec<(u32, String)> = Vec::new();
+    for entry in app_state.positions.iter() {
+        if choices
+            .iter()
+            .all(|(existing, _)| *existing != entry.instrument_token)
+        {
+            choices.push((entry.instrument_token, entry.tradingsymbol.clone()));
+        }
+    }
+    for token in app_state.watchlist.read().tokens() {
+        if choices.iter().any(|(existing, _)| *existing == token) {
+            continue;
+        }
+        let symbol = app_state
+            .instruments
+            .get(&token)
+            .map(|instrument| instrument.tradingsymbol.clone())
+            .unwrap_or_else(|| token.to_string());
+        choices.push((token, symbol));
+    }
+    choices.sort_by(|a, b| a.1.cmp(&b.1));
+
+    let selected = app_state.ui_input.selected_instrument_token;
+    let selected_text = selected
+        .and_then(|token| choices.iter().find(|(t, _)| *t == token))
+        .map(|(_, symbol)| symbol.clone())
+        .unwrap_or_else(|| "Select instrument".to_string());
+
+    let mut picked = None;
+    egui::ComboBox::from_id_salt("chart_instrument")
+        .selected_text(selected_text)
+        .show_ui(ui, |ui| {
+            for (token, symbol) in &choices {
+                if ui
+                    .selectable_label(selected == Some(*token), symbol)
+                    .clicked()
+                {
+                    picked = Some(*token);
+                }
+            }
+        });
+
+    if let Some(token) = picked {
+        app_state.select_instrument(token);
+    }
+}
+
+fn render_candlestick_chart(
+    ui: &mut Ui,
+    app_state: &mut AppState,
+    bars: &[Candle],
+    lines: &[PriceLine],
+) {
+    let theme = app_state.theme;
+    let size = egui::vec2(ui.available_width(), CHART_HEIGHT);
+    let (rect, response) = ui.allocate_exact_size(size, Sense::click_and_drag());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+
+    let viewport = &mut app_state.ui_input.chart_viewport;
+    let total = bars.len();
+
+    // Scroll zooms around the latest bar, drag pans through history
+    if response.hovered() {
+        let scroll = ui.input(|input| input.smooth_scroll_delta.y);
+        if scroll != 0.0 {
+            let factor = if scroll > 0.0 { 0.9 } else { 1.1 };
+            viewport.visible_bars = ((viewport.visible_bars as f32 * factor).round() as usize)
+                .clamp(MIN_VISIBLE_BARS, total.max(MIN_VISIBLE_BARS));
+        }
+    }
+
+    let visible = viewport.visible_bars.min(total).max(1);
+    let bar_width = rect.width() / visible as f32;
+
+    if response.dragged() {
+        viewport.bars_from_end += response.drag_delta().x / bar_width;
+    }
+    viewport.bars_from_end = viewport
+        .bars_from_end
+        .clamp(0.0, total.saturating_sub(visible) as f32);
+
+    let end = total - viewport.bars_from_end.round() as usize;
+    let start = end.saturating_sub(visible);
+    let window = &bars[start..end];
+
+    let price_rect = Rect::from_min_max(
+        rect.min,
+        egui::pos2(rect.max.x, rect.min.y + rect.height() * 0.75),
+    );
+    let volume_rect = Rect::from_min_max(egui::pos2(rect.min.x, price_rect.max.y + 4.0), rect.max);
+
+    let (low, high) = window
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(low, high), bar| {
+            (low.min(bar.low), high.max(bar.high))
+        });
+    let range = (high - low).max(f64::EPSILON);
+    let price_y = |price: f64| {
+        price_rect.bottom() - ((price - low) / range) as f32 * (price_rect.height() - 8.0) - 4.0
+    };
+    let max_volume = window
+        .iter()
+        .map(|bar| bar.volume)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    for (i, bar) in window.iter().enumerate() {
+        let center_x = rect.left() + (i as f32 + 0.5) * bar_width;
+        let color = theme.direction_color(bar.close >= bar.open);
+
+        // Wick then body
+        painter.line_segment(
+            [
+                egui::pos2(center_x, price_y(bar.high)),
+                egui::pos2(center_x, price_y(bar.low)),
+            ],
+            Stroke::new(1.0, color),
+        );
+        let body_top = price_y(bar.open.max(bar.close));
+        let body_bottom = price_y(bar.open.min(bar.close)).max(body_top + 1.0);
+        let half_body = (bar_width * 0.35).max(0.5);
+        painter.rect_filled(
+            Rect::from_min_max(
+                egui::pos2(center_x - half_body, body_top),
+                egui::pos2(center_x + half_body, body_bottom),
+            ),
+            0.0,
+            color,
+        );
+
+        let volume_height = bar.volume as f32 / max_volume as f32 * volume_rect.height();
+        painter.rect_filled(
+            Rect::from_min_max(
+                egui::pos2(center_x - half_body, volume_rect.bottom() - volume_height),
+                egui::pos2(center_x + half_body, volume_rect.bottom()),
+            ),
+            0.0,
+            color.gamma_multiply(0.4),
+        );
+    }
+
+    // Price scale
+    let label_color = ui.visuals().weak_text_color();
+    for price in [high, low] {
+        painter.text(
+            egui::pos2(rect.right() - 4.0, price_y(price)),
+            Align2::RIGHT_CENTER,
+            format!("{:.2}", price),
+            FontId::monospace(11.0),
+            label_color,
+        );
+    }
+
+    // Lines outside the visible range are pinned, stacked, to the edge they lie beyond;
+    // the top stack starts under the crosshair readout
+    let (mut above, mut below) = (0.0, 0.0);
+    for line in lines {
+        let font = FontId::monospace(11.0);
+        if line.price > high {
+            painter.text(
+                egui::pos2(price_rect.left() + 4.0, price_rect.top() + 24.0 + above),
+                Align2::LEFT_TOP,
+                format!("▲ {}", line.label),
+                font,
+                line.color,
+            );
+            above += 14.0;
+            continue;
+        }
+        if line.price < low {
+            painter.text(
+                egui::pos2(price_rect.left() + 4.0, price_rect.bottom() - 2.0 - below),
+                Align2::LEFT_BOTTOM,
+                format!("▼ {}", line.label),
+                font,
+                line.color,
+            );
+            below += 14.0;
+            continue;
+        }
+
+        let y = price_y(line.price);
+        let stroke = Stroke::new(1.0, line.color);
+        if line.dashed {
+            painter.extend(Shape::dashed_line(
+                &[
+                    egui::pos2(price_rect.left(), y),
+                    egui::pos2(price_rect.right(), y),
+                ],
+                stroke,
+                6.0,
+                4.0,
+            ));
+        } else {
+            painter.hline(price_rect.x_range(), y, stroke);
+        }
+        painter.text(
+            egui::pos2(rect.left() + 4.0, y - 2.0),
+            Align2::LEFT_BOTTOM,
+            &line.label,
+            font,
+            line.color,
+        );
+    }
+
+    if let Some(pointer) = response.hover_pos() {
+        render_crosshair(
+            &painter, rect, pointer, window, bar_width, low, range, price_rect,
+        );
+    }
+}
+
+/// Crosshair lines plus an OHLCV readout of the bar under the pointer
+#[allow(clippy::too_many_arguments)]
+fn render_crosshair(
+    painter: &egui::Painter,
+    rect: Rect,
+    pointer: Pos2,
+    window: &[Candle],
+    bar_width: f32,
+    low: f64,
+    range: f64,
+    price_rect: Rect,
+) {
+    let stroke = Stroke::new(1.0, Color32::from_gray(120));
+    painter.vline(pointer.x, rect.y_range(), stroke);
+    painter.hline(rect.x_range(), pointer.y, stroke);
+
+    if price_rect.contains(pointer) {
+        let price = low
+            + ((price_rect.bottom() - 4.0 - pointer.y) / (price_rect.height() - 8.0)) as f64
+                * range;
+        painter.text(
+            egui::pos2(rect.right() - 4.0, pointer.y - 2.0),
+            Align2::RIGHT_BOTTOM,
+            format!("{:.2}", price),
+            FontId::monospace(11.0),
+            Color32::WHITE,
+        );
+    }
+
+    let index = ((pointer.x - rect.left()) / bar_width) as usize;
+    let Some(bar) = window.get(index) else {
+        return;
+    };
+
+    let readout = format!(
+        "{}  O {:.2}  H {:.2}  L {:.2}  C {:.2}  V {}",
+        bar.start.with_timezone(&Kolkata).format("%H:%M"),
+        bar.open,
+        bar.high,
+        bar.low,
+        bar.close,
+        bar.volume
+    );
+    painter.text(
+        rect.left_top() + egui::vec2(6.0, 6.0),
+        Align2::LEFT_TOP,
+        readout,
+        FontId::monospace(12.0),
+        Color32::WHITE,
+    );
+}
@@ -0,0 +1,92 @@
+use crate::state::{AppState, ColumnLayout, SortState, TableColumn};
+use crate::ui::components::sort_header;
+use egui::Ui;
+
+/// One table column: its header, a width hint, and how to draw a row's cell
+/// `X` carries whatever the cells need beyond the row itself, such as table totals
+pub struct ColumnSpec<C, T, X = ()> {
+    pub id: C,
+    pub header: &'static str,
+    pub width: f32,
+    pub render: fn(&mut Ui, &T, &mut AppState, &X),
+}
+
+/// Spec for a column id; every id in `TableColumn::ALL` must have one
+pub fn column_spec<C: TableColumn, T, X>(
+    specs: &[ColumnSpec<C, T, X>],
+    id: C,
+) -> &ColumnSpec<C, T, X> {
+    specs
+        .iter()
+        .find(|spec| spec.id == id)
+        .expect("every table column has a spec")
+}
+
+/// Sortable headers for the visible columns, each at least its width hint wide
+pub fn column_headers<C: TableColumn, T, X>(
+    ui: &mut Ui,
+    specs: &[ColumnSpec<C, T, X>],
+    layout: &ColumnLayout<C>,
+    sort: &mut SortState<C>,
+) {
+    for id in layout.visible() {
+        let spec = column_spec(specs, id);
+        ui.horizontal(|ui| {
+            ui.set_min_width(spec.width);
+            sort_header(ui, spec.header, id, sort);
+        });
+    }
+}
+
+/// Cells of the visible columns for one row
+pub fn column_cells<C: TableColumn, T, X>(
+    ui: &mut Ui,
+    specs: &[ColumnSpec<C, T, X>],
+    layout: &ColumnLayout<C>,
+    row: &T,
+    app_state: &mut AppState,
+    extra: &X,
+) {
+    for id in layout.visible() {
+        (column_spec(specs, id).render)(ui, row, app_state, extra);
+    }
+}
+
+/// ⚙ popover to show, hide and reorder a table's columns
+pub fn column_menu<C: TableColumn, T, X>(
+    ui: &mut Ui,
+    specs: &[ColumnSpec<C, T, X>],
+    layout: &mut ColumnLayout<C>,
+) {
+    ui.menu_button("⚙ Columns", |ui| {
+        let count = layout.columns.len();
+        let mut moved = None;
+
+        for (index, (id, shown)) in layout.columns.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(index > 0, egui::Button::new("⏶").small())
+                    .clicked()
+                {
+                    moved = Some((index, false));
+                }
+                if ui
+                    .add_enabled(index + 1 < count, egui::Button::new("⏷").small())
+                    .clicked()
+                {
+                    moved = Some((index, true));
+                }
+                ui.checkbox(shown, column_spec(specs, *id).header);
+            });
+        }
+
+        if let Some((index, later)) = moved {
+            layout.move_column(index, later);
+        }
+
+        ui.separator();
+        if ui.button("Reset columns").clicked() {
+            *layout = ColumnLayout::default();
+        }
+    });
+}
@@ -0,0 +1,66 @@
+use crate::export::ExportFormat;
+use chrono::Local;
+use egui::Ui;
+use std::path::PathBuf;
+
+/// Render an export directory field with CSV/JSON buttons for each export kind
+/// Returns the clicked kind and a timestamped target path like `exports/positions-20260116-151500.csv`
+pub fn export_controls<'a>(
+    ui: &mut Ui,
+    export_dir: &mut String,
+    stems: &[&'a str],
+) -> Option<(&'a str, PathBuf)> {
+    let mut target = None;
+
+    ui.horizontal(|ui| {
+        ui.label("Export to:");
+        ui.add(
+            egui::TextEdit::singleline(export_dir)
+                .hint_text("directory (blank = current)")
+                .desired_width(160.0),
+        );
+
+        for &stem in stems {
+            for format in [ExportFormat::Csv, ExportFormat::Json] {
+                let extension = format.extension();
+                let label = if stems.len() > 1 {
+                    format!("⬇ {} {}", stem, extension.to_uppercase())
+                } else {
+                    format!("⬇ {}", extension.to_uppercase())
+                };
+
+                if ui.button(label).clicked() {
+                    let file_name = format!(
+                        "{}-{}.{}",
+                        stem,
+                        Local::now().format("%Y%m%d-%H%M%S"),
+                        extension
+                    );
+                    target = Some((stem, PathBuf::from(export_dir.trim()).join(file_name)));
+                }
+            }
+        }
+    });
+
+    target
+}
+
+/// Native save dialog for a CSV export, suggesting a timestamped file name
+/// Blocks the UI thread until the dialog closes; returns None when cancelled
+pub fn choose_export_path(stem: &str) -> Option<PathBuf> {
+    choose_save_path(stem, "CSV", "csv")
+}
+
+/// Save dialog for a `stem-<timestamp>.<extension>` file of the given kind
+pub fn choose_save_path(stem: &str, kind: &str, extension: &str) -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .set_title(format!("Export {}", stem))
+        .set_file_name(format!(
+            "{}-{}.{}",
+            stem,
+            Local::now().format("%Y%m%d-%H%M%S"),
+            extension
+        ))
+        .add_filter(kind, &[extension])
+        .save_file()
+}
@@ -1,5 +1,19 @@
+pub mod columns;
+pub mod export;
 pub mod modal;
+pub mod price_flash;
+pub mod refresh_countdown;
+pub mod sort_header;
+pub mod sparkline;
 pub mod styled_button;
+pub mod toast;
 
+pub use columns::*;
+pub use export::*;
 pub use modal::*;
+pub use price_flash::*;
+pub use refresh_countdown::*;
+pub use sort_header::*;
+pub use sparkline::*;
 pub use styled_button::*;
+pub use toast::*;
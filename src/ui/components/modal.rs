@@ -1,4 +1,7 @@
-use egui::{Align2, Area, Color32, Frame, Id, Order, Pos2, Response, Sense, Ui, Vec2, Window};
+use egui::{
+    Align2, Area, Color32, Frame, Id, Key, LayerId, Modifiers, Order, Pos2, Response, Sense, Ui,
+    Vec2, Window,
+};
 
 /// High-performance modal component for trading dialogs
 /// Optimized for responsive interactions and minimal latency
@@ -11,6 +14,13 @@ pub struct Modal {
     backdrop_color: Color32,
 }
 
+/// What a modal returned this frame, and whether the user dismissed it
+/// Callers drop their open flag or form state when `closed` is set
+pub struct ModalResponse<R> {
+    pub inner: Option<R>,
+    pub closed: bool,
+}
+
 impl Modal {
     /// Create new modal dialog
     pub fn new(id: impl Into<Id>, title: impl Into<String>) -> Self {
@@ -48,26 +58,44 @@ impl Modal {
         self
     }
 
+    /// Whether the modal was not shown on the previous frame
+    fn is_opening(&self, ctx: &egui::Context) -> bool {
+        let last_shown = ctx.data(|data| data.get_temp::<u64>(self.id.with("last_shown")));
+        last_shown.is_none_or(|pass| pass + 1 < ctx.cumulative_pass_nr())
+    }
+
     /// Show modal with content
+    /// The backdrop swallows input to everything behind the dialog, and a closable modal
+    /// also closes on Esc or its title bar ✕
     pub fn show<R>(
         self,
         ctx: &egui::Context,
         add_contents: impl FnOnce(&mut Ui) -> R,
-    ) -> Option<R> {
+    ) -> ModalResponse<R> {
         let screen_rect = ctx.screen_rect();
+        let opening = self.is_opening(ctx);
+        ctx.data_mut(|data| data.insert_temp(self.id.with("last_shown"), ctx.cumulative_pass_nr()));
 
-        // Draw backdrop
-        Area::new("modal_backdrop".into())
-            .order(Order::Background)
+        // Draw backdrop; as the modal layer it blocks focus and clicks on the layers below it
+        let backdrop = Area::new(self.id.with("backdrop"))
+            .order(Order::Foreground)
+            .interactable(true)
+            .fixed_pos(screen_rect.min)
             .show(ctx, |ui| {
-                let backdrop_rect = screen_rect;
-                ui.allocate_response(backdrop_rect.size(), Sense::click());
-
-                let painter = ui.painter();
-                painter.rect_filled(backdrop_rect, 0.0, self.backdrop_color);
+                let response = ui.allocate_response(screen_rect.size(), Sense::click_and_drag());
+                ui.painter()
+                    .rect_filled(screen_rect, 0.0, self.backdrop_color);
+                response
             });
+        ctx.memory_mut(|memory| memory.set_modal_layer(backdrop.response.layer_id));
+
+        if opening {
+            // Whatever had focus behind the dialog would otherwise keep taking keystrokes
+            ctx.memory_mut(|memory| memory.stop_text_input());
+        }
 
         // Show modal window
+        let mut open = true;
         let mut window = Window::new(&self.title)
             .id(self.id)
             .order(Order::Foreground)
@@ -79,20 +107,42 @@ impl Modal {
             window = window.fixed_size(size);
         }
 
-        if !self.closable {
+        if self.closable {
+            window = window.open(&mut open);
+        } else {
             window = window.title_bar(false);
         }
 
-        let mut result = None;
+        let mut inner = None;
 
-        window.show(ctx, |ui| {
-            result = Some(add_contents(ui));
+        let shown = window.show(ctx, |ui| {
+            inner = Some(add_contents(ui));
         });
 
-        result
+        // Pressing the backdrop raises it, so put the dialog back on top
+        if let Some(shown) = shown {
+            if opening || backdrop.inner.is_pointer_button_down_on() || backdrop.inner.clicked() {
+                ctx.move_to_top(shown.response.layer_id);
+            }
+        }
+
+        let escaped = self.closable
+            && self.is_top(ctx, backdrop.response.layer_id)
+            && ctx.input_mut(|input| input.consume_key(Modifiers::NONE, Key::Escape));
+
+        ModalResponse {
+            inner,
+            closed: !open || escaped,
+        }
+    }
+
+    /// Whether this is the front-most modal, so a key press goes to one dialog only
+    fn is_top(&self, ctx: &egui::Context, backdrop: LayerId) -> bool {
+        ctx.memory(|memory| memory.top_modal_layer()) == Some(backdrop)
     }
 
     /// Show confirmation modal
+    /// Enter confirms, while Esc or the ✕ cancels
     pub fn confirmation<R>(
         ctx: &egui::Context,
         id: impl Into<Id>,
@@ -102,34 +152,43 @@ impl Modal {
         on_cancel: impl FnOnce() -> R,
     ) -> Option<R> {
         let modal = Modal::new(id, title).size(Vec2::new(400.0, 150.0));
+        let opening = modal.is_opening(ctx);
+        let backdrop = LayerId::new(Order::Foreground, modal.id.with("backdrop"));
+        let entered = modal.is_top(ctx, backdrop)
+            && ctx.input_mut(|input| input.consume_key(Modifiers::NONE, Key::Enter));
 
-        modal
-            .show(ctx, |ui| {
-                ui.vertical_centered(|ui| {
-                    ui.add_space(10.0);
-                    ui.label(message.into());
-                    ui.add_space(20.0);
+        let response = modal.show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(10.0);
+                ui.label(message.into());
+                ui.add_space(20.0);
 
-                    ui.horizontal(|ui| {
-                        ui.add_space(50.0);
+                ui.horizontal(|ui| {
+                    ui.add_space(50.0);
 
-                        if ui.button("Confirm").clicked() {
-                            return Some(on_confirm());
-                        }
+                    let confirm = ui.button("Confirm");
+                    if opening {
+                        confirm.request_focus();
+                    }
 
-                        ui.add_space(20.0);
+                    ui.add_space(20.0);
 
-                        if ui.button("Cancel").clicked() {
-                            return Some(on_cancel());
-                        }
+                    let cancel = ui.button("Cancel");
 
-                        None
-                    })
-                    .inner
+                    (confirm.clicked(), cancel.clicked())
                 })
                 .inner
             })
-            .flatten()
+            .inner
+        });
+
+        match response.inner {
+            _ if response.closed => Some(on_cancel()),
+            Some((true, _)) => Some(on_confirm()),
+            _ if entered => Some(on_confirm()),
+            Some((_, true)) => Some(on_cancel()),
+            _ => None,
+        }
     }
 
     /// Show error modal
@@ -141,32 +200,32 @@ impl Modal {
     ) -> bool {
         let modal = Modal::new(id, title).size(Vec2::new(450.0, 200.0));
 
-        modal
-            .show(ctx, |ui| {
-                ui.vertical_centered(|ui| {
-                    ui.add_space(10.0);
-
-                    // Error icon (red circle with X)
-                    let painter = ui.painter();
-                    let icon_center = ui.cursor().center() + Vec2::new(0.0, 15.0);
-                    painter.circle_filled(icon_center, 20.0, Color32::from_rgb(239, 68, 68));
-                    painter.text(
-                        icon_center,
-                        Align2::CENTER_CENTER,
-                        "✗",
-                        egui::FontId::proportional(24.0),
-                        Color32::WHITE,
-                    );
-
-                    ui.add_space(40.0);
-                    ui.label(error_message.into());
-                    ui.add_space(20.0);
+        let response = modal.show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(10.0);
 
-                    ui.button("OK").clicked()
-                })
-                .inner
+                // Error icon (red circle with X)
+                let painter = ui.painter();
+                let icon_center = ui.cursor().center() + Vec2::new(0.0, 15.0);
+                painter.circle_filled(icon_center, 20.0, Color32::from_rgb(239, 68, 68));
+                painter.text(
+                    icon_center,
+                    Align2::CENTER_CENTER,
+                    "✗",
+                    egui::FontId::proportional(24.0),
+                    Color32::WHITE,
+                );
+
+                ui.add_space(40.0);
+                ui.label(error_message.into());
+                ui.add_space(20.0);
+
+                ui.button("OK").clicked()
             })
-            .unwrap_or(false)
+            .inner
+        });
+
+        response.closed || response.inner.unwrap_or(false)
     }
 
     /// Show loading modal
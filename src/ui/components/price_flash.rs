@@ -0,0 +1,32 @@
+use crate::state::PriceFlashes;
+use crate::ui::theme::Theme;
+use egui::{Color32, RichText, Ui};
+use std::time::Duration;
+
+/// Price label whose background flashes in the profit/loss color and fades after the price moves
+/// Only requests repaints while a flash is fading, so idle tables cost nothing
+pub fn flashing_price(
+    ui: &mut Ui,
+    flashes: &mut PriceFlashes,
+    theme: &Theme,
+    instrument_token: u32,
+    price: f64,
+    text: RichText,
+) {
+    let flash = flashes.observe(instrument_token, price);
+    let fill = match flash {
+        Some((up, intensity)) => theme.direction_color(up).gamma_multiply(intensity * 0.5),
+        None => Color32::TRANSPARENT,
+    };
+
+    egui::Frame::new()
+        .fill(fill)
+        .inner_margin(egui::Margin::symmetric(4, 1))
+        .show(ui, |ui| {
+            ui.label(text);
+        });
+
+    if flash.is_some() {
+        ui.ctx().request_repaint_after(Duration::from_millis(16));
+    }
+}
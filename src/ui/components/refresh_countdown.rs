@@ -0,0 +1,13 @@
+use egui::{Color32, RichText, Ui};
+use std::time::Duration;
+
+/// "next refresh in 12s" while a background refresh is running
+pub fn refresh_countdown(ui: &mut Ui, remaining: Option<Duration>) {
+    if let Some(remaining) = remaining {
+        ui.label(
+            RichText::new(format!("next refresh in {}s", remaining.as_secs()))
+                .small()
+                .color(Color32::GRAY),
+        );
+    }
+}
@@ -0,0 +1,24 @@
+use crate::state::SortState;
+use egui::{RichText, Ui};
+
+/// Render a clickable column header with a ▲/▼ marker on the active sort column
+pub fn sort_header<C: Copy + PartialEq>(
+    ui: &mut Ui,
+    label: &str,
+    column: C,
+    sort: &mut SortState<C>,
+) {
+    let text = if sort.column == column {
+        let arrow = if sort.descending { "▼" } else { "▲" };
+        format!("{} {}", label, arrow)
+    } else {
+        label.to_string()
+    };
+
+    if ui
+        .selectable_label(sort.column == column, RichText::new(text).strong())
+        .clicked()
+    {
+        sort.toggle(column);
+    }
+}
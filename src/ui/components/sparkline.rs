@@ -0,0 +1,37 @@
+use crate::ui::theme::Theme;
+use egui::{Color32, Response, Sense, Stroke, Ui, Vec2};
+
+/// Minimal line chart of recent values, in the profit color when the series ends above where it started
+pub fn sparkline(ui: &mut Ui, values: &[f64], size: Vec2, theme: &Theme) -> Response {
+    let rising = values.len() >= 2 && values[values.len() - 1] >= values[0];
+    sparkline_colored(ui, values, size, theme.direction_color(rising))
+}
+
+/// Sparkline in a fixed color, for series where up is not good or bad
+pub fn sparkline_colored(ui: &mut Ui, values: &[f64], size: Vec2, color: Color32) -> Response {
+    let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+
+    if values.len() < 2 || !ui.is_rect_visible(rect) {
+        return response;
+    }
+
+    let (min, max) = values.iter().fold((f64::MAX, f64::MIN), |(min, max), &v| {
+        (min.min(v), max.max(v))
+    });
+    let range = (max - min).max(f64::EPSILON);
+    let step = rect.width() / (values.len() - 1) as f32;
+
+    let points: Vec<egui::Pos2> = values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| {
+            let y = rect.bottom() - ((v - min) / range) as f32 * rect.height();
+            egui::pos2(rect.left() + i as f32 * step, y)
+        })
+        .collect();
+
+    ui.painter()
+        .add(egui::Shape::line(points, Stroke::new(1.5, color)));
+
+    response
+}
@@ -1,4 +1,9 @@
-use egui::{Button, Color32, Response, Ui, Vec2};
+use egui::response::Flags;
+use egui::{Button, Color32, Response, Spinner, Ui, Vec2};
+use std::time::{Duration, Instant};
+
+/// Clicks on an order button closer together than this place one order
+pub const ORDER_BUTTON_DEBOUNCE: Duration = Duration::from_millis(750);
 
 /// High-performance styled button component for trading actions
 /// Optimized for minimal allocations and fast rendering
@@ -7,6 +12,9 @@ pub struct StyledButton {
     style: ButtonStyle,
     size: Option<Vec2>,
     enabled: bool,
+    loading: bool,
+    debounce: Option<Duration>,
+    disabled_reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -28,6 +36,9 @@ impl StyledButton {
             style,
             size: None,
             enabled: true,
+            loading: false,
+            debounce: None,
+            disabled_reason: None,
         }
     }
 
@@ -43,6 +54,24 @@ impl StyledButton {
         self
     }
 
+    /// Disable the button while there is a reason, shown when hovering it
+    pub fn disabled_reason(mut self, reason: Option<&str>) -> Self {
+        self.disabled_reason = reason.map(str::to_string);
+        self
+    }
+
+    /// Show a spinner in place of the label and ignore clicks, e.g. while a request is in flight
+    pub fn loading(mut self, loading: bool) -> Self {
+        self.loading = loading;
+        self
+    }
+
+    /// Ignore clicks within `interval` of the last one accepted, so a double click acts once
+    pub fn debounce(mut self, interval: Duration) -> Self {
+        self.debounce = Some(interval);
+        self
+    }
+
     /// Render the button with optimized styling
     pub fn ui(self, ui: &mut Ui) -> Response {
         let (bg_color, text_color, hover_color) = self.get_colors();
@@ -56,13 +85,48 @@ impl StyledButton {
         style.visuals.widgets.hovered.fg_stroke.color = text_color;
         style.visuals.widgets.active.fg_stroke.color = text_color;
 
-        let mut button = Button::new(&self.text);
+        // Keep the label's footprint so the layout doesn't jump while loading
+        let label = if self.loading {
+            egui::RichText::new(&self.text).color(Color32::TRANSPARENT)
+        } else {
+            egui::RichText::new(&self.text)
+        };
+        let mut button = Button::new(label);
 
         if let Some(size) = self.size {
             button = button.min_size(size);
         }
 
-        ui.add_enabled(self.enabled, button)
+        let enabled = self.enabled && !self.loading && self.disabled_reason.is_none();
+        let mut response = ui.add_enabled(enabled, button);
+        if let Some(reason) = &self.disabled_reason {
+            response = response.on_disabled_hover_text(reason);
+        }
+
+        if self.loading {
+            let size = response.rect.height() - 2.0 * ui.spacing().button_padding.y;
+            Spinner::new().size(size).color(text_color).paint_at(
+                ui,
+                egui::Rect::from_center_size(response.rect.center(), Vec2::splat(size)),
+            );
+        }
+
+        if let Some(interval) = self.debounce {
+            if response.clicked() {
+                let id = response.id.with("last_click");
+                let now = Instant::now();
+                let last = ui.data(|data| data.get_temp::<Instant>(id));
+                if last.is_some_and(|last| now.duration_since(last) < interval) {
+                    response
+                        .flags
+                        .remove(Flags::CLICKED | Flags::FAKE_PRIMARY_CLICKED);
+                } else {
+                    ui.data_mut(|data| data.insert_temp(id, now));
+                }
+            }
+        }
+
+        response
     }
 
     /// Get color scheme for button style
@@ -107,6 +171,19 @@ impl StyledButton {
     }
 }
 
+/// Minimum size for a compact table-row button about `chars` characters wide
+/// Derived from the button font so rows reflow instead of clipping when text grows
+pub fn row_button_size(ui: &Ui, chars: usize) -> Vec2 {
+    let font = egui::TextStyle::Button.resolve(ui.style());
+    let char_width = ui.fonts(|fonts| fonts.glyph_width(&font, '0'));
+    let text_height = ui.text_style_height(&egui::TextStyle::Button);
+
+    Vec2::new(
+        chars as f32 * char_width + 2.0 * ui.spacing().button_padding.x,
+        text_height + 4.0,
+    )
+}
+
 /// Quick helper functions for common button types
 pub fn primary_button(text: impl Into<String>) -> StyledButton {
     StyledButton::new(text, ButtonStyle::Primary)
@@ -0,0 +1,60 @@
+use crate::data_structures::{LogLevel, Toast};
+use egui::{Align2, Area, Color32, Frame, Id, Order, RichText, Vec2};
+
+/// How long a toast stays on screen before it is dropped
+const TOAST_LIFETIME_SECS: i64 = 6;
+
+/// Render stacked toast notifications in the bottom-right corner
+/// Expired or dismissed toasts are removed from the queue
+pub fn render_toasts(ctx: &egui::Context, toasts: &mut Vec<Toast>) {
+    let now = chrono::Utc::now();
+    toasts.retain(|toast| {
+        now.signed_duration_since(toast.created_at).num_seconds() < TOAST_LIFETIME_SECS
+    });
+
+    if toasts.is_empty() {
+        return;
+    }
+
+    let mut dismissed = None;
+
+    Area::new(Id::new("toast_area"))
+        .order(Order::Foreground)
+        .anchor(Align2::RIGHT_BOTTOM, Vec2::new(-12.0, -40.0))
+        .show(ctx, |ui| {
+            for (index, toast) in toasts.iter().enumerate() {
+                let accent = level_color(toast.level);
+
+                Frame::popup(ui.style())
+                    .stroke(egui::Stroke::new(1.0, accent))
+                    .show(ui, |ui| {
+                        ui.set_max_width(360.0);
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(&toast.message).color(accent));
+                            if ui.small_button("✕").clicked() {
+                                dismissed = Some(index);
+                            }
+                        });
+                    });
+
+                ui.add_space(6.0);
+            }
+        });
+
+    if let Some(index) = dismissed {
+        toasts.remove(index);
+    }
+
+    // Keep repainting so toasts expire on time even without new data
+    ctx.request_repaint_after(std::time::Duration::from_millis(500));
+}
+
+/// Accent colour for a message of this severity
+pub fn level_color(level: LogLevel) -> Color32 {
+    match level {
+        LogLevel::Info => Color32::from_rgb(34, 197, 94),
+        LogLevel::Warning => Color32::from_rgb(245, 158, 11),
+        LogLevel::Error => Color32::from_rgb(239, 68, 68),
+        LogLevel::Debug => Color32::from_rgb(107, 114, 128),
+    }
+}
@@ -0,0 +1,153 @@
+use crate::data_structures::*;
+use crate::state::{AppState, Command, OrderType};
+use crate::ui::format::format_inr;
+use egui::{Align2, Color32, FontId, RichText, Sense, Ui};
+
+/// Render 5-level market depth for the selected instrument
+/// Clicking a level's price pre-fills the order dialog with it
+pub fn render_depth_panel(ui: &mut Ui, app_state: &mut AppState) {
+    let Some(token) = app_state.ui_input.selected_instrument_token else {
+        return;
+    };
+    let (bid_color, ask_color) = (app_state.theme.profit, app_state.theme.loss);
+
+    let instrument = app_state
+        .instruments
+        .get(&token)
+        .map(|entry| entry.value().clone());
+    let symbol = instrument
+        .as_ref()
+        .map(|instrument| instrument.tradingsymbol.clone())
+        .or_else(|| {
+            app_state
+                .positions_of(token)
+                .first()
+                .map(|position| position.tradingsymbol.clone())
+        })
+        .unwrap_or_else(|| token.to_string());
+
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(format!("Depth · {}", symbol))
+                .size(18.0)
+                .strong(),
+        );
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.small_button("✕").clicked() {
+                // Drop back to quote mode so the feed stops carrying depth we don't show
+                app_state.ui_input.selected_instrument_token = None;
+                app_state.send_command(Command::SetTickMode {
+                    instrument_tokens: vec![token],
+                    mode: TickMode::Quote,
+                });
+            }
+        });
+    });
+
+    ui.add_space(8.0);
+
+    let Some(depth) = app_state
+        .market_depth
+        .get(&token)
+        .map(|entry| entry.value().clone())
+    else {
+        ui.label(RichText::new("Waiting for market depth…").color(Color32::GRAY));
+        return;
+    };
+
+    let max_quantity = depth.max_level_quantity().max(1);
+    let mut picked_price = None;
+
+    egui::Grid::new("market_depth")
+        .num_columns(4)
+        .spacing([8.0, 3.0])
+        .show(ui, |ui| {
+            for header in ["Bid", "Qty", "Ask", "Qty"] {
+                ui.label(RichText::new(header).strong());
+            }
+            ui.end_row();
+
+            for (bid, ask) in depth.bids.iter().zip(&depth.asks) {
+                if price_button(ui, bid.price, bid_color) {
+                    picked_price = Some(bid.price);
+                }
+                quantity_bar(ui, bid, max_quantity, bid_color);
+
+                if price_button(ui, ask.price, ask_color) {
+                    picked_price = Some(ask.price);
+                }
+                quantity_bar(ui, ask, max_quantity, ask_color);
+                ui.end_row();
+            }
+        });
+
+    ui.add_space(8.0);
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        ui.colored_label(bid_color, format!("Buy {}", depth.total_buy_quantity));
+        ui.label("·");
+        ui.colored_label(ask_color, format!("Sell {}", depth.total_sell_quantity));
+    });
+
+    let total = depth.total_buy_quantity + depth.total_sell_quantity;
+    if total > 0 {
+        let buy_share = depth.total_buy_quantity as f32 / total as f32;
+        ui.add(
+            egui::ProgressBar::new(buy_share)
+                .fill(bid_color)
+                .text(format!("{:.0}% buyers", buy_share * 100.0)),
+        );
+    }
+
+    match depth.spread() {
+        Some(spread) => ui.label(format!("Spread: {}", format_inr(spread))),
+        None => ui.label(RichText::new("Spread: —").color(Color32::GRAY)),
+    };
+
+    if let Some(price) = picked_price {
+        app_state.ui_input.open_order_for(&symbol, instrument);
+        app_state.ui_input.order_price_input.set(price);
+        // A picked level price only makes sense on a priced order
+        if !app_state.ui_input.selected_order_type.requires_price() {
+            app_state.ui_input.selected_order_type = OrderType::Limit;
+        }
+        app_state.ui_input.validate_order_fields();
+    }
+}
+
+/// Clickable level price; empty levels are shown greyed out and can't be picked
+fn price_button(ui: &mut Ui, price: f64, color: Color32) -> bool {
+    if price <= 0.0 {
+        ui.label(RichText::new("—").color(Color32::GRAY));
+        return false;
+    }
+
+    ui.add(egui::Button::new(RichText::new(format!("{:.2}", price)).color(color)).frame(false))
+        .on_hover_text("Use this price in the order dialog")
+        .clicked()
+}
+
+/// Quantity drawn over a bar scaled to the largest level on either side
+fn quantity_bar(ui: &mut Ui, level: &DepthLevel, max_quantity: u32, color: Color32) {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(90.0, 18.0), Sense::hover());
+    response.on_hover_text(format!("{} orders", level.orders));
+    if !ui.is_rect_visible(rect) {
+        return;
+    }
+
+    let fraction = level.quantity as f32 / max_quantity as f32;
+    let mut bar = rect;
+    bar.set_width(rect.width() * fraction);
+
+    let painter = ui.painter();
+    painter.rect_filled(bar, 2.0, color.gamma_multiply(0.3));
+    painter.text(
+        rect.right_center() - egui::vec2(4.0, 0.0),
+        Align2::RIGHT_CENTER,
+        level.quantity.to_string(),
+        FontId::monospace(12.0),
+        ui.visuals().text_color(),
+    );
+}
@@ -0,0 +1,122 @@
+/// One crore, the point at which compact amounts switch to "Cr"
+const CRORE: f64 = 10_000_000.0;
+
+/// Rupee amount with Indian digit grouping, e.g. ₹12,34,567.89
+pub fn format_inr(value: f64) -> String {
+    let paise = paise(value);
+    let sign = if value < 0.0 && paise > 0 { "-" } else { "" };
+    format!("{}₹{}.{:02}", sign, group_indian(paise / 100), paise % 100)
+}
+
+/// P&L style amount with an explicit sign, e.g. +₹1,250.00 or -₹75.50
+/// Amounts that round to ₹0.00 get no sign
+pub fn format_inr_signed(value: f64) -> String {
+    if value > 0.0 && paise(value) > 0 {
+        format!("+{}", format_inr(value))
+    } else {
+        format_inr(value)
+    }
+}
+
+/// Summary-card amount; with `abbreviate_crores` a crore or more becomes e.g. ₹1.25 Cr
+pub fn format_inr_compact(value: f64, abbreviate_crores: bool) -> String {
+    if abbreviate_crores && value.abs() >= CRORE {
+        let sign = if value < 0.0 { "-" } else { "" };
+        format!("{}₹{:.2} Cr", sign, value.abs() / CRORE)
+    } else {
+        format_inr(value)
+    }
+}
+
+/// Signed counterpart of [`format_inr_compact`]
+pub fn format_inr_compact_signed(value: f64, abbreviate_crores: bool) -> String {
+    let formatted = format_inr_compact(value, abbreviate_crores);
+    if value > 0.0 && paise(value) > 0 {
+        format!("+{}", formatted)
+    } else {
+        formatted
+    }
+}
+
+/// Magnitude in whole paise, rounded half away from zero
+fn paise(value: f64) -> u64 {
+    (value.abs() * 100.0).round() as u64
+}
+
+/// Group whole rupees as thousands then pairs: 1234567 -> 12,34,567
+fn group_indian(rupees: u64) -> String {
+    let digits = rupees.to_string();
+    if digits.len() <= 3 {
+        return digits;
+    }
+
+    let (head, last_three) = digits.split_at(digits.len() - 3);
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 2);
+    for (i, digit) in head.chars().enumerate() {
+        if i > 0 && (head.len() - i) % 2 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped.push(',');
+    grouped.push_str(last_three);
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_thousands_then_lakhs_and_crores() {
+        for (value, expected) in [
+            (0.0, "₹0.00"),
+            (999.0, "₹999.00"),
+            (1_000.0, "₹1,000.00"),
+            (99_999.0, "₹99,999.00"),
+            (100_000.0, "₹1,00,000.00"),
+            (1_234_567.89, "₹12,34,567.89"),
+            (10_000_000.0, "₹1,00,00,000.00"),
+            (1_234_567_890.5, "₹1,23,45,67,890.50"),
+        ] {
+            assert_eq!(format_inr(value), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn negatives_carry_a_minus_before_the_rupee_sign() {
+        assert_eq!(format_inr(-75.5), "-₹75.50");
+        assert_eq!(format_inr(-1_234_567.0), "-₹12,34,567.00");
+        assert_eq!(format_inr_signed(-75.5), "-₹75.50");
+        assert_eq!(format_inr_signed(1_250.0), "+₹1,250.00");
+    }
+
+    #[test]
+    fn rounds_to_the_nearest_paisa() {
+        assert_eq!(format_inr(0.125), "₹0.13");
+        assert_eq!(format_inr(1.994), "₹1.99");
+        assert_eq!(format_inr(999.999), "₹1,000.00");
+        assert_eq!(format_inr(99_999.995), "₹1,00,000.00");
+        assert_eq!(format_inr(-0.016), "-₹0.02");
+    }
+
+    #[test]
+    fn amounts_rounding_to_zero_are_unsigned() {
+        for value in [0.0, -0.0, 0.001, -0.001, 0.004, -0.004] {
+            assert_eq!(format_inr(value), "₹0.00", "{}", value);
+            assert_eq!(format_inr_signed(value), "₹0.00", "{}", value);
+            assert_eq!(format_inr_compact_signed(value, true), "₹0.00", "{}", value);
+        }
+        assert_eq!(format_inr_signed(0.005), "+₹0.01");
+        assert_eq!(format_inr_signed(-0.005), "-₹0.01");
+    }
+
+    #[test]
+    fn compact_amounts_abbreviate_crores_on_request() {
+        assert_eq!(format_inr_compact(12_500_000.0, true), "₹1.25 Cr");
+        assert_eq!(format_inr_compact(-12_500_000.0, true), "-₹1.25 Cr");
+        assert_eq!(format_inr_compact(12_500_000.0, false), "₹1,25,00,000.00");
+        assert_eq!(format_inr_compact(9_999_999.0, true), "₹99,99,999.00");
+        assert_eq!(format_inr_compact_signed(12_500_000.0, true), "+₹1.25 Cr");
+    }
+}
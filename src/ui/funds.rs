@@ -0,0 +1,93 @@
+use crate::data_structures::SegmentMargins;
+use crate::state::{AppState, Command};
+use crate::ui::format::format_inr;
+use chrono::{TimeDelta, Utc};
+use egui::{Color32, RichText, Ui};
+
+/// Funds older than this are shown greyed out with their age
+const FUNDS_STALE_AFTER: TimeDelta = TimeDelta::minutes(2);
+
+type SegmentValue = fn(&SegmentMargins) -> f64;
+
+/// Render available cash, collateral, utilised margin and net for each segment
+pub fn render_funds_card(ui: &mut Ui, app_state: &AppState) {
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Funds").size(18.0).strong());
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .small_button("🔄")
+                .on_hover_text("Refresh funds")
+                .clicked()
+            {
+                app_state.send_command(Command::FetchMargins);
+            }
+        });
+    });
+    ui.add_space(10.0);
+
+    let Some((fetched_at, margins)) = app_state.margins.read().clone() else {
+        ui.label(RichText::new("Funds not loaded yet").color(Color32::GRAY));
+        return;
+    };
+
+    let age = Utc::now() - fetched_at;
+    let stale = age > FUNDS_STALE_AFTER;
+
+    egui::Grid::new("funds_table")
+        .num_columns(3)
+        .spacing([16.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("");
+            ui.label(RichText::new("Equity").strong());
+            ui.label(RichText::new("Commodity").strong());
+            ui.end_row();
+
+            let rows: [(&str, SegmentValue); 4] = [
+                ("Available cash", |segment| segment.available.cash),
+                ("Collateral", |segment| segment.available.collateral),
+                ("Utilised", |segment| segment.utilised.debits),
+                ("Net", |segment| segment.net),
+            ];
+            for (label, value_of) in rows {
+                ui.label(label);
+                for segment in [&margins.equity, &margins.commodity] {
+                    ui.label(funds_text(value_of(segment), stale));
+                }
+                ui.end_row();
+            }
+        });
+
+    if stale {
+        ui.label(
+            RichText::new(format!("Updated {} min ago", age.num_minutes()))
+                .small()
+                .color(Color32::GRAY),
+        );
+    }
+}
+
+/// One-line available cash for the order dialog, for the segment an exchange settles in
+pub fn render_available_cash(ui: &mut Ui, app_state: &AppState, exchange: &str) {
+    let Some((fetched_at, margins)) = app_state.margins.read().clone() else {
+        return;
+    };
+
+    let stale = Utc::now() - fetched_at > FUNDS_STALE_AFTER;
+    let net = margins.for_exchange(exchange).net;
+
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Available:").small());
+        ui.label(funds_text(net, stale).small());
+    });
+}
+
+fn funds_text(value: f64, stale: bool) -> RichText {
+    let text = RichText::new(format_inr(value));
+    if stale {
+        text.color(Color32::GRAY)
+    } else {
+        text
+    }
+}
@@ -0,0 +1,217 @@
+use crate::data_structures::*;
+use crate::instrument_index::InstrumentFacets;
+use crate::state::{AppState, Command};
+use crate::ui::components::row_button_size;
+use crate::ui::format::format_inr;
+use egui::{Color32, RichText, ScrollArea, Ui};
+use std::collections::BTreeSet;
+
+/// Results per page; the NFO dump alone runs to tens of thousands of contracts
+const PAGE_SIZE: usize = 50;
+
+/// Browse the instrument master with search, filters and per-row actions
+pub fn render_instruments(ui: &mut Ui, app_state: &mut AppState) {
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Instruments").size(24.0).strong());
+            ui.add_space(20.0);
+            if ui
+                .button("🔄 Refresh")
+                .on_hover_text(
+                    "Fetch the instrument master again and summarize new and expired instruments",
+                )
+                .clicked()
+            {
+                app_state.refresh_instruments();
+            }
+        });
+        ui.add_space(10.0);
+
+        if app_state.instruments.is_empty() {
+            ui.label(
+                RichText::new("The instrument master has not been loaded yet").color(Color32::GRAY),
+            );
+            return;
+        }
+
+        let facets = app_state.instrument_index.read().facets().clone();
+        render_instrument_filters(ui, app_state, &facets);
+        ui.add_space(10.0);
+
+        if let Some(wait) = app_state
+            .ui_input
+            .instrument_search
+            .update(&app_state.instruments)
+        {
+            ui.ctx().request_repaint_after(wait);
+        }
+
+        let results = app_state.ui_input.instrument_search.results();
+        let pages = results.len().div_ceil(PAGE_SIZE).max(1);
+        let search = &mut app_state.ui_input.instrument_search;
+        search.page = search.page.min(pages - 1);
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{} instruments", results.len()));
+            if search.is_searching() {
+                ui.spinner();
+            }
+
+            ui.add_space(20.0);
+            if ui
+                .add_enabled(search.page > 0, egui::Button::new("◀ Prev"))
+                .clicked()
+            {
+                search.page -= 1;
+            }
+            ui.label(format!("Page {}/{}", search.page + 1, pages));
+            if ui
+                .add_enabled(search.page + 1 < pages, egui::Button::new("Next ▶"))
+                .clicked()
+            {
+                search.page += 1;
+            }
+        });
+        ui.add_space(10.0);
+
+        let start = search.page * PAGE_SIZE;
+        let page = &results[start.min(results.len())..(start + PAGE_SIZE).min(results.len())];
+        render_instrument_table(ui, app_state, page);
+    });
+}
+
+/// Search box and exchange, type, segment and expiry filters
+fn render_instrument_filters(ui: &mut Ui, app_state: &mut AppState, facets: &InstrumentFacets) {
+    let query = &mut app_state.ui_input.instrument_search.query;
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Search:");
+        ui.add(
+            egui::TextEdit::singleline(&mut query.text)
+                .hint_text("Symbol or name")
+                .desired_width(180.0),
+        );
+
+        facet_combo(ui, "Exchange:", &mut query.exchange, &facets.exchanges);
+        facet_combo(
+            ui,
+            "Type:",
+            &mut query.instrument_type,
+            &facets.instrument_types,
+        );
+        facet_combo(ui, "Segment:", &mut query.segment, &facets.segments);
+        facet_combo(
+            ui,
+            "Expiry:",
+            &mut query.expiry_month,
+            &facets.expiry_months,
+        );
+
+        if ui.button("Clear").clicked() {
+            *query = Default::default();
+        }
+    });
+}
+
+/// Dropdown over a facet's values; the blank choice matches everything
+fn facet_combo(ui: &mut Ui, label: &str, selected: &mut String, values: &BTreeSet<String>) {
+    ui.label(label);
+    let text = if selected.is_empty() {
+        "Any".to_string()
+    } else {
+        selected.clone()
+    };
+
+    egui::ComboBox::from_id_salt(("instrument_filter", label))
+        .selected_text(text)
+        .height(300.0)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(selected, String::new(), "Any");
+            for value in values.iter().filter(|value| !value.is_empty()) {
+                ui.selectable_value(selected, value.clone(), value);
+            }
+        });
+}
+
+fn render_instrument_table(ui: &mut Ui, app_state: &mut AppState, tokens: &[u32]) {
+    ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("instruments_table")
+            .num_columns(10)
+            .spacing([12.0, 6.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for header in [
+                    "Symbol", "Name", "Exchange", "Type", "Segment", "Expiry", "Strike", "Lot",
+                    "Tick", "Actions",
+                ] {
+                    ui.label(RichText::new(header).strong());
+                }
+                ui.end_row();
+
+                for token in tokens {
+                    let Some(instrument) = app_state
+                        .instruments
+                        .get(token)
+                        .map(|entry| entry.value().clone())
+                    else {
+                        continue;
+                    };
+                    render_instrument_row(ui, app_state, instrument);
+                }
+            });
+    });
+}
+
+fn render_instrument_row(ui: &mut Ui, app_state: &mut AppState, instrument: Instrument) {
+    ui.label(RichText::new(&instrument.tradingsymbol).strong());
+    ui.label(&instrument.name);
+    ui.label(&instrument.exchange);
+    ui.label(&instrument.instrument_type);
+    ui.label(&instrument.segment);
+    ui.label(instrument.expiry.as_deref().unwrap_or("—"));
+    ui.label(
+        instrument
+            .strike
+            .filter(|strike| *strike > 0.0)
+            .map(format_inr)
+            .unwrap_or_else(|| "—".to_string()),
+    );
+    ui.label(instrument.lot_size.to_string());
+    ui.label(instrument.tick_size.to_string());
+
+    ui.horizontal(|ui| {
+        let watched = app_state
+            .watchlist
+            .read()
+            .contains(instrument.instrument_token);
+        if ui
+            .add_enabled(!watched, egui::Button::new("☆").small())
+            .on_hover_text("Add to watchlist")
+            .clicked()
+        {
+            app_state.send_command(Command::AddToWatchlist {
+                instrument_token: instrument.instrument_token,
+                note: None,
+            });
+        }
+
+        if ui
+            .small_button("📡")
+            .on_hover_text("Subscribe to ticks")
+            .clicked()
+        {
+            app_state.send_command(Command::SubscribeToTicks {
+                instrument_tokens: vec![instrument.instrument_token],
+            });
+        }
+
+        if ui
+            .add(egui::Button::new("Trade").min_size(row_button_size(ui, 5)))
+            .clicked()
+        {
+            let symbol = instrument.tradingsymbol.clone();
+            app_state.ui_input.open_order_for(&symbol, Some(instrument));
+        }
+    });
+    ui.end_row();
+}
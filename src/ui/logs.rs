@@ -1,10 +1,13 @@
-use crate::data_structures::LogLevel;
+use crate::data_structures::{LogEntry, LogLevel};
 use crate::state::AppState;
+use crate::ui::components::{choose_save_path, level_color};
 use egui::{Color32, RichText, ScrollArea, Ui};
 
 /// Render application logs with filtering and color coding
 pub fn render_logs(ui: &mut Ui, app_state: &mut AppState) {
     ui.vertical(|ui| {
+        let mut jump_to_end = false;
+
         // Header
         ui.horizontal(|ui| {
             ui.label(RichText::new("Application Logs").size(24.0).strong());
@@ -14,41 +17,66 @@ pub fn render_logs(ui: &mut Ui, app_state: &mut AppState) {
                     let mut logs = app_state.logs.write();
                     logs.clear();
                 }
+
+                if ui.button("💾 Export filtered logs…").clicked() {
+                    if let Some(path) = choose_save_path("logs", "Text", "txt") {
+                        let entries: Vec<LogEntry> = {
+                            let logs = app_state.logs.read();
+                            let shown = app_state.ui_input.log_snapshot.as_deref().unwrap_or(&logs);
+                            filtered_logs(app_state, shown)
+                                .into_iter()
+                                .cloned()
+                                .collect()
+                        };
+                        let filter = app_state.ui_input.log_filter.clone();
+                        app_state.export_logs_view(&entries, &filter, &path);
+                    }
+                }
+
+                let mut follow = !app_state.ui_input.log_scrolled_up;
+                if ui
+                    .checkbox(&mut follow, "Follow")
+                    .on_hover_text("Keep the newest entry in view")
+                    .changed()
+                {
+                    app_state.ui_input.log_scrolled_up = !follow;
+                    jump_to_end = follow;
+                }
             });
         });
 
         ui.add_space(10.0);
 
-        // Filter
-        ui.horizontal(|ui| {
-            ui.label("Filter:");
-            let mut filter_copy = app_state.ui_input.log_filter.clone();
-            ui.text_edit_singleline(&mut filter_copy);
-            app_state.ui_input.log_filter = filter_copy;
-        });
+        render_diagnostics(ui, app_state);
+
+        ui.add_space(10.0);
+
+        render_log_filters(ui, app_state);
 
         ui.add_space(10.0);
 
-        // Logs display
+        // Logs display, from the paused snapshot if there is one
         let logs = app_state.logs.read();
+        let shown = app_state.ui_input.log_snapshot.as_deref().unwrap_or(&logs);
+        let matching = filtered_logs(app_state, shown);
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        let following = !app_state.ui_input.log_scrolled_up;
+        let mut copied = None;
 
-        ScrollArea::vertical()
-            .stick_to_bottom(true)
+        let output = ScrollArea::vertical()
+            .stick_to_bottom(following)
             .max_height(500.0)
-            .show(ui, |ui| {
-                ui.vertical(|ui| {
-                    for log_entry in logs.iter().rev().take(1000) {
-                        // Apply filter
-                        if !app_state.ui_input.log_filter.is_empty()
-                            && !log_entry
-                                .message
-                                .to_lowercase()
-                                .contains(&app_state.ui_input.log_filter.to_lowercase())
-                        {
-                            continue;
-                        }
+            .auto_shrink([false, true])
+            .show_rows(ui, row_height, matching.len(), |ui, rows| {
+                if jump_to_end {
+                    // Clamped to the last row, after which sticking to the bottom takes over
+                    ui.scroll_with_delta(egui::Vec2::new(0.0, -f32::MAX));
+                }
 
-                        ui.horizontal(|ui| {
+                for log_entry in &matching[rows] {
+                    let row = ui
+                        .horizontal(|ui| {
                             // Timestamp
                             ui.label(
                                 RichText::new(log_entry.timestamp.format("%H:%M:%S").to_string())
@@ -57,13 +85,7 @@ pub fn render_logs(ui: &mut Ui, app_state: &mut AppState) {
                             );
 
                             // Level with color coding
-                            let (level_text, level_color) = match log_entry.level {
-                                LogLevel::Info => ("INFO", Color32::from_rgb(34, 197, 94)),
-                                LogLevel::Warning => ("WARN", Color32::from_rgb(245, 158, 11)),
-                                LogLevel::Error => ("ERROR", Color32::from_rgb(239, 68, 68)),
-                                LogLevel::Debug => ("DEBUG", Color32::from_rgb(107, 114, 128)),
-                            };
-
+                            let (level_text, level_color) = level_style(log_entry.level);
                             ui.colored_label(level_color, level_text);
 
                             // Module
@@ -75,13 +97,42 @@ pub fn render_logs(ui: &mut Ui, app_state: &mut AppState) {
                                 );
                             }
 
-                            // Message
-                            ui.label(&log_entry.message);
-                        });
-                    }
-                });
+                            // Message, cut to one line so every row is the same height
+                            ui.add(egui::Label::new(&log_entry.message).truncate());
+                        })
+                        .response
+                        .interact(egui::Sense::click());
+
+                    row.context_menu(|ui| {
+                        if ui.button("Copy line").clicked() {
+                            copied = Some(log_entry.line());
+                            ui.close();
+                        }
+                        if ui.button("Copy all visible").clicked() {
+                            copied = Some(
+                                matching
+                                    .iter()
+                                    .map(|log_entry| log_entry.line())
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            );
+                            ui.close();
+                        }
+                    });
+                }
             });
 
+        if let Some(text) = copied {
+            ui.ctx().copy_text(text);
+        }
+
+        // Scrolling up leaves the bottom, which stops following until Follow is ticked again
+        let bottom = (output.content_size.y - output.inner_rect.height()).max(0.0);
+        let scrolled_up = following && output.state.offset.y < bottom - row_height;
+        if scrolled_up {
+            app_state.ui_input.log_scrolled_up = true;
+        }
+
         ui.add_space(10.0);
 
         // Log statistics
@@ -123,3 +174,193 @@ pub fn render_logs(ui: &mut Ui, app_state: &mut AppState) {
         });
     });
 }
+
+/// Entries passing the text, level and module filters, oldest first
+fn filtered_logs<'a>(app_state: &AppState, logs: &'a [LogEntry]) -> Vec<&'a LogEntry> {
+    let filter = app_state.ui_input.log_filter.to_lowercase();
+    let min_severity = app_state.ui_input.log_min_level.severity();
+    let module = app_state.ui_input.log_module.as_deref();
+
+    logs.iter()
+        .filter(|log_entry| {
+            log_entry.level.severity() >= min_severity
+                && module.is_none_or(|module| log_entry.module.as_deref() == Some(module))
+                && (filter.is_empty() || log_entry.message.to_lowercase().contains(&filter))
+        })
+        .collect()
+}
+
+/// Text filter, minimum level, module and pause controls
+fn render_log_filters(ui: &mut Ui, app_state: &mut AppState) {
+    let mut modules: Vec<String> = app_state
+        .logs
+        .read()
+        .iter()
+        .filter_map(|log_entry| log_entry.module.clone())
+        .collect();
+    modules.sort();
+    modules.dedup();
+
+    ui.horizontal(|ui| {
+        ui.label("Filter:");
+        ui.text_edit_singleline(&mut app_state.ui_input.log_filter);
+
+        ui.add_space(10.0);
+
+        let min_level = &mut app_state.ui_input.log_min_level;
+        egui::ComboBox::from_id_salt("log_min_level")
+            .selected_text(format!("{}+", level_style(*min_level).0))
+            .show_ui(ui, |ui| {
+                for level in LogLevel::ALL {
+                    ui.selectable_value(min_level, level, format!("{}+", level_style(level).0));
+                }
+            });
+
+        let module = &mut app_state.ui_input.log_module;
+        egui::ComboBox::from_id_salt("log_module")
+            .selected_text(module.as_deref().unwrap_or("All modules"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(module, None, "All modules");
+                for name in modules {
+                    let label = name.clone();
+                    ui.selectable_value(module, Some(name), label);
+                }
+            });
+
+        ui.add_space(10.0);
+
+        match &app_state.ui_input.log_snapshot {
+            Some(snapshot) => {
+                let arrived = new_entries_since(snapshot, &app_state.logs.read());
+                let label = if arrived > 0 {
+                    format!("▶ Resume ({} new entries)", arrived)
+                } else {
+                    "▶ Resume".to_string()
+                };
+                if ui.button(label).clicked() {
+                    app_state.ui_input.log_snapshot = None;
+                }
+            }
+            None => {
+                if ui.button("⏸ Pause").clicked() {
+                    app_state.ui_input.log_snapshot = Some(app_state.logs.read().clone());
+                }
+            }
+        }
+    });
+}
+
+/// Entries logged after the newest one in a paused snapshot
+fn new_entries_since(snapshot: &[LogEntry], logs: &[LogEntry]) -> usize {
+    match snapshot.last() {
+        Some(last) => logs
+            .iter()
+            .rev()
+            .take_while(|log_entry| log_entry.timestamp > last.timestamp)
+            .count(),
+        None => logs.len(),
+    }
+}
+
+fn level_style(level: LogLevel) -> (&'static str, Color32) {
+    (level.label(), level_color(level))
+}
+
+/// Render event channel diagnostics for spotting UI backlog
+fn render_diagnostics(ui: &mut Ui, app_state: &AppState) {
+    egui::CollapsingHeader::new("Diagnostics")
+        .default_open(false)
+        .show(ui, |ui| {
+            let metrics = app_state.metrics.read();
+            let threshold = app_state.config.app.event_backlog_warn_threshold;
+
+            egui::Grid::new("diagnostics_grid")
+                .num_columns(2)
+                .spacing([10.0, 4.0])
+                .show(ui, |ui| {
+                    ui.label("Event queue depth:");
+                    let depth_color = if metrics.event_channel_depth > threshold {
+                        Color32::from_rgb(239, 68, 68)
+                    } else {
+                        Color32::from_rgb(34, 197, 94)
+                    };
+                    ui.colored_label(depth_color, metrics.event_channel_depth.to_string());
+                    ui.end_row();
+
+                    ui.label("Max depth (last minute):");
+                    ui.label(metrics.max_event_channel_depth_last_minute.to_string());
+                    ui.end_row();
+
+                    ui.label("Event processing per frame:");
+                    ui.label(format!("{} µs", metrics.process_events_time_us));
+                    ui.end_row();
+
+                    ui.label("Backlog warning threshold:");
+                    ui.label(threshold.to_string());
+                    ui.end_row();
+
+                    ui.label("Subscriptions:");
+                    let mut subscribed: Vec<u32> = app_state
+                        .explicit_subscriptions
+                        .read()
+                        .union(&app_state.auto_subscribed_tokens.read())
+                        .copied()
+                        .collect();
+                    subscribed.sort_unstable();
+                    let response = ui.label(subscribed.len().to_string());
+                    if !subscribed.is_empty() {
+                        response.on_hover_ui(|ui| {
+                            for token in &subscribed {
+                                ui.label(app_state.instrument_label(*token));
+                            }
+                        });
+                    }
+                    ui.end_row();
+
+                    ui.label("Ticks waiting:");
+                    ui.label(format!(
+                        "{} of {}",
+                        app_state.tick_receiver.len(),
+                        app_state.config.performance.tick_buffer_size
+                    ));
+                    ui.end_row();
+
+                    ui.label("Ticks dropped (buffer full):");
+                    ui.label(
+                        app_state
+                            .dropped_ticks
+                            .load(std::sync::atomic::Ordering::Relaxed)
+                            .to_string(),
+                    );
+                    ui.end_row();
+
+                    ui.label("Clock offset:");
+                    match &metrics.clock_offset {
+                        Some(clock) => {
+                            let offset = format!("{:+} ms", clock.offset.num_milliseconds());
+                            let response = if clock.is_drifting() {
+                                ui.colored_label(Color32::from_rgb(245, 158, 11), offset)
+                            } else {
+                                ui.label(offset)
+                            };
+                            response.on_hover_text(format!(
+                                "Added to local time to match {}; round trip {} ms, checked at {}",
+                                clock.server,
+                                clock.round_trip.num_milliseconds(),
+                                clock
+                                    .measured_at
+                                    .with_timezone(&chrono::Local)
+                                    .format("%H:%M:%S")
+                            ));
+                        }
+                        None if app_state.config.app.ntp_server.is_empty() => {
+                            ui.label("Not checked (app.ntp_server is off)");
+                        }
+                        None => {
+                            ui.label("Not checked yet");
+                        }
+                    }
+                    ui.end_row();
+                });
+        });
+}
@@ -1,12 +1,35 @@
+pub mod charts;
 pub mod components;
+pub mod depth;
+pub mod format;
+pub mod funds;
+pub mod instruments;
 pub mod logs;
+pub mod notifications;
 pub mod orders;
 pub mod overview;
 pub mod pnl;
 pub mod positions;
+pub mod reauth;
+pub mod settings;
+pub mod shortcuts;
+pub mod tape;
+pub mod theme;
+pub mod trades;
+pub mod watchlist;
 
+pub use charts::*;
+pub use depth::*;
+pub use instruments::*;
 pub use logs::*;
+pub use notifications::*;
 pub use orders::*;
 pub use overview::*;
 pub use pnl::*;
 pub use positions::*;
+pub use reauth::*;
+pub use settings::*;
+pub use shortcuts::*;
+pub use tape::*;
+pub use trades::*;
+pub use watchlist::*;
@@ -0,0 +1,128 @@
+use crate::state::AppState;
+use crate::ui::components::{level_color, Modal};
+use chrono_tz::Asia::Kolkata;
+use egui::{Color32, RichText, ScrollArea, Ui};
+
+/// 🔔 menu listing recent notifications, newest first, with the unread count on the button
+pub fn render_notification_bell(ui: &mut Ui, app_state: &mut AppState) {
+    let unread = app_state.unread_notifications();
+    let label = if unread > 0 {
+        RichText::new(format!("🔔 {}", unread)).strong()
+    } else {
+        RichText::new("🔔")
+    };
+
+    ui.menu_button(label, |ui| {
+        ui.set_min_width(360.0);
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Notifications").strong());
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("Clear").clicked() {
+                    app_state.notifications.clear();
+                }
+                if ui
+                    .add_enabled(unread > 0, egui::Button::new("Mark all read").small())
+                    .clicked()
+                {
+                    for notification in app_state.notifications.iter_mut() {
+                        notification.read = true;
+                    }
+                }
+            });
+        });
+        ui.separator();
+
+        if app_state.notifications.is_empty() {
+            ui.label(RichText::new("Nothing yet").color(Color32::GRAY));
+            return;
+        }
+
+        let mut opened = None;
+        ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            for (index, notification) in app_state.notifications.iter().enumerate().rev() {
+                let time = notification
+                    .created_at
+                    .with_timezone(&Kolkata)
+                    .format("%H:%M:%S");
+                let mut text = RichText::new(format!("{}  {}", time, notification.message))
+                    .color(level_color(notification.level));
+                if !notification.read {
+                    text = text.strong();
+                }
+
+                let mut response = ui.add(egui::Button::new(text).frame(false).wrap());
+                if let Some(target) = &notification.target {
+                    response = response.on_hover_text(format!("Open {}", target.view.label()));
+                }
+                if response.clicked() {
+                    opened = Some(index);
+                }
+            }
+        });
+
+        if let Some(index) = opened {
+            app_state.open_notification(index);
+            ui.close();
+        }
+    });
+}
+
+/// Critical errors in a dialog, oldest first, one at a time until each is dismissed
+pub fn render_critical_error(ctx: &egui::Context, app_state: &mut AppState) {
+    let Some(error) = app_state.critical_errors.front() else {
+        return;
+    };
+
+    let mut title = match &error.module {
+        Some(module) => format!("Error in {}", module),
+        None => "Error".to_string(),
+    };
+    let waiting = app_state.critical_errors.len() - 1;
+    if waiting > 0 {
+        title.push_str(&format!(" ({} more)", waiting));
+    }
+    let message = format!(
+        "{}\n\n{}",
+        error.message,
+        error.raised_at.with_timezone(&Kolkata).format("%H:%M:%S")
+    );
+
+    if Modal::error(ctx, "critical_error_modal", title, message) {
+        app_state.critical_errors.pop_front();
+    }
+}
+
+/// Offer the report of a crash in the last session, once
+pub fn render_crash_report(ctx: &egui::Context, app_state: &mut AppState) {
+    let Some(report) = &app_state.ui_input.crash_report else {
+        return;
+    };
+
+    let mut done = false;
+    let response = Modal::new("crash_report_modal", "The last session crashed")
+        .size(egui::Vec2::new(450.0, 160.0))
+        .show(ctx, |ui| {
+            ui.label("The dashboard hit a panic last time it ran. The report was saved to:");
+            ui.label(RichText::new(report.display().to_string()).monospace());
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if ui.button("📄 Open report").clicked() {
+                    // Relative to the working directory, which the URL needs spelled out
+                    let url = std::path::absolute(report)
+                        .ok()
+                        .and_then(|path| url::Url::from_file_path(path).ok());
+                    if let Some(url) = url {
+                        ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+                    }
+                    done = true;
+                }
+                if ui.button("Dismiss").clicked() {
+                    done = true;
+                }
+            });
+        });
+
+    if done || response.closed {
+        app_state.ui_input.crash_report = None;
+    }
+}
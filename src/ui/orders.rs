@@ -1,9 +1,18 @@
 use crate::data_structures::*;
-use crate::state::{AppState, Command};
+use crate::state::{
+    parse_with_feedback, AppState, Command, Exchange, OrderColumn, OrderEditForm, OrderTab,
+    OrderType, ProductType, TableKey, TransactionType, ValidatedField, Validity, MANUAL_ORDER_TAG,
+};
 use crate::ui::components::{
-    buy_button, danger_button, primary_button, sell_button, success_button,
+    buy_button, choose_export_path, column_cells, column_headers, column_menu, danger_button,
+    export_controls, primary_button, refresh_countdown, row_button_size, sell_button,
+    success_button, ColumnSpec, Modal, ORDER_BUTTON_DEBOUNCE,
 };
+use crate::ui::format::format_inr;
+use crate::ui::funds::render_available_cash;
 use egui::{Color32, RichText, ScrollArea, Ui};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 /// Render orders management interface with filtering and actions
 pub fn render_orders(ui: &mut Ui, app_state: &mut AppState) {
@@ -30,6 +39,9 @@ pub fn render_orders(ui: &mut Ui, app_state: &mut AppState) {
                 {
                     app_state.send_command(Command::FetchOrders);
                 }
+
+                ui.add_space(6.0);
+                refresh_countdown(ui, app_state.orders_refresh_remaining());
             });
         });
 
@@ -37,14 +49,45 @@ pub fn render_orders(ui: &mut Ui, app_state: &mut AppState) {
 
         // Filter
         ui.horizontal(|ui| {
-            ui.label("Filter by symbol:");
+            ui.label("Filter by symbol or order ID:");
             ui.text_edit_singleline(&mut app_state.ui_input.order_filter);
 
             if ui.button("Clear").clicked() {
                 app_state.ui_input.order_filter.clear();
             }
+
+            column_menu(ui, ORDER_COLUMNS, &mut app_state.ui_input.order_columns);
+
+            render_bulk_cancel(ui, app_state);
         });
 
+        ui.add_space(6.0);
+        render_order_tabs(ui, app_state);
+
+        if let Some((stem, path)) = export_controls(
+            ui,
+            &mut app_state.ui_input.export_dir,
+            &["orders", "trades"],
+        ) {
+            let command = if stem == "trades" {
+                Command::ExportTrades { path }
+            } else {
+                Command::ExportOrders { path }
+            };
+            app_state.send_command(command);
+        }
+
+        if ui.button("💾 Export CSV…").clicked() {
+            if let Some(path) = choose_export_path("orders") {
+                let orders = visible_orders(app_state);
+                let filter = match app_state.ui_input.order_tab {
+                    OrderTab::All => app_state.ui_input.order_filter.clone(),
+                    tab => format!("{} status:{}", app_state.ui_input.order_filter, tab.label()),
+                };
+                app_state.export_orders_view(&orders, &filter, &path);
+            }
+        }
+
         ui.add_space(10.0);
 
         // Orders table
@@ -58,6 +101,14 @@ pub fn render_orders(ui: &mut Ui, app_state: &mut AppState) {
         if app_state.ui_input.show_order_dialog {
             render_order_dialog(ui, app_state);
         }
+
+        if app_state.ui_input.inspected_order_id.is_some() {
+            render_order_detail(ui.ctx(), app_state);
+        }
+
+        if app_state.ui_input.order_edit.is_some() {
+            render_modify_dialog(ui.ctx(), app_state);
+        }
     });
 }
 
@@ -75,71 +126,340 @@ fn render_empty_orders(ui: &mut Ui) {
     });
 }
 
+/// Status chips with live counts for the orders the symbol filter matches
+fn render_order_tabs(ui: &mut Ui, app_state: &mut AppState) {
+    let counts = app_state.order_tab_counts(&app_state.ui_input.order_filter);
+    ui.horizontal(|ui| {
+        for tab in OrderTab::ALL {
+            let count = counts.get(&tab).copied().unwrap_or(0);
+            ui.selectable_value(
+                &mut app_state.ui_input.order_tab,
+                tab,
+                format!("{} ({})", tab.label(), count),
+            );
+        }
+    });
+}
+
+/// Orders as the table shows them: filtered, sorted and, while hovered, frozen in place
+fn visible_orders(app_state: &AppState) -> Vec<Order> {
+    let mut filtered_orders = app_state.get_filtered_orders(
+        &app_state.ui_input.order_filter,
+        app_state.ui_input.order_tab,
+    );
+
+    let sort = app_state.ui_input.order_sort;
+    filtered_orders.sort_by(|a, b| sort.apply(sort.column.compare(a, b)));
+
+    // While the pointer is over the table keep rows where they were so a status change
+    // streaming in does not move a button out from under the cursor
+    if let Some(frozen) = &app_state.ui_input.frozen_order_ids {
+        let positions: HashMap<&str, usize> = frozen
+            .iter()
+            .enumerate()
+            .map(|(index, order_id)| (order_id.as_str(), index))
+            .collect();
+        filtered_orders.sort_by_key(|order| {
+            positions
+                .get(order.order_id.as_str())
+                .copied()
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    filtered_orders
+}
+
+/// Cancel every selected order that is still open, then clear the selection
+fn render_bulk_cancel(ui: &mut Ui, app_state: &mut AppState) {
+    // Orders that are gone or no longer open drop out of the selection
+    let orders = &app_state.orders;
+    app_state.ui_input.selected_order_ids.retain(|order_id| {
+        orders
+            .get(order_id)
+            .is_some_and(|order| order.is_cancellable())
+    });
+
+    let count = app_state.ui_input.selected_order_ids.len();
+    if count == 0 {
+        return;
+    }
+
+    ui.add_space(10.0);
+
+    if danger_button(format!("Cancel selected ({})", count))
+        .size(egui::Vec2::new(150.0, 24.0))
+        .disabled_reason(app_state.trading_blocked_reason())
+        .ui(ui)
+        .clicked()
+    {
+        for order_id in std::mem::take(&mut app_state.ui_input.selected_order_ids) {
+            app_state.send_command(app_state.cancel_order_command(order_id));
+        }
+    }
+}
+
+/// Columns of the orders table between the selection checkbox and the actions
+const ORDER_COLUMNS: &[ColumnSpec<OrderColumn, Order>] = &[
+    ColumnSpec {
+        id: OrderColumn::Symbol,
+        header: "Symbol",
+        width: 110.0,
+        render: |ui, order, app_state, _| {
+            let selected =
+                app_state.ui_input.selected_instrument_token == Some(order.instrument_token);
+            if ui
+                .selectable_label(selected, &order.tradingsymbol)
+                .clicked()
+            {
+                app_state.select_instrument(order.instrument_token);
+            }
+        },
+    },
+    ColumnSpec {
+        id: OrderColumn::Type,
+        header: "Type",
+        width: 40.0,
+        render: |ui, order, app_state, _| {
+            let type_color = app_state
+                .theme
+                .direction_color(order.transaction_type == "BUY");
+            ui.colored_label(type_color, &order.transaction_type);
+        },
+    },
+    ColumnSpec {
+        id: OrderColumn::Quantity,
+        header: "Qty",
+        width: 40.0,
+        render: |ui, order, _, _| {
+            ui.label(order.quantity.to_string());
+        },
+    },
+    ColumnSpec {
+        id: OrderColumn::Price,
+        header: "Price",
+        width: 80.0,
+        render: |ui, order, _, _| {
+            ui.label(format_inr(order.price));
+        },
+    },
+    ColumnSpec {
+        id: OrderColumn::Status,
+        header: "Status",
+        width: 80.0,
+        render: |ui, order, _, _| {
+            let status_color = match order.status {
+                OrderStatus::Complete => Color32::from_rgb(34, 197, 94),
+                OrderStatus::Open => Color32::from_rgb(59, 130, 246),
+                OrderStatus::Cancelled => Color32::from_rgb(107, 114, 128),
+                OrderStatus::Rejected => Color32::from_rgb(239, 68, 68),
+                OrderStatus::CancelledAmo | OrderStatus::Unknown(_) => {
+                    Color32::from_rgb(107, 114, 128)
+                }
+                _ => Color32::from_rgb(245, 158, 11),
+            };
+            let status_label = ui.colored_label(status_color, order.status.to_string());
+            if let Some(reason) = &order.status_message {
+                status_label.on_hover_text(reason);
+            }
+        },
+    },
+    ColumnSpec {
+        id: OrderColumn::Filled,
+        header: "Filled",
+        width: 50.0,
+        render: |ui, order, _, _| {
+            ui.label(format!("{}/{}", order.filled_quantity, order.quantity));
+        },
+    },
+    ColumnSpec {
+        id: OrderColumn::Time,
+        header: "Time",
+        width: 60.0,
+        render: |ui, order, _, _| {
+            ui.label(order.order_timestamp.format("%H:%M:%S").to_string());
+        },
+    },
+];
+
+/// Extra column a status tab adds before the actions, for what matters on that tab
+fn tab_column(tab: OrderTab) -> Option<&'static str> {
+    match tab {
+        OrderTab::Open => Some("Pending"),
+        OrderTab::Rejected => Some("Reason"),
+        _ => None,
+    }
+}
+
+fn render_tab_cell(ui: &mut Ui, tab: OrderTab, order: &Order, app_state: &AppState) {
+    match tab {
+        OrderTab::Open => {
+            ui.label(
+                RichText::new(order.pending_quantity.to_string())
+                    .strong()
+                    .size(15.0)
+                    .color(app_state.theme.accent),
+            );
+        }
+        OrderTab::Rejected => {
+            ui.colored_label(
+                app_state.theme.loss,
+                order.status_message.as_deref().unwrap_or("—"),
+            );
+        }
+        _ => {}
+    }
+}
+
+/// Only rows inside the scroll viewport are laid out, so long order books stay smooth
 fn render_orders_table(ui: &mut Ui, app_state: &mut AppState) {
-    let filtered_orders = app_state.get_filtered_orders(&app_state.ui_input.order_filter);
-
-    ScrollArea::vertical().max_height(600.0).show(ui, |ui| {
-        egui::Grid::new("orders_table")
-            .num_columns(8)
-            .spacing([8.0, 4.0])
-            .striped(true)
-            .show(ui, |ui| {
-                // Header
-                ui.label(RichText::new("Symbol").strong());
-                ui.label(RichText::new("Type").strong());
-                ui.label(RichText::new("Qty").strong());
-                ui.label(RichText::new("Price").strong());
-                ui.label(RichText::new("Status").strong());
-                ui.label(RichText::new("Filled").strong());
-                ui.label(RichText::new("Time").strong());
-                ui.label(RichText::new("Actions").strong());
-                ui.end_row();
+    let sort = app_state.ui_input.order_sort;
+    let tab = app_state.ui_input.order_tab;
+    let key = TableKey {
+        revision: app_state.orders_gen.get(),
+        filter: format!("{:?}:{}", tab, app_state.ui_input.order_filter),
+        sort,
+        frozen: app_state.ui_input.frozen_order_ids.is_some(),
+    };
+    if !app_state.ui_input.order_rows.is_current(&key) {
+        let orders = visible_orders(app_state);
+        app_state.ui_input.order_rows.store(key, orders);
+    }
+    let filtered_orders = app_state.ui_input.order_rows.rows();
+    let columns = app_state.ui_input.order_columns.clone();
 
-                // Rows
-                for order in &filtered_orders {
-                    ui.label(&order.tradingsymbol);
-
-                    let type_color = if order.transaction_type == "BUY" {
-                        Color32::from_rgb(34, 197, 94)
-                    } else {
-                        Color32::from_rgb(239, 68, 68)
-                    };
-                    ui.colored_label(type_color, &order.transaction_type);
-
-                    ui.label(format!("{}", order.quantity));
-                    ui.label(format!("₹{:.2}", order.price));
-
-                    let status_color = match order.status {
-                        OrderStatus::Complete => Color32::from_rgb(34, 197, 94),
-                        OrderStatus::Open => Color32::from_rgb(59, 130, 246),
-                        OrderStatus::Cancelled => Color32::from_rgb(107, 114, 128),
-                        OrderStatus::Rejected => Color32::from_rgb(239, 68, 68),
-                        _ => Color32::from_rgb(245, 158, 11),
-                    };
-                    ui.colored_label(status_color, format!("{:?}", order.status));
-
-                    ui.label(format!("{}/{}", order.filled_quantity, order.quantity));
-                    ui.label(order.order_timestamp.format("%H:%M:%S").to_string());
-
-                    // Actions
-                    ui.horizontal(|ui| {
-                        if matches!(order.status, OrderStatus::Open | OrderStatus::Trigger) {
-                            if danger_button("Cancel")
-                                .size(egui::Vec2::new(60.0, 20.0))
-                                .ui(ui)
-                                .clicked()
-                            {
-                                app_state.send_command(Command::CancelOrder {
-                                    order_id: order.order_id.clone(),
-                                });
+    let row_height = row_button_size(ui, 1).y;
+    let row_spacing = ui.spacing().item_spacing.y;
+
+    // The header takes the first slot of whatever range is visible, so it stays on top
+    let response = ScrollArea::vertical().max_height(600.0).show_rows(
+        ui,
+        row_height,
+        filtered_orders.len() + 1,
+        |ui, visible| {
+            egui::Grid::new("orders_table")
+                .num_columns(columns.visible().count() + 2 + usize::from(tab_column(tab).is_some()))
+                .spacing([8.0, row_spacing])
+                .min_row_height(row_height)
+                .striped(true)
+                .show(ui, |ui| {
+                    // Select all open orders the filter shows
+                    let selectable: Vec<&Order> = filtered_orders
+                        .iter()
+                        .filter(|order| order.is_cancellable())
+                        .collect();
+                    let selection = &mut app_state.ui_input.selected_order_ids;
+                    let mut all_selected = !selectable.is_empty()
+                        && selectable
+                            .iter()
+                            .all(|order| selection.contains(&order.order_id));
+                    if ui
+                        .add_enabled(
+                            !selectable.is_empty(),
+                            egui::Checkbox::without_text(&mut all_selected),
+                        )
+                        .changed()
+                    {
+                        for order in &selectable {
+                            if all_selected {
+                                selection.insert(order.order_id.clone());
+                            } else {
+                                selection.remove(&order.order_id);
                             }
                         }
-                    });
+                    }
 
+                    // Header, click to sort
+                    column_headers(
+                        ui,
+                        ORDER_COLUMNS,
+                        &columns,
+                        &mut app_state.ui_input.order_sort,
+                    );
+                    if let Some(header) = tab_column(tab) {
+                        ui.label(RichText::new(header).strong());
+                    }
+                    ui.label(RichText::new("Actions").strong());
                     ui.end_row();
-                }
-            });
-    });
+
+                    // Rows
+                    let end = visible.end.saturating_sub(1).min(filtered_orders.len());
+                    for order in &filtered_orders[visible.start.min(end)..end] {
+                        let selection = &mut app_state.ui_input.selected_order_ids;
+                        let mut checked = selection.contains(&order.order_id);
+                        if ui
+                            .add_enabled(
+                                order.is_cancellable(),
+                                egui::Checkbox::without_text(&mut checked),
+                            )
+                            .changed()
+                        {
+                            if checked {
+                                selection.insert(order.order_id.clone());
+                            } else {
+                                selection.remove(&order.order_id);
+                            }
+                        }
+
+                        column_cells(ui, ORDER_COLUMNS, &columns, order, app_state, &());
+                        render_tab_cell(ui, tab, order, app_state);
+
+                        // Actions
+                        ui.horizontal(|ui| {
+                            if ui
+                                .small_button("ℹ")
+                                .on_hover_text("Order details")
+                                .clicked()
+                            {
+                                app_state.inspect_order(&order.order_id);
+                            }
+
+                            if order.is_cancellable() {
+                                if primary_button("Edit")
+                                    .size(row_button_size(ui, 4))
+                                    .disabled_reason(app_state.trading_blocked_reason())
+                                    .ui(ui)
+                                    .clicked()
+                                {
+                                    app_state.ui_input.order_edit =
+                                        Some(OrderEditForm::from_order(order));
+                                }
+
+                                if danger_button("Cancel")
+                                    .size(row_button_size(ui, 6))
+                                    .disabled_reason(app_state.trading_blocked_reason())
+                                    .ui(ui)
+                                    .clicked()
+                                {
+                                    app_state.send_command(Command::CancelOrder {
+                                        order_id: order.order_id.clone(),
+                                        variety: order.variety.clone(),
+                                    });
+                                }
+                            }
+                        });
+
+                        ui.end_row();
+                    }
+                });
+        },
+    );
+
+    // Freeze the row order when the pointer enters; a sort change always applies immediately
+    let hovered = response
+        .inner_rect
+        .contains(ui.ctx().pointer_hover_pos().unwrap_or_default())
+        && app_state.ui_input.order_sort == sort;
+    if !hovered {
+        app_state.ui_input.frozen_order_ids = None;
+    } else if app_state.ui_input.frozen_order_ids.is_none() {
+        app_state.ui_input.frozen_order_ids = Some(
+            filtered_orders
+                .iter()
+                .map(|order| order.order_id.clone())
+                .collect(),
+        );
+    }
 }
 
 fn render_order_dialog(ui: &mut Ui, app_state: &mut AppState) {
@@ -149,40 +469,143 @@ fn render_order_dialog(ui: &mut Ui, app_state: &mut AppState) {
             ui.label(RichText::new("Place New Order").size(18.0).strong());
             ui.add_space(10.0);
 
+            let mut exchange_changed = false;
             ui.horizontal(|ui| {
-                ui.label("Symbol:");
-                ui.text_edit_singleline(&mut app_state.ui_input.order_symbol_input);
+                ui.label("Exchange:");
+                egui::ComboBox::from_id_salt("order_exchange")
+                    .selected_text(app_state.ui_input.selected_exchange.as_api_str())
+                    .show_ui(ui, |ui| {
+                        for exchange in Exchange::ALL {
+                            exchange_changed |= ui
+                                .selectable_value(
+                                    &mut app_state.ui_input.selected_exchange,
+                                    exchange,
+                                    exchange.as_api_str(),
+                                )
+                                .changed();
+                        }
+                    });
             });
 
             ui.horizontal(|ui| {
-                ui.label("Quantity:");
-                ui.text_edit_singleline(&mut app_state.ui_input.order_quantity_input);
+                ui.label("Symbol:");
+                let response = ui.text_edit_singleline(&mut app_state.ui_input.order_symbol_input);
+                if response.changed() {
+                    // Typing over a picked symbol forgets the picked instrument
+                    app_state.ui_input.order_instrument = None;
+                }
+                if response.changed() || exchange_changed {
+                    app_state.ui_input.symbol_suggestions = app_state.search_instruments(
+                        &app_state.ui_input.order_symbol_input,
+                        app_state.ui_input.selected_exchange,
+                    );
+                }
             });
 
+            render_symbol_suggestions(ui, app_state);
+
+            let input = &mut app_state.ui_input;
+            let mut changed = false;
             ui.horizontal(|ui| {
-                ui.label("Price:");
-                ui.text_edit_singleline(&mut app_state.ui_input.order_price_input);
+                changed |= api_str_combo(
+                    ui,
+                    "order_type",
+                    "Type:",
+                    &mut input.selected_order_type,
+                    &OrderType::ALL,
+                );
+                changed |= api_str_combo(
+                    ui,
+                    "order_product",
+                    "Product:",
+                    &mut input.selected_product_type,
+                    &ProductType::ALL,
+                );
+                changed |= api_str_combo(
+                    ui,
+                    "order_validity",
+                    "Validity:",
+                    &mut input.selected_validity,
+                    &Validity::ALL,
+                );
             });
 
+            changed |= render_quantity_stepper(ui, app_state);
+
+            let order_type = app_state.ui_input.selected_order_type;
+            let input = &mut app_state.ui_input;
+            if order_type.requires_price() {
+                changed |= render_validated_input(ui, "Price:", &mut input.order_price_input);
+            }
+            if order_type.requires_trigger() {
+                changed |=
+                    render_validated_input(ui, "Trigger:", &mut input.order_trigger_price_input);
+            }
+            if input.selected_validity == Validity::Ttl {
+                changed |= render_validated_input(ui, "Minutes:", &mut input.order_ttl_input);
+            }
+
+            if changed {
+                app_state.ui_input.validate_order_fields();
+            }
+
+            if order_type.requires_price() {
+                render_tick_size_hint(ui, app_state);
+            }
+
+            let exchange = order_instrument(app_state)
+                .map(|instrument| instrument.exchange)
+                .unwrap_or_else(|| {
+                    app_state
+                        .ui_input
+                        .selected_exchange
+                        .as_api_str()
+                        .to_string()
+                });
+            render_margin_preview(ui, app_state, &exchange);
+
             ui.add_space(10.0);
 
+            let ready = app_state.ui_input.order_form_ready();
+            let in_flight = |side: TransactionType| {
+                app_state.orders_in_flight.read().any(|order| {
+                    order.tag.as_deref() == Some(MANUAL_ORDER_TAG)
+                        && order.transaction_type == side.as_api_str()
+                })
+            };
+            let (buying, selling) = (
+                in_flight(TransactionType::Buy),
+                in_flight(TransactionType::Sell),
+            );
+            let ready = ready && !buying && !selling;
+
             ui.horizontal(|ui| {
                 if buy_button("Buy")
                     .size(egui::Vec2::new(80.0, 30.0))
+                    .enabled(ready)
+                    .loading(buying)
+                    .debounce(ORDER_BUTTON_DEBOUNCE)
+                    .disabled_reason(app_state.trading_blocked_reason())
                     .ui(ui)
                     .clicked()
                 {
-                    place_order(app_state, "BUY");
+                    app_state.ui_input.selected_transaction_type = TransactionType::Buy;
+                    place_order(app_state);
                 }
 
                 ui.add_space(10.0);
 
                 if sell_button("Sell")
                     .size(egui::Vec2::new(80.0, 30.0))
+                    .enabled(ready)
+                    .loading(selling)
+                    .debounce(ORDER_BUTTON_DEBOUNCE)
+                    .disabled_reason(app_state.trading_blocked_reason())
                     .ui(ui)
                     .clicked()
                 {
-                    place_order(app_state, "SELL");
+                    app_state.ui_input.selected_transaction_type = TransactionType::Sell;
+                    place_order(app_state);
                 }
 
                 ui.add_space(20.0);
@@ -195,36 +618,736 @@ fn render_order_dialog(ui: &mut Ui, app_state: &mut AppState) {
     });
 }
 
-fn place_order(app_state: &mut AppState, transaction_type: &str) {
-    let quantity: i32 = app_state.ui_input.order_quantity_input.parse().unwrap_or(0);
-    let price: f64 = app_state.ui_input.order_price_input.parse().unwrap_or(0.0);
-
-    if !app_state.ui_input.order_symbol_input.is_empty() && quantity > 0 {
-        let order_request = OrderRequest {
-            tradingsymbol: app_state.ui_input.order_symbol_input.clone(),
-            exchange: "NSE".to_string(),
-            transaction_type: transaction_type.to_string(),
-            order_type: if price > 0.0 { "LIMIT" } else { "MARKET" }.to_string(),
-            quantity,
-            price: if price > 0.0 { Some(price) } else { None },
-            product: "MIS".to_string(),
-            validity: "DAY".to_string(),
-            disclosed_quantity: None,
-            trigger_price: None,
-            squareoff: None,
-            stoploss: None,
-            trailing_stoploss: None,
-            tag: Some("manual_order".to_string()),
+/// Modal for modifying an open order's quantity, price and trigger
+fn render_modify_dialog(ctx: &egui::Context, app_state: &mut AppState) {
+    let Some(mut form) = app_state.ui_input.order_edit.take() else {
+        return;
+    };
+
+    // The order may fill or be cancelled while the dialog is open
+    let order = app_state
+        .orders
+        .get(&form.order_id)
+        .map(|entry| entry.value().clone());
+    let modifiable = order.as_ref().is_some_and(|order| order.is_cancellable());
+
+    let mut keep_open = true;
+
+    let response = Modal::new("modify_order_modal", "Modify Order")
+        .size(egui::Vec2::new(360.0, 240.0))
+        .show(ctx, |ui| {
+            if let Some(order) = &order {
+                ui.label(
+                    RichText::new(format!(
+                        "{} {} · {}",
+                        order.transaction_type, order.tradingsymbol, form.order_type
+                    ))
+                    .strong(),
+                );
+                ui.label(format!(
+                    "Filled {}/{}",
+                    order.filled_quantity, order.quantity
+                ));
+            }
+            ui.add_space(10.0);
+
+            if !modifiable {
+                ui.colored_label(
+                    Color32::from_rgb(239, 68, 68),
+                    "This order is no longer open and cannot be modified",
+                );
+            }
+
+            ui.add_enabled_ui(modifiable, |ui| {
+                let mut changed = render_validated_input(ui, "Quantity:", &mut form.quantity_input);
+                if form.order_type.requires_price() {
+                    changed |= render_validated_input(ui, "Price:", &mut form.price_input);
+                }
+                if form.order_type.requires_trigger() {
+                    changed |=
+                        render_validated_input(ui, "Trigger:", &mut form.trigger_price_input);
+                }
+                if changed {
+                    form.validate();
+                }
+            });
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                if primary_button("Modify")
+                    .size(egui::Vec2::new(80.0, 30.0))
+                    .enabled(modifiable && form.is_ready())
+                    .disabled_reason(app_state.trading_blocked_reason())
+                    .ui(ui)
+                    .clicked()
+                {
+                    if let Some(details) = order.as_ref().and_then(|order| form.to_request(order)) {
+                        app_state.send_command(Command::ModifyOrder {
+                            order_id: form.order_id.clone(),
+                            details,
+                        });
+                        keep_open = false;
+                    }
+                }
+
+                ui.add_space(10.0);
+
+                if ui.button("Close").clicked() {
+                    keep_open = false;
+                }
+            });
+        });
+
+    if keep_open && !response.closed {
+        app_state.ui_input.order_edit = Some(form);
+    }
+}
+
+/// Confirm, then cancel every order that was open when the request was made
+pub fn render_cancel_all_confirmation(ctx: &egui::Context, app_state: &mut AppState) {
+    let Some(order_ids) = app_state.ui_input.pending_cancel_all.take() else {
+        return;
+    };
+
+    let decision = Modal::confirmation(
+        ctx,
+        "confirm_cancel_all",
+        "Cancel All Orders",
+        format!("Cancel {} open orders?", order_ids.len()),
+        || true,
+        || false,
+    );
+
+    match decision {
+        Some(true) => {
+            for order_id in order_ids {
+                app_state.send_command(app_state.cancel_order_command(order_id));
+            }
+        }
+        Some(false) => {}
+        None => app_state.ui_input.pending_cancel_all = Some(order_ids),
+    }
+}
+
+/// Every field of the inspected order, its status transitions and its fills
+/// The history is refetched by AppState whenever the order changes
+fn render_order_detail(ctx: &egui::Context, app_state: &mut AppState) {
+    let Some(order_id) = app_state.ui_input.inspected_order_id.clone() else {
+        return;
+    };
+    let Some(order) = app_state
+        .orders
+        .get(&order_id)
+        .map(|entry| entry.value().clone())
+    else {
+        app_state.ui_input.inspected_order_id = None;
+        return;
+    };
+    let history = app_state
+        .order_history
+        .get(&order_id)
+        .map(|entry| entry.value().clone());
+
+    let mut keep_open = true;
+
+    let response = Modal::new("order_detail_modal", "Order Details")
+        .size(egui::Vec2::new(520.0, 520.0))
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(format!(
+                        "{} {} {}",
+                        order.transaction_type, order.quantity, order.tradingsymbol
+                    ))
+                    .size(16.0)
+                    .strong(),
+                );
+                ui.label(order.status.to_string());
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&order.order_id).monospace());
+                if ui
+                    .small_button("📋")
+                    .on_hover_text("Copy order ID")
+                    .clicked()
+                {
+                    ui.ctx().copy_text(order.order_id.clone());
+                }
+            });
+
+            ui.add_space(8.0);
+
+            ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                render_order_fields(ui, &order);
+
+                ui.add_space(10.0);
+                ui.label(RichText::new("History").strong());
+                match &history {
+                    Some(history) => render_order_history(ui, history),
+                    None => {
+                        ui.label(RichText::new("Loading…").color(Color32::GRAY));
+                    }
+                }
+            });
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                if order.is_cancellable() {
+                    if primary_button("Modify")
+                        .size(egui::Vec2::new(80.0, 30.0))
+                        .disabled_reason(app_state.trading_blocked_reason())
+                        .ui(ui)
+                        .clicked()
+                    {
+                        app_state.ui_input.order_edit = Some(OrderEditForm::from_order(&order));
+                        keep_open = false;
+                    }
+
+                    if danger_button("Cancel Order")
+                        .size(egui::Vec2::new(110.0, 30.0))
+                        .disabled_reason(app_state.trading_blocked_reason())
+                        .ui(ui)
+                        .clicked()
+                    {
+                        app_state.send_command(Command::CancelOrder {
+                            order_id: order.order_id.clone(),
+                            variety: order.variety.clone(),
+                        });
+                    }
+
+                    ui.add_space(10.0);
+                }
+
+                if ui.button("Refresh").clicked() {
+                    app_state.send_command(Command::FetchOrderHistory {
+                        order_id: order.order_id.clone(),
+                    });
+                }
+
+                if ui.button("Close").clicked() {
+                    keep_open = false;
+                }
+            });
+        });
+
+    if !keep_open || response.closed {
+        app_state.ui_input.inspected_order_id = None;
+    }
+}
+
+fn render_order_fields(ui: &mut Ui, order: &Order) {
+    let optional = |value: Option<&str>| value.unwrap_or("—").to_string();
+    let fields = [
+        (
+            "Exchange order ID",
+            optional(Some(order.exchange_order_id.as_str()).filter(|id| !id.is_empty())),
+        ),
+        (
+            "Parent order ID",
+            optional(order.parent_order_id.as_deref()),
+        ),
+        ("Exchange", order.exchange.clone()),
+        ("Instrument token", order.instrument_token.to_string()),
+        ("Variety", order.variety.clone()),
+        ("Order type", order.order_type.clone()),
+        ("Product", order.product.clone()),
+        ("Validity", order.validity.clone()),
+        ("Price", format_inr(order.price)),
+        ("Trigger price", format_inr(order.trigger_price)),
+        ("Average price", format_inr(order.average_price)),
+        ("Quantity", order.quantity.to_string()),
+        ("Filled", order.filled_quantity.to_string()),
+        ("Pending", order.pending_quantity.to_string()),
+        ("Disclosed", order.disclosed_quantity.to_string()),
+        ("Placed by", order.placed_by.clone()),
+        ("Tag", optional(order.tag.as_deref())),
+        ("Placed at", format_ist(order.order_timestamp)),
+        (
+            "Exchange time",
+            order
+                .exchange_timestamp
+                .map(format_ist)
+                .unwrap_or_else(|| "—".to_string()),
+        ),
+        ("Status message", optional(order.status_message.as_deref())),
+    ];
+
+    egui::Grid::new("order_detail_fields")
+        .num_columns(2)
+        .spacing([16.0, 2.0])
+        .show(ui, |ui| {
+            for (label, value) in fields {
+                ui.label(RichText::new(label).color(Color32::GRAY));
+                ui.label(value);
+                ui.end_row();
+            }
+        });
+}
+
+fn render_order_history(ui: &mut Ui, history: &OrderHistory) {
+    egui::Grid::new("order_detail_history")
+        .num_columns(3)
+        .spacing([12.0, 2.0])
+        .striped(true)
+        .show(ui, |ui| {
+            for state in &history.states {
+                ui.label(format_ist(
+                    state.exchange_timestamp.unwrap_or(state.order_timestamp),
+                ));
+                ui.label(state.status.to_string());
+                ui.label(state.status_message.as_deref().unwrap_or(""));
+                ui.end_row();
+            }
+        });
+
+    if history.trades.is_empty() {
+        return;
+    }
+
+    ui.add_space(10.0);
+    ui.label(RichText::new("Fills").strong());
+    egui::Grid::new("order_detail_fills")
+        .num_columns(4)
+        .spacing([12.0, 2.0])
+        .striped(true)
+        .show(ui, |ui| {
+            for trade in &history.trades {
+                ui.label(format_ist(trade.fill_timestamp));
+                ui.label(RichText::new(&trade.trade_id).monospace());
+                ui.label(trade.quantity.to_string());
+                ui.label(format_inr(trade.average_price));
+                ui.end_row();
+            }
+        });
+}
+
+fn format_ist(timestamp: chrono::DateTime<chrono::Utc>) -> String {
+    timestamp
+        .with_timezone(&chrono_tz::Asia::Kolkata)
+        .format("%H:%M:%S")
+        .to_string()
+}
+
+/// Render a labelled numeric input with its validation error in red underneath
+/// Returns true when the text changed this frame
+pub fn render_validated_input<T>(ui: &mut Ui, label: &str, field: &mut ValidatedField<T>) -> bool {
+    let changed = ui
+        .horizontal(|ui| {
+            ui.label(label);
+            ui.text_edit_singleline(&mut field.text).changed()
+        })
+        .inner;
+
+    if let Some(error) = field.error() {
+        ui.colored_label(Color32::from_rgb(239, 68, 68), error);
+    }
+
+    changed
+}
+
+/// Labelled dropdown over an API enum's variants
+fn api_str_combo<T: Copy + PartialEq + std::fmt::Display>(
+    ui: &mut Ui,
+    id: &str,
+    label: &str,
+    selected: &mut T,
+    options: &[T],
+) -> bool {
+    let mut changed = false;
+    ui.label(label);
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(selected.to_string())
+        .show_ui(ui, |ui| {
+            for option in options {
+                changed |= ui
+                    .selectable_value(selected, *option, option.to_string())
+                    .changed();
+            }
+        });
+    changed
+}
+
+/// Quantity field; for instruments traded in lots, ± steps by one lot and the lot count is shown
+fn render_quantity_stepper(ui: &mut Ui, app_state: &mut AppState) -> bool {
+    let lot_size = order_instrument(app_state)
+        .map(|instrument| instrument.lot_size)
+        .filter(|lot_size| *lot_size > 1);
+
+    // Revalidate when the symbol resolves to a different lot size
+    let mut changed = lot_size != app_state.ui_input.order_lot_size;
+    app_state.ui_input.order_lot_size = lot_size;
+
+    let field = &mut app_state.ui_input.order_quantity_input;
+    ui.horizontal(|ui| {
+        ui.label("Quantity:");
+        changed |= ui
+            .add(egui::TextEdit::singleline(&mut field.text).desired_width(80.0))
+            .changed();
+
+        let Some(lot_size) = lot_size.map(|lot_size| lot_size as i32) else {
+            return;
         };
+        // Stepping from text that isn't a quantity starts over at one lot
+        let quantity =
+            parse_with_feedback::<i32>(&field.text).map_or(0, |quantity| quantity.max(0));
 
-        app_state.send_command(Command::PlaceOrder {
-            details: order_request,
+        if ui.small_button("−").clicked() {
+            let lots = (quantity - 1).max(0) / lot_size;
+            field.set(lots.max(1) * lot_size);
+            changed = true;
+        }
+        if ui.small_button("+").clicked() {
+            field.set((quantity / lot_size + 1) * lot_size);
+            changed = true;
+        }
+
+        let hint = if quantity > 0 && quantity % lot_size == 0 {
+            format!("{} lots = {} qty", quantity / lot_size, quantity)
+        } else {
+            format!("lot size {}", lot_size)
+        };
+        ui.label(RichText::new(hint).small().color(Color32::GRAY));
+    });
+
+    if let Some(error) = field.error() {
+        ui.colored_label(Color32::from_rgb(239, 68, 68), error);
+    }
+
+    changed
+}
+
+/// Dropdown of matching instruments under the symbol field; picking one fills the symbol
+fn render_symbol_suggestions(ui: &mut Ui, app_state: &mut AppState) {
+    if let Some(instrument) = &app_state.ui_input.order_instrument {
+        ui.label(
+            RichText::new(format!(
+                "{} · {} · lot {} · tick {}",
+                instrument.name, instrument.exchange, instrument.lot_size, instrument.tick_size
+            ))
+            .small()
+            .color(Color32::GRAY),
+        );
+        return;
+    }
+
+    if app_state.ui_input.symbol_suggestions.is_empty() {
+        return;
+    }
+
+    let mut picked = None;
+    ui.group(|ui| {
+        for token in &app_state.ui_input.symbol_suggestions {
+            let Some(instrument) = app_state.instruments.get(token) else {
+                continue;
+            };
+            let label = format!("{}  {}", instrument.tradingsymbol, instrument.name);
+            if ui.selectable_label(false, label).clicked() {
+                picked = Some(instrument.clone());
+            }
+        }
+    });
+
+    if let Some(instrument) = picked {
+        app_state.ui_input.order_symbol_input = instrument.tradingsymbol.clone();
+        app_state.ui_input.order_instrument = Some(instrument);
+        app_state.ui_input.symbol_suggestions.clear();
+    }
+}
+
+/// Instrument the order dialog refers to: the picked one, else a lookup on the selected exchange
+fn order_instrument(app_state: &AppState) -> Option<Instrument> {
+    app_state.ui_input.order_instrument.clone().or_else(|| {
+        app_state.find_instrument(
+            app_state.ui_input.order_symbol_input.trim(),
+            app_state.ui_input.selected_exchange.as_api_str(),
+        )
+    })
+}
+
+/// Warn when the entered price is off the instrument's tick grid and offer to round it
+fn render_tick_size_hint(ui: &mut Ui, app_state: &mut AppState) {
+    let Some(price) = app_state.ui_input.order_price_input.value() else {
+        return;
+    };
+
+    let Some(instrument) = order_instrument(app_state) else {
+        return;
+    };
+
+    if !instrument.is_valid_tick(price) {
+        let nearest = instrument.round_to_tick(price);
+
+        ui.horizontal(|ui| {
+            ui.colored_label(
+                Color32::from_rgb(239, 68, 68),
+                format!("Price must be a multiple of {}", instrument.tick_size),
+            );
+
+            if ui.small_button(format!("Round to ₹{}", nearest)).clicked() {
+                app_state.ui_input.order_price_input.set(nearest);
+            }
         });
+    }
+}
+
+fn place_order(app_state: &mut AppState) {
+    app_state.ui_input.validate_order_fields();
+    if !app_state.ui_input.order_form_ready() {
+        return;
+    }
+
+    let Some(order_request) = order_form_request(app_state) else {
+        return;
+    };
+
+    // The dialog closes once OrderPlaced comes back, and stays open to retry on rejection
+    app_state.send_command(Command::PlaceOrder {
+        details: order_request,
+    });
+}
+
+/// Order described by the dialog's current inputs
+fn order_form_request(app_state: &AppState) -> Option<OrderRequest> {
+    let quantity = app_state.ui_input.order_quantity_input.value()?;
+    let order_type = app_state.ui_input.selected_order_type;
+    let validity = app_state.ui_input.selected_validity;
+
+    let exchange = order_instrument(app_state)
+        .map(|instrument| instrument.exchange)
+        .unwrap_or_else(|| {
+            app_state
+                .ui_input
+                .selected_exchange
+                .as_api_str()
+                .to_string()
+        });
+
+    Some(OrderRequest {
+        tradingsymbol: app_state.ui_input.order_symbol_input.trim().to_string(),
+        exchange,
+        transaction_type: app_state
+            .ui_input
+            .selected_transaction_type
+            .as_api_str()
+            .to_string(),
+        order_type: order_type.as_api_str().to_string(),
+        quantity,
+        // Market and SL-M orders carry no price
+        price: if order_type.requires_price() {
+            app_state.ui_input.order_price_input.value()
+        } else {
+            None
+        },
+        product: app_state
+            .ui_input
+            .selected_product_type
+            .as_api_str()
+            .to_string(),
+        validity: validity.as_api_str().to_string(),
+        validity_ttl: if validity == Validity::Ttl {
+            app_state.ui_input.order_ttl_input.value()
+        } else {
+            None
+        },
+        disclosed_quantity: None,
+        trigger_price: if order_type.requires_trigger() {
+            app_state.ui_input.order_trigger_price_input.value()
+        } else {
+            None
+        },
+        squareoff: None,
+        stoploss: None,
+        trailing_stoploss: None,
+        tag: Some(MANUAL_ORDER_TAG.to_string()),
+        variety: "regular".to_string(),
+        bypass_margin_check: false,
+        request_id: None,
+    })
+}
+
+/// Quiet period after the last edit before the margin preview is requested
+const MARGIN_PREVIEW_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// "Required margin · Est. charges · Available" for the order as currently filled in
+/// Falls back to plain available cash while the form is incomplete
+fn render_margin_preview(ui: &mut Ui, app_state: &mut AppState, exchange: &str) {
+    let request = app_state
+        .ui_input
+        .order_form_ready()
+        .then(|| order_form_request(app_state))
+        .flatten();
+    let inputs = request
+        .as_ref()
+        .and_then(|request| serde_json::to_string(request).ok())
+        .unwrap_or_default();
+
+    let preview = &mut app_state.ui_input.margin_preview;
+    if inputs != preview.inputs {
+        preview.inputs = inputs;
+        preview.changed_at = Some(Instant::now());
+        preview.result = None;
+    }
+
+    let Some(request) = request else {
+        render_available_cash(ui, app_state, exchange);
+        return;
+    };
+
+    if let Some(changed_at) = preview.changed_at {
+        let waited = changed_at.elapsed();
+        if waited >= MARGIN_PREVIEW_DEBOUNCE {
+            preview.changed_at = None;
+            preview.requested_id += 1;
+            let preview_id = preview.requested_id;
+            app_state.send_command(Command::PreviewOrderMargin {
+                preview_id,
+                details: request,
+            });
+        } else {
+            ui.ctx()
+                .request_repaint_after(MARGIN_PREVIEW_DEBOUNCE - waited);
+        }
+    }
+
+    let available = app_state
+        .margins
+        .read()
+        .as_ref()
+        .map(|(_, margins)| margins.for_exchange(exchange).net);
+    let available_text = available.map(format_inr).unwrap_or_else(|| "—".to_string());
+
+    let text = match &app_state.ui_input.margin_preview.result {
+        None => RichText::new(format!("Previewing margin… · Available {}", available_text))
+            .color(Color32::GRAY),
+        Some(Err(_)) => RichText::new(format!(
+            "Margin preview unavailable · Available {}",
+            available_text
+        ))
+        .color(Color32::GRAY),
+        Some(Ok(margin)) => {
+            let text = RichText::new(format!(
+                "Required margin {} · Est. charges {} · Available {}",
+                format_inr(margin.total),
+                format_inr(margin.charges.total),
+                available_text
+            ));
+            if available.is_some_and(|available| margin.total > available) {
+                text.color(app_state.theme.loss)
+            } else {
+                text
+            }
+        }
+    };
+    ui.label(text.small());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{Config, SortState};
+    use chrono::{TimeZone, Utc};
+
+    /// Open INFY order placed `minute` minutes into the session
+    fn order_at(order_id: &str, minute: u32, price: f64) -> Order {
+        let mut order = Order::limit(order_id, OrderStatus::Open);
+        order.order_timestamp = Utc.with_ymd_and_hms(2024, 1, 15, 4, minute, 0).unwrap();
+        order.price = price;
+        order
+    }
+
+    fn ids(orders: &[Order]) -> Vec<&str> {
+        orders.iter().map(|order| order.order_id.as_str()).collect()
+    }
+
+    fn state_with(orders: Vec<Order>) -> AppState {
+        let (state, _receivers) = AppState::new(Config::for_tests());
+        for order in orders {
+            state.orders.insert(order.order_id.clone(), order);
+        }
+        state
+    }
+
+    #[test]
+    fn orders_list_newest_first_by_default() {
+        let state = state_with(vec![
+            order_at("A", 1, 100.0),
+            order_at("C", 3, 100.0),
+            order_at("B", 2, 100.0),
+        ]);
+        assert_eq!(ids(&visible_orders(&state)), ["C", "B", "A"]);
+    }
+
+    #[test]
+    fn header_clicks_sort_ascending_then_flip() {
+        let mut state = state_with(vec![
+            order_at("A", 1, 300.0),
+            order_at("B", 2, 100.0),
+            order_at("C", 3, 200.0),
+        ]);
+
+        state.ui_input.order_sort.toggle(OrderColumn::Price);
+        assert_eq!(ids(&visible_orders(&state)), ["B", "C", "A"]);
+        state.ui_input.order_sort.toggle(OrderColumn::Price);
+        assert_eq!(ids(&visible_orders(&state)), ["A", "C", "B"]);
+    }
+
+    #[test]
+    fn equal_keys_keep_a_stable_order() {
+        // Same price and time: the order id decides, whatever order the map yields
+        let state = state_with(vec![
+            order_at("B", 1, 100.0),
+            order_at("A", 1, 100.0),
+            order_at("C", 1, 100.0),
+        ]);
+        let sort = SortState {
+            column: OrderColumn::Price,
+            descending: false,
+        };
+        let mut orders = visible_orders(&state);
+        orders.sort_by(|a, b| sort.apply(sort.column.compare(a, b)));
+        assert_eq!(ids(&orders), ["A", "B", "C"]);
+    }
+
+    #[test]
+    fn hovered_table_keeps_rows_in_place() {
+        let mut state = state_with(vec![order_at("A", 1, 100.0), order_at("B", 2, 100.0)]);
+        state.ui_input.frozen_order_ids = Some(vec!["B".to_string(), "A".to_string()]);
+
+        // A newer order streams in; it goes below the frozen rows until the pointer leaves
+        state
+            .orders
+            .insert("C".to_string(), order_at("C", 3, 100.0));
+        assert_eq!(ids(&visible_orders(&state)), ["B", "A", "C"]);
+
+        state.ui_input.frozen_order_ids = None;
+        assert_eq!(ids(&visible_orders(&state)), ["C", "B", "A"]);
+    }
+
+    #[test]
+    fn order_dialog_resolves_symbols_on_the_selected_exchange() {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        for instrument in [
+            Instrument::equity(1, "INFY", "NSE"),
+            Instrument::equity(2, "INFY", "BSE"),
+        ] {
+            state
+                .instruments
+                .insert(instrument.instrument_token, instrument);
+        }
+        *state.instrument_index.write() =
+            crate::instrument_index::InstrumentIndex::build(&state.instruments);
+        let resolved = |state: &AppState| order_instrument(state).map(|i| i.instrument_token);
+
+        state.ui_input.order_symbol_input = "INFY".to_string();
+        assert_eq!(resolved(&state), Some(1));
+        state.ui_input.selected_exchange = Exchange::Bse;
+        assert_eq!(resolved(&state), Some(2));
 
-        // Clear inputs
-        app_state.ui_input.order_symbol_input.clear();
-        app_state.ui_input.order_quantity_input.clear();
-        app_state.ui_input.order_price_input.clear();
-        app_state.ui_input.show_order_dialog = false;
+        // A pick from the suggestions wins over the typed symbol
+        state.ui_input.order_instrument = Some(Instrument::equity(1, "INFY", "NSE"));
+        assert_eq!(resolved(&state), Some(1));
     }
 }
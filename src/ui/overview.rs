@@ -1,8 +1,11 @@
+use crate::app::AppView;
 use crate::data_structures::*;
-use crate::state::{AppState, Command};
+use crate::market_calendar::{ist_now, ClosedReason, MarketStatus};
+use crate::state::{AppState, Command, DayMover, PositionFilters};
 use crate::ui::components::{primary_button, success_button};
-use chrono::Datelike;
-use egui::{Color32, RichText, ScrollArea, Ui};
+use crate::ui::format::{format_inr, format_inr_compact};
+use crate::ui::funds::render_funds_card;
+use egui::{Color32, RichText, ScrollArea, Sense, Ui};
 
 /// Render comprehensive overview dashboard
 /// Optimized for at-a-glance trading information and quick actions
@@ -11,6 +14,9 @@ pub fn render_overview(ui: &mut Ui, app_state: &mut AppState) {
         // Quick stats row
         render_quick_stats(ui, app_state);
 
+        ui.add_space(20.0);
+        render_day_movers(ui, app_state);
+
         ui.add_space(20.0);
         ui.separator();
         ui.add_space(20.0);
@@ -26,28 +32,102 @@ pub fn render_overview(ui: &mut Ui, app_state: &mut AppState) {
             columns[1].vertical(|ui| {
                 render_orders_summary(ui, app_state);
                 ui.add_space(20.0);
+                render_funds_card(ui, app_state);
+                ui.add_space(20.0);
                 render_quick_actions(ui, app_state);
             });
         });
     });
 }
 
+/// Movers shown on each side of the day movers row
+const MOVERS_PER_SIDE: usize = 3;
+
+/// Biggest gainers and losers by day change among positions and the watchlist
+fn render_day_movers(ui: &mut Ui, app_state: &mut AppState) {
+    let key = (
+        app_state.ticks_gen.get(),
+        app_state.positions_gen.get(),
+        app_state.watchlist.read().entries.len(),
+    );
+    if !app_state.ui_input.day_movers.is_current(&key) {
+        let movers = app_state.day_movers();
+        app_state.ui_input.day_movers.store(key, movers);
+    }
+    let movers = app_state.ui_input.day_movers.rows();
+
+    let gainers: Vec<&DayMover> = movers
+        .iter()
+        .take_while(|mover| mover.change_pct > 0.0)
+        .take(MOVERS_PER_SIDE)
+        .collect();
+    let losers: Vec<&DayMover> = movers
+        .iter()
+        .rev()
+        .take_while(|mover| mover.change_pct < 0.0)
+        .take(MOVERS_PER_SIDE)
+        .collect();
+
+    ui.horizontal_wrapped(|ui| {
+        for (title, side) in [("▲ Top Gainers", gainers), ("▼ Top Losers", losers)] {
+            ui.vertical(|ui| {
+                ui.label(RichText::new(title).strong());
+                ui.horizontal(|ui| {
+                    if side.is_empty() {
+                        ui.label(RichText::new("None yet").color(Color32::GRAY));
+                    }
+                    for mover in side {
+                        render_mover_card(ui, app_state, mover);
+                    }
+                });
+            });
+            ui.add_space(20.0);
+        }
+    });
+}
+
+/// Compact card for one mover; clicking opens its position row, or its chart when not held
+fn render_mover_card(ui: &mut Ui, app_state: &mut AppState, mover: &DayMover) {
+    let color = app_state.theme.direction_color(mover.change_pct >= 0.0);
+    let response = ui
+        .group(|ui| {
+            ui.vertical(|ui| {
+                ui.label(RichText::new(&mover.tradingsymbol).strong());
+                ui.horizontal(|ui| {
+                    ui.label(format_inr(mover.last_price));
+                    ui.colored_label(color, format!("{:+.2}%", mover.change_pct));
+                });
+            });
+        })
+        .response
+        .interact(Sense::click())
+        .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+    if response.clicked() {
+        app_state.select_instrument(mover.instrument_token);
+        app_state.ui_input.requested_view = Some(if mover.in_positions {
+            app_state.ui_input.position_filter = mover.tradingsymbol.clone();
+            app_state.ui_input.position_filters = PositionFilters::default();
+            AppView::Positions
+        } else {
+            AppView::Charts
+        });
+    }
+}
+
 /// Render quick statistics cards
 fn render_quick_stats(ui: &mut Ui, app_state: &AppState) {
     ui.horizontal(|ui| {
         let pnl_data = app_state.calculate_total_pnl();
+        let abbreviate = app_state.config.app.abbreviate_crores;
 
         // Total PnL card
         ui.group(|ui| {
             ui.vertical(|ui| {
                 ui.label(RichText::new("Total P&L").strong());
-                let color = if pnl_data.total >= 0.0 {
-                    Color32::from_rgb(34, 197, 94) // Green
-                } else {
-                    Color32::from_rgb(239, 68, 68) // Red
-                };
+                let color = app_state.theme.direction_color(pnl_data.total >= 0.0);
                 ui.label(
-                    RichText::new(format!("₹{:.2}", pnl_data.total))
+                    RichText::new(format_inr_compact(pnl_data.total, abbreviate))
                         .size(24.0)
                         .color(color),
                 );
@@ -60,13 +140,9 @@ fn render_quick_stats(ui: &mut Ui, app_state: &AppState) {
         ui.group(|ui| {
             ui.vertical(|ui| {
                 ui.label(RichText::new("Day P&L").strong());
-                let color = if pnl_data.day_pnl >= 0.0 {
-                    Color32::from_rgb(34, 197, 94)
-                } else {
-                    Color32::from_rgb(239, 68, 68)
-                };
+                let color = app_state.theme.direction_color(pnl_data.day_pnl >= 0.0);
                 ui.label(
-                    RichText::new(format!("₹{:.2}", pnl_data.day_pnl))
+                    RichText::new(format_inr_compact(pnl_data.day_pnl, abbreviate))
                         .size(20.0)
                         .color(color),
                 );
@@ -138,14 +214,10 @@ fn render_positions_summary(ui: &mut Ui, app_state: &AppState) {
 
                 ui.label(&position.tradingsymbol);
                 ui.label(format!("{}", position.quantity));
-                ui.label(format!("₹{:.2}", position.last_price));
+                ui.label(format_inr(position.last_price));
 
-                let pnl_color = if position.pnl >= 0.0 {
-                    Color32::from_rgb(34, 197, 94)
-                } else {
-                    Color32::from_rgb(239, 68, 68)
-                };
-                ui.colored_label(pnl_color, format!("₹{:.2}", position.pnl));
+                let pnl_color = app_state.theme.direction_color(position.pnl >= 0.0);
+                ui.colored_label(pnl_color, format_inr(position.pnl));
                 ui.end_row();
             }
 
@@ -201,9 +273,12 @@ fn render_orders_summary(ui: &mut Ui, app_state: &AppState) {
                     OrderStatus::Open => Color32::from_rgb(59, 130, 246),
                     OrderStatus::Cancelled => Color32::from_rgb(107, 114, 128),
                     OrderStatus::Rejected => Color32::from_rgb(239, 68, 68),
+                    OrderStatus::CancelledAmo | OrderStatus::Unknown(_) => {
+                        Color32::from_rgb(107, 114, 128)
+                    }
                     _ => Color32::from_rgb(245, 158, 11),
                 };
-                ui.colored_label(status_color, format!("{:?}", order.status));
+                ui.colored_label(status_color, order.status.to_string());
                 ui.end_row();
             }
 
@@ -229,14 +304,7 @@ fn render_quick_actions(ui: &mut Ui, app_state: &mut AppState) {
             .ui(ui)
             .clicked()
         {
-            app_state.send_command(Command::FetchPositions);
-            app_state.send_command(Command::FetchOrders);
-
-            app_state.add_log(
-                LogLevel::Info,
-                "Refreshing all data...".to_string(),
-                Some("overview".to_string()),
-            );
+            app_state.refresh_all();
         }
 
         ui.add_space(10.0);
@@ -271,20 +339,13 @@ fn render_quick_actions(ui: &mut Ui, app_state: &mut AppState) {
         ui.add_space(10.0);
 
         // Fetch instruments
-        if primary_button("📥 Load NSE Instruments")
+        if primary_button("📥 Load Instruments")
             .size(egui::Vec2::new(200.0, 35.0))
             .ui(ui)
+            .on_hover_text("The exchanges in app.instrument_exchanges, or NSE")
             .clicked()
         {
-            app_state.send_command(Command::FetchInstruments {
-                exchange: "NSE".to_string(),
-            });
-
-            app_state.add_log(
-                LogLevel::Info,
-                "Loading NSE instruments...".to_string(),
-                Some("overview".to_string()),
-            );
+            app_state.refresh_instruments();
         }
     });
 
@@ -296,27 +357,23 @@ fn render_quick_actions(ui: &mut Ui, app_state: &mut AppState) {
             ui.label(RichText::new("Market Status").strong());
             ui.add_space(5.0);
 
-            // Simple market hours check (9:15 AM to 3:30 PM IST)
-            let now = chrono::Local::now();
-            let market_open = now.date_naive().and_hms_opt(9, 15, 0).unwrap();
-            let market_close = now.date_naive().and_hms_opt(15, 30, 0).unwrap();
-            let current_time = now.time();
-
-            let is_weekend =
-                now.weekday() == chrono::Weekday::Sat || now.weekday() == chrono::Weekday::Sun;
-
-            if is_weekend {
-                ui.colored_label(
-                    Color32::from_rgb(107, 114, 128),
-                    "🔒 Market Closed (Weekend)",
-                );
-            } else if current_time >= market_open.time() && current_time <= market_close.time() {
-                ui.colored_label(Color32::from_rgb(34, 197, 94), "🟢 Market Open");
-            } else {
-                ui.colored_label(Color32::from_rgb(239, 68, 68), "🔴 Market Closed");
+            for exchange in ["NSE", "MCX"] {
+                let status = MarketStatus::now(exchange);
+                let (icon, color) = match status {
+                    MarketStatus::Open => ("🟢", Color32::from_rgb(34, 197, 94)),
+                    MarketStatus::PreOpen
+                    | MarketStatus::ClosingSession
+                    | MarketStatus::EveningSession => ("🟡", Color32::from_rgb(245, 158, 11)),
+                    MarketStatus::Closed(ClosedReason::OutsideHours) => {
+                        ("🔴", Color32::from_rgb(239, 68, 68))
+                    }
+                    MarketStatus::Closed(_) => ("🔒", Color32::from_rgb(107, 114, 128)),
+                };
+                ui.colored_label(color, format!("{} {}: {}", icon, exchange, status.label()));
             }
 
-            ui.label(format!("Current Time: {}", now.format("%H:%M:%S")));
+            let now = ist_now();
+            ui.label(format!("Current Time: {} IST", now.format("%H:%M:%S")));
         });
     });
 }
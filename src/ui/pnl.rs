@@ -1,13 +1,27 @@
-use crate::state::AppState;
-use egui::{Color32, RichText, Ui};
+use crate::data_structures::{LogLevel, PnlData};
+use crate::pnl_history::PnlSample;
+use crate::state::{AppState, Command, PnlGrouping};
+use crate::ui::components::{danger_button, row_button_size};
+use crate::ui::format::{format_inr, format_inr_compact_signed, format_inr_signed};
+use chrono::{DateTime, Utc};
+use chrono_tz::Asia::Kolkata;
+use egui::{Align2, Color32, FontId, Rect, RichText, Sense, Stroke, Ui};
+
+const TOTAL_COLOR: Color32 = Color32::from_rgb(250, 204, 21);
+const REALIZED_COLOR: Color32 = Color32::from_rgb(59, 130, 246);
+const UNREALIZED_COLOR: Color32 = Color32::from_rgb(168, 85, 247);
+
+/// Picks one series' value out of a P&L sample
+type SampleValue = fn(&PnlSample) -> f64;
 
 /// Render P&L analytics and performance metrics
-pub fn render_pnl(ui: &mut Ui, app_state: &AppState) {
+pub fn render_pnl(ui: &mut Ui, app_state: &mut AppState) {
     ui.vertical(|ui| {
         ui.label(RichText::new("Profit & Loss").size(24.0).strong());
         ui.add_space(20.0);
 
         let pnl_data = app_state.calculate_total_pnl();
+        let abbreviate = app_state.config.app.abbreviate_crores;
 
         // P&L Summary Cards
         ui.horizontal(|ui| {
@@ -15,14 +29,9 @@ pub fn render_pnl(ui: &mut Ui, app_state: &AppState) {
             ui.group(|ui| {
                 ui.vertical(|ui| {
                     ui.label(RichText::new("Total P&L").size(16.0).strong());
-                    let color = if pnl_data.total >= 0.0 {
-                        Color32::from_rgb(34, 197, 94)
-                    } else {
-                        Color32::from_rgb(239, 68, 68)
-                    };
-                    let prefix = if pnl_data.total >= 0.0 { "+" } else { "" };
+                    let color = app_state.theme.direction_color(pnl_data.total >= 0.0);
                     ui.label(
-                        RichText::new(format!("{}₹{:.2}", prefix, pnl_data.total))
+                        RichText::new(format_inr_compact_signed(pnl_data.total, abbreviate))
                             .size(24.0)
                             .color(color),
                     );
@@ -35,14 +44,9 @@ pub fn render_pnl(ui: &mut Ui, app_state: &AppState) {
             ui.group(|ui| {
                 ui.vertical(|ui| {
                     ui.label(RichText::new("Realized P&L").size(16.0).strong());
-                    let color = if pnl_data.realized >= 0.0 {
-                        Color32::from_rgb(34, 197, 94)
-                    } else {
-                        Color32::from_rgb(239, 68, 68)
-                    };
-                    let prefix = if pnl_data.realized >= 0.0 { "+" } else { "" };
+                    let color = app_state.theme.direction_color(pnl_data.realized >= 0.0);
                     ui.label(
-                        RichText::new(format!("{}₹{:.2}", prefix, pnl_data.realized))
+                        RichText::new(format_inr_compact_signed(pnl_data.realized, abbreviate))
                             .size(20.0)
                             .color(color),
                     );
@@ -55,14 +59,9 @@ pub fn render_pnl(ui: &mut Ui, app_state: &AppState) {
             ui.group(|ui| {
                 ui.vertical(|ui| {
                     ui.label(RichText::new("Unrealized P&L").size(16.0).strong());
-                    let color = if pnl_data.unrealized >= 0.0 {
-                        Color32::from_rgb(34, 197, 94)
-                    } else {
-                        Color32::from_rgb(239, 68, 68)
-                    };
-                    let prefix = if pnl_data.unrealized >= 0.0 { "+" } else { "" };
+                    let color = app_state.theme.direction_color(pnl_data.unrealized >= 0.0);
                     ui.label(
-                        RichText::new(format!("{}₹{:.2}", prefix, pnl_data.unrealized))
+                        RichText::new(format_inr_compact_signed(pnl_data.unrealized, abbreviate))
                             .size(20.0)
                             .color(color),
                     );
@@ -74,46 +73,605 @@ pub fn render_pnl(ui: &mut Ui, app_state: &AppState) {
         ui.separator();
         ui.add_space(20.0);
 
-        // Position-wise P&L breakdown
+        render_equity_curve(ui, app_state);
+
+        if app_state.history.is_some() {
+            ui.add_space(30.0);
+            ui.separator();
+            ui.add_space(20.0);
+
+            render_history(ui, app_state, &pnl_data);
+        }
+
+        ui.add_space(30.0);
+        ui.separator();
+        ui.add_space(20.0);
+
+        render_pnl_breakdown(ui, app_state, &pnl_data);
+
+        ui.add_space(30.0);
+        ui.separator();
+        ui.add_space(20.0);
+
+        render_tag_breakdown(ui, app_state);
+
+        if !app_state.strategies.read().is_empty() {
+            ui.add_space(30.0);
+            ui.separator();
+            ui.add_space(20.0);
+
+            render_strategies(ui, app_state);
+        }
+    });
+}
+
+/// Render the session equity curve with toggleable series, fill markers and drawdown
+fn render_equity_curve(ui: &mut Ui, app_state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Equity Curve").size(18.0).strong());
+        ui.add_space(20.0);
+
+        let series = &mut app_state.ui_input.equity_series;
+        ui.checkbox(&mut series.total, RichText::new("Total").color(TOTAL_COLOR));
+        ui.checkbox(
+            &mut series.realized,
+            RichText::new("Realized").color(REALIZED_COLOR),
+        );
+        ui.checkbox(
+            &mut series.unrealized,
+            RichText::new("Unrealized").color(UNREALIZED_COLOR),
+        );
+    });
+    ui.add_space(10.0);
+
+    let history = app_state.pnl_history.read();
+    let samples = history.samples();
+    if samples.len() < 2 {
+        ui.label(
+            RichText::new("The curve appears once P&L has been sampled for a few seconds")
+                .color(Color32::GRAY),
+        );
+        return;
+    }
+
+    let size = egui::vec2(ui.available_width(), 260.0);
+    let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+
+    // Leave room on the left for ₹ axis labels
+    let plot = Rect::from_min_max(
+        rect.min + egui::vec2(70.0, 10.0),
+        rect.max - egui::vec2(10.0, 20.0),
+    );
+    let series = app_state.ui_input.equity_series;
+
+    let first = samples
+        .front()
+        .map(|sample| sample.timestamp)
+        .unwrap_or_default();
+    let last = samples
+        .back()
+        .map(|sample| sample.timestamp)
+        .unwrap_or_default();
+    let span = (last - first).num_milliseconds().max(1) as f32;
+    let time_x = |timestamp: DateTime<Utc>| {
+        plot.left() + (timestamp - first).num_milliseconds() as f32 / span * plot.width()
+    };
+
+    let (mut low, mut high) = (0.0_f64, 0.0_f64);
+    for sample in samples {
+        for (enabled, value) in [
+            (series.total, sample.total),
+            (series.realized, sample.realized),
+            (series.unrealized, sample.unrealized),
+        ] {
+            if enabled {
+                low = low.min(value);
+                high = high.max(value);
+            }
+        }
+    }
+
+    let step = nice_step((high - low).max(1.0) / 5.0);
+    low = (low / step).floor() * step;
+    high = (high / step).ceil() * step;
+    let value_y =
+        |value: f64| plot.bottom() - ((value - low) / (high - low)) as f32 * plot.height();
+
+    // ₹ gridlines
+    let grid_stroke = Stroke::new(1.0, ui.visuals().faint_bg_color);
+    let label_color = ui.visuals().weak_text_color();
+    let mut value = low;
+    while value <= high + step / 2.0 {
+        let y = value_y(value);
+        painter.hline(plot.x_range(), y, grid_stroke);
+        painter.text(
+            egui::pos2(plot.left() - 6.0, y),
+            Align2::RIGHT_CENTER,
+            format_rupees(value, step),
+            FontId::monospace(11.0),
+            label_color,
+        );
+        value += step;
+    }
+    painter.hline(
+        plot.x_range(),
+        value_y(0.0),
+        Stroke::new(1.0, Color32::from_gray(110)),
+    );
+
+    // Time axis at both ends
+    for (timestamp, align) in [(first, Align2::LEFT_TOP), (last, Align2::RIGHT_TOP)] {
+        painter.text(
+            egui::pos2(time_x(timestamp), plot.bottom() + 4.0),
+            align,
+            timestamp
+                .with_timezone(&Kolkata)
+                .format("%H:%M")
+                .to_string(),
+            FontId::monospace(11.0),
+            label_color,
+        );
+    }
+
+    let lines: [(bool, Color32, SampleValue); 3] = [
+        (series.realized, REALIZED_COLOR, |sample| sample.realized),
+        (series.unrealized, UNREALIZED_COLOR, |sample| {
+            sample.unrealized
+        }),
+        (series.total, TOTAL_COLOR, |sample| sample.total),
+    ];
+    for (enabled, color, value_of) in lines {
+        if !enabled {
+            continue;
+        }
+        let points = samples
+            .iter()
+            .map(|sample| egui::pos2(time_x(sample.timestamp), value_y(value_of(sample))))
+            .collect();
+        painter.add(egui::Shape::line(points, Stroke::new(1.5, color)));
+    }
+
+    // Fill markers along the bottom edge
+    let mut hovered_fill = None;
+    for fill in history.fills() {
+        if fill.timestamp < first || fill.timestamp > last {
+            continue;
+        }
+        let center = egui::pos2(time_x(fill.timestamp), plot.bottom() - 6.0);
+        let color = app_state.theme.direction_color(fill.is_buy);
+        painter.circle_filled(center, 4.0, color);
+        if response
+            .hover_pos()
+            .is_some_and(|pointer| pointer.distance(center) < 6.0)
+        {
+            hovered_fill = Some(fill.label.clone());
+        }
+    }
+
+    // High-water mark and the deepest drawdown from it
+    if series.total {
+        if let Some(high_water) = history.high_water() {
+            let y = value_y(high_water.total);
+            painter.hline(
+                plot.x_range(),
+                y,
+                Stroke::new(1.0, TOTAL_COLOR.gamma_multiply(0.4)),
+            );
+            painter.text(
+                egui::pos2(plot.right(), y - 2.0),
+                Align2::RIGHT_BOTTOM,
+                format!("High {}", format_inr(high_water.total)),
+                FontId::monospace(11.0),
+                TOTAL_COLOR,
+            );
+        }
+
+        if let Some(drawdown) = history.max_drawdown() {
+            let x = time_x(drawdown.trough.timestamp);
+            painter.line_segment(
+                [
+                    egui::pos2(x, value_y(drawdown.peak.total)),
+                    egui::pos2(x, value_y(drawdown.trough.total)),
+                ],
+                Stroke::new(1.0, app_state.theme.loss),
+            );
+            painter.text(
+                egui::pos2(x + 4.0, value_y(drawdown.trough.total)),
+                Align2::LEFT_TOP,
+                format!("Max DD {}", format_inr(drawdown.amount())),
+                FontId::monospace(11.0),
+                app_state.theme.loss,
+            );
+        }
+    }
+
+    drop(history);
+    if let Some(label) = hovered_fill {
+        response.on_hover_text(label);
+    }
+}
+
+/// Render realized P&L per day from the history store, with today's running figure last
+fn render_history(ui: &mut Ui, app_state: &AppState, today: &PnlData) {
+    ui.label(RichText::new("History").size(18.0).strong());
+    ui.add_space(10.0);
+
+    // Date, realized, hover text
+    let mut days: Vec<(String, f64, String)> = app_state
+        .daily_history
+        .iter()
+        .map(|day| {
+            let mut hover = format!(
+                "{}\nRealized {}\nUnrealized at close {}\n{} trades",
+                day.date.format("%a %d %b %Y"),
+                format_inr_signed(day.realized),
+                format_inr_signed(day.unrealized_close),
+                day.trades
+            );
+            if let Some(fees) = day.fees {
+                hover.push_str(&format!("\nFees {}", format_inr(fees)));
+            }
+            (day.date.format("%d %b").to_string(), day.realized, hover)
+        })
+        .collect();
+    days.push((
+        "Today".to_string(),
+        today.realized,
+        format!(
+            "Today so far\nRealized {}\nUnrealized {}",
+            format_inr_signed(today.realized),
+            format_inr_signed(today.unrealized)
+        ),
+    ));
+    if days.len() < 2 {
+        ui.label(
+            RichText::new("Earlier days appear here once the history store has recorded them")
+                .color(Color32::GRAY),
+        );
+    }
+
+    let size = egui::vec2(ui.available_width(), 180.0);
+    let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+
+    let plot = Rect::from_min_max(
+        rect.min + egui::vec2(70.0, 10.0),
+        rect.max - egui::vec2(10.0, 20.0),
+    );
+
+    let (mut low, mut high) = (0.0_f64, 0.0_f64);
+    for (_, realized, _) in &days {
+        low = low.min(*realized);
+        high = high.max(*realized);
+    }
+    let step = nice_step((high - low).max(1.0) / 4.0);
+    low = (low / step).floor() * step;
+    high = (high / step).ceil() * step;
+    let value_y =
+        |value: f64| plot.bottom() - ((value - low) / (high - low)) as f32 * plot.height();
+
+    let label_color = ui.visuals().weak_text_color();
+    let mut value = low;
+    while value <= high + step / 2.0 {
+        let y = value_y(value);
+        painter.hline(
+            plot.x_range(),
+            y,
+            Stroke::new(1.0, ui.visuals().faint_bg_color),
+        );
+        painter.text(
+            egui::pos2(plot.left() - 6.0, y),
+            Align2::RIGHT_CENTER,
+            format_rupees(value, step),
+            FontId::monospace(11.0),
+            label_color,
+        );
+        value += step;
+    }
+    let zero = value_y(0.0);
+    painter.hline(
+        plot.x_range(),
+        zero,
+        Stroke::new(1.0, Color32::from_gray(110)),
+    );
+
+    let slot = plot.width() / days.len() as f32;
+    let mut hovered = None;
+    for (index, (label, realized, hover)) in days.iter().enumerate() {
+        let left = plot.left() + slot * index as f32;
+        let bar = Rect::from_x_y_ranges(
+            (left + slot * 0.15)..=(left + slot * 0.85),
+            value_y(*realized).min(zero)..=value_y(*realized).max(zero),
+        );
+        let mut color = app_state.theme.direction_color(*realized >= 0.0);
+        if index + 1 == days.len() {
+            // Today's bar still moves
+            color = color.gamma_multiply(0.6);
+        }
+        painter.rect_filled(bar, 1.0, color);
+        if response
+            .hover_pos()
+            .is_some_and(|pointer| (left..left + slot).contains(&pointer.x))
+        {
+            hovered = Some(hover.clone());
+        }
+        // Label the ends and every few bars between so labels don't overlap
+        if index == 0 || index + 1 == days.len() || index % 5 == 0 {
+            painter.text(
+                egui::pos2(left + slot / 2.0, plot.bottom() + 4.0),
+                Align2::CENTER_TOP,
+                label,
+                FontId::monospace(11.0),
+                label_color,
+            );
+        }
+    }
+
+    if let Some(hover) = hovered {
+        response.on_hover_text(hover);
+    }
+}
+
+/// Round a raw axis step up to 1, 2 or 5 times a power of ten
+fn nice_step(raw: f64) -> f64 {
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let normalized = raw / magnitude;
+    let nice = if normalized <= 1.0 {
+        1.0
+    } else if normalized <= 2.0 {
+        2.0
+    } else if normalized <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+/// Axis label in rupees with only as many decimals as the step needs
+fn format_rupees(value: f64, step: f64) -> String {
+    let formatted = format_inr(value);
+    if step >= 1.0 {
+        formatted.trim_end_matches(".00").to_string()
+    } else {
+        formatted
+    }
+}
+
+/// Position-wise P&L, optionally grouped into collapsible groups with subtotal rows
+fn render_pnl_breakdown(ui: &mut Ui, app_state: &mut AppState, total: &PnlData) {
+    ui.horizontal(|ui| {
         ui.label(RichText::new("Position-wise P&L").size(18.0).strong());
-        ui.add_space(10.0);
+        ui.add_space(20.0);
+        ui.label("Group by:");
+        for grouping in PnlGrouping::ALL {
+            ui.selectable_value(
+                &mut app_state.ui_input.pnl_grouping,
+                grouping,
+                grouping.label(),
+            );
+        }
+    });
+    ui.add_space(10.0);
+
+    if app_state.positions.is_empty() {
+        ui.label("No positions to display P&L");
+        return;
+    }
 
-        if app_state.positions.is_empty() {
-            ui.label("No positions to display P&L");
+    let grouping = app_state.ui_input.pnl_grouping;
+    let snapshot = app_state.positions_snapshot.positions();
+    let groups = app_state.pnl_groups(&snapshot, grouping);
+    let theme = app_state.theme;
+    let collapsed = &mut app_state.ui_input.collapsed_pnl_groups;
+
+    // Share of the headline total; meaningless when the total is close to zero
+    let share = |value: f64| {
+        if total.total.abs() < 0.01 {
+            "—".to_string()
         } else {
-            egui::Grid::new("pnl_table")
-                .num_columns(5)
-                .spacing([10.0, 4.0])
-                .striped(true)
-                .show(ui, |ui| {
-                    // Header
-                    ui.label(RichText::new("Symbol").strong());
-                    ui.label(RichText::new("Quantity").strong());
-                    ui.label(RichText::new("Avg Price").strong());
-                    ui.label(RichText::new("LTP").strong());
-                    ui.label(RichText::new("P&L").strong());
+            format!("{:.1}%", value / total.total * 100.0)
+        }
+    };
+    let signed_cells = |ui: &mut Ui, pnl: &PnlData, strong: bool| {
+        for value in [pnl.realized, pnl.unrealized, pnl.total] {
+            let text =
+                RichText::new(format_inr_signed(value)).color(theme.direction_color(value >= 0.0));
+            ui.label(if strong { text.strong() } else { text });
+        }
+        ui.label(share(pnl.total));
+    };
+
+    egui::Grid::new("pnl_table")
+        .num_columns(8)
+        .spacing([10.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            // Header
+            for header in [
+                "Symbol",
+                "Quantity",
+                "Avg Price",
+                "LTP",
+                "Realized",
+                "Unrealized",
+                "P&L",
+                "% of Total",
+            ] {
+                ui.label(RichText::new(header).strong());
+            }
+            ui.end_row();
+
+            for (key, subtotal, positions) in &groups {
+                if grouping != PnlGrouping::None {
+                    let folded = collapsed.contains(key);
+                    let icon = if folded { "▶" } else { "▼" };
+                    let title = format!("{} {} ({})", icon, key, positions.len());
+                    if ui
+                        .add(egui::Label::new(RichText::new(title).strong()).sense(Sense::click()))
+                        .clicked()
+                        && !collapsed.remove(key)
+                    {
+                        collapsed.insert(key.clone());
+                    }
+                    ui.label("");
+                    ui.label("");
+                    ui.label("");
+                    signed_cells(ui, subtotal, true);
+                    ui.end_row();
+
+                    if folded {
+                        continue;
+                    }
+                }
+
+                for position in positions {
+                    ui.label(&position.tradingsymbol);
+                    ui.label(format!("{}", position.quantity));
+                    ui.label(format_inr(position.average_price));
+                    ui.label(format_inr(position.last_price));
+                    let mut pnl = PnlData::default();
+                    pnl.add_position(position);
+                    signed_cells(ui, &pnl, false);
                     ui.end_row();
+                }
+            }
+
+            // Subtotals sum to this row by construction
+            ui.label(RichText::new("Total").strong());
+            ui.label("");
+            ui.label("");
+            ui.label("");
+            signed_cells(ui, total, true);
+            ui.end_row();
+        });
+}
+
+/// Render net positions grouped by strategy tag with a flatten action per tag
+fn render_tag_breakdown(ui: &mut Ui, app_state: &AppState) {
+    ui.label(RichText::new("Strategy Tags").size(18.0).strong());
+    ui.add_space(10.0);
+
+    let tag_positions = app_state.tag_positions.read().clone();
+
+    if tag_positions.is_empty() {
+        ui.label("No open tagged positions");
+        return;
+    }
 
-                    // Rows
-                    for entry in app_state.positions.iter() {
-                        let position = entry.value();
+    let mut tags: Vec<_> = tag_positions.into_iter().collect();
+    tags.sort_by(|a, b| a.0.cmp(&b.0));
 
-                        ui.label(&position.tradingsymbol);
-                        ui.label(format!("{}", position.quantity));
-                        ui.label(format!("₹{:.2}", position.average_price));
-                        ui.label(format!("₹{:.2}", position.last_price));
+    egui::Grid::new("pnl_tag_table")
+        .num_columns(3)
+        .spacing([10.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            // Header
+            ui.label(RichText::new("Tag").strong());
+            ui.label(RichText::new("Net Positions").strong());
+            ui.label(RichText::new("Actions").strong());
+            ui.end_row();
+
+            // Rows
+            for (tag, positions) in tags {
+                ui.label(&tag);
+
+                let summary: Vec<String> = positions
+                    .iter()
+                    .map(|position| {
+                        format!("{} {:+}", position.tradingsymbol, position.net_quantity)
+                    })
+                    .collect();
+                ui.label(summary.join(", "));
+
+                if danger_button("Flatten")
+                    .size(row_button_size(ui, 7))
+                    .disabled_reason(app_state.trading_blocked_reason())
+                    .ui(ui)
+                    .clicked()
+                {
+                    app_state.send_command(Command::SquareOffByTag { tag });
+                }
+
+                ui.end_row();
+            }
+        });
+}
 
-                        let pnl_color = if position.pnl >= 0.0 {
-                            Color32::from_rgb(34, 197, 94)
-                        } else {
-                            Color32::from_rgb(239, 68, 68)
-                        };
-                        let prefix = if position.pnl >= 0.0 { "+" } else { "" };
-                        ui.colored_label(pnl_color, format!("{}₹{:.2}", prefix, position.pnl));
+/// Render configured strategies with the P&L of their own orders and an on/off switch each
+fn render_strategies(ui: &mut Ui, app_state: &AppState) {
+    ui.label(RichText::new("Strategies").size(18.0).strong());
+    ui.add_space(10.0);
 
-                        ui.end_row();
+    let statuses = app_state.strategies.read().clone();
+    let theme = app_state.theme;
+
+    egui::Grid::new("pnl_strategy_table")
+        .num_columns(6)
+        .spacing([10.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            // Header
+            for header in [
+                "Strategy",
+                "Instruments",
+                "Realized",
+                "Unrealized",
+                "Total",
+                "Enabled",
+            ] {
+                ui.label(RichText::new(header).strong());
+            }
+            ui.end_row();
+
+            // Rows
+            for (index, status) in statuses.iter().enumerate() {
+                let kind = format!("{} (order tag {})", status.kind, status.name);
+                match &status.error {
+                    Some(error) => {
+                        ui.label(
+                            RichText::new(format!("⚠ {}", status.name))
+                                .color(theme.direction_color(false)),
+                        )
+                        .on_hover_text(format!("{}\n{}", kind, error));
                     }
-                });
-        }
-    });
+                    None => {
+                        ui.label(&status.name).on_hover_text(kind);
+                    }
+                }
+                ui.label(status.instruments.join(", "));
+
+                let pnl = app_state.strategy_pnl(&status.name);
+                for value in [pnl.realized, pnl.unrealized, pnl.realized + pnl.unrealized] {
+                    ui.label(
+                        RichText::new(format_inr_signed(value))
+                            .color(theme.direction_color(value >= 0.0)),
+                    );
+                }
+
+                let mut enabled = status.enabled;
+                // A strategy that failed stays off until the app restarts
+                let toggle = ui.add_enabled(
+                    status.error.is_none(),
+                    egui::Checkbox::without_text(&mut enabled),
+                );
+                if toggle.changed() {
+                    app_state.strategies.write()[index].enabled = enabled;
+                    app_state.add_log(
+                        LogLevel::Info,
+                        format!(
+                            "Strategy {} {}",
+                            status.name,
+                            if enabled { "enabled" } else { "disabled" }
+                        ),
+                        Some("strategy".to_string()),
+                    );
+                }
+                ui.end_row();
+            }
+        });
 }
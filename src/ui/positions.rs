@@ -1,7 +1,19 @@
+use crate::app::AppView;
 use crate::data_structures::*;
-use crate::state::{AppState, Command};
-use crate::ui::components::{danger_button, primary_button, success_button};
-use egui::{Color32, RichText, ScrollArea, Ui};
+use crate::state::{
+    parse_with_feedback, snap_price, AppState, Command, OrderType, PendingMarketOrders,
+    PositionColumn, PositionFilters, PositionGroup, ProductType, ProtectionForm, SquareOffDraft,
+    TableKey, TransactionType, ValidatedField,
+};
+use crate::ui::components::{
+    choose_export_path, column_cells, column_headers, column_menu, danger_button, export_controls,
+    flashing_price, primary_button, refresh_countdown, row_button_size, success_button, ColumnSpec,
+    Modal, ORDER_BUTTON_DEBOUNCE,
+};
+use crate::ui::format::{format_inr, format_inr_compact_signed, format_inr_signed};
+use crate::ui::orders::render_validated_input;
+use egui::{Color32, RichText, ScrollArea, Sense, Ui};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 /// Render positions table with real-time P&L updates
 /// Optimized for high-frequency price updates without UI stuttering
@@ -25,6 +37,20 @@ pub fn render_positions(ui: &mut Ui, app_state: &mut AppState) {
                         Some("positions".to_string()),
                     );
                 }
+
+                ui.add_space(6.0);
+                refresh_countdown(ui, app_state.positions_refresh_remaining());
+
+                ui.add_space(10.0);
+
+                if danger_button("Square Off All")
+                    .size(egui::Vec2::new(120.0, 30.0))
+                    .disabled_reason(app_state.trading_blocked_reason())
+                    .ui(ui)
+                    .clicked()
+                {
+                    request_square_off_all(app_state);
+                }
             });
         });
 
@@ -42,11 +68,44 @@ pub fn render_positions(ui: &mut Ui, app_state: &mut AppState) {
             ui.label("Filter:");
             ui.text_edit_singleline(&mut app_state.ui_input.position_filter);
 
+            render_position_filters(ui, app_state);
+
             if ui.button("Clear").clicked() {
                 app_state.ui_input.position_filter.clear();
+                app_state.ui_input.position_filters = PositionFilters::default();
             }
+
+            column_menu(
+                ui,
+                POSITION_COLUMNS,
+                &mut app_state.ui_input.position_columns,
+            );
         });
 
+        if let Some((_, path)) =
+            export_controls(ui, &mut app_state.ui_input.export_dir, &["positions"])
+        {
+            app_state.send_command(Command::ExportPositions { path });
+        }
+
+        if ui.button("💾 Export CSV…").clicked() {
+            if let Some(path) = choose_export_path("positions") {
+                app_state.sync_positions_snapshot();
+                let snapshot = app_state.positions_snapshot.positions();
+                let positions: Vec<Position> = visible_position_groups(app_state)
+                    .into_iter()
+                    .flat_map(|(_, rows)| rows)
+                    .map(|row| snapshot[row].clone())
+                    .collect();
+                let filter = format!(
+                    "{} {}",
+                    app_state.ui_input.position_filter,
+                    app_state.ui_input.position_filters.describe()
+                );
+                app_state.export_positions_view(&positions, &filter, &path);
+            }
+        }
+
         ui.add_space(10.0);
 
         // Positions table
@@ -55,26 +114,65 @@ pub fn render_positions(ui: &mut Ui, app_state: &mut AppState) {
         } else {
             render_positions_table(ui, app_state);
         }
+
+        render_market_order_confirmation(ui.ctx(), app_state);
+        render_protection_dialog(ui.ctx(), app_state);
     });
 }
 
+/// Exchange and product dropdowns plus the hide-flat toggle
+fn render_position_filters(ui: &mut Ui, app_state: &mut AppState) {
+    let exchanges: BTreeSet<String> = app_state
+        .positions_snapshot
+        .positions()
+        .iter()
+        .map(|position| position.exchange.clone())
+        .collect();
+    let filters = &mut app_state.ui_input.position_filters;
+
+    egui::ComboBox::from_id_salt("position_exchange_filter")
+        .selected_text(filters.exchange.as_deref().unwrap_or("All exchanges"))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut filters.exchange, None, "All exchanges");
+            for exchange in exchanges {
+                let label = exchange.clone();
+                ui.selectable_value(&mut filters.exchange, Some(exchange), label);
+            }
+        });
+
+    egui::ComboBox::from_id_salt("position_product_filter")
+        .selected_text(
+            filters
+                .product
+                .map_or("All products", |product| product.as_api_str()),
+        )
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut filters.product, None, "All products");
+            for product in ProductType::ALL {
+                ui.selectable_value(&mut filters.product, Some(product), product.as_api_str());
+            }
+        });
+
+    ui.checkbox(&mut filters.hide_flat, "Hide zero-quantity")
+        .on_hover_text("Closed intraday positions stay listed with quantity 0");
+}
+
 /// Render summary cards with aggregated position data
+/// Totals cover only the positions the filters let through
 fn render_positions_summary_cards(ui: &mut Ui, app_state: &AppState) {
-    let pnl_data = app_state.calculate_total_pnl();
+    let (pnl_data, count) = app_state.visible_positions_pnl();
+    let filtered = !app_state.ui_input.position_filter.is_empty()
+        || app_state.ui_input.position_filters.is_active();
+    let abbreviate = app_state.config.app.abbreviate_crores;
 
     ui.horizontal(|ui| {
         // Total P&L card
         ui.group(|ui| {
             ui.vertical(|ui| {
                 ui.label(RichText::new("Total P&L").strong());
-                let color = if pnl_data.total >= 0.0 {
-                    Color32::from_rgb(34, 197, 94) // Green
-                } else {
-                    Color32::from_rgb(239, 68, 68) // Red
-                };
-                let prefix = if pnl_data.total >= 0.0 { "+" } else { "" };
+                let color = app_state.theme.direction_color(pnl_data.total >= 0.0);
                 ui.label(
-                    RichText::new(format!("{}₹{:.2}", prefix, pnl_data.total))
+                    RichText::new(format_inr_compact_signed(pnl_data.total, abbreviate))
                         .size(20.0)
                         .color(color),
                 );
@@ -87,14 +185,9 @@ fn render_positions_summary_cards(ui: &mut Ui, app_state: &AppState) {
         ui.group(|ui| {
             ui.vertical(|ui| {
                 ui.label(RichText::new("Realized").strong());
-                let color = if pnl_data.realized >= 0.0 {
-                    Color32::from_rgb(34, 197, 94)
-                } else {
-                    Color32::from_rgb(239, 68, 68)
-                };
-                let prefix = if pnl_data.realized >= 0.0 { "+" } else { "" };
+                let color = app_state.theme.direction_color(pnl_data.realized >= 0.0);
                 ui.label(
-                    RichText::new(format!("{}₹{:.2}", prefix, pnl_data.realized))
+                    RichText::new(format_inr_compact_signed(pnl_data.realized, abbreviate))
                         .size(16.0)
                         .color(color),
                 );
@@ -107,14 +200,9 @@ fn render_positions_summary_cards(ui: &mut Ui, app_state: &AppState) {
         ui.group(|ui| {
             ui.vertical(|ui| {
                 ui.label(RichText::new("Unrealized").strong());
-                let color = if pnl_data.unrealized >= 0.0 {
-                    Color32::from_rgb(34, 197, 94)
-                } else {
-                    Color32::from_rgb(239, 68, 68)
-                };
-                let prefix = if pnl_data.unrealized >= 0.0 { "+" } else { "" };
+                let color = app_state.theme.direction_color(pnl_data.unrealized >= 0.0);
                 ui.label(
-                    RichText::new(format!("{}₹{:.2}", prefix, pnl_data.unrealized))
+                    RichText::new(format_inr_compact_signed(pnl_data.unrealized, abbreviate))
                         .size(16.0)
                         .color(color),
                 );
@@ -128,12 +216,25 @@ fn render_positions_summary_cards(ui: &mut Ui, app_state: &AppState) {
             ui.vertical(|ui| {
                 ui.label(RichText::new("Positions").strong());
                 ui.label(
-                    RichText::new(format!("{}", app_state.positions.len()))
+                    RichText::new(format!("{}", count))
                         .size(16.0)
                         .color(Color32::from_rgb(59, 130, 246)),
                 );
             });
         });
+
+        if filtered {
+            ui.add_space(10.0);
+            ui.label(
+                RichText::new(format!(
+                    "Filtered: {} of {} positions",
+                    count,
+                    app_state.positions.len()
+                ))
+                .italics()
+                .color(Color32::GRAY),
+            );
+        }
     });
 }
 
@@ -181,127 +282,471 @@ fn render_empty_positions(ui: &mut Ui) {
     });
 }
 
+/// Positions as the table shows them: filtered and grouped by exchange, as rows of the
+/// positions snapshot, whose sort each group keeps
+fn visible_position_groups(app_state: &AppState) -> Vec<PositionGroup> {
+    let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (row, position) in app_state.positions_snapshot.positions().iter().enumerate() {
+        // Apply filters
+        if !app_state.position_visible(position) {
+            continue;
+        }
+
+        groups
+            .entry(position.exchange.clone())
+            .or_default()
+            .push(row);
+    }
+
+    groups.into_iter().collect()
+}
+
+/// One line of the positions table below the header
+#[derive(Debug, PartialEq)]
+enum PositionTableRow {
+    Group(usize),
+    Position(usize, usize),
+    Detail(usize, usize, DetailLine),
+}
+
+/// Lines of an expanded position's detail panel, each one table row tall
+/// so the table keeps the uniform row height its virtual scrolling relies on
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DetailLine {
+    Quantities,
+    Values,
+    Links,
+}
+
+impl DetailLine {
+    const ALL: [DetailLine; 3] = [
+        DetailLine::Quantities,
+        DetailLine::Values,
+        DetailLine::Links,
+    ];
+}
+
 /// Render the main positions table with real-time updates
+/// Positions are split into collapsible sections per exchange with a P&L subtotal
+/// Every line of the table in display order: each group's header, then its positions
+/// unless the group is collapsed, each followed by its detail lines when expanded
+fn position_table_rows(
+    groups: &[PositionGroup],
+    snapshot: &[Position],
+    collapsed: &HashSet<String>,
+    expanded: &HashSet<(u32, String)>,
+) -> Vec<PositionTableRow> {
+    let mut rows = Vec::new();
+    for (group, (exchange, positions)) in groups.iter().enumerate() {
+        rows.push(PositionTableRow::Group(group));
+        if collapsed.contains(exchange) {
+            continue;
+        }
+        for (index, &row) in positions.iter().enumerate() {
+            rows.push(PositionTableRow::Position(group, index));
+            // Keyed by instrument and product, so rows stay open as live updates re-sort the table
+            if expanded.contains(&snapshot[row].key()) {
+                rows.extend(
+                    DetailLine::ALL.map(|line| PositionTableRow::Detail(group, index, line)),
+                );
+            }
+        }
+    }
+    rows
+}
+
+/// Only rows inside the scroll viewport are laid out, so large books stay smooth
 fn render_positions_table(ui: &mut Ui, app_state: &mut AppState) {
-    let filter = app_state.ui_input.position_filter.to_lowercase();
-
-    ScrollArea::vertical().max_height(600.0).show(ui, |ui| {
-        egui::Grid::new("positions_table")
-            .num_columns(10)
-            .spacing([8.0, 4.0])
-            .striped(true)
-            .show(ui, |ui| {
-                // Table header
-                ui.label(RichText::new("Symbol").strong());
-                ui.label(RichText::new("Exchange").strong());
-                ui.label(RichText::new("Product").strong());
-                ui.label(RichText::new("Qty").strong());
-                ui.label(RichText::new("Avg Price").strong());
-                ui.label(RichText::new("LTP").strong());
-                ui.label(RichText::new("P&L").strong());
-                ui.label(RichText::new("Day P&L").strong());
-                ui.label(RichText::new("Change %").strong());
-                ui.label(RichText::new("Actions").strong());
-                ui.end_row();
-
-                // Table rows
-                let positions: Vec<_> = app_state
-                    .positions
-                    .iter()
-                    .map(|entry| entry.value().clone())
-                    .collect();
-                for position in positions {
-                    // Apply filter
-                    if !filter.is_empty()
-                        && !position.tradingsymbol.to_lowercase().contains(&filter)
-                    {
-                        continue;
+    // A sort picked last frame reorders the snapshot before rows are taken from it
+    app_state.sync_positions_snapshot();
+    let snapshot = app_state.positions_snapshot.positions();
+    let key = TableKey {
+        revision: app_state.positions_gen.get(),
+        filter: format!(
+            "{:?}:{}",
+            app_state.ui_input.position_filters, app_state.ui_input.position_filter
+        ),
+        sort: app_state.ui_input.position_sort,
+        frozen: false,
+    };
+    if !app_state.ui_input.position_rows.is_current(&key) {
+        let groups = visible_position_groups(app_state);
+        app_state.ui_input.position_rows.store(key, groups);
+    }
+    let groups = app_state.ui_input.position_rows.rows();
+    let subtotals = app_state.positions_by_exchange();
+    let columns = app_state.ui_input.position_columns.clone();
+    let totals = PositionTotals {
+        gross_value: groups
+            .iter()
+            .flat_map(|(_, rows)| rows)
+            .map(|&row| snapshot[row].value().abs())
+            .sum(),
+    };
+
+    let rows = position_table_rows(
+        &groups,
+        &snapshot,
+        &app_state.ui_input.collapsed_position_groups,
+        &app_state.ui_input.expanded_positions,
+    );
+
+    let row_height = row_button_size(ui, 1).y;
+    let row_spacing = ui.spacing().item_spacing.y;
+
+    // The header takes the first slot of whatever range is visible, so it stays on top
+    ScrollArea::vertical().max_height(600.0).show_rows(
+        ui,
+        row_height,
+        rows.len() + 1,
+        |ui, visible| {
+            let table_width = ui.available_width();
+            egui::Grid::new("positions_table")
+                .num_columns(columns.visible().count() + 2)
+                .spacing([8.0, row_spacing])
+                .min_row_height(row_height)
+                .striped(true)
+                .show(ui, |ui| {
+                    // Table header, click to sort
+                    ui.label("");
+                    column_headers(
+                        ui,
+                        POSITION_COLUMNS,
+                        &columns,
+                        &mut app_state.ui_input.position_sort,
+                    );
+                    ui.label(RichText::new("Actions").strong());
+                    ui.end_row();
+
+                    let end = visible.end.saturating_sub(1).min(rows.len());
+                    for row in &rows[visible.start.min(end)..end] {
+                        match *row {
+                            PositionTableRow::Group(group) => {
+                                let (exchange, positions) = &groups[group];
+                                let subtotal = subtotals.get(exchange).cloned().unwrap_or_default();
+                                render_group_row(
+                                    ui,
+                                    app_state,
+                                    exchange,
+                                    positions.len(),
+                                    subtotal.total,
+                                );
+                            }
+                            PositionTableRow::Position(group, index) => {
+                                let position = &snapshot[groups[group].1[index]];
+                                render_expand_toggle(ui, position, app_state);
+                                column_cells(
+                                    ui,
+                                    POSITION_COLUMNS,
+                                    &columns,
+                                    position,
+                                    app_state,
+                                    &totals,
+                                );
+                                render_position_actions(ui, position, app_state);
+                            }
+                            PositionTableRow::Detail(group, index, line) => {
+                                let position = &snapshot[groups[group].1[index]];
+                                spanning_row(ui, table_width, row_height, |ui| {
+                                    render_detail_line(ui, position, line, app_state);
+                                });
+                            }
+                        }
+                        ui.end_row();
                     }
+                });
+        },
+    );
+}
 
-                    render_position_row(ui, &position, app_state);
-                }
-            });
-    });
+/// Exchange heading inside the grid; the title is painted across the row's cells
+/// rather than laid out, so it doesn't widen the symbol column
+fn render_group_row(ui: &mut Ui, app_state: &mut AppState, exchange: &str, count: usize, pnl: f64) {
+    let collapsed = app_state
+        .ui_input
+        .collapsed_position_groups
+        .contains(exchange);
+    let title = format!(
+        "{} {} ({}) · P&L {}",
+        if collapsed { "▶" } else { "▼" },
+        exchange,
+        count,
+        format_inr_signed(pnl)
+    );
+
+    let height = ui.spacing().interact_size.y;
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(40.0, height), Sense::click());
+    ui.painter().text(
+        rect.left_center(),
+        egui::Align2::LEFT_CENTER,
+        title,
+        egui::TextStyle::Button.resolve(ui.style()),
+        ui.visuals().strong_text_color(),
+    );
+
+    if response
+        .on_hover_cursor(egui::CursorIcon::PointingHand)
+        .clicked()
+    {
+        let groups = &mut app_state.ui_input.collapsed_position_groups;
+        if collapsed {
+            groups.remove(exchange);
+        } else {
+            groups.insert(exchange.to_string());
+        }
+    }
 }
 
-/// Render individual position row with real-time data
-fn render_position_row(ui: &mut Ui, position: &Position, app_state: &mut AppState) {
-    // Symbol with color coding based on P&L
-    let symbol_color = if position.pnl >= 0.0 {
-        Color32::from_rgb(34, 197, 94)
+/// ▶/▼ in the leading column, opening the position's detail panel
+fn render_expand_toggle(ui: &mut Ui, position: &Position, app_state: &mut AppState) {
+    let expanded = &mut app_state.ui_input.expanded_positions;
+    let open = expanded.contains(&position.key());
+    let (icon, hint) = if open {
+        ("▼", "Hide details")
     } else {
-        Color32::from_rgb(239, 68, 68)
+        ("▶", "Show details")
+    };
+
+    if ui.small_button(icon).on_hover_text(hint).clicked() {
+        if open {
+            expanded.remove(&position.key());
+        } else {
+            expanded.insert(position.key());
+
+            // Today's buy/sell values come from the tradebook
+            if !app_state.ui_input.trades_requested {
+                app_state.ui_input.trades_requested = true;
+                app_state.send_command(Command::FetchTrades);
+            }
+        }
+    }
+}
+
+/// Lay out a full-width line in the leading grid cell without widening that column
+fn spanning_row(ui: &mut Ui, width: f32, height: f32, add_contents: impl FnOnce(&mut Ui)) {
+    let rect = egui::Rect::from_min_size(ui.cursor().min, egui::vec2(width, height));
+    let mut child = ui.new_child(
+        egui::UiBuilder::new()
+            .max_rect(rect)
+            .layout(egui::Layout::left_to_right(egui::Align::Center)),
+    );
+    add_contents(&mut child);
+    ui.allocate_exact_size(egui::vec2(0.0, height), Sense::hover());
+}
+
+/// One line of a position's detail panel
+fn render_detail_line(
+    ui: &mut Ui,
+    position: &Position,
+    line: DetailLine,
+    app_state: &mut AppState,
+) {
+    ui.add_space(24.0);
+    let weak = ui.visuals().weak_text_color();
+    let field = |ui: &mut Ui, name: &str, value: String| {
+        ui.label(RichText::new(name).color(weak));
+        ui.label(value);
+        ui.add_space(12.0);
     };
-    ui.colored_label(symbol_color, &position.tradingsymbol);
 
-    // Exchange
-    ui.label(&position.exchange);
+    match line {
+        DetailLine::Quantities => {
+            field(ui, "Overnight qty", position.overnight_quantity.to_string());
+            field(ui, "Day qty", position.day_quantity.to_string());
+            field(ui, "Prev close", format_inr(position.close_price));
+            field(ui, "Multiplier", position.multiplier.to_string());
+        }
+        DetailLine::Values => {
+            field(ui, "Realized", format_inr_signed(position.realized_pnl));
+            field(ui, "Unrealized", format_inr_signed(position.unrealized_pnl));
+
+            let trades = app_state.trades.read();
+            let summary = summarize_trades(trades.iter().filter(|trade| {
+                trade.instrument_token == position.instrument_token
+                    && trade.product == position.product
+            }))
+            .pop()
+            .unwrap_or_default();
+            field(
+                ui,
+                "Bought today",
+                format!(
+                    "{} ({})",
+                    format_inr(summary.bought_value),
+                    summary.bought_quantity
+                ),
+            );
+            field(
+                ui,
+                "Sold today",
+                format!(
+                    "{} ({})",
+                    format_inr(summary.sold_value),
+                    summary.sold_quantity
+                ),
+            );
+        }
+        DetailLine::Links => {
+            if ui.small_button("📈 Chart").clicked() {
+                app_state.select_instrument(position.instrument_token);
+                app_state.ui_input.requested_view = Some(AppView::Charts);
+            }
+            if ui.small_button("📊 Depth").clicked() {
+                app_state.select_instrument(position.instrument_token);
+            }
+            if ui.small_button("📋 Orders").clicked() {
+                app_state.ui_input.order_filter = position.tradingsymbol.clone();
+                app_state.ui_input.requested_view = Some(AppView::Orders);
+            }
+        }
+    }
+}
+
+/// Table-wide figures some position cells are relative to
+struct PositionTotals {
+    gross_value: f64,
+}
+
+/// Columns of the positions table, in default order
+const POSITION_COLUMNS: &[ColumnSpec<PositionColumn, Position, PositionTotals>] = &[
+    ColumnSpec {
+        id: PositionColumn::Symbol,
+        header: "Symbol",
+        width: 110.0,
+        render: symbol_cell,
+    },
+    ColumnSpec {
+        id: PositionColumn::Exchange,
+        header: "Exchange",
+        width: 60.0,
+        render: |ui, position, _, _| {
+            ui.label(&position.exchange);
+        },
+    },
+    ColumnSpec {
+        id: PositionColumn::Product,
+        header: "Product",
+        width: 60.0,
+        render: |ui, position, _, _| {
+            ui.label(&position.product);
+        },
+    },
+    ColumnSpec {
+        id: PositionColumn::Quantity,
+        header: "Qty",
+        width: 50.0,
+        render: quantity_cell,
+    },
+    ColumnSpec {
+        id: PositionColumn::AveragePrice,
+        header: "Avg Price",
+        width: 80.0,
+        render: |ui, position, _, _| {
+            ui.label(format_inr(position.average_price));
+        },
+    },
+    ColumnSpec {
+        id: PositionColumn::LastPrice,
+        header: "LTP",
+        width: 80.0,
+        render: last_price_cell,
+    },
+    ColumnSpec {
+        id: PositionColumn::Value,
+        header: "Value",
+        width: 90.0,
+        render: |ui, position, _, _| {
+            ui.label(format_inr(position.value()));
+        },
+    },
+    ColumnSpec {
+        id: PositionColumn::PortfolioPct,
+        header: "% Portfolio",
+        width: 70.0,
+        render: |ui, position, _, totals| {
+            let share = if totals.gross_value > 0.0 {
+                position.value().abs() / totals.gross_value * 100.0
+            } else {
+                0.0
+            };
+            ui.label(format!("{:.1}%", share));
+        },
+    },
+    ColumnSpec {
+        id: PositionColumn::Pnl,
+        header: "P&L",
+        width: 80.0,
+        render: |ui, position, app_state, _| {
+            let color = app_state.theme.direction_color(position.pnl >= 0.0);
+            ui.colored_label(color, format_inr_signed(position.pnl));
+        },
+    },
+    ColumnSpec {
+        id: PositionColumn::DayPnl,
+        header: "Day P&L",
+        width: 80.0,
+        render: |ui, position, app_state, _| {
+            let color = app_state
+                .theme
+                .direction_color(position.unrealized_pnl >= 0.0);
+            ui.colored_label(color, format_inr_signed(position.unrealized_pnl));
+        },
+    },
+    ColumnSpec {
+        id: PositionColumn::ChangePct,
+        header: "Change %",
+        width: 60.0,
+        render: |ui, position, app_state, _| {
+            let change_pct = position.change_pct();
+            let color = app_state.theme.direction_color(change_pct >= 0.0);
+            let prefix = if change_pct >= 0.0 { "+" } else { "" };
+            ui.colored_label(color, format!("{}{:.2}%", prefix, change_pct));
+        },
+    },
+];
 
-    // Product
-    ui.label(&position.product);
+/// Symbol with color coding based on P&L; clicking streams it in full mode
+fn symbol_cell(ui: &mut Ui, position: &Position, app_state: &mut AppState, _: &PositionTotals) {
+    let symbol_color = app_state.theme.direction_color(position.pnl >= 0.0);
+    let selected = app_state.ui_input.selected_instrument_token == Some(position.instrument_token);
+    if ui
+        .selectable_label(
+            selected,
+            RichText::new(&position.tradingsymbol).color(symbol_color),
+        )
+        .clicked()
+    {
+        app_state.select_instrument(position.instrument_token);
+    }
+}
 
-    // Quantity with directional indicator
+/// Quantity with directional indicator
+fn quantity_cell(ui: &mut Ui, position: &Position, app_state: &mut AppState, _: &PositionTotals) {
     let qty_text = if position.quantity > 0 {
         format!("+{}", position.quantity)
     } else {
         position.quantity.to_string()
     };
-    let qty_color = if position.quantity > 0 {
-        Color32::from_rgb(34, 197, 94)
-    } else {
-        Color32::from_rgb(239, 68, 68)
-    };
+    let qty_color = app_state.theme.direction_color(position.quantity > 0);
     ui.colored_label(qty_color, qty_text);
+}
 
-    // Average price
-    ui.label(format!("₹{:.2}", position.average_price));
-
-    // Last traded price (LTP) with real-time updates
-    ui.label(
-        RichText::new(format!("₹{:.2}", position.last_price))
+/// Last traded price (LTP) with real-time updates
+fn last_price_cell(ui: &mut Ui, position: &Position, app_state: &mut AppState, _: &PositionTotals) {
+    flashing_price(
+        ui,
+        &mut app_state.ui_input.price_flashes,
+        &app_state.theme,
+        position.instrument_token,
+        position.last_price,
+        RichText::new(format_inr(position.last_price))
             .color(Color32::from_rgb(59, 130, 246))
             .strong(),
     );
+}
 
-    // P&L with color coding
-    let pnl_color = if position.pnl >= 0.0 {
-        Color32::from_rgb(34, 197, 94)
-    } else {
-        Color32::from_rgb(239, 68, 68)
-    };
-    let pnl_prefix = if position.pnl >= 0.0 { "+" } else { "" };
-    ui.colored_label(pnl_color, format!("{}₹{:.2}", pnl_prefix, position.pnl));
-
-    // Day P&L (unrealized)
-    let day_pnl_color = if position.unrealized_pnl >= 0.0 {
-        Color32::from_rgb(34, 197, 94)
-    } else {
-        Color32::from_rgb(239, 68, 68)
-    };
-    let day_pnl_prefix = if position.unrealized_pnl >= 0.0 {
-        "+"
-    } else {
-        ""
-    };
-    ui.colored_label(
-        day_pnl_color,
-        format!("{}₹{:.2}", day_pnl_prefix, position.unrealized_pnl),
-    );
-
-    // Change percentage
-    let change_pct = if position.average_price > 0.0 {
-        ((position.last_price - position.average_price) / position.average_price) * 100.0
-    } else {
-        0.0
-    };
-    let change_color = if change_pct >= 0.0 {
-        Color32::from_rgb(34, 197, 94)
-    } else {
-        Color32::from_rgb(239, 68, 68)
-    };
-    let change_prefix = if change_pct >= 0.0 { "+" } else { "" };
-    ui.colored_label(change_color, format!("{}{:.2}%", change_prefix, change_pct));
-
+/// Subscribe and one-click exit buttons at the end of a position row
+fn render_position_actions(ui: &mut Ui, position: &Position, app_state: &mut AppState) {
     // Action buttons
     ui.horizontal(|ui| {
         // Subscribe to ticks button
@@ -312,70 +757,599 @@ fn render_position_row(ui: &mut Ui, position: &Position, app_state: &mut AppStat
 
             app_state.add_log(
                 LogLevel::Info,
-                format!("Subscribed to ticks for {}", position.tradingsymbol),
+                format!(
+                    "Subscribed to ticks for {}",
+                    app_state.instrument_label(position.instrument_token)
+                ),
                 Some("positions".to_string()),
             );
         }
 
-        // Quick sell/buy buttons for position management
-        if position.quantity > 0 {
-            // Show sell button for long positions
-            if danger_button("Sell")
-                .size(egui::Vec2::new(50.0, 20.0))
-                .ui(ui)
+        if position.quantity != 0
+            && ui
+                .small_button("🛡")
+                .on_hover_text("Protect: place stop-loss and target orders")
                 .clicked()
-            {
-                let order_request = OrderRequest {
-                    tradingsymbol: position.tradingsymbol.clone(),
-                    exchange: position.exchange.clone(),
-                    transaction_type: "SELL".to_string(),
-                    order_type: "MARKET".to_string(),
-                    quantity: position.quantity.abs(),
-                    price: None,
-                    product: position.product.clone(),
-                    validity: "DAY".to_string(),
-                    disclosed_quantity: None,
-                    trigger_price: None,
-                    squareoff: None,
-                    stoploss: None,
-                    trailing_stoploss: None,
-                    tag: Some("quick_sell".to_string()),
-                };
-
-                app_state.send_command(Command::PlaceOrder {
-                    details: order_request,
-                });
-            }
+        {
+            let instrument = app_state
+                .instruments
+                .get(&position.instrument_token)
+                .map(|entry| entry.value().clone());
+            app_state.ui_input.protection_form =
+                Some(ProtectionForm::from_position(position, instrument));
+        }
+
+        // Sell/Buy opens a popover to choose how much of the position to close
+        let exiting = app_state.orders_in_flight.read().any(|order| {
+            order.tradingsymbol == position.tradingsymbol
+                && order.product == position.product
+                && matches!(order.tag.as_deref(), Some(QUICK_SELL_TAG | QUICK_BUY_TAG))
+        });
+        let response = if position.quantity > 0 {
+            danger_button("Sell")
+                .size(row_button_size(ui, 4))
+                .loading(exiting)
+                .disabled_reason(app_state.trading_blocked_reason())
+                .ui(ui)
         } else if position.quantity < 0 {
-            // Show buy button for short positions
-            if success_button("Buy")
-                .size(egui::Vec2::new(50.0, 20.0))
+            success_button("Buy")
+                .size(row_button_size(ui, 4))
+                .loading(exiting)
+                .disabled_reason(app_state.trading_blocked_reason())
                 .ui(ui)
-                .clicked()
-            {
-                let order_request = OrderRequest {
-                    tradingsymbol: position.tradingsymbol.clone(),
-                    exchange: position.exchange.clone(),
-                    transaction_type: "BUY".to_string(),
-                    order_type: "MARKET".to_string(),
-                    quantity: position.quantity.abs(),
-                    price: None,
-                    product: position.product.clone(),
-                    validity: "DAY".to_string(),
-                    disclosed_quantity: None,
-                    trigger_price: None,
-                    squareoff: None,
-                    stoploss: None,
-                    trailing_stoploss: None,
-                    tag: Some("quick_buy".to_string()),
-                };
-
-                app_state.send_command(Command::PlaceOrder {
-                    details: order_request,
-                });
+        } else {
+            return;
+        };
+
+        if response.clicked() {
+            app_state.ui_input.square_off_draft = Some(SquareOffDraft {
+                instrument_token: position.instrument_token,
+                product: position.product.clone(),
+                quantity: position.quantity.abs().to_string(),
+                limit_at_ltp: false,
+            });
+        }
+
+        egui::Popup::from_toggle_button_response(&response)
+            .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+            .show(|ui| render_square_off_popover(ui, position, app_state));
+    });
+}
+
+/// Fraction presets, quantity field and order type for closing part of a position
+fn render_square_off_popover(ui: &mut Ui, position: &Position, app_state: &mut AppState) {
+    let Some(mut draft) = app_state.ui_input.square_off_draft.take().filter(|draft| {
+        draft.instrument_token == position.instrument_token && draft.product == position.product
+    }) else {
+        return;
+    };
+
+    let held = position.quantity.abs();
+    let lot_size = app_state
+        .instruments
+        .get(&position.instrument_token)
+        .filter(|instrument| instrument.is_derivative())
+        .map(|instrument| instrument.lot_size.max(1) as i32)
+        .unwrap_or(1);
+
+    ui.set_min_width(220.0);
+    ui.label(RichText::new(format!("Close {}", position.tradingsymbol)).strong());
+    ui.label(
+        RichText::new(format!("Held {} · lot size {}", held, lot_size))
+            .small()
+            .weak(),
+    );
+
+    ui.horizontal(|ui| {
+        for percent in [25, 50, 75, 100] {
+            // Round down to whole lots, but never below one lot
+            let lots = (held / lot_size * percent / 100).max(1);
+            if ui.small_button(format!("{}%", percent)).clicked() {
+                draft.quantity = (lots * lot_size).min(held).to_string();
             }
         }
     });
 
-    ui.end_row();
+    ui.horizontal(|ui| {
+        ui.label("Quantity");
+        ui.add(egui::TextEdit::singleline(&mut draft.quantity).desired_width(80.0));
+    });
+
+    ui.horizontal(|ui| {
+        ui.radio_value(&mut draft.limit_at_ltp, false, "Market");
+        ui.radio_value(&mut draft.limit_at_ltp, true, "Limit @ LTP");
+    });
+
+    let quantity = match parse_with_feedback::<i32>(&draft.quantity) {
+        Ok(quantity) if quantity <= 0 => Err("Quantity must be positive".to_string()),
+        Ok(quantity) if quantity > held => Err(format!("Only {} held", held)),
+        Ok(quantity) if quantity % lot_size != 0 => {
+            Err(format!("Must be a multiple of lot size {}", lot_size))
+        }
+        Ok(quantity) => Ok(quantity),
+        Err(e) => Err(e.to_string()),
+    };
+
+    let label = if position.quantity > 0 { "Sell" } else { "Buy" };
+    match quantity {
+        Ok(quantity) => {
+            let send = if position.quantity > 0 {
+                danger_button(label)
+            } else {
+                success_button(label)
+            };
+            let send = send
+                .debounce(ORDER_BUTTON_DEBOUNCE)
+                .disabled_reason(app_state.trading_blocked_reason())
+                .ui(ui);
+            if send.clicked() {
+                send_square_off(app_state, position, quantity, draft.limit_at_ltp);
+                ui.close();
+                return;
+            }
+        }
+        Err(message) => {
+            ui.add_enabled(false, egui::Button::new(label));
+            ui.colored_label(app_state.theme.loss, message);
+        }
+    }
+
+    app_state.ui_input.square_off_draft = Some(draft);
+}
+
+/// Tags on exits placed from a position row's Sell/Buy popover
+const QUICK_SELL_TAG: &str = "quick_sell";
+const QUICK_BUY_TAG: &str = "quick_buy";
+
+/// Place the exit chosen in the popover; market exits go through confirmation when enabled
+fn send_square_off(
+    app_state: &mut AppState,
+    position: &Position,
+    quantity: i32,
+    limit_at_ltp: bool,
+) {
+    let tag = if position.quantity > 0 {
+        QUICK_SELL_TAG
+    } else {
+        QUICK_BUY_TAG
+    };
+    let mut order = exit_order(position, quantity, tag);
+    let ltp = latest_price(app_state, position);
+
+    if limit_at_ltp {
+        let price = app_state
+            .instruments
+            .get(&position.instrument_token)
+            .map(|instrument| instrument.round_to_tick(ltp))
+            .unwrap_or(ltp);
+        order.order_type = OrderType::Limit.as_api_str().to_string();
+        order.price = Some(price);
+        app_state.send_command(Command::PlaceOrder { details: order });
+        return;
+    }
+
+    let message = format!(
+        "{} {} {} at market (≈ {})?",
+        order.transaction_type,
+        order.quantity,
+        order.tradingsymbol,
+        format_inr(ltp * quantity as f64)
+    );
+
+    if app_state.config.app.confirm_market_orders {
+        app_state.ui_input.pending_market_orders = Some(PendingMarketOrders {
+            message,
+            orders: vec![order],
+        });
+    } else {
+        app_state.send_command(Command::PlaceOrder { details: order });
+    }
+}
+
+/// Tag shared by stop-loss and target orders placed from the Protect dialog
+const PROTECTION_TAG: &str = "protection";
+
+/// Modal for placing a position's stop-loss and target orders
+fn render_protection_dialog(ctx: &egui::Context, app_state: &mut AppState) {
+    let Some(mut form) = app_state.ui_input.protection_form.take() else {
+        return;
+    };
+
+    let position = form.position.clone();
+    let long = position.quantity > 0;
+    let existing = app_state
+        .orders
+        .iter()
+        .filter(|entry| {
+            let order = entry.value();
+            order.is_cancellable()
+                && order.tradingsymbol == position.tradingsymbol
+                && order.tag.as_deref() == Some(PROTECTION_TAG)
+        })
+        .count();
+
+    let mut keep_open = true;
+
+    let response = Modal::new("protect_position_modal", "Protect Position")
+        .size(egui::Vec2::new(380.0, 300.0))
+        .show(ctx, |ui| {
+            ui.label(
+                RichText::new(format!(
+                    "{} {} · avg {}",
+                    if long { "Long" } else { "Short" },
+                    position.tradingsymbol,
+                    format_inr(position.average_price)
+                ))
+                .strong(),
+            );
+            if existing > 0 {
+                ui.colored_label(
+                    Color32::from_rgb(234, 179, 8),
+                    format!(
+                        "⚠ {} protection order(s) already open for {}",
+                        existing, position.tradingsymbol
+                    ),
+                );
+            }
+            ui.add_space(10.0);
+
+            let instrument = form.instrument.as_ref();
+            let mut changed = render_validated_input(ui, "Quantity:", &mut form.quantity_input);
+            changed |= ui
+                .checkbox(&mut form.place_stop, "Stop-loss (SL-M)")
+                .changed();
+            if form.place_stop {
+                changed |= render_tick_input(ui, "Trigger:", &mut form.stop_input, instrument);
+            }
+            changed |= ui
+                .checkbox(&mut form.place_target, "Target (LIMIT)")
+                .changed();
+            if form.place_target {
+                changed |= render_tick_input(ui, "Price:", &mut form.target_input, instrument);
+            }
+            if changed {
+                form.validate();
+            }
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                if primary_button("Place")
+                    .size(egui::Vec2::new(80.0, 30.0))
+                    .enabled(form.is_ready())
+                    .disabled_reason(app_state.trading_blocked_reason())
+                    .ui(ui)
+                    .clicked()
+                {
+                    for order in protection_orders(&form) {
+                        app_state.send_command(Command::PlaceOrder { details: order });
+                    }
+                    keep_open = false;
+                }
+
+                ui.add_space(10.0);
+
+                if ui.button("Close").clicked() {
+                    keep_open = false;
+                }
+            });
+        });
+
+    if keep_open && !response.closed {
+        app_state.ui_input.protection_form = Some(form);
+    }
+}
+
+/// Price field that snaps to the instrument's tick size when editing ends
+fn render_tick_input(
+    ui: &mut Ui,
+    label: &str,
+    field: &mut ValidatedField<f64>,
+    instrument: Option<&Instrument>,
+) -> bool {
+    let response = ui
+        .horizontal(|ui| {
+            ui.label(label);
+            ui.text_edit_singleline(&mut field.text)
+        })
+        .inner;
+    let mut changed = response.changed();
+
+    if response.lost_focus() {
+        if let Ok(price) = parse_with_feedback::<f64>(&field.text) {
+            let snapped = snap_price(instrument, price);
+            if snapped != price {
+                field.set(snapped);
+                changed = true;
+            }
+        }
+    }
+
+    if let Some(error) = field.error() {
+        ui.colored_label(Color32::from_rgb(239, 68, 68), error);
+    }
+
+    changed
+}
+
+/// SL-M and LIMIT exits for the form's checked legs, both tagged for protection
+fn protection_orders(form: &ProtectionForm) -> Vec<OrderRequest> {
+    let Some(quantity) = form.quantity_input.value() else {
+        return Vec::new();
+    };
+    let mut orders = Vec::new();
+
+    if let (true, Some(trigger)) = (form.place_stop, form.stop_input.value()) {
+        let mut order = exit_order(&form.position, quantity, PROTECTION_TAG);
+        order.order_type = OrderType::StopLossMarket.as_api_str().to_string();
+        order.trigger_price = Some(form.snap(trigger));
+        orders.push(order);
+    }
+
+    if let (true, Some(price)) = (form.place_target, form.target_input.value()) {
+        let mut order = exit_order(&form.position, quantity, PROTECTION_TAG);
+        order.order_type = OrderType::Limit.as_api_str().to_string();
+        order.price = Some(form.snap(price));
+        orders.push(order);
+    }
+
+    orders
+}
+
+/// Market order closing `quantity` of a position
+pub fn exit_order(position: &Position, quantity: i32, tag: &str) -> OrderRequest {
+    let transaction_type = if position.quantity > 0 {
+        TransactionType::Sell
+    } else {
+        TransactionType::Buy
+    };
+
+    OrderRequest {
+        tradingsymbol: position.tradingsymbol.clone(),
+        exchange: position.exchange.clone(),
+        transaction_type: transaction_type.as_api_str().to_string(),
+        order_type: OrderType::Market.as_api_str().to_string(),
+        quantity,
+        price: None,
+        product: position.product.clone(),
+        validity: "DAY".to_string(),
+        validity_ttl: None,
+        disclosed_quantity: None,
+        trigger_price: None,
+        squareoff: None,
+        stoploss: None,
+        trailing_stoploss: None,
+        tag: Some(tag.to_string()),
+        variety: "regular".to_string(),
+        bypass_margin_check: false,
+        request_id: None,
+    }
+}
+
+/// Latest tick price, falling back to the position's last price
+fn latest_price(app_state: &AppState, position: &Position) -> f64 {
+    app_state
+        .tick_data
+        .get(&position.instrument_token)
+        .map(|tick| tick.last_price)
+        .unwrap_or(position.last_price)
+}
+
+/// Value of closing a whole position at the latest price
+fn estimated_notional(app_state: &AppState, position: &Position) -> f64 {
+    latest_price(app_state, position) * position.quantity.abs() as f64
+}
+
+/// Hold every open position's exit for confirmation; bulk square-off always asks
+fn request_square_off_all(app_state: &mut AppState) {
+    let mut positions: Vec<Position> = app_state
+        .positions
+        .iter()
+        .filter(|entry| entry.value().quantity != 0)
+        .map(|entry| entry.value().clone())
+        .collect();
+    positions.sort_by(|a, b| a.tradingsymbol.cmp(&b.tradingsymbol));
+
+    if positions.is_empty() {
+        app_state.add_log(
+            LogLevel::Info,
+            "No open positions to square off".to_string(),
+            Some("positions".to_string()),
+        );
+        return;
+    }
+
+    let notional: f64 = positions
+        .iter()
+        .map(|position| estimated_notional(app_state, position))
+        .sum();
+    let symbols: Vec<String> = positions
+        .iter()
+        .map(|position| format!("{} {:+}", position.tradingsymbol, position.quantity))
+        .collect();
+
+    app_state.ui_input.pending_market_orders = Some(PendingMarketOrders {
+        message: format!(
+            "Square off {} positions at market (≈ {})?\n{}",
+            positions.len(),
+            format_inr(notional),
+            symbols.join(", ")
+        ),
+        orders: positions
+            .iter()
+            .map(|position| exit_order(position, position.quantity.abs(), "square_off_all"))
+            .collect(),
+    });
+}
+
+/// Show the confirmation modal for held-back market orders
+fn render_market_order_confirmation(ctx: &egui::Context, app_state: &mut AppState) {
+    let Some(pending) = app_state.ui_input.pending_market_orders.take() else {
+        return;
+    };
+
+    let decision = Modal::confirmation(
+        ctx,
+        "confirm_market_orders",
+        "Confirm Market Order",
+        pending.message.clone(),
+        || true,
+        || false,
+    );
+
+    match decision {
+        Some(true) => {
+            for order in pending.orders {
+                app_state.send_command(Command::PlaceOrder { details: order });
+            }
+        }
+        Some(false) => {}
+        None => app_state.ui_input.pending_market_orders = Some(pending),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::Config;
+
+    fn held(instrument_token: u32, tradingsymbol: &str, exchange: &str) -> Position {
+        Position {
+            instrument_token,
+            tradingsymbol: tradingsymbol.to_string(),
+            exchange: exchange.to_string(),
+            product: "CNC".to_string(),
+            quantity: 10,
+            average_price: 100.0,
+            last_price: 100.0,
+            close_price: 100.0,
+            pnl: 0.0,
+            unrealized_pnl: 0.0,
+            realized_pnl: 0.0,
+            multiplier: 1.0,
+            overnight_quantity: 0,
+            day_quantity: 10,
+        }
+    }
+
+    /// State holding the positions, with the snapshot synced to them
+    fn state_with(positions: Vec<Position>) -> AppState {
+        let (mut state, _receivers) = AppState::new(Config::for_tests());
+        for position in positions {
+            state.positions.insert(position.key(), position);
+        }
+        state.positions_gen.bump();
+        state.sync_positions_snapshot();
+        state
+    }
+
+    fn group_symbols(state: &AppState) -> Vec<(String, Vec<String>)> {
+        let snapshot = state.positions_snapshot.positions();
+        visible_position_groups(state)
+            .into_iter()
+            .map(|(exchange, rows)| {
+                let symbols = rows
+                    .iter()
+                    .map(|&row| snapshot[row].tradingsymbol.clone())
+                    .collect();
+                (exchange, symbols)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn positions_group_by_exchange_in_snapshot_order() {
+        let mut state = state_with(vec![
+            held(1, "INFY", "NSE"),
+            held(2, "NIFTY24JANFUT", "NFO"),
+            held(3, "TCS", "NSE"),
+        ]);
+        assert_eq!(
+            group_symbols(&state),
+            [
+                ("NFO".to_string(), vec!["NIFTY24JANFUT".to_string()]),
+                (
+                    "NSE".to_string(),
+                    vec!["INFY".to_string(), "TCS".to_string()]
+                ),
+            ]
+        );
+
+        // Filtered out positions leave no empty group behind
+        state.ui_input.position_filter = "tcs".to_string();
+        assert_eq!(
+            group_symbols(&state),
+            [("NSE".to_string(), vec!["TCS".to_string()])]
+        );
+    }
+
+    #[test]
+    fn exchange_groups_keep_every_product_of_a_shared_instrument() {
+        let mut holding = held(1, "INFY", "NSE");
+        holding.update_last_price(110.0);
+        let mut intraday = held(1, "INFY", "NSE");
+        intraday.product = "MIS".to_string();
+        intraday.quantity = -5;
+        intraday.update_last_price(110.0);
+        let state = state_with(vec![holding, intraday, held(2, "NIFTY24JANFUT", "NFO")]);
+
+        let groups = visible_position_groups(&state);
+        assert_eq!(groups[1].0, "NSE");
+        assert_eq!(groups[1].1.len(), 2);
+        // Unrealized 100 on the holding and -50 on the intraday short
+        let by_exchange = state.positions_by_exchange();
+        assert_eq!(by_exchange["NSE"].unrealized, 50.0);
+        assert_eq!(
+            by_exchange.values().map(|pnl| pnl.total).sum::<f64>(),
+            state.calculate_total_pnl().total
+        );
+
+        // Expanding the holding leaves the intraday row closed
+        let snapshot = state.positions_snapshot.positions();
+        let expanded = HashSet::from([(1, "CNC".to_string())]);
+        let details = position_table_rows(&groups, &snapshot, &HashSet::new(), &expanded)
+            .into_iter()
+            .filter(|row| matches!(row, PositionTableRow::Detail(..)))
+            .count();
+        assert_eq!(details, DetailLine::ALL.len());
+    }
+
+    #[test]
+    fn table_rows_skip_collapsed_groups_and_expand_details() {
+        let state = state_with(vec![
+            held(1, "INFY", "NSE"),
+            held(2, "NIFTY24JANFUT", "NFO"),
+            held(3, "TCS", "NSE"),
+        ]);
+        let snapshot = state.positions_snapshot.positions();
+        let groups = visible_position_groups(&state);
+
+        let rows = position_table_rows(&groups, &snapshot, &HashSet::new(), &HashSet::new());
+        assert_eq!(
+            rows,
+            [
+                PositionTableRow::Group(0),
+                PositionTableRow::Position(0, 0),
+                PositionTableRow::Group(1),
+                PositionTableRow::Position(1, 0),
+                PositionTableRow::Position(1, 1),
+            ]
+        );
+
+        let collapsed = HashSet::from(["NFO".to_string()]);
+        let expanded = HashSet::from([(3, "CNC".to_string())]);
+        let rows = position_table_rows(&groups, &snapshot, &collapsed, &expanded);
+        assert_eq!(
+            rows,
+            [
+                PositionTableRow::Group(0),
+                PositionTableRow::Group(1),
+                PositionTableRow::Position(1, 0),
+                PositionTableRow::Position(1, 1),
+                PositionTableRow::Detail(1, 1, DetailLine::Quantities),
+                PositionTableRow::Detail(1, 1, DetailLine::Values),
+                PositionTableRow::Detail(1, 1, DetailLine::Links),
+            ]
+        );
+    }
 }
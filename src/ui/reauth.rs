@@ -0,0 +1,109 @@
+use crate::api::auth_callback;
+use crate::state::{AppState, ConnectionState};
+use crate::ui::components::{primary_button, Modal};
+use egui::{Color32, RichText, Ui};
+
+/// Red strip under the navigation bar while the access token is rejected
+/// It stays until a new token is in place; there is nothing to dismiss
+pub fn render_reauth_banner(ui: &mut Ui, app_state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new("🔒 Access token expired: trading is disabled until you log in again")
+                .strong()
+                .color(Color32::WHITE),
+        );
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.button("Re-authenticate").clicked() {
+                app_state.start_reauth();
+            }
+        });
+    });
+}
+
+/// Whether the banner should show
+pub fn reauth_needed(app_state: &AppState) -> bool {
+    *app_state.connection_state.read() == ConnectionState::AuthExpired
+}
+
+/// Login URL to open, and a field for the request_token Kite redirects back with
+/// While the callback listener runs, the redirect fills the field and submits by itself
+pub fn render_reauth_dialog(ctx: &egui::Context, app_state: &mut AppState) {
+    let Some(mut form) = app_state.ui_input.reauth.take() else {
+        return;
+    };
+
+    let mut keep_open = true;
+    let mut submit = false;
+
+    let response = Modal::new("reauth_modal", "Re-authenticate")
+        .size(egui::Vec2::new(480.0, 300.0))
+        .show(ctx, |ui| {
+            ui.label("1. Log in to Kite with this URL:");
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.login_url.as_str()).desired_width(300.0),
+                );
+                if ui.button("📋 Copy").clicked() {
+                    ui.ctx().copy_text(form.login_url.clone());
+                }
+                if ui.button("🌐 Open").clicked() {
+                    ui.ctx()
+                        .open_url(egui::OpenUrl::new_tab(form.login_url.clone()));
+                }
+            });
+
+            if let Some(port) = form.listening {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(
+                        RichText::new(format!(
+                            "Waiting for the login redirect to {}",
+                            auth_callback::redirect_url(port)
+                        ))
+                        .small(),
+                    )
+                    .on_hover_text(
+                        "Register this as the redirect URL of your Kite app; the page opens in your browser",
+                    );
+                });
+            }
+
+            ui.add_space(8.0);
+            ui.label("2. Paste the request_token, or the whole URL you were redirected to:");
+            let field = ui.add_enabled(
+                !form.exchanging,
+                egui::TextEdit::singleline(&mut form.request_token)
+                    .hint_text("request_token")
+                    .desired_width(f32::INFINITY),
+            );
+            let entered =
+                field.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+
+            if let Some(error) = &form.error {
+                ui.colored_label(Color32::from_rgb(239, 68, 68), error);
+            }
+
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                submit = primary_button("Log in")
+                    .size(egui::Vec2::new(100.0, 30.0))
+                    .loading(form.exchanging)
+                    .ui(ui)
+                    .clicked()
+                    || (entered && !form.exchanging);
+
+                ui.add_space(10.0);
+
+                if ui.button("Cancel").clicked() {
+                    keep_open = false;
+                }
+            });
+        });
+
+    app_state.ui_input.reauth = Some(form);
+    if !keep_open || response.closed {
+        app_state.cancel_reauth();
+    } else if submit {
+        app_state.submit_reauth();
+    }
+}
@@ -0,0 +1,503 @@
+use crate::api::auth_callback;
+use crate::state::{
+    AppState, ConnectionTest, DropPolicy, SettingsForm, TradingMode, CONFIG_FILE,
+    INSTRUMENT_EXCHANGES, LOG_LEVELS,
+};
+use crate::ui::components::{primary_button, success_button};
+use crate::ui::theme::ThemeMode;
+use egui::{Color32, RichText, ScrollArea, Ui};
+
+/// Edit config.toml by section; saving validates, writes the file and applies it like a reload
+pub fn render_settings(ui: &mut Ui, app_state: &mut AppState) {
+    let loaded = &app_state.loaded_config;
+    let form = app_state
+        .ui_input
+        .settings_form
+        .get_or_insert_with(|| SettingsForm::new(loaded));
+    let dirty = form.is_dirty(loaded);
+
+    let mut save = false;
+    let mut test = false;
+    let mut test_notification = false;
+    let mut revert = false;
+
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Settings").size(24.0).strong());
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                save = success_button("💾 Save")
+                    .size(egui::Vec2::new(100.0, 30.0))
+                    .enabled(dirty)
+                    .ui(ui)
+                    .on_hover_text(format!("Validate and write {}", CONFIG_FILE))
+                    .clicked();
+                ui.add_space(10.0);
+                revert = ui.add_enabled(dirty, egui::Button::new("Revert")).clicked();
+                if dirty {
+                    ui.label(RichText::new("Unsaved changes").color(Color32::YELLOW));
+                }
+            });
+        });
+        ui.label(
+            RichText::new(format!(
+                "Saving rewrites {} without its comments; credentials apply after a restart",
+                CONFIG_FILE
+            ))
+            .small()
+            .color(Color32::GRAY),
+        );
+
+        for problem in &form.problems {
+            ui.colored_label(Color32::from_rgb(239, 68, 68), format!("• {}", problem));
+        }
+        ui.add_space(10.0);
+
+        ScrollArea::vertical().show(ui, |ui| {
+            test = render_credentials(ui, form);
+            render_refresh(ui, form);
+            render_risk(ui, form);
+            render_theme(ui, form);
+            render_sounds(ui, form);
+            test_notification = render_notifications(ui, form);
+            render_general(ui, form);
+            render_performance(ui, form);
+        });
+    });
+
+    if revert {
+        *form = SettingsForm::new(loaded);
+    }
+    if test {
+        app_state.test_settings_connection();
+    }
+    if test_notification {
+        app_state.test_notification();
+    }
+    if save {
+        app_state.save_settings();
+    }
+}
+
+/// Titled group with a two-column grid of labels and fields
+fn section(ui: &mut Ui, title: &str, add_contents: impl FnOnce(&mut Ui)) {
+    ui.group(|ui| {
+        ui.set_width(ui.available_width());
+        ui.label(RichText::new(title).size(16.0).strong());
+        ui.add_space(4.0);
+        egui::Grid::new(("settings_section", title))
+            .num_columns(2)
+            .spacing([16.0, 6.0])
+            .show(ui, add_contents);
+    });
+    ui.add_space(10.0);
+}
+
+/// Masked credential fields and the connection test; returns true when Test was clicked
+fn render_credentials(ui: &mut Ui, form: &mut SettingsForm) -> bool {
+    let mut test = false;
+    let reveal = form.reveal_secrets;
+    let zerodha = &mut form.draft.zerodha;
+
+    section(ui, "API credentials", |ui| {
+        for (label, value) in [
+            ("API key", &mut zerodha.api_key),
+            ("API secret", &mut zerodha.api_secret),
+            ("Access token", &mut zerodha.access_token),
+        ] {
+            ui.label(label);
+            ui.add(
+                egui::TextEdit::singleline(value)
+                    .password(!reveal)
+                    .desired_width(320.0),
+            );
+            ui.end_row();
+        }
+
+        ui.label("");
+        ui.checkbox(&mut form.reveal_secrets, "👁 Reveal");
+        ui.end_row();
+
+        ui.label("Token reminder");
+        ui.add(
+            egui::TextEdit::singleline(&mut form.draft.app.token_reminder_time)
+                .hint_text("08:45")
+                .desired_width(60.0),
+        )
+        .on_hover_text("IST time to warn when the access token is from a previous day");
+        ui.end_row();
+
+        ui.label("Login callback port");
+        ui.add(egui::DragValue::new(&mut zerodha.callback_port))
+            .on_hover_text(format!(
+                "Re-authentication captures the login redirect on {}; 0 turns it off",
+                auth_callback::redirect_url(zerodha.callback_port)
+            ));
+        ui.end_row();
+
+        ui.label("");
+        ui.horizontal(|ui| {
+            let testing = form.connection_test == ConnectionTest::Testing;
+            test = primary_button("Test connection")
+                .loading(testing)
+                .ui(ui)
+                .on_hover_text("Fetch funds with these credentials, without saving them")
+                .clicked();
+            match &form.connection_test {
+                ConnectionTest::Untested | ConnectionTest::Testing => {}
+                ConnectionTest::Passed => {
+                    ui.colored_label(Color32::from_rgb(34, 197, 94), "✔ Connected");
+                }
+                ConnectionTest::Failed(e) => {
+                    ui.colored_label(Color32::from_rgb(239, 68, 68), format!("✖ {}", e));
+                }
+            }
+        });
+        ui.end_row();
+    });
+
+    test
+}
+
+fn render_refresh(ui: &mut Ui, form: &mut SettingsForm) {
+    let app = &mut form.draft.app;
+    section(ui, "Refresh and connection", |ui| {
+        ui.label("Auto refresh");
+        ui.add(
+            egui::DragValue::new(&mut app.auto_refresh_secs)
+                .range(0..=3600)
+                .suffix(" s"),
+        )
+        .on_hover_text("Re-fetch positions and orders during market hours; 0 turns it off");
+        ui.end_row();
+
+        let fallback = app.auto_refresh_secs;
+        for (label, value) in [
+            ("Orders refresh", &mut app.orders_refresh_secs),
+            ("Positions refresh", &mut app.positions_refresh_secs),
+        ] {
+            ui.label(label);
+            ui.horizontal(|ui| {
+                let mut secs = value.unwrap_or(fallback);
+                if ui
+                    .add(egui::DragValue::new(&mut secs).range(0..=3600).suffix(" s"))
+                    .changed()
+                {
+                    *value = Some(secs);
+                }
+                if value.is_some() && ui.small_button("Use auto refresh").clicked() {
+                    *value = None;
+                }
+            });
+            ui.end_row();
+        }
+
+        ui.label("Reconnect delay");
+        ui.add(
+            egui::DragValue::new(&mut app.websocket_reconnect_delay_ms)
+                .range(1..=60_000)
+                .suffix(" ms"),
+        );
+        ui.end_row();
+
+        ui.label("Reconnect attempts");
+        ui.add(egui::DragValue::new(&mut app.max_reconnect_attempts).range(1..=1000));
+        ui.end_row();
+
+        ui.label("Positions");
+        ui.vertical(|ui| {
+            ui.checkbox(
+                &mut app.auto_subscribe_positions,
+                "Stream ticks for open positions",
+            );
+            ui.checkbox(
+                &mut app.unsubscribe_flat_positions,
+                "Stop streaming positions once flat",
+            );
+        });
+        ui.end_row();
+    });
+}
+
+fn render_risk(ui: &mut Ui, form: &mut SettingsForm) {
+    let app = &mut form.draft.app;
+    section(ui, "Risk", |ui| {
+        ui.label("Trading mode");
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut app.mode, TradingMode::Live, "Live");
+            ui.selectable_value(&mut app.mode, TradingMode::Paper, "📝 Paper");
+        })
+        .response
+        .on_hover_text("Paper orders fill against live ticks and never reach the exchange; applies after a restart");
+        ui.end_row();
+
+        ui.label("Paper slippage");
+        ui.add_enabled(
+            app.mode == TradingMode::Paper,
+            egui::DragValue::new(&mut app.paper_slippage_pct)
+                .speed(0.01)
+                .range(0.0..=5.0)
+                .suffix(" %"),
+        )
+        .on_hover_text("How much worse than the LTP paper market and stop-market orders fill");
+        ui.end_row();
+
+        ui.label("Orders");
+        ui.vertical(|ui| {
+            ui.checkbox(
+                &mut app.margin_precheck,
+                "Block orders the account cannot fund",
+            );
+            ui.checkbox(
+                &mut app.confirm_market_orders,
+                "Confirm one-click market orders",
+            );
+        });
+        ui.end_row();
+    });
+}
+
+fn render_theme(ui: &mut Ui, form: &mut SettingsForm) {
+    let app = &mut form.draft.app;
+    section(ui, "Theme", |ui| {
+        ui.label("Mode");
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut app.theme, ThemeMode::Dark, "🌙 Dark");
+            ui.selectable_value(&mut app.theme, ThemeMode::Light, "☀ Light");
+        });
+        ui.end_row();
+
+        for (label, color) in [
+            ("Accent color", &mut app.accent_color),
+            ("Profit color", &mut app.profit_color),
+            ("Loss color", &mut app.loss_color),
+        ] {
+            ui.label(label);
+            optional_text(ui, color, "Theme default, or e.g. #22c55e");
+            ui.end_row();
+        }
+
+        ui.label("Heatmap midpoint");
+        ui.add(
+            egui::DragValue::new(&mut app.heatmap_midpoint_pct)
+                .speed(0.1)
+                .range(-20.0..=20.0)
+                .suffix(" %"),
+        )
+        .on_hover_text("Day change shown as a neutral watchlist tile");
+        ui.end_row();
+
+        ui.label("Heatmap full color at");
+        ui.add(
+            egui::DragValue::new(&mut app.heatmap_full_scale_pct)
+                .speed(0.1)
+                .range(0.1..=50.0)
+                .prefix("± ")
+                .suffix(" %"),
+        )
+        .on_hover_text("Distance from the midpoint at which a tile is fully red or green");
+        ui.end_row();
+    });
+}
+
+fn render_sounds(ui: &mut Ui, form: &mut SettingsForm) {
+    let app = &mut form.draft.app;
+    section(ui, "Sounds", |ui| {
+        ui.label("Sounds");
+        ui.vertical(|ui| {
+            ui.checkbox(&mut app.sounds, "Play event sounds");
+            ui.add_enabled_ui(app.sounds, |ui| {
+                ui.checkbox(&mut app.sound_on_fill, "Fills");
+                ui.checkbox(&mut app.sound_on_rejection, "Rejections");
+                ui.checkbox(&mut app.sound_on_alert, "Price alerts");
+            });
+        });
+        ui.end_row();
+    });
+}
+
+/// Telegram and webhook destinations and a test message; returns true when Send test was
+/// clicked
+fn render_notifications(ui: &mut Ui, form: &mut SettingsForm) -> bool {
+    let mut test = false;
+    let reveal = form.reveal_secrets;
+    let notifier = &mut form.draft.notifier;
+
+    section(ui, "Remote notifications", |ui| {
+        ui.label("Telegram bot token");
+        ui.add(
+            egui::TextEdit::singleline(&mut notifier.telegram_bot_token)
+                .password(!reveal)
+                .hint_text("Or telegram_bot_token_command in config.toml")
+                .desired_width(320.0),
+        );
+        ui.end_row();
+
+        ui.label("Telegram chat id");
+        ui.add(egui::TextEdit::singleline(&mut notifier.telegram_chat_id).desired_width(200.0));
+        ui.end_row();
+
+        ui.label("Webhook URL");
+        ui.add(
+            egui::TextEdit::singleline(&mut notifier.webhook_url)
+                .password(!reveal)
+                .desired_width(320.0),
+        )
+        .on_hover_text("Receives a JSON POST per message");
+        ui.end_row();
+
+        ui.label("");
+        ui.horizontal(|ui| {
+            let testing = form.notification_test == ConnectionTest::Testing;
+            test = primary_button("Send test notification")
+                .loading(testing)
+                .ui(ui)
+                .on_hover_text("Send a message to these destinations, without saving them")
+                .clicked();
+            match &form.notification_test {
+                ConnectionTest::Untested | ConnectionTest::Testing => {}
+                ConnectionTest::Passed => {
+                    ui.colored_label(Color32::from_rgb(34, 197, 94), "✔ Sent");
+                }
+                ConnectionTest::Failed(e) => {
+                    ui.colored_label(Color32::from_rgb(239, 68, 68), format!("✖ {}", e));
+                }
+            }
+        });
+        ui.end_row();
+    });
+
+    test
+}
+
+fn render_general(ui: &mut Ui, form: &mut SettingsForm) {
+    let app = &mut form.draft.app;
+    section(ui, "General", |ui| {
+        ui.label("Log level");
+        egui::ComboBox::from_id_salt("settings_log_level")
+            .selected_text(app.log_level.as_str())
+            .show_ui(ui, |ui| {
+                for level in LOG_LEVELS {
+                    ui.selectable_value(&mut app.log_level, level.to_string(), *level);
+                }
+            });
+        ui.end_row();
+
+        ui.label("Display");
+        ui.vertical(|ui| {
+            ui.checkbox(&mut app.abbreviate_crores, "Abbreviate crores in summaries");
+            ui.checkbox(
+                &mut app.os_notifications,
+                "Desktop notifications for alerts",
+            );
+            ui.checkbox(
+                &mut app.live_window_title,
+                "P&L and connection in the window title",
+            );
+        });
+        ui.end_row();
+
+        ui.label("Large trade quantity");
+        ui.add(egui::DragValue::new(&mut app.large_trade_quantity))
+            .on_hover_text("Highlighted in the tick tape; 0 turns it off");
+        ui.end_row();
+
+        ui.label("Tick history");
+        ui.add(egui::DragValue::new(&mut app.tick_history_size).range(1..=100_000))
+            .on_hover_text("Recent ticks kept per instrument");
+        ui.end_row();
+
+        ui.label("Shutdown timeout");
+        ui.add(
+            egui::DragValue::new(&mut app.shutdown_timeout_secs)
+                .range(1..=60)
+                .suffix(" s"),
+        )
+        .on_hover_text("How long workers get to finish in-flight requests when the app closes");
+        ui.end_row();
+
+        ui.label("Event backlog warning");
+        ui.add(egui::DragValue::new(&mut app.event_backlog_warn_threshold).range(1..=1_000_000));
+        ui.end_row();
+
+        ui.label("Journal file");
+        optional_text(ui, &mut app.journal_path, "Off, or e.g. journal.db");
+        ui.end_row();
+
+        ui.label("History file");
+        optional_text(ui, &mut app.history_path, "Off, or e.g. history.db");
+        ui.end_row();
+
+        ui.label("Daily instruments");
+        ui.horizontal_wrapped(|ui| {
+            for exchange in INSTRUMENT_EXCHANGES {
+                let mut selected = app.instrument_exchanges.iter().any(|e| e == exchange);
+                if ui.checkbox(&mut selected, *exchange).changed() {
+                    if selected {
+                        app.instrument_exchanges.push(exchange.to_string());
+                    } else {
+                        app.instrument_exchanges.retain(|e| e != exchange);
+                    }
+                }
+            }
+        })
+        .response
+        .on_hover_text("Loaded at startup and refreshed each day after 08:30 IST");
+        ui.end_row();
+
+        ui.label("NTP server");
+        ui.add(
+            egui::TextEdit::singleline(&mut app.ntp_server)
+                .hint_text("Off")
+                .desired_width(160.0),
+        )
+        .on_hover_text("Clock drift check at startup and hourly; restart to apply");
+        ui.end_row();
+    });
+}
+
+fn render_performance(ui: &mut Ui, form: &mut SettingsForm) {
+    let performance = &mut form.draft.performance;
+    section(ui, "Performance (applies after a restart)", |ui| {
+        ui.label("Tick buffer");
+        ui.add(egui::DragValue::new(&mut performance.tick_buffer_size).range(1..=1_000_000))
+            .on_hover_text("Ticks that can wait for the UI before the drop policy applies");
+        ui.end_row();
+
+        ui.label("When the buffer is full");
+        ui.horizontal(|ui| {
+            for policy in DropPolicy::ALL {
+                ui.selectable_value(&mut performance.drop_policy, policy, policy.label());
+            }
+        });
+        ui.end_row();
+
+        ui.label("Conflation");
+        ui.add(
+            egui::DragValue::new(&mut performance.conflation_interval_ms)
+                .range(0..=1000)
+                .suffix(" ms"),
+        )
+        .on_hover_text("Send only each instrument's latest tick this often; 0 sends every tick");
+        ui.end_row();
+
+        ui.label("Events per frame");
+        ui.add(egui::DragValue::new(&mut performance.max_event_batch).range(1..=1_000_000));
+        ui.end_row();
+    });
+}
+
+/// Text field for an optional setting, where blank means unset
+fn optional_text(ui: &mut Ui, value: &mut Option<String>, hint: &str) {
+    let mut text = value.clone().unwrap_or_default();
+    if ui
+        .add(
+            egui::TextEdit::singleline(&mut text)
+                .hint_text(hint)
+                .desired_width(200.0),
+        )
+        .changed()
+    {
+        *value = Some(text).filter(|text| !text.trim().is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use crate::app::AppView;
+use crate::state::{AppState, TransactionType};
+use egui::{Key, KeyboardShortcut, Modifiers, RichText};
+
+/// What a keyboard shortcut does
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShortcutAction {
+    NewOrder(TransactionType),
+    CloseDialogs,
+    RefreshAll,
+    CancelAllOrders,
+    ShowView(AppView),
+    ToggleHelp,
+}
+
+impl ShortcutAction {
+    pub fn label(self) -> String {
+        match self {
+            ShortcutAction::NewOrder(TransactionType::Buy) => "New buy order".to_string(),
+            ShortcutAction::NewOrder(TransactionType::Sell) => "New sell order".to_string(),
+            ShortcutAction::CloseDialogs => "Close dialogs".to_string(),
+            ShortcutAction::RefreshAll => "Refresh all data".to_string(),
+            ShortcutAction::CancelAllOrders => "Cancel all open orders".to_string(),
+            ShortcutAction::ShowView(view) => format!("Go to {}", view.label()),
+            ShortcutAction::ToggleHelp => "Show this list".to_string(),
+        }
+    }
+}
+
+/// Key bindings, checked in order; the first match each frame wins
+#[derive(Debug, Clone)]
+pub struct ShortcutTable {
+    pub bindings: Vec<(KeyboardShortcut, ShortcutAction)>,
+}
+
+impl Default for ShortcutTable {
+    fn default() -> Self {
+        let key = |key| KeyboardShortcut::new(Modifiers::NONE, key);
+        let mut bindings = vec![
+            (key(Key::F1), ShortcutAction::NewOrder(TransactionType::Buy)),
+            (
+                key(Key::F2),
+                ShortcutAction::NewOrder(TransactionType::Sell),
+            ),
+            (key(Key::Escape), ShortcutAction::CloseDialogs),
+            (
+                KeyboardShortcut::new(Modifiers::COMMAND, Key::R),
+                ShortcutAction::RefreshAll,
+            ),
+            (
+                KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::C),
+                ShortcutAction::CancelAllOrders,
+            ),
+            (key(Key::Questionmark), ShortcutAction::ToggleHelp),
+        ];
+
+        let digits = [
+            Key::Num1,
+            Key::Num2,
+            Key::Num3,
+            Key::Num4,
+            Key::Num5,
+            Key::Num6,
+            Key::Num7,
+            Key::Num8,
+            Key::Num9,
+        ];
+        for (digit, view) in digits.into_iter().zip(AppView::ALL) {
+            bindings.push((key(digit), ShortcutAction::ShowView(view)));
+        }
+
+        Self { bindings }
+    }
+}
+
+/// Run the action of any shortcut pressed this frame
+/// Nothing fires while a text field has focus, so typing is never hijacked
+pub fn handle_shortcuts(ctx: &egui::Context, app_state: &mut AppState) {
+    if ctx.wants_keyboard_input() {
+        return;
+    }
+
+    let pressed = ctx.input_mut(|input| {
+        app_state
+            .ui_input
+            .shortcuts
+            .bindings
+            .iter()
+            .find(|(shortcut, _)| input.consume_shortcut(shortcut))
+            .map(|(_, action)| *action)
+    });
+
+    let Some(action) = pressed else {
+        return;
+    };
+
+    match action {
+        ShortcutAction::NewOrder(transaction_type) => {
+            app_state.ui_input.selected_transaction_type = transaction_type;
+            app_state.ui_input.show_order_dialog = true;
+            app_state.ui_input.requested_view = Some(AppView::Orders);
+        }
+        ShortcutAction::CloseDialogs => {
+            let ui_input = &mut app_state.ui_input;
+            ui_input.show_order_dialog = false;
+            ui_input.order_edit = None;
+            ui_input.inspected_order_id = None;
+            ui_input.pending_market_orders = None;
+            ui_input.pending_cancel_all = None;
+            ui_input.show_shortcut_help = false;
+        }
+        ShortcutAction::RefreshAll => app_state.refresh_all(),
+        // Same rule as the order buttons, which are disabled while trading is blocked
+        ShortcutAction::CancelAllOrders => {
+            if app_state.trading_enabled() {
+                app_state.request_cancel_all_orders();
+            }
+        }
+        ShortcutAction::ShowView(view) => app_state.ui_input.requested_view = Some(view),
+        ShortcutAction::ToggleHelp => {
+            app_state.ui_input.show_shortcut_help = !app_state.ui_input.show_shortcut_help;
+        }
+    }
+}
+
+/// Overlay listing the active bindings
+pub fn render_shortcut_help(ctx: &egui::Context, app_state: &mut AppState) {
+    let mut open = app_state.ui_input.show_shortcut_help;
+    if !open {
+        return;
+    }
+
+    egui::Window::new("Keyboard Shortcuts")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            egui::Grid::new("shortcut_help")
+                .num_columns(2)
+                .spacing([24.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for (shortcut, action) in &app_state.ui_input.shortcuts.bindings {
+                        ui.label(RichText::new(ctx.format_shortcut(shortcut)).monospace());
+                        ui.label(action.label());
+                        ui.end_row();
+                    }
+                });
+        });
+
+    app_state.ui_input.show_shortcut_help = open;
+}
@@ -0,0 +1,115 @@
+use crate::state::{AppState, TickSample};
+use crate::ui::format::format_inr;
+use chrono_tz::Asia::Kolkata;
+use egui::{Color32, RichText, ScrollArea, Ui};
+use std::cmp::Ordering;
+
+/// Trades shown in the tape, newest first
+const TAPE_LENGTH: usize = 200;
+
+/// Time & sales for the selected instrument, read from its tick history
+/// The tape freezes while the pointer is over it so rows can be read
+pub fn render_tape(ui: &mut Ui, app_state: &mut AppState) {
+    let Some(token) = app_state.ui_input.selected_instrument_token else {
+        return;
+    };
+
+    let paused = app_state
+        .ui_input
+        .tape_snapshot
+        .as_ref()
+        .is_some_and(|(snapshot_token, _)| *snapshot_token == token);
+
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Time & Sales").size(16.0).strong());
+        if paused {
+            ui.label(RichText::new("⏸ Paused").small().color(Color32::GRAY));
+        }
+    });
+    ui.add_space(4.0);
+
+    let ticks = match &app_state.ui_input.tape_snapshot {
+        Some((_, ticks)) if paused => ticks.clone(),
+        _ => latest_ticks(app_state, token),
+    };
+
+    if ticks.is_empty() {
+        ui.label(RichText::new("No trades yet").color(Color32::GRAY));
+        return;
+    }
+
+    let large = app_state.config.app.large_trade_quantity;
+    let theme = app_state.theme;
+
+    let response = ScrollArea::vertical()
+        .id_salt("tick_tape")
+        .max_height(ui.available_height())
+        .show(ui, |ui| {
+            egui::Grid::new("tick_tape_table")
+                .num_columns(4)
+                .spacing([12.0, 2.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    // Newest first; each tick's direction is against the one before it
+                    for (index, tick) in ticks.iter().enumerate().rev() {
+                        let previous = index.checked_sub(1).map(|index| ticks[index].price);
+                        let (arrow, color) =
+                            match previous.and_then(|previous| tick.price.partial_cmp(&previous)) {
+                                Some(Ordering::Greater) => ("▲", theme.profit),
+                                Some(Ordering::Less) => ("▼", theme.loss),
+                                _ => ("•", Color32::GRAY),
+                            };
+                        let highlight = large > 0 && tick.quantity >= large;
+
+                        ui.label(
+                            RichText::new(
+                                tick.timestamp
+                                    .with_timezone(&Kolkata)
+                                    .format("%H:%M:%S")
+                                    .to_string(),
+                            )
+                            .monospace()
+                            .small(),
+                        );
+                        ui.colored_label(color, format_inr(tick.price));
+                        let quantity = if tick.quantity > 0 {
+                            tick.quantity.to_string()
+                        } else {
+                            "—".to_string()
+                        };
+                        if highlight {
+                            ui.label(
+                                RichText::new(quantity)
+                                    .strong()
+                                    .background_color(theme.accent.gamma_multiply(0.35)),
+                            );
+                        } else {
+                            ui.label(quantity);
+                        }
+                        ui.colored_label(color, arrow);
+                        ui.end_row();
+                    }
+                });
+        });
+
+    // Freeze on hover, resume as soon as the pointer leaves
+    let hovered = ui.rect_contains_pointer(response.inner_rect);
+    app_state.ui_input.tape_snapshot = match (hovered, paused) {
+        (true, true) => app_state.ui_input.tape_snapshot.take(),
+        (true, false) => Some((token, ticks)),
+        (false, _) => None,
+    };
+}
+
+/// Last ticks of an instrument's history, oldest first, one more than shown so the
+/// oldest row still has a direction
+fn latest_ticks(app_state: &AppState, token: u32) -> Vec<TickSample> {
+    app_state
+        .tick_history
+        .get(&token)
+        .map(|history| {
+            let skip = history.len().saturating_sub(TAPE_LENGTH + 1);
+            history.iter().skip(skip).copied().collect()
+        })
+        .unwrap_or_default()
+}
@@ -0,0 +1,148 @@
+use crate::state::AppConfig;
+use egui::{Color32, Stroke};
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+
+/// Key the runtime theme choice is stored under in eframe's app storage
+pub const THEME_STORAGE_KEY: &str = "theme";
+
+/// Key for the UI scale, kept apart from the theme so either can reset alone
+pub const UI_SCALE_STORAGE_KEY: &str = "ui_scale";
+
+/// Zoom factors offered by the settings menu
+pub const UI_SCALE_RANGE: RangeInclusive<f32> = 0.75..=2.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    #[default]
+    Dark,
+    Light,
+}
+
+/// Visual style plus the colors views use for gains, losses and highlights
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    pub mode: ThemeMode,
+    pub accent: Color32,
+    pub profit: Color32,
+    pub loss: Color32,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::preset(ThemeMode::Dark)
+    }
+}
+
+impl Theme {
+    /// Built-in colors for a mode; the light palette is darker so it reads on white
+    pub fn preset(mode: ThemeMode) -> Self {
+        match mode {
+            ThemeMode::Dark => Self {
+                mode,
+                accent: Color32::from_rgb(59, 130, 246),
+                profit: Color32::from_rgb(34, 197, 94),
+                loss: Color32::from_rgb(239, 68, 68),
+            },
+            ThemeMode::Light => Self {
+                mode,
+                accent: Color32::from_rgb(37, 99, 235),
+                profit: Color32::from_rgb(22, 163, 74),
+                loss: Color32::from_rgb(220, 38, 38),
+            },
+        }
+    }
+
+    /// The configured mode's preset with any colors overridden in config.toml
+    pub fn from_config(config: &AppConfig) -> Self {
+        let preset = Self::preset(config.theme);
+        let color = |hex: &Option<String>, fallback| {
+            hex.as_deref()
+                .and_then(|hex| Color32::from_hex(hex).ok())
+                .unwrap_or(fallback)
+        };
+
+        Self {
+            mode: config.theme,
+            accent: color(&config.accent_color, preset.accent),
+            profit: color(&config.profit_color, preset.profit),
+            loss: color(&config.loss_color, preset.loss),
+        }
+    }
+
+    /// Profit color for a gain or an upward move, loss color otherwise
+    pub fn direction_color(&self, up: bool) -> Color32 {
+        if up {
+            self.profit
+        } else {
+            self.loss
+        }
+    }
+
+    /// Install the theme's visuals on the context
+    pub fn apply(&self, ctx: &egui::Context) {
+        let mut style = (*ctx.style()).clone();
+
+        // Spacing and sizing for dense information display
+        style.spacing.item_spacing = egui::vec2(8.0, 6.0);
+        style.spacing.button_padding = egui::vec2(12.0, 6.0);
+        style.spacing.menu_margin = egui::Margin::same(8);
+        style.spacing.indent = 16.0;
+
+        style.visuals = match self.mode {
+            ThemeMode::Dark => dark_visuals(),
+            ThemeMode::Light => light_visuals(),
+        };
+
+        // Accent colors for interactive elements
+        style.visuals.selection.bg_fill = self.accent;
+        style.visuals.hyperlink_color = self.accent;
+
+        ctx.set_style(style);
+    }
+}
+
+/// Dark theme optimized for trading
+fn dark_visuals() -> egui::Visuals {
+    let mut visuals = egui::Visuals::dark();
+
+    // Colors optimized for trading data
+    visuals.extreme_bg_color = Color32::from_gray(16); // Very dark background
+    visuals.panel_fill = Color32::from_gray(24); // Panel background
+    visuals.window_fill = Color32::from_gray(32); // Window background
+
+    // Text colors for readability
+    visuals.override_text_color = Some(Color32::from_gray(240));
+
+    // Grid and stroke colors
+    visuals.widgets.noninteractive.bg_stroke = Stroke::new(1.0, Color32::from_gray(64));
+    visuals.widgets.noninteractive.fg_stroke = Stroke::new(1.0, Color32::from_gray(64));
+
+    // Interactive widget styling
+    visuals.widgets.inactive.bg_fill = Color32::from_gray(48);
+    visuals.widgets.hovered.bg_fill = Color32::from_gray(64);
+    visuals.widgets.active.bg_fill = Color32::from_gray(80);
+
+    visuals
+}
+
+/// Light theme for daylight trading
+fn light_visuals() -> egui::Visuals {
+    let mut visuals = egui::Visuals::light();
+
+    visuals.extreme_bg_color = Color32::from_gray(255);
+    visuals.panel_fill = Color32::from_gray(245);
+    visuals.window_fill = Color32::from_gray(250);
+
+    visuals.override_text_color = Some(Color32::from_gray(20));
+
+    visuals.widgets.noninteractive.bg_stroke = Stroke::new(1.0, Color32::from_gray(210));
+    visuals.widgets.noninteractive.fg_stroke = Stroke::new(1.0, Color32::from_gray(60));
+
+    visuals.widgets.inactive.bg_fill = Color32::from_gray(225);
+    visuals.widgets.hovered.bg_fill = Color32::from_gray(210);
+    visuals.widgets.active.bg_fill = Color32::from_gray(195);
+
+    visuals
+}
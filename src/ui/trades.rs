@@ -0,0 +1,133 @@
+use crate::app::AppView;
+use crate::data_structures::*;
+use crate::state::{AppState, Command, OrderTab};
+use crate::ui::components::primary_button;
+use crate::ui::format::format_inr;
+use chrono_tz::Asia::Kolkata;
+use egui::{Color32, RichText, ScrollArea, Ui};
+
+/// Render today's executions with per-symbol totals on top
+pub fn render_trades(ui: &mut Ui, app_state: &mut AppState) {
+    // The tradebook is fetched the first time the view is opened
+    if !app_state.ui_input.trades_requested {
+        app_state.ui_input.trades_requested = true;
+        app_state.send_command(Command::FetchTrades);
+    }
+
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Trades").size(24.0).strong());
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if primary_button("🔄 Refresh")
+                    .size(egui::Vec2::new(100.0, 30.0))
+                    .ui(ui)
+                    .clicked()
+                {
+                    app_state.send_command(Command::FetchTrades);
+                }
+            });
+        });
+
+        ui.add_space(10.0);
+
+        let trades = app_state.trades.read().clone();
+        if trades.is_empty() {
+            ui.centered_and_justified(|ui| {
+                ui.label(
+                    RichText::new("No trades today")
+                        .size(16.0)
+                        .color(Color32::GRAY),
+                );
+            });
+            return;
+        }
+
+        render_trade_summary(ui, &trades);
+
+        ui.add_space(10.0);
+        ui.separator();
+        ui.add_space(10.0);
+
+        render_trades_table(ui, app_state, &trades);
+    });
+}
+
+fn render_trade_summary(ui: &mut Ui, trades: &[Trade]) {
+    egui::Grid::new("trade_summary")
+        .num_columns(5)
+        .spacing([20.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            for header in ["Symbol", "Bought", "Buy VWAP", "Sold", "Sell VWAP"] {
+                ui.label(RichText::new(header).strong());
+            }
+            ui.end_row();
+
+            for summary in summarize_trades(trades) {
+                ui.label(&summary.tradingsymbol);
+                ui.label(summary.bought_quantity.to_string());
+                ui.label(vwap_text(summary.buy_vwap()));
+                ui.label(summary.sold_quantity.to_string());
+                ui.label(vwap_text(summary.sell_vwap()));
+                ui.end_row();
+            }
+        });
+}
+
+fn vwap_text(vwap: Option<f64>) -> RichText {
+    match vwap {
+        Some(price) => RichText::new(format_inr(price)),
+        None => RichText::new("—").color(Color32::GRAY),
+    }
+}
+
+/// Newest first; clicking a row shows its parent order in the Orders view
+fn render_trades_table(ui: &mut Ui, app_state: &mut AppState, trades: &[Trade]) {
+    let mut picked_order = None;
+
+    ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+        egui::Grid::new("trades_table")
+            .num_columns(6)
+            .spacing([12.0, 6.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for header in ["Time", "Symbol", "Side", "Qty", "Price", "Order ID"] {
+                    ui.label(RichText::new(header).strong());
+                }
+                ui.end_row();
+
+                for trade in trades.iter().rev() {
+                    ui.label(
+                        trade
+                            .fill_timestamp
+                            .with_timezone(&Kolkata)
+                            .format("%H:%M:%S")
+                            .to_string(),
+                    );
+                    ui.label(&trade.tradingsymbol);
+
+                    let side_color = app_state
+                        .theme
+                        .direction_color(trade.transaction_type == "BUY");
+                    ui.colored_label(side_color, &trade.transaction_type);
+                    ui.label(trade.quantity.to_string());
+                    ui.label(format_inr(trade.average_price));
+
+                    let order_label = ui
+                        .link(&trade.order_id)
+                        .on_hover_text("Show this order in Orders");
+                    if order_label.clicked() {
+                        picked_order = Some(trade.order_id.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+
+    if let Some(order_id) = picked_order {
+        app_state.ui_input.order_filter = order_id;
+        app_state.ui_input.order_tab = OrderTab::All;
+        app_state.ui_input.requested_view = Some(AppView::Orders);
+    }
+}
@@ -0,0 +1,486 @@
+use crate::alerts::AlertCondition;
+use crate::state::{AlertForm, AppState, Command, Exchange};
+use crate::ui::components::{
+    danger_button, flashing_price, primary_button, row_button_size, sparkline, Modal,
+};
+use crate::ui::format::format_inr;
+use crate::ui::orders::render_validated_input;
+use egui::{Align2, Color32, FontId, RichText, ScrollArea, Sense, Stroke, Ui};
+
+/// Heatmap tile size; held instruments grow wider with their position value
+const TILE_SIZE: egui::Vec2 = egui::vec2(110.0, 64.0);
+const MAX_TILE_WIDTH_FACTOR: f32 = 3.0;
+const NEUTRAL_TILE_COLOR: Color32 = Color32::from_gray(90);
+
+/// Render the user's watchlist with live prices, day change and a tick sparkline
+pub fn render_watchlist(ui: &mut Ui, app_state: &mut AppState) {
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Watchlist").size(24.0).strong());
+            ui.add_space(20.0);
+            let heatmap = &mut app_state.ui_input.watchlist_heatmap;
+            ui.selectable_value(heatmap, false, "☰ Table");
+            ui.selectable_value(heatmap, true, "▦ Heatmap");
+        });
+        ui.add_space(10.0);
+
+        render_add_symbol(ui, app_state);
+
+        ui.add_space(10.0);
+        ui.separator();
+        ui.add_space(10.0);
+
+        let tokens = app_state.watchlist.read().tokens();
+        if tokens.is_empty() {
+            ui.centered_and_justified(|ui| {
+                ui.label(
+                    RichText::new("Watchlist is empty. Search for a symbol above to add it.")
+                        .size(16.0)
+                        .color(Color32::GRAY),
+                );
+            });
+        } else if app_state.ui_input.watchlist_heatmap {
+            render_watchlist_heatmap(ui, app_state, &tokens);
+        } else {
+            render_watchlist_table(ui, app_state, &tokens);
+        }
+
+        render_alerts(ui, app_state);
+    });
+
+    render_alert_dialog(ui.ctx(), app_state);
+}
+
+/// Symbol search box; picking a suggestion adds it to the watchlist
+fn render_add_symbol(ui: &mut Ui, app_state: &mut AppState) {
+    let mut search_changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label("Add:");
+        egui::ComboBox::from_id_salt("watchlist_exchange")
+            .selected_text(app_state.ui_input.watchlist_exchange.as_api_str())
+            .show_ui(ui, |ui| {
+                for exchange in Exchange::ALL {
+                    search_changed |= ui
+                        .selectable_value(
+                            &mut app_state.ui_input.watchlist_exchange,
+                            exchange,
+                            exchange.as_api_str(),
+                        )
+                        .changed();
+                }
+            });
+
+        search_changed |= ui
+            .add(
+                egui::TextEdit::singleline(&mut app_state.ui_input.watchlist_symbol_input)
+                    .hint_text("Search symbol or name"),
+            )
+            .changed();
+    });
+
+    if search_changed {
+        app_state.ui_input.watchlist_suggestions = app_state.search_instruments(
+            &app_state.ui_input.watchlist_symbol_input,
+            app_state.ui_input.watchlist_exchange,
+        );
+    }
+
+    if app_state.ui_input.watchlist_suggestions.is_empty() {
+        return;
+    }
+
+    let mut picked = None;
+    ui.group(|ui| {
+        for token in &app_state.ui_input.watchlist_suggestions {
+            let Some(instrument) = app_state.instruments.get(token) else {
+                continue;
+            };
+            let label = format!("{}  {}", instrument.tradingsymbol, instrument.name);
+            if ui.selectable_label(false, label).clicked() {
+                picked = Some(*token);
+            }
+        }
+    });
+
+    if let Some(instrument_token) = picked {
+        app_state.send_command(Command::AddToWatchlist {
+            instrument_token,
+            note: None,
+        });
+        app_state.ui_input.watchlist_symbol_input.clear();
+        app_state.ui_input.watchlist_suggestions.clear();
+    }
+}
+
+fn render_watchlist_table(ui: &mut Ui, app_state: &mut AppState, tokens: &[u32]) {
+    ScrollArea::vertical().max_height(600.0).show(ui, |ui| {
+        egui::Grid::new("watchlist_table")
+            .num_columns(6)
+            .spacing([12.0, 6.0])
+            .striped(true)
+            .show(ui, |ui| {
+                for header in ["Symbol", "LTP", "Change %", "Trend", "Note", "Actions"] {
+                    ui.label(RichText::new(header).strong());
+                }
+                ui.end_row();
+
+                for (row, &token) in tokens.iter().enumerate() {
+                    render_watchlist_row(ui, app_state, token, row, tokens.len());
+                }
+            });
+    });
+}
+
+fn render_watchlist_row(
+    ui: &mut Ui,
+    app_state: &mut AppState,
+    token: u32,
+    row: usize,
+    rows: usize,
+) {
+    let instrument = app_state
+        .instruments
+        .get(&token)
+        .map(|entry| entry.value().clone());
+    let symbol = instrument
+        .as_ref()
+        .map(|instrument| instrument.tradingsymbol.clone())
+        .unwrap_or_else(|| token.to_string());
+    let last_price = app_state.tick_data.get(&token).map(|tick| tick.last_price);
+
+    // Clicking a symbol selects it and streams it in full mode for depth
+    let selected = app_state.ui_input.selected_instrument_token == Some(token);
+    if ui.selectable_label(selected, &symbol).clicked() {
+        app_state.select_instrument(token);
+    }
+
+    match last_price {
+        Some(price) => flashing_price(
+            ui,
+            &mut app_state.ui_input.price_flashes,
+            &app_state.theme,
+            token,
+            price,
+            RichText::new(format_inr(price)),
+        ),
+        None => {
+            ui.label(RichText::new("—").color(Color32::GRAY));
+        }
+    }
+
+    match last_price.zip(app_state.previous_close(token)) {
+        Some((price, close)) if close > 0.0 => {
+            let change = (price - close) / close * 100.0;
+            let color = app_state.theme.direction_color(change >= 0.0);
+            ui.colored_label(color, format!("{:+.2}%", change));
+        }
+        _ => {
+            ui.label(RichText::new("—").color(Color32::GRAY));
+        }
+    }
+
+    let prices: Vec<f64> = app_state
+        .tick_history(token)
+        .into_iter()
+        .map(|tick| tick.price)
+        .collect();
+    sparkline(ui, &prices, egui::vec2(100.0, 20.0), &app_state.theme);
+
+    let note = app_state
+        .watchlist
+        .read()
+        .entries
+        .iter()
+        .find(|entry| entry.instrument_token == token)
+        .and_then(|entry| entry.note.clone())
+        .unwrap_or_default();
+    ui.label(RichText::new(note).color(Color32::GRAY));
+
+    ui.horizontal(|ui| {
+        if primary_button("Trade")
+            .size(row_button_size(ui, 5))
+            .ui(ui)
+            .clicked()
+        {
+            app_state
+                .ui_input
+                .open_order_for(&symbol, instrument.clone());
+        }
+
+        // Moves persist with the watchlist, so the order survives a restart
+        let button_size = row_button_size(ui, 1);
+        if ui
+            .add_enabled(row > 0, egui::Button::new("⬆").min_size(button_size))
+            .on_hover_text("Move up")
+            .clicked()
+        {
+            app_state.send_command(Command::ReorderWatchlist {
+                from: row,
+                to: row - 1,
+            });
+        }
+        if ui
+            .add_enabled(row + 1 < rows, egui::Button::new("⬇").min_size(button_size))
+            .on_hover_text("Move down")
+            .clicked()
+        {
+            app_state.send_command(Command::ReorderWatchlist {
+                from: row,
+                to: row + 1,
+            });
+        }
+
+        if ui
+            .add(egui::Button::new("🔔").min_size(button_size))
+            .on_hover_text("Add a price alert")
+            .clicked()
+        {
+            app_state.ui_input.alert_form = Some(AlertForm::new(token, last_price));
+        }
+
+        if danger_button("✕")
+            .size(row_button_size(ui, 1))
+            .ui(ui)
+            .clicked()
+        {
+            app_state.send_command(Command::RemoveFromWatchlist {
+                instrument_token: token,
+            });
+        }
+    });
+
+    ui.end_row();
+}
+
+/// Tiles colored by day change on a red–green scale, live with every tick
+/// Clicking a tile selects its instrument like clicking a symbol in the table
+fn render_watchlist_heatmap(ui: &mut Ui, app_state: &mut AppState, tokens: &[u32]) {
+    let midpoint = app_state.config.app.heatmap_midpoint_pct;
+    let full_scale = app_state.config.app.heatmap_full_scale_pct;
+    let theme = app_state.theme;
+
+    // Summed across products, so a CNC holding and an MIS trade both count
+    let held_value = |token: u32| {
+        app_state
+            .positions_of(token)
+            .iter()
+            .filter(|position| position.quantity != 0)
+            .map(|position| position.last_price * position.quantity.abs() as f64)
+            .reduce(|total, value| total + value)
+    };
+    let max_value = tokens
+        .iter()
+        .filter_map(|&token| held_value(token))
+        .fold(0.0, f64::max);
+
+    let mut clicked = None;
+    ScrollArea::vertical().show(ui, |ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing = egui::vec2(6.0, 6.0);
+
+            for &token in tokens {
+                let symbol = app_state
+                    .instruments
+                    .get(&token)
+                    .map(|instrument| instrument.tradingsymbol.clone())
+                    .unwrap_or_else(|| token.to_string());
+                let last_price = app_state.tick_data.get(&token).map(|tick| tick.last_price);
+                let change = last_price
+                    .zip(app_state.previous_close(token))
+                    .filter(|(_, close)| *close > 0.0)
+                    .map(|(price, close)| (price - close) / close * 100.0);
+                let value = held_value(token);
+
+                let width_factor = match value {
+                    Some(value) if max_value > 0.0 => {
+                        1.0 + (MAX_TILE_WIDTH_FACTOR - 1.0) * (value / max_value) as f32
+                    }
+                    _ => 1.0,
+                };
+                let size = egui::vec2(TILE_SIZE.x * width_factor, TILE_SIZE.y);
+                let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+
+                let fill = match change {
+                    Some(change) => {
+                        let strength = ((change - midpoint) / full_scale).clamp(-1.0, 1.0) as f32;
+                        let target = theme.direction_color(strength >= 0.0);
+                        NEUTRAL_TILE_COLOR.lerp_to_gamma(target, strength.abs())
+                    }
+                    None => NEUTRAL_TILE_COLOR,
+                };
+                let painter = ui.painter_at(rect);
+                painter.rect_filled(rect, 4.0, fill);
+                if app_state.ui_input.selected_instrument_token == Some(token) {
+                    painter.rect_stroke(
+                        rect.shrink(1.0),
+                        4.0,
+                        Stroke::new(2.0, theme.accent),
+                        egui::StrokeKind::Inside,
+                    );
+                } else if response.hovered() {
+                    painter.rect_stroke(
+                        rect.shrink(1.0),
+                        4.0,
+                        Stroke::new(1.0, Color32::WHITE),
+                        egui::StrokeKind::Inside,
+                    );
+                }
+
+                painter.text(
+                    rect.center() - egui::vec2(0.0, 9.0),
+                    Align2::CENTER_CENTER,
+                    &symbol,
+                    FontId::proportional(13.0),
+                    Color32::WHITE,
+                );
+                painter.text(
+                    rect.center() + egui::vec2(0.0, 10.0),
+                    Align2::CENTER_CENTER,
+                    change
+                        .map(|change| format!("{:+.2}%", change))
+                        .unwrap_or_else(|| "—".to_string()),
+                    FontId::monospace(12.0),
+                    Color32::WHITE,
+                );
+
+                let mut hover = format!(
+                    "{}\nLTP {}",
+                    symbol,
+                    last_price
+                        .map(format_inr)
+                        .unwrap_or_else(|| "—".to_string())
+                );
+                if let Some(value) = value {
+                    hover.push_str(&format!("\nPosition value {}", format_inr(value)));
+                }
+                if response.on_hover_text(hover).clicked() {
+                    clicked = Some(token);
+                }
+            }
+        });
+    });
+
+    if let Some(token) = clicked {
+        app_state.select_instrument(token);
+    }
+}
+
+/// Price alerts across all instruments, with toggles to pause, edit or delete each
+fn render_alerts(ui: &mut Ui, app_state: &mut AppState) {
+    let alerts = app_state.alerts.read().alerts.clone();
+    if alerts.is_empty() {
+        return;
+    }
+
+    ui.add_space(10.0);
+    ui.separator();
+    ui.label(RichText::new("Price Alerts").size(18.0).strong());
+    ui.add_space(6.0);
+
+    egui::Grid::new("price_alerts")
+        .num_columns(5)
+        .spacing([12.0, 6.0])
+        .striped(true)
+        .show(ui, |ui| {
+            for alert in alerts {
+                ui.label(app_state.instrument_label(alert.instrument_token));
+                ui.label(format!(
+                    "{} {}",
+                    alert.condition.label(),
+                    format_inr(alert.threshold)
+                ));
+                ui.label(
+                    RichText::new(if alert.repeating { "Repeating" } else { "Once" })
+                        .color(Color32::GRAY),
+                );
+
+                let mut enabled = alert.enabled;
+                if ui.checkbox(&mut enabled, "On").changed() {
+                    let mut alert = alert.clone();
+                    alert.enabled = enabled;
+                    app_state.send_command(Command::UpdateAlert { alert });
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Edit").clicked() {
+                        app_state.ui_input.alert_form = Some(AlertForm::edit(&alert));
+                    }
+                    if danger_button("✕")
+                        .size(row_button_size(ui, 1))
+                        .ui(ui)
+                        .clicked()
+                    {
+                        app_state.send_command(Command::DeleteAlert {
+                            alert_id: alert.id.clone(),
+                        });
+                    }
+                });
+
+                ui.end_row();
+            }
+        });
+}
+
+/// Modal for creating or editing a price alert
+fn render_alert_dialog(ctx: &egui::Context, app_state: &mut AppState) {
+    let Some(mut form) = app_state.ui_input.alert_form.take() else {
+        return;
+    };
+
+    let title = if form.existing.is_some() {
+        "Edit Price Alert"
+    } else {
+        "New Price Alert"
+    };
+    let label = app_state.instrument_label(form.instrument_token);
+    let mut keep_open = true;
+
+    let response = Modal::new("price_alert_modal", title)
+        .size(egui::Vec2::new(360.0, 260.0))
+        .show(ctx, |ui| {
+            ui.label(RichText::new(label).strong());
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                ui.label("When price:");
+                for condition in AlertCondition::ALL {
+                    ui.selectable_value(&mut form.condition, condition, condition.label());
+                }
+            });
+            if render_validated_input(ui, "Price:", &mut form.threshold_input) {
+                form.validate();
+            }
+            ui.checkbox(&mut form.repeating, "Repeat after price moves away");
+            ui.horizontal(|ui| {
+                ui.label("Note:");
+                ui.text_edit_singleline(&mut form.note);
+            });
+
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                if primary_button("Save")
+                    .size(egui::Vec2::new(80.0, 30.0))
+                    .enabled(form.is_ready())
+                    .ui(ui)
+                    .clicked()
+                {
+                    if let Some(command) = form.to_command() {
+                        app_state.send_command(command);
+                    }
+                    keep_open = false;
+                }
+
+                ui.add_space(10.0);
+
+                if ui.button("Close").clicked() {
+                    keep_open = false;
+                }
+            });
+        });
+
+    if keep_open && !response.closed {
+        app_state.ui_input.alert_form = Some(form);
+    }
+}
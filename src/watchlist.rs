@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Default location of the persisted watchlist, next to config.toml
+pub const WATCHLIST_FILE: &str = "watchlist.json";
+
+/// Single watchlist member with an optional user note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistEntry {
+    pub instrument_token: u32,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Ordered, user-curated list of instruments independent of positions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Watchlist {
+    pub entries: Vec<WatchlistEntry>,
+}
+
+impl Watchlist {
+    /// Load the watchlist from disk; a missing file yields an empty watchlist
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Persist the watchlist as pretty-printed JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    pub fn contains(&self, instrument_token: u32) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.instrument_token == instrument_token)
+    }
+
+    /// Instrument tokens in display order
+    pub fn tokens(&self) -> Vec<u32> {
+        self.entries
+            .iter()
+            .map(|entry| entry.instrument_token)
+            .collect()
+    }
+
+    /// Append an instrument; returns false if it was already present
+    pub fn add(&mut self, instrument_token: u32, note: Option<String>) -> bool {
+        if self.contains(instrument_token) {
+            return false;
+        }
+
+        self.entries.push(WatchlistEntry {
+            instrument_token,
+            note,
+        });
+        true
+    }
+
+    /// Remove an instrument; returns false if it was not present
+    pub fn remove(&mut self, instrument_token: u32) -> bool {
+        let len = self.entries.len();
+        self.entries
+            .retain(|entry| entry.instrument_token != instrument_token);
+        self.entries.len() != len
+    }
+
+    /// Move the entry at `from` to position `to`, clamping `to` to the list bounds
+    pub fn reorder(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.entries.len() {
+            return false;
+        }
+
+        let entry = self.entries.remove(from);
+        let to = to.min(self.entries.len());
+        self.entries.insert(to, entry);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watchlist(tokens: &[u32]) -> Watchlist {
+        let mut watchlist = Watchlist::default();
+        for &token in tokens {
+            watchlist.add(token, None);
+        }
+        watchlist
+    }
+
+    #[test]
+    fn reorder_moves_one_entry_up_or_down() {
+        let mut list = watchlist(&[1, 2, 3, 4]);
+        assert!(list.reorder(2, 1));
+        assert_eq!(list.tokens(), [1, 3, 2, 4]);
+        assert!(list.reorder(0, 1));
+        assert_eq!(list.tokens(), [3, 1, 2, 4]);
+        assert!(list.reorder(0, 3));
+        assert_eq!(list.tokens(), [1, 2, 4, 3]);
+    }
+
+    #[test]
+    fn reorder_clamps_the_target_and_refuses_a_missing_entry() {
+        let mut list = watchlist(&[1, 2, 3]);
+        assert!(list.reorder(0, 10));
+        assert_eq!(list.tokens(), [2, 3, 1]);
+        assert!(!list.reorder(3, 0));
+        assert_eq!(list.tokens(), [2, 3, 1]);
+    }
+
+    #[test]
+    fn add_and_remove_ignore_repeats() {
+        let mut list = watchlist(&[1, 2]);
+        assert!(!list.add(1, Some("again".to_string())));
+        assert!(list.remove(1));
+        assert!(!list.remove(1));
+        assert_eq!(list.tokens(), [2]);
+    }
+}
@@ -1,21 +1,208 @@
-use crate::api::ZerodhaClient;
+use crate::api::auth_callback::{self, CallbackListener};
+use crate::api::{BrokerClient, TokenRejected, ZerodhaClient};
 use crate::data_structures::*;
-use crate::state::{Command, Config, EventSender};
-use crossbeam_channel::Receiver;
+use crate::market_calendar::{ist_now, MarketStatus};
+use crate::state::{
+    ApiCommandReceiver, AppEvent, Command, Config, EventSender, OrderType, TradingMode,
+    TransactionType,
+};
+use crate::workers::circuit_breaker::{ApiCategory, CircuitBreaker, CircuitOpen};
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch, RwLock, Semaphore};
+use tokio::task::{JoinHandle, JoinSet};
+
+/// How long a fetched margins snapshot is reused for pre-checks
+const MARGIN_CACHE_TTL: Duration = Duration::from_secs(10);
+
+/// A requested fetch this soon after the same fetch started waits for the window to pass,
+/// and any further requests meanwhile are answered by that one
+const FETCH_COALESCE_WINDOW: Duration = Duration::from_secs(2);
+
+/// How often a paused background refresh checks whether it can run again
+const REFRESH_PAUSED_RECHECK: Duration = Duration::from_secs(30);
+
+/// Fetches and other reads allowed in flight at once; order mutations go one at a time
+const MAX_CONCURRENT_READS: usize = 4;
 
 /// High-performance API handler worker for REST API operations
 /// Runs in a dedicated thread to prevent blocking the UI
+/// Commands are read in order and run as tasks of their own, so a slow fetch never holds
+/// up an order
 pub struct ApiHandler {
+    shared: Arc<Handlers>,
+    config_updates: watch::Receiver<Config>,
+}
+
+/// How a command is run once read
+enum Lane {
+    /// On the command loop itself, before the next command is read
+    Inline,
+    /// On the single order task, in the order they were sent, so actions on the same order
+    /// never overtake each other
+    Mutation,
+    /// In a task of its own, up to MAX_CONCURRENT_READS at a time
+    Read,
+}
+
+impl Lane {
+    fn of(command: &Command) -> Self {
+        match command {
+            Command::PlaceOrder { .. }
+            | Command::ModifyOrder { .. }
+            | Command::CancelOrder { .. }
+            | Command::SquareOffByTag { .. } => Lane::Mutation,
+            Command::UpdateAccessToken { .. }
+            | Command::AwaitLoginCallback { .. }
+            | Command::CancelLoginCallback
+            | Command::Shutdown => Lane::Inline,
+            _ => Lane::Read,
+        }
+    }
+}
+
+/// Why the command loop woke without a command
+enum Idle {
+    /// The background refresh is due and nothing is queued
+    RefreshDue,
+    /// The app's senders are gone
+    Closed,
+}
+
+/// When a fetch last started, requested or in the background
+#[derive(Default)]
+struct FetchSchedule {
+    started_at: Option<Instant>,
+    // A request that came within the coalescing window waits to run once it has passed
+    trailing: bool,
+}
+
+/// How a requested fetch is answered
+enum FetchClaim {
+    /// Nothing started within the coalescing window, so it runs now
+    Now,
+    /// Once the coalescing window of the fetch that just started has passed
+    After(Instant),
+    /// By the trailing fetch already waiting out the window
+    Queued,
+}
+
+/// Fill and rejection state carried from one orders snapshot to the next
+#[derive(Default)]
+struct OrderTracking {
+    rejected_orders: HashSet<String>,
+    filled_orders: HashMap<String, (i32, f64)>, // order_id -> (filled_quantity, average_price)
+    orders_seen: bool,
+}
+
+impl OrderTracking {
+    /// OrderRejected for orders that turned REJECTED since the last refresh
+    fn new_rejections(&mut self, orders: &[Order], first_refresh: bool) -> Vec<AppEvent> {
+        let mut rejections = Vec::new();
+        for order in orders {
+            if !matches!(order.status, OrderStatus::Rejected) {
+                continue;
+            }
+            if !self.rejected_orders.insert(order.order_id.clone()) || first_refresh {
+                continue;
+            }
+
+            rejections.push(AppEvent::OrderRejected {
+                order_id: Some(order.order_id.clone()),
+                tradingsymbol: order.tradingsymbol.clone(),
+                reason: order
+                    .status_message
+                    .clone()
+                    .unwrap_or_else(|| "No reason given".to_string()),
+                request_id: None,
+            });
+        }
+
+        rejections
+    }
+
+    /// Build OrderFilled events for quantity filled since the last refresh
+    /// The delta's price is backed out of the cumulative average so partial fills report their own price
+    fn detect_fills(&mut self, orders: &[Order], first_refresh: bool) -> Vec<AppEvent> {
+        let mut fills = Vec::new();
+
+        for order in orders {
+            // Some snapshots report COMPLETE before filled_quantity catches up
+            let filled = if matches!(order.status, OrderStatus::Complete) {
+                order.filled_quantity.max(order.quantity)
+            } else {
+                order.filled_quantity
+            };
+
+            let (previous_filled, previous_average) = self
+                .filled_orders
+                .get(&order.order_id)
+                .copied()
+                .unwrap_or((0, 0.0));
+
+            if filled <= previous_filled {
+                continue;
+            }
+            self.filled_orders
+                .insert(order.order_id.clone(), (filled, order.average_price));
+
+            if first_refresh {
+                continue;
+            }
+
+            let delta = filled - previous_filled;
+            let fill_price = (order.average_price * filled as f64
+                - previous_average * previous_filled as f64)
+                / delta as f64;
+
+            fills.push(AppEvent::OrderFilled {
+                order_id: order.order_id.clone(),
+                fill_price: if fill_price.is_finite() && fill_price > 0.0 {
+                    fill_price
+                } else {
+                    order.average_price
+                },
+                fill_quantity: delta,
+            });
+        }
+
+        fills
+    }
+}
+
+/// The command handlers and the state they share between concurrent tasks
+struct Handlers {
     client: Arc<RwLock<ZerodhaClient>>,
+    // Where orders go and positions, orders and trades come from: the client above when
+    // trading live, the paper book otherwise
+    broker: Arc<dyn BrokerClient>,
     event_sender: EventSender,
-    config: Config,
+    config: parking_lot::RwLock<Config>,
+    margin_cache: Mutex<Option<(Instant, Margins)>>,
+    // Held for a whole orders fetch, so snapshots are compared in the order they were taken
+    order_tracking: tokio::sync::Mutex<OrderTracking>,
+    orders_fetch: Mutex<FetchSchedule>,
+    positions_fetch: Mutex<FetchSchedule>,
+    // The profile check found the access token refused; background refreshes pause
+    token_rejected: AtomicBool,
+    // Listener waiting for the Kite login redirect, run beside the command loop
+    login_callback: Mutex<Option<JoinHandle<()>>>,
+    circuits: Arc<CircuitBreaker>,
 }
 
 impl ApiHandler {
     /// Create new API handler with optimized Zerodha client
-    pub fn new(config: Config, event_sender: EventSender) -> Self {
+    /// `paper_broker` takes over order routing in paper mode
+    pub fn new(
+        config: Config,
+        event_sender: EventSender,
+        config_updates: watch::Receiver<Config>,
+        paper_broker: Option<Arc<dyn BrokerClient>>,
+    ) -> Self {
         let mut client = ZerodhaClient::new(
             config.zerodha.api_key.clone(),
             config.zerodha.api_secret.clone(),
@@ -24,44 +211,291 @@ impl ApiHandler {
         // Set the access token from configuration for personal trading
         client.set_access_token(config.zerodha.access_token.clone());
 
+        let client = Arc::new(RwLock::new(client));
+        let broker = paper_broker.unwrap_or_else(|| client.clone());
+
         Self {
-            client: Arc::new(RwLock::new(client)),
-            event_sender,
-            config,
+            shared: Arc::new(Handlers {
+                client,
+                broker,
+                event_sender,
+                config: parking_lot::RwLock::new(config),
+                margin_cache: Mutex::new(None),
+                order_tracking: tokio::sync::Mutex::new(OrderTracking::default()),
+                orders_fetch: Mutex::new(FetchSchedule::default()),
+                positions_fetch: Mutex::new(FetchSchedule::default()),
+                token_rejected: AtomicBool::new(false),
+                login_callback: Mutex::new(None),
+                circuits: Arc::new(CircuitBreaker::default()),
+            }),
+            config_updates,
         }
     }
 
     /// Main worker loop - processes commands from UI thread
     /// Designed for ultra-low latency command processing
-    pub async fn run(&mut self, command_receiver: Receiver<Command>) {
-        self.event_sender.send_notification(
+    /// Order actions arrive on `priority` and are taken ahead of anything waiting on
+    /// `commands`
+    /// Returns on Shutdown once the commands already started have finished
+    pub async fn run(&mut self, priority: ApiCommandReceiver, commands: ApiCommandReceiver) {
+        self.shared.event_sender.send_notification(
             LogLevel::Info,
             "API handler started".to_string(),
             Some("api_handler".to_string()),
         );
 
-        while let Ok(command) = command_receiver.recv() {
-            if let Command::Shutdown = command {
-                break;
+        let mut priority = priority.lock().await;
+        let mut commands = commands.lock().await;
+        let (mutations, mutation_queue) = mpsc::unbounded_channel();
+        let mut mutation_task =
+            tokio::spawn(run_mutations(Arc::clone(&self.shared), mutation_queue));
+        let read_permits = Arc::new(Semaphore::new(MAX_CONCURRENT_READS));
+        let mut reads = JoinSet::new();
+
+        loop {
+            self.apply_config_updates();
+            // A handler that panicked takes the worker down with it, for the supervisor
+            // to restart, as it did when handlers ran on this loop
+            while let Some(result) = reads.try_join_next() {
+                rethrow_panic(result);
+            }
+            if mutation_task.is_finished() {
+                rethrow_panic((&mut mutation_task).await);
             }
 
-            if let Err(e) = self.handle_command(command).await {
-                self.event_sender.send_error(
-                    format!("Command handling error: {}", e),
-                    Some("api_handler".to_string()),
-                );
+            let command =
+                match next_command(&mut priority, &mut commands, self.next_refresh_at()).await {
+                    Ok(command) => command,
+                    Err(Idle::RefreshDue) => {
+                        for command in self.claim_background_refresh() {
+                            self.spawn_read(&mut reads, &read_permits, command, None);
+                        }
+                        continue;
+                    }
+                    Err(Idle::Closed) => break,
+                };
+
+            match Lane::of(&command) {
+                Lane::Inline => {
+                    if let Command::Shutdown = command {
+                        break;
+                    }
+                    self.shared.execute(command).await;
+                }
+                Lane::Mutation => {
+                    let _ = mutations.send(command);
+                }
+                // A click landing just after a background refresh, or a burst of queued
+                // refreshes, is served by one fetch after the one that just started
+                Lane::Read => match self.shared.fetch_schedule(&command).map(claim_fetch) {
+                    Some(FetchClaim::Now) | None => {
+                        self.spawn_read(&mut reads, &read_permits, command, None)
+                    }
+                    Some(FetchClaim::After(run_at)) => {
+                        self.spawn_read(&mut reads, &read_permits, command, Some(run_at))
+                    }
+                    Some(FetchClaim::Queued) => {}
+                },
             }
         }
 
-        self.event_sender.send_notification(
+        // Let order actions and fetches already started finish before reporting stopped
+        drop(mutations);
+        rethrow_panic(mutation_task.await);
+        while let Some(result) = reads.join_next().await {
+            rethrow_panic(result);
+        }
+        self.shared.cancel_login_callback();
+
+        self.shared.event_sender.send_notification(
             LogLevel::Info,
             "API handler stopped".to_string(),
             Some("api_handler".to_string()),
         );
     }
 
+    /// Run a read in a task of its own once a read permit is free, and not before `run_at`
+    /// when it is a trailing fetch
+    fn spawn_read(
+        &self,
+        reads: &mut JoinSet<()>,
+        permits: &Arc<Semaphore>,
+        command: Command,
+        run_at: Option<Instant>,
+    ) {
+        let shared = Arc::clone(&self.shared);
+        let permits = Arc::clone(permits);
+        reads.spawn(async move {
+            if let Some(run_at) = run_at {
+                tokio::time::sleep_until(run_at.into()).await;
+                // Requests from here on need a fetch of their own
+                if let Some(schedule) = shared.fetch_schedule(&command) {
+                    schedule.lock().trailing = false;
+                }
+            }
+            let Ok(_permit) = permits.acquire_owned().await else {
+                return;
+            };
+            shared.execute(command).await;
+        });
+    }
+
+    /// Pick up settings from a reloaded config.toml before the next command
+    fn apply_config_updates(&mut self) {
+        if self.config_updates.has_changed().unwrap_or(false) {
+            let reloaded = self.config_updates.borrow_and_update().clone();
+            self.shared.config.write().apply_reload(&reloaded);
+            // Pre-check settings may have changed
+            *self.shared.margin_cache.lock() = None;
+        }
+    }
+
+    /// When the background refresh next has work to do, None when it is turned off
+    fn next_refresh_at(&self) -> Option<Instant> {
+        let config = self.shared.config.read();
+        let schedule = [
+            (
+                config.app.orders_refresh_interval(),
+                self.shared.orders_fetch.lock().started_at,
+            ),
+            (
+                config.app.positions_refresh_interval(),
+                self.shared.positions_fetch.lock().started_at,
+            ),
+        ];
+        if schedule.iter().all(|(interval, _)| interval.is_none()) {
+            return None;
+        }
+        if !self.refresh_active() {
+            return Some(Instant::now() + REFRESH_PAUSED_RECHECK);
+        }
+
+        schedule
+            .into_iter()
+            .filter_map(|(interval, fetched_at)| {
+                let interval = interval?;
+                Some(fetched_at.map_or_else(Instant::now, |at| at + interval))
+            })
+            .min()
+    }
+
+    /// Background refreshes run during market hours while the access token is accepted
+    /// and portfolio requests aren't failing fast
+    fn refresh_active(&self) -> bool {
+        !self.shared.token_rejected.load(Ordering::Relaxed)
+            && !self.shared.circuits.is_open(ApiCategory::Portfolio)
+            && MarketStatus::now("NSE").is_trading()
+    }
+
+    /// The fetches among orders and positions that are due, marked as started so the
+    /// schedule moves on while they run
+    /// Any fetch restarts its interval, so a manual refresh pushes the next one back
+    fn claim_background_refresh(&self) -> Vec<Command> {
+        if !self.refresh_active() {
+            return Vec::new();
+        }
+        let now = Instant::now();
+        let config = self.shared.config.read();
+        let mut due = Vec::new();
+        for (interval, command) in [
+            (config.app.orders_refresh_interval(), Command::FetchOrders),
+            (
+                config.app.positions_refresh_interval(),
+                Command::FetchPositions,
+            ),
+        ] {
+            let Some(schedule) = self.shared.fetch_schedule(&command) else {
+                continue;
+            };
+            let mut schedule = schedule.lock();
+            if interval
+                .is_some_and(|interval| schedule.started_at.is_none_or(|at| at + interval <= now))
+            {
+                schedule.started_at = Some(now);
+                due.push(command);
+            }
+        }
+        due
+    }
+}
+
+/// Run order mutations one at a time, in the order they were sent, until the loop stops
+async fn run_mutations(shared: Arc<Handlers>, mut queue: mpsc::UnboundedReceiver<Command>) {
+    while let Some(command) = queue.recv().await {
+        shared.execute(command).await;
+    }
+}
+
+/// Resume a handler task's panic on the command loop
+fn rethrow_panic(result: Result<(), tokio::task::JoinError>) {
+    if let Err(e) = result {
+        if e.is_panic() {
+            std::panic::resume_unwind(e.into_panic());
+        }
+    }
+}
+
+/// Mark a requested fetch as started, or queue one trailing fetch when the last one
+/// started within the coalescing window
+fn claim_fetch(schedule: &Mutex<FetchSchedule>) -> FetchClaim {
+    let mut schedule = schedule.lock();
+    let now = Instant::now();
+    match schedule.started_at {
+        Some(at) if now < at + FETCH_COALESCE_WINDOW => {
+            if schedule.trailing {
+                FetchClaim::Queued
+            } else {
+                schedule.trailing = true;
+                FetchClaim::After(at + FETCH_COALESCE_WINDOW)
+            }
+        }
+        _ => {
+            schedule.started_at = Some(now);
+            FetchClaim::Now
+        }
+    }
+}
+
+impl Handlers {
+    /// The schedule of a fetch that is coalesced and refreshed in the background
+    fn fetch_schedule(&self, command: &Command) -> Option<&Mutex<FetchSchedule>> {
+        match command {
+            Command::FetchOrders => Some(&self.orders_fetch),
+            Command::FetchPositions => Some(&self.positions_fetch),
+            _ => None,
+        }
+    }
+
+    /// Handle a command, reporting its error and how long it took
+    async fn execute(&self, command: Command) {
+        let started = Instant::now();
+        if let Err(e) = self.handle_command(command).await {
+            self.event_sender.send_error(
+                format!("Command handling error: {}", e),
+                Some("api_handler".to_string()),
+            );
+        }
+        self.report_duration(started);
+    }
+
+    /// Run a Kite call through its category's circuit breaker
+    async fn guarded<T>(
+        &self,
+        category: ApiCategory,
+        call: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        self.circuits.call(category, &self.event_sender, call).await
+    }
+
+    /// Report how long a command or refresh took, for the status bar and metrics
+    fn report_duration(&self, started: Instant) {
+        let _ = self
+            .event_sender
+            .send(AppEvent::ApiRequestTimed(started.elapsed()));
+    }
+
     /// Handle individual commands with comprehensive error handling
-    async fn handle_command(&mut self, command: Command) -> anyhow::Result<()> {
+    async fn handle_command(&self, command: Command) -> anyhow::Result<()> {
         match command {
             Command::FetchPositions => {
                 self.handle_fetch_positions().await?;
@@ -71,6 +505,14 @@ impl ApiHandler {
                 self.handle_fetch_orders().await?;
             }
 
+            Command::FetchTrades => {
+                self.handle_fetch_trades().await?;
+            }
+
+            Command::FetchOrderHistory { order_id } => {
+                self.handle_fetch_order_history(order_id).await?;
+            }
+
             Command::FetchUserProfile => {
                 self.handle_fetch_user_profile().await?;
             }
@@ -79,6 +521,31 @@ impl ApiHandler {
                 self.handle_fetch_instruments(exchange).await?;
             }
 
+            Command::FetchMargins => {
+                self.handle_fetch_margins().await?;
+            }
+
+            Command::PreviewOrderMargin {
+                preview_id,
+                details,
+            } => {
+                self.handle_preview_order_margin(preview_id, details)
+                    .await?;
+            }
+
+            Command::EstimateExitCharges {
+                instrument_token,
+                details,
+            } => {
+                self.handle_estimate_exit_charges(instrument_token, details)
+                    .await?;
+            }
+
+            Command::FetchHistoricalCandles { instrument_token } => {
+                self.handle_fetch_historical_candles(instrument_token)
+                    .await?;
+            }
+
             Command::PlaceOrder { details } => {
                 self.handle_place_order(details).await?;
             }
@@ -87,42 +554,112 @@ impl ApiHandler {
                 self.handle_modify_order(order_id, details).await?;
             }
 
-            Command::CancelOrder { order_id } => {
-                self.handle_cancel_order(order_id).await?;
+            Command::CancelOrder { order_id, variety } => {
+                self.handle_cancel_order(order_id, variety).await?;
+            }
+
+            Command::SquareOffByTag { tag } => {
+                self.handle_square_off_by_tag(tag).await?;
+            }
+
+            Command::ExchangeRequestToken { request_token } => {
+                self.handle_exchange_request_token(request_token).await?;
+            }
+
+            Command::AwaitLoginCallback { port, login_url } => {
+                self.start_login_callback(port, login_url);
+            }
+
+            Command::CancelLoginCallback => {
+                self.cancel_login_callback();
+            }
+
+            Command::UpdateAccessToken { access_token } => {
+                self.token_rejected.store(false, Ordering::Relaxed);
+                self.client
+                    .write()
+                    .await
+                    .set_access_token(access_token.clone());
+                self.config.write().zerodha.access_token = access_token;
+                *self.margin_cache.lock() = None;
+            }
+
+            Command::TestConnection {
+                api_key,
+                api_secret,
+                access_token,
+            } => {
+                self.handle_test_connection(api_key, api_secret, access_token)
+                    .await?;
             }
 
-            // WebSocket commands are handled by websocket_handler
+            // WebSocket commands are routed to websocket_handler and never arrive here
             Command::SubscribeToTicks { .. }
             | Command::UnsubscribeFromTicks { .. }
-            | Command::ReconnectWebSocket => {
-                // These are handled by websocket_handler, ignore here
+            | Command::SetTickMode { .. }
+            | Command::ReconnectWebSocket => {}
+
+            // Watchlist, alert and export commands are applied by AppState and never reach workers
+            Command::AddToWatchlist { .. }
+            | Command::RemoveFromWatchlist { .. }
+            | Command::ReorderWatchlist { .. }
+            | Command::CreateAlert { .. }
+            | Command::UpdateAlert { .. }
+            | Command::DeleteAlert { .. }
+            | Command::ExportPositions { .. }
+            | Command::ExportOrders { .. }
+            | Command::ExportTrades { .. } => {}
+
+            Command::SyncPaperBook => {
+                self.handle_fetch_orders().await?;
+                self.handle_fetch_positions().await?;
+                self.handle_fetch_trades().await?;
             }
 
             Command::Shutdown => {
-                // Already handled above
+                // Handled by the command loop
             }
         }
 
         Ok(())
     }
 
-    /// Fetch user profile information
-    async fn handle_fetch_user_profile(&mut self) -> anyhow::Result<()> {
-        // For personal trading, we can skip user profile fetching
-        self.event_sender.send_notification(
-            LogLevel::Info,
-            "User profile not needed for personal trading".to_string(),
-            Some("api_handler".to_string()),
-        );
+    /// Fetch the profile, doubling as a health check of the access token
+    async fn handle_fetch_user_profile(&self) -> anyhow::Result<()> {
+        let result = {
+            let client = self.client.read().await;
+            self.guarded(ApiCategory::Account, client.get_profile())
+                .await
+        };
+
+        match result {
+            Ok(profile) => {
+                self.token_rejected.store(false, Ordering::Relaxed);
+                self.event_sender
+                    .send(AppEvent::UserProfileUpdated(profile))?;
+            }
+            Err(e) if e.downcast_ref::<TokenRejected>().is_some() => {
+                self.token_rejected.store(true, Ordering::Relaxed);
+                self.event_sender.send(AppEvent::AccessTokenRejected)?;
+            }
+            Err(e) => {
+                self.event_sender.send_error(
+                    format!("Failed to fetch profile: {}", e),
+                    Some("api_handler".to_string()),
+                );
+            }
+        }
 
         Ok(())
     }
 
     /// Fetch positions with optimized error handling
-    async fn handle_fetch_positions(&mut self) -> anyhow::Result<()> {
-        let client = self.client.read().await;
-
-        match client.get_positions().await {
+    async fn handle_fetch_positions(&self) -> anyhow::Result<()> {
+        self.positions_fetch.lock().started_at = Some(Instant::now());
+        match self
+            .guarded(ApiCategory::Portfolio, self.broker.get_positions())
+            .await
+        {
             Ok(positions) => {
                 self.event_sender
                     .send(crate::state::AppEvent::PositionsUpdated(positions))?;
@@ -145,14 +682,38 @@ impl ApiHandler {
     }
 
     /// Fetch orders with optimized performance
-    async fn handle_fetch_orders(&mut self) -> anyhow::Result<()> {
-        let client = self.client.read().await;
+    async fn handle_fetch_orders(&self) -> anyhow::Result<()> {
+        let mut tracking = self.order_tracking.lock().await;
+        self.orders_fetch.lock().started_at = Some(Instant::now());
+        let result = self
+            .guarded(ApiCategory::Portfolio, self.broker.get_orders())
+            .await;
 
-        match client.get_orders().await {
+        match result {
             Ok(orders) => {
+                // The first refresh only seeds state so startup does not replay the day
+                let first_refresh = !tracking.orders_seen;
+                tracking.orders_seen = true;
+
+                for rejection in tracking.new_rejections(&orders, first_refresh) {
+                    self.event_sender.send(rejection)?;
+                }
+                let fills = tracking.detect_fills(&orders, first_refresh);
+
                 self.event_sender
                     .send(crate::state::AppEvent::OrdersUpdated(orders))?;
 
+                // Sent after the snapshot so the UI already knows the filled orders
+                let filled = !fills.is_empty();
+                for fill in fills {
+                    self.event_sender.send(fill)?;
+                }
+
+                // Fills change the cash available, so refresh funds right away
+                if filled {
+                    self.handle_fetch_margins().await?;
+                }
+
                 self.event_sender.send_notification(
                     LogLevel::Info,
                     "Orders fetched successfully".to_string(),
@@ -170,24 +731,128 @@ impl ApiHandler {
         Ok(())
     }
 
-    /// Fetch instruments for a specific exchange
-    async fn handle_fetch_instruments(&mut self, exchange: String) -> anyhow::Result<()> {
-        let client = self.client.read().await;
-
-        match client.get_instruments(&exchange).await {
-            Ok(instruments) => {
+    /// Fetch the day's tradebook
+    async fn handle_fetch_trades(&self) -> anyhow::Result<()> {
+        match self
+            .guarded(ApiCategory::Portfolio, self.broker.get_trades())
+            .await
+        {
+            Ok(trades) => {
                 self.event_sender
-                    .send(crate::state::AppEvent::InstrumentsUpdated(instruments))?;
+                    .send(crate::state::AppEvent::TradesUpdated(trades))?;
+            }
+            Err(e) => {
+                self.event_sender.send_error(
+                    format!("Failed to fetch trades: {}", e),
+                    Some("api_handler".to_string()),
+                );
+            }
+        }
 
-                self.event_sender.send_notification(
-                    LogLevel::Info,
-                    format!("Instruments fetched for exchange: {}", exchange),
+        Ok(())
+    }
+
+    /// Fetch an order's status transitions together with its fills
+    async fn handle_fetch_order_history(&self, order_id: String) -> anyhow::Result<()> {
+        let (states, trades) = tokio::join!(
+            self.guarded(
+                ApiCategory::Portfolio,
+                self.broker.get_order_history(&order_id)
+            ),
+            self.guarded(
+                ApiCategory::Portfolio,
+                self.broker.get_order_trades(&order_id)
+            )
+        );
+
+        match states.and_then(|states| Ok((states, trades?))) {
+            Ok((states, trades)) => {
+                self.event_sender
+                    .send(crate::state::AppEvent::OrderHistoryLoaded {
+                        order_id,
+                        history: OrderHistory { states, trades },
+                    })?;
+            }
+            Err(e) => {
+                self.event_sender.send_error(
+                    format!("Failed to fetch history for order {}: {}", order_id, e),
                     Some("api_handler".to_string()),
                 );
             }
+        }
+
+        Ok(())
+    }
+
+    /// Fetch instruments for a specific exchange
+    /// The download is large, so it runs in a task of its own on a copy of the client and
+    /// the command loop moves on
+    async fn handle_fetch_instruments(&self, exchange: String) -> anyhow::Result<()> {
+        let client = self.client.read().await.clone();
+        let event_sender = self.event_sender.clone();
+        let circuits = Arc::clone(&self.circuits);
+
+        tokio::spawn(async move {
+            match circuits
+                .call(
+                    ApiCategory::MarketData,
+                    &event_sender,
+                    client.get_instruments(&exchange),
+                )
+                .await
+            {
+                Ok(instruments) => {
+                    let _ = event_sender.send(AppEvent::InstrumentsUpdated {
+                        exchange: exchange.clone(),
+                        instruments,
+                    });
+
+                    event_sender.send_notification(
+                        LogLevel::Info,
+                        format!("Instruments fetched for exchange: {}", exchange),
+                        Some("api_handler".to_string()),
+                    );
+                }
+                Err(e) => {
+                    event_sender.send_error(
+                        format!("Failed to fetch instruments for {}: {}", exchange, e),
+                        Some("api_handler".to_string()),
+                    );
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Fetch today's one-minute candles so far for the chart
+    async fn handle_fetch_historical_candles(&self, instrument_token: u32) -> anyhow::Result<()> {
+        let client = self.client.read().await;
+
+        let today = ist_now().date_naive();
+        let from = format!("{} 09:00:00", today);
+        let to = ist_now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        match self
+            .guarded(
+                ApiCategory::MarketData,
+                client.get_historical_candles(instrument_token, &from, &to),
+            )
+            .await
+        {
+            Ok(candles) => {
+                self.event_sender
+                    .send(crate::state::AppEvent::HistoricalCandlesLoaded {
+                        instrument_token,
+                        candles,
+                    })?;
+            }
             Err(e) => {
                 self.event_sender.send_error(
-                    format!("Failed to fetch instruments for {}: {}", exchange, e),
+                    format!(
+                        "Failed to fetch historical candles for {}: {}",
+                        instrument_token, e
+                    ),
                     Some("api_handler".to_string()),
                 );
             }
@@ -197,23 +862,52 @@ impl ApiHandler {
     }
 
     /// Place a new order with validation
-    async fn handle_place_order(&mut self, order_request: OrderRequest) -> anyhow::Result<()> {
+    async fn handle_place_order(&self, order_request: OrderRequest) -> anyhow::Result<()> {
         self.event_sender.send_notification(
             LogLevel::Info,
             format!("Placing order for {}", order_request.tradingsymbol),
             Some("api_handler".to_string()),
         );
 
-        let order_id = {
-            let client = self.client.read().await;
-            client.place_order(&order_request).await
+        // Paper fills never block the account's margin, so there is nothing to check
+        let margin_precheck = {
+            let config = self.config.read();
+            config.app.margin_precheck && config.app.mode != TradingMode::Paper
         };
+        if margin_precheck && !order_request.bypass_margin_check {
+            match self.check_margin(&order_request).await {
+                Ok(Some(message)) => {
+                    self.event_sender
+                        .send(crate::state::AppEvent::OrderRejected {
+                            order_id: None,
+                            tradingsymbol: order_request.tradingsymbol.clone(),
+                            reason: message,
+                            request_id: order_request.request_id,
+                        })?;
+                    return Ok(());
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    // A failed preview should not block trading; the exchange still checks margin
+                    self.event_sender.send_notification(
+                        LogLevel::Warning,
+                        format!("Margin pre-check skipped: {}", e),
+                        Some("api_handler".to_string()),
+                    );
+                }
+            }
+        }
+
+        let order_id = self
+            .guarded(ApiCategory::Orders, self.broker.place_order(&order_request))
+            .await;
 
         match order_id {
             Ok(order_id) => {
                 self.event_sender
                     .send(crate::state::AppEvent::OrderPlaced {
                         order_id: order_id.clone(),
+                        request_id: order_request.request_id,
                     })?;
 
                 self.event_sender.send_notification(
@@ -230,9 +924,123 @@ impl ApiHandler {
                     );
                 }
             }
+            Err(e) if is_transport_failure(&e) => {
+                self.event_sender
+                    .send(crate::state::AppEvent::OrderPlaceFailed {
+                        tradingsymbol: order_request.tradingsymbol.clone(),
+                        error: format!("{:#}", e),
+                        request_id: order_request.request_id,
+                    })?;
+
+                // The request may have reached Kite before the connection failed
+                if let Err(e) = self.handle_fetch_orders().await {
+                    self.event_sender.send_error(
+                        format!("Failed to refresh orders after a failed placement: {}", e),
+                        Some("api_handler".to_string()),
+                    );
+                }
+            }
+            Err(e) => {
+                self.event_sender
+                    .send(crate::state::AppEvent::OrderRejected {
+                        order_id: None,
+                        tradingsymbol: order_request.tradingsymbol.clone(),
+                        reason: e.to_string(),
+                        request_id: order_request.request_id,
+                    })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compare required margin against the available cash in the order's segment
+    /// Returns a description of the shortfall when the order cannot be funded
+    async fn check_margin(&self, order_request: &OrderRequest) -> anyhow::Result<Option<String>> {
+        let margins = self.cached_margins().await?;
+        let required = {
+            let client = self.client.read().await;
+            self.guarded(ApiCategory::Margins, client.get_order_margin(order_request))
+                .await?
+                .total
+        };
+        let available = margins.for_exchange(&order_request.exchange).available.cash;
+
+        if required > available {
+            return Ok(Some(format!(
+                "insufficient margin: required ₹{:.2}, available ₹{:.2}, shortfall ₹{:.2}",
+                required,
+                available,
+                required - available
+            )));
+        }
+
+        Ok(None)
+    }
+
+    /// Preview margin and charges for the order dialog; failures are reported to the dialog only
+    async fn handle_preview_order_margin(
+        &self,
+        preview_id: u64,
+        details: OrderRequest,
+    ) -> anyhow::Result<()> {
+        let result = {
+            let client = self.client.read().await;
+            self.guarded(ApiCategory::Margins, client.get_order_margin(&details))
+                .await
+        };
+
+        self.event_sender
+            .send(crate::state::AppEvent::OrderMarginPreviewed {
+                preview_id,
+                result: result.map_err(|e| e.to_string()),
+            })?;
+
+        Ok(())
+    }
+
+    /// Estimate the charges of a closing order for the chart's breakeven line
+    async fn handle_estimate_exit_charges(
+        &self,
+        instrument_token: u32,
+        details: OrderRequest,
+    ) -> anyhow::Result<()> {
+        let quantity = details.quantity;
+        let result = {
+            let client = self.client.read().await;
+            self.guarded(ApiCategory::Margins, client.get_order_margin(&details))
+                .await
+        };
+
+        self.event_sender
+            .send(crate::state::AppEvent::ExitChargesEstimated {
+                instrument_token,
+                quantity,
+                result: result
+                    .map(|margin| margin.charges.total)
+                    .map_err(|e| e.to_string()),
+            })?;
+
+        Ok(())
+    }
+
+    /// Fetch a fresh margins snapshot for the funds panel, refreshing the pre-check cache too
+    async fn handle_fetch_margins(&self) -> anyhow::Result<()> {
+        let result = {
+            let client = self.client.read().await;
+            self.guarded(ApiCategory::Margins, client.get_margins())
+                .await
+        };
+
+        match result {
+            Ok(margins) => {
+                *self.margin_cache.lock() = Some((Instant::now(), margins.clone()));
+                self.event_sender
+                    .send(crate::state::AppEvent::MarginsUpdated(margins))?;
+            }
             Err(e) => {
                 self.event_sender.send_error(
-                    format!("Failed to place order: {}", e),
+                    format!("Failed to fetch margins: {}", e),
                     Some("api_handler".to_string()),
                 );
             }
@@ -241,50 +1049,256 @@ impl ApiHandler {
         Ok(())
     }
 
-    /// Modify an existing order
-    async fn handle_modify_order(
-        &mut self,
-        order_id: String,
-        _order_request: OrderRequest,
+    /// Trade a request_token from the Kite login redirect for a new access token
+    async fn handle_exchange_request_token(&self, request_token: String) -> anyhow::Result<()> {
+        let result = {
+            let mut client = self.client.write().await;
+            let checksum = client.generate_checksum(&request_token);
+            self.guarded(
+                ApiCategory::Account,
+                client.generate_session(&request_token, &checksum),
+            )
+            .await
+        };
+
+        let result = result
+            .map(|session| session.access_token)
+            .map_err(|e| e.to_string());
+        self.event_sender.send(AppEvent::SessionGenerated(result))?;
+
+        Ok(())
+    }
+
+    /// Listen for the login redirect in a task of its own, so commands keep flowing while
+    /// the user logs in; the login page is opened once the port is held
+    fn start_login_callback(&self, port: u16, login_url: String) {
+        self.cancel_login_callback();
+
+        let event_sender = self.event_sender.clone();
+        *self.login_callback.lock() = Some(tokio::spawn(async move {
+            let result = match CallbackListener::bind(port).await {
+                Ok(listener) => {
+                    auth_callback::open_in_browser(&login_url);
+                    listener
+                        .wait_for_request_token(auth_callback::LOGIN_TIMEOUT)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            let _ = event_sender.send(AppEvent::LoginCallback(result.map_err(|e| e.to_string())));
+        }));
+    }
+
+    /// Stop a running login listener, releasing its port
+    fn cancel_login_callback(&self) {
+        if let Some(task) = self.login_callback.lock().take() {
+            task.abort();
+        }
+    }
+
+    /// Fetch margins with a throwaway client, so untested credentials never replace the live ones
+    async fn handle_test_connection(
+        &self,
+        api_key: String,
+        api_secret: String,
+        access_token: String,
     ) -> anyhow::Result<()> {
+        let mut client = ZerodhaClient::new(api_key, api_secret);
+        client.set_access_token(access_token);
+
+        let result = client
+            .get_margins()
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string());
+        self.event_sender.send(AppEvent::ConnectionTested(result))?;
+
+        Ok(())
+    }
+
+    /// Margins snapshot, refetched only when the cached copy is stale
+    async fn cached_margins(&self) -> anyhow::Result<Margins> {
+        if let Some((fetched_at, margins)) = &*self.margin_cache.lock() {
+            if fetched_at.elapsed() < MARGIN_CACHE_TTL {
+                return Ok(margins.clone());
+            }
+        }
+
+        let margins = {
+            let client = self.client.read().await;
+            self.guarded(ApiCategory::Margins, client.get_margins())
+                .await?
+        };
+        *self.margin_cache.lock() = Some((Instant::now(), margins.clone()));
+
+        Ok(margins)
+    }
+
+    /// Flatten every position built by orders carrying the given tag
+    /// Quantities are capped at the live position so manual exits are never reversed
+    async fn handle_square_off_by_tag(&self, tag: String) -> anyhow::Result<()> {
         self.event_sender.send_notification(
             LogLevel::Info,
-            format!("Modifying order: {}", order_id),
+            format!("Squaring off positions for tag: {}", tag),
             Some("api_handler".to_string()),
         );
 
-        // Note: In a real implementation, you would call client.modify_order()
-        // This is a placeholder as the modify_order method would need to be implemented
+        let (orders, positions) = (
+            self.guarded(ApiCategory::Portfolio, self.broker.get_orders())
+                .await?,
+            self.guarded(ApiCategory::Portfolio, self.broker.get_positions())
+                .await?,
+        );
+
+        let tag_positions = aggregate_tag_positions(&orders)
+            .remove(&tag)
+            .unwrap_or_default();
+
+        let mut orders_placed = 0;
+        let mut failures = Vec::new();
+
+        for tag_position in tag_positions {
+            let live_quantity = positions
+                .iter()
+                .find(|position| {
+                    position.instrument_token == tag_position.instrument_token
+                        && position.product == tag_position.product
+                })
+                .map(|position| position.quantity)
+                .unwrap_or(0);
+
+            // Only close what is still open in the same direction
+            let quantity = if live_quantity.signum() == tag_position.net_quantity.signum() {
+                live_quantity.abs().min(tag_position.net_quantity.abs())
+            } else {
+                0
+            };
+            if quantity == 0 {
+                continue;
+            }
+
+            let order_request = OrderRequest {
+                tradingsymbol: tag_position.tradingsymbol.clone(),
+                exchange: tag_position.exchange.clone(),
+                transaction_type: if tag_position.net_quantity > 0 {
+                    TransactionType::Sell
+                } else {
+                    TransactionType::Buy
+                }
+                .as_api_str()
+                .to_string(),
+                order_type: OrderType::Market.as_api_str().to_string(),
+                quantity,
+                price: None,
+                product: tag_position.product.clone(),
+                validity: "DAY".to_string(),
+                validity_ttl: None,
+                disclosed_quantity: None,
+                trigger_price: None,
+                squareoff: None,
+                stoploss: None,
+                trailing_stoploss: None,
+                tag: Some(tag.clone()),
+                variety: "regular".to_string(),
+                // Exits free margin, so never block them on a pre-check
+                bypass_margin_check: true,
+                request_id: None,
+            };
+
+            let result = self
+                .guarded(ApiCategory::Orders, self.broker.place_order(&order_request))
+                .await;
+
+            match result {
+                Ok(order_id) => {
+                    orders_placed += 1;
+                    self.event_sender
+                        .send(crate::state::AppEvent::OrderPlaced {
+                            order_id,
+                            request_id: None,
+                        })?;
+                }
+                Err(e) => {
+                    failures.push(format!("{}: {}", tag_position.tradingsymbol, e));
+                }
+            }
+        }
+
         self.event_sender
-            .send(crate::state::AppEvent::OrderModified {
-                order_id: order_id.clone(),
+            .send(crate::state::AppEvent::TagSquaredOff {
+                tag,
+                orders_placed,
+                failures,
             })?;
 
+        if orders_placed > 0 {
+            self.handle_fetch_orders().await?;
+            self.handle_fetch_positions().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Modify an existing order
+    async fn handle_modify_order(
+        &self,
+        order_id: String,
+        order_request: OrderRequest,
+    ) -> anyhow::Result<()> {
         self.event_sender.send_notification(
             LogLevel::Info,
-            format!("Order modified: {}", order_id),
+            format!("Modifying order: {}", order_id),
             Some("api_handler".to_string()),
         );
 
-        // Refresh orders after modification
+        let modify_result = self
+            .guarded(
+                ApiCategory::Orders,
+                self.broker.modify_order(&order_id, &order_request),
+            )
+            .await;
+
+        match modify_result {
+            Ok(modified_order_id) => {
+                self.event_sender
+                    .send(crate::state::AppEvent::OrderModified {
+                        order_id: modified_order_id.clone(),
+                    })?;
+
+                self.event_sender.send_notification(
+                    LogLevel::Info,
+                    format!("Order modified: {}", modified_order_id),
+                    Some("api_handler".to_string()),
+                );
+            }
+            Err(e) => {
+                self.event_sender.send_critical_error(
+                    format!("Failed to modify order {}: {}", order_id, e),
+                    Some("api_handler".to_string()),
+                );
+            }
+        }
+
+        // Refresh orders so the table shows the broker's view either way
         self.handle_fetch_orders().await?;
 
         Ok(())
     }
 
     /// Cancel an existing order
-    async fn handle_cancel_order(&mut self, order_id: String) -> anyhow::Result<()> {
+    async fn handle_cancel_order(&self, order_id: String, variety: String) -> anyhow::Result<()> {
         self.event_sender.send_notification(
             LogLevel::Info,
             format!("Cancelling order: {}", order_id),
             Some("api_handler".to_string()),
         );
 
-        let cancel_result = {
-            let client = self.client.read().await;
-            // Default to "regular" variety - in real implementation, track order varieties
-            client.cancel_order(&order_id, "regular").await
-        };
+        let cancel_result = self
+            .guarded(
+                ApiCategory::Orders,
+                self.broker.cancel_order(&order_id, &variety),
+            )
+            .await;
 
         match cancel_result {
             Ok(cancelled_order_id) => {
@@ -308,7 +1322,7 @@ impl ApiHandler {
                 }
             }
             Err(e) => {
-                self.event_sender.send_error(
+                self.event_sender.send_critical_error(
                     format!("Failed to cancel order {}: {}", order_id, e),
                     Some("api_handler".to_string()),
                 );
@@ -318,3 +1332,455 @@ impl ApiHandler {
         Ok(())
     }
 }
+
+/// Whether a call failed without an answer from the broker, rather than being refused
+fn is_transport_failure(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| cause.is::<reqwest::Error>() || cause.is::<CircuitOpen>())
+}
+
+/// The next command, preferring `priority` whenever both channels have one waiting
+/// Gives way to the background refresh at `deadline`
+async fn next_command(
+    priority: &mut mpsc::UnboundedReceiver<Command>,
+    commands: &mut mpsc::UnboundedReceiver<Command>,
+    deadline: Option<Instant>,
+) -> Result<Command, Idle> {
+    let refresh_due = async {
+        match deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        biased;
+        command = priority.recv() => command.ok_or(Idle::Closed),
+        command = commands.recv() => command.ok_or(Idle::Closed),
+        () = refresh_due => Err(Idle::RefreshDue),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::BoxFuture;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Calls of one kind in flight, and the most there have been at once
+    #[derive(Default)]
+    struct Gauge {
+        current: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl Gauge {
+        fn enter(&self) {
+            let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(current, Ordering::SeqCst);
+        }
+
+        fn exit(&self) {
+            self.current.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        fn peak(&self) -> usize {
+            self.peak.load(Ordering::SeqCst)
+        }
+    }
+
+    /// Broker that answers every call after `delay`, logging each call as it starts
+    struct MockBroker {
+        delay: Duration,
+        started: Mutex<Vec<String>>,
+        // Order placements, modifications and cancellations
+        mutations: Gauge,
+        trades: Gauge,
+    }
+
+    impl MockBroker {
+        fn new(delay: Duration) -> Arc<Self> {
+            Arc::new(Self {
+                delay,
+                started: Mutex::new(Vec::new()),
+                mutations: Gauge::default(),
+                trades: Gauge::default(),
+            })
+        }
+
+        async fn call<T>(
+            &self,
+            name: String,
+            gauge: Option<&Gauge>,
+            value: T,
+        ) -> anyhow::Result<T> {
+            self.started.lock().push(name);
+            gauge.inspect(|gauge| gauge.enter());
+            tokio::time::sleep(self.delay).await;
+            gauge.inspect(|gauge| gauge.exit());
+            Ok(value)
+        }
+
+        fn started(&self) -> Vec<String> {
+            self.started.lock().clone()
+        }
+    }
+
+    impl BrokerClient for MockBroker {
+        fn get_positions(&self) -> BoxFuture<'_, anyhow::Result<Vec<Position>>> {
+            Box::pin(self.call("get_positions".to_string(), None, Vec::new()))
+        }
+
+        fn get_orders(&self) -> BoxFuture<'_, anyhow::Result<Vec<Order>>> {
+            Box::pin(self.call("get_orders".to_string(), None, Vec::new()))
+        }
+
+        fn get_trades(&self) -> BoxFuture<'_, anyhow::Result<Vec<Trade>>> {
+            Box::pin(self.call("get_trades".to_string(), Some(&self.trades), Vec::new()))
+        }
+
+        fn get_order_history<'a>(
+            &'a self,
+            order_id: &'a str,
+        ) -> BoxFuture<'a, anyhow::Result<Vec<Order>>> {
+            Box::pin(self.call(format!("get_order_history {}", order_id), None, Vec::new()))
+        }
+
+        fn get_order_trades<'a>(
+            &'a self,
+            order_id: &'a str,
+        ) -> BoxFuture<'a, anyhow::Result<Vec<Trade>>> {
+            Box::pin(self.call(format!("get_order_trades {}", order_id), None, Vec::new()))
+        }
+
+        fn place_order<'a>(
+            &'a self,
+            order_request: &'a OrderRequest,
+        ) -> BoxFuture<'a, anyhow::Result<String>> {
+            let name = format!("place_order {}", order_request.tradingsymbol);
+            let order_id = format!("ORDER-{}", order_request.tradingsymbol);
+            Box::pin(self.call(name, Some(&self.mutations), order_id))
+        }
+
+        fn modify_order<'a>(
+            &'a self,
+            order_id: &'a str,
+            _order_request: &'a OrderRequest,
+        ) -> BoxFuture<'a, anyhow::Result<String>> {
+            let name = format!("modify_order {}", order_id);
+            Box::pin(self.call(name, Some(&self.mutations), order_id.to_string()))
+        }
+
+        fn cancel_order<'a>(
+            &'a self,
+            order_id: &'a str,
+            variety: &'a str,
+        ) -> BoxFuture<'a, anyhow::Result<String>> {
+            let name = format!("cancel_order {} {}", order_id, variety);
+            Box::pin(self.call(name, Some(&self.mutations), order_id.to_string()))
+        }
+    }
+
+    /// Run a handler over `broker` through the commands queued on each channel, and the
+    /// Shutdown queued after them
+    async fn run_handler(broker: Arc<MockBroker>, priority: Vec<Command>, commands: Vec<Command>) {
+        run_handler_with(Config::for_tests(), broker, priority, commands).await;
+    }
+
+    /// `run_handler` under `config`, returning the events the handler sent
+    async fn run_handler_with(
+        config: Config,
+        broker: Arc<MockBroker>,
+        priority: Vec<Command>,
+        commands: Vec<Command>,
+    ) -> Vec<AppEvent> {
+        let (events, event_receiver) = crossbeam_channel::unbounded();
+        let (_config_sender, config_updates) = watch::channel(config.clone());
+        let mut handler = ApiHandler::new(
+            config,
+            EventSender::new(events),
+            config_updates,
+            Some(broker),
+        );
+
+        let (priority_sender, priority_receiver) = mpsc::unbounded_channel();
+        let (command_sender, command_receiver) = mpsc::unbounded_channel();
+        for command in priority {
+            priority_sender.send(command).unwrap();
+        }
+        for command in commands.into_iter().chain([Command::Shutdown]) {
+            command_sender.send(command).unwrap();
+        }
+        handler
+            .run(
+                Arc::new(tokio::sync::Mutex::new(priority_receiver)),
+                Arc::new(tokio::sync::Mutex::new(command_receiver)),
+            )
+            .await;
+        event_receiver.try_iter().collect()
+    }
+
+    #[tokio::test]
+    async fn next_command_takes_priority_commands_first() {
+        let (priority_sender, mut priority) = mpsc::unbounded_channel();
+        let (sender, mut commands) = mpsc::unbounded_channel();
+        sender.send(Command::FetchPositions).unwrap();
+        priority_sender
+            .send(Command::CancelOrder {
+                order_id: "ORD1".to_string(),
+                variety: "regular".to_string(),
+            })
+            .unwrap();
+
+        let first = next_command(&mut priority, &mut commands, None).await;
+        assert!(matches!(first, Ok(Command::CancelOrder { .. })));
+        let second = next_command(&mut priority, &mut commands, None).await;
+        assert!(matches!(second, Ok(Command::FetchPositions)));
+    }
+
+    #[tokio::test]
+    async fn cancel_reaches_the_broker_before_a_slow_fetch_queued_ahead_of_it() {
+        let broker = MockBroker::new(Duration::from_millis(50));
+        run_handler(
+            Arc::clone(&broker),
+            vec![Command::CancelOrder {
+                order_id: "ORD1".to_string(),
+                variety: "amo".to_string(),
+            }],
+            vec![Command::FetchPositions],
+        )
+        .await;
+
+        // Cancelled with the order's own variety
+        let started = broker.started();
+        assert_eq!(started[0], "cancel_order ORD1 amo");
+        assert!(started.contains(&"get_positions".to_string()));
+    }
+
+    #[tokio::test]
+    async fn paper_orders_skip_the_margin_precheck() {
+        let mut config = Config::for_tests();
+        config.app.mode = TradingMode::Paper;
+        config.app.margin_precheck = true;
+        let broker = MockBroker::new(Duration::ZERO);
+        let events = run_handler_with(
+            config,
+            Arc::clone(&broker),
+            Vec::new(),
+            vec![Command::PlaceOrder {
+                details: OrderRequest::market("INFY", "BUY", 1),
+            }],
+        )
+        .await;
+
+        assert_eq!(broker.started()[0], "place_order INFY");
+        assert!(!events.iter().any(|event| matches!(
+            event,
+            AppEvent::Notification { message, .. } if message.starts_with("Margin pre-check")
+        )));
+    }
+
+    #[test]
+    fn only_unanswered_calls_count_as_transport_failures() {
+        let refused = anyhow::anyhow!("Order placement error: Insufficient funds");
+        assert!(!is_transport_failure(&refused));
+
+        let circuit_open = anyhow::Error::new(CircuitOpen {
+            category: ApiCategory::Orders,
+            retry_at: chrono::Utc::now(),
+        })
+        .context("Failed to place order");
+        assert!(is_transport_failure(&circuit_open));
+    }
+
+    #[test]
+    fn claim_fetch_runs_the_first_request_now() {
+        let schedule = Mutex::new(FetchSchedule::default());
+        assert!(matches!(claim_fetch(&schedule), FetchClaim::Now));
+        assert!(schedule.lock().started_at.is_some());
+    }
+
+    #[test]
+    fn claim_fetch_queues_one_trailing_fetch_inside_the_window() {
+        let started_at = Instant::now();
+        let schedule = Mutex::new(FetchSchedule {
+            started_at: Some(started_at),
+            trailing: false,
+        });
+        match claim_fetch(&schedule) {
+            FetchClaim::After(run_at) => assert_eq!(run_at, started_at + FETCH_COALESCE_WINDOW),
+            _ => panic!("expected a trailing fetch"),
+        }
+        assert!(matches!(claim_fetch(&schedule), FetchClaim::Queued));
+        assert_eq!(schedule.lock().started_at, Some(started_at));
+    }
+
+    #[test]
+    fn claim_fetch_runs_now_once_the_window_has_passed() {
+        let schedule = Mutex::new(FetchSchedule {
+            started_at: Some(Instant::now() - FETCH_COALESCE_WINDOW),
+            trailing: false,
+        });
+        assert!(matches!(claim_fetch(&schedule), FetchClaim::Now));
+    }
+
+    #[tokio::test]
+    async fn next_command_wakes_for_the_refresh_deadline() {
+        let (_priority_sender, mut priority) = mpsc::unbounded_channel();
+        let (_sender, mut commands) = mpsc::unbounded_channel();
+        let deadline = Instant::now() + Duration::from_millis(20);
+        let next = next_command(&mut priority, &mut commands, Some(deadline)).await;
+        assert!(matches!(next, Err(Idle::RefreshDue)));
+        assert!(Instant::now() >= deadline);
+    }
+
+    #[tokio::test]
+    async fn next_command_reports_closed_channels() {
+        let (priority_sender, mut priority) = mpsc::unbounded_channel::<Command>();
+        let (sender, mut commands) = mpsc::unbounded_channel();
+        drop((priority_sender, sender));
+        let next = next_command(&mut priority, &mut commands, None).await;
+        assert!(matches!(next, Err(Idle::Closed)));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn mixed_load_runs_one_order_mutation_at_a_time_in_send_order() {
+        let broker = MockBroker::new(Duration::from_millis(10));
+        let mut mutations = Vec::new();
+        let mut expected = Vec::new();
+        let mut reads = Vec::new();
+        for i in 0..8 {
+            let symbol = format!("SYM{}", i);
+            let order_id = format!("ORDER-{}", symbol);
+            let details = OrderRequest::market(&symbol, "BUY", 1);
+            mutations.push(Command::PlaceOrder {
+                details: details.clone(),
+            });
+            mutations.push(Command::ModifyOrder {
+                order_id: order_id.clone(),
+                details,
+            });
+            mutations.push(Command::CancelOrder {
+                order_id: order_id.clone(),
+                variety: "regular".to_string(),
+            });
+            expected.extend([
+                format!("place_order {}", symbol),
+                format!("modify_order {}", order_id),
+                format!("cancel_order {} regular", order_id),
+            ]);
+            reads.extend([
+                Command::FetchTrades,
+                Command::FetchOrderHistory { order_id },
+                Command::FetchTrades,
+            ]);
+        }
+        run_handler(Arc::clone(&broker), mutations, reads).await;
+
+        assert_eq!(broker.mutations.peak(), 1);
+        let started: Vec<String> = broker
+            .started()
+            .into_iter()
+            .filter(|call| {
+                ["place_order", "modify_order", "cancel_order"]
+                    .iter()
+                    .any(|kind| call.starts_with(kind))
+            })
+            .collect();
+        assert_eq!(started, expected);
+
+        // Reads still overlap each other, within their limit
+        let trades = broker.trades.peak();
+        assert!(trades > 1, "fetches ran one at a time");
+        assert!(trades <= MAX_CONCURRENT_READS, "{} fetches at once", trades);
+        let fetched = broker
+            .started()
+            .iter()
+            .filter(|call| *call == "get_trades")
+            .count();
+        assert_eq!(fetched, 16);
+    }
+
+    fn filled(order_id: &str, status: OrderStatus, quantity: i32, average_price: f64) -> Order {
+        let mut order = Order::limit(order_id, status);
+        order.filled_quantity = quantity;
+        order.pending_quantity = order.quantity - quantity;
+        order.average_price = average_price;
+        order
+    }
+
+    /// (order_id, price, quantity) of each OrderFilled
+    fn fills(events: Vec<AppEvent>) -> Vec<(String, f64, i32)> {
+        events
+            .into_iter()
+            .map(|event| match event {
+                AppEvent::OrderFilled {
+                    order_id,
+                    fill_price,
+                    fill_quantity,
+                } => (order_id, fill_price, fill_quantity),
+                other => panic!("expected OrderFilled, got {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_snapshot_seeds_fills_without_reporting_them() {
+        let mut tracking = OrderTracking::default();
+        let day = [filled("ORD1", OrderStatus::Complete, 10, 100.0)];
+        assert!(tracking.detect_fills(&day, true).is_empty());
+        assert!(tracking.detect_fills(&day, false).is_empty());
+    }
+
+    #[test]
+    fn partial_fills_report_the_delta_at_its_own_price() {
+        let mut tracking = OrderTracking::default();
+        tracking.detect_fills(&[Order::limit("ORD1", OrderStatus::Open)], true);
+
+        let partial = [filled("ORD1", OrderStatus::Open, 4, 100.0)];
+        assert_eq!(
+            fills(tracking.detect_fills(&partial, false)),
+            [("ORD1".to_string(), 100.0, 4)]
+        );
+
+        // 4 at 100 then 6 more for a cumulative average of 101.2: the 6 went at 102
+        let complete = [filled("ORD1", OrderStatus::Complete, 10, 101.2)];
+        let reported = fills(tracking.detect_fills(&complete, false));
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].2, 6);
+        assert!((reported[0].1 - 102.0).abs() < 1e-9);
+
+        assert!(tracking.detect_fills(&complete, false).is_empty());
+    }
+
+    #[test]
+    fn complete_order_counts_as_filled_before_its_quantity_catches_up() {
+        let mut tracking = OrderTracking::default();
+        tracking.detect_fills(&[], true);
+
+        let lagging = [filled("ORD1", OrderStatus::Complete, 0, 99.5)];
+        assert_eq!(
+            fills(tracking.detect_fills(&lagging, false)),
+            [("ORD1".to_string(), 99.5, 10)]
+        );
+    }
+
+    #[test]
+    fn rejections_are_reported_once_and_not_replayed_at_startup() {
+        let mut tracking = OrderTracking::default();
+        let mut earlier = Order::limit("ORD1", OrderStatus::Rejected);
+        earlier.status_message = Some("Insufficient funds".to_string());
+        assert!(tracking
+            .new_rejections(std::slice::from_ref(&earlier), true)
+            .is_empty());
+
+        let orders = [earlier, Order::limit("ORD2", OrderStatus::Rejected)];
+        let rejections = tracking.new_rejections(&orders, false);
+        assert!(matches!(
+            rejections.as_slice(),
+            [AppEvent::OrderRejected { order_id: Some(id), reason, .. }]
+                if id == "ORD2" && reason == "No reason given"
+        ));
+        assert!(tracking.new_rejections(&orders, false).is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+use crate::api::TokenRejected;
+use crate::state::{AppEvent, EventSender};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Consecutive outage failures of one category before its circuit opens
+const FAILURE_THRESHOLD: u32 = 5;
+
+/// How long an open circuit fails calls fast before letting a probe through
+const COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Kinds of Kite request, each with a circuit of its own so an outage of one part of the
+/// API doesn't stop the rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ApiCategory {
+    Orders,
+    Portfolio,
+    Margins,
+    MarketData,
+    Account,
+}
+
+impl ApiCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            ApiCategory::Orders => "orders",
+            ApiCategory::Portfolio => "portfolio",
+            ApiCategory::Margins => "margins",
+            ApiCategory::MarketData => "market data",
+            ApiCategory::Account => "account",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    /// Calls fail fast until `retry_at`
+    Open {
+        retry_at: DateTime<Utc>,
+    },
+    /// One probe call is let through; its outcome closes or reopens the circuit
+    HalfOpen,
+}
+
+/// Error a call fails fast with while its circuit is open
+#[derive(Debug)]
+pub struct CircuitOpen {
+    pub category: ApiCategory,
+    pub retry_at: DateTime<Utc>,
+}
+
+impl std::fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} circuit open, retrying at {}",
+            self.category.label(),
+            self.retry_at
+                .with_timezone(&chrono::Local)
+                .format("%H:%M:%S")
+        )
+    }
+}
+
+impl std::error::Error for CircuitOpen {}
+
+struct Circuit {
+    state: CircuitState,
+    failures: u32,
+    open_until: Instant,
+    // When the half-open probe went out; a probe that never reports back is replaced
+    // after a cooldown
+    probe_started: Option<Instant>,
+}
+
+impl Default for Circuit {
+    fn default() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            failures: 0,
+            open_until: Instant::now(),
+            probe_started: None,
+        }
+    }
+}
+
+/// Stops calling a part of the Kite API that keeps failing, so an outage costs one error
+/// per cooldown instead of one per request
+/// Only transport failures count: timeouts, refused connections and unreadable answers
+/// such as a gateway's HTML error page; an error the API itself answers with, like a
+/// margin rejection, shows the API is up
+#[derive(Default)]
+pub struct CircuitBreaker {
+    circuits: Mutex<HashMap<ApiCategory, Circuit>>,
+}
+
+impl CircuitBreaker {
+    /// Run `call` unless the category's circuit is open, reporting state changes as
+    /// ApiCircuitChanged
+    pub async fn call<T>(
+        &self,
+        category: ApiCategory,
+        event_sender: &EventSender,
+        call: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        self.admit(category, event_sender)?;
+        let result = call.await;
+        let outage = result.as_ref().err().is_some_and(is_outage);
+        self.record(category, outage, event_sender);
+        result
+    }
+
+    /// Whether calls of the category currently fail fast
+    pub fn is_open(&self, category: ApiCategory) -> bool {
+        self.circuits
+            .lock()
+            .get(&category)
+            .is_some_and(|circuit| matches!(circuit.state, CircuitState::Open { .. }))
+    }
+
+    fn admit(&self, category: ApiCategory, event_sender: &EventSender) -> Result<(), CircuitOpen> {
+        let mut circuits = self.circuits.lock();
+        let circuit = circuits.entry(category).or_default();
+        match circuit.state {
+            CircuitState::Closed => Ok(()),
+            CircuitState::Open { retry_at } => {
+                if Instant::now() < circuit.open_until {
+                    return Err(CircuitOpen { category, retry_at });
+                }
+                circuit.state = CircuitState::HalfOpen;
+                circuit.probe_started = Some(Instant::now());
+                let _ = event_sender.send(AppEvent::ApiCircuitChanged {
+                    category,
+                    state: CircuitState::HalfOpen,
+                });
+                Ok(())
+            }
+            CircuitState::HalfOpen => {
+                if circuit
+                    .probe_started
+                    .is_some_and(|started| started.elapsed() < COOLDOWN)
+                {
+                    return Err(CircuitOpen {
+                        category,
+                        retry_at: Utc::now(),
+                    });
+                }
+                circuit.probe_started = Some(Instant::now());
+                Ok(())
+            }
+        }
+    }
+
+    fn record(&self, category: ApiCategory, outage: bool, event_sender: &EventSender) {
+        let mut circuits = self.circuits.lock();
+        let circuit = circuits.entry(category).or_default();
+        let previous = circuit.state;
+        if outage {
+            circuit.failures += 1;
+            let trips = match circuit.state {
+                CircuitState::Closed => circuit.failures >= FAILURE_THRESHOLD,
+                CircuitState::HalfOpen => true,
+                // A call admitted before the circuit opened
+                CircuitState::Open { .. } => false,
+            };
+            if trips {
+                circuit.open_until = Instant::now() + COOLDOWN;
+                circuit.state = CircuitState::Open {
+                    retry_at: Utc::now() + COOLDOWN,
+                };
+                circuit.probe_started = None;
+            }
+        } else {
+            circuit.failures = 0;
+            circuit.state = CircuitState::Closed;
+            circuit.probe_started = None;
+        }
+        if circuit.state != previous {
+            let _ = event_sender.send(AppEvent::ApiCircuitChanged {
+                category,
+                state: circuit.state,
+            });
+        }
+    }
+}
+
+/// Whether an error means the API could not be reached or answered, rather than it
+/// refusing the request
+fn is_outage(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<TokenRejected>().is_none()
+        && error
+            .chain()
+            .any(|cause| cause.downcast_ref::<reqwest::Error>().is_some())
+}
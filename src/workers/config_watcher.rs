@@ -0,0 +1,89 @@
+use crate::data_structures::*;
+use crate::state::{AppEvent, Config, EventSender, CONFIG_FILE};
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+use tokio::time::sleep;
+
+/// How often config.toml's modification time is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Background task that re-parses config.toml when it changes on disk
+/// Valid changes go to the UI as AppEvent::ConfigReloaded and to workers over a watch channel
+pub struct ConfigWatcher {
+    event_sender: EventSender,
+    updates: watch::Sender<Config>,
+    last_modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    /// Create a watcher seeded with the config the app started with
+    pub fn new(config: Config, event_sender: EventSender) -> (Self, watch::Receiver<Config>) {
+        let (updates, receiver) = watch::channel(config);
+
+        let watcher = Self {
+            event_sender,
+            updates,
+            last_modified: Self::modified(),
+        };
+
+        (watcher, receiver)
+    }
+
+    /// Poll forever; the task ends with the runtime
+    pub async fn run(mut self) {
+        loop {
+            sleep(POLL_INTERVAL).await;
+            self.poll();
+        }
+    }
+
+    fn modified() -> Option<SystemTime> {
+        std::fs::metadata(CONFIG_FILE)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    fn poll(&mut self) {
+        let modified = Self::modified();
+        if modified.is_none() || modified == self.last_modified {
+            return;
+        }
+        self.last_modified = modified;
+
+        let config = match Config::load() {
+            Ok(config) => config,
+            Err(e) => {
+                self.event_sender.send_error(
+                    format!("Ignoring {} change, failed to parse: {}", CONFIG_FILE, e),
+                    Some("config".to_string()),
+                );
+                return;
+            }
+        };
+
+        let problems = config.validate();
+        if !problems.is_empty() {
+            self.event_sender.send_error(
+                format!(
+                    "Ignoring {} change, validation failed: {}",
+                    CONFIG_FILE,
+                    problems.join("; ")
+                ),
+                Some("config".to_string()),
+            );
+            return;
+        }
+
+        self.event_sender.send_notification(
+            LogLevel::Debug,
+            format!("{} changed, applying new settings", CONFIG_FILE),
+            Some("config".to_string()),
+        );
+
+        // Workers may have exited; the UI still needs the update
+        let _ = self.updates.send(config.clone());
+        let _ = self
+            .event_sender
+            .send(AppEvent::ConfigReloaded(Box::new(config)));
+    }
+}
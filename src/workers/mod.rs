@@ -1,5 +1,8 @@
 pub mod api_handler;
+pub mod circuit_breaker;
+pub mod config_watcher;
 pub mod websocket_handler;
 
 pub use api_handler::*;
+pub use config_watcher::*;
 pub use websocket_handler::*;
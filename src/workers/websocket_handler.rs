@@ -1,13 +1,19 @@
 use crate::data_structures::*;
-use crate::state::{Command, Config, EventSender};
-use chrono::Utc;
+use crate::redact;
+use crate::state::{
+    instrument_list_label, AppEvent, Command, Config, EventSender, PLACEHOLDER_ACCESS_TOKEN,
+};
+use chrono::{DateTime, Utc};
 use crossbeam_channel::Receiver;
+use dashmap::DashMap;
 use futures_util::{SinkExt, StreamExt};
 use reqwest;
 use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, Notify, RwLock};
 use tokio::time::{sleep, timeout};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
@@ -18,21 +24,32 @@ pub struct WebSocketHandler {
     config: Config,
     access_token: Arc<RwLock<Option<String>>>,
     subscribed_tokens: Arc<RwLock<Vec<u32>>>,
+    tick_modes: Arc<RwLock<HashMap<u32, TickMode>>>, // tokens streamed in a non-default mode
     reconnect_attempts: u32,
     is_connected: Arc<RwLock<bool>>,
+    reconnect_signal: Arc<Notify>, // raised by a manual Reconnect from the UI
+    shutting_down: Arc<AtomicBool>, // set on Shutdown; the connection loop exits when it sees it
+    config_updates: watch::Receiver<Config>,
+    instruments: Arc<DashMap<u32, Instrument>>, // the app's instrument master, to name tokens
 }
 
 impl WebSocketHandler {
     /// Create new WebSocket handler with optimized configuration
-    pub fn new(config: Config, event_sender: EventSender) -> Self {
+    pub fn new(
+        config: Config,
+        event_sender: EventSender,
+        config_updates: watch::Receiver<Config>,
+        instruments: Arc<DashMap<u32, Instrument>>,
+    ) -> Self {
         // Only use access token if it's not the placeholder
-        let access_token = if config.zerodha.access_token != "your_access_token_here" 
-            && !config.zerodha.access_token.is_empty() {
+        let access_token = if config.zerodha.access_token != PLACEHOLDER_ACCESS_TOKEN
+            && !config.zerodha.access_token.is_empty()
+        {
             Some(config.zerodha.access_token.clone())
         } else {
             None
         };
-        
+
         let access_token = Arc::new(RwLock::new(access_token));
 
         Self {
@@ -40,8 +57,13 @@ impl WebSocketHandler {
             config,
             access_token,
             subscribed_tokens: Arc::new(RwLock::new(Vec::new())),
+            tick_modes: Arc::new(RwLock::new(HashMap::new())),
             reconnect_attempts: 0,
             is_connected: Arc::new(RwLock::new(false)),
+            reconnect_signal: Arc::new(Notify::new()),
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            config_updates,
+            instruments,
         }
     }
 
@@ -67,28 +89,49 @@ impl WebSocketHandler {
             self_clone.command_processor(command_receiver_clone).await;
         });
 
-        // Main WebSocket connection loop with auto-reconnect
-        loop {
+        self.connection_loop().await;
+    }
+
+    /// Run the connection loop again after it panicked, on state shared with the command
+    /// processor from the first run, which keeps the token and subscriptions current
+    pub async fn resume(&mut self) {
+        let reloaded = self.config_updates.borrow_and_update().clone();
+        self.config.apply_reload(&reloaded);
+        *self.is_connected.write().await = false;
+        self.connection_loop().await;
+    }
+
+    /// Main WebSocket connection loop with auto-reconnect
+    async fn connection_loop(&mut self) {
+        while !self.shutting_down.load(Ordering::Acquire) {
             let token = {
                 let access_token_guard = self.access_token.read().await;
                 access_token_guard.clone()
             };
 
             if let Some(token) = token {
-                if let Err(e) = self.connect_and_process(&token).await {
-                    self.event_sender.send_error(
-                        format!("WebSocket connection error: {}", e),
-                        Some("websocket_handler".to_string()),
-                    );
+                match self.connect_and_process(&token).await {
+                    Err(e) => {
+                        self.event_sender.send_error(
+                            format!("WebSocket connection error: {}", e),
+                            Some("websocket_handler".to_string()),
+                        );
 
-                    self.handle_reconnection().await;
-                } else {
+                        self.handle_reconnection().await;
+                    }
+                    // Dropped on request, connect again straight away
+                    Ok(Disconnect::Requested) => {
+                        self.reconnect_attempts = 0;
+                    }
                     // Connection closed gracefully
-                    break;
+                    Ok(Disconnect::Closed) => break,
                 }
             } else {
-                // Wait for access token
-                sleep(Duration::from_millis(100)).await;
+                // Wait for access token, or for a shutdown
+                tokio::select! {
+                    _ = sleep(Duration::from_millis(100)) => {}
+                    _ = self.reconnect_signal.notified() => {}
+                }
             }
         }
 
@@ -100,14 +143,19 @@ impl WebSocketHandler {
     }
 
     /// Clone self for task spawning (simplified version)
-    fn clone_for_task(&self) -> Self {
+    pub fn clone_for_task(&self) -> Self {
         Self {
             event_sender: self.event_sender.clone(),
             config: self.config.clone(),
             access_token: Arc::clone(&self.access_token),
             subscribed_tokens: Arc::clone(&self.subscribed_tokens),
+            tick_modes: Arc::clone(&self.tick_modes),
             reconnect_attempts: 0,
             is_connected: Arc::clone(&self.is_connected),
+            reconnect_signal: Arc::clone(&self.reconnect_signal),
+            shutting_down: Arc::clone(&self.shutting_down),
+            config_updates: self.config_updates.clone(),
+            instruments: Arc::clone(&self.instruments),
         }
     }
 
@@ -123,16 +171,33 @@ impl WebSocketHandler {
                     self.handle_unsubscribe(instrument_tokens).await;
                 }
 
+                Command::SetTickMode {
+                    instrument_tokens,
+                    mode,
+                } => {
+                    self.handle_set_mode(instrument_tokens, mode).await;
+                }
+
                 Command::ReconnectWebSocket => {
                     self.event_sender.send_notification(
                         LogLevel::Info,
                         "Manual reconnection requested".to_string(),
                         Some("websocket_handler".to_string()),
                     );
-                    // The main loop will handle reconnection
+                    // Wakes the connection loop, or cuts a reconnect backoff short
+                    self.reconnect_signal.notify_one();
+                }
+
+                Command::UpdateAccessToken { access_token } => {
+                    self.set_access_token(access_token).await;
+                    // Connect with the new token now rather than after the current backoff
+                    self.reconnect_signal.notify_one();
                 }
 
                 Command::Shutdown => {
+                    // Drops the connection, or ends a reconnect wait, so the loop sees the flag
+                    self.shutting_down.store(true, Ordering::Release);
+                    self.reconnect_signal.notify_one();
                     break;
                 }
 
@@ -143,19 +208,14 @@ impl WebSocketHandler {
     }
 
     /// Establish WebSocket connection and process incoming messages
-    async fn connect_and_process(&mut self, access_token: &str) -> anyhow::Result<()> {
-        // DEBUGGING: Confirm we're using the updated code
-        println!("🚀 USING UPDATED WEBSOCKET HANDLER - Fixed URL format");
-        
+    async fn connect_and_process(&mut self, access_token: &str) -> anyhow::Result<Disconnect> {
         // FIXED: Remove extra slash - Zerodha requires exact format
         let ws_url = format!(
             "wss://ws.kite.trade?api_key={}&access_token={}",
             self.config.zerodha.api_key, access_token
         );
         
-        println!("🔗 WebSocket URL: {}", ws_url);
-        println!("🔑 Using API Key: {}", self.config.zerodha.api_key);
-        println!("🎫 Access Token: {}...", &access_token[..8]);
+        log::debug!("WebSocket URL: {}", redact::redact_params(&ws_url));
 
         self.event_sender.send_notification(
             LogLevel::Info,
@@ -164,7 +224,7 @@ impl WebSocketHandler {
         );
 
         // First, validate the access token with a REST API call
-        println!("🧪 Testing access token with REST API...");
+        log::debug!("Testing access token with REST API");
         let test_client = reqwest::Client::new();
         let test_response = test_client
             .get("https://api.kite.trade/user/profile")
@@ -175,12 +235,18 @@ impl WebSocketHandler {
             
         match test_response {
             Ok(resp) if resp.status() == 200 => {
-                println!("✅ Access token is valid - proceeding with WebSocket connection");
+                log::debug!("Access token is valid - proceeding with WebSocket connection");
             }
             Ok(resp) => {
                 let status = resp.status();
+                if status == reqwest::StatusCode::FORBIDDEN
+                    || status == reqwest::StatusCode::UNAUTHORIZED
+                {
+                    self.event_sender
+                        .send(crate::state::AppEvent::AccessTokenRejected)?;
+                }
                 let error_text = resp.text().await.unwrap_or_default();
-                return Err(anyhow::anyhow!("❌ REST API test failed: {} - {}", status, error_text));
+                return Err(anyhow::anyhow!("❌ REST API test failed: {} - {}", status, redact::redact_params(&error_text)));
             }
             Err(e) => {
                 return Err(anyhow::anyhow!("❌ REST API test failed: {}", e));
@@ -193,7 +259,11 @@ impl WebSocketHandler {
         let (ws_stream, _) = match connect_result {
             Ok(Ok(connection)) => connection,
             Ok(Err(e)) => {
-                return Err(anyhow::anyhow!("WebSocket connection failed: {}", e));
+                // The handshake URL carries the credentials, so keep them out of the error
+                return Err(anyhow::anyhow!(
+                    "WebSocket connection failed: {}",
+                    redact::redact_params(&e.to_string())
+                ));
             }
             Err(_) => {
                 return Err(anyhow::anyhow!("WebSocket connection timeout"));
@@ -224,12 +294,36 @@ impl WebSocketHandler {
             self.send_subscription(&mut ws_sender, &tokens).await?;
         }
 
+        // With conflation on, ticks are held and sent as each instrument's latest per interval
+        let conflation_interval = self.config.performance.conflation_interval_ms;
+        let mut conflated = (conflation_interval > 0).then(Conflated::default);
+        let mut flush = tokio::time::interval(Duration::from_millis(conflation_interval.max(1)));
+
         // Process incoming messages with high-frequency optimization
-        while let Some(msg_result) = ws_receiver.next().await {
+        let mut disconnect = Disconnect::Closed;
+        loop {
+            let msg_result = tokio::select! {
+                message = ws_receiver.next() => match message {
+                    Some(message) => message,
+                    None => break,
+                },
+                _ = flush.tick(), if conflated.is_some() => {
+                    self.flush_conflated(&mut conflated)?;
+                    continue;
+                }
+                _ = self.reconnect_signal.notified() => {
+                    if self.shutting_down.load(Ordering::Acquire) {
+                        let _ = ws_sender.close().await;
+                    }
+                    disconnect = Disconnect::Requested;
+                    break;
+                }
+            };
+
             match msg_result {
                 Ok(Message::Binary(data)) => {
                     // High-frequency tick processing using zero-copy deserialization
-                    if let Err(e) = self.process_tick_data(&data).await {
+                    if let Err(e) = self.process_tick_data(&data, &mut conflated).await {
                         self.event_sender.send_error(
                             format!("Tick processing error: {}", e),
                             Some("websocket_handler".to_string()),
@@ -276,11 +370,12 @@ impl WebSocketHandler {
             let mut is_connected = self.is_connected.write().await;
             *is_connected = false;
         }
+        self.flush_conflated(&mut conflated)?;
 
         self.event_sender
             .send(crate::state::AppEvent::WebSocketDisconnected)?;
 
-        Ok(())
+        Ok(disconnect)
     }
 
     /// Handle subscription to instrument tokens
@@ -296,7 +391,10 @@ impl WebSocketHandler {
 
         self.event_sender.send_notification(
             LogLevel::Info,
-            format!("Subscribed to {} tokens", instrument_tokens.len()),
+            format!(
+                "Subscribed to ticks for {}",
+                instrument_list_label(&self.instruments, &instrument_tokens)
+            ),
             Some("websocket_handler".to_string()),
         );
 
@@ -312,9 +410,42 @@ impl WebSocketHandler {
         // Remove tokens
         tokens.retain(|token| !instrument_tokens.contains(token));
 
+        let mut modes = self.tick_modes.write().await;
+        modes.retain(|token, _| !instrument_tokens.contains(token));
+
+        self.event_sender.send_notification(
+            LogLevel::Info,
+            format!(
+                "Unsubscribed from ticks for {}",
+                instrument_list_label(&self.instruments, &instrument_tokens)
+            ),
+            Some("websocket_handler".to_string()),
+        );
+    }
+
+    /// Subscribe tokens if needed and record the mode they should stream in
+    async fn handle_set_mode(&self, instrument_tokens: Vec<u32>, mode: TickMode) {
+        {
+            let mut tokens = self.subscribed_tokens.write().await;
+            for token in &instrument_tokens {
+                if !tokens.contains(token) {
+                    tokens.push(*token);
+                }
+            }
+        }
+
+        let mut modes = self.tick_modes.write().await;
+        for token in &instrument_tokens {
+            modes.insert(*token, mode);
+        }
+
         self.event_sender.send_notification(
             LogLevel::Info,
-            format!("Unsubscribed from {} tokens", instrument_tokens.len()),
+            format!(
+                "Set {} mode for {}",
+                mode.as_api_str(),
+                instrument_list_label(&self.instruments, &instrument_tokens)
+            ),
             Some("websocket_handler".to_string()),
         );
     }
@@ -340,9 +471,29 @@ impl WebSocketHandler {
 
         ws_sender.send(Message::Text(msg_text.into())).await?;
 
+        // Tokens without an explicit mode keep the ticker's default
+        let mut by_mode: HashMap<TickMode, Vec<u32>> = HashMap::new();
+        for (token, mode) in self.tick_modes.read().await.iter() {
+            if tokens.contains(token) {
+                by_mode.entry(*mode).or_default().push(*token);
+            }
+        }
+        for (mode, mode_tokens) in by_mode {
+            let mode_msg = serde_json::json!({
+                "a": "mode",
+                "v": [mode.as_api_str(), mode_tokens]
+            });
+            ws_sender
+                .send(Message::Text(serde_json::to_string(&mode_msg)?.into()))
+                .await?;
+        }
+
         self.event_sender.send_notification(
             LogLevel::Info,
-            format!("Sent subscription for {} tokens", tokens.len()),
+            format!(
+                "Sent subscription for {}",
+                instrument_list_label(&self.instruments, tokens)
+            ),
             Some("websocket_handler".to_string()),
         );
 
@@ -350,42 +501,134 @@ impl WebSocketHandler {
     }
 
     /// Process binary tick data with zero-copy deserialization for ultra-low latency
-    async fn process_tick_data(&self, data: &[u8]) -> anyhow::Result<()> {
-        // Zerodha tick format parsing
-        // Note: This is a simplified implementation. Real Zerodha ticks have a specific binary format
-        // that would require proper parsing according to their documentation
+    /// A message is a big-endian packet count followed by length-prefixed packets
+    async fn process_tick_data(
+        &self,
+        data: &[u8],
+        conflated: &mut Option<Conflated>,
+    ) -> anyhow::Result<()> {
+        // Heartbeats are a single byte
+        if data.len() < 2 {
+            return Ok(());
+        }
 
-        if data.len() < 8 {
-            return Ok(()); // Invalid tick data
+        let packet_count = u16::from_be_bytes([data[0], data[1]]) as usize;
+        let mut offset = 2;
+
+        for _ in 0..packet_count {
+            let Some(length) = data.get(offset..offset + 2) else {
+                break;
+            };
+            let length = u16::from_be_bytes([length[0], length[1]]) as usize;
+            offset += 2;
+
+            let Some(packet) = data.get(offset..offset + length) else {
+                break;
+            };
+            self.process_packet(packet, conflated)?;
+            offset += length;
         }
 
-        // Parse instrument token (first 4 bytes, big-endian)
-        let instrument_token = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+        Ok(())
+    }
+
+    /// Decode a single ltp (8 bytes), quote (44) or full (184) packet
+    fn process_packet(
+        &self,
+        packet: &[u8],
+        conflated: &mut Option<Conflated>,
+    ) -> anyhow::Result<()> {
+        if packet.len() < 8 {
+            return Ok(()); // Invalid tick data
+        }
 
-        // Parse last price (next 4 bytes as f32, then convert to f64)
-        let last_price_raw = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
-        let last_price = f32::from_bits(last_price_raw) as f64;
+        let instrument_token = read_u32(packet, 0);
+        let divisor = price_divisor(instrument_token);
+        let price_at = |offset: usize| read_u32(packet, offset) as f64 / divisor;
 
-        // For volume, we'd need more bytes - simplified here
-        let volume = if data.len() >= 16 {
-            u64::from_be_bytes([
-                data[8], data[9], data[10], data[11], data[12], data[13], data[14], data[15],
-            ])
+        let last_price = price_at(4);
+        let (last_quantity, volume) = if packet.len() >= 44 {
+            (read_u32(packet, 8), read_u32(packet, 16) as u64)
         } else {
-            0
+            (0, 0)
         };
 
+        // Full mode carries the exchange timestamp, in seconds, at offset 60
+        let exchange_timestamp = (packet.len() >= 184)
+            .then(|| read_u32(packet, 60))
+            .filter(|seconds| *seconds != 0)
+            .and_then(|seconds| DateTime::from_timestamp(seconds as i64, 0));
+
         // Send tick update event
-        self.event_sender.send(crate::state::AppEvent::TickUpdate {
+        self.emit(
             instrument_token,
-            last_price,
-            volume,
-            timestamp: Utc::now(),
-        })?;
+            AppEvent::TickUpdate {
+                instrument_token,
+                last_price,
+                last_quantity,
+                volume,
+                timestamp: Utc::now(),
+                exchange_timestamp,
+            },
+            conflated,
+        )?;
+
+        // Full mode carries 5 bid then 5 ask levels of 12 bytes from offset 64
+        if packet.len() >= 184 {
+            let level_at = |offset: usize| DepthLevel {
+                quantity: read_u32(packet, offset),
+                price: price_at(offset + 4),
+                orders: u16::from_be_bytes([packet[offset + 8], packet[offset + 9]]),
+            };
+
+            let depth = MarketDepth {
+                bids: (0..5).map(|i| level_at(64 + i * 12)).collect(),
+                asks: (5..10).map(|i| level_at(64 + i * 12)).collect(),
+                total_buy_quantity: read_u32(packet, 20) as u64,
+                total_sell_quantity: read_u32(packet, 24) as u64,
+            };
+
+            self.emit(
+                instrument_token,
+                AppEvent::DepthUpdate {
+                    instrument_token,
+                    depth,
+                },
+                conflated,
+            )?;
+        }
 
         Ok(())
     }
 
+    /// Send market data now, or hold it as the instrument's latest until the next flush
+    fn emit(
+        &self,
+        instrument_token: u32,
+        event: AppEvent,
+        conflated: &mut Option<Conflated>,
+    ) -> anyhow::Result<()> {
+        match conflated {
+            Some(conflated) => {
+                conflated
+                    .latest
+                    .insert((instrument_token, std::mem::discriminant(&event)), event);
+            }
+            None => self.event_sender.send(event)?,
+        }
+        Ok(())
+    }
+
+    /// Send everything held for conflation
+    fn flush_conflated(&self, conflated: &mut Option<Conflated>) -> anyhow::Result<()> {
+        if let Some(conflated) = conflated {
+            for (_, event) in conflated.latest.drain() {
+                self.event_sender.send(event)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Process text messages from WebSocket
     async fn process_text_message(&self, text: &str) {
         // Parse status messages, connection confirmations, etc.
@@ -406,8 +649,17 @@ impl WebSocketHandler {
         }
     }
 
+    /// Pick up reconnect settings from a reloaded config.toml
+    fn apply_config_updates(&mut self) {
+        if self.config_updates.has_changed().unwrap_or(false) {
+            let reloaded = self.config_updates.borrow_and_update().clone();
+            self.config.apply_reload(&reloaded);
+        }
+    }
+
     /// Handle reconnection with exponential backoff
     async fn handle_reconnection(&mut self) {
+        self.apply_config_updates();
         self.reconnect_attempts += 1;
 
         if self.reconnect_attempts > self.config.app.max_reconnect_attempts {
@@ -415,6 +667,9 @@ impl WebSocketHandler {
                 "Max reconnection attempts reached".to_string(),
                 Some("websocket_handler".to_string()),
             );
+            // Stay down until the user asks to reconnect
+            self.reconnect_signal.notified().await;
+            self.reconnect_attempts = 0;
             return;
         }
 
@@ -439,7 +694,43 @@ impl WebSocketHandler {
             Some("websocket_handler".to_string()),
         );
 
-        sleep(Duration::from_millis(total_delay)).await;
+        tokio::select! {
+            _ = sleep(Duration::from_millis(total_delay)) => {}
+            _ = self.reconnect_signal.notified() => {
+                self.reconnect_attempts = 0;
+            }
+        }
+    }
+}
+
+/// Market data held between conflation flushes: each instrument's latest tick and depth
+#[derive(Default)]
+struct Conflated {
+    latest: HashMap<(u32, std::mem::Discriminant<AppEvent>), AppEvent>,
+}
+
+/// Why a connection that was up came down
+enum Disconnect {
+    Closed,
+    Requested,
+}
+
+/// Big-endian u32 at a fixed offset; callers check the packet length first
+fn read_u32(packet: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        packet[offset],
+        packet[offset + 1],
+        packet[offset + 2],
+        packet[offset + 3],
+    ])
+}
+
+/// Prices arrive as integers in paise, except currency segments which use more decimals
+fn price_divisor(instrument_token: u32) -> f64 {
+    match instrument_token & 0xff {
+        3 => 10_000_000.0, // CDS
+        6 => 10_000.0,     // BCD
+        _ => 100.0,
     }
 }
 
@@ -450,8 +741,13 @@ impl Clone for WebSocketHandler {
             config: self.config.clone(),
             access_token: Arc::clone(&self.access_token),
             subscribed_tokens: Arc::clone(&self.subscribed_tokens),
+            tick_modes: Arc::clone(&self.tick_modes),
             reconnect_attempts: self.reconnect_attempts,
             is_connected: Arc::clone(&self.is_connected),
+            reconnect_signal: Arc::clone(&self.reconnect_signal),
+            shutting_down: Arc::clone(&self.shutting_down),
+            config_updates: self.config_updates.clone(),
+            instruments: Arc::clone(&self.instruments),
         }
     }
 }